@@ -0,0 +1,268 @@
+//! Benchmarks of the read, write, index and stream paths.
+//!
+//! See the [workloads] module for the datasets and for running the suite.
+
+mod workloads;
+
+use criterion::{criterion_group, Criterion, Throughput};
+use std::path::{Path, PathBuf};
+use tfrecord::IntegrityMode;
+use workloads::Scale;
+
+/// The directory of the generated datasets, kept across runs.
+fn data_dir() -> PathBuf {
+    Path::new(env!("CARGO_TARGET_TMPDIR")).join("bench_data")
+}
+
+fn bench_write(c: &mut Criterion) {
+    let scale = Scale::bench();
+    let mut group = c.benchmark_group("write");
+
+    for (name, count, len) in [
+        ("small", scale.num_small_records, scale.small_record_len),
+        ("large", scale.num_large_records, scale.large_record_len),
+    ] {
+        let records = workloads::records(count, len, 0);
+        group.throughput(Throughput::Bytes((count * len) as u64));
+        group.bench_function(name, |b| {
+            b.iter(|| workloads::write_sequential(&records).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_read(c: &mut Criterion) {
+    let scale = Scale::bench();
+    let paths = workloads::dataset(
+        &data_dir().join("read"),
+        1,
+        scale.num_small_records,
+        scale.small_record_len,
+        1,
+    )
+    .unwrap();
+
+    let mut group = c.benchmark_group("read");
+    group.throughput(Throughput::Bytes(
+        (scale.num_small_records * scale.small_record_len) as u64,
+    ));
+    for (name, integrity) in [("full", IntegrityMode::Full), ("off", IntegrityMode::Off)] {
+        group.bench_function(name, |b| {
+            b.iter(|| workloads::read_sequential(&paths, integrity).unwrap())
+        });
+    }
+    // the savings of reading into a reused buffer
+    for (name, integrity) in [
+        ("full_reused", IntegrityMode::Full),
+        ("off_reused", IntegrityMode::Off),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| workloads::read_sequential_reused(&paths, integrity).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_index(c: &mut Criterion) {
+    let scale = Scale::bench();
+    let many_files = workloads::dataset(
+        &data_dir().join("many_files"),
+        scale.num_small_files,
+        scale.records_per_small_file,
+        scale.small_record_len,
+        2,
+    )
+    .unwrap();
+    let huge_file = workloads::dataset(
+        &data_dir().join("huge_file"),
+        1,
+        scale.records_in_huge_file,
+        scale.small_record_len,
+        3,
+    )
+    .unwrap();
+
+    let mut group = c.benchmark_group("index");
+    for (name, paths) in [("many_files", &many_files), ("huge_file", &huge_file)] {
+        group.bench_function(name, |b| b.iter(|| workloads::build_index(paths).unwrap()));
+    }
+    group.finish();
+
+    let indexes = workloads::build_index(&many_files).unwrap();
+    let mut group = c.benchmark_group("random_get");
+    group.throughput(Throughput::Elements(scale.num_random_gets as u64));
+    group.bench_function("many_files", |b| {
+        b.iter(|| workloads::random_gets(&indexes, scale.num_random_gets, 4).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_synth(c: &mut Criterion) {
+    let scale = Scale::bench();
+    let mut group = c.benchmark_group("synth");
+    group.throughput(Throughput::Elements(scale.num_synth_records as u64));
+    group.bench_function("examples", |b| {
+        b.iter(|| workloads::synth_examples(scale.num_synth_records, 0).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_journal(c: &mut Criterion) {
+    let scale = Scale::bench();
+    let dir = data_dir().join("journal");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("journal.tfrecord");
+
+    let mut group = c.benchmark_group("journal");
+    group.throughput(Throughput::Elements(
+        (scale.num_journal_threads * scale.journal_records_per_thread) as u64,
+    ));
+    group.bench_function("burst", |b| {
+        b.iter(|| {
+            workloads::journal_burst(
+                &path,
+                scale.num_journal_threads,
+                scale.journal_records_per_thread,
+            )
+            .unwrap()
+        })
+    });
+    group.finish();
+
+    // the latency of a lone record, measured per record
+    let mut group = c.benchmark_group("journal_latency");
+    group.bench_function("trickle", |b| {
+        b.iter_custom(|iters| {
+            workloads::journal_trickle(&path, iters, std::time::Duration::from_millis(1)).unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn bench_sequence(c: &mut Criterion) {
+    let scale = Scale::bench();
+    let records =
+        workloads::sequence_records(scale.num_sequence_records, scale.frames_per_sequence, 0)
+            .unwrap();
+
+    // the savings of skipping the feature lists
+    let mut group = c.benchmark_group("sequence_decode");
+    group.throughput(Throughput::Bytes(
+        records.iter().map(|record| record.len() as u64).sum(),
+    ));
+    group.bench_function("full", |b| {
+        b.iter(|| workloads::decode_sequences(&records).unwrap())
+    });
+    group.bench_function("context_only", |b| {
+        b.iter(|| workloads::decode_contexts(&records).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_batch(c: &mut Criterion) {
+    let scale = Scale::bench();
+    let records = workloads::batch_records(scale.num_batch_records, 0).unwrap();
+
+    // one hash map per example against one buffer per feature and batch
+    let mut group = c.benchmark_group("batch_decode");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(records.len() as u64));
+    group.bench_function("hash_map", |b| {
+        b.iter(|| workloads::decode_hash_maps(&records, scale.batch_size).unwrap())
+    });
+    group.bench_function("columnar", |b| {
+        b.iter(|| workloads::decode_columnar(&records, scale.batch_size).unwrap())
+    });
+    group.finish();
+}
+
+#[cfg(feature = "async")]
+fn bench_stream(c: &mut Criterion) {
+    let scale = Scale::bench();
+    let paths = workloads::dataset(
+        &data_dir().join("many_files"),
+        scale.num_small_files,
+        scale.records_per_small_file,
+        scale.small_record_len,
+        2,
+    )
+    .unwrap();
+    let indexes = workloads::build_index(&paths).unwrap();
+
+    let mut group = c.benchmark_group("stream_prefetch");
+    group.throughput(Throughput::Elements(indexes.len() as u64));
+    for prefetch in [1, 8, 64] {
+        group.bench_with_input(
+            criterion::BenchmarkId::from_parameter(prefetch),
+            &prefetch,
+            |b, &prefetch| {
+                b.iter(|| {
+                    async_std::task::block_on(workloads::stream_prefetch(&indexes, prefetch))
+                        .unwrap()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+#[cfg(not(feature = "async"))]
+fn bench_stream(_c: &mut Criterion) {}
+
+#[cfg(feature = "async")]
+fn bench_decode(c: &mut Criterion) {
+    let scale = Scale::bench();
+    let path = data_dir().join("examples").join("examples.tfrecord");
+    workloads::example_file(&path, scale.num_decode_records, 5).unwrap();
+
+    // the scaling of decoding with the number of workers
+    let mut group = c.benchmark_group("decode_parallel");
+    group.throughput(Throughput::Elements(scale.num_decode_records as u64));
+    for num_workers in [1, 2, 4, 8] {
+        group.bench_with_input(
+            criterion::BenchmarkId::from_parameter(num_workers),
+            &num_workers,
+            |b, &num_workers| {
+                b.iter(|| {
+                    async_std::task::block_on(workloads::decode_parallel(&path, num_workers))
+                        .unwrap()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+#[cfg(not(feature = "async"))]
+fn bench_decode(_c: &mut Criterion) {}
+
+criterion_group!(
+    benches,
+    bench_write,
+    bench_read,
+    bench_index,
+    bench_synth,
+    bench_journal,
+    bench_sequence,
+    bench_batch,
+    bench_stream,
+    bench_decode
+);
+
+fn main() {
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+
+    // print the baseline table after measurements, but not in test mode
+    let args: Vec<_> = std::env::args().collect();
+    if args.iter().any(|arg| arg == "--bench") && !args.iter().any(|arg| arg == "--test") {
+        let criterion_dir = Path::new(env!("CARGO_TARGET_TMPDIR"))
+            .parent()
+            .unwrap()
+            .join("criterion");
+        match workloads::markdown_table(&criterion_dir) {
+            Ok(table) => println!("\n{}", table),
+            Err(err) => eprintln!("unable to collect the results: {:#}", err),
+        }
+    }
+}
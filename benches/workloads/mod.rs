@@ -0,0 +1,558 @@
+//! Workloads shared by the benchmarks and their smoke test.
+//!
+//! The benchmarks in `benches/io.rs` measure the read, write, index and stream paths on
+//! synthetic datasets. The datasets are generated from fixed seeds by a portable
+//! generator, so the numbers are comparable across machines in relative terms.
+//!
+//! ```sh
+//! cargo bench --features async
+//! ```
+//!
+//! After a run, the bench binary prints the results collected by criterion as a markdown
+//! table, which can be pasted into a pull request as the baseline. A performance change is
+//! evaluated by running the suite before and after the change on the same machine.
+//!
+//! Contributors adding a performance feature should add the workload here, sized by
+//! [Scale], and register it in `benches/io.rs`. The smoke test in `tests/bench_smoke.rs`
+//! runs every workload at the [smoke](Scale::smoke) scale under `cargo test`, so the bench
+//! code cannot rot.
+
+#![allow(dead_code)]
+
+use anyhow::{ensure, Context as _, Result};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use tfrecord::{
+    batch::BatchDecoder,
+    indexer::{self, RecordIndex},
+    protobuf::SequenceExample,
+    schema::{FeatureSpec, ValueCount, ValueType},
+    synth::{self, Distribution, GenOptions},
+    BytesIter, BytesWriter, ContextOnly, Example, ExampleWriter, Feature, IntegrityMode,
+    JournalWriter, ProstRecord, Record, SequenceExampleBuilder,
+};
+
+/// The sizes of the generated datasets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scale {
+    /// The number of small records written or read at once.
+    pub num_small_records: usize,
+    /// The payload length of small records.
+    pub small_record_len: usize,
+    /// The number of large records written or read at once.
+    pub num_large_records: usize,
+    /// The payload length of large records.
+    pub large_record_len: usize,
+    /// The number of files in the many-files dataset.
+    pub num_small_files: usize,
+    /// The number of records per file in the many-files dataset.
+    pub records_per_small_file: usize,
+    /// The number of records in the single-file dataset.
+    pub records_in_huge_file: usize,
+    /// The number of random lookups.
+    pub num_random_gets: usize,
+    /// The number of generated synthetic examples.
+    pub num_synth_records: usize,
+    /// The number of threads sending records to the journal at once.
+    pub num_journal_threads: usize,
+    /// The number of records sent to the journal by each thread.
+    pub journal_records_per_thread: usize,
+    /// The number of serialized sequence examples decoded at once.
+    pub num_sequence_records: usize,
+    /// The number of frames of each sequence example.
+    pub frames_per_sequence: usize,
+    /// The number of synthetic examples decoded in parallel.
+    pub num_decode_records: usize,
+    /// The number of serialized examples decoded in batches.
+    pub num_batch_records: usize,
+    /// The number of examples per batch.
+    pub batch_size: usize,
+}
+
+impl Scale {
+    /// The scale of the benchmarks.
+    pub fn bench() -> Self {
+        Self {
+            num_small_records: 10_000,
+            small_record_len: 64,
+            num_large_records: 64,
+            large_record_len: 1 << 20,
+            num_small_files: 256,
+            records_per_small_file: 100,
+            records_in_huge_file: 200_000,
+            num_random_gets: 1_000,
+            num_synth_records: 10_000,
+            num_journal_threads: 16,
+            journal_records_per_thread: 64,
+            num_sequence_records: 1_000,
+            frames_per_sequence: 256,
+            num_decode_records: 10_000,
+            num_batch_records: 1_000_000,
+            batch_size: 1024,
+        }
+    }
+
+    /// The tiny scale of the smoke test.
+    pub fn smoke() -> Self {
+        Self {
+            num_small_records: 16,
+            small_record_len: 8,
+            num_large_records: 2,
+            large_record_len: 1024,
+            num_small_files: 4,
+            records_per_small_file: 4,
+            records_in_huge_file: 32,
+            num_random_gets: 8,
+            num_synth_records: 16,
+            num_journal_threads: 2,
+            journal_records_per_thread: 4,
+            num_sequence_records: 4,
+            frames_per_sequence: 8,
+            num_decode_records: 16,
+            num_batch_records: 16,
+            batch_size: 5,
+        }
+    }
+}
+
+/// The portable pseudo-random generator, SplitMix64.
+#[derive(Debug, Clone)]
+pub struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+/// Generate records of the same length from a seed.
+pub fn records(count: usize, len: usize, seed: u64) -> Vec<Vec<u8>> {
+    let mut rng = SplitMix64::new(seed);
+    (0..count)
+        .map(|_| {
+            let mut record: Vec<u8> = (0..len.div_ceil(8))
+                .flat_map(|_| rng.next_u64().to_le_bytes())
+                .collect();
+            record.truncate(len);
+            record
+        })
+        .collect()
+}
+
+/// Write a dataset of files with records generated from a seed.
+///
+/// The files are regenerated only if the directory does not exist.
+pub fn dataset(
+    dir: &Path,
+    num_files: usize,
+    records_per_file: usize,
+    len: usize,
+    seed: u64,
+) -> Result<Vec<PathBuf>> {
+    let paths: Vec<_> = (0..num_files)
+        .map(|index| dir.join(format!("part-{:05}.tfrecord", index)))
+        .collect();
+    if dir.exists() {
+        return Ok(paths);
+    }
+
+    let tmp_dir = dir.with_extension("tmp");
+    if tmp_dir.exists() {
+        fs::remove_dir_all(&tmp_dir)?;
+    }
+    fs::create_dir_all(&tmp_dir)?;
+    for (index, path) in paths.iter().enumerate() {
+        let path = tmp_dir.join(path.file_name().unwrap());
+        let mut writer = BytesWriter::create(path)?;
+        for record in records(records_per_file, len, seed + index as u64) {
+            writer.send(record)?;
+        }
+        writer.flush()?;
+    }
+    // the complete dataset appears at once
+    fs::rename(&tmp_dir, dir)?;
+    Ok(paths)
+}
+
+/// Write records sequentially to memory, returning the number of written bytes.
+pub fn write_sequential(records: &[Vec<u8>]) -> Result<usize> {
+    let mut bytes = vec![];
+    let mut writer = BytesWriter::from_writer(&mut bytes)?;
+    for record in records {
+        writer.send(record.clone())?;
+    }
+    drop(writer);
+    Ok(bytes.len())
+}
+
+/// Read all records of files sequentially, returning the number of payload bytes.
+pub fn read_sequential(paths: &[PathBuf], integrity: IntegrityMode) -> Result<usize> {
+    let mut total = 0;
+    for path in paths {
+        let config = tfrecord::RecordReaderConfig {
+            integrity,
+            ..Default::default()
+        };
+        for record in BytesIter::open(path, config)? {
+            total += record?.len();
+        }
+    }
+    Ok(total)
+}
+
+/// Read all records of files sequentially into a reused buffer, returning the number of
+/// payload bytes.
+pub fn read_sequential_reused(paths: &[PathBuf], integrity: IntegrityMode) -> Result<usize> {
+    let mut total = 0;
+    let mut buf = vec![];
+    for path in paths {
+        let config = tfrecord::RecordReaderConfig {
+            integrity,
+            ..Default::default()
+        };
+        let mut reader = BytesIter::open(path, config)?;
+        while let Some(len) = reader.read_into(&mut buf)? {
+            total += len;
+        }
+    }
+    Ok(total)
+}
+
+/// Build the record indexes of files.
+pub fn build_index(paths: &[PathBuf]) -> Result<Vec<RecordIndex>> {
+    let indexes = indexer::load_paths(paths, Default::default()).collect::<Result<_, _>>()?;
+    Ok(indexes)
+}
+
+/// Load records at random indexes, returning the number of payload bytes.
+pub fn random_gets(indexes: &[RecordIndex], count: usize, seed: u64) -> Result<usize> {
+    let mut rng = SplitMix64::new(seed);
+    let mut total = 0;
+    for _ in 0..count {
+        let record: Vec<u8> = indexes[rng.below(indexes.len())].load()?;
+        total += record.len();
+    }
+    Ok(total)
+}
+
+/// Generate synthetic examples of a fixed schema, returning the number of features.
+pub fn synth_examples(count: usize, seed: u64) -> Result<usize> {
+    let total = synth_example_iter(count, seed)?
+        .map(|example| {
+            example
+                .features
+                .map_or(0, |features| features.feature.len())
+        })
+        .sum();
+    Ok(total)
+}
+
+/// Write a file of synthetic examples generated from a seed.
+///
+/// The file is regenerated only if it does not exist.
+pub fn example_file(path: &Path, count: usize, seed: u64) -> Result<()> {
+    if path.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(path.parent().unwrap())?;
+    let tmp_path = path.with_extension("tmp");
+    let mut writer = ExampleWriter::create(&tmp_path)?;
+    for example in synth_example_iter(count, seed)? {
+        writer.send(example)?;
+    }
+    writer.flush()?;
+    drop(writer);
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+fn synth_example_iter(count: usize, seed: u64) -> Result<synth::SynthIter> {
+    let specs = [
+        FeatureSpec::new("id", ValueType::I64, ValueCount::Fixed(1)),
+        FeatureSpec::new("embedding", ValueType::F32, ValueCount::Fixed(32)),
+        FeatureSpec::new(
+            "tokens",
+            ValueType::I64,
+            ValueCount::Var {
+                min: 1,
+                max: Some(64),
+            },
+        ),
+        FeatureSpec::new("name", ValueType::Bytes, ValueCount::Fixed(1)),
+    ];
+    let mut options = GenOptions {
+        records: count as u64,
+        seed,
+        ..Default::default()
+    };
+    options.distributions.insert(
+        "embedding".into(),
+        Distribution::NormalF32 {
+            mean: 0.0,
+            std_dev: 1.0,
+        },
+    );
+    options.distributions.insert(
+        "tokens".into(),
+        Distribution::Zipf {
+            ranks: 30_000,
+            exponent: 1.1,
+        },
+    );
+    Ok(synth::generate(&specs, options)?)
+}
+
+/// Serialize examples of a scalar and a variable-length feature, as fed to models.
+pub fn batch_records(count: usize, seed: u64) -> Result<Vec<Vec<u8>>> {
+    let specs = [
+        FeatureSpec::new("age", ValueType::F32, ValueCount::Fixed(1)),
+        FeatureSpec::new(
+            "movie_ids",
+            ValueType::I64,
+            ValueCount::Var {
+                min: 0,
+                max: Some(16),
+            },
+        ),
+    ];
+    let options = GenOptions {
+        records: count as u64,
+        seed,
+        ..Default::default()
+    };
+    synth::generate(&specs, options)?
+        .map(|example| Ok(Example::to_bytes(example)?))
+        .collect()
+}
+
+/// Decode batches of examples into a hash map per example, returning the number of
+/// movie ids.
+pub fn decode_hash_maps(records: &[Vec<u8>], batch_size: usize) -> Result<usize> {
+    let mut total = 0;
+    for chunk in records.chunks(batch_size) {
+        let rows = chunk
+            .iter()
+            .map(|bytes| Ok(Example::from_slice(bytes)?.into_hash_map()))
+            .collect::<Result<Vec<HashMap<String, Feature>>>>()?;
+        total += rows
+            .iter()
+            .map(|row| row["movie_ids"].as_i64_list().map_or(0, |ids| ids.len()))
+            .sum::<usize>();
+    }
+    Ok(total)
+}
+
+/// Decode batches of examples into columns, returning the number of movie ids.
+pub fn decode_columnar(records: &[Vec<u8>], batch_size: usize) -> Result<usize> {
+    let examples = records.iter().map(|bytes| Example::from_slice(bytes));
+    let batches = BatchDecoder::new()
+        .key::<f32>("age")
+        .var_key::<i64>("movie_ids")
+        .collect(examples, batch_size)?;
+    let mut total = 0;
+    for batch in batches {
+        total += batch?.column("movie_ids").unwrap().values.len();
+    }
+    Ok(total)
+}
+
+/// Serialize sequence examples with a small context and large feature lists.
+pub fn sequence_records(count: usize, num_frames: usize, seed: u64) -> Result<Vec<Vec<u8>>> {
+    let mut rng = SplitMix64::new(seed);
+    (0..count)
+        .map(|index| {
+            let mut builder = SequenceExampleBuilder::new()
+                .context_i64s("user", &[index as i64])
+                .context_bytes("country", vec![b"tw".to_vec()]);
+            for _ in 0..num_frames {
+                let embedding: Vec<f32> = (0..32).map(|_| rng.below(1000) as f32).collect();
+                builder = builder
+                    .push_frame_f32s("embedding", &embedding)
+                    .push_frame_i64s("item", &[rng.below(100_000) as i64]);
+            }
+            Ok(ProstRecord::to_bytes(ProstRecord(builder.build()?))?)
+        })
+        .collect()
+}
+
+/// Decode whole sequence examples, returning the number of context features.
+pub fn decode_sequences(records: &[Vec<u8>]) -> Result<usize> {
+    let mut total = 0;
+    for bytes in records {
+        let ProstRecord(sequence) = ProstRecord::<SequenceExample>::from_slice(bytes)?;
+        total += sequence.context.map_or(0, |context| context.feature.len());
+    }
+    Ok(total)
+}
+
+/// Decode only the contexts of sequence examples, returning the number of context
+/// features.
+pub fn decode_contexts(records: &[Vec<u8>]) -> Result<usize> {
+    let mut total = 0;
+    for bytes in records {
+        total += ContextOnly::from_slice(bytes)?.context.feature.len();
+    }
+    Ok(total)
+}
+
+/// Send durable records to a journal from threads at once, returning the number of
+/// durable records.
+pub fn journal_burst(path: &Path, num_threads: usize, per_thread: usize) -> Result<u64> {
+    let writer = JournalWriter::<Vec<u8>>::create(path, Default::default())?;
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..num_threads)
+            .map(|thread| {
+                let writer = &writer;
+                scope.spawn(move || -> Result<()> {
+                    for record in records(per_thread, 64, thread as u64) {
+                        writer.send_durable(record)?;
+                    }
+                    Ok(())
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .try_for_each(|handle| handle.join().unwrap())
+    })?;
+    Ok(writer.close()?)
+}
+
+/// Send durable records to a journal one at a time with a gap in between, returning the
+/// total time spent waiting for durability.
+pub fn journal_trickle(path: &Path, count: u64, gap: Duration) -> Result<Duration> {
+    let writer = JournalWriter::<Vec<u8>>::create(path, Default::default())?;
+    let mut total = Duration::ZERO;
+    for record in records(count as usize, 64, 0) {
+        std::thread::sleep(gap);
+        let since = Instant::now();
+        writer.send_durable(record)?;
+        total += since.elapsed();
+    }
+    writer.close()?;
+    Ok(total)
+}
+
+/// Load records in index order with the number of loads in flight, returning the number
+/// of payload bytes.
+#[cfg(feature = "async")]
+pub async fn stream_prefetch(indexes: &[RecordIndex], prefetch: usize) -> Result<usize> {
+    use futures::stream::{self, StreamExt as _, TryStreamExt as _};
+
+    let total = stream::iter(indexes)
+        .map(|index| index.load_async::<Vec<u8>>())
+        .buffered(prefetch)
+        .try_fold(0, |total, record| async move { Ok(total + record.len()) })
+        .await?;
+    Ok(total)
+}
+
+/// Decode the examples of a file on a number of workers, returning the number of
+/// examples.
+#[cfg(feature = "async")]
+pub async fn decode_parallel(path: &Path, num_workers: usize) -> Result<usize> {
+    use futures::stream::TryStreamExt as _;
+    use tfrecord::{ExampleStream, ParallelDecodeConfig};
+
+    let parallel = ParallelDecodeConfig {
+        num_workers,
+        ..Default::default()
+    };
+    let count = ExampleStream::open_parallel(path, Default::default(), parallel)
+        .await?
+        .try_fold(0, |count, _| async move { Ok(count + 1) })
+        .await?;
+    Ok(count)
+}
+
+/// Render the results under a criterion output directory as a markdown table.
+///
+/// The rows are the benchmarks sorted by their ids, with the mean and the standard
+/// deviation of the iteration time.
+pub fn markdown_table(criterion_dir: &Path) -> Result<String> {
+    let mut rows = vec![];
+    collect_results(criterion_dir, &mut rows)?;
+    rows.sort_by(|(lhs, ..), (rhs, ..)| lhs.cmp(rhs));
+
+    let mut table = "| benchmark | mean | std. dev. |\n|---|---:|---:|\n".to_string();
+    for (id, mean, std_dev) in rows {
+        table.push_str(&format!(
+            "| {} | {} | {} |\n",
+            id,
+            format_nanos(mean),
+            format_nanos(std_dev)
+        ));
+    }
+    Ok(table)
+}
+
+fn collect_results(dir: &Path, rows: &mut Vec<(String, f64, f64)>) -> Result<()> {
+    let result_dir = dir.join("new");
+    if result_dir.join("estimates.json").is_file() {
+        let read_json = |name: &str| -> Result<serde_json::Value> {
+            let path = result_dir.join(name);
+            let text = fs::read_to_string(&path)
+                .with_context(|| format!("unable to read {}", path.display()))?;
+            Ok(serde_json::from_str(&text)?)
+        };
+        let benchmark = read_json("benchmark.json")?;
+        let estimates = read_json("estimates.json")?;
+
+        let id = benchmark["full_id"].as_str();
+        let mean = estimates["mean"]["point_estimate"].as_f64();
+        let std_dev = estimates["std_dev"]["point_estimate"].as_f64();
+        let (id, mean, std_dev) = match (id, mean, std_dev) {
+            (Some(id), Some(mean), Some(std_dev)) => (id, mean, std_dev),
+            _ => anyhow::bail!("malformed results in {}", result_dir.display()),
+        };
+        rows.push((id.to_string(), mean, std_dev));
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        // the report directory has no results
+        if entry.file_type()?.is_dir() && entry.file_name() != "report" {
+            collect_results(&entry.path(), rows)?;
+        }
+    }
+    Ok(())
+}
+
+fn format_nanos(nanos: f64) -> String {
+    let units = [("ns", 1.0), ("µs", 1e3), ("ms", 1e6), ("s", 1e9)];
+    let (unit, scale) = units
+        .iter()
+        .rev()
+        .find(|(_, scale)| nanos >= *scale)
+        .unwrap_or(&units[0]);
+    format!("{:.2} {}", nanos / scale, unit)
+}
+
+/// Check that a workload processed the expected number of bytes.
+pub fn ensure_total(total: usize, expect: usize) -> Result<()> {
+    ensure!(
+        total == expect,
+        "expect {} bytes, but found {}",
+        expect,
+        total
+    );
+    Ok(())
+}
@@ -1,20 +1,20 @@
 use anyhow::Result;
 use std::env;
 
-const PROTOBUF_FILE_W_SERDE: &str = concat!(
+const PROTOBUF_DIR_W_SERDE: &str = concat!(
     env!("CARGO_MANIFEST_DIR"),
-    "/prebuild_src/tensorflow_with_serde.rs",
+    "/prebuild_src/tensorflow_with_serde",
 );
-const PROTOBUF_FILE_WO_SERDE: &str = concat!(
+const PROTOBUF_DIR_WO_SERDE: &str = concat!(
     env!("CARGO_MANIFEST_DIR"),
-    "/prebuild_src/tensorflow_without_serde.rs",
+    "/prebuild_src/tensorflow_without_serde",
 );
 const BUILD_METHOD_ENV: &str = "TFRECORD_BUILD_METHOD";
 
 fn main() -> Result<()> {
     // re-run conditions
-    println!("cargo:rerun-if-changed={}", PROTOBUF_FILE_WO_SERDE);
-    println!("cargo:rerun-if-changed={}", PROTOBUF_FILE_W_SERDE);
+    println!("cargo:rerun-if-changed={}", PROTOBUF_DIR_WO_SERDE);
+    println!("cargo:rerun-if-changed={}", PROTOBUF_DIR_W_SERDE);
     println!("cargo:rerun-if-env-changed={}", BUILD_METHOD_ENV);
 
     #[cfg(feature = "generate_protobuf_src")]
@@ -60,6 +60,40 @@ mod codegen {
         ".tar.gz"
     );
 
+    /// The groups of generated code, each selected by a cargo feature, and the
+    /// ProtocolBuffer documents under `tensorflow/core` compiled into each group.
+    ///
+    /// Types referred to by several groups, such as the tensor types, belong to the
+    /// `example` group, which is always compiled. The `graph` group takes the
+    /// documents under `framework` not listed in the other groups.
+    const PROTO_GROUPS: &[(&str, &[&str])] = &[
+        (
+            "example",
+            &[
+                "example/example.proto",
+                "example/example_parser_configuration.proto",
+                "example/feature.proto",
+                "framework/resource_handle.proto",
+                "framework/tensor.proto",
+                "framework/tensor_shape.proto",
+                "framework/types.proto",
+            ],
+        ),
+        ("summary", &["framework/summary.proto", "util/event.proto"]),
+        (
+            "runtime",
+            &[
+                "framework/allocation_description.proto",
+                "framework/device_attributes.proto",
+                "framework/log_memory.proto",
+                "framework/reader_base.proto",
+                "framework/step_stats.proto",
+                "framework/tensor_description.proto",
+            ],
+        ),
+    ];
+    const GRAPH_GROUP: &str = "graph";
+
     #[derive(Debug, Clone)]
     pub enum BuildMethod {
         Url(String),
@@ -195,48 +229,49 @@ the environment variable "{}" must be set with the following format.
     pub fn compile_protobuf(dir: impl AsRef<Path>) -> Result<()> {
         let dir = dir.as_ref();
         let include_dir = dir;
-        let proto_paths = {
-            let example_iter = glob::glob(
-                dir.join("tensorflow")
-                    .join("core")
-                    .join("example")
-                    .join("*.proto")
-                    .to_str()
-                    .unwrap(),
-            )?;
-            let framework_iter = glob::glob(
-                dir.join("tensorflow")
-                    .join("core")
-                    .join("framework")
-                    .join("*.proto")
-                    .to_str()
-                    .unwrap(),
-            )?;
-            let util_iter = std::iter::once(Ok(dir
-                .join("tensorflow")
-                .join("core")
-                .join("util")
-                .join("event.proto")));
-            example_iter
-                .chain(framework_iter)
-                .chain(util_iter)
+        let core_dir = dir.join("tensorflow").join("core");
+
+        let mut groups: Vec<(&str, Vec<PathBuf>)> = PROTO_GROUPS
+            .iter()
+            .map(|&(group, files)| {
+                let paths = files.iter().map(|file| core_dir.join(file)).collect();
+                (group, paths)
+            })
+            .collect();
+        let graph_paths = {
+            let grouped: Vec<_> = groups.iter().flat_map(|(_, paths)| paths).collect();
+            glob::glob(core_dir.join("framework").join("*.proto").to_str().unwrap())?
+                .filter(|path| !matches!(path, Ok(path) if grouped.contains(&path)))
                 .collect::<Result<Vec<_>, _>>()?
         };
+        groups.push((GRAPH_GROUP, graph_paths));
+
+        fs::create_dir_all(PROTOBUF_DIR_WO_SERDE)?;
+        fs::create_dir_all(PROTOBUF_DIR_W_SERDE)?;
+
+        // each group is compiled alone, so its output contains only its own types
+        for (group, proto_paths) in &groups {
+            let file_name = format!("{}.rs", group);
+
+            // without serde
+            {
+                prost_build::compile_protos(proto_paths, &[PathBuf::from(include_dir)])?;
+                fs::copy(
+                    &*GENERATED_PROTOBUF_FILE,
+                    Path::new(PROTOBUF_DIR_WO_SERDE).join(&file_name),
+                )?;
+            }
 
-        // without serde
-        {
-            prost_build::compile_protos(&proto_paths, &[PathBuf::from(include_dir)])?;
-            fs::create_dir_all(Path::new(PROTOBUF_FILE_WO_SERDE).parent().unwrap())?;
-            fs::copy(&*GENERATED_PROTOBUF_FILE, &*PROTOBUF_FILE_WO_SERDE)?;
-        }
-
-        // with serde
-        {
-            prost_build::Config::new()
-                .type_attribute(".", "#[derive(serde::Serialize, serde::Deserialize)]")
-                .compile_protos(&proto_paths, &[PathBuf::from(include_dir)])?;
-            fs::create_dir_all(Path::new(PROTOBUF_FILE_W_SERDE).parent().unwrap())?;
-            fs::copy(&*GENERATED_PROTOBUF_FILE, &*PROTOBUF_FILE_W_SERDE)?;
+            // with serde
+            {
+                prost_build::Config::new()
+                    .type_attribute(".", "#[derive(serde::Serialize, serde::Deserialize)]")
+                    .compile_protos(proto_paths, &[PathBuf::from(include_dir)])?;
+                fs::copy(
+                    &*GENERATED_PROTOBUF_FILE,
+                    Path::new(PROTOBUF_DIR_W_SERDE).join(&file_name),
+                )?;
+            }
         }
 
         Ok(())
@@ -4,10 +4,7 @@ use indexmap::IndexSet;
 use serde::Serialize;
 use std::path::PathBuf;
 use structopt::StructOpt;
-use tfrecord::{
-    protobuf::{event::What, summary::value::Value::SimpleValue, Summary},
-    EventIter,
-};
+use tfrecord::prelude::*;
 
 #[derive(StructOpt)]
 struct Args {
@@ -56,14 +53,16 @@ fn main() -> Result<()> {
         .map(|result| -> Result<_> {
             let event = result?;
             let tag = match event.what {
-                Some(What::Summary(Summary { value })) => {
+                Some(protobuf::event::What::Summary(Summary { value })) => {
                     let val = &value[0];
                     match val.value {
-                        Some(SimpleValue(value)) => Some(TagData {
-                            step: event.step,
-                            tag: val.tag.clone(),
-                            value,
-                        }),
+                        Some(protobuf::summary::value::Value::SimpleValue(value)) => {
+                            Some(TagData {
+                                step: event.step,
+                                tag: val.tag.clone(),
+                                value,
+                            })
+                        }
                         _ => None,
                     }
                 }
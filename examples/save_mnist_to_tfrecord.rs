@@ -4,7 +4,7 @@ use itertools::izip;
 use packed_struct::prelude::*;
 use packed_struct_codegen::PackedStruct;
 use std::io::{self, prelude::*, Cursor};
-use tfrecord::{Example, ExampleWriter, Feature, RecordWriter};
+use tfrecord::prelude::*;
 
 const IMAGES_URL: &str = "http://yann.lecun.com/exdb/mnist/train-images-idx3-ubyte.gz";
 const LABELS_URL: &str = "http://yann.lecun.com/exdb/mnist/train-labels-idx1-ubyte.gz";
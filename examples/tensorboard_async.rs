@@ -4,7 +4,7 @@ use once_cell::sync::Lazy;
 use rand::seq::SliceRandom;
 use rand_distr::{Distribution, Normal};
 use std::{f32::consts::PI, fs, io, path::Path, time::Duration};
-use tfrecord::{protobuf::HistogramProto, EventAsyncWriter};
+use tfrecord::prelude::*;
 
 pub const IMAGE_URLS: &[&str] = &[
     "https://farm3.staticflickr.com/2564/3946548112_77df49fe87_z.jpg",
@@ -4,7 +4,7 @@ use std::{
     io::{self, prelude::*, BufWriter},
     path::{Path, PathBuf},
 };
-use tfrecord::{Error, ExampleIter, FeatureKind};
+use tfrecord::prelude::*;
 
 static INPUT_TFRECORD_PATH: Lazy<PathBuf> = Lazy::new(|| {
     (move || {
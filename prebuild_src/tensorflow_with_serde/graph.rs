@@ -1,658 +1,7 @@
-/// LINT.IfChange
-/// Containers to hold repeated fundamental values.
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct BytesList {
-    #[prost(bytes="vec", repeated, tag="1")]
-    pub value: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct FloatList {
-    #[prost(float, repeated, tag="1")]
-    pub value: ::prost::alloc::vec::Vec<f32>,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct Int64List {
-    #[prost(int64, repeated, tag="1")]
-    pub value: ::prost::alloc::vec::Vec<i64>,
-}
-/// Containers for non-sequential data.
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct Feature {
-    /// Each feature can be exactly one kind.
-    #[prost(oneof="feature::Kind", tags="1, 2, 3")]
-    pub kind: ::core::option::Option<feature::Kind>,
-}
-/// Nested message and enum types in `Feature`.
-pub mod feature {
-    /// Each feature can be exactly one kind.
-    #[derive(Clone, PartialEq, ::prost::Oneof)]
-    pub enum Kind {
-        #[prost(message, tag="1")]
-        BytesList(super::BytesList),
-        #[prost(message, tag="2")]
-        FloatList(super::FloatList),
-        #[prost(message, tag="3")]
-        Int64List(super::Int64List),
-    }
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct Features {
-    /// Map from feature name to feature.
-    #[prost(map="string, message", tag="1")]
-    pub feature: ::std::collections::HashMap<::prost::alloc::string::String, Feature>,
-}
-/// Containers for sequential data.
-///
-/// A FeatureList contains lists of Features.  These may hold zero or more
-/// Feature values.
-///
-/// FeatureLists are organized into categories by name.  The FeatureLists message
-/// contains the mapping from name to FeatureList.
-///
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct FeatureList {
-    #[prost(message, repeated, tag="1")]
-    pub feature: ::prost::alloc::vec::Vec<Feature>,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct FeatureLists {
-    /// Map from feature name to feature list.
-    #[prost(map="string, message", tag="1")]
-    pub feature_list: ::std::collections::HashMap<::prost::alloc::string::String, FeatureList>,
-}
-// LINT.IfChange
-// An Example is a mostly-normalized data format for storing data for
-// training and inference.  It contains a key-value store (features); where
-// each key (string) maps to a Feature message (which is oneof packed BytesList,
-// FloatList, or Int64List).  This flexible and compact format allows the
-// storage of large amounts of typed data, but requires that the data shape
-// and use be determined by the configuration files and parsers that are used to
-// read and write this format.  That is, the Example is mostly *not* a
-// self-describing format.  In TensorFlow, Examples are read in row-major
-// format, so any configuration that describes data with rank-2 or above
-// should keep this in mind.  For example, to store an M x N matrix of Bytes,
-// the BytesList must contain M*N bytes, with M rows of N contiguous values
-// each.  That is, the BytesList value must store the matrix as:
-//     .... row 0 .... .... row 1 .... // ...........  // ... row M-1 ....
-//
-// An Example for a movie recommendation application:
-//   features {
-//     feature {
-//       key: "age"
-//       value { float_list {
-//         value: 29.0
-//       }}
-//     }
-//     feature {
-//       key: "movie"
-//       value { bytes_list {
-//         value: "The Shawshank Redemption"
-//         value: "Fight Club"
-//       }}
-//     }
-//     feature {
-//       key: "movie_ratings"
-//       value { float_list {
-//         value: 9.0
-//         value: 9.7
-//       }}
-//     }
-//     feature {
-//       key: "suggestion"
-//       value { bytes_list {
-//         value: "Inception"
-//       }}
-//     }
-//     # Note that this feature exists to be used as a label in training.
-//     # E.g., if training a logistic regression model to predict purchase
-//     # probability in our learning tool we would set the label feature to
-//     # "suggestion_purchased".
-//     feature {
-//       key: "suggestion_purchased"
-//       value { float_list {
-//         value: 1.0
-//       }}
-//     }
-//     # Similar to "suggestion_purchased" above this feature exists to be used
-//     # as a label in training.
-//     # E.g., if training a linear regression model to predict purchase
-//     # price in our learning tool we would set the label feature to
-//     # "purchase_price".
-//     feature {
-//       key: "purchase_price"
-//       value { float_list {
-//         value: 9.99
-//       }}
-//     }
-//  }
-//
-// A conformant Example data set obeys the following conventions:
-//   - If a Feature K exists in one example with data type T, it must be of
-//       type T in all other examples when present. It may be omitted.
-//   - The number of instances of Feature K list data may vary across examples,
-//       depending on the requirements of the model.
-//   - If a Feature K doesn't exist in an example, a K-specific default will be
-//       used, if configured.
-//   - If a Feature K exists in an example but contains no items, the intent
-//       is considered to be an empty tensor and no default will be used.
-
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct Example {
-    #[prost(message, optional, tag="1")]
-    pub features: ::core::option::Option<Features>,
-}
-// A SequenceExample is an Example representing one or more sequences, and
-// some context.  The context contains features which apply to the entire
-// example. The feature_lists contain a key, value map where each key is
-// associated with a repeated set of Features (a FeatureList).
-// A FeatureList thus represents the values of a feature identified by its key
-// over time / frames.
-//
-// Below is a SequenceExample for a movie recommendation application recording a
-// sequence of ratings by a user. The time-independent features ("locale",
-// "age", "favorites") describing the user are part of the context. The sequence
-// of movies the user rated are part of the feature_lists. For each movie in the
-// sequence we have information on its name and actors and the user's rating.
-// This information is recorded in three separate feature_list(s).
-// In the example below there are only two movies. All three feature_list(s),
-// namely "movie_ratings", "movie_names", and "actors" have a feature value for
-// both movies. Note, that "actors" is itself a bytes_list with multiple
-// strings per movie.
-//
-// context: {
-//   feature: {
-//     key  : "locale"
-//     value: {
-//       bytes_list: {
-//         value: [ "pt_BR" ]
-//       }
-//     }
-//   }
-//   feature: {
-//     key  : "age"
-//     value: {
-//       float_list: {
-//         value: [ 19.0 ]
-//       }
-//     }
-//   }
-//   feature: {
-//     key  : "favorites"
-//     value: {
-//       bytes_list: {
-//         value: [ "Majesty Rose", "Savannah Outen", "One Direction" ]
-//       }
-//     }
-//   }
-// }
-// feature_lists: {
-//   feature_list: {
-//     key  : "movie_ratings"
-//     value: {
-//       feature: {
-//         float_list: {
-//           value: [ 4.5 ]
-//         }
-//       }
-//       feature: {
-//         float_list: {
-//           value: [ 5.0 ]
-//         }
-//       }
-//     }
-//   }
-//   feature_list: {
-//     key  : "movie_names"
-//     value: {
-//       feature: {
-//         bytes_list: {
-//           value: [ "The Shawshank Redemption" ]
-//         }
-//       }
-//       feature: {
-//         bytes_list: {
-//           value: [ "Fight Club" ]
-//         }
-//       }
-//     }
-//   }
-//   feature_list: {
-//     key  : "actors"
-//     value: {
-//       feature: {
-//         bytes_list: {
-//           value: [ "Tim Robbins", "Morgan Freeman" ]
-//         }
-//       }
-//       feature: {
-//         bytes_list: {
-//           value: [ "Brad Pitt", "Edward Norton", "Helena Bonham Carter" ]
-//         }
-//       }
-//     }
-//   }
-// }
-//
-// A conformant SequenceExample data set obeys the following conventions:
-//
-// Context:
-//   - All conformant context features K must obey the same conventions as
-//     a conformant Example's features (see above).
-// Feature lists:
-//   - A FeatureList L may be missing in an example; it is up to the
-//     parser configuration to determine if this is allowed or considered
-//     an empty list (zero length).
-//   - If a FeatureList L exists, it may be empty (zero length).
-//   - If a FeatureList L is non-empty, all features within the FeatureList
-//     must have the same data type T. Even across SequenceExamples, the type T
-//     of the FeatureList identified by the same key must be the same. An entry
-//     without any values may serve as an empty feature.
-//   - If a FeatureList L is non-empty, it is up to the parser configuration
-//     to determine if all features within the FeatureList must
-//     have the same size.  The same holds for this FeatureList across multiple
-//     examples.
-//   - For sequence modeling, e.g.:
-//        <http://colah.github.io/posts/2015-08-Understanding-LSTMs/>
-//        <https://github.com/tensorflow/nmt>
-//     the feature lists represent a sequence of frames.
-//     In this scenario, all FeatureLists in a SequenceExample have the same
-//     number of Feature messages, so that the ith element in each FeatureList
-//     is part of the ith frame (or time step).
-// Examples of conformant and non-conformant examples' FeatureLists:
-//
-// Conformant FeatureLists:
-//    feature_lists: { feature_list: {
-//      key: "movie_ratings"
-//      value: { feature: { float_list: { value: [ 4.5 ] } }
-//               feature: { float_list: { value: [ 5.0 ] } } }
-//    } }
-//
-// Non-conformant FeatureLists (mismatched types):
-//    feature_lists: { feature_list: {
-//      key: "movie_ratings"
-//      value: { feature: { float_list: { value: [ 4.5 ] } }
-//               feature: { int64_list: { value: [ 5 ] } } }
-//    } }
-//
-// Conditionally conformant FeatureLists, the parser configuration determines
-// if the feature sizes must match:
-//    feature_lists: { feature_list: {
-//      key: "movie_ratings"
-//      value: { feature: { float_list: { value: [ 4.5 ] } }
-//               feature: { float_list: { value: [ 5.0, 6.0 ] } } }
-//    } }
-//
-// Conformant pair of SequenceExample
-//    feature_lists: { feature_list: {
-//      key: "movie_ratings"
-//      value: { feature: { float_list: { value: [ 4.5 ] } }
-//               feature: { float_list: { value: [ 5.0 ] } } }
-//    } }
-// and:
-//    feature_lists: { feature_list: {
-//      key: "movie_ratings"
-//      value: { feature: { float_list: { value: [ 4.5 ] } }
-//               feature: { float_list: { value: [ 5.0 ] } }
-//               feature: { float_list: { value: [ 2.0 ] } } }
-//    } }
-//
-// Conformant pair of SequenceExample
-//    feature_lists: { feature_list: {
-//      key: "movie_ratings"
-//      value: { feature: { float_list: { value: [ 4.5 ] } }
-//               feature: { float_list: { value: [ 5.0 ] } } }
-//    } }
-// and:
-//    feature_lists: { feature_list: {
-//      key: "movie_ratings"
-//      value: { }
-//    } }
-//
-// Conditionally conformant pair of SequenceExample, the parser configuration
-// determines if the second feature_lists is consistent (zero-length) or
-// invalid (missing "movie_ratings"):
-//    feature_lists: { feature_list: {
-//      key: "movie_ratings"
-//      value: { feature: { float_list: { value: [ 4.5 ] } }
-//               feature: { float_list: { value: [ 5.0 ] } } }
-//    } }
-// and:
-//    feature_lists: { }
-//
-// Non-conformant pair of SequenceExample (mismatched types)
-//    feature_lists: { feature_list: {
-//      key: "movie_ratings"
-//      value: { feature: { float_list: { value: [ 4.5 ] } }
-//               feature: { float_list: { value: [ 5.0 ] } } }
-//    } }
-// and:
-//    feature_lists: { feature_list: {
-//      key: "movie_ratings"
-//      value: { feature: { int64_list: { value: [ 4 ] } }
-//               feature: { int64_list: { value: [ 5 ] } }
-//               feature: { int64_list: { value: [ 2 ] } } }
-//    } }
-//
-// Conditionally conformant pair of SequenceExample; the parser configuration
-// determines if the feature sizes must match:
-//    feature_lists: { feature_list: {
-//      key: "movie_ratings"
-//      value: { feature: { float_list: { value: [ 4.5 ] } }
-//               feature: { float_list: { value: [ 5.0 ] } } }
-//    } }
-// and:
-//    feature_lists: { feature_list: {
-//      key: "movie_ratings"
-//      value: { feature: { float_list: { value: [ 4.0 ] } }
-//               feature: { float_list: { value: [ 5.0, 3.0 ] } }
-//    } }
-
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct SequenceExample {
-    #[prost(message, optional, tag="1")]
-    pub context: ::core::option::Option<Features>,
-    #[prost(message, optional, tag="2")]
-    pub feature_lists: ::core::option::Option<FeatureLists>,
-}
-/// Dimensions of a tensor.
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct TensorShapeProto {
-    /// Dimensions of the tensor, such as {"input", 30}, {"output", 40}
-    /// for a 30 x 40 2D tensor.  If an entry has size -1, this
-    /// corresponds to a dimension of unknown size. The names are
-    /// optional.
-    ///
-    /// The order of entries in "dim" matters: It indicates the layout of the
-    /// values in the tensor in-memory representation.
-    ///
-    /// The first entry in "dim" is the outermost dimension used to layout the
-    /// values, the last entry is the innermost dimension.  This matches the
-    /// in-memory layout of RowMajor Eigen tensors.
-    ///
-    /// If "dim.size()" > 0, "unknown_rank" must be false.
-    #[prost(message, repeated, tag="2")]
-    pub dim: ::prost::alloc::vec::Vec<tensor_shape_proto::Dim>,
-    /// If true, the number of dimensions in the shape is unknown.
-    ///
-    /// If true, "dim.size()" must be 0.
-    #[prost(bool, tag="3")]
-    pub unknown_rank: bool,
-}
-/// Nested message and enum types in `TensorShapeProto`.
-pub mod tensor_shape_proto {
-    /// One dimension of the tensor.
-    #[derive(Clone, PartialEq, ::prost::Message)]
-    pub struct Dim {
-        /// Size of the tensor in that dimension.
-        /// This value must be >= -1, but values of -1 are reserved for "unknown"
-        /// shapes (values of -1 mean "unknown" dimension).  Certain wrappers
-        /// that work with TensorShapeProto may fail at runtime when deserializing
-        /// a TensorShapeProto containing a dim value of -1.
-        #[prost(int64, tag="1")]
-        pub size: i64,
-        /// Optional name of the tensor dimension.
-        #[prost(string, tag="2")]
-        pub name: ::prost::alloc::string::String,
-    }
-}
-/// (== suppress_warning documentation-presence ==)
-/// LINT.IfChange
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
-#[repr(i32)]
-pub enum DataType {
-    /// Not a legal value for DataType.  Used to indicate a DataType field
-    /// has not been set.
-    DtInvalid = 0,
-    /// Data types that all computation devices are expected to be
-    /// capable to support.
-    DtFloat = 1,
-    DtDouble = 2,
-    DtInt32 = 3,
-    DtUint8 = 4,
-    DtInt16 = 5,
-    DtInt8 = 6,
-    DtString = 7,
-    /// Single-precision complex
-    DtComplex64 = 8,
-    DtInt64 = 9,
-    DtBool = 10,
-    /// Quantized int8
-    DtQint8 = 11,
-    /// Quantized uint8
-    DtQuint8 = 12,
-    /// Quantized int32
-    DtQint32 = 13,
-    /// Float32 truncated to 16 bits.  Only for cast ops.
-    DtBfloat16 = 14,
-    /// Quantized int16
-    DtQint16 = 15,
-    /// Quantized uint16
-    DtQuint16 = 16,
-    DtUint16 = 17,
-    /// Double-precision complex
-    DtComplex128 = 18,
-    DtHalf = 19,
-    DtResource = 20,
-    /// Arbitrary C++ data types
-    DtVariant = 21,
-    DtUint32 = 22,
-    DtUint64 = 23,
-    /// Do not use!  These are only for parameters.  Every enum above
-    /// should have a corresponding value below (verified by types_test).
-    DtFloatRef = 101,
-    DtDoubleRef = 102,
-    DtInt32Ref = 103,
-    DtUint8Ref = 104,
-    DtInt16Ref = 105,
-    DtInt8Ref = 106,
-    DtStringRef = 107,
-    DtComplex64Ref = 108,
-    DtInt64Ref = 109,
-    DtBoolRef = 110,
-    DtQint8Ref = 111,
-    DtQuint8Ref = 112,
-    DtQint32Ref = 113,
-    DtBfloat16Ref = 114,
-    DtQint16Ref = 115,
-    DtQuint16Ref = 116,
-    DtUint16Ref = 117,
-    DtComplex128Ref = 118,
-    DtHalfRef = 119,
-    DtResourceRef = 120,
-    DtVariantRef = 121,
-    DtUint32Ref = 122,
-    DtUint64Ref = 123,
-}
-/// Protocol buffer representing a handle to a tensorflow resource. Handles are
-/// not valid across executions, but can be serialized back and forth from within
-/// a single run.
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ResourceHandleProto {
-    /// Unique name for the device containing the resource.
-    #[prost(string, tag="1")]
-    pub device: ::prost::alloc::string::String,
-    /// Container in which this resource is placed.
-    #[prost(string, tag="2")]
-    pub container: ::prost::alloc::string::String,
-    /// Unique name of this resource.
-    #[prost(string, tag="3")]
-    pub name: ::prost::alloc::string::String,
-    /// Hash code for the type of the resource. Is only valid in the same device
-    /// and in the same execution.
-    #[prost(uint64, tag="4")]
-    pub hash_code: u64,
-    /// For debug-only, the name of the type pointed to by this handle, if
-    /// available.
-    #[prost(string, tag="5")]
-    pub maybe_type_name: ::prost::alloc::string::String,
-    /// Data types and shapes for the underlying resource.
-    #[prost(message, repeated, tag="6")]
-    pub dtypes_and_shapes: ::prost::alloc::vec::Vec<resource_handle_proto::DtypeAndShape>,
-}
-/// Nested message and enum types in `ResourceHandleProto`.
-pub mod resource_handle_proto {
-    /// Protocol buffer representing a pair of (data type, tensor shape).
-    #[derive(Clone, PartialEq, ::prost::Message)]
-    pub struct DtypeAndShape {
-        #[prost(enumeration="super::DataType", tag="1")]
-        pub dtype: i32,
-        #[prost(message, optional, tag="2")]
-        pub shape: ::core::option::Option<super::TensorShapeProto>,
-    }
-}
-/// Protocol buffer representing a tensor.
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct TensorProto {
-    #[prost(enumeration="DataType", tag="1")]
-    pub dtype: i32,
-    /// Shape of the tensor.  TODO(touts): sort out the 0-rank issues.
-    #[prost(message, optional, tag="2")]
-    pub tensor_shape: ::core::option::Option<TensorShapeProto>,
-    // Only one of the representations below is set, one of "tensor_contents" and
-    // the "xxx_val" attributes.  We are not using oneof because as oneofs cannot
-    // contain repeated fields it would require another extra set of messages.
-
-    /// Version number.
-    ///
-    /// In version 0, if the "repeated xxx" representations contain only one
-    /// element, that element is repeated to fill the shape.  This makes it easy
-    /// to represent a constant Tensor with a single value.
-    #[prost(int32, tag="3")]
-    pub version_number: i32,
-    /// Serialized raw tensor content from either Tensor::AsProtoTensorContent or
-    /// memcpy in tensorflow::grpc::EncodeTensorToByteBuffer. This representation
-    /// can be used for all tensor types. The purpose of this representation is to
-    /// reduce serialization overhead during RPC call by avoiding serialization of
-    /// many repeated small items.
-    #[prost(bytes="vec", tag="4")]
-    pub tensor_content: ::prost::alloc::vec::Vec<u8>,
-    // Type specific representations that make it easy to create tensor protos in
-    // all languages.  Only the representation corresponding to "dtype" can
-    // be set.  The values hold the flattened representation of the tensor in
-    // row major order.
-
-    /// DT_HALF, DT_BFLOAT16. Note that since protobuf has no int16 type, we'll
-    /// have some pointless zero padding for each value here.
-    #[prost(int32, repeated, tag="13")]
-    pub half_val: ::prost::alloc::vec::Vec<i32>,
-    /// DT_FLOAT.
-    #[prost(float, repeated, tag="5")]
-    pub float_val: ::prost::alloc::vec::Vec<f32>,
-    /// DT_DOUBLE.
-    #[prost(double, repeated, tag="6")]
-    pub double_val: ::prost::alloc::vec::Vec<f64>,
-    /// DT_INT32, DT_INT16, DT_UINT16, DT_INT8, DT_UINT8.
-    #[prost(int32, repeated, tag="7")]
-    pub int_val: ::prost::alloc::vec::Vec<i32>,
-    /// DT_STRING
-    #[prost(bytes="vec", repeated, tag="8")]
-    pub string_val: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
-    /// DT_COMPLEX64. scomplex_val(2*i) and scomplex_val(2*i+1) are real
-    /// and imaginary parts of i-th single precision complex.
-    #[prost(float, repeated, tag="9")]
-    pub scomplex_val: ::prost::alloc::vec::Vec<f32>,
-    /// DT_INT64
-    #[prost(int64, repeated, tag="10")]
-    pub int64_val: ::prost::alloc::vec::Vec<i64>,
-    /// DT_BOOL
-    #[prost(bool, repeated, tag="11")]
-    pub bool_val: ::prost::alloc::vec::Vec<bool>,
-    /// DT_COMPLEX128. dcomplex_val(2*i) and dcomplex_val(2*i+1) are real
-    /// and imaginary parts of i-th double precision complex.
-    #[prost(double, repeated, tag="12")]
-    pub dcomplex_val: ::prost::alloc::vec::Vec<f64>,
-    /// DT_RESOURCE
-    #[prost(message, repeated, tag="14")]
-    pub resource_handle_val: ::prost::alloc::vec::Vec<ResourceHandleProto>,
-    /// DT_VARIANT
-    #[prost(message, repeated, tag="15")]
-    pub variant_val: ::prost::alloc::vec::Vec<VariantTensorDataProto>,
-    /// DT_UINT32
-    #[prost(uint32, repeated, tag="16")]
-    pub uint32_val: ::prost::alloc::vec::Vec<u32>,
-    /// DT_UINT64
-    #[prost(uint64, repeated, tag="17")]
-    pub uint64_val: ::prost::alloc::vec::Vec<u64>,
-}
-/// Protocol buffer representing the serialization format of DT_VARIANT tensors.
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct VariantTensorDataProto {
-    /// Name of the type of objects being serialized.
-    #[prost(string, tag="1")]
-    pub type_name: ::prost::alloc::string::String,
-    /// Portions of the object that are not Tensors.
-    #[prost(bytes="vec", tag="2")]
-    pub metadata: ::prost::alloc::vec::Vec<u8>,
-    /// Tensors contained within objects being serialized.
-    #[prost(message, repeated, tag="3")]
-    pub tensors: ::prost::alloc::vec::Vec<TensorProto>,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct VarLenFeatureProto {
-    #[prost(enumeration="DataType", tag="1")]
-    pub dtype: i32,
-    #[prost(string, tag="2")]
-    pub values_output_tensor_name: ::prost::alloc::string::String,
-    #[prost(string, tag="3")]
-    pub indices_output_tensor_name: ::prost::alloc::string::String,
-    #[prost(string, tag="4")]
-    pub shapes_output_tensor_name: ::prost::alloc::string::String,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct FixedLenFeatureProto {
-    #[prost(enumeration="DataType", tag="1")]
-    pub dtype: i32,
-    #[prost(message, optional, tag="2")]
-    pub shape: ::core::option::Option<TensorShapeProto>,
-    #[prost(message, optional, tag="3")]
-    pub default_value: ::core::option::Option<TensorProto>,
-    #[prost(string, tag="4")]
-    pub values_output_tensor_name: ::prost::alloc::string::String,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct FeatureConfiguration {
-    #[prost(oneof="feature_configuration::Config", tags="1, 2")]
-    pub config: ::core::option::Option<feature_configuration::Config>,
-}
-/// Nested message and enum types in `FeatureConfiguration`.
-pub mod feature_configuration {
-    #[derive(Clone, PartialEq, ::prost::Oneof)]
-    pub enum Config {
-        #[prost(message, tag="1")]
-        FixedLenFeature(super::FixedLenFeatureProto),
-        #[prost(message, tag="2")]
-        VarLenFeature(super::VarLenFeatureProto),
-    }
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ExampleParserConfiguration {
-    #[prost(map="string, message", tag="1")]
-    pub feature_map: ::std::collections::HashMap<::prost::alloc::string::String, FeatureConfiguration>,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct AllocationDescription {
-    /// Total number of bytes requested
-    #[prost(int64, tag="1")]
-    pub requested_bytes: i64,
-    /// Total number of bytes allocated if known
-    #[prost(int64, tag="2")]
-    pub allocated_bytes: i64,
-    /// Name of the allocator used
-    #[prost(string, tag="3")]
-    pub allocator_name: ::prost::alloc::string::String,
-    /// Identifier of the allocated buffer if known
-    #[prost(int64, tag="4")]
-    pub allocation_id: i64,
-    /// Set if this tensor only has one remaining reference
-    #[prost(bool, tag="5")]
-    pub has_single_reference: bool,
-    /// Address of the allocation.
-    #[prost(uint64, tag="6")]
-    pub ptr: u64,
-}
 /// Protocol buffer representing the value for an attr used to configure an Op.
 /// Comment indicates the corresponding attr type.  Only the field matching the
 /// attr type may be filled.
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AttrValue {
     #[prost(oneof="attr_value::Value", tags="2, 3, 4, 5, 6, 7, 8, 1, 10, 9")]
@@ -661,6 +10,7 @@ pub struct AttrValue {
 /// Nested message and enum types in `AttrValue`.
 pub mod attr_value {
     /// LINT.IfChange
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct ListValue {
         /// "list(string)"
@@ -688,6 +38,7 @@ pub mod attr_value {
         #[prost(message, repeated, tag="9")]
         pub func: ::prost::alloc::vec::Vec<super::NameAttrList>,
     }
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum Value {
         /// "string"
@@ -733,6 +84,7 @@ pub mod attr_value {
 }
 /// A list of attr names and their values. The whole list is attached
 /// with a string name.  E.g., MatMul\[T=float\].
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct NameAttrList {
     #[prost(string, tag="1")]
@@ -756,6 +108,7 @@ pub struct NameAttrList {
 /// you can change the semantics of existing code.  These changes may
 /// need to wait until a major release of TensorFlow to avoid breaking
 /// our compatibility promises.
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ApiDef {
     /// Name of the op (in the OpDef) to specify the API for.
@@ -805,6 +158,7 @@ pub mod api_def {
     /// inherited endpoints.  The first endpoint should be the
     /// "canonical" endpoint, and should not be deprecated (unless all
     /// endpoints are deprecated).
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct Endpoint {
         /// Name should be either like "CamelCaseName" or
@@ -823,6 +177,7 @@ pub mod api_def {
         #[prost(int32, tag="4")]
         pub deprecation_version: i32,
     }
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct Arg {
         #[prost(string, tag="1")]
@@ -841,6 +196,7 @@ pub mod api_def {
     /// Description of the graph-construction-time configuration of this
     /// Op.  That is to say, this describes the attr fields that will
     /// be specified in the NodeDef.
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct Attr {
         #[prost(string, tag="1")]
@@ -861,6 +217,7 @@ pub mod api_def {
         #[prost(string, tag="4")]
         pub description: ::prost::alloc::string::String,
     }
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
     #[repr(i32)]
     pub enum Visibility {
@@ -877,11 +234,13 @@ pub mod api_def {
         Hidden = 3,
     }
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ApiDefs {
     #[prost(message, repeated, tag="1")]
     pub op: ::prost::alloc::vec::Vec<ApiDef>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CostGraphDef {
     #[prost(message, repeated, tag="1")]
@@ -891,6 +250,7 @@ pub struct CostGraphDef {
 }
 /// Nested message and enum types in `CostGraphDef`.
 pub mod cost_graph_def {
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct Node {
         /// The name of the node. Names are globally unique.
@@ -949,6 +309,7 @@ pub mod cost_graph_def {
         /// Inputs of this node. They must be executed before this node can be
         /// executed. An input is a particular output of another node, specified
         /// by the node id and the output index.
+        #[derive(serde::Serialize, serde::Deserialize)]
         #[derive(Clone, PartialEq, ::prost::Message)]
         pub struct InputInfo {
             #[prost(int32, tag="1")]
@@ -957,6 +318,7 @@ pub mod cost_graph_def {
             pub preceding_port: i32,
         }
         /// Outputs of this node.
+        #[derive(serde::Serialize, serde::Deserialize)]
         #[derive(Clone, PartialEq, ::prost::Message)]
         pub struct OutputInfo {
             #[prost(int64, tag="1")]
@@ -973,6 +335,7 @@ pub mod cost_graph_def {
         }
     }
     /// Total cost of this graph, typically used for balancing decisions.
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct AggregatedCost {
         /// Aggregated cost value.
@@ -983,65 +346,11 @@ pub mod cost_graph_def {
         pub dimension: ::prost::alloc::string::String,
     }
 }
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct InterconnectLink {
-    #[prost(int32, tag="1")]
-    pub device_id: i32,
-    #[prost(string, tag="2")]
-    pub r#type: ::prost::alloc::string::String,
-    #[prost(int32, tag="3")]
-    pub strength: i32,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct LocalLinks {
-    #[prost(message, repeated, tag="1")]
-    pub link: ::prost::alloc::vec::Vec<InterconnectLink>,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct DeviceLocality {
-    /// Optional bus locality of device.  Default value of 0 means
-    /// no specific locality.  Specific localities are indexed from 1.
-    #[prost(int32, tag="1")]
-    pub bus_id: i32,
-    /// Optional NUMA locality of device.
-    #[prost(int32, tag="2")]
-    pub numa_node: i32,
-    /// Optional local interconnect links to other devices.
-    #[prost(message, optional, tag="3")]
-    pub links: ::core::option::Option<LocalLinks>,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct DeviceAttributes {
-    /// Fully specified name of the device within a cluster.
-    #[prost(string, tag="1")]
-    pub name: ::prost::alloc::string::String,
-    /// String representation of device_type.
-    #[prost(string, tag="2")]
-    pub device_type: ::prost::alloc::string::String,
-    /// Memory capacity of device in bytes.
-    #[prost(int64, tag="4")]
-    pub memory_limit: i64,
-    /// Platform-specific data about device that may be useful
-    /// for supporting efficient data transfers.
-    #[prost(message, optional, tag="5")]
-    pub locality: ::core::option::Option<DeviceLocality>,
-    /// A device is assigned a global unique number each time it is
-    /// initialized. "incarnation" should never be 0.
-    #[prost(fixed64, tag="6")]
-    pub incarnation: u64,
-    /// String representation of the physical device that this device maps to.
-    #[prost(string, tag="7")]
-    pub physical_device_desc: ::prost::alloc::string::String,
-    /// A physical device ID for use in XLA DeviceAssignments, unique across
-    /// clients in a multi-client setup. Set to -1 if unavailable, non-negative
-    /// otherwise.
-    #[prost(int64, tag="8")]
-    pub xla_global_id: i64,
-}
 /// Highly experimental and very likely to change.
 /// This encoding uses tags instead of dedicated messages for regularity. In
 /// particular the encoding imposes no restrictions on what the parameters of any
 /// type should be, which in particular needs to be true for type symbols.
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FullTypeDef {
     /// The principal type represented by this object. This may be a concrete type
@@ -1064,6 +373,7 @@ pub mod full_type_def {
     /// For example, a type variable admits a string attribute - its name.
     /// Shape-related types may admit int attributes - their static shape values.
     /// Fields for more data types to be added as needed.
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum Attr {
         #[prost(string, tag="3")]
@@ -1074,6 +384,7 @@ pub mod full_type_def {
     }
 }
 /// Experimental. Represents the complete type information of a TensorFlow value.
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum FullTypeId {
@@ -1267,6 +578,7 @@ pub enum FullTypeId {
     /// The string element type.
     TftString = 214,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct NodeDef {
     /// The name given to this operator. Used for naming inputs,
@@ -1333,6 +645,7 @@ pub struct NodeDef {
 }
 /// Nested message and enum types in `NodeDef`.
 pub mod node_def {
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct ExperimentalDebugInfo {
         /// Opaque string inserted into error messages created by the runtime.
@@ -1358,6 +671,7 @@ pub mod node_def {
 /// Defines an operation. A NodeDef in a GraphDef specifies an Op by
 /// using the "op" field which should match the name of a OpDef.
 /// LINT.IfChange
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OpDef {
     /// Op names starting with an underscore are reserved for internal use.
@@ -1444,6 +758,7 @@ pub struct OpDef {
 /// Nested message and enum types in `OpDef`.
 pub mod op_def {
     /// For describing inputs and outputs.
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct ArgDef {
         /// Name for the input/output.  Should match the regexp "\[a-z][a-z0-9_\]*".
@@ -1497,6 +812,7 @@ pub mod op_def {
     /// Description of the graph-construction-time configuration of this
     /// Op.  That is to say, this describes the attr fields that will
     /// be specified in the NodeDef.
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct AttrDef {
         /// A descriptive name for the argument.  May be used, e.g. by the
@@ -1538,6 +854,7 @@ pub mod op_def {
     }
 }
 /// Information about version-dependent deprecation of an op
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OpDeprecation {
     /// First GraphDef version at which the op is disallowed.
@@ -1548,12 +865,14 @@ pub struct OpDeprecation {
     pub explanation: ::prost::alloc::string::String,
 }
 /// A collection of OpDefs
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OpList {
     #[prost(message, repeated, tag="1")]
     pub op: ::prost::alloc::vec::Vec<OpDef>,
 }
 /// A library is a set of named functions.
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FunctionDefLibrary {
     #[prost(message, repeated, tag="1")]
@@ -1569,6 +888,7 @@ pub struct FunctionDefLibrary {
 ///
 /// TODO(zhifengc):
 ///   * device spec, etc.
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FunctionDef {
     /// The definition of the function's name, arguments, return values,
@@ -1644,6 +964,7 @@ pub struct FunctionDef {
 pub mod function_def {
     /// Attributes for function arguments. These attributes are the same set of
     /// valid attributes as to _Arg nodes.
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct ArgAttrs {
         #[prost(map="string, message", tag="1")]
@@ -1668,6 +989,7 @@ pub mod function_def {
 /// where L is a scalar-value function of (x1, x2, ..., xN) (e.g., the
 /// loss function). dL/dx_i is the partial derivative of L with respect
 /// to x_i.
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GradientDef {
     /// The function name.
@@ -1681,6 +1003,7 @@ pub struct GradientDef {
 /// gradients library and used in the ops of a function in the function library.
 /// Unlike GradientDef, these gradients are identified by op type, and not
 /// directly linked to any function.
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RegisteredGradient {
     /// The gradient function's name.
@@ -1703,6 +1026,7 @@ pub struct RegisteredGradient {
 ///   consumer >= min_consumer
 ///   consumer not in bad_consumers
 ///
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct VersionDef {
     /// The version of the code that produced this data.
@@ -1716,6 +1040,7 @@ pub struct VersionDef {
     pub bad_consumers: ::prost::alloc::vec::Vec<i32>,
 }
 /// Represents the graph of operations
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GraphDef {
     #[prost(message, repeated, tag="1")]
@@ -1760,6 +1085,7 @@ pub struct GraphDef {
     #[prost(message, optional, tag="2")]
     pub library: ::core::option::Option<FunctionDefLibrary>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GraphTransferNodeInput {
     #[prost(int32, tag="1")]
@@ -1767,6 +1093,7 @@ pub struct GraphTransferNodeInput {
     #[prost(int32, tag="2")]
     pub output_port: i32,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GraphTransferNodeInfo {
     #[prost(string, tag="1")]
@@ -1784,6 +1111,7 @@ pub struct GraphTransferNodeInfo {
     #[prost(int32, tag="7")]
     pub output_count: i32,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GraphTransferConstNodeInfo {
     #[prost(string, tag="1")]
@@ -1797,6 +1125,7 @@ pub struct GraphTransferConstNodeInfo {
     #[prost(enumeration="DataType", tag="5")]
     pub dtype: i32,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GraphTransferNodeInputInfo {
     #[prost(int32, tag="1")]
@@ -1804,6 +1133,7 @@ pub struct GraphTransferNodeInputInfo {
     #[prost(message, repeated, tag="2")]
     pub node_input: ::prost::alloc::vec::Vec<GraphTransferNodeInput>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GraphTransferNodeOutputInfo {
     #[prost(int32, tag="1")]
@@ -1811,6 +1141,7 @@ pub struct GraphTransferNodeOutputInfo {
     #[prost(int32, repeated, tag="2")]
     pub max_byte_size: ::prost::alloc::vec::Vec<i32>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GraphTransferGraphInputNodeInfo {
     #[prost(string, tag="1")]
@@ -1820,6 +1151,7 @@ pub struct GraphTransferGraphInputNodeInfo {
     #[prost(enumeration="DataType", tag="3")]
     pub dtype: i32,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GraphTransferGraphOutputNodeInfo {
     #[prost(string, tag="1")]
@@ -1832,6 +1164,7 @@ pub struct GraphTransferGraphOutputNodeInfo {
 /// Protocol buffer representing a handle to a tensorflow resource. Handles are
 /// not valid across executions, but can be serialized back and forth from within
 /// a single run.
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GraphTransferInfo {
     #[prost(message, repeated, tag="1")]
@@ -1853,6 +1186,7 @@ pub struct GraphTransferInfo {
 }
 /// Nested message and enum types in `GraphTransferInfo`.
 pub mod graph_transfer_info {
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
     #[repr(i32)]
     pub enum Destination {
@@ -1860,6 +1194,7 @@ pub mod graph_transfer_info {
         Hexagon = 1,
     }
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct KernelDef {
     /// Must match the name of an Op.
@@ -1887,6 +1222,7 @@ pub struct KernelDef {
 }
 /// Nested message and enum types in `KernelDef`.
 pub mod kernel_def {
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct AttrConstraint {
         /// Name of an attr from the Op.
@@ -1899,418 +1235,14 @@ pub mod kernel_def {
     }
 }
 /// A collection of KernelDefs
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct KernelList {
     #[prost(message, repeated, tag="1")]
     pub kernel: ::prost::alloc::vec::Vec<KernelDef>,
 }
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct TensorDescription {
-    /// Data type of tensor elements
-    #[prost(enumeration="DataType", tag="1")]
-    pub dtype: i32,
-    /// Shape of the tensor.
-    #[prost(message, optional, tag="2")]
-    pub shape: ::core::option::Option<TensorShapeProto>,
-    /// Information about the size and allocator used for the data
-    #[prost(message, optional, tag="4")]
-    pub allocation_description: ::core::option::Option<AllocationDescription>,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct MemoryLogStep {
-    /// Process-unique step id.
-    #[prost(int64, tag="1")]
-    pub step_id: i64,
-    /// Handle describing the feeds and fetches of the step.
-    #[prost(string, tag="2")]
-    pub handle: ::prost::alloc::string::String,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct MemoryLogTensorAllocation {
-    /// Process-unique step id.
-    #[prost(int64, tag="1")]
-    pub step_id: i64,
-    /// Name of the kernel making the allocation as set in GraphDef,
-    /// e.g., "affine2/weights/Assign".
-    #[prost(string, tag="2")]
-    pub kernel_name: ::prost::alloc::string::String,
-    /// Allocated tensor details.
-    #[prost(message, optional, tag="3")]
-    pub tensor: ::core::option::Option<TensorDescription>,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct MemoryLogTensorDeallocation {
-    /// Id of the tensor buffer being deallocated, used to match to a
-    /// corresponding allocation.
-    #[prost(int64, tag="1")]
-    pub allocation_id: i64,
-    /// Name of the allocator used.
-    #[prost(string, tag="2")]
-    pub allocator_name: ::prost::alloc::string::String,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct MemoryLogTensorOutput {
-    /// Process-unique step id.
-    #[prost(int64, tag="1")]
-    pub step_id: i64,
-    /// Name of the kernel producing an output as set in GraphDef, e.g.,
-    /// "affine2/weights/Assign".
-    #[prost(string, tag="2")]
-    pub kernel_name: ::prost::alloc::string::String,
-    /// Index of the output being set.
-    #[prost(int32, tag="3")]
-    pub index: i32,
-    /// Output tensor details.
-    #[prost(message, optional, tag="4")]
-    pub tensor: ::core::option::Option<TensorDescription>,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct MemoryLogRawAllocation {
-    /// Process-unique step id.
-    #[prost(int64, tag="1")]
-    pub step_id: i64,
-    /// Name of the operation making the allocation.
-    #[prost(string, tag="2")]
-    pub operation: ::prost::alloc::string::String,
-    /// Number of bytes in the allocation.
-    #[prost(int64, tag="3")]
-    pub num_bytes: i64,
-    /// Address of the allocation.
-    #[prost(uint64, tag="4")]
-    pub ptr: u64,
-    /// Id of the tensor buffer being allocated, used to match to a
-    /// corresponding deallocation.
-    #[prost(int64, tag="5")]
-    pub allocation_id: i64,
-    /// Name of the allocator used.
-    #[prost(string, tag="6")]
-    pub allocator_name: ::prost::alloc::string::String,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct MemoryLogRawDeallocation {
-    /// Process-unique step id.
-    #[prost(int64, tag="1")]
-    pub step_id: i64,
-    /// Name of the operation making the deallocation.
-    #[prost(string, tag="2")]
-    pub operation: ::prost::alloc::string::String,
-    /// Id of the tensor buffer being deallocated, used to match to a
-    /// corresponding allocation.
-    #[prost(int64, tag="3")]
-    pub allocation_id: i64,
-    /// Name of the allocator used.
-    #[prost(string, tag="4")]
-    pub allocator_name: ::prost::alloc::string::String,
-    /// True if the deallocation is queued and will be performed later,
-    /// e.g. for GPU lazy freeing of buffers.
-    #[prost(bool, tag="5")]
-    pub deferred: bool,
-}
-/// For serializing and restoring the state of ReaderBase, see
-/// reader_base.h for details.
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ReaderBaseState {
-    #[prost(int64, tag="1")]
-    pub work_started: i64,
-    #[prost(int64, tag="2")]
-    pub work_finished: i64,
-    #[prost(int64, tag="3")]
-    pub num_records_produced: i64,
-    #[prost(bytes="vec", tag="4")]
-    pub current_work: ::prost::alloc::vec::Vec<u8>,
-}
-/// An allocation/de-allocation operation performed by the allocator.
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct AllocationRecord {
-    /// The timestamp of the operation.
-    #[prost(int64, tag="1")]
-    pub alloc_micros: i64,
-    /// Number of bytes allocated, or de-allocated if negative.
-    #[prost(int64, tag="2")]
-    pub alloc_bytes: i64,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct AllocatorMemoryUsed {
-    #[prost(string, tag="1")]
-    pub allocator_name: ::prost::alloc::string::String,
-    /// These are per-node allocator memory stats.
-    #[prost(int64, tag="2")]
-    pub total_bytes: i64,
-    #[prost(int64, tag="3")]
-    pub peak_bytes: i64,
-    /// The bytes that are not deallocated.
-    #[prost(int64, tag="4")]
-    pub live_bytes: i64,
-    /// The allocation and deallocation timeline.
-    #[prost(message, repeated, tag="6")]
-    pub allocation_records: ::prost::alloc::vec::Vec<AllocationRecord>,
-    /// These are snapshots of the overall allocator memory stats.
-    /// The number of live bytes currently allocated by the allocator.
-    #[prost(int64, tag="5")]
-    pub allocator_bytes_in_use: i64,
-}
-/// Output sizes recorded for a single execution of a graph node.
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct NodeOutput {
-    #[prost(int32, tag="1")]
-    pub slot: i32,
-    #[prost(message, optional, tag="3")]
-    pub tensor_description: ::core::option::Option<TensorDescription>,
-}
-/// For memory tracking.
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct MemoryStats {
-    #[prost(int64, tag="1")]
-    pub temp_memory_size: i64,
-    #[prost(int64, tag="3")]
-    pub persistent_memory_size: i64,
-    #[prost(int64, repeated, tag="5")]
-    pub persistent_tensor_alloc_ids: ::prost::alloc::vec::Vec<i64>,
-    #[deprecated]
-    #[prost(int64, tag="2")]
-    pub device_temp_memory_size: i64,
-    #[deprecated]
-    #[prost(int64, tag="4")]
-    pub device_persistent_memory_size: i64,
-    #[deprecated]
-    #[prost(int64, repeated, packed="false", tag="6")]
-    pub device_persistent_tensor_alloc_ids: ::prost::alloc::vec::Vec<i64>,
-}
-/// Time/size stats recorded for a single execution of a graph node.
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct NodeExecStats {
-    /// TODO(tucker): Use some more compact form of node identity than
-    /// the full string name.  Either all processes should agree on a
-    /// global id (cost_id?) for each node, or we should use a hash of
-    /// the name.
-    #[prost(string, tag="1")]
-    pub node_name: ::prost::alloc::string::String,
-    #[prost(int64, tag="2")]
-    pub all_start_micros: i64,
-    #[prost(int64, tag="3")]
-    pub op_start_rel_micros: i64,
-    #[prost(int64, tag="4")]
-    pub op_end_rel_micros: i64,
-    #[prost(int64, tag="5")]
-    pub all_end_rel_micros: i64,
-    #[prost(message, repeated, tag="6")]
-    pub memory: ::prost::alloc::vec::Vec<AllocatorMemoryUsed>,
-    #[prost(message, repeated, tag="7")]
-    pub output: ::prost::alloc::vec::Vec<NodeOutput>,
-    #[prost(string, tag="8")]
-    pub timeline_label: ::prost::alloc::string::String,
-    #[prost(int64, tag="9")]
-    pub scheduled_micros: i64,
-    #[prost(uint32, tag="10")]
-    pub thread_id: u32,
-    #[prost(message, repeated, tag="11")]
-    pub referenced_tensor: ::prost::alloc::vec::Vec<AllocationDescription>,
-    #[prost(message, optional, tag="12")]
-    pub memory_stats: ::core::option::Option<MemoryStats>,
-    #[prost(int64, tag="13")]
-    pub all_start_nanos: i64,
-    #[prost(int64, tag="14")]
-    pub op_start_rel_nanos: i64,
-    #[prost(int64, tag="15")]
-    pub op_end_rel_nanos: i64,
-    #[prost(int64, tag="16")]
-    pub all_end_rel_nanos: i64,
-    #[prost(int64, tag="17")]
-    pub scheduled_nanos: i64,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct DeviceStepStats {
-    #[prost(string, tag="1")]
-    pub device: ::prost::alloc::string::String,
-    #[prost(message, repeated, tag="2")]
-    pub node_stats: ::prost::alloc::vec::Vec<NodeExecStats>,
-    /// Its key is thread id.
-    #[prost(map="uint32, string", tag="3")]
-    pub thread_names: ::std::collections::HashMap<u32, ::prost::alloc::string::String>,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct StepStats {
-    #[prost(message, repeated, tag="1")]
-    pub dev_stats: ::prost::alloc::vec::Vec<DeviceStepStats>,
-}
-/// Metadata associated with a series of Summary data
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct SummaryDescription {
-    /// Hint on how plugins should process the data in this series.
-    /// Supported values include "scalar", "histogram", "image", "audio"
-    #[prost(string, tag="1")]
-    pub type_hint: ::prost::alloc::string::String,
-}
-/// Serialization format for histogram module in
-/// core/lib/histogram/histogram.h
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct HistogramProto {
-    #[prost(double, tag="1")]
-    pub min: f64,
-    #[prost(double, tag="2")]
-    pub max: f64,
-    #[prost(double, tag="3")]
-    pub num: f64,
-    #[prost(double, tag="4")]
-    pub sum: f64,
-    #[prost(double, tag="5")]
-    pub sum_squares: f64,
-    /// Parallel arrays encoding the bucket boundaries and the bucket values.
-    /// bucket(i) is the count for the bucket i.  The range for
-    /// a bucket is:
-    ///   i == 0:  -DBL_MAX .. bucket_limit(0)
-    ///   i != 0:  bucket_limit(i-1) .. bucket_limit(i)
-    #[prost(double, repeated, tag="6")]
-    pub bucket_limit: ::prost::alloc::vec::Vec<f64>,
-    #[prost(double, repeated, tag="7")]
-    pub bucket: ::prost::alloc::vec::Vec<f64>,
-}
-/// A SummaryMetadata encapsulates information on which plugins are able to make
-/// use of a certain summary value.
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct SummaryMetadata {
-    /// Data that associates a summary with a certain plugin.
-    #[prost(message, optional, tag="1")]
-    pub plugin_data: ::core::option::Option<summary_metadata::PluginData>,
-    /// Display name for viewing in TensorBoard.
-    #[prost(string, tag="2")]
-    pub display_name: ::prost::alloc::string::String,
-    /// Longform readable description of the summary sequence. Markdown supported.
-    #[prost(string, tag="3")]
-    pub summary_description: ::prost::alloc::string::String,
-    /// Class of data stored in this time series. Required for compatibility with
-    /// TensorBoard's generic data facilities (`DataProvider`, et al.). This value
-    /// imposes constraints on the dtype and shape of the corresponding tensor
-    /// values. See `DataClass` docs for details.
-    #[prost(enumeration="DataClass", tag="4")]
-    pub data_class: i32,
-}
-/// Nested message and enum types in `SummaryMetadata`.
-pub mod summary_metadata {
-    #[derive(Clone, PartialEq, ::prost::Message)]
-    pub struct PluginData {
-        /// The name of the plugin this data pertains to.
-        #[prost(string, tag="1")]
-        pub plugin_name: ::prost::alloc::string::String,
-        /// The content to store for the plugin. The best practice is for this to be
-        /// a binary serialized protocol buffer.
-        #[prost(bytes="vec", tag="2")]
-        pub content: ::prost::alloc::vec::Vec<u8>,
-    }
-}
-/// A Summary is a set of named values to be displayed by the
-/// visualizer.
-///
-/// Summaries are produced regularly during training, as controlled by
-/// the "summary_interval_secs" attribute of the training operation.
-/// Summaries are also produced at the end of an evaluation.
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct Summary {
-    /// Set of values for the summary.
-    #[prost(message, repeated, tag="1")]
-    pub value: ::prost::alloc::vec::Vec<summary::Value>,
-}
-/// Nested message and enum types in `Summary`.
-pub mod summary {
-    #[derive(Clone, PartialEq, ::prost::Message)]
-    pub struct Image {
-        /// Dimensions of the image.
-        #[prost(int32, tag="1")]
-        pub height: i32,
-        #[prost(int32, tag="2")]
-        pub width: i32,
-        /// Valid colorspace values are
-        ///   1 - grayscale
-        ///   2 - grayscale + alpha
-        ///   3 - RGB
-        ///   4 - RGBA
-        ///   5 - DIGITAL_YUV
-        ///   6 - BGRA
-        #[prost(int32, tag="3")]
-        pub colorspace: i32,
-        /// Image data in encoded format.  All image formats supported by
-        /// image_codec::CoderUtil can be stored here.
-        #[prost(bytes="vec", tag="4")]
-        pub encoded_image_string: ::prost::alloc::vec::Vec<u8>,
-    }
-    #[derive(Clone, PartialEq, ::prost::Message)]
-    pub struct Audio {
-        /// Sample rate of the audio in Hz.
-        #[prost(float, tag="1")]
-        pub sample_rate: f32,
-        /// Number of channels of audio.
-        #[prost(int64, tag="2")]
-        pub num_channels: i64,
-        /// Length of the audio in frames (samples per channel).
-        #[prost(int64, tag="3")]
-        pub length_frames: i64,
-        /// Encoded audio data and its associated RFC 2045 content type (e.g.
-        /// "audio/wav").
-        #[prost(bytes="vec", tag="4")]
-        pub encoded_audio_string: ::prost::alloc::vec::Vec<u8>,
-        #[prost(string, tag="5")]
-        pub content_type: ::prost::alloc::string::String,
-    }
-    #[derive(Clone, PartialEq, ::prost::Message)]
-    pub struct Value {
-        /// This field is deprecated and will not be set.
-        #[prost(string, tag="7")]
-        pub node_name: ::prost::alloc::string::String,
-        /// Tag name for the data. Used by TensorBoard plugins to organize data. Tags
-        /// are often organized by scope (which contains slashes to convey
-        /// hierarchy). For example: foo/bar/0
-        #[prost(string, tag="1")]
-        pub tag: ::prost::alloc::string::String,
-        /// Contains metadata on the summary value such as which plugins may use it.
-        /// Take note that many summary values may lack a metadata field. This is
-        /// because the FileWriter only keeps a metadata object on the first summary
-        /// value with a certain tag for each tag. TensorBoard then remembers which
-        /// tags are associated with which plugins. This saves space.
-        #[prost(message, optional, tag="9")]
-        pub metadata: ::core::option::Option<super::SummaryMetadata>,
-        /// Value associated with the tag.
-        #[prost(oneof="value::Value", tags="2, 3, 4, 5, 6, 8")]
-        pub value: ::core::option::Option<value::Value>,
-    }
-    /// Nested message and enum types in `Value`.
-    pub mod value {
-        /// Value associated with the tag.
-        #[derive(Clone, PartialEq, ::prost::Oneof)]
-        pub enum Value {
-            #[prost(float, tag="2")]
-            SimpleValue(f32),
-            #[prost(bytes, tag="3")]
-            ObsoleteOldStyleHistogram(::prost::alloc::vec::Vec<u8>),
-            #[prost(message, tag="4")]
-            Image(super::Image),
-            #[prost(message, tag="5")]
-            Histo(super::super::HistogramProto),
-            #[prost(message, tag="6")]
-            Audio(super::Audio),
-            #[prost(message, tag="8")]
-            Tensor(super::super::TensorProto),
-        }
-    }
-}
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
-#[repr(i32)]
-pub enum DataClass {
-    /// Unknown data class, used (implicitly) for legacy data. Will not be
-    /// processed by data ingestion pipelines.
-    Unknown = 0,
-    /// Scalar time series. Each `Value` for the corresponding tag must have
-    /// `tensor` set to a rank-0 tensor of type `DT_FLOAT` (float32).
-    Scalar = 1,
-    /// Tensor time series. Each `Value` for the corresponding tag must have
-    /// `tensor` set. The tensor value is arbitrary, but should be small to
-    /// accommodate direct storage in database backends: an upper bound of a few
-    /// kilobytes is a reasonable rule of thumb.
-    Tensor = 2,
-    /// Blob sequence time series. Each `Value` for the corresponding tag must
-    /// have `tensor` set to a rank-1 tensor of bytestring dtype.
-    BlobSequence = 3,
-}
 /// Can only be interpreted if you know the corresponding TensorShape.
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TensorSliceProto {
     /// Extent of the slice in all tensor dimensions.
@@ -2327,6 +1259,7 @@ pub mod tensor_slice_proto {
     ///
     /// Either both or no attributes must be set.  When no attribute is set
     /// means: All data in that dimension.
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct Extent {
         /// Start index of the slice, starting at 0.
@@ -2347,6 +1280,7 @@ pub mod tensor_slice_proto {
         /// "oneof" to preserve information about whether the length is
         /// present without changing the serialization format from the
         /// prior proto2 version of this proto.
+        #[derive(serde::Serialize, serde::Deserialize)]
         #[derive(Clone, PartialEq, ::prost::Oneof)]
         pub enum HasLength {
             #[prost(int64, tag="2")]
@@ -2355,6 +1289,7 @@ pub mod tensor_slice_proto {
     }
 }
 /// Protocol buffer representing a Variable.
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct VariableDef {
     /// Name of the variable tensor.
@@ -2385,6 +1320,7 @@ pub struct VariableDef {
     #[prost(enumeration="VariableAggregation", tag="9")]
     pub aggregation: i32,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SaveSliceInfoDef {
     /// Name of the full variable of which this is a slice.
@@ -2401,6 +1337,7 @@ pub struct SaveSliceInfoDef {
     pub var_shape: ::prost::alloc::vec::Vec<i64>,
 }
 /// Indicates when a distributed variable will be synced.
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum VariableSynchronization {
@@ -2420,6 +1357,7 @@ pub enum VariableSynchronization {
     OnRead = 3,
 }
 /// Indicates how a distributed variable will be aggregated.
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum VariableAggregation {
@@ -2436,161 +1374,3 @@ pub enum VariableAggregation {
     /// global step counter.
     OnlyFirstReplica = 3,
 }
-/// Protocol buffer representing an event that happened during
-/// the execution of a Brain model.
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct Event {
-    /// Timestamp of the event.
-    #[prost(double, tag="1")]
-    pub wall_time: f64,
-    /// Global step of the event.
-    #[prost(int64, tag="2")]
-    pub step: i64,
-    #[prost(oneof="event::What", tags="3, 4, 5, 6, 7, 8, 9")]
-    pub what: ::core::option::Option<event::What>,
-}
-/// Nested message and enum types in `Event`.
-pub mod event {
-    #[derive(Clone, PartialEq, ::prost::Oneof)]
-    pub enum What {
-        /// An event file was started, with the specified version.
-        /// This is use to identify the contents of the record IO files
-        /// easily.  Current version is "brain.Event:2".  All versions
-        /// start with "brain.Event:".
-        #[prost(string, tag="3")]
-        FileVersion(::prost::alloc::string::String),
-        /// An encoded version of a GraphDef.
-        #[prost(bytes, tag="4")]
-        GraphDef(::prost::alloc::vec::Vec<u8>),
-        /// A summary was generated.
-        #[prost(message, tag="5")]
-        Summary(super::Summary),
-        /// The user output a log message. This was theoretically used by the defunct
-        /// tensorboard_logging module, which has since been removed; this field is
-        /// now deprecated and should not be used.
-        #[prost(message, tag="6")]
-        LogMessage(super::LogMessage),
-        /// The state of the session which can be used for restarting after crashes.
-        #[prost(message, tag="7")]
-        SessionLog(super::SessionLog),
-        /// The metadata returned by running a session.run() call.
-        #[prost(message, tag="8")]
-        TaggedRunMetadata(super::TaggedRunMetadata),
-        /// An encoded version of a MetaGraphDef.
-        #[prost(bytes, tag="9")]
-        MetaGraphDef(::prost::alloc::vec::Vec<u8>),
-    }
-}
-/// Protocol buffer used for logging messages to the events file.
-///
-/// This was theoretically used by the defunct tensorboard_logging module, which
-/// has been removed; this message is now deprecated and should not be used.
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct LogMessage {
-    #[prost(enumeration="log_message::Level", tag="1")]
-    pub level: i32,
-    #[prost(string, tag="2")]
-    pub message: ::prost::alloc::string::String,
-}
-/// Nested message and enum types in `LogMessage`.
-pub mod log_message {
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
-    #[repr(i32)]
-    pub enum Level {
-        Unknown = 0,
-        /// Note: The logging level 10 cannot be named DEBUG. Some software
-        /// projects compile their C/C++ code with -DDEBUG in debug builds. So the
-        /// C++ code generated from this file should not have an identifier named
-        /// DEBUG.
-        Debugging = 10,
-        Info = 20,
-        Warn = 30,
-        Error = 40,
-        Fatal = 50,
-    }
-}
-/// Protocol buffer used for logging session state.
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct SessionLog {
-    #[prost(enumeration="session_log::SessionStatus", tag="1")]
-    pub status: i32,
-    /// This checkpoint_path contains both the path and filename.
-    #[prost(string, tag="2")]
-    pub checkpoint_path: ::prost::alloc::string::String,
-    #[prost(string, tag="3")]
-    pub msg: ::prost::alloc::string::String,
-}
-/// Nested message and enum types in `SessionLog`.
-pub mod session_log {
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
-    #[repr(i32)]
-    pub enum SessionStatus {
-        StatusUnspecified = 0,
-        Start = 1,
-        Stop = 2,
-        Checkpoint = 3,
-    }
-}
-/// For logging the metadata output for a single session.run() call.
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct TaggedRunMetadata {
-    /// Tag name associated with this metadata.
-    #[prost(string, tag="1")]
-    pub tag: ::prost::alloc::string::String,
-    /// Byte-encoded version of the `RunMetadata` proto in order to allow lazy
-    /// deserialization.
-    #[prost(bytes="vec", tag="2")]
-    pub run_metadata: ::prost::alloc::vec::Vec<u8>,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct WatchdogConfig {
-    #[prost(int64, tag="1")]
-    pub timeout_ms: i64,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct RequestedExitCode {
-    #[prost(int32, tag="1")]
-    pub exit_code: i32,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct WorkerHeartbeatRequest {
-    #[prost(enumeration="WorkerShutdownMode", tag="1")]
-    pub shutdown_mode: i32,
-    #[prost(message, optional, tag="2")]
-    pub watchdog_config: ::core::option::Option<WatchdogConfig>,
-    #[prost(message, optional, tag="3")]
-    pub exit_code: ::core::option::Option<RequestedExitCode>,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct WorkerHeartbeatResponse {
-    #[prost(enumeration="WorkerHealth", tag="1")]
-    pub health_status: i32,
-    #[prost(message, repeated, tag="2")]
-    pub worker_log: ::prost::alloc::vec::Vec<Event>,
-    #[prost(string, tag="3")]
-    pub hostname: ::prost::alloc::string::String,
-}
-// Worker heartbeat messages.  Support for these operations is currently
-// internal and expected to change.
-
-/// Current health status of a worker.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
-#[repr(i32)]
-pub enum WorkerHealth {
-    /// By default a worker is healthy.
-    Ok = 0,
-    ReceivedShutdownSignal = 1,
-    InternalError = 2,
-    /// Worker has been instructed to shutdown after a timeout.
-    ShuttingDown = 3,
-}
-/// Indicates the behavior of the worker when an internal error or shutdown
-/// signal is received.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
-#[repr(i32)]
-pub enum WorkerShutdownMode {
-    Default = 0,
-    NotConfigured = 1,
-    WaitForCoordinator = 2,
-    ShutdownAfterTimeout = 3,
-}
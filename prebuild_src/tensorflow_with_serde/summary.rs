@@ -0,0 +1,358 @@
+/// Metadata associated with a series of Summary data
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SummaryDescription {
+    /// Hint on how plugins should process the data in this series.
+    /// Supported values include "scalar", "histogram", "image", "audio"
+    #[prost(string, tag="1")]
+    pub type_hint: ::prost::alloc::string::String,
+}
+/// Serialization format for histogram module in
+/// core/lib/histogram/histogram.h
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HistogramProto {
+    #[prost(double, tag="1")]
+    pub min: f64,
+    #[prost(double, tag="2")]
+    pub max: f64,
+    #[prost(double, tag="3")]
+    pub num: f64,
+    #[prost(double, tag="4")]
+    pub sum: f64,
+    #[prost(double, tag="5")]
+    pub sum_squares: f64,
+    /// Parallel arrays encoding the bucket boundaries and the bucket values.
+    /// bucket(i) is the count for the bucket i.  The range for
+    /// a bucket is:
+    ///   i == 0:  -DBL_MAX .. bucket_limit(0)
+    ///   i != 0:  bucket_limit(i-1) .. bucket_limit(i)
+    #[prost(double, repeated, tag="6")]
+    pub bucket_limit: ::prost::alloc::vec::Vec<f64>,
+    #[prost(double, repeated, tag="7")]
+    pub bucket: ::prost::alloc::vec::Vec<f64>,
+}
+/// A SummaryMetadata encapsulates information on which plugins are able to make
+/// use of a certain summary value.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SummaryMetadata {
+    /// Data that associates a summary with a certain plugin.
+    #[prost(message, optional, tag="1")]
+    pub plugin_data: ::core::option::Option<summary_metadata::PluginData>,
+    /// Display name for viewing in TensorBoard.
+    #[prost(string, tag="2")]
+    pub display_name: ::prost::alloc::string::String,
+    /// Longform readable description of the summary sequence. Markdown supported.
+    #[prost(string, tag="3")]
+    pub summary_description: ::prost::alloc::string::String,
+    /// Class of data stored in this time series. Required for compatibility with
+    /// TensorBoard's generic data facilities (`DataProvider`, et al.). This value
+    /// imposes constraints on the dtype and shape of the corresponding tensor
+    /// values. See `DataClass` docs for details.
+    #[prost(enumeration="DataClass", tag="4")]
+    pub data_class: i32,
+}
+/// Nested message and enum types in `SummaryMetadata`.
+pub mod summary_metadata {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct PluginData {
+        /// The name of the plugin this data pertains to.
+        #[prost(string, tag="1")]
+        pub plugin_name: ::prost::alloc::string::String,
+        /// The content to store for the plugin. The best practice is for this to be
+        /// a binary serialized protocol buffer.
+        #[prost(bytes="vec", tag="2")]
+        pub content: ::prost::alloc::vec::Vec<u8>,
+    }
+}
+/// A Summary is a set of named values to be displayed by the
+/// visualizer.
+///
+/// Summaries are produced regularly during training, as controlled by
+/// the "summary_interval_secs" attribute of the training operation.
+/// Summaries are also produced at the end of an evaluation.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Summary {
+    /// Set of values for the summary.
+    #[prost(message, repeated, tag="1")]
+    pub value: ::prost::alloc::vec::Vec<summary::Value>,
+}
+/// Nested message and enum types in `Summary`.
+pub mod summary {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Image {
+        /// Dimensions of the image.
+        #[prost(int32, tag="1")]
+        pub height: i32,
+        #[prost(int32, tag="2")]
+        pub width: i32,
+        /// Valid colorspace values are
+        ///   1 - grayscale
+        ///   2 - grayscale + alpha
+        ///   3 - RGB
+        ///   4 - RGBA
+        ///   5 - DIGITAL_YUV
+        ///   6 - BGRA
+        #[prost(int32, tag="3")]
+        pub colorspace: i32,
+        /// Image data in encoded format.  All image formats supported by
+        /// image_codec::CoderUtil can be stored here.
+        #[prost(bytes="vec", tag="4")]
+        pub encoded_image_string: ::prost::alloc::vec::Vec<u8>,
+    }
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Audio {
+        /// Sample rate of the audio in Hz.
+        #[prost(float, tag="1")]
+        pub sample_rate: f32,
+        /// Number of channels of audio.
+        #[prost(int64, tag="2")]
+        pub num_channels: i64,
+        /// Length of the audio in frames (samples per channel).
+        #[prost(int64, tag="3")]
+        pub length_frames: i64,
+        /// Encoded audio data and its associated RFC 2045 content type (e.g.
+        /// "audio/wav").
+        #[prost(bytes="vec", tag="4")]
+        pub encoded_audio_string: ::prost::alloc::vec::Vec<u8>,
+        #[prost(string, tag="5")]
+        pub content_type: ::prost::alloc::string::String,
+    }
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Value {
+        /// This field is deprecated and will not be set.
+        #[prost(string, tag="7")]
+        pub node_name: ::prost::alloc::string::String,
+        /// Tag name for the data. Used by TensorBoard plugins to organize data. Tags
+        /// are often organized by scope (which contains slashes to convey
+        /// hierarchy). For example: foo/bar/0
+        #[prost(string, tag="1")]
+        pub tag: ::prost::alloc::string::String,
+        /// Contains metadata on the summary value such as which plugins may use it.
+        /// Take note that many summary values may lack a metadata field. This is
+        /// because the FileWriter only keeps a metadata object on the first summary
+        /// value with a certain tag for each tag. TensorBoard then remembers which
+        /// tags are associated with which plugins. This saves space.
+        #[prost(message, optional, tag="9")]
+        pub metadata: ::core::option::Option<super::SummaryMetadata>,
+        /// Value associated with the tag.
+        #[prost(oneof="value::Value", tags="2, 3, 4, 5, 6, 8")]
+        pub value: ::core::option::Option<value::Value>,
+    }
+    /// Nested message and enum types in `Value`.
+    pub mod value {
+        /// Value associated with the tag.
+        #[derive(serde::Serialize, serde::Deserialize)]
+        #[derive(Clone, PartialEq, ::prost::Oneof)]
+        pub enum Value {
+            #[prost(float, tag="2")]
+            SimpleValue(f32),
+            #[prost(bytes, tag="3")]
+            ObsoleteOldStyleHistogram(::prost::alloc::vec::Vec<u8>),
+            #[prost(message, tag="4")]
+            Image(super::Image),
+            #[prost(message, tag="5")]
+            Histo(super::super::HistogramProto),
+            #[prost(message, tag="6")]
+            Audio(super::Audio),
+            #[prost(message, tag="8")]
+            Tensor(super::super::TensorProto),
+        }
+    }
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum DataClass {
+    /// Unknown data class, used (implicitly) for legacy data. Will not be
+    /// processed by data ingestion pipelines.
+    Unknown = 0,
+    /// Scalar time series. Each `Value` for the corresponding tag must have
+    /// `tensor` set to a rank-0 tensor of type `DT_FLOAT` (float32).
+    Scalar = 1,
+    /// Tensor time series. Each `Value` for the corresponding tag must have
+    /// `tensor` set. The tensor value is arbitrary, but should be small to
+    /// accommodate direct storage in database backends: an upper bound of a few
+    /// kilobytes is a reasonable rule of thumb.
+    Tensor = 2,
+    /// Blob sequence time series. Each `Value` for the corresponding tag must
+    /// have `tensor` set to a rank-1 tensor of bytestring dtype.
+    BlobSequence = 3,
+}
+/// Protocol buffer representing an event that happened during
+/// the execution of a Brain model.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Event {
+    /// Timestamp of the event.
+    #[prost(double, tag="1")]
+    pub wall_time: f64,
+    /// Global step of the event.
+    #[prost(int64, tag="2")]
+    pub step: i64,
+    #[prost(oneof="event::What", tags="3, 4, 5, 6, 7, 8, 9")]
+    pub what: ::core::option::Option<event::What>,
+}
+/// Nested message and enum types in `Event`.
+pub mod event {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum What {
+        /// An event file was started, with the specified version.
+        /// This is use to identify the contents of the record IO files
+        /// easily.  Current version is "brain.Event:2".  All versions
+        /// start with "brain.Event:".
+        #[prost(string, tag="3")]
+        FileVersion(::prost::alloc::string::String),
+        /// An encoded version of a GraphDef.
+        #[prost(bytes, tag="4")]
+        GraphDef(::prost::alloc::vec::Vec<u8>),
+        /// A summary was generated.
+        #[prost(message, tag="5")]
+        Summary(super::Summary),
+        /// The user output a log message. This was theoretically used by the defunct
+        /// tensorboard_logging module, which has since been removed; this field is
+        /// now deprecated and should not be used.
+        #[prost(message, tag="6")]
+        LogMessage(super::LogMessage),
+        /// The state of the session which can be used for restarting after crashes.
+        #[prost(message, tag="7")]
+        SessionLog(super::SessionLog),
+        /// The metadata returned by running a session.run() call.
+        #[prost(message, tag="8")]
+        TaggedRunMetadata(super::TaggedRunMetadata),
+        /// An encoded version of a MetaGraphDef.
+        #[prost(bytes, tag="9")]
+        MetaGraphDef(::prost::alloc::vec::Vec<u8>),
+    }
+}
+/// Protocol buffer used for logging messages to the events file.
+///
+/// This was theoretically used by the defunct tensorboard_logging module, which
+/// has been removed; this message is now deprecated and should not be used.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LogMessage {
+    #[prost(enumeration="log_message::Level", tag="1")]
+    pub level: i32,
+    #[prost(string, tag="2")]
+    pub message: ::prost::alloc::string::String,
+}
+/// Nested message and enum types in `LogMessage`.
+pub mod log_message {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Level {
+        Unknown = 0,
+        /// Note: The logging level 10 cannot be named DEBUG. Some software
+        /// projects compile their C/C++ code with -DDEBUG in debug builds. So the
+        /// C++ code generated from this file should not have an identifier named
+        /// DEBUG.
+        Debugging = 10,
+        Info = 20,
+        Warn = 30,
+        Error = 40,
+        Fatal = 50,
+    }
+}
+/// Protocol buffer used for logging session state.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SessionLog {
+    #[prost(enumeration="session_log::SessionStatus", tag="1")]
+    pub status: i32,
+    /// This checkpoint_path contains both the path and filename.
+    #[prost(string, tag="2")]
+    pub checkpoint_path: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub msg: ::prost::alloc::string::String,
+}
+/// Nested message and enum types in `SessionLog`.
+pub mod session_log {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum SessionStatus {
+        StatusUnspecified = 0,
+        Start = 1,
+        Stop = 2,
+        Checkpoint = 3,
+    }
+}
+/// For logging the metadata output for a single session.run() call.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TaggedRunMetadata {
+    /// Tag name associated with this metadata.
+    #[prost(string, tag="1")]
+    pub tag: ::prost::alloc::string::String,
+    /// Byte-encoded version of the `RunMetadata` proto in order to allow lazy
+    /// deserialization.
+    #[prost(bytes="vec", tag="2")]
+    pub run_metadata: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WatchdogConfig {
+    #[prost(int64, tag="1")]
+    pub timeout_ms: i64,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RequestedExitCode {
+    #[prost(int32, tag="1")]
+    pub exit_code: i32,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WorkerHeartbeatRequest {
+    #[prost(enumeration="WorkerShutdownMode", tag="1")]
+    pub shutdown_mode: i32,
+    #[prost(message, optional, tag="2")]
+    pub watchdog_config: ::core::option::Option<WatchdogConfig>,
+    #[prost(message, optional, tag="3")]
+    pub exit_code: ::core::option::Option<RequestedExitCode>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WorkerHeartbeatResponse {
+    #[prost(enumeration="WorkerHealth", tag="1")]
+    pub health_status: i32,
+    #[prost(message, repeated, tag="2")]
+    pub worker_log: ::prost::alloc::vec::Vec<Event>,
+    #[prost(string, tag="3")]
+    pub hostname: ::prost::alloc::string::String,
+}
+// Worker heartbeat messages.  Support for these operations is currently
+// internal and expected to change.
+
+/// Current health status of a worker.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum WorkerHealth {
+    /// By default a worker is healthy.
+    Ok = 0,
+    ReceivedShutdownSignal = 1,
+    InternalError = 2,
+    /// Worker has been instructed to shutdown after a timeout.
+    ShuttingDown = 3,
+}
+/// Indicates the behavior of the worker when an internal error or shutdown
+/// signal is received.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum WorkerShutdownMode {
+    Default = 0,
+    NotConfigured = 1,
+    WaitForCoordinator = 2,
+    ShutdownAfterTimeout = 3,
+}
@@ -0,0 +1,305 @@
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AllocationDescription {
+    /// Total number of bytes requested
+    #[prost(int64, tag="1")]
+    pub requested_bytes: i64,
+    /// Total number of bytes allocated if known
+    #[prost(int64, tag="2")]
+    pub allocated_bytes: i64,
+    /// Name of the allocator used
+    #[prost(string, tag="3")]
+    pub allocator_name: ::prost::alloc::string::String,
+    /// Identifier of the allocated buffer if known
+    #[prost(int64, tag="4")]
+    pub allocation_id: i64,
+    /// Set if this tensor only has one remaining reference
+    #[prost(bool, tag="5")]
+    pub has_single_reference: bool,
+    /// Address of the allocation.
+    #[prost(uint64, tag="6")]
+    pub ptr: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InterconnectLink {
+    #[prost(int32, tag="1")]
+    pub device_id: i32,
+    #[prost(string, tag="2")]
+    pub r#type: ::prost::alloc::string::String,
+    #[prost(int32, tag="3")]
+    pub strength: i32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LocalLinks {
+    #[prost(message, repeated, tag="1")]
+    pub link: ::prost::alloc::vec::Vec<InterconnectLink>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeviceLocality {
+    /// Optional bus locality of device.  Default value of 0 means
+    /// no specific locality.  Specific localities are indexed from 1.
+    #[prost(int32, tag="1")]
+    pub bus_id: i32,
+    /// Optional NUMA locality of device.
+    #[prost(int32, tag="2")]
+    pub numa_node: i32,
+    /// Optional local interconnect links to other devices.
+    #[prost(message, optional, tag="3")]
+    pub links: ::core::option::Option<LocalLinks>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeviceAttributes {
+    /// Fully specified name of the device within a cluster.
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+    /// String representation of device_type.
+    #[prost(string, tag="2")]
+    pub device_type: ::prost::alloc::string::String,
+    /// Memory capacity of device in bytes.
+    #[prost(int64, tag="4")]
+    pub memory_limit: i64,
+    /// Platform-specific data about device that may be useful
+    /// for supporting efficient data transfers.
+    #[prost(message, optional, tag="5")]
+    pub locality: ::core::option::Option<DeviceLocality>,
+    /// A device is assigned a global unique number each time it is
+    /// initialized. "incarnation" should never be 0.
+    #[prost(fixed64, tag="6")]
+    pub incarnation: u64,
+    /// String representation of the physical device that this device maps to.
+    #[prost(string, tag="7")]
+    pub physical_device_desc: ::prost::alloc::string::String,
+    /// A physical device ID for use in XLA DeviceAssignments, unique across
+    /// clients in a multi-client setup. Set to -1 if unavailable, non-negative
+    /// otherwise.
+    #[prost(int64, tag="8")]
+    pub xla_global_id: i64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TensorDescription {
+    /// Data type of tensor elements
+    #[prost(enumeration="DataType", tag="1")]
+    pub dtype: i32,
+    /// Shape of the tensor.
+    #[prost(message, optional, tag="2")]
+    pub shape: ::core::option::Option<TensorShapeProto>,
+    /// Information about the size and allocator used for the data
+    #[prost(message, optional, tag="4")]
+    pub allocation_description: ::core::option::Option<AllocationDescription>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MemoryLogStep {
+    /// Process-unique step id.
+    #[prost(int64, tag="1")]
+    pub step_id: i64,
+    /// Handle describing the feeds and fetches of the step.
+    #[prost(string, tag="2")]
+    pub handle: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MemoryLogTensorAllocation {
+    /// Process-unique step id.
+    #[prost(int64, tag="1")]
+    pub step_id: i64,
+    /// Name of the kernel making the allocation as set in GraphDef,
+    /// e.g., "affine2/weights/Assign".
+    #[prost(string, tag="2")]
+    pub kernel_name: ::prost::alloc::string::String,
+    /// Allocated tensor details.
+    #[prost(message, optional, tag="3")]
+    pub tensor: ::core::option::Option<TensorDescription>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MemoryLogTensorDeallocation {
+    /// Id of the tensor buffer being deallocated, used to match to a
+    /// corresponding allocation.
+    #[prost(int64, tag="1")]
+    pub allocation_id: i64,
+    /// Name of the allocator used.
+    #[prost(string, tag="2")]
+    pub allocator_name: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MemoryLogTensorOutput {
+    /// Process-unique step id.
+    #[prost(int64, tag="1")]
+    pub step_id: i64,
+    /// Name of the kernel producing an output as set in GraphDef, e.g.,
+    /// "affine2/weights/Assign".
+    #[prost(string, tag="2")]
+    pub kernel_name: ::prost::alloc::string::String,
+    /// Index of the output being set.
+    #[prost(int32, tag="3")]
+    pub index: i32,
+    /// Output tensor details.
+    #[prost(message, optional, tag="4")]
+    pub tensor: ::core::option::Option<TensorDescription>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MemoryLogRawAllocation {
+    /// Process-unique step id.
+    #[prost(int64, tag="1")]
+    pub step_id: i64,
+    /// Name of the operation making the allocation.
+    #[prost(string, tag="2")]
+    pub operation: ::prost::alloc::string::String,
+    /// Number of bytes in the allocation.
+    #[prost(int64, tag="3")]
+    pub num_bytes: i64,
+    /// Address of the allocation.
+    #[prost(uint64, tag="4")]
+    pub ptr: u64,
+    /// Id of the tensor buffer being allocated, used to match to a
+    /// corresponding deallocation.
+    #[prost(int64, tag="5")]
+    pub allocation_id: i64,
+    /// Name of the allocator used.
+    #[prost(string, tag="6")]
+    pub allocator_name: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MemoryLogRawDeallocation {
+    /// Process-unique step id.
+    #[prost(int64, tag="1")]
+    pub step_id: i64,
+    /// Name of the operation making the deallocation.
+    #[prost(string, tag="2")]
+    pub operation: ::prost::alloc::string::String,
+    /// Id of the tensor buffer being deallocated, used to match to a
+    /// corresponding allocation.
+    #[prost(int64, tag="3")]
+    pub allocation_id: i64,
+    /// Name of the allocator used.
+    #[prost(string, tag="4")]
+    pub allocator_name: ::prost::alloc::string::String,
+    /// True if the deallocation is queued and will be performed later,
+    /// e.g. for GPU lazy freeing of buffers.
+    #[prost(bool, tag="5")]
+    pub deferred: bool,
+}
+/// For serializing and restoring the state of ReaderBase, see
+/// reader_base.h for details.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReaderBaseState {
+    #[prost(int64, tag="1")]
+    pub work_started: i64,
+    #[prost(int64, tag="2")]
+    pub work_finished: i64,
+    #[prost(int64, tag="3")]
+    pub num_records_produced: i64,
+    #[prost(bytes="vec", tag="4")]
+    pub current_work: ::prost::alloc::vec::Vec<u8>,
+}
+/// An allocation/de-allocation operation performed by the allocator.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AllocationRecord {
+    /// The timestamp of the operation.
+    #[prost(int64, tag="1")]
+    pub alloc_micros: i64,
+    /// Number of bytes allocated, or de-allocated if negative.
+    #[prost(int64, tag="2")]
+    pub alloc_bytes: i64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AllocatorMemoryUsed {
+    #[prost(string, tag="1")]
+    pub allocator_name: ::prost::alloc::string::String,
+    /// These are per-node allocator memory stats.
+    #[prost(int64, tag="2")]
+    pub total_bytes: i64,
+    #[prost(int64, tag="3")]
+    pub peak_bytes: i64,
+    /// The bytes that are not deallocated.
+    #[prost(int64, tag="4")]
+    pub live_bytes: i64,
+    /// The allocation and deallocation timeline.
+    #[prost(message, repeated, tag="6")]
+    pub allocation_records: ::prost::alloc::vec::Vec<AllocationRecord>,
+    /// These are snapshots of the overall allocator memory stats.
+    /// The number of live bytes currently allocated by the allocator.
+    #[prost(int64, tag="5")]
+    pub allocator_bytes_in_use: i64,
+}
+/// Output sizes recorded for a single execution of a graph node.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NodeOutput {
+    #[prost(int32, tag="1")]
+    pub slot: i32,
+    #[prost(message, optional, tag="3")]
+    pub tensor_description: ::core::option::Option<TensorDescription>,
+}
+/// For memory tracking.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MemoryStats {
+    #[prost(int64, tag="1")]
+    pub temp_memory_size: i64,
+    #[prost(int64, tag="3")]
+    pub persistent_memory_size: i64,
+    #[prost(int64, repeated, tag="5")]
+    pub persistent_tensor_alloc_ids: ::prost::alloc::vec::Vec<i64>,
+    #[deprecated]
+    #[prost(int64, tag="2")]
+    pub device_temp_memory_size: i64,
+    #[deprecated]
+    #[prost(int64, tag="4")]
+    pub device_persistent_memory_size: i64,
+    #[deprecated]
+    #[prost(int64, repeated, packed="false", tag="6")]
+    pub device_persistent_tensor_alloc_ids: ::prost::alloc::vec::Vec<i64>,
+}
+/// Time/size stats recorded for a single execution of a graph node.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NodeExecStats {
+    /// TODO(tucker): Use some more compact form of node identity than
+    /// the full string name.  Either all processes should agree on a
+    /// global id (cost_id?) for each node, or we should use a hash of
+    /// the name.
+    #[prost(string, tag="1")]
+    pub node_name: ::prost::alloc::string::String,
+    #[prost(int64, tag="2")]
+    pub all_start_micros: i64,
+    #[prost(int64, tag="3")]
+    pub op_start_rel_micros: i64,
+    #[prost(int64, tag="4")]
+    pub op_end_rel_micros: i64,
+    #[prost(int64, tag="5")]
+    pub all_end_rel_micros: i64,
+    #[prost(message, repeated, tag="6")]
+    pub memory: ::prost::alloc::vec::Vec<AllocatorMemoryUsed>,
+    #[prost(message, repeated, tag="7")]
+    pub output: ::prost::alloc::vec::Vec<NodeOutput>,
+    #[prost(string, tag="8")]
+    pub timeline_label: ::prost::alloc::string::String,
+    #[prost(int64, tag="9")]
+    pub scheduled_micros: i64,
+    #[prost(uint32, tag="10")]
+    pub thread_id: u32,
+    #[prost(message, repeated, tag="11")]
+    pub referenced_tensor: ::prost::alloc::vec::Vec<AllocationDescription>,
+    #[prost(message, optional, tag="12")]
+    pub memory_stats: ::core::option::Option<MemoryStats>,
+    #[prost(int64, tag="13")]
+    pub all_start_nanos: i64,
+    #[prost(int64, tag="14")]
+    pub op_start_rel_nanos: i64,
+    #[prost(int64, tag="15")]
+    pub op_end_rel_nanos: i64,
+    #[prost(int64, tag="16")]
+    pub all_end_rel_nanos: i64,
+    #[prost(int64, tag="17")]
+    pub scheduled_nanos: i64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeviceStepStats {
+    #[prost(string, tag="1")]
+    pub device: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag="2")]
+    pub node_stats: ::prost::alloc::vec::Vec<NodeExecStats>,
+    /// Its key is thread id.
+    #[prost(map="uint32, string", tag="3")]
+    pub thread_names: ::std::collections::HashMap<u32, ::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StepStats {
+    #[prost(message, repeated, tag="1")]
+    pub dev_stats: ::prost::alloc::vec::Vec<DeviceStepStats>,
+}
@@ -0,0 +1,530 @@
+//! Audit the wire encoding of examples for anomalies hidden by permissive decoding.
+//!
+//! Decoding accepts many encodings that no conforming writer produces. A
+//! [DecodeReport] counts them per [Anomaly] while examples are decoded by
+//! [Example::decode_with_report], and keeps the locations of the first few as
+//! [exemplars](AnomalyExemplar). The decoded examples are the same as without the
+//! report.
+//!
+//! [wire_anomalies] audits the files of a dataset in parallel. The reports of files are
+//! merged in the order of files, so the result, including the exemplars, does not
+//! depend on the scheduling of threads.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{
+//!     audit::{self, AuditOptions},
+//!     samples,
+//! };
+//!
+//! let dataset = samples::tiny_dataset(2, 5)?;
+//! let report = audit::wire_anomalies(dataset.paths(), AuditOptions::default())?;
+//! assert_eq!(report.num_records, 10);
+//! assert_eq!(report.counts.total(), 0);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    protobuf::Example,
+    record_reader::{BytesIter, RecordReaderConfig},
+};
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+/// The message nesting depth beyond which [Anomaly::DeepNesting] is counted.
+///
+/// The example itself is at depth 1, and the value lists of features are at depth 5.
+/// Only groups, the deprecated delimited encoding of unknown fields, nest deeper.
+pub const DEEP_NESTING_DEPTH: usize = 8;
+
+/// A category of wire-level anomalies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Anomaly {
+    /// A field with a tag or wire type not in the schema, which decoding skips.
+    UnknownField,
+    /// A feature key appearing again in the same example, where the last one wins.
+    DuplicateKey,
+    /// A feature without any value list.
+    KindNotSet,
+    /// An unknown field nesting groups beyond [DEEP_NESTING_DEPTH], counted once per
+    /// top-level unknown field.
+    DeepNesting,
+    /// A varint encoded in more bytes than needed.
+    NonMinimalVarint,
+}
+
+/// The number of anomalies per category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnomalyCounts {
+    pub unknown_fields: u64,
+    pub duplicate_keys: u64,
+    pub kind_not_set: u64,
+    pub deep_nesting: u64,
+    pub non_minimal_varints: u64,
+}
+
+impl AnomalyCounts {
+    /// The count of a category.
+    pub fn get(&self, anomaly: Anomaly) -> u64 {
+        *self.slot(anomaly)
+    }
+
+    /// The count of all categories.
+    pub fn total(&self) -> u64 {
+        self.unknown_fields
+            + self.duplicate_keys
+            + self.kind_not_set
+            + self.deep_nesting
+            + self.non_minimal_varints
+    }
+
+    fn slot(&self, anomaly: Anomaly) -> &u64 {
+        match anomaly {
+            Anomaly::UnknownField => &self.unknown_fields,
+            Anomaly::DuplicateKey => &self.duplicate_keys,
+            Anomaly::KindNotSet => &self.kind_not_set,
+            Anomaly::DeepNesting => &self.deep_nesting,
+            Anomaly::NonMinimalVarint => &self.non_minimal_varints,
+        }
+    }
+
+    fn slot_mut(&mut self, anomaly: Anomaly) -> &mut u64 {
+        match anomaly {
+            Anomaly::UnknownField => &mut self.unknown_fields,
+            Anomaly::DuplicateKey => &mut self.duplicate_keys,
+            Anomaly::KindNotSet => &mut self.kind_not_set,
+            Anomaly::DeepNesting => &mut self.deep_nesting,
+            Anomaly::NonMinimalVarint => &mut self.non_minimal_varints,
+        }
+    }
+}
+
+/// The location of an anomaly.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnomalyExemplar {
+    pub anomaly: Anomaly,
+    /// The file of the record, set by [wire_anomalies].
+    pub path: Option<PathBuf>,
+    /// The index of the record, as given to [Example::decode_with_report].
+    pub record: u64,
+    /// The key of the feature map entry holding the anomaly, if any.
+    pub key: Option<String>,
+}
+
+/// The accumulator of wire-level anomalies.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecodeReport {
+    /// The number of decoded records.
+    pub num_records: u64,
+    pub counts: AnomalyCounts,
+    /// The first anomalies in the order found.
+    pub exemplars: Vec<AnomalyExemplar>,
+    /// The maximum number of exemplars kept.
+    pub max_exemplars: usize,
+}
+
+impl DecodeReport {
+    /// An empty report keeping up to `max_exemplars` exemplars.
+    pub fn new(max_exemplars: usize) -> Self {
+        Self {
+            num_records: 0,
+            counts: AnomalyCounts::default(),
+            exemplars: vec![],
+            max_exemplars,
+        }
+    }
+
+    /// Merge the report of later records into this one.
+    pub fn merge(&mut self, other: DecodeReport) {
+        self.num_records += other.num_records;
+        for anomaly in [
+            Anomaly::UnknownField,
+            Anomaly::DuplicateKey,
+            Anomaly::KindNotSet,
+            Anomaly::DeepNesting,
+            Anomaly::NonMinimalVarint,
+        ] {
+            *self.counts.slot_mut(anomaly) += other.counts.get(anomaly);
+        }
+        let room = self.max_exemplars.saturating_sub(self.exemplars.len());
+        self.exemplars
+            .extend(other.exemplars.into_iter().take(room));
+    }
+
+    fn add(&mut self, anomaly: Anomaly, record: u64, key: Option<&str>) {
+        *self.counts.slot_mut(anomaly) += 1;
+        if self.exemplars.len() < self.max_exemplars {
+            self.exemplars.push(AnomalyExemplar {
+                anomaly,
+                path: None,
+                record,
+                key: key.map(str::to_string),
+            });
+        }
+    }
+}
+
+impl Default for DecodeReport {
+    fn default() -> Self {
+        Self::new(16)
+    }
+}
+
+/// Options for [wire_anomalies].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AuditOptions {
+    /// The maximum number of exemplars kept across the dataset.
+    pub max_exemplars: usize,
+    /// The configuration of the readers, whose limits also apply to decoding.
+    pub reader: RecordReaderConfig,
+}
+
+impl Default for AuditOptions {
+    fn default() -> Self {
+        Self {
+            max_exemplars: 16,
+            reader: RecordReaderConfig::default(),
+        }
+    }
+}
+
+/// Decode the examples of the files, counting their wire-level anomalies.
+///
+/// The files are audited in parallel. Records failing to decode fail the audit with
+/// [Error::RecordFailed].
+pub fn wire_anomalies<'a, I, P>(paths: I, options: AuditOptions) -> Result<DecodeReport>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Cow<'a, Path>>,
+{
+    let AuditOptions {
+        max_exemplars,
+        reader,
+    } = options;
+    let paths: Vec<PathBuf> = paths
+        .into_iter()
+        .map(|path| path.into().into_owned())
+        .collect();
+
+    let num_workers = std::thread::available_parallelism()
+        .map(|num| num.get())
+        .unwrap_or(1)
+        .min(paths.len())
+        .max(1);
+    let chunk_size = paths.len().div_ceil(num_workers).max(1);
+
+    let reports: Vec<DecodeReport> = std::thread::scope(|scope| {
+        let workers: Vec<_> = paths
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let reader = &reader;
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|path| audit_file(path, max_exemplars, reader))
+                        .collect::<Result<Vec<_>>>()
+                })
+            })
+            .collect();
+        let mut reports = Vec::with_capacity(paths.len());
+        for worker in workers {
+            reports.extend(worker.join().expect("the audit thread panicked")?);
+        }
+        Ok::<_, Error>(reports)
+    })?;
+
+    // reduce in the order of files
+    let mut total = DecodeReport::new(max_exemplars);
+    for report in reports {
+        total.merge(report);
+    }
+    Ok(total)
+}
+
+fn audit_file(
+    path: &Path,
+    max_exemplars: usize,
+    reader: &RecordReaderConfig,
+) -> Result<DecodeReport> {
+    let file = File::open(path).map_err(|err| Error::from_io_with_context(err, path, None))?;
+    let records = BytesIter::from_reader(BufReader::new(file), reader.clone());
+    let mut report = DecodeReport::new(max_exemplars);
+    for (index, bytes) in records.enumerate() {
+        let index = index as u64;
+        bytes
+            .and_then(|bytes| {
+                Example::decode_with_report(&bytes, &reader.limits, &mut report, index)
+            })
+            .map_err(|err| Error::RecordFailed {
+                path: path.to_path_buf(),
+                index,
+                source: Box::new(err.with_io_context(path, None)),
+            })?;
+    }
+    for exemplar in &mut report.exemplars {
+        exemplar.path = Some(path.to_path_buf());
+    }
+    Ok(report)
+}
+
+/// Count the anomalies of a serialized example, which must decode successfully.
+pub(crate) fn scan_example(bytes: &[u8], record: u64, report: &mut DecodeReport) -> Result<()> {
+    let mut scanner = Scanner {
+        report,
+        record,
+        keys: HashSet::new(),
+    };
+    let mut found = vec![];
+    let mut buf = bytes;
+    while !buf.is_empty() {
+        let (tag, wire_type) = key(&mut buf, &mut found)?;
+        if (tag, wire_type) == (1, LEN) {
+            let body = length_delimited(&mut buf, &mut found)?;
+            scanner.features(body, &mut found)?;
+        } else {
+            found.push(Anomaly::UnknownField);
+            skip(&mut buf, tag, wire_type, 1, &mut found)?;
+        }
+    }
+    scanner.flush(&mut found, None);
+    Ok(())
+}
+
+const VARINT: u8 = 0;
+const FIXED64: u8 = 1;
+const LEN: u8 = 2;
+const START_GROUP: u8 = 3;
+const END_GROUP: u8 = 4;
+const FIXED32: u8 = 5;
+
+struct Scanner<'a> {
+    report: &'a mut DecodeReport,
+    record: u64,
+    /// The keys of the example seen so far.
+    keys: HashSet<String>,
+}
+
+impl Scanner<'_> {
+    fn flush(&mut self, found: &mut Vec<Anomaly>, key: Option<&str>) {
+        for anomaly in found.drain(..) {
+            self.report.add(anomaly, self.record, key);
+        }
+    }
+
+    /// Scan the `Features` message at depth 2.
+    fn features(&mut self, mut buf: &[u8], found: &mut Vec<Anomaly>) -> Result<()> {
+        while !buf.is_empty() {
+            let (tag, wire_type) = key(&mut buf, found)?;
+            if (tag, wire_type) == (1, LEN) {
+                let entry = length_delimited(&mut buf, found)?;
+                // anomalies of the entry are located at its key
+                let mut entry_found = vec![];
+                let key = entry_key(entry, &mut entry_found)?;
+                self.flush(found, None);
+                if !self.keys.insert(key.to_string()) {
+                    entry_found.push(Anomaly::DuplicateKey);
+                }
+                self.flush(&mut entry_found, Some(key));
+            } else {
+                found.push(Anomaly::UnknownField);
+                skip(&mut buf, tag, wire_type, 2, found)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Scan a map entry at depth 3, returning its key.
+fn entry_key<'a>(mut buf: &'a [u8], found: &mut Vec<Anomaly>) -> Result<&'a str> {
+    let mut key_str = "";
+    let mut has_value = false;
+    while !buf.is_empty() {
+        let (tag, wire_type) = key(&mut buf, found)?;
+        match (tag, wire_type) {
+            (1, LEN) => {
+                let bytes = length_delimited(&mut buf, found)?;
+                key_str = std::str::from_utf8(bytes)
+                    .map_err(|_| Error::conversion("the feature key is not UTF-8 encoded"))?;
+            }
+            (2, LEN) => {
+                let body = length_delimited(&mut buf, found)?;
+                feature(body, found)?;
+                has_value = true;
+            }
+            _ => {
+                found.push(Anomaly::UnknownField);
+                skip(&mut buf, tag, wire_type, 3, found)?;
+            }
+        }
+    }
+    if !has_value {
+        found.push(Anomaly::KindNotSet);
+    }
+    Ok(key_str)
+}
+
+/// Scan a `Feature` message at depth 4.
+fn feature(mut buf: &[u8], found: &mut Vec<Anomaly>) -> Result<()> {
+    let mut has_kind = false;
+    while !buf.is_empty() {
+        let (tag, wire_type) = key(&mut buf, found)?;
+        match (tag, wire_type) {
+            (1..=3, LEN) => {
+                let body = length_delimited(&mut buf, found)?;
+                value_list(body, tag, found)?;
+                has_kind = true;
+            }
+            _ => {
+                found.push(Anomaly::UnknownField);
+                skip(&mut buf, tag, wire_type, 4, found)?;
+            }
+        }
+    }
+    if !has_kind {
+        found.push(Anomaly::KindNotSet);
+    }
+    Ok(())
+}
+
+/// Scan a `BytesList`, `FloatList` or `Int64List` message at depth 5 by the tag of the
+/// kind.
+fn value_list(mut buf: &[u8], kind: u32, found: &mut Vec<Anomaly>) -> Result<()> {
+    while !buf.is_empty() {
+        let (tag, wire_type) = key(&mut buf, found)?;
+        match (kind, tag, wire_type) {
+            (1, 1, LEN) => {
+                length_delimited(&mut buf, found)?;
+            }
+            (2, 1, LEN) => {
+                length_delimited(&mut buf, found)?;
+            }
+            (2, 1, FIXED32) => {
+                take(&mut buf, 4)?;
+            }
+            (3, 1, LEN) => {
+                let mut packed = length_delimited(&mut buf, found)?;
+                while !packed.is_empty() {
+                    varint(&mut packed, found)?;
+                }
+            }
+            (3, 1, VARINT) => {
+                varint(&mut buf, found)?;
+            }
+            _ => {
+                found.push(Anomaly::UnknownField);
+                skip(&mut buf, tag, wire_type, 5, found)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Skip a field of a message at the depth.
+fn skip(
+    buf: &mut &[u8],
+    tag: u32,
+    wire_type: u8,
+    depth: usize,
+    found: &mut Vec<Anomaly>,
+) -> Result<()> {
+    let mut deep = false;
+    skip_nested(buf, tag, wire_type, depth, &mut deep, found)?;
+    if deep {
+        found.push(Anomaly::DeepNesting);
+    }
+    Ok(())
+}
+
+fn skip_nested(
+    buf: &mut &[u8],
+    tag: u32,
+    wire_type: u8,
+    depth: usize,
+    deep: &mut bool,
+    found: &mut Vec<Anomaly>,
+) -> Result<()> {
+    match wire_type {
+        VARINT => {
+            varint(buf, found)?;
+        }
+        FIXED64 => take(buf, 8)?,
+        LEN => {
+            length_delimited(buf, found)?;
+        }
+        FIXED32 => take(buf, 4)?,
+        START_GROUP => {
+            let depth = depth + 1;
+            *deep |= depth > DEEP_NESTING_DEPTH;
+            loop {
+                let (inner_tag, inner_type) = key(buf, found)?;
+                if inner_type == END_GROUP {
+                    if inner_tag != tag {
+                        return Err(Error::conversion("unexpected end group tag"));
+                    }
+                    break;
+                }
+                skip_nested(buf, inner_tag, inner_type, depth, deep, found)?;
+            }
+        }
+        _ => {
+            return Err(Error::conversion(format!(
+                "invalid wire type {}",
+                wire_type
+            )))
+        }
+    }
+    Ok(())
+}
+
+fn key(buf: &mut &[u8], found: &mut Vec<Anomaly>) -> Result<(u32, u8)> {
+    let key = varint(buf, found)?;
+    let tag = u32::try_from(key >> 3)
+        .ok()
+        .filter(|&tag| tag > 0)
+        .ok_or_else(|| Error::conversion(format!("invalid field key {}", key)))?;
+    Ok((tag, (key & 0b111) as u8))
+}
+
+fn length_delimited<'a>(buf: &mut &'a [u8], found: &mut Vec<Anomaly>) -> Result<&'a [u8]> {
+    let len = varint(buf, found)?;
+    let len = usize::try_from(len).map_err(|_| Error::UnexpectedEof)?;
+    let head = buf.get(..len).ok_or(Error::UnexpectedEof)?;
+    *buf = &buf[len..];
+    Ok(head)
+}
+
+fn take(buf: &mut &[u8], len: usize) -> Result<()> {
+    if buf.len() < len {
+        return Err(Error::UnexpectedEof);
+    }
+    *buf = &buf[len..];
+    Ok(())
+}
+
+/// Decode a varint, counting it if it ends with redundant zero groups.
+fn varint(buf: &mut &[u8], found: &mut Vec<Anomaly>) -> Result<u64> {
+    let mut value = 0u64;
+    for index in 0..10 {
+        let (&byte, rest) = buf.split_first().ok_or(Error::UnexpectedEof)?;
+        *buf = rest;
+        value |= u64::from(byte & 0x7f) << (7 * index);
+        if byte & 0x80 == 0 {
+            if index > 0 && byte == 0 {
+                found.push(Anomaly::NonMinimalVarint);
+            }
+            return Ok(value);
+        }
+    }
+    Err(Error::conversion("invalid varint"))
+}
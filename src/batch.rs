@@ -0,0 +1,525 @@
+//! Decoding of examples into columnar batches.
+//!
+//! A [BatchDecoder] declares the features to decode with their value types, and groups
+//! examples into [ColumnarBatch]es of a fixed number of rows. The values of a feature
+//! are accumulated into one contiguous buffer per batch rather than one list per
+//! example, which is the layout expected by array and tensor libraries.
+//!
+//! A [fixed-length](BatchDecoder::key) feature has the same number of values in every
+//! row, and a [variable-length](BatchDecoder::var_key) feature records the value range
+//! of each row in row splits, as `tf.RaggedTensor` does.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{batch::BatchDecoder, Example};
+//!
+//! let examples = (0..5).map(|index| {
+//!     let mut example = Example::empty();
+//!     example.push_f32s("age", &[20.0 + index as f32]);
+//!     example.push_i64s("movie_ids", &vec![index; index as usize]);
+//!     Ok(example)
+//! });
+//! let batches: Vec<_> = BatchDecoder::new()
+//!     .key::<f32>("age")
+//!     .var_key::<i64>("movie_ids")
+//!     .collect(examples, 2)?
+//!     .collect::<Result<_, _>>()?;
+//!
+//! // the last batch holds the remaining rows
+//! assert_eq!(batches.len(), 3);
+//! assert!(!batches[1].partial);
+//! assert!(batches[2].partial && batches[2].num_rows == 1);
+//!
+//! let movie_ids = batches[1].column("movie_ids").unwrap();
+//! assert_eq!(movie_ids.i64s(), Some(&[2, 2, 3, 3, 3][..]));
+//! assert_eq!(movie_ids.row(1), Some(2..5));
+//! assert_eq!(batches[1].column("age").unwrap().f32s(), Some(&[22.0, 23.0][..]));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::{ensure_argument, Error, Result},
+    protobuf::{feature::Kind, Example, Feature},
+};
+use std::{collections::HashSet, ops::Range};
+
+/// The value types of columns, which are the value types of `FloatList`, `Int64List`
+/// and `BytesList` features.
+pub trait ColumnValue {
+    /// Empty values of the type.
+    fn empty_values() -> ColumnValues;
+}
+
+impl ColumnValue for f32 {
+    fn empty_values() -> ColumnValues {
+        ColumnValues::F32(vec![])
+    }
+}
+
+impl ColumnValue for i64 {
+    fn empty_values() -> ColumnValues {
+        ColumnValues::I64(vec![])
+    }
+}
+
+impl ColumnValue for Vec<u8> {
+    fn empty_values() -> ColumnValues {
+        ColumnValues::Bytes(BytesValues::default())
+    }
+}
+
+/// The values of a column in row-major order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnValues {
+    F32(Vec<f32>),
+    I64(Vec<i64>),
+    Bytes(BytesValues),
+}
+
+impl ColumnValues {
+    /// Get the number of values.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::F32(values) => values.len(),
+            Self::I64(values) => values.len(),
+            Self::Bytes(values) => values.len(),
+        }
+    }
+
+    /// Returns true if there are no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn kind_name(&self) -> &'static str {
+        match self {
+            Self::F32(_) => "FloatList",
+            Self::I64(_) => "Int64List",
+            Self::Bytes(_) => "BytesList",
+        }
+    }
+
+    /// Append the values of a feature, returning the number of appended values, or
+    /// `None` if the feature is of another kind.
+    ///
+    /// A feature without a kind has no values.
+    fn extend_from(&mut self, feature: &Feature) -> Option<usize> {
+        let len = match (self, &feature.kind) {
+            (_, None) => 0,
+            (Self::F32(values), Some(Kind::FloatList(list))) => {
+                values.extend_from_slice(&list.value);
+                list.value.len()
+            }
+            (Self::I64(values), Some(Kind::Int64List(list))) => {
+                values.extend_from_slice(&list.value);
+                list.value.len()
+            }
+            (Self::Bytes(values), Some(Kind::BytesList(list))) => {
+                list.value.iter().for_each(|bytes| values.push(bytes));
+                list.value.len()
+            }
+            _ => return None,
+        };
+        Some(len)
+    }
+
+    /// Take the values, leaving empty values of the same type with room for as many
+    /// values.
+    fn take(&mut self) -> Self {
+        let empty = match self {
+            Self::F32(values) => Self::F32(Vec::with_capacity(values.len())),
+            Self::I64(values) => Self::I64(Vec::with_capacity(values.len())),
+            Self::Bytes(values) => {
+                let mut splits = Vec::with_capacity(values.splits.len());
+                splits.push(0);
+                Self::Bytes(BytesValues {
+                    data: Vec::with_capacity(values.data.len()),
+                    splits,
+                })
+            }
+        };
+        std::mem::replace(self, empty)
+    }
+}
+
+/// Byte strings concatenated in one buffer.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BytesValues {
+    data: Vec<u8>,
+    splits: Vec<usize>,
+}
+
+impl Default for BytesValues {
+    fn default() -> Self {
+        Self {
+            data: vec![],
+            splits: vec![0],
+        }
+    }
+}
+
+impl BytesValues {
+    /// Get the number of byte strings.
+    pub fn len(&self) -> usize {
+        self.splits.len() - 1
+    }
+
+    /// Returns true if there are no byte strings.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get a byte string by index.
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        let end = *self.splits.get(index + 1)?;
+        Some(&self.data[self.splits[index]..end])
+    }
+
+    /// Iterate over the byte strings.
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        self.splits
+            .windows(2)
+            .map(|split| &self.data[split[0]..split[1]])
+    }
+
+    /// Get the concatenated bytes.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Get the offsets of byte strings in the [data](Self::data), starting with 0 and
+    /// ending with the data length.
+    pub fn splits(&self) -> &[usize] {
+        &self.splits
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+        self.splits.push(self.data.len());
+    }
+}
+
+/// The partition of the values of a column into rows.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RowLayout {
+    /// Every row has the same number of values.
+    Fixed { row_len: usize },
+    /// The row `i` has the values in `row_splits[i]..row_splits[i + 1]`.
+    Variable { row_splits: Vec<usize> },
+}
+
+/// The values of a feature over the rows of a batch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub name: String,
+    pub num_rows: usize,
+    pub values: ColumnValues,
+    pub layout: RowLayout,
+}
+
+impl Column {
+    /// Get the range of the values of a row.
+    pub fn row(&self, index: usize) -> Option<Range<usize>> {
+        if index >= self.num_rows {
+            return None;
+        }
+        let range = match &self.layout {
+            RowLayout::Fixed { row_len } => index * row_len..(index + 1) * row_len,
+            RowLayout::Variable { row_splits } => row_splits[index]..row_splits[index + 1],
+        };
+        Some(range)
+    }
+
+    /// Get the values of a `FloatList` column.
+    pub fn f32s(&self) -> Option<&[f32]> {
+        match &self.values {
+            ColumnValues::F32(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Get the values of an `Int64List` column.
+    pub fn i64s(&self) -> Option<&[i64]> {
+        match &self.values {
+            ColumnValues::I64(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Get the values of a `BytesList` column.
+    pub fn bytes(&self) -> Option<&BytesValues> {
+        match &self.values {
+            ColumnValues::Bytes(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+/// A batch of examples in columns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnarBatch {
+    /// The number of examples.
+    pub num_rows: usize,
+    /// True if the batch has fewer rows than the batch size, which only happens to the
+    /// last batch.
+    pub partial: bool,
+    /// The columns in the order of declaration.
+    pub columns: Vec<Column>,
+}
+
+impl ColumnarBatch {
+    /// Get a column by feature name.
+    pub fn column(&self, name: &str) -> Option<&Column> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+}
+
+/// The builder of [ColumnarBatch]es.
+#[derive(Debug, Clone, Default)]
+pub struct BatchDecoder {
+    columns: Vec<ColumnSpec>,
+}
+
+#[derive(Debug, Clone)]
+struct ColumnSpec {
+    name: String,
+    values: ColumnValues,
+    variable: bool,
+}
+
+impl BatchDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode a feature with the same number of values in every example.
+    ///
+    /// The number of values is set by the first example. A missing feature is an error.
+    pub fn key<T>(mut self, name: impl Into<String>) -> Self
+    where
+        T: ColumnValue,
+    {
+        self.columns.push(ColumnSpec {
+            name: name.into(),
+            values: T::empty_values(),
+            variable: false,
+        });
+        self
+    }
+
+    /// Decode a feature with any number of values in each example.
+    ///
+    /// A missing feature has no values.
+    pub fn var_key<T>(mut self, name: impl Into<String>) -> Self
+    where
+        T: ColumnValue,
+    {
+        self.columns.push(ColumnSpec {
+            name: name.into(),
+            values: T::empty_values(),
+            variable: true,
+        });
+        self
+    }
+
+    /// Group examples into batches of `batch_size` rows.
+    ///
+    /// A feature of another kind than the declared value type, or a fixed-length
+    /// feature with another number of values, is an error. The first error, from the
+    /// examples or from decoding, ends the batches.
+    pub fn collect<I>(
+        self,
+        examples: I,
+        batch_size: usize,
+    ) -> Result<impl Iterator<Item = Result<ColumnarBatch>>>
+    where
+        I: IntoIterator<Item = Result<Example>>,
+    {
+        let builder = BatchBuilder::new(self, batch_size)?;
+        let mut state = Some((examples.into_iter(), builder));
+
+        Ok(std::iter::from_fn(move || {
+            let (examples, builder) = state.as_mut()?;
+            let result = loop {
+                let example = match examples.next() {
+                    Some(example) => example,
+                    None => break builder.finish().map(Ok),
+                };
+                if let Err(err) = example.and_then(|example| builder.push(&example)) {
+                    break Some(Err(err));
+                }
+                if builder.is_full() {
+                    return builder.finish().map(Ok);
+                }
+            };
+            state = None;
+            result
+        }))
+    }
+
+    /// Group a stream of examples into batches of `batch_size` rows.
+    ///
+    /// It works as [collect](Self::collect) does.
+    #[cfg(feature = "async")]
+    pub fn collect_stream<S>(
+        self,
+        examples: S,
+        batch_size: usize,
+    ) -> Result<impl futures::stream::Stream<Item = Result<ColumnarBatch>>>
+    where
+        S: futures::stream::Stream<Item = Result<Example>>,
+    {
+        use futures::stream::{self, StreamExt as _};
+
+        let builder = BatchBuilder::new(self, batch_size)?;
+        let init = Some((Box::pin(examples), builder));
+
+        Ok(stream::unfold(init, |state| async move {
+            let (mut examples, mut builder) = state?;
+            while let Some(example) = examples.next().await {
+                if let Err(err) = example.and_then(|example| builder.push(&example)) {
+                    return Some((Err(err), None));
+                }
+                if builder.is_full() {
+                    let batch = builder.finish()?;
+                    return Some((Ok(batch), Some((examples, builder))));
+                }
+            }
+            builder.finish().map(|batch| (Ok(batch), None))
+        }))
+    }
+}
+
+/// The accumulated columns of the batch in progress.
+#[derive(Debug)]
+struct BatchBuilder {
+    batch_size: usize,
+    num_rows: usize,
+    /// The number of rows of the finished batches.
+    row_offset: usize,
+    columns: Vec<ColumnBuilder>,
+}
+
+#[derive(Debug)]
+struct ColumnBuilder {
+    spec: ColumnSpec,
+    row_splits: Vec<usize>,
+    /// The number of values of a fixed-length feature, set by the first example.
+    row_len: Option<usize>,
+}
+
+impl BatchBuilder {
+    fn new(decoder: BatchDecoder, batch_size: usize) -> Result<Self> {
+        ensure_argument!(batch_size > 0, "the batch size must be positive");
+        let mut names = HashSet::new();
+        for spec in &decoder.columns {
+            ensure_argument!(
+                names.insert(spec.name.as_str()),
+                "the feature '{}' is declared more than once",
+                spec.name
+            );
+        }
+
+        let columns = decoder
+            .columns
+            .into_iter()
+            .map(|spec| ColumnBuilder {
+                spec,
+                row_splits: vec![0],
+                row_len: None,
+            })
+            .collect();
+        Ok(Self {
+            batch_size,
+            num_rows: 0,
+            row_offset: 0,
+            columns,
+        })
+    }
+
+    fn is_full(&self) -> bool {
+        self.num_rows == self.batch_size
+    }
+
+    fn push(&mut self, example: &Example) -> Result<()> {
+        let row = self.row_offset + self.num_rows;
+        let features = example.features.as_ref().map(|features| &features.feature);
+        for column in &mut self.columns {
+            let ColumnSpec {
+                name,
+                values,
+                variable,
+            } = &mut column.spec;
+            let len = match features.and_then(|features| features.get(name.as_str())) {
+                Some(feature) => values.extend_from(feature).ok_or_else(|| {
+                    Error::conversion(format!(
+                        "row {}: the feature '{}' is not a {}",
+                        row,
+                        name,
+                        values.kind_name()
+                    ))
+                })?,
+                None if *variable => 0,
+                None => {
+                    return Err(Error::conversion(format!(
+                        "row {}: the feature '{}' is missing",
+                        row, name
+                    )));
+                }
+            };
+
+            if *variable {
+                column.row_splits.push(values.len());
+            } else {
+                let expect = *column.row_len.get_or_insert(len);
+                if len != expect {
+                    return Err(Error::conversion(format!(
+                        "row {}: the feature '{}' expects {} values, but get {}",
+                        row, name, expect, len
+                    )));
+                }
+            }
+        }
+        self.num_rows += 1;
+        Ok(())
+    }
+
+    /// Take the batch in progress, or `None` if it has no rows.
+    fn finish(&mut self) -> Option<ColumnarBatch> {
+        if self.num_rows == 0 {
+            return None;
+        }
+        let num_rows = self.num_rows;
+        let columns = self
+            .columns
+            .iter_mut()
+            .map(|column| {
+                let layout = if column.spec.variable {
+                    let row_splits =
+                        std::mem::replace(&mut column.row_splits, Vec::with_capacity(num_rows + 1));
+                    column.row_splits.push(0);
+                    RowLayout::Variable { row_splits }
+                } else {
+                    RowLayout::Fixed {
+                        row_len: column.row_len.unwrap_or(0),
+                    }
+                };
+                Column {
+                    name: column.spec.name.clone(),
+                    num_rows,
+                    values: column.spec.values.take(),
+                    layout,
+                }
+            })
+            .collect();
+
+        self.row_offset += num_rows;
+        self.num_rows = 0;
+        Some(ColumnarBatch {
+            num_rows,
+            partial: num_rows < self.batch_size,
+            columns,
+        })
+    }
+}
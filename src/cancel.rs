@@ -0,0 +1,135 @@
+//! Cooperative cancellation of long-running operations.
+//!
+//! Long-running operations accept an optional [CancelFlag], such as
+//! [RecordIndexerConfig::cancel](crate::indexer::RecordIndexerConfig::cancel). A flag
+//! is shared by cloning, and [cancel](CancelFlag::cancel) can be called from any thread.
+//! Operations check the flag before each file and every [CHECK_INTERVAL] records, and
+//! return [Error::Cancelled] with a [Progress] snapshot once cancelled.
+//!
+//! Operations writing files remove their partial outputs on cancellation, and list them
+//! in [partial_outputs](Progress::partial_outputs). Outputs which fail to be removed are
+//! reported with [cleaned_up](Progress::cleaned_up) unset.
+
+use crate::error::{Error, Result};
+use std::{
+    hash::{Hash, Hasher},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// The number of records processed between checks of the flag.
+pub const CHECK_INTERVAL: u64 = 1024;
+
+/// The shared flag to cancel operations.
+///
+/// Clones share the same flag. Flags are compared by identity.
+#[derive(Debug, Clone, Default)]
+pub struct CancelFlag(Arc<AtomicBool>);
+
+impl CancelFlag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request the cancellation of operations observing the flag.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    /// Returns true if the cancellation is requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+
+    /// Return [Error::Cancelled] with the progress if the cancellation is requested.
+    pub fn check<F>(&self, progress: F) -> Result<()>
+    where
+        F: FnOnce() -> Progress,
+    {
+        if self.is_cancelled() {
+            return Err(Error::Cancelled {
+                progress: progress(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for CancelFlag {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CancelFlag {}
+
+impl Hash for CancelFlag {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).hash(state);
+    }
+}
+
+/// How far a cancelled operation got.
+///
+/// The counts of operations making several passes over the inputs accumulate over
+/// passes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Progress {
+    /// The number of completely processed files.
+    pub files_done: u64,
+    /// The number of processed records.
+    pub records_processed: u64,
+    /// The output files written before the cancellation.
+    pub partial_outputs: Vec<PathBuf>,
+    /// True if all partial outputs are removed.
+    pub cleaned_up: bool,
+}
+
+impl Progress {
+    pub(crate) fn new(files_done: u64, records_processed: u64) -> Self {
+        Self {
+            files_done,
+            records_processed,
+            partial_outputs: vec![],
+            cleaned_up: true,
+        }
+    }
+}
+
+/// Check the optional flag.
+pub(crate) fn check(cancel: Option<&CancelFlag>, files_done: u64, records: u64) -> Result<()> {
+    match cancel {
+        Some(cancel) => cancel.check(|| Progress::new(files_done, records)),
+        None => Ok(()),
+    }
+}
+
+/// Check the optional flag if the number of records reaches a multiple of
+/// [CHECK_INTERVAL].
+pub(crate) fn check_periodically(
+    cancel: Option<&CancelFlag>,
+    files_done: u64,
+    records: u64,
+) -> Result<()> {
+    if records.is_multiple_of(CHECK_INTERVAL) {
+        check(cancel, files_done, records)?;
+    }
+    Ok(())
+}
+
+impl Error {
+    /// Add the progress of preceding files to a cancellation error.
+    pub(crate) fn after_progress(self, files_done: u64, records: u64) -> Self {
+        match self {
+            Self::Cancelled { mut progress } => {
+                progress.files_done += files_done;
+                progress.records_processed += records;
+                Self::Cancelled { progress }
+            }
+            error => error,
+        }
+    }
+}
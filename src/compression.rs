@@ -0,0 +1,526 @@
+//! Compressed record files.
+//!
+//! TensorFlow compresses a record file as a whole when written with
+//! `compression_type="GZIP"`, so the framing of records is only visible after
+//! decompression. Setting [compression](crate::RecordReaderConfig::compression) to
+//! [Compression::Gzip] decompresses the stream before reading records, which requires
+//! the `gzip` cargo feature. Concatenated GZIP members are read as one stream.
+//!
+//! A compressed file has no record offsets to seek to. The
+//! [indexer](crate::indexer) with [compression](crate::indexer::RecordIndexerConfig::compression)
+//! set indexes GZIP-compressed files by the offsets of records in the decompressed
+//! stream, so [RecordIndex::load](crate::indexer::RecordIndex::load) decompresses the
+//! file from the start up to the record. Read such datasets sequentially, such as by
+//! [iter_from](crate::indexer::iter_from), which decompresses each file once. Byte
+//! ranges of [SequentialRangeReader](crate::SequentialRangeReader) do not support
+//! compressed files, and the async indexer rejects the compression setting.
+//!
+//! Writers built by [RecordWriter::create_gzip](crate::RecordWriter::create_gzip) and
+//! [RecordAsyncWriter::create_gzip](crate::RecordAsyncWriter::create_gzip) compress the
+//! framed records as TensorFlow does, so the output is readable by
+//! `tf.data.TFRecordDataset(..., compression_type="GZIP")`. The stream ends when the
+//! writer is finished.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! # #[cfg(feature = "gzip")]
+//! # {
+//! use std::io::Write as _;
+//! use tfrecord::{compression::Compression, BytesIter, BytesWriter, RecordReaderConfig};
+//!
+//! let (mut writer, buffer) = BytesWriter::in_memory()?;
+//! writer.send(b"record".to_vec())?;
+//! writer.flush()?;
+//!
+//! let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+//! encoder.write_all(&buffer.to_vec())?;
+//! let compressed = encoder.finish()?;
+//!
+//! let config = RecordReaderConfig {
+//!     compression: Compression::Gzip,
+//!     ..Default::default()
+//! };
+//! let records: Vec<_> = BytesIter::from_bytes(compressed, config).collect::<Result<_, _>>()?;
+//! assert_eq!(records, vec![b"record".to_vec()]);
+//! # }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{Error, Result};
+use std::io::{self, prelude::*, BufReader, SeekFrom};
+
+/// The compression of a record stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Compression {
+    /// Plain records.
+    #[default]
+    None,
+    /// A GZIP-compressed stream, as written by TensorFlow with `compression_type="GZIP"`.
+    Gzip,
+}
+
+impl Compression {
+    /// Fail with [Error::Unsupported] if the compression is not compiled in.
+    pub(crate) fn check_supported(self) -> Result<()> {
+        match self {
+            Self::None => Ok(()),
+            Self::Gzip if cfg!(feature = "gzip") => Ok(()),
+            Self::Gzip => Err(Error::Unsupported {
+                capability: "GZIP compression".into(),
+                feature: Some("gzip"),
+            }),
+        }
+    }
+}
+
+/// The GZIP compression of the level from 0, storing uncompressed, to 9, compressing
+/// best.
+#[cfg(feature = "gzip")]
+pub(crate) fn gzip_level(level: u32) -> Result<flate2::Compression> {
+    crate::error::ensure_argument!(
+        level <= 9,
+        "the GZIP compression level {} is not within 0 to 9",
+        level
+    );
+    Ok(flate2::Compression::new(level))
+}
+
+/// A reader decompressing the inner reader as configured.
+pub(crate) enum Decoded<R>
+where
+    R: Read,
+{
+    Plain(R),
+    #[cfg(feature = "gzip")]
+    Gzip(Box<GzipReader<R>>),
+}
+
+impl<R> Decoded<R>
+where
+    R: Read,
+{
+    /// Wrap the reader. The compression must be [supported](Compression::check_supported),
+    /// or the reader is read as plain.
+    pub(crate) fn new(reader: R, compression: Compression) -> Self {
+        match compression {
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => Self::Gzip(Box::new(GzipReader::new(reader))),
+            _ => Self::Plain(reader),
+        }
+    }
+}
+
+impl<R> Decoded<BufReader<R>>
+where
+    R: Read + Seek,
+{
+    /// Seek relative to the current position, keeping the buffer of plain readers.
+    pub(crate) fn seek_relative(&mut self, offset: i64) -> io::Result<()> {
+        match self {
+            Self::Plain(reader) => reader.seek_relative(offset),
+            #[cfg(feature = "gzip")]
+            Self::Gzip(reader) => reader.seek(SeekFrom::Current(offset)).map(|_| ()),
+        }
+    }
+}
+
+impl<R> Read for Decoded<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(reader) => reader.read(buf),
+            #[cfg(feature = "gzip")]
+            Self::Gzip(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl<R> Seek for Decoded<R>
+where
+    R: Read + Seek,
+{
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self {
+            Self::Plain(reader) => reader.seek(pos),
+            #[cfg(feature = "gzip")]
+            Self::Gzip(reader) => reader.seek(pos),
+        }
+    }
+}
+
+/// A GZIP decoder seeking in the decompressed stream.
+///
+/// Seeking forward decompresses and discards the bytes in between, and seeking backward
+/// decompresses again from the start. Seeking from the end decompresses the whole
+/// stream once to find its length.
+#[cfg(feature = "gzip")]
+pub(crate) struct GzipReader<R>
+where
+    R: Read,
+{
+    /// It is only taken while rewinding.
+    decoder: Option<BufReader<flate2::read::MultiGzDecoder<R>>>,
+    /// The position in the decompressed stream.
+    pos: u64,
+    /// The length of the decompressed stream, once known.
+    len: Option<u64>,
+}
+
+#[cfg(feature = "gzip")]
+impl<R> GzipReader<R>
+where
+    R: Read,
+{
+    fn new(reader: R) -> Self {
+        Self {
+            decoder: Some(BufReader::new(flate2::read::MultiGzDecoder::new(reader))),
+            pos: 0,
+            len: None,
+        }
+    }
+
+    fn decoder(&mut self) -> &mut BufReader<flate2::read::MultiGzDecoder<R>> {
+        self.decoder.as_mut().unwrap()
+    }
+
+    /// Decompress and discard bytes, failing if the stream ends before.
+    fn skip(&mut self, n: u64) -> io::Result<()> {
+        let skipped = io::copy(&mut self.by_ref().take(n), &mut io::sink())?;
+        if skipped < n {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl<R> GzipReader<R>
+where
+    R: Read + Seek,
+{
+    fn rewind_stream(&mut self) -> io::Result<()> {
+        let mut inner = self.decoder.take().unwrap().into_inner().into_inner();
+        let result = inner.seek(SeekFrom::Start(0));
+        *self = Self {
+            len: self.len,
+            ..Self::new(inner)
+        };
+        result.map(|_| ())
+    }
+
+    fn stream_len(&mut self) -> io::Result<u64> {
+        if let Some(len) = self.len {
+            return Ok(len);
+        }
+        let pos = self.pos;
+        io::copy(self, &mut io::sink())?;
+        let len = self.pos;
+        self.len = Some(len);
+        self.seek(SeekFrom::Start(pos))?;
+        Ok(len)
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl<R> Read for GzipReader<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.decoder().read(buf)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl<R> Seek for GzipReader<R>
+where
+    R: Read + Seek,
+{
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+            SeekFrom::End(offset) => self.stream_len()?.checked_add_signed(offset),
+        };
+        let target = target.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek to a negative or overflowing position",
+            )
+        })?;
+        if target < self.pos {
+            self.rewind_stream()?;
+        }
+        self.skip(target - self.pos)?;
+        Ok(self.pos)
+    }
+}
+
+/// An async reader decompressing the inner reader as configured.
+#[cfg(feature = "async")]
+pub(crate) use self::r#async::DecodedAsync;
+#[cfg(all(feature = "async", feature = "gzip"))]
+pub use self::r#async::GzipAsyncWriter;
+
+#[cfg(feature = "async")]
+mod r#async {
+    use super::Compression;
+    use futures::io::AsyncRead;
+    #[cfg(feature = "gzip")]
+    use futures::io::AsyncWrite;
+    use pin_project::pin_project;
+    use std::{
+        io,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    #[pin_project(project = DecodedAsyncProj)]
+    pub(crate) enum DecodedAsync<R>
+    where
+        R: AsyncRead,
+    {
+        Plain(#[pin] R),
+        #[cfg(feature = "gzip")]
+        Gzip(Pin<Box<GzipAsyncReader<R>>>),
+    }
+
+    impl<R> DecodedAsync<R>
+    where
+        R: AsyncRead,
+    {
+        /// Wrap the reader. The compression must be
+        /// [supported](Compression::check_supported), or the reader is read as plain.
+        pub(crate) fn new(reader: R, compression: Compression) -> Self {
+            match compression {
+                #[cfg(feature = "gzip")]
+                Compression::Gzip => Self::Gzip(Box::pin(GzipAsyncReader::new(reader))),
+                _ => Self::Plain(reader),
+            }
+        }
+    }
+
+    impl<R> AsyncRead for DecodedAsync<R>
+    where
+        R: AsyncRead,
+    {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            match self.project() {
+                DecodedAsyncProj::Plain(reader) => reader.poll_read(cx, buf),
+                #[cfg(feature = "gzip")]
+                DecodedAsyncProj::Gzip(reader) => reader.as_mut().poll_read(cx, buf),
+            }
+        }
+    }
+
+    /// A GZIP decoder fed by chunks of the inner reader.
+    #[cfg(feature = "gzip")]
+    #[pin_project]
+    pub(crate) struct GzipAsyncReader<R>
+    where
+        R: AsyncRead,
+    {
+        #[pin]
+        inner: R,
+        /// The decoder writing decompressed bytes to its buffer.
+        decoder: flate2::write::MultiGzDecoder<Vec<u8>>,
+        /// The number of decompressed bytes consumed from the buffer.
+        consumed: usize,
+        chunk: Vec<u8>,
+        eof: bool,
+    }
+
+    #[cfg(feature = "gzip")]
+    impl<R> GzipAsyncReader<R>
+    where
+        R: AsyncRead,
+    {
+        const CHUNK_LEN: usize = 8192;
+
+        fn new(inner: R) -> Self {
+            Self {
+                inner,
+                decoder: flate2::write::MultiGzDecoder::new(vec![]),
+                consumed: 0,
+                chunk: vec![0; Self::CHUNK_LEN],
+                eof: false,
+            }
+        }
+    }
+
+    #[cfg(feature = "gzip")]
+    impl<R> AsyncRead for GzipAsyncReader<R>
+    where
+        R: AsyncRead,
+    {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            use std::io::Write as _;
+
+            let mut this = self.project();
+            loop {
+                let decompressed = this.decoder.get_ref();
+                if *this.consumed < decompressed.len() {
+                    let available = &decompressed[*this.consumed..];
+                    let n = available.len().min(buf.len());
+                    buf[..n].copy_from_slice(&available[..n]);
+                    *this.consumed += n;
+                    return Poll::Ready(Ok(n));
+                }
+                this.decoder.get_mut().clear();
+                *this.consumed = 0;
+                if *this.eof {
+                    return Poll::Ready(Ok(0));
+                }
+
+                let n = match this.inner.as_mut().poll_read(cx, this.chunk) {
+                    Poll::Ready(Ok(n)) => n,
+                    Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                    Poll::Pending => return Poll::Pending,
+                };
+                if n == 0 {
+                    *this.eof = true;
+                    this.decoder.try_finish()?;
+                } else {
+                    this.decoder.write_all(&this.chunk[..n])?;
+                }
+            }
+        }
+    }
+
+    /// A GZIP encoder writing to an async writer.
+    ///
+    /// Records are compressed into a buffer, which is written to the inner writer before
+    /// more bytes are accepted. Flushing ends the current deflate block, and closing
+    /// writes the end of the stream.
+    #[cfg(feature = "gzip")]
+    #[pin_project]
+    pub struct GzipAsyncWriter<W>
+    where
+        W: AsyncWrite,
+    {
+        #[pin]
+        inner: W,
+        /// The encoder writing compressed bytes to its buffer.
+        encoder: flate2::write::GzEncoder<Vec<u8>>,
+        /// The number of compressed bytes written from the buffer.
+        written: usize,
+        /// Set when the encoder is flushed and the inner writer is not yet flushed.
+        flushing: bool,
+        finished: bool,
+    }
+
+    #[cfg(feature = "gzip")]
+    impl<W> GzipAsyncWriter<W>
+    where
+        W: AsyncWrite,
+    {
+        pub(crate) fn new(inner: W, level: flate2::Compression) -> Self {
+            Self {
+                inner,
+                encoder: flate2::write::GzEncoder::new(vec![], level),
+                written: 0,
+                flushing: false,
+                finished: false,
+            }
+        }
+
+        /// Write the buffered compressed bytes to the inner writer.
+        fn poll_drain(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            let mut this = self.project();
+            loop {
+                let compressed = this.encoder.get_ref();
+                if *this.written == compressed.len() {
+                    this.encoder.get_mut().clear();
+                    *this.written = 0;
+                    return Poll::Ready(Ok(()));
+                }
+                let n = match this
+                    .inner
+                    .as_mut()
+                    .poll_write(cx, &compressed[*this.written..])
+                {
+                    Poll::Ready(Ok(0)) => return Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+                    Poll::Ready(Ok(n)) => n,
+                    Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                    Poll::Pending => return Poll::Pending,
+                };
+                *this.written += n;
+            }
+        }
+    }
+
+    #[cfg(feature = "gzip")]
+    impl<W> AsyncWrite for GzipAsyncWriter<W>
+    where
+        W: AsyncWrite,
+    {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            use std::io::Write as _;
+
+            if self.finished {
+                return Poll::Ready(Err(io::Error::other(
+                    "write after the GZIP stream is closed",
+                )));
+            }
+            match self.as_mut().poll_drain(cx) {
+                Poll::Ready(Ok(())) => {}
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            }
+            let this = self.project();
+            // the output of an interrupted flush is drained, so the next flush flushes again
+            *this.flushing = false;
+            Poll::Ready(this.encoder.write(buf))
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            use std::io::Write as _;
+
+            if !self.flushing {
+                let this = self.as_mut().project();
+                this.encoder.flush()?;
+                *this.flushing = true;
+            }
+            match self.as_mut().poll_drain(cx) {
+                Poll::Ready(Ok(())) => {}
+                other => return other,
+            }
+            let this = self.project();
+            let poll = this.inner.poll_flush(cx);
+            if poll.is_ready() {
+                *this.flushing = false;
+            }
+            poll
+        }
+
+        fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            if !self.finished {
+                let this = self.as_mut().project();
+                this.encoder.try_finish()?;
+                *this.finished = true;
+            }
+            match self.as_mut().poll_drain(cx) {
+                Poll::Ready(Ok(())) => {}
+                other => return other,
+            }
+            self.project().inner.poll_close(cx)
+        }
+    }
+}
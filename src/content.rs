@@ -0,0 +1,346 @@
+//! Classify the contents of record files.
+//!
+//! Run directories often mix data shards with events files. Protobuf decoding is
+//! permissive, so an events file loaded as examples may decode into nonsense features
+//! instead of failing. [content_kind] tells them apart by sampling at most
+//! [SAMPLE_RECORDS] records from the start of a file.
+//!
+//! - A file whose first record is an event with a wall time or a file version, as
+//!   every event written by TensorFlow is, is an [events file](ContentKind::EventsFile).
+//! - A file whose sampled records are all strictly valid examples is
+//!   [example data](ContentKind::ExampleData). Strict validation rejects unknown fields
+//!   and mismatched wire types, which the permissive decoder would skip.
+//! - A file whose sampled records are all strictly valid sequence examples is
+//!   [sequence data](ContentKind::SequenceData). An example is also a valid sequence
+//!   example with only the context, so the kind is chosen only if some record has
+//!   feature lists.
+//! - Other files, including empty files and files of
+//!   [unsupported formats](crate::format), are [unknown](ContentKind::Unknown).
+//!
+//! The indexer checks the kinds of files against
+//! [expect_kind](crate::indexer::RecordIndexerConfig::expect_kind).
+
+use crate::{
+    error::{Error, Result},
+    format::detect_format,
+};
+use prost::{
+    bytes::Buf as _,
+    encoding::{self, WireType},
+};
+use std::{
+    fmt,
+    fs::File,
+    io::{prelude::*, BufReader, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+/// The maximum number of records read to classify a file.
+pub const SAMPLE_RECORDS: usize = 4;
+
+/// The kind of records in a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContentKind {
+    /// Events written by a summary writer.
+    EventsFile,
+    /// Serialized `Example`s.
+    ExampleData,
+    /// Serialized `SequenceExample`s.
+    SequenceData,
+    /// The kind is not recognized.
+    Unknown,
+}
+
+impl fmt::Display for ContentKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Self::EventsFile => "events file",
+            Self::ExampleData => "example data",
+            Self::SequenceData => "sequence example data",
+            Self::Unknown => "unknown content",
+        };
+        f.write_str(text)
+    }
+}
+
+/// A file whose detected kind differs from the expected kind.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KindMismatch {
+    pub expected: ContentKind,
+    pub found: ContentKind,
+}
+
+impl KindMismatch {
+    pub(crate) fn into_error(self, path: PathBuf) -> Error {
+        let Self { expected, found } = self;
+        Error::ContentKindMismatch {
+            path,
+            expected,
+            found,
+        }
+    }
+}
+
+/// Detect the kind of records in a file.
+///
+/// Empty files and files truncated before the first complete record are of the
+/// [Unknown](ContentKind::Unknown) kind.
+pub fn content_kind<P>(path: P) -> Result<ContentKind>
+where
+    P: AsRef<Path>,
+{
+    content_kind_of_reader(BufReader::new(File::open(path)?))
+}
+
+/// Detect the kind of records in a stream.
+///
+/// The stream is rewound to the start afterwards.
+pub fn content_kind_of_reader<R>(mut reader: R) -> Result<ContentKind>
+where
+    R: Read + Seek,
+{
+    if detect_format(&mut reader)?.unsupported().is_some() {
+        return Ok(ContentKind::Unknown);
+    }
+
+    let mut records = vec![];
+    while records.len() < SAMPLE_RECORDS {
+        match crate::io::sync::try_read_record(&mut reader, true) {
+            Ok(Some(record)) => records.push(record),
+            // a truncated file is classified by its complete records
+            Ok(None) | Err(Error::UnexpectedEof) => break,
+            Err(err) => return Err(err),
+        }
+    }
+    reader.seek(SeekFrom::Start(0))?;
+    Ok(classify_records(&records))
+}
+
+/// Detect the kind of records in a stream asynchronously.
+///
+/// The stream is rewound to the start afterwards.
+#[cfg(feature = "async")]
+pub async fn content_kind_of_reader_async<R>(mut reader: R) -> Result<ContentKind>
+where
+    R: futures::io::AsyncRead + futures::io::AsyncSeek + Unpin,
+{
+    use futures::io::AsyncSeekExt as _;
+
+    if crate::format::detect_format_async(&mut reader)
+        .await?
+        .unsupported()
+        .is_some()
+    {
+        return Ok(ContentKind::Unknown);
+    }
+
+    let mut records = vec![];
+    while records.len() < SAMPLE_RECORDS {
+        match crate::io::r#async::try_read_record(&mut reader, true).await {
+            Ok(Some(record)) => records.push(record),
+            // a truncated file is classified by its complete records
+            Ok(None) | Err(Error::UnexpectedEof) => break,
+            Err(err) => return Err(err),
+        }
+    }
+    reader.seek(SeekFrom::Start(0)).await?;
+    Ok(classify_records(&records))
+}
+
+/// Classify the leading records of a file.
+pub fn classify_records<B>(records: &[B]) -> ContentKind
+where
+    B: AsRef<[u8]>,
+{
+    let first = match records.first() {
+        Some(first) => first.as_ref(),
+        None => return ContentKind::Unknown,
+    };
+
+    if is_event(first) {
+        ContentKind::EventsFile
+    } else if records.iter().all(|record| is_example(record.as_ref())) {
+        ContentKind::ExampleData
+    } else if records
+        .iter()
+        .all(|record| is_sequence_example(record.as_ref()))
+    {
+        ContentKind::SequenceData
+    } else {
+        ContentKind::Unknown
+    }
+}
+
+/// Check the kind of a file against the expected kind.
+pub(crate) fn check_kind<R>(
+    reader: R,
+    expected: Option<ContentKind>,
+) -> Result<Option<KindMismatch>>
+where
+    R: Read + Seek,
+{
+    let expected = match expected {
+        Some(expected) => expected,
+        None => return Ok(None),
+    };
+    let found = content_kind_of_reader(reader)?;
+    Ok((found != expected).then_some(KindMismatch { expected, found }))
+}
+
+/// Check the kind of a file against the expected kind asynchronously.
+#[cfg(feature = "async")]
+pub(crate) async fn check_kind_async<R>(
+    reader: R,
+    expected: Option<ContentKind>,
+) -> Result<Option<KindMismatch>>
+where
+    R: futures::io::AsyncRead + futures::io::AsyncSeek + Unpin,
+{
+    let expected = match expected {
+        Some(expected) => expected,
+        None => return Ok(None),
+    };
+    let found = content_kind_of_reader_async(reader).await?;
+    Ok((found != expected).then_some(KindMismatch { expected, found }))
+}
+
+// strict wire format validation
+
+/// Returns true if every field of the message is accepted by the check, which must
+/// consume the field.
+fn all_fields<F>(mut buf: &[u8], mut check: F) -> bool
+where
+    F: FnMut(u32, WireType, &mut &[u8]) -> bool,
+{
+    while buf.has_remaining() {
+        let (tag, wire_type) = match encoding::decode_key(&mut buf) {
+            Ok(key) => key,
+            Err(_) => return false,
+        };
+        if !check(tag, wire_type, &mut buf) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Take the body of a length-delimited field.
+fn take_nested<'a>(wire_type: WireType, buf: &mut &'a [u8]) -> Option<&'a [u8]> {
+    if wire_type != WireType::LengthDelimited {
+        return None;
+    }
+    let len = encoding::decode_varint(buf).ok()? as usize;
+    if len > buf.len() {
+        return None;
+    }
+    let (body, rest) = buf.split_at(len);
+    *buf = rest;
+    Some(body)
+}
+
+fn skip(wire_type: WireType, tag: u32, buf: &mut &[u8]) -> bool {
+    encoding::skip_field(wire_type, tag, buf, Default::default()).is_ok()
+}
+
+/// The fields of `Event`: the wall time, the step and the `oneof` of payloads.
+fn is_event(bytes: &[u8]) -> bool {
+    let mut has_wall_time = false;
+    let mut has_file_version = false;
+
+    let valid = all_fields(bytes, |tag, wire_type, buf| match (tag, wire_type) {
+        (1, WireType::SixtyFourBit) => {
+            has_wall_time = true;
+            skip(wire_type, tag, buf)
+        }
+        (2, WireType::Varint) => skip(wire_type, tag, buf),
+        (3, _) => match take_nested(wire_type, buf) {
+            Some(version) => {
+                has_file_version = version.starts_with(b"brain.Event:");
+                true
+            }
+            None => false,
+        },
+        (4..=10, WireType::LengthDelimited) => skip(wire_type, tag, buf),
+        _ => false,
+    });
+    valid && (has_wall_time || has_file_version)
+}
+
+fn is_example(bytes: &[u8]) -> bool {
+    all_fields(bytes, |tag, wire_type, buf| {
+        tag == 1 && take_nested(wire_type, buf).is_some_and(is_features)
+    })
+}
+
+fn is_sequence_example(bytes: &[u8]) -> bool {
+    all_fields(bytes, |tag, wire_type, buf| match tag {
+        1 => take_nested(wire_type, buf).is_some_and(is_features),
+        2 => take_nested(wire_type, buf).is_some_and(is_feature_lists),
+        _ => false,
+    })
+}
+
+fn is_features(bytes: &[u8]) -> bool {
+    all_fields(bytes, |tag, wire_type, buf| {
+        tag == 1 && take_nested(wire_type, buf).is_some_and(|entry| is_map_entry(entry, is_feature))
+    })
+}
+
+fn is_feature_lists(bytes: &[u8]) -> bool {
+    all_fields(bytes, |tag, wire_type, buf| {
+        tag == 1
+            && take_nested(wire_type, buf).is_some_and(|entry| is_map_entry(entry, is_feature_list))
+    })
+}
+
+fn is_feature_list(bytes: &[u8]) -> bool {
+    all_fields(bytes, |tag, wire_type, buf| {
+        tag == 1 && take_nested(wire_type, buf).is_some_and(is_feature)
+    })
+}
+
+/// A `map<string, V>` entry with a UTF-8 key.
+fn is_map_entry(bytes: &[u8], is_value: fn(&[u8]) -> bool) -> bool {
+    all_fields(bytes, |tag, wire_type, buf| match tag {
+        1 => take_nested(wire_type, buf).is_some_and(|key| std::str::from_utf8(key).is_ok()),
+        2 => take_nested(wire_type, buf).is_some_and(is_value),
+        _ => false,
+    })
+}
+
+/// The `oneof` of `BytesList`, `FloatList` and `Int64List`.
+fn is_feature(bytes: &[u8]) -> bool {
+    all_fields(bytes, |tag, wire_type, buf| {
+        let list = match take_nested(wire_type, buf) {
+            Some(list) => list,
+            None => return false,
+        };
+        match tag {
+            1 => all_fields(list, |tag, wire_type, buf| {
+                tag == 1 && take_nested(wire_type, buf).is_some()
+            }),
+            2 => all_fields(list, |tag, wire_type, buf| match (tag, wire_type) {
+                (1, WireType::ThirtyTwoBit) => skip(wire_type, tag, buf),
+                (1, WireType::LengthDelimited) => {
+                    take_nested(wire_type, buf).is_some_and(|packed| packed.len() % 4 == 0)
+                }
+                _ => false,
+            }),
+            3 => all_fields(list, |tag, wire_type, buf| match (tag, wire_type) {
+                (1, WireType::Varint) => skip(wire_type, tag, buf),
+                (1, WireType::LengthDelimited) => {
+                    take_nested(wire_type, buf).is_some_and(|mut packed| {
+                        while packed.has_remaining() {
+                            if encoding::decode_varint(&mut packed).is_err() {
+                                return false;
+                            }
+                        }
+                        true
+                    })
+                }
+                _ => false,
+            }),
+            _ => false,
+        }
+    })
+}
@@ -0,0 +1,280 @@
+//! Explain errors with their likely causes and the actions fixing them.
+//!
+//! Most failures seen in practice are one of a few: a file which is not a TFRecord
+//! file, a truncated file, an events file read as examples, a missing or unreadable
+//! path, and a record exceeding a length limit. [explain] maps an [Error] to a
+//! [Diagnosis] for these and every other [ErrorCode]. The texts are maintained here, so
+//! they are versioned with the errors they explain.
+//!
+//! If the error carries the path of a file, the [format](crate::format) of the file is
+//! detected, and a file of another format, such as a GZIP-compressed file, is reported
+//! as the first likely cause.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{diagnostics, Error, ErrorCode};
+//!
+//! let diagnosis = diagnostics::explain(&Error::UnexpectedEof);
+//! assert_eq!(diagnosis.code, ErrorCode::UnexpectedEof);
+//! assert!(!diagnosis.suggested_actions.is_empty());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::{Error, ErrorCode},
+    format::{self, FileFormat},
+};
+use std::{io::ErrorKind, path::Path};
+
+/// The explanation of an error.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnosis {
+    /// The code of the explained error. Wrapping errors, such as
+    /// [RecordFailed](Error::RecordFailed) and
+    /// [WriteInterrupted](Error::WriteInterrupted), are explained by their causes.
+    pub code: ErrorCode,
+    pub summary: &'static str,
+    /// The likely causes, the most likely first.
+    pub likely_causes: Vec<&'static str>,
+    /// The actions to take, in the order of the causes.
+    pub suggested_actions: Vec<&'static str>,
+}
+
+/// Explain the error.
+///
+/// When the error carries a file path, the leading bytes of the file are read to detect
+/// its format. Failures to read the file are ignored.
+pub fn explain(error: &Error) -> Diagnosis {
+    // explain the cause of wrapping errors, keeping the location
+    let mut error = error;
+    let mut path = None;
+    let mut record = None;
+    loop {
+        match error {
+            Error::RecordFailed {
+                path: record_path,
+                index,
+                source,
+            } => {
+                path = Some(record_path.as_path());
+                record = Some(*index);
+                error = source;
+            }
+            Error::WriterPoisoned { original } => error = original,
+            Error::WriteInterrupted { source, .. } => error = source,
+            _ => break,
+        }
+    }
+    // a frame at the start of a file is the first record
+    if matches!(
+        error,
+        Error::ChecksumMismatch {
+            offset: Some(0),
+            ..
+        }
+    ) {
+        record = record.or(Some(0));
+    }
+    let path = path.or_else(|| path_of(error));
+
+    let mut diagnosis = diagnose(error, record);
+    let format = path.and_then(|path| format::detect_file_format(path).ok());
+    if let Some(format) = format {
+        if let Some((cause, action)) = describe_format(format) {
+            diagnosis.likely_causes.insert(0, cause);
+            diagnosis.suggested_actions.insert(0, action);
+        }
+    }
+    diagnosis
+}
+
+fn path_of(error: &Error) -> Option<&Path> {
+    match error {
+        Error::IoErrorWithContext { path, .. }
+        | Error::ChecksumMismatch {
+            path: Some(path), ..
+        }
+        | Error::FileChanged { path, .. }
+        | Error::ContentKindMismatch { path, .. }
+        | Error::VerificationFailed { path, .. } => Some(path),
+        _ => None,
+    }
+}
+
+fn describe_format(format: FileFormat) -> Option<(&'static str, &'static str)> {
+    let described = match format {
+        FileFormat::Plain => return None,
+        FileFormat::Gzip => (
+            "this looks like a GZIP-compressed file, which is not a plain TFRecord file",
+            "decompress the file on reading by setting compression to Compression::Gzip with the `gzip` cargo feature, or before reading by `gunzip`",
+        ),
+        FileFormat::Zstd => (
+            "this looks like a Zstandard-compressed file, which is not a plain TFRecord file",
+            "decompress the file before reading, such as by `zstd -d`",
+        ),
+        FileFormat::Encrypted { .. } => (
+            "this looks like an encrypted file written by EncryptedWriter",
+            "read the file by encryption::EncryptedReader with the key it was written with",
+        ),
+    };
+    Some(described)
+}
+
+fn diagnose(error: &Error, record: Option<u64>) -> Diagnosis {
+    let code = error.code();
+    let (summary, likely_causes, suggested_actions) = match error {
+        Error::ChecksumMismatch { .. } if record == Some(0) => (
+            "the first record fails checksum verification",
+            vec![
+                "the file is not a TFRecord file",
+                "the file is compressed or encrypted",
+            ],
+            vec![
+                "check the file format by format::detect_file_format",
+                "decompress or decrypt the file before reading",
+            ],
+        ),
+        Error::ChecksumMismatch { .. } => (
+            "a record fails checksum verification",
+            vec![
+                "the file is not a TFRecord file, if it fails at the first record",
+                "the file is corrupted on disk or in transfer",
+                "the file is being written by another process",
+            ],
+            vec![
+                "check the file format by format::detect_file_format",
+                "copy the file again and compare it with the source",
+                "wait until the writer closes the file",
+            ],
+        ),
+        Error::UnexpectedEof => (
+            "a file ends in the middle of a record",
+            vec![
+                "the file is truncated, such as by an interrupted copy or a crashed writer",
+                "the file is still being written",
+            ],
+            vec![
+                "copy the file again from its source",
+                "read the file after the writer closes it, or watch the directory by indexer::LiveIndexes",
+            ],
+        ),
+        Error::ExampleDecodeError(_) => (
+            "a record fails to decode as the requested message",
+            vec![
+                "the file holds other messages, such as an events file read as examples",
+                "the file is corrupted without checksum verification",
+            ],
+            vec![
+                "check the content of the file by content::content_kind, and read events files by EventIter",
+                "read with IntegrityMode::Full to detect corrupted records",
+            ],
+        ),
+        Error::FeatureDecodeFailed { .. } => (
+            "a bytes feature fails to decode as the requested message",
+            vec![
+                "the feature holds messages of another schema",
+                "the feature holds raw bytes rather than serialized messages",
+            ],
+            vec![
+                "write the feature by push_typed_protos and read it by get_typed_protos, so the schema is verified",
+                "check the writer of the feature for the message type it serializes",
+            ],
+        ),
+        Error::MissingShards { .. } => (
+            "some files of a sharded file spec do not exist",
+            vec![
+                "the dataset is still being written or copied",
+                "the shard count of the spec differs from the written files",
+            ],
+            vec![
+                "compare the spec with the `-of-` count in the file names by shardspec::parse_filename",
+                "set allow_missing in ShardSpecOptions to load the existing shards",
+            ],
+        ),
+        Error::SchemaUnavailable { .. } => (
+            "a schema provider fails to fetch the schema of a validating writer",
+            vec![
+                "the schema id is unknown to the provider",
+                "the schema registry behind the provider is unreachable",
+            ],
+            vec![
+                "check the schema id against the provider, and the cause in the source error",
+                "write with SchemaSource::File or SchemaSource::Inline while the registry is down",
+            ],
+        ),
+        Error::IoError(error) | Error::IoErrorWithContext { source: error, .. } => {
+            match error.kind() {
+                ErrorKind::NotFound => (
+                    "a file or directory does not exist",
+                    vec![
+                        "the path is misspelled or relative to another working directory",
+                        "the file was removed or renamed",
+                    ],
+                    vec![
+                        "check the path, and pass an absolute path",
+                        "list the directory again before reading",
+                    ],
+                ),
+                ErrorKind::PermissionDenied => (
+                    "a file or directory is not accessible",
+                    vec!["the process lacks the permission to read or write the path"],
+                    vec!["check the permissions of the path and its parent directories"],
+                ),
+                _ => (
+                    "an I/O operation failed",
+                    vec![
+                        "the path names a directory where a file is expected",
+                        "the storage is full or unavailable",
+                    ],
+                    vec![
+                        "check the path and the storage it resides on",
+                        "retry the operation",
+                    ],
+                ),
+            }
+        }
+        Error::LimitExceeded { .. } => (
+            "a record exceeds a configured limit",
+            vec![
+                "the record is legitimately larger than the limit",
+                "the file is not a TFRecord file, so a length is read from unrelated bytes",
+            ],
+            vec![
+                "raise the limit in the limits of the reader configuration",
+                "check the file format by format::detect_file_format",
+            ],
+        ),
+        Error::Unsupported { .. } => (
+            "a file requires a capability missing in this build",
+            vec!["the file is compressed or encrypted, or a cargo feature is disabled"],
+            vec!["enable the cargo feature named in the message, or convert the file first"],
+        ),
+        Error::ContentKindMismatch { .. } => (
+            "a file holds another kind of content than expected",
+            vec!["a directory mixes data shards with other files, such as events files"],
+            vec!["select the files by a file name prefix, or skip mismatched kinds by the indexer configuration"],
+        ),
+        Error::FileChanged { .. } => (
+            "a file changed after it was indexed",
+            vec!["the file was rewritten or replaced by another process"],
+            vec!["index the dataset again, or reindex changed files by FileChangePolicy::Reindex"],
+        ),
+        Error::Timeout { .. } => (
+            "an I/O operation timed out",
+            vec!["the storage is slow or unavailable"],
+            vec!["raise the operation timeout, or retry"],
+        ),
+        _ => (
+            "see the error message",
+            vec![],
+            vec![],
+        ),
+    };
+    Diagnosis {
+        code,
+        summary,
+        likely_causes,
+        suggested_actions,
+    }
+}
@@ -0,0 +1,492 @@
+//! Whole-file encryption at rest.
+//!
+//! The [EncryptedWriter] encrypts the entire framed record stream, so that record
+//! boundaries and sizes are not leaked. The stream is split into chunks of
+//! [chunk_size](EncryptionConfig::chunk_size) bytes, each sealed by ChaCha20-Poly1305
+//! with a nonce derived from the random file nonce and the chunk index. The file starts
+//! with a cleartext header identifying the key id and the chunk size.
+//!
+//! The [EncryptedReader] looks up the key by the key id, and decrypts chunks on demand.
+//! It implements [Read] and [Seek] over the plaintext, so it can be passed to
+//! [RecordIter](crate::RecordIter) and [indexer::load_reader](crate::indexer::load_reader).
+//! Record positions from the indexer are logical offsets in the plaintext, and can be
+//! loaded by [Position::load_from](crate::indexer::Position::load_from).
+//!
+//! Random access is only available through an [EncryptedReader]. The path-based indexer
+//! functions, such as [indexer::load_paths](crate::indexer::load_paths), have no way to
+//! look up keys. They reject encrypted files with [Error::Unsupported](crate::Error::Unsupported),
+//! and the resulting [RecordIndex](crate::indexer::RecordIndex) cannot be loaded from
+//! encrypted files.
+//!
+//! The chunk size in the file header is checked against [MAX_CHUNK_SIZE] before any
+//! chunk buffer is allocated, failing with [Error::LimitExceeded](crate::Error::LimitExceeded)
+//! on crafted or corrupted headers.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use std::io::Cursor;
+//! use tfrecord::{
+//!     encryption::{EncryptedReader, EncryptedWriter, EncryptionKey},
+//!     indexer, BytesIter, BytesWriter,
+//! };
+//!
+//! let key = EncryptionKey::new([7; 32]);
+//!
+//! // write
+//! let writer = EncryptedWriter::new(vec![], "key-1", &key, Default::default())?;
+//! let mut writer = BytesWriter::from_writer(writer)?;
+//! writer.send(b"hello".to_vec())?;
+//! writer.send(b"world".to_vec())?;
+//! let bytes = writer.into_inner().finish()?;
+//!
+//! // read sequentially
+//! let lookup = |key_id: &str| {
+//!     assert_eq!(key_id, "key-1");
+//!     Ok(key.clone())
+//! };
+//! let reader = EncryptedReader::new(Cursor::new(&bytes), lookup)?;
+//! let records: Vec<_> = BytesIter::from_reader(reader, Default::default()).collect::<Result<_, _>>()?;
+//! assert_eq!(records, vec![b"hello".to_vec(), b"world".to_vec()]);
+//!
+//! // read randomly
+//! let mut reader = EncryptedReader::new(Cursor::new(&bytes), lookup)?;
+//! let positions: Vec<_> = indexer::load_reader(&mut reader, Default::default()).collect::<Result<_, _>>()?;
+//! let record: Vec<u8> = positions[1].load_from(&mut reader)?;
+//! assert_eq!(record, b"world");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    format::{ENCRYPTED_MAGIC as MAGIC, ENCRYPTED_VERSION as VERSION},
+};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN},
+    rand::{SecureRandom as _, SystemRandom},
+};
+use std::{
+    fmt,
+    fs::File,
+    io::{self, prelude::*, BufReader, SeekFrom},
+    mem,
+    path::Path,
+};
+
+const TAG_LEN: usize = 16;
+
+/// The maximum chunk size in bytes accepted by [EncryptedWriter] and [EncryptedReader].
+pub const MAX_CHUNK_SIZE: usize = 64 * 1024 * 1024;
+
+/// A 256-bit data key.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptionKey([u8; 32]);
+
+impl EncryptionKey {
+    /// Build from raw key bytes.
+    pub fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    fn to_aead_key(&self) -> LessSafeKey {
+        LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, &self.0).unwrap())
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncryptionKey(..)")
+    }
+}
+
+/// Configuration for [EncryptedWriter].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EncryptionConfig {
+    /// The plaintext size in bytes of every chunk except the last one.
+    /// It must not exceed [MAX_CHUNK_SIZE].
+    pub chunk_size: usize,
+}
+
+impl Default for EncryptionConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: 64 * 1024,
+        }
+    }
+}
+
+/// The cleartext file header.
+#[derive(Debug, Clone)]
+struct Header {
+    key_id: String,
+    chunk_size: usize,
+    file_nonce: [u8; NONCE_LEN],
+    bytes: Vec<u8>,
+}
+
+impl Header {
+    fn new(key_id: &str, chunk_size: usize, file_nonce: [u8; NONCE_LEN]) -> Result<Self> {
+        crate::error::ensure_argument!(chunk_size > 0, "chunk_size must be positive");
+        crate::error::ensure_argument!(
+            chunk_size <= MAX_CHUNK_SIZE,
+            "chunk_size must not exceed {}",
+            MAX_CHUNK_SIZE
+        );
+        crate::error::ensure_argument!(
+            key_id.len() <= u16::MAX as usize,
+            "the key id must not exceed {} bytes",
+            u16::MAX
+        );
+
+        let mut bytes = vec![];
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&(chunk_size as u32).to_le_bytes());
+        bytes.extend_from_slice(&file_nonce);
+        bytes.extend_from_slice(&(key_id.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(key_id.as_bytes());
+
+        Ok(Self {
+            key_id: key_id.to_string(),
+            chunk_size,
+            file_nonce,
+            bytes,
+        })
+    }
+
+    fn read<R>(reader: &mut R) -> Result<Self>
+    where
+        R: Read,
+    {
+        let mut fixed = [0u8; 8 + 1 + 4 + NONCE_LEN + 2];
+        reader.read_exact(&mut fixed)?;
+        let (magic, rest) = fixed.split_at(8);
+        let (version, rest) = rest.split_at(1);
+        let (chunk_size, rest) = rest.split_at(4);
+        let (file_nonce, key_id_len) = rest.split_at(NONCE_LEN);
+
+        if magic != MAGIC {
+            return Err(Error::crypto("not an encrypted TFRecord file"));
+        }
+        if version[0] != VERSION {
+            return Err(Error::crypto(format!(
+                "unsupported encryption format version {}",
+                version[0]
+            )));
+        }
+        let chunk_size = u32::from_le_bytes(chunk_size.try_into().unwrap()) as usize;
+        if chunk_size > MAX_CHUNK_SIZE {
+            return Err(Error::limit_exceeded(
+                "chunk_size",
+                MAX_CHUNK_SIZE,
+                chunk_size,
+            ));
+        }
+        let file_nonce: [u8; NONCE_LEN] = file_nonce.try_into().unwrap();
+        let key_id_len = u16::from_le_bytes(key_id_len.try_into().unwrap()) as usize;
+
+        let mut key_id = vec![0u8; key_id_len];
+        reader.read_exact(&mut key_id)?;
+        let key_id = String::from_utf8(key_id)
+            .map_err(|_| Error::crypto("the key id is not UTF-8 encoded"))?;
+
+        Self::new(&key_id, chunk_size, file_nonce)
+    }
+
+    fn nonce(&self, index: u64) -> Nonce {
+        let mut nonce = self.file_nonce;
+        let counter = &mut nonce[NONCE_LEN - 8..];
+        let value = u64::from_le_bytes((&*counter).try_into().unwrap()) ^ index;
+        counter.copy_from_slice(&value.to_le_bytes());
+        Nonce::assume_unique_for_key(nonce)
+    }
+
+    /// The header and the chunk position are authenticated along with every chunk.
+    fn aad(&self, index: u64, is_last: bool) -> Vec<u8> {
+        let mut aad = self.bytes.clone();
+        aad.extend_from_slice(&index.to_le_bytes());
+        aad.push(is_last as u8);
+        aad
+    }
+}
+
+/// The writer encrypting the byte stream written to it.
+///
+/// The writer must be [finished](EncryptedWriter::finish) to write the last chunk.
+/// Dropping the writer finishes it as well, but errors are ignored.
+pub struct EncryptedWriter<W>
+where
+    W: Write,
+{
+    writer: Option<W>,
+    key: LessSafeKey,
+    header: Header,
+    buffer: Vec<u8>,
+    num_chunks: u64,
+}
+
+impl EncryptedWriter<io::BufWriter<File>> {
+    /// Build a writer writing to a new file.
+    pub fn create<P>(
+        path: P,
+        key_id: &str,
+        key: &EncryptionKey,
+        config: EncryptionConfig,
+    ) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let writer = io::BufWriter::new(File::create(path)?);
+        Self::new(writer, key_id, key, config)
+    }
+}
+
+impl<W> EncryptedWriter<W>
+where
+    W: Write,
+{
+    /// Build from a writer with [Write] trait and write the file header.
+    pub fn new(
+        mut writer: W,
+        key_id: &str,
+        key: &EncryptionKey,
+        config: EncryptionConfig,
+    ) -> Result<Self> {
+        let EncryptionConfig { chunk_size } = config;
+
+        let mut file_nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut file_nonce)
+            .map_err(|_| Error::crypto("unable to generate the file nonce"))?;
+        let header = Header::new(key_id, chunk_size, file_nonce)?;
+        writer.write_all(&header.bytes)?;
+
+        Ok(Self {
+            writer: Some(writer),
+            key: key.to_aead_key(),
+            header,
+            buffer: vec![],
+            num_chunks: 0,
+        })
+    }
+
+    /// Write the last chunk and return the underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.write_last_chunk()?;
+        Ok(self.writer.take().unwrap())
+    }
+
+    fn write_chunk(&mut self, mut chunk: Vec<u8>, is_last: bool) -> Result<()> {
+        let index = self.num_chunks;
+        self.key
+            .seal_in_place_append_tag(
+                self.header.nonce(index),
+                Aad::from(self.header.aad(index, is_last)),
+                &mut chunk,
+            )
+            .map_err(|_| Error::crypto("unable to encrypt the chunk"))?;
+        self.writer.as_mut().unwrap().write_all(&chunk)?;
+        self.num_chunks += 1;
+        Ok(())
+    }
+
+    fn write_last_chunk(&mut self) -> Result<()> {
+        let chunk = mem::take(&mut self.buffer);
+        self.write_chunk(chunk, true)?;
+        self.writer.as_mut().unwrap().flush()?;
+        Ok(())
+    }
+}
+
+impl<W> Write for EncryptedWriter<W>
+where
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+
+        // A full chunk is kept in the buffer until more data arrives,
+        // because the last chunk is marked differently.
+        let chunk_size = self.header.chunk_size;
+        while self.buffer.len() > chunk_size {
+            let rest = self.buffer.split_off(chunk_size);
+            let chunk = mem::replace(&mut self.buffer, rest);
+            self.write_chunk(chunk, false).map_err(io::Error::from)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.as_mut().unwrap().flush()
+    }
+}
+
+impl<W> Drop for EncryptedWriter<W>
+where
+    W: Write,
+{
+    fn drop(&mut self) {
+        if self.writer.is_some() {
+            let _ = self.write_last_chunk();
+        }
+    }
+}
+
+/// The reader decrypting a stream written by [EncryptedWriter].
+///
+/// It supports random access over the plaintext. Only the chunks being read are decrypted.
+pub struct EncryptedReader<R>
+where
+    R: Read + Seek,
+{
+    reader: R,
+    key: LessSafeKey,
+    header: Header,
+    num_chunks: u64,
+    last_chunk_len: usize,
+    len: u64,
+    pos: u64,
+    chunk: Option<(u64, Vec<u8>)>,
+}
+
+impl EncryptedReader<BufReader<File>> {
+    /// Open an encrypted file.
+    pub fn open<P, F>(path: P, lookup: F) -> Result<Self>
+    where
+        P: AsRef<Path>,
+        F: FnOnce(&str) -> Result<EncryptionKey>,
+    {
+        let reader = BufReader::new(File::open(path)?);
+        Self::new(reader, lookup)
+    }
+}
+
+impl<R> EncryptedReader<R>
+where
+    R: Read + Seek,
+{
+    /// Build from a reader with [Read] and [Seek] traits.
+    ///
+    /// The `lookup` callback receives the key id from the file header and returns the data key.
+    pub fn new<F>(mut reader: R, lookup: F) -> Result<Self>
+    where
+        F: FnOnce(&str) -> Result<EncryptionKey>,
+    {
+        reader.seek(SeekFrom::Start(0))?;
+        let header = Header::read(&mut reader)?;
+        let key = lookup(&header.key_id)?.to_aead_key();
+
+        let body_len = reader.seek(SeekFrom::End(0))? - header.bytes.len() as u64;
+        let sealed_chunk_size = (header.chunk_size + TAG_LEN) as u64;
+        let num_chunks = body_len.div_ceil(sealed_chunk_size).max(1);
+        let last_sealed_len = body_len - (num_chunks - 1) * sealed_chunk_size;
+        if last_sealed_len < TAG_LEN as u64 {
+            return Err(Error::crypto("the encrypted file is truncated"));
+        }
+        let last_chunk_len = last_sealed_len as usize - TAG_LEN;
+        let len = (num_chunks - 1) * header.chunk_size as u64 + last_chunk_len as u64;
+
+        Ok(Self {
+            reader,
+            key,
+            header,
+            num_chunks,
+            last_chunk_len,
+            len,
+            pos: 0,
+            chunk: None,
+        })
+    }
+
+    /// The key id in the file header.
+    pub fn key_id(&self) -> &str {
+        &self.header.key_id
+    }
+
+    /// The plaintext length in bytes.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns true if the plaintext is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Unwraps the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn load_chunk(&mut self, index: u64) -> Result<&[u8]> {
+        let is_cached = matches!(self.chunk, Some((cached, _)) if cached == index);
+
+        if !is_cached {
+            let chunk_size = self.header.chunk_size;
+            let is_last = index + 1 == self.num_chunks;
+            let plain_len = if is_last {
+                self.last_chunk_len
+            } else {
+                chunk_size
+            };
+            let offset = self.header.bytes.len() as u64 + index * (chunk_size + TAG_LEN) as u64;
+
+            let mut chunk = vec![0u8; plain_len + TAG_LEN];
+            self.reader.seek(SeekFrom::Start(offset))?;
+            self.reader.read_exact(&mut chunk)?;
+            self.key
+                .open_in_place(
+                    self.header.nonce(index),
+                    Aad::from(self.header.aad(index, is_last)),
+                    &mut chunk,
+                )
+                .map_err(|_| Error::crypto(format!("authentication failed for chunk {}", index)))?;
+            chunk.truncate(plain_len);
+            self.chunk = Some((index, chunk));
+        }
+
+        Ok(&self.chunk.as_ref().unwrap().1)
+    }
+}
+
+impl<R> Read for EncryptedReader<R>
+where
+    R: Read + Seek,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let chunk_size = self.header.chunk_size as u64;
+        let index = self.pos / chunk_size;
+        let offset = (self.pos % chunk_size) as usize;
+        let chunk = self.load_chunk(index).map_err(io::Error::from)?;
+
+        let len = buf.len().min(chunk.len() - offset);
+        buf[..len].copy_from_slice(&chunk[offset..(offset + len)]);
+        self.pos += len as u64;
+        Ok(len)
+    }
+}
+
+impl<R> Seek for EncryptedReader<R>
+where
+    R: Read + Seek,
+{
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::End(delta) => self.len.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+        };
+        let pos = pos.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        self.pos = pos;
+        Ok(pos)
+    }
+}
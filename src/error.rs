@@ -1,33 +1,388 @@
 //! Error types and error handling utilities.
 
-use std::{borrow::Cow, convert::Infallible};
+use crate::{cancel::Progress, content::ContentKind, indexer::FileIdentity, manifest::FileDiff};
+use std::{
+    borrow::Cow,
+    convert::Infallible,
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
 /// The result with error type defaults to [Error].
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 /// The error type for this crate.
+///
+/// Each message starts with the stable [code](Error::code) of the variant.
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
-    #[error("checksum mismatch error: expect {expect:#010x}, but found {found:#010x}")]
-    ChecksumMismatch { expect: u32, found: u32 },
-    #[error("unexpected end of file")]
+    #[error(
+        "[TFR0001] checksum mismatch error{}: expect {expect:#010x}, but found {found:#010x}",
+        describe_location(.path, .offset)
+    )]
+    ChecksumMismatch {
+        expect: u32,
+        found: u32,
+        /// The file of the record, if known.
+        path: Option<PathBuf>,
+        /// The offset of the record frame, which starts at the length field, if known.
+        offset: Option<u64>,
+    },
+    #[error("[TFR0002] unexpected end of file")]
     UnexpectedEof,
-    #[error("errored to decode example: {0}")]
+    #[error("[TFR0003] errored to decode example: {0}")]
     ExampleDecodeError(prost::DecodeError),
-    #[error("errored to encode example: {0}")]
+    #[error("[TFR0004] errored to encode example: {0}")]
     ExampleEncodeError(prost::EncodeError),
-    #[error("I/O error: {0}")]
+    #[error("[TFR0005] I/O error: {0}")]
     IoError(std::io::Error),
-    #[error("conversion error: {desc:}")]
+    #[error("[TFR0005] I/O error in {}{}: {source}", .path.display(), describe_offset(.offset))]
+    IoErrorWithContext {
+        path: PathBuf,
+        /// The offset in the file, if the error occurred at a record.
+        offset: Option<u64>,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("[TFR0006] conversion error: {desc:}")]
     ConversionError { desc: Cow<'static, str> },
-    #[error("invalid arguments: {desc:}")]
+    #[error("[TFR0007] invalid arguments: {desc:}")]
     InvalidArgumentsError { desc: Cow<'static, str> },
+    #[error("[TFR0008] limit {which} exceeded: limit {limit}, observed {observed}")]
+    LimitExceeded {
+        which: &'static str,
+        limit: u64,
+        observed: u64,
+    },
+    #[error("[TFR0009] operation {operation} timed out after {elapsed:?}")]
+    Timeout {
+        operation: &'static str,
+        elapsed: Duration,
+    },
+    #[error("[TFR0010] writer poisoned by an earlier error: {original}")]
+    WriterPoisoned { original: Arc<Error> },
+    #[error("[TFR0011] unknown {name} value {value}")]
+    UnknownEnumValue { name: &'static str, value: i32 },
+    #[error("[TFR0012] unsupported {capability}: {}", describe_feature(.feature))]
+    Unsupported {
+        capability: Cow<'static, str>,
+        /// The cargo feature providing the capability, if any.
+        feature: Option<&'static str>,
+    },
+    #[error("[TFR0013] file {} changed after indexing: expect {expected}, but found {found}", .path.display())]
+    FileChanged {
+        path: PathBuf,
+        expected: FileIdentity,
+        found: FileIdentity,
+    },
+    #[error("[TFR0014] file {} holds {found}, but expect {expected}", .path.display())]
+    ContentKindMismatch {
+        path: PathBuf,
+        expected: ContentKind,
+        found: ContentKind,
+    },
+    #[error(
+        "[TFR0015] operation cancelled after {} files and {} records",
+        .progress.files_done,
+        .progress.records_processed
+    )]
+    Cancelled { progress: Progress },
+    #[error("[TFR0016] dataset differs from its manifest: {}", describe_diffs(.diffs))]
+    ManifestMismatch { diffs: Vec<FileDiff> },
+    #[error("[TFR0017] record {index} of {}: {source}", .path.display())]
+    RecordFailed {
+        path: PathBuf,
+        /// The index of the record in the file.
+        index: u64,
+        #[source]
+        source: Box<Error>,
+    },
+    #[error("[TFR0018] file {} differs from its source at record {index}: {reason}", .path.display())]
+    VerificationFailed {
+        path: PathBuf,
+        /// The index of the first differing record.
+        index: u64,
+        reason: Cow<'static, str>,
+    },
+    #[error(
+        "[TFR0019] the build side of the join exceeds the memory cap of {limit} bytes after \
+         {num_keys} keys; raise max_build_bytes or build on the smaller dataset"
+    )]
+    JoinBuildTooLarge { limit: u64, num_keys: u64 },
+    #[error("[TFR0020] feature checksums mismatch for keys {keys:?}")]
+    FeatureChecksumMismatch { keys: Vec<String> },
+    #[error(
+        "[TFR0021] the saved accumulator covers files that were removed {removed:?} or changed {changed:?}"
+    )]
+    StaleAccumulator {
+        removed: Vec<PathBuf>,
+        changed: Vec<PathBuf>,
+    },
+    #[error("[TFR0024] element {index} of the feature '{key}' fails to decode: {source}")]
+    FeatureDecodeFailed {
+        key: String,
+        /// The index of the element in the `BytesList`.
+        index: usize,
+        #[source]
+        source: prost::DecodeError,
+    },
+    #[error(
+        "[TFR0025] {} of {num_shards} shards of {spec} are missing: {}",
+        .missing.len(),
+        describe_paths(.missing)
+    )]
+    MissingShards {
+        spec: String,
+        num_shards: usize,
+        /// The paths of the missing shards, in the order of shards.
+        missing: Vec<PathBuf>,
+    },
+    #[error("[TFR0026] schema '{schema_id}' is unavailable: {source}")]
+    SchemaUnavailable {
+        schema_id: String,
+        /// The error of the [SchemaProvider](crate::schema::SchemaProvider).
+        #[source]
+        source: Box<Error>,
+    },
+    #[error("[TFR0027] writing stopped after {committed} committed records: {source}")]
+    WriteInterrupted {
+        /// The number of records written and flushed before the failure.
+        committed: u64,
+        #[source]
+        source: Box<Error>,
+    },
+    #[cfg(feature = "encryption")]
+    #[error("[TFR0022] encryption error: {desc:}")]
+    CryptoError { desc: Cow<'static, str> },
     #[cfg(feature = "with-tch")]
-    #[error("tch error: {0}")]
+    #[error("[TFR0023] tch error: {0}")]
     TchError(tch::TchError),
 }
 
+/// The stable code of an [Error], shown in its message as `[TFRnnnn]`.
+///
+/// Codes are never reused or renumbered, so they can be searched for across versions.
+/// The [diagnostics](crate::diagnostics) module explains the common ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// `TFR0001`: A record failed checksum verification.
+    ChecksumMismatch = 1,
+    /// `TFR0002`: A file ended in the middle of a record.
+    UnexpectedEof = 2,
+    /// `TFR0003`: A record failed to decode as a protobuf message.
+    DecodeFailed = 3,
+    /// `TFR0004`: A message failed to encode.
+    EncodeFailed = 4,
+    /// `TFR0005`: An I/O error, with or without the file context.
+    Io = 5,
+    /// `TFR0006`: A value failed to convert.
+    Conversion = 6,
+    /// `TFR0007`: The arguments of a call are invalid.
+    InvalidArguments = 7,
+    /// `TFR0008`: A configured limit was exceeded.
+    LimitExceeded = 8,
+    /// `TFR0009`: An I/O operation timed out.
+    Timeout = 9,
+    /// `TFR0010`: A writer was used after an earlier error.
+    WriterPoisoned = 10,
+    /// `TFR0011`: A protobuf enum holds an unknown value.
+    UnknownEnumValue = 11,
+    /// `TFR0012`: A capability missing in this build is required.
+    Unsupported = 12,
+    /// `TFR0013`: A file changed after indexing.
+    FileChanged = 13,
+    /// `TFR0014`: A file holds another kind of content than expected.
+    ContentKindMismatch = 14,
+    /// `TFR0015`: An operation was cancelled.
+    Cancelled = 15,
+    /// `TFR0016`: A dataset differs from its manifest.
+    ManifestMismatch = 16,
+    /// `TFR0017`: A record failed, wrapping the cause.
+    RecordFailed = 17,
+    /// `TFR0018`: A written file differs from its source.
+    VerificationFailed = 18,
+    /// `TFR0019`: The build side of a join exceeds its memory cap.
+    JoinBuildTooLarge = 19,
+    /// `TFR0020`: Feature checksums mismatch.
+    FeatureChecksumMismatch = 20,
+    /// `TFR0021`: A saved accumulator covers removed or changed files.
+    StaleAccumulator = 21,
+    /// `TFR0022`: An encryption error.
+    Crypto = 22,
+    /// `TFR0023`: An error from the tch crate.
+    Tch = 23,
+    /// `TFR0024`: An element of a bytes feature failed to decode as a protobuf message.
+    FeatureDecodeFailed = 24,
+    /// `TFR0025`: Shards of a sharded file spec are missing.
+    MissingShards = 25,
+    /// `TFR0026`: A schema provider failed to fetch a schema.
+    SchemaUnavailable = 26,
+    /// `TFR0027`: Writing a sequence of records stopped, wrapping the cause.
+    WriteInterrupted = 27,
+}
+
+impl ErrorCode {
+    /// The number of the code.
+    pub fn number(self) -> u16 {
+        self as u16
+    }
+
+    /// The code as shown in messages, such as `TFR0001`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ChecksumMismatch => "TFR0001",
+            Self::UnexpectedEof => "TFR0002",
+            Self::DecodeFailed => "TFR0003",
+            Self::EncodeFailed => "TFR0004",
+            Self::Io => "TFR0005",
+            Self::Conversion => "TFR0006",
+            Self::InvalidArguments => "TFR0007",
+            Self::LimitExceeded => "TFR0008",
+            Self::Timeout => "TFR0009",
+            Self::WriterPoisoned => "TFR0010",
+            Self::UnknownEnumValue => "TFR0011",
+            Self::Unsupported => "TFR0012",
+            Self::FileChanged => "TFR0013",
+            Self::ContentKindMismatch => "TFR0014",
+            Self::Cancelled => "TFR0015",
+            Self::ManifestMismatch => "TFR0016",
+            Self::RecordFailed => "TFR0017",
+            Self::VerificationFailed => "TFR0018",
+            Self::JoinBuildTooLarge => "TFR0019",
+            Self::FeatureChecksumMismatch => "TFR0020",
+            Self::StaleAccumulator => "TFR0021",
+            Self::Crypto => "TFR0022",
+            Self::Tch => "TFR0023",
+            Self::FeatureDecodeFailed => "TFR0024",
+            Self::MissingShards => "TFR0025",
+            Self::SchemaUnavailable => "TFR0026",
+            Self::WriteInterrupted => "TFR0027",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 impl Error {
+    /// Wrap an I/O error with the file and the offset it occurred at.
+    pub fn from_io_with_context(
+        error: std::io::Error,
+        path: impl Into<PathBuf>,
+        offset: Option<u64>,
+    ) -> Self {
+        Self::IoErrorWithContext {
+            path: path.into(),
+            offset,
+            source: error,
+        }
+    }
+
+    /// Attach the file and the offset to an I/O error. Other errors are kept.
+    pub(crate) fn with_io_context(self, path: &Path, offset: Option<u64>) -> Self {
+        match self {
+            Self::IoError(error) => Self::from_io_with_context(error, path, offset),
+            error => error,
+        }
+    }
+
+    /// Attach the file and the offset of the record frame to a checksum mismatch. Other
+    /// errors are kept.
+    pub(crate) fn with_checksum_context(self, path: &Path, frame_offset: u64) -> Self {
+        match self {
+            Self::ChecksumMismatch {
+                expect,
+                found,
+                path: None,
+                ..
+            } => Self::ChecksumMismatch {
+                expect,
+                found,
+                path: Some(path.to_path_buf()),
+                offset: Some(frame_offset),
+            },
+            error => error,
+        }
+    }
+
+    /// The stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::ChecksumMismatch { .. } => ErrorCode::ChecksumMismatch,
+            Self::UnexpectedEof => ErrorCode::UnexpectedEof,
+            Self::ExampleDecodeError(_) => ErrorCode::DecodeFailed,
+            Self::ExampleEncodeError(_) => ErrorCode::EncodeFailed,
+            Self::IoError(_) | Self::IoErrorWithContext { .. } => ErrorCode::Io,
+            Self::ConversionError { .. } => ErrorCode::Conversion,
+            Self::InvalidArgumentsError { .. } => ErrorCode::InvalidArguments,
+            Self::LimitExceeded { .. } => ErrorCode::LimitExceeded,
+            Self::Timeout { .. } => ErrorCode::Timeout,
+            Self::WriterPoisoned { .. } => ErrorCode::WriterPoisoned,
+            Self::UnknownEnumValue { .. } => ErrorCode::UnknownEnumValue,
+            Self::Unsupported { .. } => ErrorCode::Unsupported,
+            Self::FileChanged { .. } => ErrorCode::FileChanged,
+            Self::ContentKindMismatch { .. } => ErrorCode::ContentKindMismatch,
+            Self::Cancelled { .. } => ErrorCode::Cancelled,
+            Self::ManifestMismatch { .. } => ErrorCode::ManifestMismatch,
+            Self::RecordFailed { .. } => ErrorCode::RecordFailed,
+            Self::VerificationFailed { .. } => ErrorCode::VerificationFailed,
+            Self::JoinBuildTooLarge { .. } => ErrorCode::JoinBuildTooLarge,
+            Self::FeatureChecksumMismatch { .. } => ErrorCode::FeatureChecksumMismatch,
+            Self::StaleAccumulator { .. } => ErrorCode::StaleAccumulator,
+            Self::FeatureDecodeFailed { .. } => ErrorCode::FeatureDecodeFailed,
+            Self::MissingShards { .. } => ErrorCode::MissingShards,
+            Self::SchemaUnavailable { .. } => ErrorCode::SchemaUnavailable,
+            Self::WriteInterrupted { .. } => ErrorCode::WriteInterrupted,
+            #[cfg(feature = "encryption")]
+            Self::CryptoError { .. } => ErrorCode::Crypto,
+            #[cfg(feature = "with-tch")]
+            Self::TchError(_) => ErrorCode::Tch,
+        }
+    }
+
+    /// The [ErrorKind](std::io::ErrorKind) of the error converted to an I/O error.
+    pub fn io_error_kind(&self) -> std::io::ErrorKind {
+        use std::io::ErrorKind;
+
+        match self {
+            Self::IoError(error) => error.kind(),
+            Self::IoErrorWithContext { source, .. } => source.kind(),
+            Self::UnexpectedEof => ErrorKind::UnexpectedEof,
+            Self::ChecksumMismatch { .. }
+            | Self::ExampleDecodeError(_)
+            | Self::ConversionError { .. }
+            | Self::LimitExceeded { .. }
+            | Self::UnknownEnumValue { .. }
+            | Self::ContentKindMismatch { .. }
+            | Self::ManifestMismatch { .. }
+            | Self::VerificationFailed { .. }
+            | Self::FeatureChecksumMismatch { .. }
+            | Self::StaleAccumulator { .. }
+            | Self::FeatureDecodeFailed { .. } => ErrorKind::InvalidData,
+            Self::InvalidArgumentsError { .. } => ErrorKind::InvalidInput,
+            Self::MissingShards { .. } => ErrorKind::NotFound,
+            Self::Timeout { .. } => ErrorKind::TimedOut,
+            Self::JoinBuildTooLarge { .. } => ErrorKind::OutOfMemory,
+            Self::Unsupported { .. } => ErrorKind::Unsupported,
+            Self::Cancelled { .. } => ErrorKind::Interrupted,
+            Self::WriterPoisoned { original } => original.io_error_kind(),
+            Self::RecordFailed { source, .. }
+            | Self::SchemaUnavailable { source, .. }
+            | Self::WriteInterrupted { source, .. } => source.io_error_kind(),
+            Self::ExampleEncodeError(_) | Self::FileChanged { .. } => ErrorKind::Other,
+            #[cfg(feature = "encryption")]
+            Self::CryptoError { .. } => ErrorKind::InvalidData,
+            #[cfg(feature = "with-tch")]
+            Self::TchError(_) => ErrorKind::Other,
+        }
+    }
+
     #[allow(dead_code)]
     pub(crate) fn conversion(desc: impl Into<Cow<'static, str>>) -> Self {
         Self::ConversionError { desc: desc.into() }
@@ -36,14 +391,50 @@ impl Error {
     pub(crate) fn invalid_argument(desc: impl Into<Cow<'static, str>>) -> Self {
         Self::ConversionError { desc: desc.into() }
     }
+
+    #[cfg(feature = "encryption")]
+    pub(crate) fn crypto(desc: impl Into<Cow<'static, str>>) -> Self {
+        Self::CryptoError { desc: desc.into() }
+    }
+
+    pub(crate) fn limit_exceeded(which: &'static str, limit: usize, observed: usize) -> Self {
+        Self::LimitExceeded {
+            which,
+            limit: limit as u64,
+            observed: observed as u64,
+        }
+    }
+
+    pub(crate) fn unknown_enum_value(name: &'static str, value: i32) -> Self {
+        Self::UnknownEnumValue { name, value }
+    }
 }
 
+/// Unwrap an error of this crate carried by the I/O error, so that round trips do not
+/// nest errors.
 impl From<std::io::Error> for Error {
     fn from(error: std::io::Error) -> Self {
+        if error.get_ref().is_some_and(|inner| inner.is::<Error>()) {
+            let inner = error.into_inner().unwrap();
+            return *inner.downcast::<Error>().unwrap();
+        }
         Self::IoError(error)
     }
 }
 
+/// Convert to an I/O error of the [kind](Error::io_error_kind) carrying the error as
+/// the inner error, which can be recovered by
+/// [downcast](std::io::Error::downcast). A plain [IoError](Error::IoError) is unwrapped
+/// instead.
+impl From<Error> for std::io::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::IoError(error) => error,
+            error => std::io::Error::new(error.io_error_kind(), error),
+        }
+    }
+}
+
 impl From<prost::EncodeError> for Error {
     fn from(error: prost::EncodeError) -> Self {
         Self::ExampleEncodeError(error)
@@ -69,6 +460,67 @@ impl From<tch::TchError> for Error {
     }
 }
 
+#[cfg(feature = "with-serde")]
+impl serde::ser::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        Self::conversion(msg.to_string())
+    }
+}
+
+#[cfg(feature = "with-serde")]
+impl serde::de::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        Self::conversion(msg.to_string())
+    }
+}
+
+fn describe_feature(feature: &Option<&'static str>) -> String {
+    match feature {
+        Some(feature) => format!("requires the `{}` cargo feature", feature),
+        None => "not supported by this build".into(),
+    }
+}
+
+fn describe_location(path: &Option<PathBuf>, offset: &Option<u64>) -> String {
+    match path {
+        Some(path) => format!(" in {}{}", path.display(), describe_offset(offset)),
+        None => describe_offset(offset),
+    }
+}
+
+fn describe_offset(offset: &Option<u64>) -> String {
+    match offset {
+        Some(offset) => format!(" at offset {}", offset),
+        None => String::new(),
+    }
+}
+
+fn describe_diffs(diffs: &[FileDiff]) -> String {
+    let diffs: Vec<_> = diffs.iter().map(|diff| diff.to_string()).collect();
+    diffs.join("; ")
+}
+
+/// List the first few paths, so that a large count stays readable.
+fn describe_paths(paths: &[PathBuf]) -> String {
+    const MAX_LISTED: usize = 8;
+
+    let mut listed: Vec<_> = paths
+        .iter()
+        .take(MAX_LISTED)
+        .map(|path| path.display().to_string())
+        .collect();
+    if paths.len() > MAX_LISTED {
+        listed.push(format!("and {} more", paths.len() - MAX_LISTED));
+    }
+    listed.join(", ")
+}
+
 macro_rules! ensure_argument {
     ($cond:expr, $($arg:tt) *) => {
         if !$cond {
@@ -0,0 +1,207 @@
+//! Estimate the sizes of written files.
+//!
+//! Each record in a TFRecord file takes its payload length plus [FRAME_OVERHEAD]
+//! bytes, so the size of an uncompressed file is known exactly before writing.
+//!
+//! - [Example::encoded_len_framed] and [FramedLen] give the size of one record.
+//! - [framed_size] and [FramedSize] accumulate the size of many records.
+//! - [estimate_with_codec] extrapolates the compressed size from a sample of records.
+//!
+//! ```rust
+//! use tfrecord::{estimate, prelude::*};
+//!
+//! let example: Example = vec![("value".into(), Feature::from_i64_list(vec![1, 2, 3]))]
+//!     .into_iter()
+//!     .collect();
+//! let examples = vec![example.clone(), example.clone()];
+//!
+//! let size = estimate::framed_size(&examples);
+//! assert_eq!(size, example.encoded_len_framed() * 2);
+//! ```
+
+#[cfg(feature = "proto-summary")]
+use crate::protobuf::Event;
+use crate::{
+    error::{ensure_argument, Result},
+    protobuf::Example,
+};
+#[cfg(feature = "proto-summary")]
+use prost::Message as _;
+
+/// The number of bytes of the framing of a record.
+///
+/// It consists of the 8-byte length, the 4-byte length checksum and the 4-byte payload checksum.
+pub const FRAME_OVERHEAD: u64 = 16;
+
+/// The size of a framed record with the payload length.
+pub fn framed_len(payload_len: usize) -> u64 {
+    payload_len as u64 + FRAME_OVERHEAD
+}
+
+/// Types with known framed sizes in TFRecord files.
+pub trait FramedLen {
+    /// The exact size in bytes of the record written in a TFRecord file.
+    fn encoded_len_framed(&self) -> u64;
+}
+
+impl FramedLen for Example {
+    fn encoded_len_framed(&self) -> u64 {
+        Example::encoded_len_framed(self)
+    }
+}
+
+#[cfg(feature = "proto-summary")]
+impl FramedLen for Event {
+    fn encoded_len_framed(&self) -> u64 {
+        framed_len(self.encoded_len())
+    }
+}
+
+impl FramedLen for [u8] {
+    fn encoded_len_framed(&self) -> u64 {
+        framed_len(self.len())
+    }
+}
+
+impl FramedLen for Vec<u8> {
+    fn encoded_len_framed(&self) -> u64 {
+        framed_len(self.len())
+    }
+}
+
+impl<T> FramedLen for &T
+where
+    T: FramedLen + ?Sized,
+{
+    fn encoded_len_framed(&self) -> u64 {
+        (**self).encoded_len_framed()
+    }
+}
+
+/// The accumulator of framed record sizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FramedSize {
+    pub num_records: u64,
+    /// The total size in bytes.
+    pub num_bytes: u64,
+}
+
+impl FramedSize {
+    /// Add a record.
+    pub fn push<T>(&mut self, record: &T)
+    where
+        T: FramedLen + ?Sized,
+    {
+        self.num_records += 1;
+        self.num_bytes += record.encoded_len_framed();
+    }
+
+    /// Add a record by its payload length.
+    pub fn push_payload_len(&mut self, payload_len: usize) {
+        self.num_records += 1;
+        self.num_bytes += framed_len(payload_len);
+    }
+}
+
+/// The total size in bytes of the records written in a TFRecord file.
+pub fn framed_size<I>(records: I) -> u64
+where
+    I: IntoIterator,
+    I::Item: FramedLen,
+{
+    records
+        .into_iter()
+        .fold(FramedSize::default(), |mut size, record| {
+            size.push(&record);
+            size
+        })
+        .num_bytes
+}
+
+/// A compression algorithm applied on whole TFRecord files.
+pub trait CompressionCodec {
+    /// The compressed size of the bytes.
+    fn compressed_len(&self, bytes: &[u8]) -> Result<u64>;
+}
+
+/// The compression ratio extrapolated from a sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompressionEstimate {
+    /// The compressed size divided by the uncompressed size.
+    pub ratio: f64,
+    /// The confidence in `[0, 1]` of the ratio.
+    ///
+    /// It drops when the ratios of parts of the sample diverge, and grows with the
+    /// number of parts. It is zero if the sample is too small to compare parts.
+    pub confidence: f64,
+}
+
+impl CompressionEstimate {
+    /// Extrapolate the compressed size from the uncompressed size.
+    pub fn compressed_size(&self, framed_size: u64) -> u64 {
+        (framed_size as f64 * self.ratio).ceil() as u64
+    }
+}
+
+/// The maximum number of parts a sample is split into.
+const MAX_SAMPLE_PARTS: usize = 8;
+
+/// Estimate the compression ratio by compressing a sample of records.
+///
+/// The sample is framed as it would be written and compressed to compute the ratio.
+/// The sample is also split into up to 8 contiguous parts compressed separately,
+/// and the confidence is computed from the agreement of the ratios of parts.
+pub fn estimate_with_codec<I, C>(sample_records: I, codec: &C) -> Result<CompressionEstimate>
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+    C: CompressionCodec + ?Sized,
+{
+    let records: Vec<_> = sample_records.into_iter().collect();
+    ensure_argument!(!records.is_empty(), "the sample must not be empty");
+
+    let framed = {
+        let mut framed = vec![];
+        for record in &records {
+            crate::io::sync::try_write_record(&mut framed, record.as_ref().to_vec())?;
+        }
+        framed
+    };
+    let ratio = codec.compressed_len(&framed)? as f64 / framed.len() as f64;
+
+    // compress parts separately to measure the agreement
+    let num_parts = records.len().min(MAX_SAMPLE_PARTS);
+    let part_size = records.len().div_ceil(num_parts);
+    let parts: Vec<(u64, u64)> = records
+        .chunks(part_size)
+        .map(|part| {
+            let mut framed = vec![];
+            for record in part {
+                crate::io::sync::try_write_record(&mut framed, record.as_ref().to_vec())?;
+            }
+            let compressed_len = codec.compressed_len(&framed)?;
+            Ok((framed.len() as u64, compressed_len))
+        })
+        .collect::<Result<_>>()?;
+
+    let confidence = if parts.len() < 2 || ratio == 0.0 {
+        0.0
+    } else {
+        let ratios: Vec<f64> = parts
+            .iter()
+            .map(|&(framed, compressed)| compressed as f64 / framed as f64)
+            .collect();
+        let num_parts = ratios.len() as f64;
+        let mean = ratios.iter().sum::<f64>() / num_parts;
+        let variance = ratios
+            .iter()
+            .map(|ratio| (ratio - mean).powi(2))
+            .sum::<f64>()
+            / (num_parts - 1.0);
+        // the relative standard error of the mean ratio
+        let rel_stderr = variance.sqrt() / num_parts.sqrt() / mean;
+        (1.0 - rel_stderr).clamp(0.0, 1.0)
+    };
+
+    Ok(CompressionEstimate { ratio, confidence })
+}
@@ -1,7 +1,78 @@
 //! Summary and event types.
 
 use crate::protobuf::{event::What, Event, Summary};
-use std::time::SystemTime;
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    sync::Arc,
+    time::SystemTime,
+};
+
+/// The source of wall time for events built without an explicit wall time.
+pub trait WallClock
+where
+    Self: Send + Sync,
+{
+    /// The current wall time in seconds since UNIX epoch.
+    fn wall_time(&self) -> f64;
+}
+
+/// The [WallClock] reading the system time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SystemClock;
+
+impl WallClock for SystemClock {
+    fn wall_time(&self) -> f64 {
+        wall_time_now()
+    }
+}
+
+/// A shared handle to a [WallClock].
+///
+/// Two handles are equal if they point to the same clock.
+#[derive(Clone)]
+pub struct EventClock(Arc<dyn WallClock>);
+
+impl EventClock {
+    /// Create from a clock.
+    pub fn new<C>(clock: C) -> Self
+    where
+        C: 'static + WallClock,
+    {
+        Self(Arc::new(clock))
+    }
+
+    /// The current wall time in seconds since UNIX epoch.
+    pub fn wall_time(&self) -> f64 {
+        self.0.wall_time()
+    }
+}
+
+impl Default for EventClock {
+    fn default() -> Self {
+        Self::new(SystemClock)
+    }
+}
+
+impl fmt::Debug for EventClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EventClock").finish()
+    }
+}
+
+impl PartialEq for EventClock {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for EventClock {}
+
+impl Hash for EventClock {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (Arc::as_ptr(&self.0) as *const () as usize).hash(state);
+    }
+}
 
 /// [Event] metadata.
 #[derive(Debug, Clone, PartialEq)]
@@ -31,6 +102,15 @@ impl EventMeta {
         }
     }
 
+    /// Fill the wall time from the clock if it is not set.
+    pub fn or_wall_time_from(self, clock: &EventClock) -> Self {
+        let Self { wall_time, step } = self;
+        Self {
+            wall_time: Some(wall_time.unwrap_or_else(|| clock.wall_time())),
+            step,
+        }
+    }
+
     /// Build an empty event.
     pub fn build_empty(&self) -> Event {
         let (wall_time, step) = self.to_parts();
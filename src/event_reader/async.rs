@@ -0,0 +1,127 @@
+use super::{EventCheck, EventReaderConfig, HistogramPoint};
+use crate::{
+    error::{Error, Result},
+    export::ScalarPoint,
+    io::r#async::with_timeout,
+    protobuf::Event,
+    record_reader::EventStream,
+};
+use async_std::{fs::File, io::BufReader, path::Path};
+use futures::{
+    io::AsyncRead,
+    stream::{self, Stream, StreamExt},
+};
+use pin_project::pin_project;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Stream of the events of an event file from reader `R`.
+///
+/// It is the async counterpart of [EventFileIter](super::EventFileIter).
+#[pin_project]
+pub struct EventFileStream<R>
+where
+    R: AsyncRead,
+{
+    #[pin]
+    events: EventStream<R>,
+    check: EventCheck,
+}
+
+impl<R> EventFileStream<R>
+where
+    R: 'static + AsyncRead + Unpin + Send,
+{
+    /// Read the events of an event file from a reader.
+    pub fn from_reader(reader: R, config: EventReaderConfig) -> Self {
+        let check = EventCheck::new(&config);
+        Self {
+            events: EventStream::from_reader(reader, config.record),
+            check,
+        }
+    }
+}
+
+impl<R> EventFileStream<R>
+where
+    R: AsyncRead,
+{
+    /// Get the file version of the first event, once it is read.
+    pub fn file_version(&self) -> Option<&str> {
+        self.check.file_version.as_deref()
+    }
+
+    /// Get the error of the corrupt record before which the events ended, unless
+    /// [fail_on_corrupt](EventReaderConfig::fail_on_corrupt) is set.
+    pub fn corrupt_tail(&self) -> Option<&Error> {
+        self.check.corrupt_tail.as_ref()
+    }
+
+    /// Stream the scalars with tags starting with the prefix, in the order of events.
+    pub fn scalars(
+        self,
+        tag_prefix: impl Into<String>,
+    ) -> impl Stream<Item = Result<(String, ScalarPoint)>> {
+        let tag_prefix = tag_prefix.into();
+        self.flat_map(move |event| {
+            let points = match event {
+                Ok(event) => super::scalar_points(event, &tag_prefix)
+                    .into_iter()
+                    .map(Ok)
+                    .collect(),
+                Err(err) => vec![Err(err)],
+            };
+            stream::iter(points)
+        })
+    }
+
+    /// Stream the histograms with tags starting with the prefix, in the order of events.
+    pub fn histograms(
+        self,
+        tag_prefix: impl Into<String>,
+    ) -> impl Stream<Item = Result<(String, HistogramPoint)>> {
+        let tag_prefix = tag_prefix.into();
+        self.flat_map(move |event| {
+            let points = match event {
+                Ok(event) => super::histogram_points(event, &tag_prefix)
+                    .into_iter()
+                    .map(Ok)
+                    .collect(),
+                Err(err) => vec![Err(err)],
+            };
+            stream::iter(points)
+        })
+    }
+}
+
+impl EventFileStream<BufReader<File>> {
+    /// Read the events of an event file.
+    pub async fn open<P>(path: P, config: EventReaderConfig) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let file = with_timeout("open", config.record.op_timeout, async {
+            Ok(File::open(path).await?)
+        })
+        .await?;
+        Ok(Self::from_reader(BufReader::new(file), config))
+    }
+}
+
+impl<R> Stream for EventFileStream<R>
+where
+    R: AsyncRead,
+{
+    type Item = Result<Event>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        if this.check.done {
+            return Poll::Ready(None);
+        }
+        let result = futures::ready!(this.events.poll_next(cx));
+        Poll::Ready(this.check.check(result))
+    }
+}
@@ -0,0 +1,200 @@
+//! Event file reader.
+//!
+//! The [EventFileIter] and [EventFileStream] read TensorBoard event files, such as the
+//! files written by TensorFlow's summary writers or by [EventWriter](crate::EventWriter).
+//! Unlike the plain [EventIter](crate::EventIter), they check that a file starts with
+//! the `file_version` event, and by default end before the first corrupt record instead
+//! of failing, as TensorBoard does with the files of killed trainers.
+//!
+//! Scalars are stored in `simple_value` by TensorFlow 1.x, and in rank-0 `DT_FLOAT`
+//! tensors by TensorFlow 2.x. The [scalars](EventFileIter::scalars) filter reads both.
+
+mod sync;
+pub use sync::*;
+
+#[cfg(feature = "async")]
+mod r#async;
+#[cfg(feature = "async")]
+pub use r#async::*;
+
+use crate::{
+    error::{Error, Result},
+    export::ScalarPoint,
+    protobuf::{
+        event::What,
+        summary::{value::Value, Value as SummaryValue},
+        DataType, Event, HistogramProto,
+    },
+    record_reader::RecordReaderConfig,
+};
+
+/// The prefix of the file versions of event files.
+const FILE_VERSION_PREFIX: &str = "brain.Event:";
+
+/// Configuration for event file readers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EventReaderConfig {
+    /// The configuration of the underlying record reader.
+    pub record: RecordReaderConfig,
+    /// If set, the first event must be a `file_version` event of `brain.Event:`
+    /// followed by the version.
+    pub check_file_version: bool,
+    /// If set, a corrupt or truncated record fails the reader. Otherwise, the reader
+    /// ends before it, keeping the error as the corrupt tail.
+    pub fail_on_corrupt: bool,
+}
+
+impl Default for EventReaderConfig {
+    fn default() -> Self {
+        Self {
+            record: RecordReaderConfig::default(),
+            check_file_version: true,
+            fail_on_corrupt: false,
+        }
+    }
+}
+
+/// A histogram at a step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistogramPoint {
+    /// The wall time in seconds since UNIX epoch.
+    pub wall_time: f64,
+    pub step: i64,
+    pub histogram: HistogramProto,
+}
+
+/// Get the value of a scalar summary, stored in `simple_value` or in a rank-0
+/// `DT_FLOAT` tensor.
+pub(crate) fn scalar_value(value: &SummaryValue) -> Option<f32> {
+    match &value.value {
+        Some(Value::SimpleValue(value)) => Some(*value),
+        Some(Value::Tensor(tensor)) => {
+            let is_scalar =
+                tensor.try_dtype().ok()? == DataType::DtFloat && tensor.shape().ok()?.is_empty();
+            if !is_scalar {
+                return None;
+            }
+            match *tensor.to_vec::<f32>().ok()? {
+                [value] => Some(value),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The scalars of an event with tags starting with the prefix.
+fn scalar_points(event: Event, tag_prefix: &str) -> Vec<(String, ScalarPoint)> {
+    let summary = match event.what {
+        Some(What::Summary(summary)) => summary,
+        _ => return vec![],
+    };
+    summary
+        .value
+        .into_iter()
+        .filter(|value| value.tag.starts_with(tag_prefix))
+        .filter_map(|value| {
+            let point = ScalarPoint {
+                wall_time: event.wall_time,
+                step: event.step,
+                value: scalar_value(&value)?,
+            };
+            Some((value.tag, point))
+        })
+        .collect()
+}
+
+/// The histograms of an event with tags starting with the prefix.
+fn histogram_points(event: Event, tag_prefix: &str) -> Vec<(String, HistogramPoint)> {
+    let summary = match event.what {
+        Some(What::Summary(summary)) => summary,
+        _ => return vec![],
+    };
+    summary
+        .value
+        .into_iter()
+        .filter(|value| value.tag.starts_with(tag_prefix))
+        .filter_map(|value| match value.value {
+            Some(Value::Histo(histogram)) => {
+                let point = HistogramPoint {
+                    wall_time: event.wall_time,
+                    step: event.step,
+                    histogram,
+                };
+                Some((value.tag, point))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns true if the error is caused by a damaged or partially written record.
+fn is_corrupt(error: &Error) -> bool {
+    match error {
+        Error::ChecksumMismatch { .. } | Error::UnexpectedEof | Error::ExampleDecodeError(_) => {
+            true
+        }
+        Error::IoError(error) => error.kind() == std::io::ErrorKind::UnexpectedEof,
+        _ => false,
+    }
+}
+
+/// The checks of the events read from a file, shared by the iterator and the stream.
+#[derive(Debug)]
+struct EventCheck {
+    check_file_version: bool,
+    fail_on_corrupt: bool,
+    num_events: u64,
+    file_version: Option<String>,
+    corrupt_tail: Option<Error>,
+    done: bool,
+}
+
+impl EventCheck {
+    fn new(config: &EventReaderConfig) -> Self {
+        Self {
+            check_file_version: config.check_file_version,
+            fail_on_corrupt: config.fail_on_corrupt,
+            num_events: 0,
+            file_version: None,
+            corrupt_tail: None,
+            done: false,
+        }
+    }
+
+    /// Check the next result of the record reader. The events end after the first error.
+    fn check(&mut self, result: Option<Result<Event>>) -> Option<Result<Event>> {
+        if self.done {
+            return None;
+        }
+        let result = result.and_then(|result| match result {
+            Ok(event) => Some(self.check_event(event)),
+            Err(err) if is_corrupt(&err) && !self.fail_on_corrupt => {
+                self.corrupt_tail = Some(err);
+                None
+            }
+            Err(err) => Some(Err(err)),
+        });
+        self.done = !matches!(result, Some(Ok(_)));
+        result
+    }
+
+    fn check_event(&mut self, event: Event) -> Result<Event> {
+        if self.num_events == 0 {
+            if let Some(What::FileVersion(version)) = &event.what {
+                self.file_version = Some(version.clone());
+            }
+            let version = self.file_version.as_deref();
+            if self.check_file_version
+                && !version.is_some_and(|version| version.starts_with(FILE_VERSION_PREFIX))
+            {
+                return Err(Error::conversion(format!(
+                    "expect the first event to be a file version of '{}*', but get {:?}",
+                    FILE_VERSION_PREFIX, version
+                )));
+            }
+        }
+        self.num_events += 1;
+        Ok(event)
+    }
+}
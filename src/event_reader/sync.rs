@@ -0,0 +1,135 @@
+use super::{EventCheck, EventReaderConfig, HistogramPoint};
+use crate::{
+    error::{Error, Result},
+    export::ScalarPoint,
+    protobuf::Event,
+    record_reader::EventIter,
+};
+use std::{
+    fs::File,
+    io::{prelude::*, BufReader, Cursor},
+    path::Path,
+};
+
+/// Iterator of the events of an event file from reader `R`.
+///
+/// ```rust
+/// # fn main() -> tfrecord::Result<()> {
+/// use tfrecord::{EventFileIter, EventWriter};
+///
+/// let (mut writer, buffer) = EventWriter::in_memory(Default::default())?;
+/// for step in 0..3 {
+///     writer.write_scalar("train/loss", step, 1.0 / (step + 1) as f32)?;
+/// }
+/// writer.write_scalar("eval/loss", 3, 0.5)?;
+/// drop(writer);
+///
+/// let points: Vec<_> = EventFileIter::from_bytes(buffer.to_vec(), Default::default())
+///     .scalars("train/")
+///     .collect::<Result<_, _>>()?;
+/// let values: Vec<_> = points.iter().map(|(_, point)| point.value).collect();
+/// assert_eq!(values, vec![1.0, 0.5, 1.0 / 3.0]);
+/// # Ok(())
+/// # }
+/// ```
+pub struct EventFileIter<R>
+where
+    R: Read,
+{
+    events: EventIter<R>,
+    check: EventCheck,
+}
+
+impl<R> EventFileIter<R>
+where
+    R: Read,
+{
+    /// Read the events of an event file from a reader.
+    pub fn from_reader(reader: R, config: EventReaderConfig) -> Self {
+        let check = EventCheck::new(&config);
+        Self {
+            events: EventIter::from_reader(reader, config.record),
+            check,
+        }
+    }
+
+    /// Get the file version of the first event, once it is read.
+    pub fn file_version(&self) -> Option<&str> {
+        self.check.file_version.as_deref()
+    }
+
+    /// Get the error of the corrupt record before which the events ended, unless
+    /// [fail_on_corrupt](EventReaderConfig::fail_on_corrupt) is set.
+    pub fn corrupt_tail(&self) -> Option<&Error> {
+        self.check.corrupt_tail.as_ref()
+    }
+
+    /// Iterate over the scalars with tags starting with the prefix, in the order of
+    /// events.
+    pub fn scalars(
+        self,
+        tag_prefix: impl Into<String>,
+    ) -> impl Iterator<Item = Result<(String, ScalarPoint)>> {
+        let tag_prefix = tag_prefix.into();
+        self.flat_map(move |event| match event {
+            Ok(event) => super::scalar_points(event, &tag_prefix)
+                .into_iter()
+                .map(Ok)
+                .collect(),
+            Err(err) => vec![Err(err)],
+        })
+    }
+
+    /// Iterate over the histograms with tags starting with the prefix, in the order of
+    /// events.
+    pub fn histograms(
+        self,
+        tag_prefix: impl Into<String>,
+    ) -> impl Iterator<Item = Result<(String, HistogramPoint)>> {
+        let tag_prefix = tag_prefix.into();
+        self.flat_map(move |event| match event {
+            Ok(event) => super::histogram_points(event, &tag_prefix)
+                .into_iter()
+                .map(Ok)
+                .collect(),
+            Err(err) => vec![Err(err)],
+        })
+    }
+}
+
+impl EventFileIter<BufReader<File>> {
+    /// Read the events of an event file.
+    pub fn open<P>(path: P, config: EventReaderConfig) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let reader = BufReader::new(File::open(path.as_ref())?);
+        Ok(Self::from_reader(reader, config))
+    }
+}
+
+impl EventFileIter<Cursor<Vec<u8>>> {
+    /// Read the events of an event file in memory.
+    pub fn from_bytes<B>(bytes: B, config: EventReaderConfig) -> Self
+    where
+        B: Into<Vec<u8>>,
+    {
+        Self::from_reader(Cursor::new(bytes.into()), config)
+    }
+}
+
+impl<R> Iterator for EventFileIter<R>
+where
+    R: Read,
+{
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = if self.check.done {
+            None
+        } else {
+            self.events.next()
+        };
+        self.check.check(result)
+    }
+}
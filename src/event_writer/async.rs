@@ -1,17 +1,19 @@
-use super::EventWriterConfig;
+use super::{DedupStats, EventWriterConfig, ScalarDedup, ScalarDedupState};
+#[cfg(feature = "with-image")]
+use crate::protobuf_ext::{Box2D, BoxDrawReport, BoxStyle};
 use crate::{
     error::{Error, Result},
-    event::EventMeta,
+    event::{EventClock, EventMeta},
     protobuf::{
         summary::{Audio, Image},
         Event, Summary, TensorProto,
     },
     protobuf_ext::{IntoHistogram, IntoImageList},
-    record_writer::RecordAsyncWriter,
+    record_writer::{FlushPolicy, RecordAsyncWriter, RecordWriterConfig},
 };
 use async_std::{fs::File, io::BufWriter, path::Path};
 use futures::io::AsyncWrite;
-use std::{borrow::Cow, convert::TryInto, string::ToString};
+use std::{borrow::Cow, convert::TryInto, string::ToString, time::Instant};
 
 /// The event writer.
 ///
@@ -20,12 +22,17 @@ use std::{borrow::Cow, convert::TryInto, string::ToString};
 /// It can be built from a writer using [from_writer](EventAsyncWriter::from_writer), or write a new file
 /// specified by path prefix using [from_writer](EventAsyncWriter::from_prefix).
 ///
+/// The writer is [Send] if the underlying writer is, so that tasks can share it in an
+/// `Arc<Mutex<_>>`. With [auto_flush](EventWriterConfig::auto_flush) disabled, the
+/// buffered events are flushed by [flush](EventAsyncWriter::flush) or by a
+/// [FlushPolicy] set with [with_flush_policy](EventAsyncWriter::with_flush_policy).
+///
 /// ```rust
 /// # async_std::task::block_on(async move {
 /// use anyhow::Result;
 /// use std::time::SystemTime;
 /// use tch::{kind::FLOAT_CPU, Tensor};
-/// use tfrecord::EventAsyncWriter;
+/// use tfrecord::prelude::*;
 ///
 /// let mut writer = EventAsyncWriter::from_prefix("log_dir/myprefix-", "", Default::default())
 ///     .await
@@ -53,7 +60,13 @@ use std::{borrow::Cow, convert::TryInto, string::ToString};
 #[derive(Debug, Clone, PartialEq)]
 pub struct EventAsyncWriter<W> {
     auto_flush: bool,
+    flush_policy: FlushPolicy,
+    last_flush: Instant,
+    clock: EventClock,
     events_writer: RecordAsyncWriter<Event, W>,
+    /// The file version and the producer stamp written before the first event.
+    pending_header: Vec<Event>,
+    scalar_dedup: Option<ScalarDedupState>,
 }
 
 impl EventAsyncWriter<BufWriter<File>> {
@@ -67,6 +80,11 @@ impl EventAsyncWriter<BufWriter<File>> {
     }
 
     /// Build a writer writing events to a file, which path is specified by a path prefix and file name suffix.
+    ///
+    /// The file is named `<prefix>.out.tfevents.<unix_time>.<hostname><suffix>` as by
+    /// TensorFlow, where the prefix defaults to `events` for a directory prefix such as
+    /// `log_dir/`. The time is in microseconds, so that writers created in the same
+    /// second do not overwrite each other.
     pub async fn from_prefix<'a, 'b, P, S>(
         prefix: P,
         file_name_suffix: S,
@@ -89,10 +107,23 @@ where
 {
     /// Build from a writer with [AsyncWrite] trait.
     pub fn from_writer(writer: W, config: EventWriterConfig) -> Result<Self> {
-        let EventWriterConfig { auto_flush } = config;
+        let EventWriterConfig {
+            auto_flush,
+            clock,
+            file_version,
+            stamp_producer,
+        } = config;
+        let events_config = RecordWriterConfig::default();
+        let pending_header =
+            super::header_events(&clock, file_version, stamp_producer, &events_config);
         Ok(Self {
             auto_flush,
-            events_writer: RecordAsyncWriter::from_writer(writer)?,
+            flush_policy: FlushPolicy::default(),
+            last_flush: Instant::now(),
+            clock,
+            events_writer: RecordAsyncWriter::from_writer_with_config(writer, events_config)?,
+            pending_header,
+            scalar_dedup: None,
         })
     }
 
@@ -103,13 +134,19 @@ where
         event_meta: impl Into<EventMeta>,
         value: f32,
     ) -> Result<()> {
-        let summary = Summary::from_scalar(tag, value)?;
-        let event = event_meta.into().build_with_summary(summary);
-        self.events_writer.send(event).await?;
-        if self.auto_flush {
-            self.events_writer.flush().await?;
+        let tag = tag.to_string();
+        let summary = Summary::from_scalar(&tag, value)?;
+        let event = event_meta
+            .into()
+            .or_wall_time_from(&self.clock)
+            .build_with_summary(summary);
+        match &mut self.scalar_dedup {
+            Some(dedup) => {
+                let events = dedup.admit(tag, value, event);
+                self.write_events(events).await
+            }
+            None => self.write_event(event).await,
         }
-        Ok(())
     }
 
     /// Write a histogram summary asynchronously.
@@ -120,12 +157,11 @@ where
         histogram: impl IntoHistogram,
     ) -> Result<()> {
         let summary = Summary::from_histogram(tag, histogram)?;
-        let event = event_meta.into().build_with_summary(summary);
-        self.events_writer.send(event).await?;
-        if self.auto_flush {
-            self.events_writer.flush().await?;
-        }
-        Ok(())
+        let event = event_meta
+            .into()
+            .or_wall_time_from(&self.clock)
+            .build_with_summary(summary);
+        self.write_event(event).await
     }
 
     /// Write a tensor summary asynchronously.
@@ -136,12 +172,11 @@ where
         tensor: impl TryInto<TensorProto, Error = impl Into<Error>>,
     ) -> Result<()> {
         let summary = Summary::from_tensor(tag, tensor)?;
-        let event = event_meta.into().build_with_summary(summary);
-        self.events_writer.send(event).await?;
-        if self.auto_flush {
-            self.events_writer.flush().await?;
-        }
-        Ok(())
+        let event = event_meta
+            .into()
+            .or_wall_time_from(&self.clock)
+            .build_with_summary(summary);
+        self.write_event(event).await
     }
 
     /// Write an image summary asynchronously.
@@ -152,12 +187,33 @@ where
         image: impl TryInto<Image, Error = impl Into<Error>>,
     ) -> Result<()> {
         let summary = Summary::from_image(tag, image)?;
-        let event = event_meta.into().build_with_summary(summary);
-        self.events_writer.send(event).await?;
-        if self.auto_flush {
-            self.events_writer.flush().await?;
-        }
-        Ok(())
+        let event = event_meta
+            .into()
+            .or_wall_time_from(&self.clock)
+            .build_with_summary(summary);
+        self.write_event(event).await
+    }
+
+    /// Write an image summary with boxes drawn onto the image asynchronously.
+    ///
+    /// The report counts the boxes clamped to or outside of the image bounds. See
+    /// [draw_boxes](crate::protobuf_ext::draw_boxes) for details.
+    #[cfg(feature = "with-image")]
+    pub async fn write_image_with_boxes(
+        &mut self,
+        tag: impl ToString,
+        event_meta: impl Into<EventMeta>,
+        image: &image::DynamicImage,
+        boxes: &[Box2D],
+        style: &BoxStyle,
+    ) -> Result<BoxDrawReport> {
+        let (summary, report) = Summary::from_image_with_boxes(tag, image, boxes, style)?;
+        let event = event_meta
+            .into()
+            .or_wall_time_from(&self.clock)
+            .build_with_summary(summary);
+        self.write_event(event).await?;
+        Ok(report)
     }
 
     /// Write a summary with multiple images asynchronously.
@@ -168,12 +224,11 @@ where
         images: impl IntoImageList,
     ) -> Result<()> {
         let summary = Summary::from_image_list(tag, images)?;
-        let event = event_meta.into().build_with_summary(summary);
-        self.events_writer.send(event).await?;
-        if self.auto_flush {
-            self.events_writer.flush().await?;
-        }
-        Ok(())
+        let event = event_meta
+            .into()
+            .or_wall_time_from(&self.clock)
+            .build_with_summary(summary);
+        self.write_event(event).await
     }
 
     /// Write an audio summary asynchronously.
@@ -184,26 +239,79 @@ where
         audio: impl TryInto<Audio, Error = impl Into<Error>>,
     ) -> Result<()> {
         let summary = Summary::from_audio(tag, audio)?;
-        let event = event_meta.into().build_with_summary(summary);
-        self.events_writer.send(event).await?;
-        if self.auto_flush {
-            self.events_writer.flush().await?;
-        }
-        Ok(())
+        let event = event_meta
+            .into()
+            .or_wall_time_from(&self.clock)
+            .build_with_summary(summary);
+        self.write_event(event).await
     }
 
     /// Write a custom event asynchronously.
     pub async fn write_event(&mut self, event: Event) -> Result<()> {
-        self.events_writer.send(event).await?;
-        if self.auto_flush {
+        self.write_events(vec![event]).await
+    }
+
+    async fn write_events(&mut self, events: Vec<Event>) -> Result<()> {
+        for event in std::mem::take(&mut self.pending_header) {
+            self.events_writer.send(event).await?;
+        }
+        for event in events {
+            self.events_writer.send(event).await?;
+        }
+        let due = self.auto_flush
+            || self
+                .flush_policy
+                .is_due(self.events_writer.unflushed_bytes(), self.last_flush);
+        if due {
             self.events_writer.flush().await?;
+            self.last_flush = Instant::now();
         }
         Ok(())
     }
 
-    /// Flush this output stream asynchronously.
+    /// Flush by the policy when [auto_flush](EventWriterConfig::auto_flush) is disabled.
+    ///
+    /// The policy is checked when events are written, so an idle writer does not flush
+    /// until it is flushed explicitly.
+    pub fn with_flush_policy(mut self, policy: FlushPolicy) -> Self {
+        self.flush_policy = policy;
+        self
+    }
+
+    /// Aggregate consecutive unchanged scalars by the [ScalarDedup].
+    pub fn with_scalar_dedup(mut self, dedup: ScalarDedup) -> Self {
+        self.scalar_dedup = Some(ScalarDedupState::new(dedup));
+        self
+    }
+
+    /// The counts of scalar points over all tags, or `None` without a [ScalarDedup].
+    pub fn dedup_stats(&self) -> Option<DedupStats> {
+        self.scalar_dedup.as_ref().map(ScalarDedupState::stats)
+    }
+
+    /// The counts of scalar points of the tag.
+    pub fn dedup_stats_for(&self, tag: &str) -> Option<DedupStats> {
+        self.scalar_dedup.as_ref()?.stats_for(tag)
+    }
+
+    /// Flush this output stream asynchronously, writing the last suppressed scalar of
+    /// every tag first.
     pub async fn flush(&mut self) -> Result<()> {
+        for event in std::mem::take(&mut self.pending_header) {
+            self.events_writer.send(event).await?;
+        }
+        if let Some(dedup) = &mut self.scalar_dedup {
+            for event in dedup.take_pending() {
+                self.events_writer.send(event).await?;
+            }
+        }
         self.events_writer.flush().await?;
+        self.last_flush = Instant::now();
         Ok(())
     }
+
+    /// Flush and close the writer.
+    pub async fn finish(mut self) -> Result<()> {
+        self.flush().await
+    }
 }
@@ -8,9 +8,18 @@ mod r#async;
 #[cfg(feature = "async")]
 pub use r#async::*;
 
-use crate::{error::Error, utils};
+use crate::{
+    compression::Compression,
+    error::Error,
+    event::EventClock,
+    inspect::ProducerInfo,
+    protobuf::{event::What, Event},
+    record_writer::RecordWriterConfig,
+    utils,
+};
 use std::{
     borrow::Cow,
+    collections::BTreeMap,
     ffi::{OsStr, OsString},
     path::PathBuf,
     string::ToString,
@@ -18,18 +27,246 @@ use std::{
 };
 
 /// The event writer initializer.
+///
+/// The config is non-exhaustive so that options can be added later. Build it from
+/// [Default] and the `with_*` methods.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct EventWriterConfig {
     /// If set, the writer flushes the buffer after writing a event.
     pub auto_flush: bool,
+    /// The clock that fills the wall time of events without explicit wall time.
+    pub clock: EventClock,
+    /// If set, the file starts with the `file_version: "brain.Event:2"` event, as
+    /// TensorBoard expects of event files.
+    pub file_version: bool,
+    /// If set, the file is stamped with the [ProducerInfo](crate::inspect::ProducerInfo)
+    /// of the writer. See [inspect](crate::inspect) for details.
+    pub stamp_producer: bool,
 }
 
 impl Default for EventWriterConfig {
     fn default() -> Self {
-        Self { auto_flush: true }
+        Self {
+            auto_flush: true,
+            clock: EventClock::default(),
+            file_version: true,
+            stamp_producer: true,
+        }
+    }
+}
+
+impl EventWriterConfig {
+    /// Set [auto_flush](EventWriterConfig::auto_flush).
+    pub fn with_auto_flush(mut self, auto_flush: bool) -> Self {
+        self.auto_flush = auto_flush;
+        self
+    }
+
+    /// Set the [clock](EventWriterConfig::clock).
+    pub fn with_clock(mut self, clock: EventClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Set [file_version](EventWriterConfig::file_version).
+    pub fn with_file_version(mut self, file_version: bool) -> Self {
+        self.file_version = file_version;
+        self
+    }
+
+    /// Set [stamp_producer](EventWriterConfig::stamp_producer).
+    pub fn with_stamp_producer(mut self, stamp_producer: bool) -> Self {
+        self.stamp_producer = stamp_producer;
+        self
+    }
+}
+
+/// The write-time aggregation of consecutive scalar events of the same tag, attached by
+/// [with_scalar_dedup](EventWriter::with_scalar_dedup).
+///
+/// A scalar is suppressed if the [policy](ScalarDedup::policy) finds it unchanged from
+/// the last written value of its tag, with these exceptions, so that plots keep their
+/// shape.
+///
+/// - After [max_gap](ScalarDedup::max_gap) suppressed points in a row, the next point is
+///   written as an anchor.
+/// - When the value changes, the last suppressed point is written before the changed
+///   one, so a step stays sharp.
+/// - Flushing writes the last suppressed point of every tag, so the final value is
+///   never lost. A writer dropped without [flush](EventWriter::flush) or
+///   [finish](EventWriter::finish) loses it.
+///
+/// Other summaries and custom events are written as they come.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScalarDedup {
+    pub policy: DedupPolicy,
+    /// The maximum number of suppressed points in a row.
+    pub max_gap: usize,
+}
+
+/// The condition to suppress a scalar.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DedupPolicy {
+    /// Suppress scalars within the absolute tolerance of the last written value.
+    SkipIfUnchanged { tolerance: f32 },
+}
+
+impl DedupPolicy {
+    fn is_unchanged(&self, last: f32, value: f32) -> bool {
+        match *self {
+            Self::SkipIfUnchanged { tolerance } => {
+                last.to_bits() == value.to_bits() || (value - last).abs() <= tolerance
+            }
+        }
+    }
+}
+
+/// The counts of scalar points seen by a [ScalarDedup].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct DedupStats {
+    /// The number of points written.
+    pub num_written: u64,
+    /// The number of points not written so far, including the pending last points.
+    pub num_suppressed: u64,
+    /// The number of points written as anchors after
+    /// [max_gap](ScalarDedup::max_gap) suppressed points.
+    pub num_anchors: u64,
+}
+
+impl DedupStats {
+    fn add(&mut self, other: &Self) {
+        self.num_written += other.num_written;
+        self.num_suppressed += other.num_suppressed;
+        self.num_anchors += other.num_anchors;
     }
 }
 
+/// The per-tag state of a [ScalarDedup].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ScalarDedupState {
+    dedup: ScalarDedup,
+    tags: BTreeMap<String, TagState>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct TagState {
+    last_written: f32,
+    /// The number of points suppressed since the last written one.
+    gap: usize,
+    /// The value and the event of the last suppressed point.
+    pending: Option<(f32, Event)>,
+    stats: DedupStats,
+}
+
+impl ScalarDedupState {
+    pub(crate) fn new(dedup: ScalarDedup) -> Self {
+        Self {
+            dedup,
+            tags: BTreeMap::new(),
+        }
+    }
+
+    /// Admit a scalar point, returning the events to write in order.
+    pub(crate) fn admit(&mut self, tag: String, value: f32, event: Event) -> Vec<Event> {
+        let ScalarDedup { policy, max_gap } = self.dedup;
+        let state = match self.tags.get_mut(&tag) {
+            Some(state) => state,
+            None => {
+                let stats = DedupStats {
+                    num_written: 1,
+                    ..Default::default()
+                };
+                self.tags.insert(
+                    tag,
+                    TagState {
+                        last_written: value,
+                        gap: 0,
+                        pending: None,
+                        stats,
+                    },
+                );
+                return vec![event];
+            }
+        };
+
+        let mut events = Vec::with_capacity(2);
+        if policy.is_unchanged(state.last_written, value) {
+            if state.gap < max_gap {
+                state.gap += 1;
+                state.pending = Some((value, event));
+                state.stats.num_suppressed += 1;
+                return vec![];
+            }
+            // the anchor supersedes the pending point
+            state.pending = None;
+            state.stats.num_anchors += 1;
+        } else if let Some((_, pending)) = state.pending.take() {
+            // end the plateau at its last point
+            events.push(pending);
+            state.stats.num_suppressed -= 1;
+            state.stats.num_written += 1;
+        }
+        events.push(event);
+        state.stats.num_written += 1;
+        state.last_written = value;
+        state.gap = 0;
+        events
+    }
+
+    /// Take the last suppressed point of every tag, in the order of tags.
+    pub(crate) fn take_pending(&mut self) -> Vec<Event> {
+        self.tags
+            .values_mut()
+            .filter_map(|state| {
+                let (value, pending) = state.pending.take()?;
+                state.stats.num_suppressed -= 1;
+                state.stats.num_written += 1;
+                state.last_written = value;
+                state.gap = 0;
+                Some(pending)
+            })
+            .collect()
+    }
+
+    pub(crate) fn stats(&self) -> DedupStats {
+        let mut total = DedupStats::default();
+        self.tags.values().for_each(|state| total.add(&state.stats));
+        total
+    }
+
+    pub(crate) fn stats_for(&self, tag: &str) -> Option<DedupStats> {
+        self.tags.get(tag).map(|state| state.stats)
+    }
+}
+
+/// The file version of the event files written by event writers.
+pub const FILE_VERSION: &str = "brain.Event:2";
+
+/// Build the leading events of a file, the file version and the producer stamp of the
+/// record writer configuration, as enabled. They share the first reading of the clock.
+fn header_events(
+    clock: &EventClock,
+    file_version: bool,
+    stamp_producer: bool,
+    events_config: &RecordWriterConfig,
+) -> Vec<Event> {
+    if !file_version && !stamp_producer {
+        return vec![];
+    }
+    let wall_time = clock.wall_time();
+    let version = file_version.then(|| Event {
+        wall_time,
+        step: 0,
+        what: Some(What::FileVersion(FILE_VERSION.to_string())),
+    });
+    let stamp = stamp_producer.then(|| {
+        // event files are written uncompressed
+        ProducerInfo::for_writer(events_config, Compression::None).to_event(wall_time)
+    });
+    version.into_iter().chain(stamp).collect()
+}
+
 fn create_tf_style_path<'a, 'b, P, S>(
     prefix: P,
     file_name_suffix: S,
@@ -53,7 +290,10 @@ where
     //     let dir = prefix.parent().unwrap(); // TODO
     //     (dir, file_name_prefix)
     // };
-    let (dir, file_name_prefix) = utils::split_prefix(prefix);
+    let (dir, mut file_name_prefix) = utils::split_prefix(prefix);
+    if file_name_prefix.is_empty() {
+        file_name_prefix = OsString::from("events");
+    }
 
     let timestamp = SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
@@ -1,13 +1,16 @@
-use super::EventWriterConfig;
+use super::{DedupStats, EventWriterConfig, ScalarDedup, ScalarDedupState};
+#[cfg(feature = "with-image")]
+use crate::protobuf_ext::{Box2D, BoxDrawReport, BoxStyle};
 use crate::{
     error::{Error, Result},
-    event::EventMeta,
+    event::{EventClock, EventMeta},
+    memory::MemoryBuffer,
     protobuf::{
         summary::{Audio, Image},
         Event, Summary, TensorProto,
     },
     protobuf_ext::{IntoHistogram, IntoImageList},
-    record_writer::RecordWriter,
+    record_writer::{FlushPolicy, RecordWriter, RecordWriterConfig},
 };
 use std::{
     borrow::Cow,
@@ -17,6 +20,7 @@ use std::{
     io::{BufWriter, Write},
     path::Path,
     string::ToString,
+    time::Instant,
 };
 
 /// The event writer.
@@ -25,7 +29,6 @@ use std::{
 ///
 /// It can be built from a writer using [from_writer](EventWriter::from_writer), or write a new file
 /// specified by path prefix using [from_writer](EventWriter::from_prefix).
-
 #[cfg_attr(
     feature = "tch",
     doc = r##"
@@ -34,7 +37,7 @@ use std::{
 use anyhow::Result;
 use std::time::SystemTime;
 use tch::{kind::FLOAT_CPU, Tensor};
-use tfrecord::EventWriter;
+use tfrecord::prelude::*;
 
 let mut writer = EventWriter::from_prefix("log_dir/myprefix-", "", Default::default()).unwrap();
 
@@ -58,11 +61,19 @@ writer.write_tensor(
 #[derive(Debug, Clone, PartialEq)]
 pub struct EventWriter<W> {
     auto_flush: bool,
+    flush_policy: FlushPolicy,
+    last_flush: Instant,
+    clock: EventClock,
     events_writer: RecordWriter<Event, W>,
+    scalar_dedup: Option<ScalarDedupState>,
 }
 
 impl EventWriter<BufWriter<File>> {
     /// Build a writer writing events to a file.
+    ///
+    /// A file without events holds the file version and the producer stamp only, or is
+    /// empty if both [file_version](EventWriterConfig::file_version) and
+    /// [stamp_producer](EventWriterConfig::stamp_producer) are disabled.
     pub fn create<P>(path: P, config: EventWriterConfig) -> Result<Self>
     where
         P: AsRef<Path>,
@@ -72,6 +83,11 @@ impl EventWriter<BufWriter<File>> {
     }
 
     /// Build a writer writing events to a file, which path is specified by a path prefix and file name suffix.
+    ///
+    /// The file is named `<prefix>.out.tfevents.<unix_time>.<hostname><suffix>` as by
+    /// TensorFlow, where the prefix defaults to `events` for a directory prefix such as
+    /// `log_dir/`. The time is in microseconds, so that writers created in the same
+    /// second do not overwrite each other.
     pub fn from_prefix<'a, 'b, P, S>(
         prefix: P,
         file_name_suffix: S,
@@ -88,6 +104,18 @@ impl EventWriter<BufWriter<File>> {
     }
 }
 
+impl EventWriter<BufWriter<MemoryBuffer>> {
+    /// Build a writer writing events to memory, and the handle to the written bytes.
+    ///
+    /// The bytes become visible in the [MemoryBuffer] after [flush](EventWriter::flush)
+    /// or drop, as they do in a file.
+    pub fn in_memory(config: EventWriterConfig) -> Result<(Self, MemoryBuffer)> {
+        let buffer = MemoryBuffer::new();
+        let writer = Self::from_writer(BufWriter::new(buffer.clone()), config)?;
+        Ok((writer, buffer))
+    }
+}
+
 impl<W> EventWriter<W>
 where
     W: Write,
@@ -97,12 +125,27 @@ where
     where
         W: Write,
     {
-        let EventWriterConfig { auto_flush } = config;
+        let EventWriterConfig {
+            auto_flush,
+            clock,
+            file_version,
+            stamp_producer,
+        } = config;
+        let events_config = RecordWriterConfig::default();
+        let header = super::header_events(&clock, file_version, stamp_producer, &events_config);
 
-        Ok(Self {
+        let mut event_writer = Self {
             auto_flush,
-            events_writer: RecordWriter::from_writer(writer)?,
-        })
+            flush_policy: FlushPolicy::default(),
+            last_flush: Instant::now(),
+            clock,
+            events_writer: RecordWriter::from_writer_with_config(writer, events_config)?,
+            scalar_dedup: None,
+        };
+        for event in header {
+            event_writer.write_event(event)?;
+        }
+        Ok(event_writer)
     }
 
     /// Write a scalar summary.
@@ -112,12 +155,20 @@ where
         event_meta: impl Into<EventMeta>,
         value: f32,
     ) -> Result<()> {
-        let summary = Summary::from_scalar(tag, value)?;
-        let event = event_meta.into().build_with_summary(summary);
-        self.events_writer.send(event)?;
-        if self.auto_flush {
-            self.events_writer.flush()?;
+        let tag = tag.to_string();
+        let summary = Summary::from_scalar(&tag, value)?;
+        let event = event_meta
+            .into()
+            .or_wall_time_from(&self.clock)
+            .build_with_summary(summary);
+        let events = match &mut self.scalar_dedup {
+            Some(dedup) => dedup.admit(tag, value, event),
+            None => vec![event],
+        };
+        for event in events {
+            self.events_writer.send(event)?;
         }
+        self.flush_if_due()?;
         Ok(())
     }
 
@@ -129,11 +180,12 @@ where
         histogram: impl IntoHistogram,
     ) -> Result<()> {
         let summary = Summary::from_histogram(tag, histogram)?;
-        let event = event_meta.into().build_with_summary(summary);
+        let event = event_meta
+            .into()
+            .or_wall_time_from(&self.clock)
+            .build_with_summary(summary);
         self.events_writer.send(event)?;
-        if self.auto_flush {
-            self.events_writer.flush()?;
-        }
+        self.flush_if_due()?;
         Ok(())
     }
 
@@ -145,11 +197,12 @@ where
         tensor: impl TryInto<TensorProto, Error = impl Into<Error>>,
     ) -> Result<()> {
         let summary = Summary::from_tensor(tag, tensor)?;
-        let event = event_meta.into().build_with_summary(summary);
+        let event = event_meta
+            .into()
+            .or_wall_time_from(&self.clock)
+            .build_with_summary(summary);
         self.events_writer.send(event)?;
-        if self.auto_flush {
-            self.events_writer.flush()?;
-        }
+        self.flush_if_due()?;
         Ok(())
     }
 
@@ -161,14 +214,38 @@ where
         image: impl TryInto<Image, Error = impl Into<Error>>,
     ) -> Result<()> {
         let summary = Summary::from_image(tag, image)?;
-        let event = event_meta.into().build_with_summary(summary);
+        let event = event_meta
+            .into()
+            .or_wall_time_from(&self.clock)
+            .build_with_summary(summary);
         self.events_writer.send(event)?;
-        if self.auto_flush {
-            self.events_writer.flush()?;
-        }
+        self.flush_if_due()?;
         Ok(())
     }
 
+    /// Write an image summary with boxes drawn onto the image.
+    ///
+    /// The report counts the boxes clamped to or outside of the image bounds. See
+    /// [draw_boxes](crate::protobuf_ext::draw_boxes) for details.
+    #[cfg(feature = "with-image")]
+    pub fn write_image_with_boxes(
+        &mut self,
+        tag: impl ToString,
+        event_meta: impl Into<EventMeta>,
+        image: &image::DynamicImage,
+        boxes: &[Box2D],
+        style: &BoxStyle,
+    ) -> Result<BoxDrawReport> {
+        let (summary, report) = Summary::from_image_with_boxes(tag, image, boxes, style)?;
+        let event = event_meta
+            .into()
+            .or_wall_time_from(&self.clock)
+            .build_with_summary(summary);
+        self.events_writer.send(event)?;
+        self.flush_if_due()?;
+        Ok(report)
+    }
+
     /// Write a summary with multiple images.
     pub fn write_image_list(
         &mut self,
@@ -177,11 +254,12 @@ where
         images: impl IntoImageList,
     ) -> Result<()> {
         let summary = Summary::from_image_list(tag, images)?;
-        let event = event_meta.into().build_with_summary(summary);
+        let event = event_meta
+            .into()
+            .or_wall_time_from(&self.clock)
+            .build_with_summary(summary);
         self.events_writer.send(event)?;
-        if self.auto_flush {
-            self.events_writer.flush()?;
-        }
+        self.flush_if_due()?;
         Ok(())
     }
 
@@ -193,11 +271,12 @@ where
         audio: impl TryInto<Audio, Error = impl Into<Error>>,
     ) -> Result<()> {
         let summary = Summary::from_audio(tag, audio)?;
-        let event = event_meta.into().build_with_summary(summary);
+        let event = event_meta
+            .into()
+            .or_wall_time_from(&self.clock)
+            .build_with_summary(summary);
         self.events_writer.send(event)?;
-        if self.auto_flush {
-            self.events_writer.flush()?;
-        }
+        self.flush_if_due()?;
         Ok(())
     }
 
@@ -210,15 +289,82 @@ where
     /// Write a custom event.
     pub fn write_event(&mut self, event: Event) -> Result<()> {
         self.events_writer.send(event)?;
-        if self.auto_flush {
-            self.events_writer.flush()?;
-        }
+        self.flush_if_due()?;
         Ok(())
     }
 
-    /// Flush this output stream.
+    /// Aggregate consecutive unchanged scalars by the [ScalarDedup].
+    ///
+    /// ```rust
+    /// # fn main() -> tfrecord::Result<()> {
+    /// use tfrecord::{DedupPolicy, EventWriter, ScalarDedup};
+    ///
+    /// let (writer, _buffer) = EventWriter::in_memory(Default::default())?;
+    /// let mut writer = writer.with_scalar_dedup(ScalarDedup {
+    ///     policy: DedupPolicy::SkipIfUnchanged { tolerance: 0.0 },
+    ///     max_gap: 100,
+    /// });
+    /// for step in 0..10 {
+    ///     writer.write_scalar("lr", step, 0.1)?;
+    /// }
+    /// writer.flush()?;
+    ///
+    /// // the first and the final points are written
+    /// let stats = writer.dedup_stats().unwrap();
+    /// assert_eq!((stats.num_written, stats.num_suppressed), (2, 8));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_scalar_dedup(mut self, dedup: ScalarDedup) -> Self {
+        self.scalar_dedup = Some(ScalarDedupState::new(dedup));
+        self
+    }
+
+    /// The counts of scalar points over all tags, or `None` without a [ScalarDedup].
+    pub fn dedup_stats(&self) -> Option<DedupStats> {
+        self.scalar_dedup.as_ref().map(ScalarDedupState::stats)
+    }
+
+    /// The counts of scalar points of the tag.
+    pub fn dedup_stats_for(&self, tag: &str) -> Option<DedupStats> {
+        self.scalar_dedup.as_ref()?.stats_for(tag)
+    }
+
+    /// Flush by the policy when [auto_flush](EventWriterConfig::auto_flush) is disabled.
+    ///
+    /// The policy is checked when events are written, so an idle writer does not flush
+    /// until it is flushed explicitly.
+    pub fn with_flush_policy(mut self, policy: FlushPolicy) -> Self {
+        self.flush_policy = policy;
+        self
+    }
+
+    /// Flush this output stream, writing the last suppressed scalar of every tag first.
     pub fn flush(&mut self) -> Result<()> {
+        if let Some(dedup) = &mut self.scalar_dedup {
+            for event in dedup.take_pending() {
+                self.events_writer.send(event)?;
+            }
+        }
         self.events_writer.flush()?;
+        self.last_flush = Instant::now();
+        Ok(())
+    }
+
+    fn flush_if_due(&mut self) -> Result<()> {
+        let due = self.auto_flush
+            || self
+                .flush_policy
+                .is_due(self.events_writer.unflushed_bytes(), self.last_flush);
+        if due {
+            self.events_writer.flush()?;
+            self.last_flush = Instant::now();
+        }
         Ok(())
     }
+
+    /// Flush and close the writer.
+    pub fn finish(mut self) -> Result<()> {
+        self.flush()
+    }
 }
@@ -0,0 +1,319 @@
+//! Export scalar series in TensorBoard's CSV and JSON formats.
+//!
+//! The layouts follow the downloads of the TensorBoard scalars dashboard.
+//! - CSV: the `Wall time,Step,Value` columns per tag, or with an extra leading `Tag`
+//!   column in the [long](CsvLayout::Long) layout holding several tags in one file.
+//! - JSON: an array of `[wall_time, step, value]` triples per tag.
+//!
+//! Numbers are written in the shortest representation that parses back to the same
+//! value, so series round-trip exactly. Non-finite values are written as `NaN`,
+//! `Infinity` and `-Infinity`.
+
+use crate::{
+    error::{Error, Result},
+    event_reader,
+    protobuf::Event,
+};
+use std::io::{prelude::*, BufReader};
+
+const CSV_HEADER: &str = "Wall time,Step,Value";
+const CSV_LONG_HEADER: &str = "Tag,Wall time,Step,Value";
+
+/// A scalar value at a step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScalarPoint {
+    /// The wall time in seconds since UNIX epoch.
+    pub wall_time: f64,
+    pub step: i64,
+    pub value: f32,
+}
+
+/// The scalar values of a tag.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ScalarSeries {
+    pub tag: String,
+    pub points: Vec<ScalarPoint>,
+}
+
+impl ScalarSeries {
+    /// Collect scalar summaries from events, one series per tag in the order of first appearance.
+    ///
+    /// Scalars are read from `simple_value` and from rank-0 `DT_FLOAT` tensors.
+    pub fn from_events<I>(events: I) -> Result<Vec<Self>>
+    where
+        I: IntoIterator<Item = Result<Event>>,
+    {
+        let mut series: Vec<Self> = vec![];
+
+        for event in events {
+            let event = event?;
+            let summary = match event.what {
+                Some(crate::protobuf::event::What::Summary(summary)) => summary,
+                _ => continue,
+            };
+
+            for value in summary.value {
+                let scalar = match event_reader::scalar_value(&value) {
+                    Some(scalar) => scalar,
+                    None => continue,
+                };
+                let point = ScalarPoint {
+                    wall_time: event.wall_time,
+                    step: event.step,
+                    value: scalar,
+                };
+
+                match series.iter_mut().find(|series| series.tag == value.tag) {
+                    Some(series) => series.points.push(point),
+                    None => series.push(Self {
+                        tag: value.tag,
+                        points: vec![point],
+                    }),
+                }
+            }
+        }
+
+        Ok(series)
+    }
+}
+
+/// The CSV layout for [scalars_to_csv].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CsvLayout {
+    /// The `Wall time,Step,Value` columns for exactly one tag.
+    PerTag,
+    /// The `Tag,Wall time,Step,Value` columns for any number of tags.
+    Long,
+}
+
+/// Write scalar series in CSV format.
+pub fn scalars_to_csv<W>(series: &[ScalarSeries], layout: CsvLayout, mut writer: W) -> Result<()>
+where
+    W: Write,
+{
+    match layout {
+        CsvLayout::PerTag => {
+            let series = match series {
+                [series] => series,
+                _ => {
+                    return Err(Error::invalid_argument(format!(
+                        "the per-tag CSV layout expects exactly one series, but found {}",
+                        series.len()
+                    )))
+                }
+            };
+            writeln!(writer, "{}", CSV_HEADER)?;
+            for point in &series.points {
+                writeln!(writer, "{}", format_point(point))?;
+            }
+        }
+        CsvLayout::Long => {
+            writeln!(writer, "{}", CSV_LONG_HEADER)?;
+            for series in series {
+                let tag = quote_csv(&series.tag);
+                for point in &series.points {
+                    writeln!(writer, "{},{}", tag, format_point(point))?;
+                }
+            }
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write a scalar series in JSON format.
+pub fn scalars_to_json<W>(series: &ScalarSeries, mut writer: W) -> Result<()>
+where
+    W: Write,
+{
+    let triples: Vec<_> = series
+        .points
+        .iter()
+        .map(|point| format!("[{}]", format_point(point)))
+        .collect();
+    writeln!(writer, "[{}]", triples.join(","))?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Read scalar series from CSV in either layout.
+///
+/// The tag of the series in the [per-tag](CsvLayout::PerTag) layout is left empty.
+pub fn series_from_csv<R>(reader: R) -> Result<Vec<ScalarSeries>>
+where
+    R: Read,
+{
+    let mut lines = BufReader::new(reader).lines();
+    let header = lines
+        .next()
+        .transpose()?
+        .ok_or_else(|| Error::conversion("the CSV is empty"))?;
+    let layout = match header.trim_end() {
+        CSV_HEADER => CsvLayout::PerTag,
+        CSV_LONG_HEADER => CsvLayout::Long,
+        header => {
+            return Err(Error::conversion(format!(
+                "unrecognized CSV header '{}'",
+                header
+            )))
+        }
+    };
+
+    let mut series: Vec<ScalarSeries> = match layout {
+        CsvLayout::PerTag => vec![ScalarSeries::default()],
+        CsvLayout::Long => vec![],
+    };
+
+    for (index, line) in lines.enumerate() {
+        let line = line?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            continue;
+        }
+        let line_no = index + 2;
+
+        let (tag, rest) = match layout {
+            CsvLayout::PerTag => (None, line),
+            CsvLayout::Long => {
+                let (tag, rest) = unquote_csv(line)
+                    .ok_or_else(|| Error::conversion(format!("invalid tag at line {}", line_no)))?;
+                (Some(tag), rest)
+            }
+        };
+        let point = parse_point(rest.split(','))
+            .ok_or_else(|| Error::conversion(format!("invalid values at line {}", line_no)))?;
+
+        let series = match tag {
+            None => &mut series[0],
+            Some(tag) => match series.iter().position(|series| series.tag == tag) {
+                Some(position) => &mut series[position],
+                None => {
+                    series.push(ScalarSeries {
+                        tag,
+                        points: vec![],
+                    });
+                    series.last_mut().unwrap()
+                }
+            },
+        };
+        series.points.push(point);
+    }
+
+    Ok(series)
+}
+
+/// Read a scalar series from JSON.
+pub fn series_from_json<R>(mut reader: R, tag: impl Into<String>) -> Result<ScalarSeries>
+where
+    R: Read,
+{
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    let text: String = text.chars().filter(|ch| !ch.is_whitespace()).collect();
+
+    let inner = text
+        .strip_prefix('[')
+        .and_then(|text| text.strip_suffix(']'))
+        .ok_or_else(|| Error::conversion("the JSON is not an array"))?;
+    let points = if inner.is_empty() {
+        vec![]
+    } else {
+        inner
+            .strip_prefix('[')
+            .and_then(|inner| inner.strip_suffix(']'))
+            .ok_or_else(|| Error::conversion("the JSON is not an array of triples"))?
+            .split("],[")
+            .enumerate()
+            .map(|(index, triple)| {
+                parse_point(triple.split(','))
+                    .ok_or_else(|| Error::conversion(format!("invalid triple at index {}", index)))
+            })
+            .collect::<Result<_>>()?
+    };
+
+    Ok(ScalarSeries {
+        tag: tag.into(),
+        points,
+    })
+}
+
+fn format_point(point: &ScalarPoint) -> String {
+    let ScalarPoint {
+        wall_time,
+        step,
+        value,
+    } = *point;
+    format!(
+        "{},{},{}",
+        format_float(wall_time),
+        step,
+        format_float(value)
+    )
+}
+
+/// Format in the shortest round-trip representation.
+fn format_float<F>(value: F) -> String
+where
+    F: num_traits::Float + std::fmt::Display,
+{
+    if value.is_nan() {
+        "NaN".into()
+    } else if value.is_infinite() {
+        if value.is_sign_positive() {
+            "Infinity".into()
+        } else {
+            "-Infinity".into()
+        }
+    } else {
+        value.to_string()
+    }
+}
+
+fn parse_point<'a>(mut fields: impl Iterator<Item = &'a str>) -> Option<ScalarPoint> {
+    let wall_time = fields.next()?.parse().ok()?;
+    let step = fields.next()?.parse().ok()?;
+    let value = fields.next()?.parse().ok()?;
+    if fields.next().is_some() {
+        return None;
+    }
+    Some(ScalarPoint {
+        wall_time,
+        step,
+        value,
+    })
+}
+
+fn quote_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Split the leading, possibly quoted, field from a CSV line.
+fn unquote_csv(line: &str) -> Option<(String, &str)> {
+    match line.strip_prefix('"') {
+        Some(rest) => {
+            let mut field = String::new();
+            let mut chars = rest.char_indices();
+            while let Some((index, ch)) = chars.next() {
+                if ch != '"' {
+                    field.push(ch);
+                } else if rest[index + 1..].starts_with('"') {
+                    field.push('"');
+                    chars.next();
+                } else {
+                    return rest[index + 1..]
+                        .strip_prefix(',')
+                        .map(|rest| (field, rest));
+                }
+            }
+            None
+        }
+        None => {
+            let (field, rest) = line.split_once(',')?;
+            Some((field.to_string(), rest))
+        }
+    }
+}
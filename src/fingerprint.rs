@@ -0,0 +1,428 @@
+//! Fingerprint datasets for the invalidation of derived artifacts.
+//!
+//! A [Fingerprint] identifies the input dataset of a cached artifact, such as a
+//! vocabulary or statistics computed from the records. Four [levels](FingerprintLevel)
+//! trade the cost of computation for the strength of the identity.
+//!
+//! - [Metadata](FingerprintLevel::Metadata) hashes the canonical paths, sizes and
+//!   modification times of files. It reads no file contents.
+//! - [Layout](FingerprintLevel::Layout) hashes the sizes and the record indexes like
+//!   [Structure](FingerprintLevel::Structure), but excludes paths, so it survives
+//!   moving the dataset to another location.
+//! - [Structure](FingerprintLevel::Structure) hashes the canonical paths, sizes and the
+//!   record indexes, namely the number, offsets and lengths of records. It requires
+//!   the indexes but reads no payloads. Modification times are excluded, so touching
+//!   a file keeps the fingerprint.
+//! - [Content](FingerprintLevel::Content) hashes the payloads of all records with XXH3.
+//!   Files are hashed in parallel. Paths are excluded, so a copy of the dataset at
+//!   another location has the same fingerprint.
+//!   Payloads are hashed as stored, so floats are identified
+//!   [bitwise](crate::FloatIdentity::Bitwise), and records differing only in NaN
+//!   payloads or the sign of zero have different fingerprints.
+//!
+//! The digest embeds the level it was computed at, so fingerprints of different levels
+//! never collide. A fingerprint is formatted as 32 hexadecimal digits, which can be
+//! stored along with an artifact and parsed back to compare with the current one.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{
+//!     fingerprint::{self, Fingerprint, FingerprintLevel},
+//!     samples,
+//! };
+//!
+//! let dataset = samples::tiny_dataset(2, 8)?;
+//! let current = fingerprint::fingerprint_paths(
+//!     dataset.paths(),
+//!     FingerprintLevel::Structure,
+//!     Default::default(),
+//! )?;
+//! # let stored_path = dataset.dir().join("vocab.fingerprint");
+//! # std::fs::write(&stored_path, current.to_string())?;
+//! let stored: Fingerprint = std::fs::read_to_string(stored_path)?.trim().parse()?;
+//! if stored != current {
+//!     // rebuild the vocabulary
+//! }
+//! # assert_eq!(stored, current);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    cancel::{self, CancelFlag, Progress, CHECK_INTERVAL},
+    error::{Error, Result},
+    indexer::{self, Position, RecordIndex, RecordIndexerConfig},
+};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt,
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::atomic::{AtomicU64, Ordering},
+    time::UNIX_EPOCH,
+};
+use xxhash_rust::xxh3::Xxh3;
+
+/// The domain separator of fingerprint digests.
+const DOMAIN: &[u8] = b"tfrecord-fingerprint-v1";
+
+/// The thoroughness of a [Fingerprint].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FingerprintLevel {
+    /// Hash the paths, sizes and modification times of files.
+    Metadata,
+    /// Hash the sizes of files and the record indexes.
+    Layout,
+    /// Hash the paths and sizes of files, and the record indexes.
+    Structure,
+    /// Hash the payloads of records.
+    Content,
+}
+
+impl FingerprintLevel {
+    fn tag(self) -> u8 {
+        match self {
+            Self::Metadata => 1,
+            Self::Structure => 2,
+            Self::Content => 3,
+            Self::Layout => 4,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        Some(match tag {
+            1 => Self::Metadata,
+            2 => Self::Structure,
+            3 => Self::Content,
+            4 => Self::Layout,
+            _ => return None,
+        })
+    }
+}
+
+/// The 16-byte digest identifying a dataset.
+///
+/// The first byte of the digest encodes the level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fingerprint {
+    digest: [u8; 16],
+}
+
+impl Fingerprint {
+    /// The level the fingerprint was computed at.
+    pub fn level(&self) -> FingerprintLevel {
+        FingerprintLevel::from_tag(self.digest[0]).unwrap()
+    }
+
+    /// The digest bytes.
+    pub fn digest(&self) -> [u8; 16] {
+        self.digest
+    }
+
+    fn finish(level: FingerprintLevel, hasher: Xxh3) -> Self {
+        let mut digest = hasher.digest128().to_le_bytes();
+        digest[0] = level.tag();
+        Self { digest }
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.digest
+            .iter()
+            .try_for_each(|byte| write!(f, "{:02x}", byte))
+    }
+}
+
+impl FromStr for Fingerprint {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let invalid = || Error::conversion(format!("invalid fingerprint '{}'", text));
+        if text.len() != 32 || !text.is_ascii() {
+            return Err(invalid());
+        }
+
+        let mut digest = [0u8; 16];
+        for (byte, hex) in digest.iter_mut().zip(text.as_bytes().chunks(2)) {
+            let hex = std::str::from_utf8(hex).unwrap();
+            *byte = u8::from_str_radix(hex, 16).map_err(|_| invalid())?;
+        }
+        FingerprintLevel::from_tag(digest[0]).ok_or_else(invalid)?;
+        Ok(Self { digest })
+    }
+}
+
+#[cfg(feature = "with-serde")]
+impl serde::Serialize for Fingerprint {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "with-serde")]
+impl<'de> serde::Deserialize<'de> for Fingerprint {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let text = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Compute the fingerprint of the files of record indexes.
+///
+/// The files are visited in the order of appearance in the indexes. Files without
+/// records do not appear in indexes, and are not part of the fingerprint.
+pub fn fingerprint(indexes: &[RecordIndex], level: FingerprintLevel) -> Result<Fingerprint> {
+    fingerprint_files(&group_files(indexes), level, None)
+}
+
+/// Compute the fingerprint of each file of record indexes in the order of appearance.
+///
+/// The fingerprint of a file equals the [fingerprint] of its indexes alone. The files
+/// are hashed in parallel at the [Content](FingerprintLevel::Content) level.
+pub fn fingerprint_each(
+    indexes: &[RecordIndex],
+    level: FingerprintLevel,
+) -> Result<Vec<Fingerprint>> {
+    let files = group_files(indexes);
+    match level {
+        FingerprintLevel::Content => {
+            let digests = content_digests(&files, None)?;
+            Ok(digests
+                .into_iter()
+                .map(|digest| {
+                    let mut hasher = new_hasher(level, 1);
+                    hasher.update(&digest.to_le_bytes());
+                    Fingerprint::finish(level, hasher)
+                })
+                .collect())
+        }
+        FingerprintLevel::Metadata | FingerprintLevel::Layout | FingerprintLevel::Structure => {
+            files
+                .iter()
+                .map(|file| fingerprint_files(std::slice::from_ref(file), level, None))
+                .collect()
+        }
+    }
+}
+
+/// Group record indexes by files in the order of appearance.
+fn group_files(indexes: &[RecordIndex]) -> Vec<FileRecords> {
+    let mut files: Vec<FileRecords> = vec![];
+    let mut file_indexes: HashMap<&Path, usize> = HashMap::new();
+    for index in indexes {
+        let file_index = *file_indexes.entry(&index.path).or_insert_with(|| {
+            files.push(FileRecords {
+                path: (*index.path).clone(),
+                positions: vec![],
+            });
+            files.len() - 1
+        });
+        files[file_index].positions.push(Position {
+            offset: index.offset,
+            len: index.len,
+        });
+    }
+    files
+}
+
+/// Compute the fingerprint of files.
+///
+/// The files are visited in the [path order](RecordIndexerConfig::path_order) of the
+/// configuration. The files are indexed for all levels but
+/// [Metadata](FingerprintLevel::Metadata).
+pub fn fingerprint_paths<'a, P, I>(
+    paths: I,
+    level: FingerprintLevel,
+    config: RecordIndexerConfig,
+) -> Result<Fingerprint>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Cow<'a, Path>>,
+{
+    let paths: Vec<PathBuf> = paths
+        .into_iter()
+        .map(|path| path.into().into_owned())
+        .collect();
+    let paths = indexer::sort_paths(paths, config.path_order)?;
+
+    let mut num_records = 0;
+    let files: Vec<FileRecords> = paths
+        .into_iter()
+        .enumerate()
+        .map(|(files_done, path)| {
+            let files_done = files_done as u64;
+            cancel::check(config.cancel.as_ref(), files_done, num_records)?;
+            let positions: Vec<_> = match level {
+                FingerprintLevel::Metadata => vec![],
+                FingerprintLevel::Layout
+                | FingerprintLevel::Structure
+                | FingerprintLevel::Content => {
+                    let reader = BufReader::new(File::open(&path)?);
+                    indexer::load_reader(reader, config.clone())
+                        .collect::<Result<_>>()
+                        .map_err(|err| err.after_progress(files_done, num_records))?
+                }
+            };
+            num_records += positions.len() as u64;
+            Ok(FileRecords { path, positions })
+        })
+        .collect::<Result<_>>()?;
+
+    // hashing payloads is a second pass over the files
+    let cancel = config.cancel.as_ref();
+    fingerprint_files(&files, level, cancel)
+        .map_err(|err| err.after_progress(files.len() as u64, num_records))
+}
+
+/// A file and the positions of its records.
+struct FileRecords {
+    path: PathBuf,
+    positions: Vec<Position>,
+}
+
+fn fingerprint_files(
+    files: &[FileRecords],
+    level: FingerprintLevel,
+    cancel: Option<&CancelFlag>,
+) -> Result<Fingerprint> {
+    let mut hasher = new_hasher(level, files.len());
+    match level {
+        FingerprintLevel::Metadata => {
+            for file in files {
+                let metadata = std::fs::metadata(&file.path)?;
+                let modified = metadata
+                    .modified()?
+                    .duration_since(UNIX_EPOCH)
+                    .map(|elapsed| elapsed.as_nanos())
+                    .unwrap_or(0);
+                update_path(&mut hasher, &file.path)?;
+                hasher.update(&metadata.len().to_le_bytes());
+                hasher.update(&modified.to_le_bytes());
+            }
+        }
+        FingerprintLevel::Layout | FingerprintLevel::Structure => {
+            for file in files {
+                if level == FingerprintLevel::Structure {
+                    update_path(&mut hasher, &file.path)?;
+                }
+                hasher.update(&std::fs::metadata(&file.path)?.len().to_le_bytes());
+                hasher.update(&(file.positions.len() as u64).to_le_bytes());
+                for &Position { offset, len } in &file.positions {
+                    hasher.update(&offset.to_le_bytes());
+                    hasher.update(&(len as u64).to_le_bytes());
+                }
+            }
+        }
+        FingerprintLevel::Content => {
+            for digest in content_digests(files, cancel)? {
+                hasher.update(&digest.to_le_bytes());
+            }
+        }
+    }
+
+    Ok(Fingerprint::finish(level, hasher))
+}
+
+fn new_hasher(level: FingerprintLevel, num_files: usize) -> Xxh3 {
+    let mut hasher = Xxh3::new();
+    hasher.update(DOMAIN);
+    hasher.update(&[level.tag()]);
+    hasher.update(&(num_files as u64).to_le_bytes());
+    hasher
+}
+
+fn update_path(hasher: &mut Xxh3, path: &Path) -> Result<()> {
+    let path = std::fs::canonicalize(path)?;
+    let path = path.to_string_lossy();
+    hasher.update(&(path.len() as u64).to_le_bytes());
+    hasher.update(path.as_bytes());
+    Ok(())
+}
+
+/// Hash the payloads of files in parallel, returning the digests in the order of files.
+fn content_digests(files: &[FileRecords], cancel: Option<&CancelFlag>) -> Result<Vec<u128>> {
+    let num_workers = std::thread::available_parallelism()
+        .map(|num| num.get())
+        .unwrap_or(1)
+        .min(files.len())
+        .max(1);
+    let chunk_size = files.len().div_ceil(num_workers).max(1);
+    let files_done = AtomicU64::new(0);
+    let num_records = AtomicU64::new(0);
+    let progress = || {
+        Progress::new(
+            files_done.load(Ordering::Relaxed),
+            num_records.load(Ordering::Relaxed),
+        )
+    };
+
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = files
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let progress = &progress;
+                let files_done = &files_done;
+                let num_records = &num_records;
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|file| {
+                            let digest = content_digest(file, || {
+                                let records = num_records.fetch_add(1, Ordering::Relaxed);
+                                match cancel {
+                                    Some(cancel) if records.is_multiple_of(CHECK_INTERVAL) => {
+                                        cancel.check(progress)
+                                    }
+                                    _ => Ok(()),
+                                }
+                            })?;
+                            files_done.fetch_add(1, Ordering::Relaxed);
+                            Ok(digest)
+                        })
+                        .collect::<Result<Vec<_>>>()
+                })
+            })
+            .collect();
+        let mut digests = Vec::with_capacity(files.len());
+        for worker in workers {
+            digests.extend(worker.join().expect("the hashing thread panicked")?);
+        }
+        Ok(digests)
+    })
+}
+
+/// Hash the payloads of a file, calling `on_record` before reading each record.
+fn content_digest<F>(file: &FileRecords, mut on_record: F) -> Result<u128>
+where
+    F: FnMut() -> Result<()>,
+{
+    let mut reader = BufReader::new(File::open(&file.path)?);
+    let mut hasher = Xxh3::new();
+    let mut buf = vec![];
+    let mut cursor = 0u64;
+    hasher.update(&(file.positions.len() as u64).to_le_bytes());
+
+    for &Position { offset, len } in &file.positions {
+        on_record()?;
+        if offset != cursor {
+            reader.seek(SeekFrom::Start(offset))?;
+        }
+        buf.resize(len, 0);
+        reader.read_exact(&mut buf)?;
+        cursor = offset + len as u64;
+
+        hasher.update(&(len as u64).to_le_bytes());
+        hasher.update(&buf);
+    }
+    Ok(hasher.digest128())
+}
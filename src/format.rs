@@ -0,0 +1,173 @@
+//! Detect the formats of files and the capabilities required to read them.
+//!
+//! A directory of shards may mix plain TFRecord files with files which this build
+//! cannot index directly, such as encrypted or compressed files. The format of a file
+//! is detected from its leading bytes by [detect_format], in every build regardless of
+//! the enabled features.
+//!
+//! A plain TFRecord file is recognized by the checksum of its first length header, so
+//! a record length coincidentally starting with the magic bytes of another format is
+//! never misdetected. Files which are neither plain nor of a known format are treated as
+//! plain, leaving the corruption to be reported by the reader.
+//!
+//! GZIP-compressed files are read through [compression](crate::compression) when it is
+//! configured. Otherwise, the indexer rejects files of unsupported formats with
+//! [Error::Unsupported](crate::Error::Unsupported), or skips them if
+//! [skip_unsupported](crate::indexer::RecordIndexerConfig::skip_unsupported) is set.
+
+use crate::error::{Error, Result};
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{prelude::*, SeekFrom},
+    path::Path,
+};
+
+/// The magic bytes of encrypted files.
+pub(crate) const ENCRYPTED_MAGIC: &[u8; 8] = b"TFRECENC";
+/// The framing version of encrypted files.
+pub(crate) const ENCRYPTED_VERSION: u8 = 1;
+
+/// The number of leading bytes inspected, which covers the first length header of
+/// plain files.
+const HEADER_LEN: u64 = 12;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// The format of a file detected from its leading bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileFormat {
+    /// A plain TFRecord file, including empty files.
+    Plain,
+    /// A file written by [EncryptedWriter](crate::encryption::EncryptedWriter).
+    Encrypted {
+        /// The framing version in the header.
+        version: u8,
+    },
+    /// A GZIP-compressed file.
+    Gzip,
+    /// A Zstandard-compressed file.
+    Zstd,
+}
+
+impl FileFormat {
+    /// The capability missing to index the file directly, or `None` for plain files.
+    pub fn unsupported(&self) -> Option<Unsupported> {
+        let unsupported = match *self {
+            Self::Plain => return None,
+            Self::Encrypted { version } if version != ENCRYPTED_VERSION => Unsupported {
+                capability: format!("encryption framing version {}", version).into(),
+                feature: None,
+            },
+            Self::Encrypted { .. } if cfg!(feature = "encryption") => Unsupported {
+                capability: "encryption, which must be read through encryption::EncryptedReader"
+                    .into(),
+                feature: None,
+            },
+            Self::Encrypted { .. } => Unsupported {
+                capability: "encryption".into(),
+                feature: Some("encryption"),
+            },
+            Self::Gzip if cfg!(feature = "gzip") => Unsupported {
+                capability: "GZIP compression, which must be read with compression set to Gzip"
+                    .into(),
+                feature: None,
+            },
+            Self::Gzip => Unsupported {
+                capability: "GZIP compression".into(),
+                feature: Some("gzip"),
+            },
+            Self::Zstd => Unsupported {
+                capability: "Zstandard compression".into(),
+                feature: None,
+            },
+        };
+        Some(unsupported)
+    }
+}
+
+/// A capability required by a file but missing in this build.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Unsupported {
+    /// The description of the capability.
+    pub capability: Cow<'static, str>,
+    /// The cargo feature providing the capability, if any.
+    pub feature: Option<&'static str>,
+}
+
+impl Unsupported {
+    pub(crate) fn into_error(self) -> Error {
+        let Self {
+            capability,
+            feature,
+        } = self;
+        Error::Unsupported {
+            capability,
+            feature,
+        }
+    }
+}
+
+/// Detect the format of a stream from its leading bytes.
+///
+/// The stream is rewound to the start afterwards.
+pub fn detect_format<R>(mut reader: R) -> Result<FileFormat>
+where
+    R: Read + Seek,
+{
+    reader.seek(SeekFrom::Start(0))?;
+    let mut header = vec![];
+    reader.by_ref().take(HEADER_LEN).read_to_end(&mut header)?;
+    reader.seek(SeekFrom::Start(0))?;
+    Ok(classify(&header))
+}
+
+/// Detect the format of a stream from its leading bytes asynchronously.
+///
+/// The stream is rewound to the start afterwards.
+#[cfg(feature = "async")]
+pub async fn detect_format_async<R>(mut reader: R) -> Result<FileFormat>
+where
+    R: futures::io::AsyncRead + futures::io::AsyncSeek + Unpin,
+{
+    use futures::io::{AsyncReadExt as _, AsyncSeekExt as _};
+
+    reader.seek(SeekFrom::Start(0)).await?;
+    let mut header = vec![];
+    (&mut reader)
+        .take(HEADER_LEN)
+        .read_to_end(&mut header)
+        .await?;
+    reader.seek(SeekFrom::Start(0)).await?;
+    Ok(classify(&header))
+}
+
+fn classify(header: &[u8]) -> FileFormat {
+    if header.len() == HEADER_LEN as usize {
+        let len_cksum = u32::from_le_bytes(header[8..12].try_into().unwrap());
+        if crate::utils::checksum(&header[0..8]) == len_cksum {
+            return FileFormat::Plain;
+        }
+    }
+
+    if header.len() > ENCRYPTED_MAGIC.len() && header.starts_with(ENCRYPTED_MAGIC) {
+        FileFormat::Encrypted {
+            version: header[ENCRYPTED_MAGIC.len()],
+        }
+    } else if header.starts_with(GZIP_MAGIC) {
+        FileFormat::Gzip
+    } else if header.starts_with(ZSTD_MAGIC) {
+        FileFormat::Zstd
+    } else {
+        FileFormat::Plain
+    }
+}
+
+/// Detect the format of a file from its leading bytes.
+pub fn detect_file_format<P>(path: P) -> Result<FileFormat>
+where
+    P: AsRef<Path>,
+{
+    detect_format(File::open(path)?)
+}
@@ -0,0 +1,481 @@
+//! Update vocabularies and statistics incrementally as a dataset grows.
+//!
+//! An [Accumulator] folds examples into a summary, such as the [VocabCounter] of a
+//! bytes feature or the [NumericStats] of a numeric feature, and merges with another
+//! accumulator of the same configuration. An [Accumulated] pairs the accumulator with
+//! the files it covers and their [fingerprints](crate::fingerprint), and is saved as
+//! JSON.
+//!
+//! [update] loads the saved accumulator, reads only the files it does not cover yet,
+//! merges their contribution and atomically replaces the saved file. Covered files that
+//! were removed or whose fingerprints changed cannot be subtracted from the
+//! accumulator, so they are handled by the [OnChange] policy: either fail with
+//! [Error::StaleAccumulator], or recompute from scratch over the current files.
+//!
+//! The [limits](crate::Limits) of the reader bound the work on pathological records.
+//! Records exceeding the per-record limits, such as
+//! [max_features](crate::Limits::max_features), are handled by the [OnOversized]
+//! policy: either fail, or skip and count them. Accumulators holding more distinct keys
+//! than [max_distinct_keys](crate::Limits::max_distinct_keys) fail the update with
+//! [Error::LimitExceeded] instead of growing without bound.
+//!
+//! Files are identified by their paths as given, so the paths of a dataset must be
+//! given in the same form on every update.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{
+//!     incremental::{self, NumericStats},
+//!     samples,
+//! };
+//!
+//! let dataset = samples::tiny_dataset(3, 10)?;
+//! let paths = dataset.paths();
+//! let state_path = dataset.dir().join("score.stats.json");
+//! let empty = NumericStats::new("score");
+//!
+//! // day 1
+//! incremental::update(&state_path, &paths[..2], empty.clone(), Default::default())?;
+//! // day 2, reading the new file only
+//! let updated = incremental::update(&state_path, paths, empty, Default::default())?;
+//! assert_eq!(updated.new_files, [paths[2].clone()]);
+//! assert_eq!(updated.accumulated.accumulator.count, 30);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    fingerprint::{self, Fingerprint, FingerprintLevel},
+    indexer::RecordIndexerConfig,
+    protobuf::{feature::Kind, Example},
+    record_reader::{ExampleIter, RecordReaderConfig},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashSet},
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The version of the saved accumulator format written by this crate.
+pub const ACCUMULATED_VERSION: u32 = 1;
+
+/// The suffix appended to the saved file name to name its temporary file.
+const TEMP_SUFFIX: &str = ".updating";
+
+/// A mergeable summary of examples.
+///
+/// Merging the accumulators of two sets of examples must give the same result as
+/// accumulating the union, so that an incremental update equals a recomputation.
+pub trait Accumulator
+where
+    Self: Sized + Serialize + DeserializeOwned,
+{
+    /// An accumulator of the same configuration without any example.
+    fn empty(&self) -> Self;
+
+    /// Fold an example into the accumulator.
+    fn update(&mut self, example: &Example) -> Result<()>;
+
+    /// Merge the accumulator of other examples into this one.
+    fn merge(&mut self, other: Self);
+
+    /// The number of distinct keys held by the accumulator, which is checked against
+    /// [max_distinct_keys](crate::Limits::max_distinct_keys) by [update].
+    fn num_keys(&self) -> usize {
+        0
+    }
+}
+
+/// The counts of the values of a bytes feature.
+///
+/// Values are counted by their UTF-8 decoding, with invalid sequences replaced.
+/// Examples without the feature are skipped.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct VocabCounter {
+    pub key: String,
+    pub counts: BTreeMap<String, u64>,
+}
+
+impl VocabCounter {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            counts: BTreeMap::new(),
+        }
+    }
+}
+
+impl Accumulator for VocabCounter {
+    fn empty(&self) -> Self {
+        Self::new(self.key.clone())
+    }
+
+    fn update(&mut self, example: &Example) -> Result<()> {
+        let values = match feature_kind(example, &self.key) {
+            None => return Ok(()),
+            Some(Kind::BytesList(list)) => &list.value,
+            Some(_) => {
+                return Err(Error::conversion(format!(
+                    "the feature '{}' is not a bytes list",
+                    self.key
+                )))
+            }
+        };
+        for value in values {
+            *self
+                .counts
+                .entry(String::from_utf8_lossy(value).into_owned())
+                .or_insert(0) += 1;
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, other: Self) {
+        for (value, count) in other.counts {
+            *self.counts.entry(value).or_insert(0) += count;
+        }
+    }
+
+    fn num_keys(&self) -> usize {
+        self.counts.len()
+    }
+}
+
+/// The count, sum and range of the values of an `Int64List` or `FloatList` feature.
+///
+/// Examples without the feature are skipped. The sum is exact as long as the partial
+/// sums are exactly representable in `f64`, otherwise the incremental and recomputed
+/// sums may differ by rounding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NumericStats {
+    pub key: String,
+    pub count: u64,
+    pub sum: f64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl NumericStats {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            count: 0,
+            sum: 0.0,
+            min: None,
+            max: None,
+        }
+    }
+
+    /// The mean of the values, or `None` without values.
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+
+    fn push(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        self.min = Some(self.min.map_or(value, |min| min.min(value)));
+        self.max = Some(self.max.map_or(value, |max| max.max(value)));
+    }
+}
+
+impl Accumulator for NumericStats {
+    fn empty(&self) -> Self {
+        Self::new(self.key.clone())
+    }
+
+    fn update(&mut self, example: &Example) -> Result<()> {
+        match feature_kind(example, &self.key) {
+            None => {}
+            Some(Kind::Int64List(list)) => list.value.iter().for_each(|&v| self.push(v as f64)),
+            Some(Kind::FloatList(list)) => list.value.iter().for_each(|&v| self.push(v as f64)),
+            Some(Kind::BytesList(_)) => {
+                return Err(Error::conversion(format!(
+                    "the feature '{}' is not numeric",
+                    self.key
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, other: Self) {
+        self.count += other.count;
+        self.sum += other.sum;
+        self.min = match (self.min, other.min) {
+            (Some(lhs), Some(rhs)) => Some(lhs.min(rhs)),
+            (lhs, rhs) => lhs.or(rhs),
+        };
+        self.max = match (self.max, other.max) {
+            (Some(lhs), Some(rhs)) => Some(lhs.max(rhs)),
+            (lhs, rhs) => lhs.or(rhs),
+        };
+    }
+}
+
+fn feature_kind<'a>(example: &'a Example, key: &str) -> Option<&'a Kind> {
+    example.features.as_ref()?.feature.get(key)?.kind.as_ref()
+}
+
+/// A file covered by an [Accumulated].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CoveredFile {
+    pub path: PathBuf,
+    pub fingerprint: Fingerprint,
+}
+
+/// An accumulator with the files it covers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Accumulated<A> {
+    pub version: u32,
+    /// The covered files in the order they were accumulated.
+    pub files: Vec<CoveredFile>,
+    pub accumulator: A,
+}
+
+impl<A> Accumulated<A>
+where
+    A: Accumulator,
+{
+    /// An accumulator covering no files.
+    pub fn new(accumulator: A) -> Self {
+        Self {
+            version: ACCUMULATED_VERSION,
+            files: vec![],
+            accumulator,
+        }
+    }
+
+    /// Load a saved accumulator, failing on unknown format versions.
+    pub fn load<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let text =
+            fs::read_to_string(path).map_err(|err| Error::from_io_with_context(err, path, None))?;
+        let accumulated: Self = serde_json::from_str(&text).map_err(|err| {
+            Error::conversion(format!(
+                "invalid saved accumulator {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+        if accumulated.version != ACCUMULATED_VERSION {
+            return Err(Error::Unsupported {
+                capability: format!("saved accumulator version {}", accumulated.version).into(),
+                feature: None,
+            });
+        }
+        Ok(accumulated)
+    }
+
+    /// Save the accumulator, replacing the file atomically by renaming a temporary file
+    /// next to it.
+    pub fn save<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let text = serde_json::to_string_pretty(self)
+            .map_err(|err| Error::conversion(format!("cannot serialize accumulator: {}", err)))?;
+        let temp = temp_path(path);
+        let with_path = |err| Error::from_io_with_context(err, path, None);
+        fs::write(&temp, text).map_err(with_path)?;
+        fs::File::open(&temp)
+            .and_then(|file| file.sync_all())
+            .map_err(with_path)?;
+        fs::rename(&temp, path).map_err(with_path)?;
+        Ok(())
+    }
+
+    /// Accumulate the examples of the file and add it to the covered files, returning
+    /// the number of skipped records.
+    fn cover(&mut self, path: PathBuf, options: &UpdateOptions) -> Result<u64> {
+        let limits = &options.reader.limits;
+        let fingerprint = fingerprint_file(&path, options)?;
+        let mut delta = self.accumulator.empty();
+        let mut num_skipped = 0;
+        let examples = ExampleIter::open(&path, options.reader.clone())
+            .map_err(|err| err.with_io_context(&path, None))?;
+        for (index, example) in examples.enumerate() {
+            let example = match example {
+                Err(err) if options.on_oversized == OnOversized::Skip && is_oversized(&err) => {
+                    num_skipped += 1;
+                    continue;
+                }
+                example => example,
+            };
+            example
+                .and_then(|example| delta.update(&example))
+                .and_then(|()| limits.check_distinct_keys(delta.num_keys()))
+                .map_err(|err| Error::RecordFailed {
+                    path: path.clone(),
+                    index: index as u64,
+                    source: Box::new(err),
+                })?;
+        }
+        self.accumulator.merge(delta);
+        limits.check_distinct_keys(self.accumulator.num_keys())?;
+        self.files.push(CoveredFile { path, fingerprint });
+        Ok(num_skipped)
+    }
+}
+
+/// The handling of covered files that were removed or changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OnChange {
+    /// Fail with [Error::StaleAccumulator].
+    #[default]
+    Error,
+    /// Discard the saved accumulator and accumulate all current files.
+    Recompute,
+}
+
+/// The handling of records exceeding the per-record limits of the reader.
+///
+/// Only the limits checked on decoding, [max_features](crate::Limits::max_features) and
+/// [max_key_len](crate::Limits::max_key_len), can be skipped. Records exceeding
+/// [max_record_len](crate::Limits::max_record_len) always fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OnOversized {
+    /// Fail with [Error::RecordFailed].
+    #[default]
+    Error,
+    /// Skip the record and count it in [skipped_records](Updated::skipped_records).
+    Skip,
+}
+
+/// Options for [update].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UpdateOptions {
+    pub on_change: OnChange,
+    pub on_oversized: OnOversized,
+    /// The level of the per-file fingerprints detecting changed files.
+    pub level: FingerprintLevel,
+    /// The configuration of the readers of new files.
+    pub reader: RecordReaderConfig,
+}
+
+impl Default for UpdateOptions {
+    fn default() -> Self {
+        Self {
+            on_change: OnChange::default(),
+            on_oversized: OnOversized::default(),
+            level: FingerprintLevel::Structure,
+            reader: RecordReaderConfig::default(),
+        }
+    }
+}
+
+/// The outcome of [update].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Updated<A> {
+    /// The saved accumulator.
+    pub accumulated: Accumulated<A>,
+    /// The files read by this update in order.
+    pub new_files: Vec<PathBuf>,
+    /// Whether the saved accumulator was discarded by [OnChange::Recompute].
+    pub recomputed: bool,
+    /// The number of records skipped by [OnOversized::Skip] in this update.
+    pub skipped_records: u64,
+}
+
+/// Update the accumulator saved at the path with the files of the dataset.
+///
+/// If no accumulator is saved yet, `empty` is the starting accumulator, otherwise it is
+/// ignored. The saved accumulator is replaced only if the update succeeds.
+pub fn update<'a, A, I, P>(
+    accumulator_path: impl AsRef<Path>,
+    paths: I,
+    empty: A,
+    options: UpdateOptions,
+) -> Result<Updated<A>>
+where
+    A: Accumulator,
+    I: IntoIterator<Item = P>,
+    P: Into<Cow<'a, Path>>,
+{
+    let accumulator_path = accumulator_path.as_ref();
+    let paths: Vec<PathBuf> = paths
+        .into_iter()
+        .map(|path| path.into().into_owned())
+        .collect();
+
+    let mut accumulated = if accumulator_path.exists() {
+        Accumulated::<A>::load(accumulator_path)?
+    } else {
+        Accumulated::new(empty)
+    };
+
+    // covered files can only be added to, never subtracted from, the accumulator
+    let current: HashSet<&Path> = paths.iter().map(PathBuf::as_path).collect();
+    let mut removed = vec![];
+    let mut changed = vec![];
+    for file in &accumulated.files {
+        if !current.contains(file.path.as_path()) {
+            removed.push(file.path.clone());
+        } else if fingerprint_file(&file.path, &options)? != file.fingerprint {
+            changed.push(file.path.clone());
+        }
+    }
+    let recomputed = !(removed.is_empty() && changed.is_empty());
+    if recomputed {
+        match options.on_change {
+            OnChange::Error => return Err(Error::StaleAccumulator { removed, changed }),
+            OnChange::Recompute => {
+                accumulated = Accumulated::new(accumulated.accumulator.empty());
+            }
+        }
+    }
+
+    let mut covered: HashSet<PathBuf> = accumulated
+        .files
+        .iter()
+        .map(|file| file.path.clone())
+        .collect();
+    let mut new_files = vec![];
+    let mut skipped_records = 0;
+    for path in paths {
+        if !covered.insert(path.clone()) {
+            continue;
+        }
+        skipped_records += accumulated.cover(path.clone(), &options)?;
+        new_files.push(path);
+    }
+
+    accumulated.save(accumulator_path)?;
+    Ok(Updated {
+        accumulated,
+        new_files,
+        recomputed,
+        skipped_records,
+    })
+}
+
+/// Whether the error is a per-record limit checked on decoding, after which the reader
+/// continues with the next record.
+fn is_oversized(err: &Error) -> bool {
+    matches!(
+        err,
+        Error::LimitExceeded {
+            which: "max_features" | "max_key_len",
+            ..
+        }
+    )
+}
+
+fn fingerprint_file(path: &Path, options: &UpdateOptions) -> Result<Fingerprint> {
+    fingerprint::fingerprint_paths([path], options.level, RecordIndexerConfig::default())
+        .map_err(|err| err.with_io_context(path, None))
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut file_name: OsString = path.file_name().unwrap_or_default().into();
+    file_name.push(TEMP_SUFFIX);
+    path.with_file_name(file_name)
+}
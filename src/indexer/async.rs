@@ -1,7 +1,14 @@
-use super::{Position, RecordIndex, RecordIndexerConfig};
+use super::{is_index_path, sort_paths, PathOrder, Position, RecordIndex, RecordIndexerConfig};
 use crate::{
+    cancel::{self, Progress},
+    compression::Compression,
     error::{Error, Result},
+    io::{r#async::with_timeout, OpTimeout},
+    protobuf::Example,
+    protobuf_ext::FeatureProjection,
     record::Record,
+    source::{RecordSource, SourceReader},
+    trace::{AttributeValue, Operation, Span, TraceHooks},
     utils,
 };
 use async_std::{
@@ -14,11 +21,24 @@ use futures::{
     stream,
     stream::{Stream, StreamExt as _, TryStreamExt as _},
 };
-use std::{borrow::Cow, future::Future, io::SeekFrom, sync::Arc};
+use std::{
+    borrow::Cow,
+    future::Future,
+    io::SeekFrom,
+    sync::{Arc, Mutex},
+};
 
 impl RecordIndex {
     /// Load the record data for the index.
     pub async fn load_async<T>(&self) -> Result<T>
+    where
+        T: Record,
+    {
+        self.load_async_with_timeout(None).await
+    }
+
+    /// Load the record data for the index with a deadline on each I/O operation.
+    pub async fn load_async_with_timeout<T>(&self, op_timeout: Option<OpTimeout>) -> Result<T>
     where
         T: Record,
     {
@@ -27,11 +47,74 @@ impl RecordIndex {
             offset,
             len,
         } = *self;
-        let mut reader = BufReader::new(File::open(&**path).await?);
-        let bytes = read_record_at(&mut reader, offset, len).await?;
+        let mut reader = with_timeout("open", op_timeout, async {
+            Ok(BufReader::new(File::open(&**path).await?))
+        })
+        .await?;
+        with_timeout("seek", op_timeout, async {
+            reader.seek(SeekFrom::Start(offset)).await?;
+            Ok(())
+        })
+        .await?;
+        let bytes = with_timeout("read_record", op_timeout, async {
+            crate::io::r#async::try_read_record_data(&mut reader, len, false).await
+        })
+        .await?;
         let record = T::from_bytes(bytes)?;
         Ok(record)
     }
+
+    /// Load the example for the index, decoding only the features in the projection.
+    pub async fn load_projected_async(&self, projection: &FeatureProjection) -> Result<Example> {
+        let Self {
+            ref path,
+            offset,
+            len,
+        } = *self;
+        let mut reader = BufReader::new(File::open(&**path).await?);
+        let bytes = read_record_at(&mut reader, offset, len).await?;
+        Example::decode_projected(&bytes, projection)
+    }
+}
+
+/// The source and position of a record in a [RecordSource].
+#[derive(Debug)]
+pub struct SourceIndex<S> {
+    pub source: Arc<S>,
+    pub offset: u64,
+    pub len: usize,
+}
+
+impl<S> Clone for SourceIndex<S> {
+    fn clone(&self) -> Self {
+        Self {
+            source: self.source.clone(),
+            offset: self.offset,
+            len: self.len,
+        }
+    }
+}
+
+impl<S> SourceIndex<S>
+where
+    S: RecordSource,
+{
+    /// Load the record data for the index by a single range read.
+    pub async fn load_async<T>(&self) -> Result<T>
+    where
+        T: Record,
+    {
+        let Self {
+            ref source,
+            offset,
+            len,
+        } = *self;
+        let bytes = source.read_range(offset..offset + len as u64).await?;
+        if bytes.len() != len {
+            return Err(Error::UnexpectedEof);
+        }
+        T::from_bytes(bytes)
+    }
 }
 
 /// Load record indexes from files specified by a prefix.
@@ -62,7 +145,7 @@ where
     let file_name_prefix = Arc::new(file_name_prefix);
 
     // filter paths
-    let mut paths: Vec<_> = dir
+    let paths: Vec<_> = dir
         .read_dir()
         .await?
         .map(|result| result.map_err(Error::from))
@@ -74,9 +157,11 @@ where
                     return Ok(None);
                 }
                 let file_name = PathBuf::from(entry.file_name());
-                let path = file_name
-                    .starts_with(&*file_name_prefix)
-                    .then(|| std::path::PathBuf::from(entry.path().into_os_string()));
+                let is_record_file = file_name.starts_with(&*file_name_prefix)
+                    && !crate::metadata::is_metadata_path(&file_name)
+                    && !is_index_path(&file_name);
+                let path =
+                    is_record_file.then(|| std::path::PathBuf::from(entry.path().into_os_string()));
                 Ok(path)
             }
         })
@@ -85,14 +170,20 @@ where
 
     // sort paths
     // TODO: fix blocking?
-    paths.sort();
+    let paths = sort_paths(paths, config.path_order.listed())?;
 
     // construct dataset
+    let config = RecordIndexerConfig {
+        path_order: PathOrder::AsGiven,
+        ..config
+    };
     let stream = load_paths_futures(paths, config);
     Ok(stream)
 }
 
 /// Load record indexes from file paths.
+///
+/// The files are loaded in the [path order](RecordIndexerConfig::path_order) of the configuration.
 pub fn load_paths_async<'a, P, I>(
     paths: I,
     config: RecordIndexerConfig,
@@ -101,12 +192,166 @@ where
     I: IntoIterator<Item = P>,
     P: Into<Cow<'a, std::path::Path>>,
 {
-    load_paths_futures(paths, config)
+    flatten_files(load_paths_futures(paths, config))
+}
+
+/// Flatten the record indexes of files loaded in order, counting the records of
+/// preceding files on cancellation.
+fn flatten_files<F, S, T>(futures: impl Stream<Item = F>) -> impl Stream<Item = Result<T>>
+where
+    F: Future<Output = Result<S>>,
+    S: Stream<Item = Result<T>>,
+{
+    futures
         .then(|fut| fut)
-        .try_flatten()
+        .enumerate()
+        .flat_map(|(file_index, result)| match result {
+            Ok(stream) => stream.map(move |index| (file_index, index)).left_stream(),
+            Err(err) => stream::once(async move { (file_index, Err(err)) }).right_stream(),
+        })
+        // count the records of preceding files, and stop at cancellation
+        .scan(
+            (0, false),
+            |(num_records, cancelled), (file_index, index)| {
+                let item = match index {
+                    _ if *cancelled => None,
+                    Ok(index) => {
+                        *num_records += 1;
+                        Some(Ok(index))
+                    }
+                    Err(Error::Cancelled { .. }) => {
+                        *cancelled = true;
+                        Some(Err(Error::Cancelled {
+                            progress: Progress::new(file_index as u64, *num_records),
+                        }))
+                    }
+                    Err(err) => Some(Err(err)),
+                };
+                futures::future::ready(item)
+            },
+        )
+}
+
+/// The span of a dataset, shared by the futures and the streams of its files. It ends
+/// once all of them are dropped.
+struct DatasetSpan {
+    span: Span,
+    files_done: u64,
+}
+
+impl DatasetSpan {
+    fn start(hooks: Option<&TraceHooks>, num_files: usize) -> Option<Arc<Mutex<Self>>> {
+        let mut span = Span::start(
+            hooks,
+            Operation::IndexDataset,
+            None,
+            &[("files", AttributeValue::U64(num_files as u64))],
+        )?
+        .with_counts();
+        span.set("files", AttributeValue::U64(0));
+        Some(Arc::new(Mutex::new(Self {
+            span,
+            files_done: 0,
+        })))
+    }
+}
+
+/// The spans of a file within a dataset.
+struct FileSpans {
+    dataset: Option<Arc<Mutex<DatasetSpan>>>,
+    file: Option<Span>,
+}
+
+impl FileSpans {
+    fn new(dataset: Option<Arc<Mutex<DatasetSpan>>>) -> Self {
+        Self {
+            dataset,
+            file: None,
+        }
+    }
+
+    /// Start the file span under the dataset span.
+    fn start_file(&mut self, hooks: Option<&TraceHooks>, path: String) {
+        let parent = self
+            .dataset
+            .as_ref()
+            .map(|dataset| dataset.lock().unwrap().span.handle());
+        self.file = Span::start(
+            hooks,
+            Operation::IndexFile,
+            parent,
+            &[("path", AttributeValue::Str(path))],
+        )
+        .map(Span::with_counts);
+    }
+
+    fn fail(&mut self, err: &Error) {
+        if let Some(file) = &mut self.file {
+            file.fail(err);
+        }
+        if let Some(dataset) = &self.dataset {
+            dataset.lock().unwrap().span.fail(err);
+        }
+    }
+
+    /// Count a record or a failure in both spans, or end the file span at the end of
+    /// the file.
+    fn observe<T>(&mut self, item: Option<&Result<T>>, len: fn(&T) -> usize) {
+        match item {
+            Some(Ok(index)) => {
+                if let Some(file) = &mut self.file {
+                    file.count_record(len(index));
+                }
+                if let Some(dataset) = &self.dataset {
+                    dataset.lock().unwrap().span.count_record(len(index));
+                }
+            }
+            Some(Err(err)) => self.fail(err),
+            None => {
+                self.file = None;
+                if let Some(dataset) = &self.dataset {
+                    let mut dataset = dataset.lock().unwrap();
+                    dataset.files_done += 1;
+                    let files_done = dataset.files_done;
+                    dataset.span.set("files", AttributeValue::U64(files_done));
+                }
+            }
+        }
+    }
+}
+
+/// Report the loading of a file and its record indexes to the spans.
+fn trace_file<S, T>(
+    result: Result<S>,
+    mut spans: FileSpans,
+    len: fn(&T) -> usize,
+) -> Result<impl Stream<Item = Result<T>>>
+where
+    S: Stream<Item = Result<T>>,
+{
+    let stream = match result {
+        Ok(stream) => stream,
+        Err(err) => {
+            spans.fail(&err);
+            return Err(err);
+        }
+    };
+    let stream = stream::unfold(
+        (Box::pin(stream), spans),
+        move |(mut stream, mut spans)| async move {
+            let item = stream.next().await;
+            spans.observe(item.as_ref(), len);
+            Some((item?, (stream, spans)))
+        },
+    );
+    Ok(stream)
 }
 
 /// Generate futures that load record indexes from file paths.
+///
+/// If [trace](RecordIndexerConfig::trace) is set, the
+/// [IndexDataset](Operation::IndexDataset) span ends once the stream and all the
+/// futures and their streams are dropped.
 pub fn load_paths_futures<'a, P, I>(
     paths: I,
     config: RecordIndexerConfig,
@@ -115,9 +360,31 @@ where
     I: IntoIterator<Item = P>,
     P: Into<Cow<'a, std::path::Path>>,
 {
-    stream::iter(paths)
+    let paths: Vec<_> = paths
+        .into_iter()
         .map(|path| path.into().into_owned())
-        .map(move |path| load_file_async(path, config.clone()))
+        .collect();
+    // TODO: fix blocking?
+    let paths: Vec<Result<_>> = match sort_paths(paths, config.path_order) {
+        Ok(paths) => paths.into_iter().map(Ok).collect(),
+        Err(err) => vec![Err(err)],
+    };
+
+    let dataset_span = DatasetSpan::start(config.trace.as_ref(), paths.len());
+
+    stream::iter(paths).map(move |path| {
+        let config = config.clone();
+        let mut spans = FileSpans::new(dataset_span.clone());
+        async move {
+            let result = async {
+                let path = path?;
+                spans.start_file(config.trace.as_ref(), path.display().to_string());
+                load_file_async(path, config).await
+            }
+            .await;
+            trace_file(result, spans, |index: &RecordIndex| index.len)
+        }
+    })
 }
 
 /// Load record indexes from a file.
@@ -129,10 +396,13 @@ where
     P: Into<Cow<'a, std::path::Path>>,
 {
     let file = file.into().into_owned();
-    let reader = BufReader::new(File::open(&file).await?);
-
-    let file = Arc::new(std::path::PathBuf::from(file.into_os_string()));
-    let stream = load_reader_async(reader, config).map(move |pos| {
+    let reader = with_timeout("open", config.op_timeout, async {
+        Ok(BufReader::new(File::open(&file).await?))
+    })
+    .await?;
+    let file = Arc::new(file);
+    let stream = load_checked_positions(reader, config, file.clone()).await?;
+    let stream = stream.map(move |pos| {
         let Position { offset, len } = pos?;
         Ok(RecordIndex {
             path: file.clone(),
@@ -143,6 +413,109 @@ where
     Ok(stream)
 }
 
+/// Load record positions from a reader after checking its format and content kind,
+/// attaching the path to the errors.
+async fn load_checked_positions<R>(
+    mut reader: R,
+    config: RecordIndexerConfig,
+    path: Arc<std::path::PathBuf>,
+) -> Result<impl Stream<Item = Result<Position>>>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    check_config(&config)?;
+    let unsupported = crate::format::detect_format_async(&mut reader)
+        .await?
+        .unsupported();
+    if let Some(unsupported) = &unsupported {
+        if !config.skip_unsupported {
+            return Err(unsupported.clone().into_error());
+        }
+    }
+    let mismatch = match unsupported {
+        None => crate::content::check_kind_async(&mut reader, config.expect_kind).await?,
+        Some(_) => None,
+    };
+    if let Some(mismatch) = &mismatch {
+        if !config.skip_mismatched_kind {
+            return Err(mismatch.clone().into_error((*path).clone()));
+        }
+    }
+
+    let stream = match (unsupported, mismatch) {
+        (None, None) => load_positions_async(reader, config, Some(path)).left_stream(),
+        _ => futures::stream::empty().right_stream(),
+    };
+    Ok(stream)
+}
+
+/// Load record indexes from sources in the given order.
+///
+/// The sources are loaded one after another. Load them concurrently by buffering the
+/// futures of [load_sources_futures], bounding the requests by a
+/// [ConnectionLimit](crate::source::ConnectionLimit).
+pub fn load_sources_async<S, I>(
+    sources: I,
+    config: RecordIndexerConfig,
+) -> impl Stream<Item = Result<SourceIndex<S>>>
+where
+    I: IntoIterator<Item = S>,
+    S: RecordSource,
+{
+    flatten_files(load_sources_futures(sources, config))
+}
+
+/// Generate futures that load record indexes from sources in the given order.
+///
+/// If [trace](RecordIndexerConfig::trace) is set, the
+/// [IndexDataset](Operation::IndexDataset) span ends once the stream and all the
+/// futures and their streams are dropped.
+pub fn load_sources_futures<S, I>(
+    sources: I,
+    config: RecordIndexerConfig,
+) -> impl Stream<Item = impl Future<Output = Result<impl Stream<Item = Result<SourceIndex<S>>>>>>
+where
+    I: IntoIterator<Item = S>,
+    S: RecordSource,
+{
+    let sources: Vec<_> = sources.into_iter().map(Arc::new).collect();
+    let dataset_span = DatasetSpan::start(config.trace.as_ref(), sources.len());
+
+    stream::iter(sources).map(move |source| {
+        let config = config.clone();
+        let mut spans = FileSpans::new(dataset_span.clone());
+        async move {
+            spans.start_file(config.trace.as_ref(), source.name().into_owned());
+            let result = load_source_async(source, config).await;
+            trace_file(result, spans, |index: &SourceIndex<S>| index.len)
+        }
+    })
+}
+
+/// Load record indexes from a source.
+///
+/// Errors are located by the [name](RecordSource::name) of the source.
+pub async fn load_source_async<S>(
+    source: Arc<S>,
+    config: RecordIndexerConfig,
+) -> Result<impl Stream<Item = Result<SourceIndex<S>>>>
+where
+    S: RecordSource,
+{
+    let name = Arc::new(std::path::PathBuf::from(source.name().into_owned()));
+    let reader = SourceReader::new(source.clone());
+    let stream = load_checked_positions(reader, config, name).await?;
+    let stream = stream.map(move |pos| {
+        let Position { offset, len } = pos?;
+        Ok(SourceIndex {
+            source: source.clone(),
+            offset,
+            len,
+        })
+    });
+    Ok(stream)
+}
+
 /// Load record indexes from a reader.
 pub fn load_reader_async<R>(
     reader: R,
@@ -151,19 +524,104 @@ pub fn load_reader_async<R>(
 where
     R: AsyncRead + AsyncSeek + Unpin,
 {
-    let RecordIndexerConfig { check_integrity } = config;
+    match check_config(&config) {
+        Ok(()) => load_positions_async(reader, config, None).left_stream(),
+        Err(err) => stream::once(async move { Err(err) }).right_stream(),
+    }
+}
 
-    stream::try_unfold(reader, move |mut reader| async move {
-        let len = match crate::io::r#async::try_read_len(&mut reader, check_integrity).await? {
-            Some(len) => len,
-            None => return Ok(None),
-        };
+/// Reject the options the async indexer does not support.
+fn check_config(config: &RecordIndexerConfig) -> Result<()> {
+    if config.skip_corrupt {
+        return Err(Error::InvalidArgumentsError {
+            desc: "skip_corrupt is not supported by the async indexer".into(),
+        });
+    }
+    if config.compression != Compression::None {
+        return Err(Error::InvalidArgumentsError {
+            desc: "compression is not supported by the async indexer".into(),
+        });
+    }
+    Ok(())
+}
+
+/// Load record positions from a reader, attaching the file to checksum mismatches if
+/// given.
+fn load_positions_async<R>(
+    reader: R,
+    config: RecordIndexerConfig,
+    path: Option<Arc<std::path::PathBuf>>,
+) -> impl Stream<Item = Result<Position>>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    let RecordIndexerConfig {
+        integrity,
+        limits,
+        op_timeout,
+        path_order: _,
+        skip_unsupported: _,
+        expect_kind: _,
+        skip_mismatched_kind: _,
+        skip_corrupt: _,
+        cancel,
+        compression: _,
+        trace: _,
+    } = config;
 
-        let offset = reader.seek(SeekFrom::Current(0)).await?;
-        skip_or_check(&mut reader, len, check_integrity).await?;
+    // the reader, the number of records, the end of the stream if required and the start
+    // of the next frame once known
+    let init: (R, u64, Option<u64>, Option<u64>) = (reader, 0, None, None);
+    stream::try_unfold(init, move |(mut reader, index, mut end, next_frame)| {
+        let limits = limits.clone();
+        let cancel = cancel.clone();
+        let path = path.clone();
+        async move {
+            cancel::check_periodically(cancel.as_ref(), 0, index)?;
+            let mut frame = next_frame;
+            let read_record = async {
+                let frame = match frame {
+                    Some(frame) => frame,
+                    None => *frame.insert(reader.seek(SeekFrom::Current(0)).await?),
+                };
+                let len =
+                    match crate::io::r#async::try_read_len(&mut reader, integrity.checks_len())
+                        .await?
+                    {
+                        Some(len) => len,
+                        None => return Ok(None),
+                    };
+                limits.check_record_len(len)?;
 
-        let pos = Position { offset, len };
-        Result::<_, Error>::Ok(Some((pos, reader)))
+                let offset = frame + crate::io::sync::HEADER_LEN;
+                let is_last = if integrity.needs_last() {
+                    let end = match end {
+                        Some(end) => end,
+                        None => {
+                            let end_pos = reader.seek(SeekFrom::End(0)).await?;
+                            reader.seek(SeekFrom::Start(offset)).await?;
+                            *end.insert(end_pos)
+                        }
+                    };
+                    offset + len as u64 + 4 >= end
+                } else {
+                    false
+                };
+                let check_data = integrity.checks_data(index, is_last);
+                skip_or_check(&mut reader, len, check_data).await?;
+                Ok(Some(Position { offset, len }))
+            };
+            let pos = with_timeout("read_record", op_timeout, read_record)
+                .await
+                .map_err(|err| match (&path, frame) {
+                    (Some(path), Some(frame)) => err.with_checksum_context(path, frame),
+                    _ => err,
+                })?;
+            Result::<_, Error>::Ok(pos.map(|pos| {
+                let next_frame = pos.offset + pos.len as u64 + 4;
+                (pos, (reader, index + 1, end, Some(next_frame)))
+            }))
+        }
     })
 }
 
@@ -184,7 +642,8 @@ where
     if check_integrity {
         crate::io::r#async::try_read_record_data(reader, len, check_integrity).await?;
     } else {
-        reader.seek(SeekFrom::Current(len as i64)).await?;
+        // skip the payload and its checksum
+        reader.seek(SeekFrom::Current(len as i64 + 4)).await?;
     }
     Ok(())
 }
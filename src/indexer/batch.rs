@@ -0,0 +1,155 @@
+use super::{iter_from, RecordIndex};
+use crate::{
+    error::{Error, Result},
+    record::Record,
+    record_reader::RecordReaderConfig,
+};
+use std::{fmt, sync::Arc};
+
+/// The outcome of reading one record of a batch.
+#[derive(Debug)]
+pub enum RecordResult<T> {
+    /// The record is read and decoded.
+    Ok(T),
+    /// The requested index is out of range.
+    Missing,
+    /// The record fails to be read or decoded.
+    Failed(Error),
+}
+
+impl<T> RecordResult<T> {
+    /// Returns true if the record is read.
+    pub fn is_ok(&self) -> bool {
+        matches!(self, Self::Ok(_))
+    }
+
+    /// Get the record, discarding the reason of absence.
+    pub fn ok(self) -> Option<T> {
+        match self {
+            Self::Ok(record) => Some(record),
+            Self::Missing | Self::Failed(_) => None,
+        }
+    }
+}
+
+/// The numbers of outcomes in a batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BatchSummary {
+    pub num_ok: usize,
+    pub num_missing: usize,
+    pub num_failed: usize,
+}
+
+/// The per-record outcomes of a batch in the order of the requested indexes.
+#[derive(Debug)]
+pub struct BatchResult<T> {
+    pub records: Vec<RecordResult<T>>,
+    pub summary: BatchSummary,
+}
+
+impl<T> FromIterator<RecordResult<T>> for BatchResult<T> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = RecordResult<T>>,
+    {
+        let mut summary = BatchSummary::default();
+        let records = iter
+            .into_iter()
+            .inspect(|record| match record {
+                RecordResult::Ok(_) => summary.num_ok += 1,
+                RecordResult::Missing => summary.num_missing += 1,
+                RecordResult::Failed(_) => summary.num_failed += 1,
+            })
+            .collect();
+        Self { records, summary }
+    }
+}
+
+/// Read the records at the requested global indexes, with an outcome per request.
+///
+/// A corrupted record fails on its own without failing the batch. The checksums are
+/// verified by the integrity mode of the configuration as in [iter_from].
+pub fn get_many_detailed<T>(
+    indexes: &[RecordIndex],
+    requests: &[usize],
+    config: RecordReaderConfig,
+) -> BatchResult<T>
+where
+    T: Record,
+{
+    requests
+        .iter()
+        .map(
+            |&request| match iter_from(indexes, request, config.clone()).next() {
+                Some(Ok((_, record))) => RecordResult::Ok(record),
+                Some(Err(error)) => RecordResult::Failed(error),
+                None => RecordResult::Missing,
+            },
+        )
+        .collect()
+}
+
+/// The function called with the global index and the error of a skipped record.
+pub type RecordErrorFn = dyn Fn(usize, &Error) + Send + Sync;
+
+/// The action on records failing to be read in a stream.
+#[derive(Clone, Default)]
+pub enum ErrorPolicy {
+    /// Yield the error and end the stream.
+    #[default]
+    Fail,
+    /// Pass the error to the function and continue with the next record.
+    Skip(Arc<RecordErrorFn>),
+}
+
+impl ErrorPolicy {
+    /// Create a [Skip](ErrorPolicy::Skip) policy.
+    pub fn skip<F>(on_error: F) -> Self
+    where
+        F: 'static + Fn(usize, &Error) + Send + Sync,
+    {
+        Self::Skip(Arc::new(on_error))
+    }
+}
+
+impl fmt::Debug for ErrorPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fail => f.debug_tuple("Fail").finish(),
+            Self::Skip(_) => f.debug_tuple("Skip").finish(),
+        }
+    }
+}
+
+/// Iterate records from the `start`-th index as [iter_from], handling failed records
+/// by the policy.
+pub fn iter_from_with_policy<T>(
+    indexes: &[RecordIndex],
+    start: usize,
+    config: RecordReaderConfig,
+    on_record_error: ErrorPolicy,
+) -> impl Iterator<Item = Result<(usize, T)>> + '_
+where
+    T: Record,
+{
+    let mut failed = false;
+    iter_from(indexes, start, config)
+        .enumerate()
+        .map_while(move |(offset, result)| {
+            if failed {
+                return None;
+            }
+            match (result, &on_record_error) {
+                (Err(error), ErrorPolicy::Skip(on_error)) => {
+                    on_error(start + offset, &error);
+                    Some(None)
+                }
+                (Err(error), ErrorPolicy::Fail) => {
+                    failed = true;
+                    Some(Some(Err(error)))
+                }
+                (Ok(record), _) => Some(Some(Ok(record))),
+            }
+        })
+        .flatten()
+}
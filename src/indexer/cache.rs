@@ -0,0 +1,283 @@
+use super::{load_file, sort_paths, FileIdentity, Position, RecordIndex, RecordIndexerConfig};
+use crate::{
+    cancel,
+    error::{Error, Result},
+};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, UNIX_EPOCH},
+};
+
+/// The suffix appended to file names to name their index sidecar files.
+pub const INDEX_SUFFIX: &str = ".idx";
+
+/// The first bytes of index sidecar files, ending with the format version.
+const MAGIC: &[u8; 8] = b"TFRIDX01";
+
+/// The length of the magic, the file length, the modification time and the count.
+const HEADER_LEN: usize = 8 + 8 + 12 + 8;
+
+/// The suffix of sidecars being written.
+const TEMP_SUFFIX: &str = ".tmp";
+
+/// Record indexes loaded by [load_paths_cached].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedIndexes {
+    pub indexes: Vec<RecordIndex>,
+    /// The number of files whose indexes are loaded from sidecars.
+    pub num_cached: usize,
+    /// The number of files scanned for missing, stale or corrupt sidecars.
+    pub num_scanned: usize,
+}
+
+/// The path of the index sidecar file of a record file, named by appending
+/// [INDEX_SUFFIX] to the file name.
+pub fn index_path<P>(path: P) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let mut file_name: OsString = path.file_name().unwrap_or_default().into();
+    file_name.push(INDEX_SUFFIX);
+    path.with_file_name(file_name)
+}
+
+/// Returns true if the path names an index sidecar file.
+pub fn is_index_path<P>(path: P) -> bool
+where
+    P: AsRef<Path>,
+{
+    path.as_ref()
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().ends_with(INDEX_SUFFIX))
+}
+
+/// Save the record indexes to the index sidecar files of their files.
+///
+/// The sidecars record the current [FileIdentity] of the files, so the indexes must be
+/// saved before the files change. Each sidecar is replaced atomically by renaming a
+/// temporary file next to it.
+pub fn save_indexes(indexes: &[RecordIndex]) -> Result<()> {
+    // group the positions by file in the order of first appearance
+    let mut files: Vec<(&Path, Vec<Position>)> = vec![];
+    let mut slots: HashMap<&Path, usize> = HashMap::new();
+    for index in indexes {
+        let slot = *slots.entry(index.path.as_path()).or_insert_with(|| {
+            files.push((index.path.as_path(), vec![]));
+            files.len() - 1
+        });
+        files[slot].1.push(Position {
+            offset: index.offset,
+            len: index.len,
+        });
+    }
+
+    for (path, positions) in files {
+        let identity = FileIdentity::of(path).map_err(|err| err.with_io_context(path, None))?;
+        write_sidecar(path, &identity, &positions)?;
+    }
+    Ok(())
+}
+
+/// Load record indexes from file paths, reusing the index sidecar files of the files.
+///
+/// The files are loaded in the [path order](RecordIndexerConfig::path_order) of the
+/// configuration. A sidecar is used if it is intact and records the current size and
+/// modification time of its file. Otherwise the file is scanned, and the sidecar is
+/// written again. Sidecars are an optimization, so failures to write them are ignored.
+/// The total length of the sidecars loaded must be within
+/// [max_cache_bytes](crate::Limits::max_cache_bytes) of the limits.
+///
+/// The sidecar holds the indexes as scanned by the configuration it was written with.
+/// Checksums, [content kinds](RecordIndexerConfig::expect_kind) and formats are not
+/// checked again when it is used.
+///
+/// ```rust
+/// # fn main() -> tfrecord::Result<()> {
+/// use tfrecord::{indexer, samples};
+///
+/// let dataset = samples::tiny_dataset(2, 3)?;
+/// let scanned = indexer::load_paths_cached(dataset.paths(), Default::default())?;
+/// assert_eq!(scanned.num_scanned, 2);
+///
+/// let cached = indexer::load_paths_cached(dataset.paths(), Default::default())?;
+/// assert_eq!(cached.num_cached, 2);
+/// assert_eq!(cached.indexes, scanned.indexes);
+/// # Ok(())
+/// # }
+/// ```
+pub fn load_paths_cached<'a, P, I>(paths: I, config: RecordIndexerConfig) -> Result<CachedIndexes>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Cow<'a, Path>>,
+{
+    let paths: Vec<_> = paths
+        .into_iter()
+        .map(|path| path.into().into_owned())
+        .collect();
+    let paths = sort_paths(paths, config.path_order)?;
+
+    let mut indexes = vec![];
+    let mut num_cached = 0;
+    let mut num_scanned = 0;
+    let mut cache_bytes = 0;
+    for (files_done, path) in paths.into_iter().enumerate() {
+        let records_before = indexes.len() as u64;
+        cancel::check(config.cancel.as_ref(), files_done as u64, records_before)?;
+        let identity = FileIdentity::of(&path).map_err(|err| err.with_io_context(&path, None))?;
+
+        if let Some(positions) = read_sidecar(&path, &identity, &config, &mut cache_bytes)? {
+            let path = Arc::new(path);
+            indexes.extend(
+                positions
+                    .into_iter()
+                    .map(|Position { offset, len }| RecordIndex {
+                        path: path.clone(),
+                        offset,
+                        len,
+                    }),
+            );
+            num_cached += 1;
+            continue;
+        }
+
+        let mut positions = vec![];
+        for index in load_file(&path, config.clone())? {
+            let index =
+                index.map_err(|err| err.after_progress(files_done as u64, records_before))?;
+            positions.push(Position {
+                offset: index.offset,
+                len: index.len,
+            });
+            indexes.push(index);
+        }
+        // the identity before scanning, so that changes during the scan make it stale
+        let _ = write_sidecar(&path, &identity, &positions);
+        num_scanned += 1;
+    }
+
+    Ok(CachedIndexes {
+        indexes,
+        num_cached,
+        num_scanned,
+    })
+}
+
+fn write_sidecar(path: &Path, identity: &FileIdentity, positions: &[Position]) -> Result<()> {
+    let sidecar = index_path(path);
+    let mut temp: OsString = sidecar.clone().into_os_string();
+    temp.push(TEMP_SUFFIX);
+    let temp = PathBuf::from(temp);
+
+    let bytes = encode(identity, positions);
+    let with_path = |err| Error::from_io_with_context(err, &sidecar, None);
+    fs::write(&temp, bytes).map_err(with_path)?;
+    fs::rename(&temp, &sidecar).map_err(with_path)?;
+    Ok(())
+}
+
+/// Read the positions of an intact sidecar recording the identity, or `None` if it is
+/// missing, stale or corrupt.
+///
+/// The sidecar is counted in the bytes loaded so far, which must be within
+/// [max_cache_bytes](crate::Limits::max_cache_bytes).
+fn read_sidecar(
+    path: &Path,
+    identity: &FileIdentity,
+    config: &RecordIndexerConfig,
+    cache_bytes: &mut usize,
+) -> Result<Option<Vec<Position>>> {
+    let sidecar = index_path(path);
+    let len = match fs::metadata(&sidecar) {
+        Ok(stat) => usize::try_from(stat.len()).unwrap_or(usize::MAX),
+        Err(_) => return Ok(None),
+    };
+    *cache_bytes = cache_bytes.saturating_add(len);
+    config.limits.check_cache_bytes(*cache_bytes)?;
+
+    let bytes = match fs::read(&sidecar) {
+        Ok(bytes) => bytes,
+        Err(_) => return Ok(None),
+    };
+    let positions = decode(&bytes).and_then(|(recorded, positions)| {
+        (recorded.len == identity.len && recorded.modified == identity.modified)
+            .then_some(positions)
+    });
+    Ok(positions)
+}
+
+fn encode(identity: &FileIdentity, positions: &[Position]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN + positions.len() * 16 + 4);
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&identity.len.to_le_bytes());
+    // an unknown modification time is recorded as u32::MAX nanoseconds
+    let (secs, nanos) = match identity
+        .modified
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+    {
+        Some(since) => (since.as_secs(), since.subsec_nanos()),
+        None => (0, u32::MAX),
+    };
+    bytes.extend_from_slice(&secs.to_le_bytes());
+    bytes.extend_from_slice(&nanos.to_le_bytes());
+    bytes.extend_from_slice(&(positions.len() as u64).to_le_bytes());
+    for pos in positions {
+        bytes.extend_from_slice(&pos.offset.to_le_bytes());
+        bytes.extend_from_slice(&(pos.len as u64).to_le_bytes());
+    }
+    let checksum = crate::utils::checksum(&bytes);
+    bytes.extend_from_slice(&checksum.to_le_bytes());
+    bytes
+}
+
+fn decode(bytes: &[u8]) -> Option<(FileIdentity, Vec<Position>)> {
+    let (body, checksum) = bytes.split_last_chunk::<4>()?;
+    if body.len() < HEADER_LEN
+        || &body[..8] != MAGIC
+        || crate::utils::checksum(body) != u32::from_le_bytes(*checksum)
+    {
+        return None;
+    }
+    let u64_at = |pos: usize| u64::from_le_bytes(body[pos..pos + 8].try_into().unwrap());
+
+    let len = u64_at(8);
+    let secs = u64_at(16);
+    let nanos = u32::from_le_bytes(body[24..28].try_into().unwrap());
+    let modified = (nanos != u32::MAX)
+        .then(|| UNIX_EPOCH.checked_add(Duration::new(secs, nanos)))
+        .flatten();
+    let count = u64_at(28);
+    if (body.len() - HEADER_LEN) as u64 != count.checked_mul(16)? {
+        return None;
+    }
+
+    // frames do not overlap
+    let mut positions = Vec::with_capacity(count as usize);
+    let mut next_offset = crate::io::sync::HEADER_LEN;
+    for pos in (HEADER_LEN..body.len()).step_by(16) {
+        let offset = u64_at(pos);
+        let record_len = u64_at(pos + 8);
+        if offset < next_offset {
+            return None;
+        }
+        next_offset = offset
+            .checked_add(record_len)?
+            .checked_add(4 + crate::io::sync::HEADER_LEN)?;
+        positions.push(Position {
+            offset,
+            len: record_len.try_into().ok()?,
+        });
+    }
+
+    let identity = FileIdentity {
+        len,
+        modified,
+        inode: None,
+    };
+    Some((identity, positions))
+}
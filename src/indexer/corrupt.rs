@@ -0,0 +1,56 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+/// A record skipped by the indexer for failing checksum verification.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SkippedRecord {
+    /// The file of the record, or `None` for records indexed by
+    /// [load_reader_with_report](super::load_reader_with_report).
+    pub path: Option<PathBuf>,
+    /// The offset of the record frame, which starts at the length field.
+    pub offset: u64,
+    /// The number of skipped bytes up to the next frame or the end of the file.
+    pub num_bytes: u64,
+    /// The checksum stored in the file.
+    pub expect: u32,
+    /// The checksum computed from the bytes.
+    pub found: u32,
+}
+
+/// The report of records skipped by the indexer with
+/// [skip_corrupt](super::RecordIndexerConfig::skip_corrupt) set, returned by
+/// [load_paths_with_report](super::load_paths_with_report) and its siblings.
+///
+/// A record with a corrupted payload is skipped by its length. A record with a corrupted
+/// length is skipped by scanning for the next valid frame, as
+/// [find_frame_start](crate::io::sync::find_frame_start) does.
+///
+/// The report is filled as the returned iterator advances. Clones share the same report.
+#[derive(Debug, Clone)]
+pub struct CorruptReport(Arc<Mutex<Vec<SkippedRecord>>>);
+
+impl CorruptReport {
+    pub(crate) fn new() -> Self {
+        Self(Arc::default())
+    }
+
+    /// The skipped records in the order they are found.
+    pub fn records(&self) -> Vec<SkippedRecord> {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// The number of skipped records.
+    pub fn len(&self) -> usize {
+        self.0.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn push(&self, record: SkippedRecord) {
+        self.0.lock().unwrap().push(record);
+    }
+}
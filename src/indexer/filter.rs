@@ -0,0 +1,225 @@
+use super::{load_file, sort_paths, RecordIndex, RecordIndexerConfig};
+use crate::{
+    cancel,
+    content::{self, KindMismatch},
+    error::Result,
+    format::{detect_file_format, Unsupported},
+};
+use std::{
+    borrow::Cow,
+    fmt,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// The function of the file path, the payload offset and the payload length of a record.
+pub type IndexFilterFn = dyn Fn(&Path, u64, usize) -> bool + Send + Sync;
+
+/// A predicate on record locations evaluated while building record indexes.
+///
+/// Filters see only the file path, the payload offset and the payload length, so they
+/// are evaluated without reading payloads. Filtering on the contents of records
+/// requires decoding them, which is out of the scope of the indexer.
+#[derive(Clone)]
+pub enum IndexFilter {
+    /// Keep records with payloads of at most the length in bytes.
+    MaxLen(usize),
+    /// Keep records with payloads of at least the length in bytes.
+    MinLen(usize),
+    /// Keep records in files whose paths match the glob pattern.
+    #[cfg(feature = "glob")]
+    FileMatches(glob::Pattern),
+    /// Keep records accepted by the function of the file path, the payload offset and
+    /// the payload length.
+    Custom(Arc<IndexFilterFn>),
+}
+
+impl IndexFilter {
+    /// Create a [FileMatches](IndexFilter::FileMatches) filter from a glob pattern.
+    #[cfg(feature = "glob")]
+    pub fn file_matches(pattern: &str) -> Result<Self> {
+        let pattern = glob::Pattern::new(pattern).map_err(|err| {
+            crate::error::Error::invalid_argument(format!(
+                "invalid glob pattern '{}': {}",
+                pattern, err
+            ))
+        })?;
+        Ok(Self::FileMatches(pattern))
+    }
+
+    /// Create a [Custom](IndexFilter::Custom) filter.
+    pub fn custom<F>(filter: F) -> Self
+    where
+        F: 'static + Fn(&Path, u64, usize) -> bool + Send + Sync,
+    {
+        Self::Custom(Arc::new(filter))
+    }
+
+    /// Returns true if the record at the location is kept.
+    pub fn accepts(&self, path: &Path, offset: u64, len: usize) -> bool {
+        match self {
+            Self::MaxLen(max_len) => len <= *max_len,
+            Self::MinLen(min_len) => len >= *min_len,
+            #[cfg(feature = "glob")]
+            Self::FileMatches(pattern) => pattern.matches_path(path),
+            Self::Custom(filter) => filter(path, offset, len),
+        }
+    }
+}
+
+impl fmt::Debug for IndexFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MaxLen(max_len) => f.debug_tuple("MaxLen").field(max_len).finish(),
+            Self::MinLen(min_len) => f.debug_tuple("MinLen").field(min_len).finish(),
+            #[cfg(feature = "glob")]
+            Self::FileMatches(pattern) => f.debug_tuple("FileMatches").field(pattern).finish(),
+            Self::Custom(_) => f.debug_tuple("Custom").finish(),
+        }
+    }
+}
+
+/// The numbers of kept and excluded records in a file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FileFilterStats {
+    pub path: Arc<PathBuf>,
+    pub num_kept: u64,
+    pub num_excluded: u64,
+}
+
+/// A file skipped without indexing.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SkippedFile {
+    pub path: Arc<PathBuf>,
+    pub reason: SkipReason,
+}
+
+/// The reason to skip a file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SkipReason {
+    /// The capability missing to index the file.
+    Unsupported(Unsupported),
+    /// The content kind of the file differs from the expected kind.
+    KindMismatch(KindMismatch),
+}
+
+/// The record indexes passing index filters.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FilteredIndexes {
+    /// The kept record indexes in the order of files and records.
+    pub indexes: Vec<RecordIndex>,
+    /// The statistics per file in the order of files, excluding skipped files.
+    pub files: Vec<FileFilterStats>,
+    /// The files skipped by [skip_unsupported](RecordIndexerConfig::skip_unsupported)
+    /// or [skip_mismatched_kind](RecordIndexerConfig::skip_mismatched_kind).
+    pub skipped: Vec<SkippedFile>,
+}
+
+impl FilteredIndexes {
+    /// The number of kept records.
+    pub fn num_records(&self) -> usize {
+        self.indexes.len()
+    }
+
+    /// The total number of excluded records.
+    pub fn num_excluded(&self) -> u64 {
+        self.files.iter().map(|stats| stats.num_excluded).sum()
+    }
+}
+
+/// Load record indexes from file paths, keeping records accepted by all filters.
+///
+/// The files are loaded in the [path order](RecordIndexerConfig::path_order) of the
+/// configuration. Excluded records never enter the indexes, so the `i`-th index refers
+/// to the `i`-th kept record. Files excluded by their paths are still indexed to count
+/// their excluded records. Files skipped for unsupported formats or mismatched content
+/// kinds are reported in [skipped](FilteredIndexes::skipped).
+pub fn load_paths_filtered<'a, P, I>(
+    paths: I,
+    config: RecordIndexerConfig,
+    filters: &[IndexFilter],
+) -> Result<FilteredIndexes>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Cow<'a, Path>>,
+{
+    let paths: Vec<_> = paths
+        .into_iter()
+        .map(|path| path.into().into_owned())
+        .collect();
+    let paths = sort_paths(paths, config.path_order)?;
+
+    let mut indexes = vec![];
+    let mut files = vec![];
+    let mut skipped = vec![];
+
+    // the numbers of processed files and records
+    let mut files_done = 0;
+    let mut num_records = 0;
+
+    for path in paths {
+        cancel::check(config.cancel.as_ref(), files_done, num_records)?;
+        let path = Arc::new(path);
+        if let Some(unsupported) = detect_file_format(&*path)?.unsupported() {
+            if !config.skip_unsupported {
+                return Err(unsupported.into_error());
+            }
+            skipped.push(SkippedFile {
+                path,
+                reason: SkipReason::Unsupported(unsupported),
+            });
+            files_done += 1;
+            continue;
+        }
+        if let Some(mismatch) =
+            content::check_kind(BufReader::new(File::open(&*path)?), config.expect_kind)?
+        {
+            if !config.skip_mismatched_kind {
+                return Err(mismatch.into_error(path.to_path_buf()));
+            }
+            skipped.push(SkippedFile {
+                path,
+                reason: SkipReason::KindMismatch(mismatch),
+            });
+            files_done += 1;
+            continue;
+        }
+        let mut num_kept = 0;
+        let mut num_excluded = 0;
+
+        let records_before = num_records;
+        for index in load_file(&*path, config.clone())? {
+            let RecordIndex { offset, len, .. } =
+                index.map_err(|err| err.after_progress(files_done, records_before))?;
+            num_records += 1;
+            let accepted = filters
+                .iter()
+                .all(|filter| filter.accepts(&path, offset, len));
+            if accepted {
+                num_kept += 1;
+                indexes.push(RecordIndex {
+                    path: path.clone(),
+                    offset,
+                    len,
+                });
+            } else {
+                num_excluded += 1;
+            }
+        }
+
+        files.push(FileFilterStats {
+            path,
+            num_kept,
+            num_excluded,
+        });
+        files_done += 1;
+    }
+
+    Ok(FilteredIndexes {
+        indexes,
+        files,
+        skipped,
+    })
+}
@@ -0,0 +1,368 @@
+use super::{
+    load_reader, sort_paths, BatchResult, Position, RecordIndex, RecordIndexerConfig, RecordResult,
+};
+use crate::{
+    error::{Error, Result},
+    record::Record,
+};
+use std::{
+    borrow::Cow,
+    fmt,
+    fs::{File, Metadata},
+    io::BufReader,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The identity of a file, which changes when the file is replaced or rewritten.
+///
+/// It consists of the file size, the modification time and, on Unix, the device and
+/// inode numbers. Reading it costs a single metadata call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileIdentity {
+    pub len: u64,
+    pub modified: Option<SystemTime>,
+    /// The device and inode numbers.
+    pub inode: Option<(u64, u64)>,
+}
+
+impl FileIdentity {
+    /// Read the identity of the file at the path.
+    pub fn of<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(Self::from_metadata(&std::fs::metadata(path)?))
+    }
+
+    /// Get the identity from file metadata.
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        #[cfg(unix)]
+        let inode = {
+            use std::os::unix::fs::MetadataExt as _;
+            Some((metadata.dev(), metadata.ino()))
+        };
+        #[cfg(not(unix))]
+        let inode = None;
+
+        Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            inode,
+        }
+    }
+}
+
+impl fmt::Display for FileIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "len={}", self.len)?;
+        if let Some(modified) = self.modified {
+            match modified.duration_since(UNIX_EPOCH) {
+                Ok(since) => write!(
+                    f,
+                    " modified={}.{:09}",
+                    since.as_secs(),
+                    since.subsec_nanos()
+                )?,
+                Err(_) => write!(f, " modified=before-epoch")?,
+            }
+        }
+        if let Some((dev, ino)) = self.inode {
+            write!(f, " inode={}:{}", dev, ino)?;
+        }
+        Ok(())
+    }
+}
+
+/// The action on files changed after indexing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FileChangePolicy {
+    /// Fail with [Error::FileChanged].
+    #[default]
+    Fail,
+    /// Index the changed file again and continue with the new indexes.
+    Reindex,
+}
+
+/// Configuration for [GuardedIndexes].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct GuardConfig {
+    /// The action on files changed after indexing.
+    pub on_change: FileChangePolicy,
+    /// If set, the identity of an open file is checked again every specified number of
+    /// reads, in addition to every time the file is opened.
+    pub check_interval: Option<NonZeroUsize>,
+}
+
+/// Record indexes guarded against files replaced or truncated after indexing.
+///
+/// The [FileIdentity] of each file is recorded when it is indexed. The identity is
+/// checked whenever a file is opened and, optionally, periodically while the file is
+/// open, so that records are never read from a file with stale indexes.
+///
+/// Files are kept open across [get](GuardedIndexes::get) calls. With
+/// [Reindex](FileChangePolicy::Reindex), the number of records of a changed file may
+/// differ after reindexing, which shifts the indexes of the records in later files.
+#[derive(Debug)]
+pub struct GuardedIndexes {
+    config: RecordIndexerConfig,
+    guard: GuardConfig,
+    files: Vec<GuardedFile>,
+    /// The global index of the first record of each file.
+    starts: Vec<usize>,
+}
+
+#[derive(Debug)]
+struct GuardedFile {
+    path: Arc<PathBuf>,
+    identity: FileIdentity,
+    positions: Vec<Position>,
+    reader: Option<BufReader<File>>,
+    /// The number of reads since the last identity check.
+    num_unchecked_reads: usize,
+}
+
+impl GuardedIndexes {
+    /// Load guarded record indexes from file paths.
+    ///
+    /// The files are loaded in the [path order](RecordIndexerConfig::path_order) of the configuration.
+    pub fn load_paths<'a, P, I>(
+        paths: I,
+        config: RecordIndexerConfig,
+        guard: GuardConfig,
+    ) -> Result<Self>
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<Cow<'a, Path>>,
+    {
+        let paths: Vec<_> = paths
+            .into_iter()
+            .map(|path| path.into().into_owned())
+            .collect();
+        let mut num_records = 0;
+        let files: Vec<_> = sort_paths(paths, config.path_order)?
+            .into_iter()
+            .enumerate()
+            .map(|(files_done, path)| {
+                let files_done = files_done as u64;
+                let (identity, positions) = index_file(&path, &config)
+                    .map_err(|err| err.after_progress(files_done, num_records))?;
+                num_records += positions.len() as u64;
+                Ok(GuardedFile {
+                    path: Arc::new(path),
+                    identity,
+                    positions,
+                    reader: None,
+                    num_unchecked_reads: 0,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        let mut indexes = Self {
+            config,
+            guard,
+            files,
+            starts: vec![],
+        };
+        indexes.update_starts();
+        Ok(indexes)
+    }
+
+    /// The number of records.
+    pub fn len(&self) -> usize {
+        self.files.iter().map(|file| file.positions.len()).sum()
+    }
+
+    /// Returns true if there are no records.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The recorded identity of each file in the order of files.
+    pub fn identities(&self) -> impl Iterator<Item = (&Path, FileIdentity)> + '_ {
+        self.files
+            .iter()
+            .map(|file| (file.path.as_path(), file.identity))
+    }
+
+    /// The record indexes, which are not guarded on their own.
+    pub fn to_indexes(&self) -> Vec<RecordIndex> {
+        self.files
+            .iter()
+            .flat_map(|file| {
+                file.positions
+                    .iter()
+                    .map(|&Position { offset, len }| RecordIndex {
+                        path: file.path.clone(),
+                        offset,
+                        len,
+                    })
+            })
+            .collect()
+    }
+
+    /// Load the `index`-th record, checking the identity of its file.
+    pub fn get<T>(&mut self, index: usize) -> Result<T>
+    where
+        T: Record,
+    {
+        // reindexing may move the record to another file
+        let (file_index, position) = loop {
+            let (file_index, position) = self.locate(index)?;
+            if !self.check(file_index)? {
+                break (file_index, position);
+            }
+        };
+        let file = &mut self.files[file_index];
+        file.num_unchecked_reads += 1;
+        let reader = file.reader.as_mut().unwrap();
+        position.load_from(reader)
+    }
+
+    /// Load the records at the global indexes, with an outcome per index.
+    ///
+    /// Indexes out of range are [Missing](RecordResult::Missing), and a record failing
+    /// to be read, including by a changed file, is [Failed](RecordResult::Failed)
+    /// without failing the others.
+    pub fn get_many_detailed<T>(&mut self, requests: &[usize]) -> BatchResult<T>
+    where
+        T: Record,
+    {
+        requests
+            .iter()
+            .map(|&index| {
+                if index >= self.len() {
+                    return RecordResult::Missing;
+                }
+                match self.get(index) {
+                    Ok(record) => RecordResult::Ok(record),
+                    Err(error) => RecordResult::Failed(error),
+                }
+            })
+            .collect()
+    }
+
+    /// The configuration of indexing files.
+    pub(crate) fn config(&self) -> &RecordIndexerConfig {
+        &self.config
+    }
+
+    /// Index a file and append its records, returning the number of appended records.
+    pub(crate) fn push_file(&mut self, path: PathBuf) -> Result<usize> {
+        let (identity, positions) = index_file(&path, &self.config)?;
+        let num_records = positions.len();
+        self.files.push(GuardedFile {
+            path: Arc::new(path),
+            identity,
+            positions,
+            reader: None,
+            num_unchecked_reads: 0,
+        });
+        self.update_starts();
+        Ok(num_records)
+    }
+
+    /// Find the file and the position of the `index`-th record.
+    fn locate(&self, index: usize) -> Result<(usize, Position)> {
+        let file_index = self.starts.partition_point(|&start| start <= index);
+        let position = file_index
+            .checked_sub(1)
+            .and_then(|file_index| {
+                let position = self.files[file_index]
+                    .positions
+                    .get(index - self.starts[file_index])?;
+                Some((file_index, *position))
+            })
+            .ok_or_else(|| {
+                Error::invalid_argument(format!(
+                    "the record index {} is out of range of {} records",
+                    index,
+                    self.len()
+                ))
+            })?;
+        Ok(position)
+    }
+
+    /// Open the file if necessary and check its identity when due.
+    ///
+    /// It returns true if the file is reindexed.
+    fn check(&mut self, file_index: usize) -> Result<bool> {
+        let file = &mut self.files[file_index];
+        let found = match &file.reader {
+            None => {
+                let reader = File::open(&*file.path)?;
+                let found = FileIdentity::from_metadata(&reader.metadata()?);
+                file.reader = Some(BufReader::new(reader));
+                found
+            }
+            Some(_) => {
+                let due = self
+                    .guard
+                    .check_interval
+                    .is_some_and(|interval| file.num_unchecked_reads >= interval.get());
+                if !due {
+                    return Ok(false);
+                }
+                // the path is checked in case the file was replaced by another one
+                FileIdentity::of(&*file.path)?
+            }
+        };
+        file.num_unchecked_reads = 0;
+
+        if found == file.identity {
+            return Ok(false);
+        }
+        file.reader = None;
+
+        match self.guard.on_change {
+            FileChangePolicy::Fail => Err(Error::FileChanged {
+                path: file.path.to_path_buf(),
+                expected: file.identity,
+                found,
+            }),
+            FileChangePolicy::Reindex => {
+                let (identity, positions) = index_file(&file.path, &self.config)?;
+                file.identity = identity;
+                file.positions = positions;
+                self.update_starts();
+                Ok(true)
+            }
+        }
+    }
+
+    fn update_starts(&mut self) {
+        self.starts = self
+            .files
+            .iter()
+            .scan(0, |start, file| {
+                let curr = *start;
+                *start += file.positions.len();
+                Some(curr)
+            })
+            .collect();
+    }
+}
+
+/// Index a file, returning the identity of the indexed file.
+fn index_file(path: &Path, config: &RecordIndexerConfig) -> Result<(FileIdentity, Vec<Position>)> {
+    let file = File::open(path)?;
+    let identity = FileIdentity::from_metadata(&file.metadata()?);
+    let mut reader = BufReader::new(file);
+
+    if let Some(unsupported) = crate::format::detect_format(&mut reader)?.unsupported() {
+        if !config.skip_unsupported {
+            return Err(unsupported.into_error());
+        }
+        return Ok((identity, vec![]));
+    }
+    if let Some(mismatch) = crate::content::check_kind(&mut reader, config.expect_kind)? {
+        if !config.skip_mismatched_kind {
+            return Err(mismatch.into_error(path.to_path_buf()));
+        }
+        return Ok((identity, vec![]));
+    }
+    let positions = load_reader(reader, config.clone()).collect::<Result<_>>()?;
+    Ok((identity, positions))
+}
@@ -0,0 +1,293 @@
+use super::{sort_paths, FileIdentity, GuardConfig, GuardedIndexes, RecordIndexerConfig};
+use crate::{
+    error::{Error, Result},
+    record::Record,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// Options for [LiveIndexes::watch_directory].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WatchOptions {
+    /// Only files whose names start with the prefix are watched. Sidecar
+    /// [metadata](crate::metadata) files are never watched.
+    pub file_name_prefix: String,
+    /// The minimum time between two scans of the directory.
+    pub poll_interval: Duration,
+    /// The time the identity of a new file must stay unchanged before it is indexed.
+    pub settle_time: Duration,
+    /// If set, iterating [records](LiveIndexes::records) stops once no record arrives
+    /// for the duration. Otherwise, it waits for new files forever.
+    pub max_idle: Option<Duration>,
+    /// The configuration of indexing each file. The files found by one scan are
+    /// indexed in the [listed order](super::PathOrder) of the path order.
+    pub indexer: RecordIndexerConfig,
+    /// The staleness guard of indexed files.
+    pub guard: GuardConfig,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            file_name_prefix: String::new(),
+            poll_interval: Duration::from_secs(1),
+            settle_time: Duration::from_secs(5),
+            max_idle: None,
+            indexer: RecordIndexerConfig::default(),
+            guard: GuardConfig::default(),
+        }
+    }
+}
+
+/// Record indexes of a directory that grow as new files arrive.
+///
+/// The directory is scanned by [poll](LiveIndexes::poll), either explicitly or while
+/// iterating [records](LiveIndexes::records). A new file is indexed once its
+/// [FileIdentity] is unchanged across scans for at least the
+/// [settle time](WatchOptions::settle_time), so files being written are not read
+/// before they are complete. Files present when watching starts settle the same way.
+///
+/// Records of indexed files keep their indexes and are yielded exactly once, in the
+/// order the files are indexed. Indexed files are [guarded](GuardedIndexes) against
+/// changes by the [guard configuration](WatchOptions::guard), so a file removed or
+/// rewritten after indexing fails reading or is reindexed as by [GuardedIndexes].
+#[derive(Debug)]
+pub struct LiveIndexes {
+    dir: PathBuf,
+    file_name_prefix: String,
+    poll_interval: Duration,
+    settle_time: Duration,
+    max_idle: Option<Duration>,
+    indexes: GuardedIndexes,
+    indexed: HashSet<PathBuf>,
+    pending: HashMap<PathBuf, PendingFile>,
+    last_poll: Option<Instant>,
+    /// The last time a file with records was indexed.
+    last_arrival: Instant,
+    num_yielded: usize,
+}
+
+/// A file waiting to settle.
+#[derive(Debug)]
+struct PendingFile {
+    identity: FileIdentity,
+    /// The time the identity was first seen.
+    since: Instant,
+}
+
+impl LiveIndexes {
+    /// Start watching the directory, which is scanned once before returning.
+    pub fn watch_directory<P>(dir: P, options: WatchOptions) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let WatchOptions {
+            file_name_prefix,
+            poll_interval,
+            settle_time,
+            max_idle,
+            indexer,
+            guard,
+        } = options;
+        let dir = dir.as_ref().to_path_buf();
+        if !dir.is_dir() {
+            return Err(Error::invalid_argument(format!(
+                "the watched path {} is not a directory",
+                dir.display()
+            )));
+        }
+
+        let mut live = Self {
+            dir,
+            file_name_prefix,
+            poll_interval,
+            settle_time,
+            max_idle,
+            indexes: GuardedIndexes::load_paths(Vec::<PathBuf>::new(), indexer, guard)?,
+            indexed: HashSet::new(),
+            pending: HashMap::new(),
+            last_poll: None,
+            last_arrival: Instant::now(),
+            num_yielded: 0,
+        };
+        live.poll()?;
+        Ok(live)
+    }
+
+    /// Scan the directory once and index the settled files, returning the number of
+    /// newly indexed files.
+    pub fn poll(&mut self) -> Result<usize> {
+        let now = Instant::now();
+        self.last_poll = Some(now);
+
+        let mut found = HashSet::new();
+        let mut settled = vec![];
+        for entry in self
+            .dir
+            .read_dir()
+            .map_err(|err| Error::from_io_with_context(err, &self.dir, None))?
+        {
+            let path = entry?.path();
+            if !self.is_watched(&path) || self.indexed.contains(&path) {
+                continue;
+            }
+            // the file may be renamed or removed since listed
+            let identity = match std::fs::metadata(&path) {
+                Ok(metadata) if metadata.is_file() => FileIdentity::from_metadata(&metadata),
+                Ok(_) => continue,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(Error::from_io_with_context(err, &path, None)),
+            };
+            found.insert(path.clone());
+
+            match self.pending.get_mut(&path) {
+                Some(pending) if pending.identity == identity => {
+                    if now.duration_since(pending.since) >= self.settle_time {
+                        settled.push(path);
+                    }
+                }
+                Some(pending) => {
+                    pending.identity = identity;
+                    pending.since = now;
+                }
+                None => {
+                    self.pending.insert(
+                        path,
+                        PendingFile {
+                            identity,
+                            since: now,
+                        },
+                    );
+                }
+            }
+        }
+        self.pending.retain(|path, _| found.contains(path));
+
+        let settled = sort_paths(settled, self.indexes.config().path_order.listed())?;
+        let num_files = settled.len();
+        for path in settled {
+            self.pending.remove(&path);
+            let num_records = self
+                .indexes
+                .push_file(path.clone())
+                .map_err(|err| err.with_io_context(&path, None))?;
+            if num_records > 0 {
+                self.last_arrival = now;
+            }
+            self.indexed.insert(path);
+        }
+        Ok(num_files)
+    }
+
+    /// The number of indexed files.
+    pub fn files_discovered(&self) -> usize {
+        self.indexed.len()
+    }
+
+    /// The number of new files waiting to settle as of the last scan.
+    pub fn files_pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// The number of indexed records.
+    pub fn len(&self) -> usize {
+        self.indexes.len()
+    }
+
+    /// Returns true if no records are indexed yet.
+    pub fn is_empty(&self) -> bool {
+        self.indexes.is_empty()
+    }
+
+    /// The number of records yielded by [records](LiveIndexes::records).
+    pub fn num_yielded(&self) -> usize {
+        self.num_yielded
+    }
+
+    /// The guarded indexes of the indexed files.
+    pub fn indexes(&mut self) -> &mut GuardedIndexes {
+        &mut self.indexes
+    }
+
+    /// Iterate over the records not yielded yet, waiting for new files when all
+    /// indexed records are yielded.
+    pub fn records<T>(&mut self) -> LiveRecordIter<'_, T>
+    where
+        T: Record,
+    {
+        LiveRecordIter {
+            live: self,
+            done: false,
+            _phantom: PhantomData,
+        }
+    }
+
+    fn is_watched(&self, path: &Path) -> bool {
+        path.file_name().is_some_and(|name| {
+            name.to_string_lossy().starts_with(&self.file_name_prefix)
+                && !crate::metadata::is_metadata_path(path)
+                && !super::is_index_path(path)
+        })
+    }
+
+    fn next_record<T>(&mut self) -> Result<Option<T>>
+    where
+        T: Record,
+    {
+        loop {
+            if self.num_yielded < self.indexes.len() {
+                let record = self.indexes.get(self.num_yielded)?;
+                self.num_yielded += 1;
+                return Ok(Some(record));
+            }
+            if self
+                .max_idle
+                .is_some_and(|max_idle| self.last_arrival.elapsed() >= max_idle)
+            {
+                return Ok(None);
+            }
+            let cancel = self.indexes.config().cancel.as_ref();
+            crate::cancel::check(cancel, self.indexed.len() as u64, self.num_yielded as u64)?;
+
+            let mut wait = self
+                .last_poll
+                .map(|last_poll| self.poll_interval.saturating_sub(last_poll.elapsed()))
+                .unwrap_or_default();
+            if let Some(max_idle) = self.max_idle {
+                wait = wait.min(max_idle.saturating_sub(self.last_arrival.elapsed()));
+            }
+            std::thread::sleep(wait);
+            self.poll()?;
+        }
+    }
+}
+
+/// Iterator of records of a [LiveIndexes], returned by [records](LiveIndexes::records).
+pub struct LiveRecordIter<'a, T> {
+    live: &'a mut LiveIndexes,
+    done: bool,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> Iterator for LiveRecordIter<'_, T>
+where
+    T: Record,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.live.next_record().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.done = true;
+        }
+        result
+    }
+}
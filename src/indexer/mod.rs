@@ -1,6 +1,22 @@
 //! The indexer that enumerate record locations from one or multiple TFRecord files.
 
+mod batch;
+mod cache;
+mod corrupt;
+mod filter;
+mod guard;
+mod live;
+mod shuffle;
+mod stable;
 mod sync;
+pub use batch::*;
+pub use cache::*;
+pub use corrupt::*;
+pub use filter::*;
+pub use guard::*;
+pub use live::*;
+pub use shuffle::*;
+pub use stable::*;
 pub use sync::*;
 
 #[cfg(feature = "async")]
@@ -8,7 +24,15 @@ mod r#async;
 #[cfg(feature = "async")]
 pub use r#async::*;
 
-use std::{path::PathBuf, sync::Arc};
+use crate::{
+    cancel::CancelFlag, compression::Compression, content::ContentKind, error::Result,
+    integrity::IntegrityMode, io::OpTimeout, limits::Limits, trace::TraceHooks,
+};
+use std::{
+    collections::HashSet,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
 
 /// The file path and record position in file.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -28,13 +52,168 @@ pub struct Position {
 /// Configuration for indexer methods.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct RecordIndexerConfig {
-    pub check_integrity: bool,
+    /// The policy of verifying checksums.
+    pub integrity: IntegrityMode,
+    /// The caps on resource consumption. Records longer than
+    /// [max_record_len](Limits::max_record_len) fail with
+    /// [Error::LimitExceeded](crate::Error::LimitExceeded).
+    pub limits: Limits,
+    /// The deadline of each I/O operation. It only applies to async indexer functions.
+    pub op_timeout: Option<OpTimeout>,
+    /// The order of files loaded from multiple paths.
+    pub path_order: PathOrder,
+    /// If set, files of [unsupported formats](crate::format) are skipped instead of
+    /// failing with [Error::Unsupported](crate::Error::Unsupported).
+    pub skip_unsupported: bool,
+    /// If set, the [content kind](crate::content) of each file is checked before
+    /// indexing, failing with [Error::ContentKindMismatch](crate::Error::ContentKindMismatch)
+    /// on other kinds.
+    pub expect_kind: Option<ContentKind>,
+    /// If set, files of other kinds than [expect_kind](Self::expect_kind) are skipped
+    /// instead of failing.
+    pub skip_mismatched_kind: bool,
+    /// If set, records failing checksum verification are skipped instead of failing with
+    /// [Error::ChecksumMismatch](crate::Error::ChecksumMismatch). The skipped records are
+    /// returned by [load_paths_with_report], [load_file_with_report] and
+    /// [load_reader_with_report]. The async indexer functions fail with
+    /// [Error::InvalidArgumentsError](crate::Error::InvalidArgumentsError) if set.
+    pub skip_corrupt: bool,
+    /// If set, indexing is cancelled once the flag is raised, failing with
+    /// [Error::Cancelled](crate::Error::Cancelled).
+    pub cancel: Option<CancelFlag>,
+    /// If set to [Gzip](Compression::Gzip), GZIP-compressed files are indexed by the
+    /// offsets of records in the decompressed stream, instead of failing with
+    /// [Error::Unsupported](crate::Error::Unsupported). Plain files are still read as
+    /// plain. See [compression](crate::compression) for the cost of loading such indexes.
+    /// The async indexer functions fail with
+    /// [Error::InvalidArgumentsError](crate::Error::InvalidArgumentsError) if set.
+    pub compression: Compression,
+    /// If set, [IndexDataset](crate::trace::Operation::IndexDataset) and
+    /// [IndexFile](crate::trace::Operation::IndexFile) spans of the datasets loaded from
+    /// multiple files or sources are reported to the hooks.
+    pub trace: Option<TraceHooks>,
 }
 
 impl Default for RecordIndexerConfig {
     fn default() -> Self {
         Self {
-            check_integrity: true,
+            integrity: IntegrityMode::Full,
+            limits: Limits::default(),
+            op_timeout: None,
+            path_order: PathOrder::AsGiven,
+            skip_unsupported: false,
+            expect_kind: None,
+            skip_mismatched_kind: false,
+            skip_corrupt: false,
+            cancel: None,
+            compression: Compression::None,
+            trace: None,
         }
     }
 }
+
+impl RecordIndexerConfig {
+    /// Build the default configuration with the on/off switch of checksum verification.
+    ///
+    /// The `check_integrity` field is replaced by
+    /// [integrity](RecordIndexerConfig::integrity). `true` maps to [IntegrityMode::Full]
+    /// and `false` to [IntegrityMode::Off].
+    #[deprecated(
+        since = "0.14.0",
+        note = "set `integrity` to an `IntegrityMode`, such as `check_integrity.into()`"
+    )]
+    pub fn with_check_integrity(check_integrity: bool) -> Self {
+        Self {
+            integrity: check_integrity.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// The order of files when loading record indexes from multiple files.
+///
+/// The order of files determines the order of records, so a platform-independent
+/// order is required for deterministic training. Paths listed from a directory, by
+/// [load_prefix] or [load_glob], have no meaningful order of their own, so they are
+/// sorted [lexicographically](PathOrder::Lexicographic) if [AsGiven](PathOrder::AsGiven)
+/// is specified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PathOrder {
+    /// Keep the order given by the caller.
+    #[default]
+    AsGiven,
+    /// Sort by the UTF-8 bytes of paths with `/` separators.
+    ///
+    /// Paths sharing a base directory, such as the matches of a glob pattern, are
+    /// effectively sorted by their paths relative to the base directory. Non-UTF-8
+    /// components are compared after lossy conversion.
+    Lexicographic,
+    /// Sort by the modification time of files, breaking ties lexicographically.
+    ByModifiedTime,
+}
+
+impl PathOrder {
+    /// The order for paths listed from a directory.
+    pub(crate) fn listed(self) -> Self {
+        match self {
+            Self::AsGiven => Self::Lexicographic,
+            order => order,
+        }
+    }
+}
+
+/// Sort paths in the specified order.
+///
+/// The sort is stable, so the output is identical for the same set of paths given in
+/// different orders, except for [AsGiven](PathOrder::AsGiven).
+pub fn sort_paths(mut paths: Vec<PathBuf>, order: PathOrder) -> Result<Vec<PathBuf>> {
+    match order {
+        PathOrder::AsGiven => {}
+        PathOrder::Lexicographic => {
+            paths.sort_by_cached_key(|path| path_sort_key(path));
+        }
+        PathOrder::ByModifiedTime => {
+            let mut keyed: Vec<_> = paths
+                .into_iter()
+                .map(|path| {
+                    let modified = std::fs::metadata(&path)?.modified()?;
+                    Ok(((modified, path_sort_key(&path)), path))
+                })
+                .collect::<Result<_>>()?;
+            keyed.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+            paths = keyed.into_iter().map(|(_, path)| path).collect();
+        }
+    }
+    Ok(paths)
+}
+
+/// Get the distinct file paths of record indexes in the order of appearance.
+pub fn file_paths(indexes: &[RecordIndex]) -> Vec<Arc<PathBuf>> {
+    let mut paths: Vec<Arc<PathBuf>> = vec![];
+    let mut seen: HashSet<Arc<PathBuf>> = HashSet::new();
+    for index in indexes {
+        // indexes are usually grouped by file
+        if paths.last() == Some(&index.path) {
+            continue;
+        }
+        if seen.insert(index.path.clone()) {
+            paths.push(index.path.clone());
+        }
+    }
+    paths
+}
+
+/// The platform-independent sort key of a path.
+fn path_sort_key(path: &Path) -> String {
+    let components: Vec<_> = path
+        .components()
+        .map(|component| match component {
+            Component::RootDir => "".into(),
+            component => component.as_os_str().to_string_lossy(),
+        })
+        .collect();
+    match components.as_slice() {
+        [root] if root.is_empty() => "/".into(),
+        components => components.join("/"),
+    }
+}
@@ -0,0 +1,284 @@
+use super::{sync::open_decoded, RecordIndex};
+use crate::{
+    compression::Decoded,
+    error::{ensure_argument, Error, Result},
+    latency::{self, Phase},
+    record::Record,
+    record_reader::RecordReaderConfig,
+    trace::{AttributeValue, Operation, Span},
+    utils::SplitMix64,
+};
+use std::{
+    fs::File,
+    io::{BufReader, Seek as _, SeekFrom},
+    marker::PhantomData,
+    path::PathBuf,
+    sync::Arc,
+};
+
+/// Options for [iter_permuted] and [iter_shuffled].
+#[derive(Debug, Clone)]
+pub struct PermutedIterOptions {
+    /// The maximum number of files open at a time. It must be positive and within
+    /// [max_open_files](crate::Limits::max_open_files) of the reader limits.
+    pub max_open_files: usize,
+    /// The configuration of the readers of files.
+    pub reader: RecordReaderConfig,
+}
+
+impl Default for PermutedIterOptions {
+    fn default() -> Self {
+        Self {
+            max_open_files: 16,
+            reader: RecordReaderConfig::default(),
+        }
+    }
+}
+
+/// The permutation of `0..len` determined by the seed.
+///
+/// The permutation is stable across runs, versions and platforms.
+pub fn shuffled_permutation(len: usize, seed: u64) -> Vec<usize> {
+    let mut permutation: Vec<usize> = (0..len).collect();
+    SplitMix64(seed).shuffle(&mut permutation);
+    permutation
+}
+
+/// Iterate records in an order shuffled by the seed, yielding global indexes alongside
+/// records.
+///
+/// It is [iter_permuted] over the [shuffled_permutation] of the indexes, so the same
+/// seed gives the same order across runs.
+///
+/// ```rust
+/// # fn main() -> tfrecord::Result<()> {
+/// use tfrecord::{indexer, samples, Example};
+///
+/// let dataset = samples::tiny_dataset(2, 50)?;
+/// let indexes: Vec<_> =
+///     indexer::load_paths(dataset.paths(), Default::default()).collect::<Result<_, _>>()?;
+/// for epoch in 0..2 {
+///     for record in indexer::iter_shuffled::<Example>(&indexes, epoch, Default::default())? {
+///         let (_index, _example) = record?;
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn iter_shuffled<T>(
+    indexes: &[RecordIndex],
+    seed: u64,
+    options: PermutedIterOptions,
+) -> Result<PermutedIter<'_, T>>
+where
+    T: Record,
+{
+    iter_permuted(indexes, shuffled_permutation(indexes.len(), seed), options)
+}
+
+/// Iterate the records of the indexes in the order of the permutation, yielding global
+/// indexes alongside records.
+///
+/// The permutation holds positions in the indexes, which must be in bounds. It may skip
+/// or repeat positions. At most [max_open_files](PermutedIterOptions::max_open_files)
+/// files are open at a time, and the least recently read file is closed beyond the
+/// budget. Consecutive positions in the same file reuse its reader, and a forward jump
+/// within the buffer of the reader costs no system call, so permutations that group
+/// positions by file read almost as fast as [iter_from](super::iter_from).
+///
+/// The index of a record within its file, used by the
+/// [integrity mode](crate::integrity::IntegrityMode), is counted from the preceding
+/// indexes of the same file regardless of the permutation.
+pub fn iter_permuted<T>(
+    indexes: &[RecordIndex],
+    permutation: Vec<usize>,
+    options: PermutedIterOptions,
+) -> Result<PermutedIter<'_, T>>
+where
+    T: Record,
+{
+    let PermutedIterOptions {
+        max_open_files,
+        reader,
+    } = options;
+    ensure_argument!(max_open_files > 0, "max_open_files must be positive");
+    reader.limits.check_open_files(max_open_files)?;
+    if let Some(&position) = permutation
+        .iter()
+        .find(|&&position| position >= indexes.len())
+    {
+        return Err(Error::invalid_argument(format!(
+            "the permutation position {} is out of bounds of {} indexes",
+            position,
+            indexes.len()
+        )));
+    }
+
+    // the first position of the run of indexes of the same file containing each index
+    let mut file_starts = Vec::with_capacity(indexes.len());
+    for (position, index) in indexes.iter().enumerate() {
+        let start = match position.checked_sub(1) {
+            Some(prev) if indexes[prev].path == index.path => file_starts[prev],
+            _ => position,
+        };
+        file_starts.push(start);
+    }
+
+    let span = Span::start(
+        reader.trace.as_ref(),
+        Operation::Stream,
+        None,
+        &[("records", AttributeValue::U64(permutation.len() as u64))],
+    )
+    .map(Span::with_counts);
+
+    Ok(PermutedIter {
+        indexes,
+        permutation: permutation.into_iter(),
+        file_starts,
+        files: vec![],
+        max_open_files,
+        reader,
+        num_reads: 0,
+        buf: vec![],
+        span,
+        _phantom: PhantomData,
+    })
+}
+
+/// The iterator returned by [iter_permuted] and [iter_shuffled].
+pub struct PermutedIter<'a, T>
+where
+    T: Record,
+{
+    indexes: &'a [RecordIndex],
+    permutation: std::vec::IntoIter<usize>,
+    file_starts: Vec<usize>,
+    files: Vec<OpenFile>,
+    max_open_files: usize,
+    reader: RecordReaderConfig,
+    num_reads: u64,
+    buf: Vec<u8>,
+    span: Option<Span>,
+    _phantom: PhantomData<T>,
+}
+
+struct OpenFile {
+    path: Arc<PathBuf>,
+    reader: Decoded<BufReader<File>>,
+    /// The current position, or `None` if unknown after a failed read.
+    pos: Option<u64>,
+    last_read: u64,
+}
+
+impl<T> PermutedIter<'_, T>
+where
+    T: Record,
+{
+    /// The number of files currently open.
+    pub fn num_open_files(&self) -> usize {
+        self.files.len()
+    }
+
+    fn read(&mut self, position: usize) -> Result<T> {
+        let RecordIndex {
+            ref path,
+            offset,
+            len,
+        } = self.indexes[position];
+        let RecordReaderConfig {
+            integrity,
+            ref limits,
+            ref latency,
+            ..
+        } = self.reader;
+        let latency = latency.as_ref();
+        limits.check_record_len(len)?;
+
+        let file_index = (position - self.file_starts[position]) as u64;
+        let is_last = self
+            .indexes
+            .get(position + 1)
+            .is_none_or(|next| next.path != *path);
+        let check_data = integrity.checks_data(file_index, is_last);
+
+        self.num_reads += 1;
+        let slot = match self.files.iter().position(|file| file.path == *path) {
+            Some(slot) => slot,
+            None => {
+                if self.files.len() >= self.max_open_files {
+                    let lru = self
+                        .files
+                        .iter()
+                        .enumerate()
+                        .min_by_key(|(_, file)| file.last_read)
+                        .map(|(slot, _)| slot)
+                        .unwrap();
+                    self.files.swap_remove(lru);
+                }
+                let reader = latency::time(latency, Phase::Open, || open_decoded(path))?;
+                self.files.push(OpenFile {
+                    path: path.clone(),
+                    reader,
+                    pos: Some(0),
+                    last_read: 0,
+                });
+                self.files.len() - 1
+            }
+        };
+        let file = &mut self.files[slot];
+        file.last_read = self.num_reads;
+
+        let sought = latency::time(latency, Phase::Seek, || match file.pos {
+            Some(pos) => file.reader.seek_relative(offset as i64 - pos as i64),
+            None => file.reader.seek(SeekFrom::Start(offset)).map(|_| ()),
+        });
+        if let Err(err) = sought {
+            file.pos = None;
+            return Err(Error::from_io_with_context(
+                err,
+                path.as_path(),
+                Some(offset),
+            ));
+        }
+        let expect_cksum = match latency::time(latency, Phase::Read, || {
+            crate::io::sync::try_read_record_data_into(&mut file.reader, len, &mut self.buf)
+        }) {
+            Ok(expect_cksum) => expect_cksum,
+            Err(err) => {
+                // the position is unknown, so the next read seeks from the start
+                file.pos = None;
+                return Err(err.with_io_context(path, Some(offset)));
+            }
+        };
+        // the payload checksum is consumed as well
+        file.pos = Some(offset + len as u64 + 4);
+
+        latency::time(latency, Phase::Decode, || {
+            integrity.verify_and_decode(&self.buf, expect_cksum, check_data, limits)
+        })
+    }
+}
+
+impl<T> Iterator for PermutedIter<'_, T>
+where
+    T: Record,
+{
+    type Item = Result<(usize, T)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let position = self.permutation.next()?;
+        let result = self.read(position);
+        if let Some(span) = &mut self.span {
+            match &result {
+                Ok(_) => span.count_record(self.indexes[position].len),
+                Err(err) => span.fail(err),
+            }
+        }
+        Some(result.map(|record| (position, record)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.permutation.size_hint()
+    }
+}
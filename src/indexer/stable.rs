@@ -0,0 +1,182 @@
+use super::{get_many_detailed, load_paths, BatchResult, RecordIndex, RecordIndexerConfig};
+use crate::{
+    error::{Error, Result},
+    fingerprint::{self, Fingerprint, FingerprintLevel},
+    record::Record,
+    record_reader::RecordReaderConfig,
+};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, OnceLock},
+};
+
+/// The identifier of a record which is independent of other files in the dataset.
+///
+/// It consists of the [Content](FingerprintLevel::Content) fingerprint of the file of
+/// the record and the byte offset of the record in the file. Adding, removing or
+/// reordering other files keeps the id, as does moving or renaming the file. Any change
+/// to the contents of the file breaks the ids of all its records.
+///
+/// The id is formatted as the fingerprint and the offset separated by `@`, which can
+/// be parsed back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StableId {
+    pub file: Fingerprint,
+    pub offset: u64,
+}
+
+impl fmt::Display for StableId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.file, self.offset)
+    }
+}
+
+impl FromStr for StableId {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let invalid = || Error::conversion(format!("invalid stable id '{}'", text));
+        let (file, offset) = text.split_once('@').ok_or_else(invalid)?;
+        let file: Fingerprint = file.parse()?;
+        if file.level() != FingerprintLevel::Content {
+            return Err(invalid());
+        }
+        Ok(Self {
+            file,
+            offset: offset.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// Record indexes addressable by [StableId]s.
+///
+/// The global index of a record changes when files are added to or removed from the
+/// path list, while its [StableId] does not. The files are hashed once on construction
+/// to derive the ids. The map from ids back to global indexes is built on the first
+/// [find_by_stable_id](StableIndexes::find_by_stable_id) call.
+///
+/// Files with identical contents share the same ids, which resolve to the records of
+/// the first such file.
+#[derive(Debug)]
+pub struct StableIndexes {
+    indexes: Vec<RecordIndex>,
+    file_ids: HashMap<Arc<PathBuf>, Fingerprint>,
+    lookup: OnceLock<HashMap<StableId, usize>>,
+}
+
+impl StableIndexes {
+    /// Load record indexes from file paths and hash the files.
+    ///
+    /// The files are loaded in the [path order](RecordIndexerConfig::path_order) of the configuration.
+    pub fn load_paths<'a, P, I>(paths: I, config: RecordIndexerConfig) -> Result<Self>
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<Cow<'a, Path>>,
+    {
+        let indexes: Vec<_> = load_paths(paths, config).collect::<Result<_>>()?;
+        Self::from_indexes(indexes)
+    }
+
+    /// Hash the files of record indexes.
+    pub fn from_indexes(indexes: Vec<RecordIndex>) -> Result<Self> {
+        let paths = super::file_paths(&indexes);
+        let fingerprints = fingerprint::fingerprint_each(&indexes, FingerprintLevel::Content)?;
+        Ok(Self {
+            file_ids: paths.into_iter().zip(fingerprints).collect(),
+            indexes,
+            lookup: OnceLock::new(),
+        })
+    }
+
+    /// The number of records.
+    pub fn len(&self) -> usize {
+        self.indexes.len()
+    }
+
+    /// Returns true if there are no records.
+    pub fn is_empty(&self) -> bool {
+        self.indexes.is_empty()
+    }
+
+    /// The record indexes in global order.
+    pub fn indexes(&self) -> &[RecordIndex] {
+        &self.indexes
+    }
+
+    /// The content fingerprint of a file, or `None` if the file has no records here.
+    pub fn file_id(&self, path: &Path) -> Option<Fingerprint> {
+        self.file_ids.get(&path.to_path_buf()).copied()
+    }
+
+    /// The id of the `index`-th record.
+    pub fn stable_id(&self, index: usize) -> Option<StableId> {
+        let record_index = self.indexes.get(index)?;
+        Some(StableId {
+            file: self.file_ids[&record_index.path],
+            offset: record_index.offset,
+        })
+    }
+
+    /// Find the global index of the record with the id.
+    pub fn find_by_stable_id(&self, id: &StableId) -> Option<usize> {
+        let lookup = self.lookup.get_or_init(|| {
+            let mut lookup = HashMap::with_capacity(self.indexes.len());
+            for index in 0..self.indexes.len() {
+                lookup
+                    .entry(self.stable_id(index).unwrap())
+                    .or_insert(index);
+            }
+            lookup
+        });
+        lookup.get(id).copied()
+    }
+
+    /// Load the `index`-th record.
+    pub fn get<T>(&self, index: usize) -> Result<T>
+    where
+        T: Record,
+    {
+        self.indexes
+            .get(index)
+            .ok_or_else(|| {
+                Error::invalid_argument(format!(
+                    "the record index {} is out of range of {} records",
+                    index,
+                    self.len()
+                ))
+            })?
+            .load()
+    }
+
+    /// Load the records at the global indexes, with an outcome per index.
+    ///
+    /// See [get_many_detailed].
+    pub fn get_many_detailed<T>(
+        &self,
+        requests: &[usize],
+        config: RecordReaderConfig,
+    ) -> BatchResult<T>
+    where
+        T: Record,
+    {
+        get_many_detailed(&self.indexes, requests, config)
+    }
+
+    /// Iterate records in global order, yielding ids alongside records.
+    pub fn iter<T>(
+        &self,
+        config: RecordReaderConfig,
+    ) -> impl Iterator<Item = Result<(StableId, T)>> + '_
+    where
+        T: Record,
+    {
+        super::iter_from(&self.indexes, 0, config).map(move |result| {
+            let (index, record) = result?;
+            Ok((self.stable_id(index).unwrap(), record))
+        })
+    }
+}
@@ -1,7 +1,19 @@
-use super::{Position, RecordIndex, RecordIndexerConfig};
+use super::{
+    is_index_path, sort_paths, CorruptReport, PathOrder, Position, RecordIndex,
+    RecordIndexerConfig, SkippedRecord,
+};
 use crate::{
+    cancel::{self, Progress},
+    compression::{Compression, Decoded},
     error::{Error, Result},
+    format::FileFormat,
+    latency::{self, Phase},
+    protobuf::Example,
+    protobuf_ext::FeatureProjection,
     record::Record,
+    record_reader::RecordReaderConfig,
+    shardspec::{ShardSpec, ShardSpecOptions},
+    trace::{AttributeValue, Operation, Span},
     utils,
 };
 use itertools::Itertools as _;
@@ -23,14 +35,242 @@ impl RecordIndex {
             offset,
             len,
         } = *self;
-        let mut reader = BufReader::new(File::open(&**path)?);
-        let bytes = read_record_at(&mut reader, offset, len)?;
+        let bytes = read_record_from(path, offset, len)?;
         let record = T::from_bytes(bytes)?;
         Ok(record)
     }
+
+    /// Load the record for the index, reading the bytes into a buffer reused across
+    /// loads.
+    ///
+    /// The record is decoded by [from_slice](Record::from_slice) from the buffer, which
+    /// saves the allocation per record of [load](RecordIndex::load).
+    pub fn load_with_buf<T>(&self, buf: &mut Vec<u8>) -> Result<T>
+    where
+        T: Record,
+    {
+        let Self {
+            ref path,
+            offset,
+            len,
+        } = *self;
+        read_record_into(&mut open_decoded(path)?, offset, len, buf)
+            .map_err(|error| error.with_io_context(path, Some(offset)))?;
+        T::from_slice(buf)
+    }
+
+    /// Load the raw payload for the index, verifying its checksum.
+    ///
+    /// The bytes are exactly the stored payload, without the frame, so that records are
+    /// copied or decoded by other means without a decode and encode cycle.
+    pub fn load_raw(&self) -> Result<Vec<u8>> {
+        let Self {
+            ref path,
+            offset,
+            len,
+        } = *self;
+        let mut reader = open_decoded(path)?;
+        let mut read = || -> Result<Vec<u8>> {
+            reader.seek(SeekFrom::Start(offset))?;
+            crate::io::sync::try_read_record_data(&mut reader, len, true)
+        };
+        read().map_err(|error| {
+            let frame = offset.saturating_sub(crate::io::sync::HEADER_LEN);
+            error
+                .with_io_context(path, Some(offset))
+                .with_checksum_context(path, frame)
+        })
+    }
+
+    /// Load the example for the index, decoding only the features in the projection.
+    pub fn load_projected(&self, projection: &FeatureProjection) -> Result<Example> {
+        let Self {
+            ref path,
+            offset,
+            len,
+        } = *self;
+        let bytes = read_record_from(path, offset, len)?;
+        Example::decode_projected(&bytes, projection)
+    }
+}
+
+impl Position {
+    /// Load the record at the position from a reader.
+    ///
+    /// The reader must be the one the position was enumerated from by [load_reader].
+    pub fn load_from<T, R>(&self, reader: &mut R) -> Result<T>
+    where
+        T: Record,
+        R: Read + Seek,
+    {
+        let Self { offset, len } = *self;
+        let bytes = read_record_at(reader, offset, len)?;
+        let record = T::from_bytes(bytes)?;
+        Ok(record)
+    }
+}
+
+/// Iterate records from the `start`-th index, yielding global indexes alongside records.
+///
+/// The iterator seeks directly to the record of the `start`-th index, and then reads the
+/// following records sequentially, reopening files when the indexes cross file boundaries.
+pub fn iter_from<T>(
+    indexes: &[RecordIndex],
+    start: usize,
+    config: RecordReaderConfig,
+) -> impl Iterator<Item = Result<(usize, T)>> + '_
+where
+    T: Record,
+{
+    let RecordReaderConfig {
+        integrity,
+        limits,
+        op_timeout: _,
+        latency,
+        compression: _,
+        trace,
+    } = config;
+    let mut span = Span::start(
+        trace.as_ref(),
+        Operation::Stream,
+        None,
+        &[(
+            "records",
+            AttributeValue::U64(indexes.len().saturating_sub(start) as u64),
+        )],
+    )
+    .map(Span::with_counts);
+    // the open file and the current position
+    let mut state: Option<(Arc<PathBuf>, Decoded<BufReader<File>>, u64)> = None;
+    let mut buf = vec![];
+    // the record index in the file, counted from the preceding indexes of the same file
+    let mut file_index = indexes.get(start).map_or(0, |first| {
+        indexes[..start]
+            .iter()
+            .rev()
+            .take_while(|index| index.path == first.path)
+            .count() as u64
+    });
+
+    indexes
+        .iter()
+        .enumerate()
+        .skip(start)
+        .map(move |(index, record_index)| {
+            let RecordIndex {
+                ref path,
+                offset,
+                len,
+            } = *record_index;
+            limits.check_record_len(len)?;
+
+            let is_first = index == 0 || indexes[index - 1].path != *path;
+            let is_last = indexes.get(index + 1).is_none_or(|next| next.path != *path);
+            if is_first {
+                file_index = 0;
+            }
+            let check_data = integrity.checks_data(file_index, is_last);
+            file_index += 1;
+
+            let latency = latency.as_ref();
+            let reader = match &mut state {
+                Some((curr_path, reader, pos)) if curr_path == path => {
+                    latency::time(latency, Phase::Seek, || {
+                        reader.seek_relative(offset as i64 - *pos as i64)
+                    })?;
+                    reader
+                }
+                _ => {
+                    let mut reader = latency::time(latency, Phase::Open, || open_decoded(path))?;
+                    latency::time(latency, Phase::Seek, || {
+                        reader.seek(SeekFrom::Start(offset))
+                    })?;
+                    &mut state.insert((path.clone(), reader, offset)).1
+                }
+            };
+            let expect_cksum = match latency::time(latency, Phase::Read, || {
+                crate::io::sync::try_read_record_data_into(reader, len, &mut buf)
+            }) {
+                Ok(expect_cksum) => expect_cksum,
+                Err(err) => {
+                    // the position is unknown, so the file is reopened for the next index
+                    state = None;
+                    return Err(err.with_io_context(path, Some(offset)));
+                }
+            };
+
+            // the payload checksum is consumed as well
+            if let Some((_, _, pos)) = &mut state {
+                *pos = offset + len as u64 + 4;
+            }
+            let record = latency::time(latency, Phase::Decode, || {
+                integrity.verify_and_decode(&buf, expect_cksum, check_data, &limits)
+            })
+            .map_err(|err| {
+                let frame = offset.saturating_sub(crate::io::sync::HEADER_LEN);
+                err.with_checksum_context(path, frame)
+            })?;
+            Ok((index, record))
+        })
+        .inspect(move |result| {
+            if let Some(span) = &mut span {
+                match result {
+                    Ok((index, _)) => span.count_record(indexes[*index].len),
+                    Err(err) => span.fail(err),
+                }
+            }
+        })
+}
+
+/// Load the raw payload of the `index`-th record by [load_raw](RecordIndex::load_raw), or
+/// `None` if the index is out of range.
+pub fn get_raw(indexes: &[RecordIndex], index: usize) -> Result<Option<Vec<u8>>> {
+    indexes.get(index).map(RecordIndex::load_raw).transpose()
+}
+
+/// Iterate raw payloads from the `start`-th index, yielding global indexes alongside
+/// payloads.
+///
+/// It is [iter_from] without decoding. The payloads are exactly the stored bytes, and
+/// are verified as configured by the [integrity](RecordReaderConfig::integrity) mode.
+///
+/// ```rust
+/// # fn main() -> tfrecord::Result<()> {
+/// use tfrecord::{indexer, samples, BytesIter, BytesWriter};
+///
+/// let dataset = samples::tiny_dataset(2, 3)?;
+/// let indexes: Vec<_> =
+///     indexer::load_paths(dataset.paths(), Default::default()).collect::<Result<_, _>>()?;
+///
+/// // copy the records with an odd index byte for byte
+/// let (mut writer, buffer) = BytesWriter::in_memory()?;
+/// for result in indexer::iter_raw_from(&indexes, 0, Default::default()) {
+///     let (index, bytes) = result?;
+///     if index % 2 == 1 {
+///         writer.send(bytes)?;
+///     }
+/// }
+/// writer.flush()?;
+///
+/// let copied: Vec<_> = BytesIter::from_bytes(buffer.to_vec(), Default::default())
+///     .collect::<Result<_, _>>()?;
+/// assert_eq!(copied.len(), 3);
+/// assert_eq!(indexer::get_raw(&indexes, 1)?, Some(copied[0].clone()));
+/// assert_eq!(indexer::get_raw(&indexes, 6)?, None);
+/// # Ok(())
+/// # }
+/// ```
+pub fn iter_raw_from(
+    indexes: &[RecordIndex],
+    start: usize,
+    config: RecordReaderConfig,
+) -> impl Iterator<Item = Result<(usize, Vec<u8>)>> + '_ {
+    iter_from(indexes, start, config)
 }
 
 /// Load record indexes from files specified by a prefix.
+///
+/// [Sidecar files](crate::metadata) and [index sidecar files](super::index_path) are excluded.
 pub fn load_prefix<'a, P>(
     prefix: P,
     config: RecordIndexerConfig,
@@ -43,7 +283,7 @@ where
     let file_name_prefix = Arc::new(file_name_prefix);
 
     // filter paths
-    let mut paths: Vec<_> = dir
+    let paths: Vec<_> = dir
         .read_dir()?
         .map(|result| result.map_err(Error::from))
         .filter_map(move |entry| {
@@ -55,9 +295,10 @@ where
                     return Ok(None);
                 }
                 let file_name = PathBuf::from(entry.file_name());
-                let path = file_name
-                    .starts_with(&*file_name_prefix)
-                    .then(|| entry.path());
+                let is_record_file = file_name.starts_with(&*file_name_prefix)
+                    && !crate::metadata::is_metadata_path(&file_name)
+                    && !is_index_path(&file_name);
+                let path = is_record_file.then(|| entry.path());
                 Ok(path)
             })()
             .transpose()
@@ -65,15 +306,38 @@ where
         .try_collect()?;
 
     // sort paths
-    // TODO: fix blocking?
-    paths.sort();
+    let paths = sort_paths(paths, config.path_order.listed())?;
 
     // construct dataset
+    let config = RecordIndexerConfig {
+        path_order: PathOrder::AsGiven,
+        ..config
+    };
     let indexes = load_paths(paths, config);
     Ok(indexes)
 }
 
 /// Load record indexes from file paths.
+///
+/// The files are loaded in the [path order](RecordIndexerConfig::path_order) of the configuration.
+/// Empty files are valid and contribute no indexes, so a dataset of empty files yields
+/// nothing.
+///
+/// ```rust
+/// # fn main() -> tfrecord::Result<()> {
+/// use tfrecord::{indexer, samples, Example};
+///
+/// let dataset = samples::tiny_dataset(3, 4)?;
+/// let indexes: Vec<_> =
+///     indexer::load_paths(dataset.paths(), Default::default()).collect::<Result<_, _>>()?;
+/// assert_eq!(indexes.len(), 12);
+///
+/// // random access to the 6th record
+/// let example: Example = indexes[5].load()?;
+/// assert_eq!(example, samples::example(5));
+/// # Ok(())
+/// # }
+/// ```
 pub fn load_paths<'a, P, I>(
     paths: I,
     config: RecordIndexerConfig,
@@ -82,40 +346,274 @@ where
     I: IntoIterator<Item = P>,
     P: Into<Cow<'a, Path>>,
 {
-    paths
+    load_paths_with_report(paths, config).0
+}
+
+/// Load record indexes from file paths, returning the report of records skipped by
+/// [skip_corrupt](RecordIndexerConfig::skip_corrupt).
+///
+/// The report is filled as the iterator advances.
+pub fn load_paths_with_report<'a, P, I>(
+    paths: I,
+    config: RecordIndexerConfig,
+) -> (impl Iterator<Item = Result<RecordIndex>>, CorruptReport)
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Cow<'a, Path>>,
+{
+    let report = CorruptReport::new();
+    let paths: Vec<_> = paths
         .into_iter()
         .map(|path| path.into().into_owned())
-        .map(move |path| load_file(path, config.clone()))
-        .map(
-            |iter| -> Box<dyn Iterator<Item = Result<RecordIndex>> + Send> {
-                match iter {
-                    Ok(iter) => Box::new(iter),
-                    Err(err) => Box::new([Err(err)].into_iter()),
+        .collect();
+    let paths: Vec<Result<_>> = match sort_paths(paths, config.path_order) {
+        Ok(paths) => paths.into_iter().map(Ok).collect(),
+        Err(err) => vec![Err(err)],
+    };
+
+    // the dataset span and the span of the current file
+    let mut dataset_span = Span::start(
+        config.trace.as_ref(),
+        Operation::IndexDataset,
+        None,
+        &[("files", AttributeValue::U64(paths.len() as u64))],
+    )
+    .map(Span::with_counts);
+    if let Some(span) = &mut dataset_span {
+        span.set("files", AttributeValue::U64(0));
+    }
+    let mut file_span: Option<Span> = None;
+
+    // the number of loaded files and records, and the iterator of the current file
+    let mut files_done = 0;
+    let mut num_records = 0;
+    let mut curr: Option<Box<dyn Iterator<Item = Result<RecordIndex>> + Send>> = None;
+    let mut paths = paths.into_iter();
+
+    let file_report = report.clone();
+    let iter = std::iter::from_fn(move || loop {
+        if let Some(iter) = &mut curr {
+            let result = iter.next();
+            for span in [&mut dataset_span, &mut file_span].into_iter().flatten() {
+                match &result {
+                    Some(Ok(index)) => span.count_record(index.len),
+                    Some(Err(err)) => span.fail(err),
+                    None => {}
                 }
-            },
-        )
-        .flatten()
+            }
+            match result {
+                Some(Ok(index)) => {
+                    num_records += 1;
+                    return Some(Ok(index));
+                }
+                Some(Err(Error::Cancelled { .. })) => {
+                    // stop at cancellation, counting the records of preceding files
+                    paths = vec![].into_iter();
+                    curr = None;
+                    file_span = None;
+                    return Some(Err(Error::Cancelled {
+                        progress: Progress::new(files_done, num_records),
+                    }));
+                }
+                Some(Err(err)) => return Some(Err(err)),
+                None => {
+                    curr = None;
+                    file_span = None;
+                    files_done += 1;
+                    if let Some(span) = &mut dataset_span {
+                        span.set("files", AttributeValue::U64(files_done));
+                    }
+                    continue;
+                }
+            }
+        }
+
+        let Some(path) = paths.next() else {
+            dataset_span = None;
+            return None;
+        };
+        if let Ok(path) = &path {
+            file_span = Span::start(
+                config.trace.as_ref(),
+                Operation::IndexFile,
+                dataset_span.as_ref().map(Span::handle),
+                &[("path", AttributeValue::Str(path.display().to_string()))],
+            )
+            .map(Span::with_counts);
+        }
+        match path.and_then(|path| load_file_reporting(path, config.clone(), file_report.clone())) {
+            Ok(iter) => curr = Some(Box::new(iter)),
+            Err(err) => {
+                for span in [&mut dataset_span, &mut file_span].into_iter().flatten() {
+                    span.fail(&err);
+                }
+                file_span = None;
+                return Some(Err(err));
+            }
+        }
+    });
+    (iter, report)
+}
+
+/// Expand a glob pattern to the paths of matching files.
+///
+/// Directories, [sidecar files](crate::metadata) and [index sidecar files](super::index_path) are
+/// excluded. The paths are sorted in
+/// the order, where [AsGiven](PathOrder::AsGiven) sorts
+/// [lexicographically](PathOrder::Lexicographic).
+#[cfg(feature = "glob")]
+pub fn expand_glob(pattern: &str, order: PathOrder) -> Result<Vec<PathBuf>> {
+    let paths: Vec<_> = glob::glob(pattern)
+        .map_err(|err| {
+            Error::invalid_argument(format!("invalid glob pattern '{}': {}", pattern, err))
+        })?
+        .map(|path| -> Result<_> {
+            let path = path.map_err(|err| {
+                let path = err.path().to_path_buf();
+                Error::from_io_with_context(err.into(), path, None)
+            })?;
+            let is_record_file = path.is_file()
+                && !crate::metadata::is_metadata_path(&path)
+                && !is_index_path(&path);
+            Ok(is_record_file.then_some(path))
+        })
+        .filter_map(Result::transpose)
+        .try_collect()?;
+    sort_paths(paths, order.listed())
+}
+
+/// Load record indexes from files matching a glob pattern.
+///
+/// The files are expanded by [expand_glob] in the [path order](RecordIndexerConfig::path_order).
+#[cfg(feature = "glob")]
+pub fn load_glob(
+    pattern: &str,
+    config: RecordIndexerConfig,
+) -> Result<impl Iterator<Item = Result<RecordIndex>>> {
+    let paths = expand_glob(pattern, config.path_order)?;
+    let config = RecordIndexerConfig {
+        path_order: PathOrder::AsGiven,
+        ..config
+    };
+    Ok(load_paths(paths, config))
+}
+
+/// Load record indexes from the shards of a [ShardSpec], in the order of shards.
+///
+/// The shards are resolved relative to the working directory, or under the directories
+/// in the [base](ShardSpec::base). The spec fails as a whole with
+/// [Error::MissingShards] if some shards do not exist, unless
+/// [allow_missing](ShardSpecOptions::allow_missing) is set.
+///
+/// ```rust
+/// # fn main() -> tfrecord::Result<()> {
+/// use tfrecord::{indexer, samples, shardspec::{self, ShardSpec}, BytesWriter};
+///
+/// let dataset = samples::tiny_dataset(0, 0)?;
+/// let spec = ShardSpec {
+///     base: dataset.dir().join("train").display().to_string(),
+///     num_shards: 3,
+/// };
+/// for path in spec.paths("") {
+///     BytesWriter::create(path)?.send(b"record".to_vec())?;
+/// }
+///
+/// let spec = shardspec::parse_spec(&spec.to_string())?;
+/// let num_records = indexer::load_shard_spec(&spec, Default::default(), Default::default())?
+///     .count();
+/// assert_eq!(num_records, 3);
+/// # Ok(())
+/// # }
+/// ```
+pub fn load_shard_spec(
+    spec: &ShardSpec,
+    options: ShardSpecOptions,
+    config: RecordIndexerConfig,
+) -> Result<impl Iterator<Item = Result<RecordIndex>>> {
+    let paths = spec.resolve("", options)?;
+    let config = RecordIndexerConfig {
+        path_order: PathOrder::AsGiven,
+        ..config
+    };
+    Ok(load_paths(paths, config))
 }
 
 /// Load record indexes from a file.
+///
+/// An empty file is valid and has no indexes. A file shorter than a record header is
+/// truncated, which fails with [UnexpectedEof](Error::UnexpectedEof).
 pub fn load_file<'a, P>(
     file: P,
     config: RecordIndexerConfig,
 ) -> Result<impl Iterator<Item = Result<RecordIndex>>>
+where
+    P: Into<Cow<'a, Path>>,
+{
+    load_file_reporting(file, config, CorruptReport::new())
+}
+
+/// Load record indexes from a file, returning the report of records skipped by
+/// [skip_corrupt](RecordIndexerConfig::skip_corrupt).
+///
+/// The report is filled as the iterator advances.
+pub fn load_file_with_report<'a, P>(
+    file: P,
+    config: RecordIndexerConfig,
+) -> Result<(impl Iterator<Item = Result<RecordIndex>>, CorruptReport)>
+where
+    P: Into<Cow<'a, Path>>,
+{
+    let report = CorruptReport::new();
+    let iter = load_file_reporting(file, config, report.clone())?;
+    Ok((iter, report))
+}
+
+/// Load record indexes from a file, adding skipped records to the report.
+fn load_file_reporting<'a, P>(
+    file: P,
+    config: RecordIndexerConfig,
+    report: CorruptReport,
+) -> Result<impl Iterator<Item = Result<RecordIndex>>>
 where
     P: Into<Cow<'a, Path>>,
 {
     let file = file.into().into_owned();
-    let reader = BufReader::new(File::open(&file)?);
+    let mut reader = BufReader::new(File::open(&file)?);
+    let format = crate::format::detect_format(&mut reader)?;
+    let (compression, unsupported) = match (format, config.compression) {
+        (FileFormat::Gzip, Compression::Gzip) => (Compression::Gzip, None),
+        _ => (Compression::None, format.unsupported()),
+    };
+    compression.check_supported()?;
+    let mut reader = Decoded::new(reader, compression);
+    if let Some(unsupported) = &unsupported {
+        if !config.skip_unsupported {
+            return Err(unsupported.clone().into_error());
+        }
+    }
+    let mismatch = match unsupported {
+        None => crate::content::check_kind(&mut reader, config.expect_kind)?,
+        Some(_) => None,
+    };
+    if let Some(mismatch) = &mismatch {
+        if !config.skip_mismatched_kind {
+            return Err(mismatch.clone().into_error(file));
+        }
+    }
+
     let file = Arc::new(file);
-    let iter = load_reader(reader, config).map(move |pos| {
-        let Position { offset, len } = pos?;
-        Ok(RecordIndex {
-            path: file.clone(),
-            offset,
-            len,
-        })
-    });
+    let iter = (unsupported.is_none() && mismatch.is_none())
+        .then(|| load_positions(reader, config, Some(file.clone()), report))
+        .into_iter()
+        .flatten()
+        .map(move |pos| {
+            let Position { offset, len } = pos?;
+            Ok(RecordIndex {
+                path: file.clone(),
+                offset,
+                len,
+            })
+        });
     Ok(iter)
 }
 
@@ -127,36 +625,195 @@ pub fn load_reader<R>(
 where
     R: Read + Seek,
 {
-    let RecordIndexerConfig { check_integrity } = config;
+    load_positions(reader, config, None, CorruptReport::new())
+}
+
+/// Load record positions from a reader, returning the report of records skipped by
+/// [skip_corrupt](RecordIndexerConfig::skip_corrupt).
+///
+/// The report is filled as the iterator advances.
+pub fn load_reader_with_report<R>(
+    reader: R,
+    config: RecordIndexerConfig,
+) -> (impl Iterator<Item = Result<Position>>, CorruptReport)
+where
+    R: Read + Seek,
+{
+    let report = CorruptReport::new();
+    let iter = load_positions(reader, config, None, report.clone());
+    (iter, report)
+}
 
-    itertools::unfold(Some(reader), move |reader_opt| {
+/// Load record positions from a reader, attaching the file to checksum mismatches and
+/// skipped records if given, and adding skipped records to the report.
+fn load_positions<R>(
+    reader: R,
+    config: RecordIndexerConfig,
+    path: Option<Arc<PathBuf>>,
+    report: CorruptReport,
+) -> impl Iterator<Item = Result<Position>>
+where
+    R: Read + Seek,
+{
+    let RecordIndexerConfig {
+        integrity,
+        limits,
+        op_timeout: _,
+        path_order: _,
+        skip_unsupported: _,
+        expect_kind: _,
+        skip_mismatched_kind: _,
+        skip_corrupt,
+        cancel,
+        compression: _,
+        trace: _,
+    } = config;
+    // the number of records, the end of the stream, if required, and the start of the
+    // next frame, once known
+    let mut index = 0;
+    let mut end: Option<u64> = None;
+    let mut next_frame: Option<u64> = None;
+
+    itertools::unfold(Some(reader), move |reader_opt| loop {
         let mut reader = reader_opt.as_mut()?;
-        let len = match crate::io::sync::try_read_len(&mut reader, check_integrity).transpose()? {
-            Ok(len) => len,
+        if let Err(err) = cancel::check_periodically(cancel.as_ref(), 0, index) {
+            *reader_opt = None;
+            return Some(Err(err));
+        }
+
+        let mut len = None;
+        let result = (|| {
+            let frame = match next_frame {
+                Some(frame) => frame,
+                None => reader.stream_position()?,
+            };
+            let read = (|| {
+                let Some(record_len) =
+                    crate::io::sync::try_read_len(&mut reader, integrity.checks_len())?
+                else {
+                    return Ok(None);
+                };
+                len = Some(record_len);
+                limits.check_record_len(record_len)?;
+                let offset = frame + crate::io::sync::HEADER_LEN;
+                let is_last = integrity.needs_last() && {
+                    let end = match end {
+                        Some(end) => end,
+                        None => {
+                            let end_pos = reader.seek(SeekFrom::End(0))?;
+                            reader.seek(SeekFrom::Start(offset))?;
+                            *end.insert(end_pos)
+                        }
+                    };
+                    offset + record_len as u64 + 4 >= end
+                };
+                let check_data = integrity.checks_data(index, is_last);
+                skip_or_check(&mut reader, record_len, check_data)?;
+                Ok(Some(Position {
+                    offset,
+                    len: record_len,
+                }))
+            })();
+            Ok::<_, Error>((frame, read))
+        })();
+        let (frame, read) = match result {
+            Ok(result) => result,
             Err(err) => {
                 *reader_opt = None;
                 return Some(Err(err));
             }
         };
 
-        let offset = (move || {
-            let offset = reader.seek(SeekFrom::Current(0))?;
-            skip_or_check(&mut reader, len, check_integrity)?;
-            Ok(offset)
-        })();
-        let offset = match offset {
-            Ok(offset) => offset,
+        match read {
+            Ok(Some(pos)) => {
+                next_frame = Some(pos.offset + pos.len as u64 + 4);
+                index += 1;
+                return Some(Ok(pos));
+            }
+            Ok(None) => {
+                *reader_opt = None;
+                return None;
+            }
+            Err(Error::ChecksumMismatch { expect, found, .. }) if skip_corrupt => {
+                // the payload is skipped by the length, unless the length is corrupted
+                let skipped = match len {
+                    Some(len) => {
+                        index += 1;
+                        Ok(Some(frame + crate::io::sync::HEADER_LEN + len as u64 + 4))
+                    }
+                    None => {
+                        crate::io::sync::find_frame_start(&mut reader, frame + 1).and_then(|next| {
+                            if let Some(next) = next {
+                                reader.seek(SeekFrom::Start(next))?;
+                            }
+                            Ok(next)
+                        })
+                    }
+                };
+                let resumed = match skipped {
+                    Ok(resumed) => resumed,
+                    Err(err) => {
+                        *reader_opt = None;
+                        return Some(Err(err));
+                    }
+                };
+                let resumed_at = match resumed {
+                    Some(resumed) => resumed,
+                    None => match reader.seek(SeekFrom::End(0)) {
+                        Ok(end) => end,
+                        Err(err) => {
+                            *reader_opt = None;
+                            return Some(Err(err.into()));
+                        }
+                    },
+                };
+                report.push(SkippedRecord {
+                    path: path.as_deref().cloned(),
+                    offset: frame,
+                    num_bytes: resumed_at - frame,
+                    expect,
+                    found,
+                });
+                if resumed.is_none() {
+                    *reader_opt = None;
+                    return None;
+                }
+                next_frame = resumed;
+            }
             Err(err) => {
                 *reader_opt = None;
+                let err = match &path {
+                    Some(path) => err.with_checksum_context(path, frame),
+                    None => err,
+                };
                 return Some(Err(err));
             }
-        };
-
-        let pos = Position { offset, len };
-        Some(Ok(pos))
+        }
     })
 }
 
+/// Read the record at the offset of a file, attaching the path and the offset to I/O
+/// errors.
+fn read_record_from(path: &Path, offset: u64, len: usize) -> Result<Vec<u8>> {
+    read_record_at(&mut open_decoded(path)?, offset, len)
+        .map_err(|error| error.with_io_context(path, Some(offset)))
+}
+
+/// Open a file, decompressing it if it is compressed, so that offsets of indexes apply.
+///
+/// Indexes of compressed files are only loaded with
+/// [compression](RecordIndexerConfig::compression) set, so detecting the format is
+/// enough.
+pub(super) fn open_decoded(path: &Path) -> Result<Decoded<BufReader<File>>> {
+    let file = File::open(path).map_err(|error| Error::from_io_with_context(error, path, None))?;
+    let mut reader = BufReader::new(file);
+    let compression = match crate::format::detect_format(&mut reader)? {
+        FileFormat::Gzip if cfg!(feature = "gzip") => Compression::Gzip,
+        _ => Compression::None,
+    };
+    Ok(Decoded::new(reader, compression))
+}
+
 fn read_record_at<R>(reader: &mut R, offset: u64, len: usize) -> Result<Vec<u8>>
 where
     R: Read + Seek,
@@ -166,6 +823,15 @@ where
     Ok(bytes)
 }
 
+fn read_record_into<R>(reader: &mut R, offset: u64, len: usize, buf: &mut Vec<u8>) -> Result<()>
+where
+    R: Read + Seek,
+{
+    reader.seek(SeekFrom::Start(offset))?;
+    crate::io::sync::try_read_record_data_into(reader, len, buf)?;
+    Ok(())
+}
+
 fn skip_or_check<R>(reader: &mut R, len: usize, check_integrity: bool) -> Result<()>
 where
     R: Read + Seek,
@@ -173,7 +839,8 @@ where
     if check_integrity {
         crate::io::sync::try_read_record_data(reader, len, check_integrity)?;
     } else {
-        reader.seek(SeekFrom::Current(len as i64))?;
+        // skip the payload and its checksum
+        reader.seek(SeekFrom::Current(len as i64 + 4))?;
     }
     Ok(())
 }
@@ -0,0 +1,183 @@
+//! Inspect files written by this crate.
+//!
+//! Record files created by path are stamped with the [ProducerInfo] of the writer in
+//! their [shard metadata](crate::metadata) sidecar, unless
+//! [stamp_producer](crate::RecordWriterConfig::stamp_producer) is disabled. Plain
+//! TFRecord files have no place for metadata that TensorFlow readers skip, so writers
+//! built from other writers do not stamp them.
+//!
+//! Event files written by [EventWriter](crate::EventWriter) are stamped with the
+//! [ProducerInfo] of the writer, unless
+//! [stamp_producer](crate::EventWriterConfig::stamp_producer) is disabled. The stamp
+//! follows the [file version](crate::EventWriterConfig::file_version) event, carrying a
+//! [LogMessage] which TensorFlow and TensorBoard readers ignore. Its wall time is read from the clock of the writer, so the stamp is
+//! deterministic with the fixed clocks in [testing](crate::testing). Reading event files
+//! requires the `proto-summary` cargo feature.
+
+use crate::{compression::Compression, error::Result, metadata::ShardMetadata, RecordWriterConfig};
+#[cfg(feature = "proto-summary")]
+use crate::{
+    protobuf::{event::What, log_message::Level, Event, LogMessage},
+    record_reader::{EventIter, RecordReaderConfig},
+};
+use std::{collections::BTreeMap, path::Path};
+
+/// The producer name of this crate.
+const PRODUCER_NAME: &str = "rust-tfrecord";
+
+/// The number of leading events searched for the stamp.
+#[cfg(feature = "proto-summary")]
+const MAX_STAMP_POSITION: usize = 2;
+
+/// The writer that produced a file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProducerInfo {
+    /// The producer name, which is `rust-tfrecord` for this crate.
+    pub name: String,
+    /// The version of the producer.
+    pub version: String,
+    /// The writer options affecting the file contents, such as `canonical_encoding`.
+    pub options: BTreeMap<String, String>,
+}
+
+impl ProducerInfo {
+    /// The producer info of this crate with the writer options.
+    pub(crate) fn current<I, K, V>(options: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: ToString,
+        V: ToString,
+    {
+        Self {
+            name: PRODUCER_NAME.into(),
+            version: env!("CARGO_PKG_VERSION").into(),
+            options: options
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    /// The producer info of this crate with the options of the writer configuration
+    /// which affect the file contents.
+    pub(crate) fn for_writer(config: &RecordWriterConfig, compression: Compression) -> Self {
+        let RecordWriterConfig {
+            canonical_encoding,
+            sorted_features,
+            on_unflushed_drop: _,
+            panic_on_unflushed_drop: _,
+            encode_sorted_indices,
+            stamp_producer: _,
+        } = config;
+        let compression = match compression {
+            Compression::None => "none",
+            Compression::Gzip => "gzip",
+        };
+        let options = [
+            ("canonical_encoding", canonical_encoding.to_string()),
+            ("compression", compression.to_string()),
+            (
+                "encode_sorted_indices",
+                encode_sorted_indices.is_some().to_string(),
+            ),
+            ("sorted_features", sorted_features.to_string()),
+        ];
+        Self::current(options)
+    }
+
+    /// Build the stamp event.
+    #[cfg(feature = "proto-summary")]
+    pub(crate) fn to_event(&self, wall_time: f64) -> Event {
+        let mut message = format!("producer={} version={}", self.name, self.version);
+        for (key, value) in &self.options {
+            message.push_str(&format!(" {}={}", key, value));
+        }
+
+        Event {
+            wall_time,
+            step: 0,
+            what: Some(What::LogMessage(LogMessage {
+                level: Level::Info as i32,
+                message,
+            })),
+        }
+    }
+
+    /// Parse the stamp event.
+    #[cfg(feature = "proto-summary")]
+    pub(crate) fn from_event(event: &Event) -> Option<Self> {
+        let message = match &event.what {
+            Some(What::LogMessage(LogMessage { message, .. })) => message,
+            _ => return None,
+        };
+
+        let mut pairs = message.split(' ').map(|pair| pair.split_once('='));
+        let name = match pairs.next()?? {
+            ("producer", name) => name.to_string(),
+            _ => return None,
+        };
+        let version = match pairs.next()?? {
+            ("version", version) => version.to_string(),
+            _ => return None,
+        };
+        let options = pairs
+            .map(|pair| {
+                let (key, value) = pair?;
+                Some((key.to_string(), value.to_string()))
+            })
+            .collect::<Option<_>>()?;
+
+        Some(Self {
+            name,
+            version,
+            options,
+        })
+    }
+}
+
+/// Read the producer stamp of a record file or an event file.
+///
+/// The stamp in the shard metadata sidecar of the file is read first. Otherwise, the
+/// leading events of the file are searched with the `proto-summary` cargo feature.
+///
+/// It returns `None` if the file is not stamped, including record files written without
+/// a sidecar and event files written by other producers. Empty event files and event
+/// files truncated before the stamp, such as files being written, are not stamped
+/// either. Sidecars ignored by [ShardMetadata::load_for] stamp nothing.
+pub fn producer_info<P>(path: P) -> Result<Option<ProducerInfo>>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    if let Some(Ok(ShardMetadata {
+        producer: Some(info),
+        ..
+    })) = ShardMetadata::load_for(path)
+    {
+        return Ok(Some(info));
+    }
+    stamp_event(path)
+}
+
+#[cfg(not(feature = "proto-summary"))]
+fn stamp_event(_path: &Path) -> Result<Option<ProducerInfo>> {
+    Ok(None)
+}
+
+#[cfg(feature = "proto-summary")]
+fn stamp_event(path: &Path) -> Result<Option<ProducerInfo>> {
+    let events = EventIter::open(path, RecordReaderConfig::default())?;
+    for event in events.take(MAX_STAMP_POSITION) {
+        let event = match event {
+            Ok(event) => event,
+            // not an event file
+            Err(crate::Error::ExampleDecodeError(_)) => return Ok(None),
+            Err(crate::Error::UnexpectedEof) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        if let Some(info) = ProducerInfo::from_event(&event) {
+            return Ok(Some(info));
+        }
+    }
+    Ok(None)
+}
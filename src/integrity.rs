@@ -0,0 +1,163 @@
+//! Checksum verification policies.
+//!
+//! An [IntegrityMode] is accepted by [RecordReaderConfig](crate::RecordReaderConfig)
+//! and [RecordIndexerConfig](crate::indexer::RecordIndexerConfig). It decides which
+//! record checksums are verified, trading corruption detection for throughput.
+//!
+//! Every record is framed by a checksummed length header and a checksummed payload.
+//! All modes except [Off](IntegrityMode::Off) verify the length checksums, so
+//! a corrupted header never leads to a misframed read. The modes differ in the
+//! payload checksums they verify.
+//!
+//! - [Full](IntegrityMode::Full) verifies every payload.
+//! - [Sample](IntegrityMode::Sample) verifies a pseudo-random subset of payloads.
+//!   The subset is determined by the seed and the record positions in each file, so
+//!   repeated runs verify the same records.
+//! - [FirstAndLastPerFile](IntegrityMode::FirstAndLastPerFile) verifies the first and
+//!   the last payloads in each file, which catches most truncated or partially
+//!   overwritten files.
+//! - [OnDecodeError](IntegrityMode::OnDecodeError) verifies a payload only if it fails
+//!   to decode. A corrupted payload results in
+//!   [Error::ChecksumMismatch](crate::Error::ChecksumMismatch), while intact bytes of
+//!   a wrong record type keep the decoding error. The indexer decodes no records, so
+//!   the mode verifies no payloads there.
+
+use crate::{
+    error::{ensure_argument, Result},
+    limits::Limits,
+    record::Record,
+};
+use std::hash::{Hash, Hasher};
+
+/// The policy of verifying record checksums.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum IntegrityMode {
+    /// Verify all checksums.
+    #[default]
+    Full,
+    /// Verify no checksums.
+    Off,
+    /// Verify the payloads of randomly sampled records.
+    Sample {
+        /// The probability in `[0, 1]` to verify a record.
+        probability: f64,
+        /// The seed determining the sampled records.
+        seed: u64,
+    },
+    /// Verify the payloads of the first and the last records in each file.
+    FirstAndLastPerFile,
+    /// Verify the payload of a record only if it fails to decode.
+    OnDecodeError,
+}
+
+impl IntegrityMode {
+    /// Create a [Sample](IntegrityMode::Sample) mode.
+    ///
+    /// The probability must be within `[0, 1]`.
+    pub fn sample(probability: f64, seed: u64) -> Result<Self> {
+        ensure_argument!(
+            (0.0..=1.0).contains(&probability),
+            "the sampling probability must be within [0, 1], but found {}",
+            probability
+        );
+        Ok(Self::Sample { probability, seed })
+    }
+
+    /// Returns true if the checksums of record lengths are verified.
+    pub fn checks_len(&self) -> bool {
+        !matches!(self, Self::Off)
+    }
+
+    /// Returns true if the payload checksum of the `index`-th record in a file is verified
+    /// before decoding.
+    pub fn checks_data(&self, index: u64, is_last: bool) -> bool {
+        match *self {
+            Self::Full => true,
+            Self::Off | Self::OnDecodeError => false,
+            Self::Sample { probability, seed } => {
+                let bits = splitmix64(seed ^ splitmix64(index)) >> 11;
+                (bits as f64 / (1u64 << 53) as f64) < probability
+            }
+            Self::FirstAndLastPerFile => index == 0 || is_last,
+        }
+    }
+
+    /// Returns true if [checks_data](IntegrityMode::checks_data) depends on whether the
+    /// record is the last one in the file.
+    pub(crate) fn needs_last(&self) -> bool {
+        matches!(self, Self::FirstAndLastPerFile)
+    }
+
+    /// Verify the payload if requested, and decode the record.
+    pub(crate) fn verify_and_decode<T>(
+        &self,
+        bytes: &[u8],
+        expect_cksum: u32,
+        check_data: bool,
+        limits: &Limits,
+    ) -> Result<T>
+    where
+        T: Record,
+    {
+        if check_data {
+            crate::utils::verify_checksum(bytes, expect_cksum)?;
+        }
+
+        T::from_slice_with_limits(bytes, limits).map_err(|error| match self {
+            // tell corrupted bytes from bytes of a wrong type
+            Self::OnDecodeError => match crate::utils::verify_checksum(bytes, expect_cksum) {
+                Ok(()) => error,
+                Err(mismatch) => mismatch,
+            },
+            _ => error,
+        })
+    }
+}
+
+impl From<bool> for IntegrityMode {
+    /// Convert from the on/off switch.
+    fn from(check_integrity: bool) -> Self {
+        if check_integrity {
+            Self::Full
+        } else {
+            Self::Off
+        }
+    }
+}
+
+impl PartialEq for IntegrityMode {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Self::Sample {
+                    probability: lp,
+                    seed: ls,
+                },
+                Self::Sample {
+                    probability: rp,
+                    seed: rs,
+                },
+            ) => lp.to_bits() == rp.to_bits() && ls == rs,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+impl Eq for IntegrityMode {}
+
+impl Hash for IntegrityMode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        if let Self::Sample { probability, seed } = self {
+            probability.to_bits().hash(state);
+            seed.hash(state);
+        }
+    }
+}
+
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
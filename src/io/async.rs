@@ -1,8 +1,31 @@
-use std::mem;
+use std::{future::Future, mem};
 
+use super::OpTimeout;
 use crate::error::{Error, Result};
 use futures::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
+/// Run a future with an optional deadline.
+///
+/// It returns [Error::Timeout] labeled by `operation` if the deadline is exceeded.
+pub async fn with_timeout<F, T>(
+    operation: &'static str,
+    timeout: Option<OpTimeout>,
+    future: F,
+) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    match timeout {
+        Some(timeout) => async_std::future::timeout(timeout.duration(), future)
+            .await
+            .map_err(|_| Error::Timeout {
+                operation,
+                elapsed: timeout.duration(),
+            })?,
+        None => future.await,
+    }
+}
+
 /// Try to extract raw bytes of a record from a generic reader.
 ///
 /// It reads the record length and data from a generic reader,
@@ -61,25 +84,48 @@ pub async fn try_read_record_data<R>(
 where
     R: AsyncRead + Unpin,
 {
-    let buf = {
-        let mut buf = vec![0u8; len];
-        reader.read_exact(&mut buf).await?;
-        buf
-    };
+    let mut buf = vec![];
+    let expect_cksum = try_read_record_data_into(reader, len, &mut buf).await?;
+    if check_integrity {
+        crate::utils::verify_checksum(&buf, expect_cksum)?;
+    }
+    Ok(buf)
+}
+
+/// Read the record raw bytes with given length into a buffer, and return the expected
+/// checksum without verification.
+///
+/// The buffer is resized to the record length, so that it can be reused across records.
+pub async fn try_read_record_data_into<R>(
+    reader: &mut R,
+    len: usize,
+    buf: &mut Vec<u8>,
+) -> Result<u32>
+where
+    R: AsyncRead + Unpin,
+{
+    buf.clear();
+    buf.resize(len, 0);
+    reader.read_exact(buf).await?;
     let expect_cksum = {
-        let mut buf = [0u8; mem::size_of::<u32>()];
+        let mut buf = [0u8; std::mem::size_of::<u32>()];
         reader.read_exact(&mut buf).await?;
         u32::from_le_bytes(buf)
     };
 
-    if check_integrity {
-        crate::utils::verify_checksum(&buf, expect_cksum)?;
-    }
-    Ok(buf)
+    Ok(expect_cksum)
 }
 
 /// Write the raw record bytes to a generic writer.
 pub async fn try_write_record<W>(writer: &mut W, bytes: Vec<u8>) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    try_write_record_slice(writer, &bytes).await
+}
+
+/// Write the raw record bytes in a slice to a generic writer.
+pub async fn try_write_record_slice<W>(writer: &mut W, bytes: &[u8]) -> Result<()>
 where
     W: AsyncWrite + Unpin,
 {
@@ -96,10 +142,10 @@ where
 
     // write data
     {
-        let cksum = crate::utils::checksum(&bytes);
+        let cksum = crate::utils::checksum(bytes);
         let cksum_buf = cksum.to_le_bytes();
 
-        writer.write_all(bytes.as_slice()).await?;
+        writer.write_all(bytes).await?;
         writer.write_all(&cksum_buf).await?;
     }
     Ok(())
@@ -0,0 +1,370 @@
+//! The byte layout of a record frame.
+//!
+//! A frame consists of four regions, each number in little-endian.
+//!
+//! | Offset      | Size | Region                                   |
+//! |-------------|------|------------------------------------------|
+//! | 0           | 8    | the payload length                       |
+//! | 8           | 4    | the masked CRC-32C of the length bytes   |
+//! | 12          | len  | the payload                              |
+//! | 12 + len    | 4    | the masked CRC-32C of the payload        |
+//!
+//! The [layout] function describes the frame a writer produces for a payload, and
+//! [annotate] describes the frame in raw bytes. The [Display](std::fmt::Display) of a
+//! [FrameLayout] prints the regions in aligned columns.
+//!
+//! ```rust
+//! # fn main() -> Result<(), tfrecord::io::frame::FrameError> {
+//! use tfrecord::io::frame::{self, Region};
+//!
+//! let layout = frame::layout(b"hello");
+//! assert_eq!(layout.frame_len(), 21);
+//! assert_eq!(layout.payload, 12..17);
+//! assert_eq!(
+//!     layout.to_string(),
+//!     "\
+//! offset  size  region       bytes                    value
+//!      0     8  length       05 00 00 00 00 00 00 00  5
+//!      8     4  length crc   ea b2 04 3e              0x3e04b2ea ok
+//!     12     5  payload      68 65 6c 6c 6f
+//!     17     4  payload crc  bb 1f 1c 19              0x191c1fbb ok
+//! "
+//! );
+//!
+//! // a corrupted checksum is marked invalid
+//! let mut bytes = vec![];
+//! tfrecord::io::sync::try_write_record_slice(&mut bytes, b"hello").unwrap();
+//! bytes[20] ^= 0xff;
+//! let layout = frame::annotate(&bytes)?;
+//! assert_eq!(layout.invalid_regions(), [Region::PayloadCrc]);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{fmt, ops::Range};
+
+/// The size of the length field.
+const LENGTH_LEN: u64 = 8;
+/// The size of a checksum field.
+const CRC_LEN: u64 = 4;
+/// The size of the frame header, the length and its checksum.
+const HEADER_LEN: u64 = LENGTH_LEN + CRC_LEN;
+
+/// The number of payload bytes shown by the display of a layout.
+const DISPLAYED_PAYLOAD_LEN: usize = 8;
+
+/// A region of a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Region {
+    /// The payload length.
+    Length,
+    /// The masked checksum of the length bytes.
+    LengthCrc,
+    /// The payload.
+    Payload,
+    /// The masked checksum of the payload.
+    PayloadCrc,
+}
+
+impl Region {
+    /// The name of the region, such as `length crc`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Length => "length",
+            Self::LengthCrc => "length crc",
+            Self::Payload => "payload",
+            Self::PayloadCrc => "payload crc",
+        }
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The length field of a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LengthField {
+    /// The offset from the frame start.
+    pub offset: u64,
+    /// The raw bytes.
+    pub bytes: [u8; 8],
+    /// The payload length.
+    pub value: u64,
+}
+
+/// A checksum field of a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CrcField {
+    /// The offset from the frame start.
+    pub offset: u64,
+    /// The raw bytes.
+    pub bytes: [u8; 4],
+    /// The stored masked checksum.
+    pub value: u32,
+    /// The masked checksum computed from the covered bytes.
+    pub computed: u32,
+}
+
+impl CrcField {
+    fn new(offset: u64, bytes: [u8; 4], covered: &[u8]) -> Self {
+        Self {
+            offset,
+            bytes,
+            value: u32::from_le_bytes(bytes),
+            computed: crate::utils::checksum(covered),
+        }
+    }
+
+    /// Returns true if the stored checksum matches the covered bytes.
+    pub fn is_valid(&self) -> bool {
+        self.value == self.computed
+    }
+}
+
+/// The regions of a frame at offsets from the frame start.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FrameLayout {
+    /// The payload length.
+    pub length: LengthField,
+    /// The checksum of the length bytes.
+    pub length_crc: CrcField,
+    /// The range of the payload.
+    pub payload: Range<u64>,
+    /// The checksum of the payload.
+    pub payload_crc: CrcField,
+    /// The leading bytes of the payload shown by the display.
+    payload_head: Vec<u8>,
+}
+
+impl FrameLayout {
+    fn new(
+        length_bytes: [u8; 8],
+        length_crc: [u8; 4],
+        payload: &[u8],
+        payload_crc: [u8; 4],
+    ) -> Self {
+        let payload_end = HEADER_LEN + payload.len() as u64;
+        Self {
+            length: LengthField {
+                offset: 0,
+                bytes: length_bytes,
+                value: u64::from_le_bytes(length_bytes),
+            },
+            length_crc: CrcField::new(LENGTH_LEN, length_crc, &length_bytes),
+            payload: HEADER_LEN..payload_end,
+            payload_crc: CrcField::new(payload_end, payload_crc, payload),
+            payload_head: payload[..payload.len().min(DISPLAYED_PAYLOAD_LEN)].to_vec(),
+        }
+    }
+
+    /// The size of the whole frame.
+    pub fn frame_len(&self) -> u64 {
+        self.payload.end + CRC_LEN
+    }
+
+    /// The range of a region.
+    pub fn range(&self, region: Region) -> Range<u64> {
+        match region {
+            Region::Length => 0..LENGTH_LEN,
+            Region::LengthCrc => LENGTH_LEN..HEADER_LEN,
+            Region::Payload => self.payload.clone(),
+            Region::PayloadCrc => self.payload.end..self.frame_len(),
+        }
+    }
+
+    /// The regions whose checksums do not match, in the order of offsets.
+    pub fn invalid_regions(&self) -> Vec<Region> {
+        [
+            (Region::LengthCrc, &self.length_crc),
+            (Region::PayloadCrc, &self.payload_crc),
+        ]
+        .into_iter()
+        .filter(|(_, field)| !field.is_valid())
+        .map(|(region, _)| region)
+        .collect()
+    }
+
+    /// Returns true if both checksums match.
+    pub fn is_valid(&self) -> bool {
+        self.length_crc.is_valid() && self.payload_crc.is_valid()
+    }
+}
+
+impl fmt::Display for FrameLayout {
+    /// Print a line per region with the offset, the size, the bytes and the value.
+    ///
+    /// Payloads longer than 8 bytes are shown by their leading bytes.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn hex(bytes: &[u8]) -> String {
+            bytes
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+        fn crc(field: &CrcField) -> String {
+            if field.is_valid() {
+                format!("{:#010x} ok", field.value)
+            } else {
+                format!(
+                    "{:#010x} invalid, computed {:#010x}",
+                    field.value, field.computed
+                )
+            }
+        }
+
+        let mut payload = hex(&self.payload_head);
+        if self.payload.end - self.payload.start > self.payload_head.len() as u64 {
+            payload.push_str(" ..");
+        }
+        let rows = [
+            (
+                Region::Length,
+                hex(&self.length.bytes),
+                self.length.value.to_string(),
+            ),
+            (
+                Region::LengthCrc,
+                hex(&self.length_crc.bytes),
+                crc(&self.length_crc),
+            ),
+            (Region::Payload, payload, String::new()),
+            (
+                Region::PayloadCrc,
+                hex(&self.payload_crc.bytes),
+                crc(&self.payload_crc),
+            ),
+        ];
+
+        writeln!(
+            f,
+            "{:>6}  {:>4}  {:<11}  {:<23}  value",
+            "offset", "size", "region", "bytes"
+        )?;
+        for (region, bytes, value) in rows {
+            let range = self.range(region);
+            let line = format!(
+                "{:>6}  {:>4}  {:<11}  {:<23}  {}",
+                range.start,
+                range.end - range.start,
+                region.name(),
+                bytes,
+                value
+            );
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// The failure to annotate bytes not forming a single frame.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FrameError {
+    /// The bytes end within the region.
+    Truncated {
+        region: Region,
+        /// The bytes needed up to the end of the region.
+        needed: u64,
+        available: u64,
+    },
+    /// The bytes continue after the frame.
+    TrailingBytes { frame_len: u64, available: u64 },
+}
+
+impl FrameError {
+    /// The region that failed, or `None` for trailing bytes.
+    pub fn region(&self) -> Option<Region> {
+        match self {
+            Self::Truncated { region, .. } => Some(*region),
+            Self::TrailingBytes { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated {
+                region,
+                needed,
+                available,
+            } => write!(
+                f,
+                "the frame is truncated in the {} region: {} bytes needed, but {} available",
+                region, needed, available
+            ),
+            Self::TrailingBytes {
+                frame_len,
+                available,
+            } => write!(
+                f,
+                "{} bytes follow the frame of {} bytes",
+                available - frame_len,
+                frame_len
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+/// The layout of the frame a writer produces for the payload.
+pub fn layout(payload: &[u8]) -> FrameLayout {
+    let length_bytes = (payload.len() as u64).to_le_bytes();
+    FrameLayout::new(
+        length_bytes,
+        crate::utils::checksum(&length_bytes).to_le_bytes(),
+        payload,
+        crate::utils::checksum(payload).to_le_bytes(),
+    )
+}
+
+/// The layout of a frame in raw bytes.
+///
+/// Checksum mismatches do not fail, but are reported by the
+/// [invalid regions](FrameLayout::invalid_regions) of the layout. It fails if the
+/// bytes end before the frame does, as told by the length field, or continue after it.
+pub fn annotate(frame_bytes: &[u8]) -> Result<FrameLayout, FrameError> {
+    let available = frame_bytes.len() as u64;
+    let truncated = |region, needed| FrameError::Truncated {
+        region,
+        needed,
+        available,
+    };
+
+    if available < LENGTH_LEN {
+        return Err(truncated(Region::Length, LENGTH_LEN));
+    }
+    if available < HEADER_LEN {
+        return Err(truncated(Region::LengthCrc, HEADER_LEN));
+    }
+    let (length_bytes, rest) = frame_bytes.split_at(LENGTH_LEN as usize);
+    let (length_crc, rest) = rest.split_at(CRC_LEN as usize);
+    let len = u64::from_le_bytes(length_bytes.try_into().unwrap());
+
+    let payload_end = match HEADER_LEN.checked_add(len) {
+        Some(end) if end <= available => end,
+        _ => return Err(truncated(Region::Payload, HEADER_LEN.saturating_add(len))),
+    };
+    let frame_len = payload_end + CRC_LEN;
+    if available < frame_len {
+        return Err(truncated(Region::PayloadCrc, frame_len));
+    }
+    if available > frame_len {
+        return Err(FrameError::TrailingBytes {
+            frame_len,
+            available,
+        });
+    }
+    let (payload, payload_crc) = rest.split_at(len as usize);
+
+    Ok(FrameLayout::new(
+        length_bytes.try_into().unwrap(),
+        length_crc.try_into().unwrap(),
+        payload,
+        payload_crc.try_into().unwrap(),
+    ))
+}
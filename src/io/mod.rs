@@ -5,4 +5,31 @@
 
 #[cfg(feature = "async")]
 pub mod r#async;
+pub mod frame;
 pub mod sync;
+
+use crate::error::{ensure_argument, Result};
+use std::time::Duration;
+
+/// The deadline of a single I/O operation in async readers.
+///
+/// An operation, such as opening a file, seeking or reading one record, exceeding
+/// the deadline fails with [Error::Timeout](crate::Error::Timeout) instead of hanging.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OpTimeout(Duration);
+
+impl OpTimeout {
+    /// Create a deadline. Zero duration is rejected.
+    pub fn new(duration: Duration) -> Result<Self> {
+        ensure_argument!(
+            !duration.is_zero(),
+            "the operation timeout must be positive"
+        );
+        Ok(Self(duration))
+    }
+
+    /// Get the duration.
+    pub fn duration(&self) -> Duration {
+        self.0
+    }
+}
@@ -1,5 +1,5 @@
 use crate::error::{Error, Result};
-use std::io::prelude::*;
+use std::io::{prelude::*, SeekFrom};
 
 /// Try to extract raw bytes of a record from a generic reader.
 ///
@@ -54,25 +54,44 @@ pub fn try_read_record_data<R>(reader: &mut R, len: usize, check_integrity: bool
 where
     R: Read,
 {
-    let buf = {
-        let mut buf = vec![0u8; len];
-        reader.read_exact(&mut buf)?;
-        buf
-    };
+    let mut buf = vec![];
+    let expect_cksum = try_read_record_data_into(reader, len, &mut buf)?;
+    if check_integrity {
+        crate::utils::verify_checksum(&buf, expect_cksum)?;
+    }
+    Ok(buf)
+}
+
+/// Read the record raw bytes with given length into a buffer, and return the expected
+/// checksum without verification.
+///
+/// The buffer is resized to the record length, so that it can be reused across records.
+pub fn try_read_record_data_into<R>(reader: &mut R, len: usize, buf: &mut Vec<u8>) -> Result<u32>
+where
+    R: Read,
+{
+    buf.clear();
+    buf.resize(len, 0);
+    reader.read_exact(buf)?;
     let expect_cksum = {
-        let mut buf = [0; std::mem::size_of::<u32>()];
+        let mut buf = [0u8; std::mem::size_of::<u32>()];
         reader.read_exact(&mut buf)?;
         u32::from_le_bytes(buf)
     };
 
-    if check_integrity {
-        crate::utils::verify_checksum(&buf, expect_cksum)?;
-    }
-    Ok(buf)
+    Ok(expect_cksum)
 }
 
 /// Write the raw record bytes to a generic writer.
 pub fn try_write_record<W>(writer: &mut W, bytes: Vec<u8>) -> Result<()>
+where
+    W: Write,
+{
+    try_write_record_slice(writer, &bytes)
+}
+
+/// Write the raw record bytes in a slice to a generic writer.
+pub fn try_write_record_slice<W>(writer: &mut W, bytes: &[u8]) -> Result<()>
 where
     W: Write,
 {
@@ -89,15 +108,96 @@ where
 
     // write data
     {
-        let cksum = crate::utils::checksum(&bytes);
+        let cksum = crate::utils::checksum(bytes);
         let cksum_buf = cksum.to_le_bytes();
 
-        writer.write_all(bytes.as_slice())?;
+        writer.write_all(bytes)?;
         writer.write_all(&cksum_buf)?;
     }
     Ok(())
 }
 
+/// The size of the frame header, the length and its checksum.
+pub(crate) const HEADER_LEN: u64 = 12;
+
+/// The size of the window read at a time when scanning for frames.
+const SCAN_WINDOW: usize = 64 * 1024;
+
+/// Find the first frame start at or after the offset.
+///
+/// An offset is accepted as a frame start if the length checksum and the payload
+/// checksum of the frame are valid, and the frame is followed either by the end of the
+/// reader or by a header with a valid length checksum. Payloads embedding a complete
+/// valid frame followed by another valid header can still be mistaken for frames.
+///
+/// Returns `Ok(None)` if no frame starts after the offset. The position of the reader
+/// is unspecified afterwards.
+pub fn find_frame_start<R>(reader: &mut R, from: u64) -> Result<Option<u64>>
+where
+    R: Read + Seek,
+{
+    let reader_len = reader.seek(SeekFrom::End(0))?;
+    let mut window = vec![];
+    let mut window_start = from;
+
+    while window_start + HEADER_LEN <= reader_len {
+        let size = SCAN_WINDOW.min((reader_len - window_start) as usize);
+        window.resize(size, 0);
+        reader.seek(SeekFrom::Start(window_start))?;
+        reader.read_exact(&mut window)?;
+
+        for (index, header) in window.windows(HEADER_LEN as usize).enumerate() {
+            let offset = window_start + index as u64;
+            if let Some(len) = parse_header(header) {
+                if is_frame_at(reader, offset, len, reader_len)? {
+                    return Ok(Some(offset));
+                }
+            }
+        }
+
+        // the next window overlaps with the headers not entirely in this window
+        window_start += (size as u64 + 1).saturating_sub(HEADER_LEN).max(1);
+    }
+    Ok(None)
+}
+
+/// Parse the length of a header if its checksum is valid.
+pub(crate) fn parse_header(header: &[u8]) -> Option<u64> {
+    let (len_buf, cksum_buf) = header.split_at(std::mem::size_of::<u64>());
+    let expect_cksum = u32::from_le_bytes(cksum_buf.try_into().unwrap());
+    (crate::utils::checksum(len_buf) == expect_cksum)
+        .then(|| u64::from_le_bytes(len_buf.try_into().unwrap()))
+}
+
+fn is_frame_at<R>(reader: &mut R, offset: u64, len: u64, reader_len: u64) -> Result<bool>
+where
+    R: Read + Seek,
+{
+    let next = match (offset + HEADER_LEN)
+        .checked_add(len)
+        .and_then(|end| end.checked_add(4))
+    {
+        Some(next) if next <= reader_len => next,
+        _ => return Ok(false),
+    };
+
+    reader.seek(SeekFrom::Start(offset + HEADER_LEN))?;
+    let mut buf = vec![];
+    let expect_cksum = try_read_record_data_into(reader, len as usize, &mut buf)?;
+    if crate::utils::checksum(&buf) != expect_cksum {
+        return Ok(false);
+    }
+    if next == reader_len {
+        return Ok(true);
+    }
+    if next + HEADER_LEN > reader_len {
+        return Ok(false);
+    }
+    let mut header = [0u8; HEADER_LEN as usize];
+    reader.read_exact(&mut header)?;
+    Ok(parse_header(&header).is_some())
+}
+
 fn try_read_exact<R, B>(reader: &mut R, mut buf: B) -> Result<Option<B>>
 where
     R: Read,
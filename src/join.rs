@@ -0,0 +1,331 @@
+//! Join two datasets of examples on a key.
+//!
+//! [hash_join] loads the examples of the build side into an in-memory table by key,
+//! then streams the probe side, merging each probe example with the build example of
+//! the same key by [Example::merge]. The build side should be the smaller dataset. Its
+//! size is capped by [max_build_bytes](JoinOptions::max_build_bytes), and exceeding the
+//! cap fails with [Error::JoinBuildTooLarge] before any example is yielded.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{
+//!     join::{self, JoinOptions},
+//!     samples, Example, ExampleWriter,
+//! };
+//!
+//! let facts = samples::tiny_dataset(2, 5)?;
+//! // attributes keyed by the same id
+//! let build_path = facts.dir().join("attributes.tfrecord");
+//! let mut writer = ExampleWriter::create(&build_path)?;
+//! for id in 0..10 {
+//!     let mut example = Example::empty();
+//!     example.push_i64s("id", &[id]);
+//!     example.push_f32s("weight", &[id as f32 * 0.1]);
+//!     writer.send(example)?;
+//! }
+//! drop(writer);
+//!
+//! let joined = join::hash_join(
+//!     facts.paths(),
+//!     [build_path.as_path()],
+//!     join::feature_key("id"),
+//!     join::feature_key("id"),
+//!     JoinOptions::default(),
+//! )?;
+//! for example in joined {
+//!     let example = example?;
+//!     assert_eq!(example.get_f32s("weight")?.len(), 1);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::{ensure_argument, Error, Result},
+    protobuf::Example,
+    protobuf_ext::MergePolicy,
+    record_reader::{ExampleIter, RecordReaderConfig},
+};
+use prost::Message as _;
+use std::{
+    borrow::Cow,
+    collections::{hash_map, HashMap},
+    fs::File,
+    hash::Hash,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+/// The handling of probe examples without a matching build example.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JoinType {
+    /// Skip the probe example.
+    Inner,
+    /// Handle the probe example by the policy.
+    Left(MissingMatch),
+}
+
+/// The handling of unmatched probe examples in a [Left](JoinType::Left) join.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MissingMatch {
+    /// Yield the probe example as is.
+    #[default]
+    KeepUnenriched,
+    /// Skip the probe example, the same as an [Inner](JoinType::Inner) join.
+    Skip,
+    /// Fail the join.
+    Error,
+}
+
+/// The handling of build examples with the same key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DuplicateKeys {
+    /// Keep the first example.
+    First,
+    /// Keep the last example.
+    Last,
+    /// Fail the join.
+    #[default]
+    Error,
+}
+
+/// Options for [hash_join].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct JoinOptions {
+    pub join_type: JoinType,
+    pub duplicates: DuplicateKeys,
+    /// The resolution of keys present in both merged examples with different features.
+    pub collision: MergePolicy,
+    /// The maximum total encoded size of the build examples kept in memory.
+    ///
+    /// The memory used by the table is larger by the overhead of decoded examples and
+    /// the keys.
+    pub max_build_bytes: u64,
+    /// The configuration of the readers of both sides.
+    pub reader: RecordReaderConfig,
+}
+
+impl Default for JoinOptions {
+    fn default() -> Self {
+        Self {
+            join_type: JoinType::Inner,
+            duplicates: DuplicateKeys::default(),
+            collision: MergePolicy::default(),
+            max_build_bytes: 1 << 30,
+            reader: RecordReaderConfig::default(),
+        }
+    }
+}
+
+/// The key of examples by the encoded feature of the key, which fails if the feature
+/// is missing.
+///
+/// Features of the same kind and values have the same key.
+pub fn feature_key(key: &str) -> impl Fn(&Example) -> Result<Vec<u8>> + Clone {
+    let key = key.to_string();
+    move |example| {
+        let feature = example
+            .features
+            .as_ref()
+            .and_then(|features| features.feature.get(&key))
+            .ok_or_else(|| Error::conversion(format!("the key feature '{}' is missing", key)))?;
+        Ok(feature.encode_to_vec())
+    }
+}
+
+/// Join the probe files with the build files on the keys given by the functions.
+///
+/// The build files are loaded before returning, and the probe files are read lazily
+/// in order. Failures at a record are reported as [Error::RecordFailed] with the file
+/// and the index of the record.
+pub fn hash_join<'a, 'b, K, PI, PP, BI, BP, FP, FB>(
+    probe: PI,
+    build: BI,
+    probe_key: FP,
+    build_key: FB,
+    options: JoinOptions,
+) -> Result<JoinIter<K, FP>>
+where
+    K: Eq + Hash,
+    PI: IntoIterator<Item = PP>,
+    PP: Into<Cow<'a, Path>>,
+    BI: IntoIterator<Item = BP>,
+    BP: Into<Cow<'b, Path>>,
+    FP: FnMut(&Example) -> Result<K>,
+    FB: FnMut(&Example) -> Result<K>,
+{
+    let JoinOptions {
+        join_type,
+        duplicates,
+        collision,
+        max_build_bytes,
+        reader,
+    } = options;
+    ensure_argument!(max_build_bytes > 0, "max_build_bytes must be positive");
+
+    let mut build_key = build_key;
+    let mut table: HashMap<K, Example> = HashMap::new();
+    let mut num_bytes = 0;
+    for path in build {
+        let path = path.into();
+        let file =
+            File::open(&path).map_err(|err| Error::from_io_with_context(err, &*path, None))?;
+        let examples = ExampleIter::from_reader(BufReader::new(file), reader.clone());
+        for (index, example) in examples.enumerate() {
+            let record_failed = |err| Error::RecordFailed {
+                path: path.to_path_buf(),
+                index: index as u64,
+                source: Box::new(err),
+            };
+            let example = example.map_err(record_failed)?;
+            let key = build_key(&example).map_err(record_failed)?;
+            let len = example.encoded_len() as u64;
+            match table.entry(key) {
+                hash_map::Entry::Vacant(entry) => {
+                    num_bytes += len;
+                    entry.insert(example);
+                }
+                hash_map::Entry::Occupied(mut entry) => match duplicates {
+                    DuplicateKeys::First => continue,
+                    DuplicateKeys::Last => {
+                        num_bytes = num_bytes - entry.get().encoded_len() as u64 + len;
+                        entry.insert(example);
+                    }
+                    DuplicateKeys::Error => {
+                        return Err(record_failed(Error::conversion(
+                            "the key appears again on the build side",
+                        )))
+                    }
+                },
+            }
+            if num_bytes > max_build_bytes {
+                return Err(Error::JoinBuildTooLarge {
+                    limit: max_build_bytes,
+                    num_keys: table.len() as u64,
+                });
+            }
+        }
+    }
+
+    let probe: Vec<PathBuf> = probe
+        .into_iter()
+        .map(|path| path.into().into_owned())
+        .collect();
+    Ok(JoinIter {
+        table,
+        probe_key,
+        join_type,
+        collision,
+        reader,
+        paths: probe.into_iter(),
+        file: None,
+        done: false,
+    })
+}
+
+/// The probe file being read by a [JoinIter].
+struct ProbeFile {
+    path: PathBuf,
+    examples: ExampleIter<BufReader<File>>,
+    /// The index of the next record in the file.
+    index: u64,
+}
+
+/// Iterator of joined examples, returned by [hash_join].
+pub struct JoinIter<K, F> {
+    table: HashMap<K, Example>,
+    probe_key: F,
+    join_type: JoinType,
+    collision: MergePolicy,
+    reader: RecordReaderConfig,
+    paths: std::vec::IntoIter<PathBuf>,
+    file: Option<ProbeFile>,
+    done: bool,
+}
+
+impl<K, F> JoinIter<K, F>
+where
+    K: Eq + Hash,
+    F: FnMut(&Example) -> Result<K>,
+{
+    /// The number of distinct keys of the build side.
+    pub fn num_build_keys(&self) -> usize {
+        self.table.len()
+    }
+
+    fn next_joined(&mut self) -> Result<Option<Example>> {
+        loop {
+            let file = match &mut self.file {
+                Some(file) => file,
+                None => match self.paths.next() {
+                    Some(path) => {
+                        let file = File::open(&path)
+                            .map_err(|err| Error::from_io_with_context(err, &path, None))?;
+                        let examples =
+                            ExampleIter::from_reader(BufReader::new(file), self.reader.clone());
+                        self.file.insert(ProbeFile {
+                            path,
+                            examples,
+                            index: 0,
+                        })
+                    }
+                    None => return Ok(None),
+                },
+            };
+            let example = match file.examples.next() {
+                Some(example) => example,
+                None => {
+                    self.file = None;
+                    continue;
+                }
+            };
+            let index = file.index;
+            file.index += 1;
+
+            let joined = (|| {
+                let mut example = example?;
+                let key = (self.probe_key)(&example)?;
+                match self.table.get(&key) {
+                    Some(build) => {
+                        example.merge(build.clone(), &self.collision)?;
+                        Ok(Some(example))
+                    }
+                    None => match self.join_type {
+                        JoinType::Inner | JoinType::Left(MissingMatch::Skip) => Ok(None),
+                        JoinType::Left(MissingMatch::KeepUnenriched) => Ok(Some(example)),
+                        JoinType::Left(MissingMatch::Error) => Err(Error::conversion(
+                            "no build example matches the key of the probe example",
+                        )),
+                    },
+                }
+            })()
+            .map_err(|err| Error::RecordFailed {
+                path: file.path.clone(),
+                index,
+                source: Box::new(err),
+            })?;
+            if let Some(joined) = joined {
+                return Ok(Some(joined));
+            }
+        }
+    }
+}
+
+impl<K, F> Iterator for JoinIter<K, F>
+where
+    K: Eq + Hash,
+    F: FnMut(&Example) -> Result<K>,
+{
+    type Item = Result<Example>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.next_joined().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.done = true;
+        }
+        result
+    }
+}
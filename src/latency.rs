@@ -0,0 +1,241 @@
+//! Latency histograms of record reads for diagnosing storage tail latency.
+//!
+//! A [LatencyRecorder] is attached to readers by
+//! [RecordReaderConfig::latency](crate::record_reader::RecordReaderConfig::latency). It
+//! times the [phases](Phase) of each read and counts the durations in fixed histograms,
+//! whose buckets double from 10µs up to about 10s. A [snapshot](LatencyRecorder::snapshot)
+//! reports the count, the percentiles and the maximum per phase.
+//!
+//! The recorder is instrumented into [RecordIter](crate::record_reader::RecordIter) and
+//! [iter_from](crate::indexer::iter_from), and the readers built on them. Without a
+//! recorder, readers do not read the clock at all.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{
+//!     indexer, latency::{LatencyRecorder, Phase}, record_reader::RecordReaderConfig, samples,
+//!     Example,
+//! };
+//!
+//! let dataset = samples::tiny_dataset(2, 5)?;
+//! let indexes: Vec<_> =
+//!     indexer::load_paths(dataset.paths(), Default::default()).collect::<Result<_, _>>()?;
+//!
+//! let recorder = LatencyRecorder::new();
+//! let config = RecordReaderConfig {
+//!     latency: Some(recorder.clone()),
+//!     ..Default::default()
+//! };
+//! for result in indexer::iter_from::<Example>(&indexes, 0, config) {
+//!     result?;
+//! }
+//! let snapshot = recorder.snapshot();
+//! assert_eq!(snapshot.get(Phase::Open).count, 2);
+//! assert_eq!(snapshot.get(Phase::Decode).count, 10);
+//! println!("p99 read latency: {:?}", snapshot.get(Phase::Read).p99);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    hash::{Hash, Hasher},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// The number of histogram bucket bounds.
+pub const NUM_LATENCY_BOUNDS: usize = 21;
+
+/// The inclusive upper bounds of histogram buckets, doubling from 10µs. Durations above
+/// the last bound fall into an overflow bucket.
+pub const LATENCY_BOUNDS: [Duration; NUM_LATENCY_BOUNDS] = {
+    let mut bounds = [Duration::ZERO; NUM_LATENCY_BOUNDS];
+    let mut index = 0;
+    while index < NUM_LATENCY_BOUNDS {
+        bounds[index] = Duration::from_nanos(10_000 << index);
+        index += 1;
+    }
+    bounds
+};
+
+/// A timed phase of reading a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Phase {
+    /// Opening a file.
+    Open,
+    /// Seeking to a record.
+    Seek,
+    /// Reading the frame of a record.
+    Read,
+    /// Verifying the checksum and decoding the payload.
+    Decode,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 4] = [Phase::Open, Phase::Seek, Phase::Read, Phase::Decode];
+
+    fn slot(self) -> usize {
+        self as usize
+    }
+}
+
+/// The latency statistics of a phase.
+///
+/// Percentiles are the upper bounds of the buckets they fall into, capped by the
+/// maximum. They are zero without samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct PhaseStats {
+    pub count: u64,
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+/// The latency statistics of all phases at a point of time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LatencySnapshot {
+    pub open: PhaseStats,
+    pub seek: PhaseStats,
+    pub read: PhaseStats,
+    pub decode: PhaseStats,
+}
+
+impl LatencySnapshot {
+    pub fn get(&self, phase: Phase) -> &PhaseStats {
+        match phase {
+            Phase::Open => &self.open,
+            Phase::Seek => &self.seek,
+            Phase::Read => &self.read,
+            Phase::Decode => &self.decode,
+        }
+    }
+}
+
+/// The recorder of read latencies.
+///
+/// Clones share the same histograms, so a recorder can be attached to several readers
+/// across threads. Recorders are compared by identity.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyRecorder(Arc<[Histogram; 4]>);
+
+impl LatencyRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a duration of the phase.
+    pub fn record(&self, phase: Phase, elapsed: Duration) {
+        self.0[phase.slot()].record(elapsed);
+    }
+
+    /// Get the statistics of all phases.
+    pub fn snapshot(&self) -> LatencySnapshot {
+        let [open, seek, read, decode] = Phase::ALL.map(|phase| self.0[phase.slot()].stats());
+        LatencySnapshot {
+            open,
+            seek,
+            read,
+            decode,
+        }
+    }
+
+    /// Clear the histograms of all phases.
+    pub fn reset(&self) {
+        self.0.iter().for_each(Histogram::reset);
+    }
+}
+
+impl PartialEq for LatencyRecorder {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for LatencyRecorder {}
+
+impl Hash for LatencyRecorder {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).hash(state);
+    }
+}
+
+#[derive(Debug, Default)]
+struct Histogram {
+    /// The counts of buckets, the last of which is the overflow bucket.
+    buckets: [AtomicU64; NUM_LATENCY_BOUNDS + 1],
+    max_nanos: AtomicU64,
+}
+
+impl Histogram {
+    fn record(&self, elapsed: Duration) {
+        let bucket = LATENCY_BOUNDS.partition_point(|&bound| bound < elapsed);
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        let nanos = elapsed.as_nanos().min(u64::MAX as u128) as u64;
+        self.max_nanos.fetch_max(nanos, Ordering::Relaxed);
+    }
+
+    fn stats(&self) -> PhaseStats {
+        let counts: Vec<u64> = self
+            .buckets
+            .iter()
+            .map(|count| count.load(Ordering::Relaxed))
+            .collect();
+        let count: u64 = counts.iter().sum();
+        let max = Duration::from_nanos(self.max_nanos.load(Ordering::Relaxed));
+
+        // the smallest bucket bound covering the rank of the quantile
+        let percentile = |quantile: f64| {
+            if count == 0 {
+                return Duration::ZERO;
+            }
+            let rank = ((quantile * count as f64).ceil() as u64).max(1);
+            let mut cumulative = 0;
+            let bucket = counts
+                .iter()
+                .position(|&bucket_count| {
+                    cumulative += bucket_count;
+                    cumulative >= rank
+                })
+                .unwrap();
+            LATENCY_BOUNDS
+                .get(bucket)
+                .map_or(max, |&bound| bound.min(max))
+        };
+
+        PhaseStats {
+            count,
+            p50: percentile(0.50),
+            p95: percentile(0.95),
+            p99: percentile(0.99),
+            max,
+        }
+    }
+
+    fn reset(&self) {
+        self.buckets
+            .iter()
+            .for_each(|count| count.store(0, Ordering::Relaxed));
+        self.max_nanos.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Run the function, timing it as the phase if a recorder is present.
+pub(crate) fn time<T>(
+    recorder: Option<&LatencyRecorder>,
+    phase: Phase,
+    f: impl FnOnce() -> T,
+) -> T {
+    match recorder {
+        Some(recorder) => {
+            let since = Instant::now();
+            let output = f();
+            recorder.record(phase, since.elapsed());
+            output
+        }
+        None => f(),
+    }
+}
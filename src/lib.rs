@@ -1,20 +1,68 @@
 //! The crate provides the functionality to serialize and deserialize TFRecord data format from TensorFlow.
 //!
+//! # Getting Started
+//!
+//! The [prelude] re-exports the common types and traits with a single import.
+//!
+//! ```rust
+//! use tfrecord::prelude::*;
+//! ```
+//!
 //! # Cargo Features
 //!
 //! Optional features:
 //! - `full`: Enable all features.
 //! - `async`: Enable async/await feature.
+//! - `encryption`: Enable the [encryption] module for whole-file encryption at rest.
+//! - `glob`: Enable loading record indexes from files matching a glob pattern.
+//! - `gzip`: Enable reading GZIP-compressed record files, see [compression].
+//! - `http`: Enable reading record files over HTTP(S) by range requests, see [source].
+//!   It implies `async`.
+//! - `incremental`: Enable the [incremental] module for accumulators updated as a
+//!   dataset grows, saved as JSON. It implies `with-serde`.
+//! - `mmap`: Enable the [mmap] module to write files through preallocated memory mappings.
+//! - `s3`: Enable reading record files from S3 objects, see [source]. It implies `http`.
+//! - `schema-file`: Enable loading [schema] specs from JSON and TOML files. It implies
+//!   `with-serde`.
+//! - `testing`: Enable the [testing] module for deterministic snapshot tests and the
+//!   [samples] module for sample data. It is always enabled for tests and doc tests.
+//!
+//! ProtocolBuffer groups, see [protobuf] for the types in each group:
+//! - `proto-example`: Examples, features and tensors. The group is always compiled, and
+//!   the feature names it in minimal builds.
+//! - `proto-summary` (default): Events and summaries, required by the event writer and
+//!   the `with-image` feature.
+//! - `proto-graph` (default): Graphs, functions, operations and variables.
+//! - `proto-runtime` (default): Devices, allocations, memory logs and step statistics.
 //!
 //! Third-party crate supports:
-//! - `with-serde`: Enable interoperability with [serde](https://crates.io/crates/serde) to serialize and deserialize example types.
+//! - `with-serde`: Enable interoperability with [serde](https://crates.io/crates/serde) to serialize and deserialize example types,
+//!   and the [serde_example] module to convert serde types to and from examples.
 //! - `with-tch`: Enable [tch](https://crates.io/crates/tch) types support.
 //! - `with-image`: Enable [image](https://crates.io/crates/image) types support.
 //! - `with-ndarray`: Enable [ndarray](https://crates.io/crates/ndarray) types support.
 //!
+//! ## Minimal Build
+//!
+//! The reader, the writer and the indexer of examples only need the `proto-example`
+//! group. Disabling the default features skips the other groups.
+//!
+//! ```toml
+//! tfrecord = { version = "0.14", default-features = false, features = ["proto-example"] }
+//! ```
+//!
+//! The table compares the builds of this crate alone, with its dependencies already
+//! built, measured on a Linux x86-64 machine with Rust 1.95. The numbers vary across
+//! machines, but the ratio is representative.
+//!
+//! | build | debug | release | rlib size (release) |
+//! |---|---:|---:|---:|
+//! | `default-features = false, features = ["proto-example"]` | 2.8 s | 6.8 s | 2.7 MB |
+//! | default features | 4.9 s | 11.6 s | 6.8 MB |
+//!
 //! # Manualy ProtocolBuffer Code Generation
 //!
-//! The crate compiles the pre-generated ProtocolBuffer code from TensorFlow. In the case of TensorFlow version update, you may generate the code manually. It accepts several ways to access the TensorFlow source code specified by `TFRECORD_BUILD_METHOD` environment variable. The generated code is placed under `prebuild_src` directory. See the examples below and change `X.Y.Z` to actual TensorFlow version.
+//! The crate compiles the pre-generated ProtocolBuffer code from TensorFlow, one file per group. In the case of TensorFlow version update, you may generate the code manually. It accepts several ways to access the TensorFlow source code specified by `TFRECORD_BUILD_METHOD` environment variable. The generated code is placed under `prebuild_src` directory. See the examples below and change `X.Y.Z` to actual TensorFlow version.
 //!
 //! ## Build from a source tarball
 //!
@@ -48,24 +96,84 @@
 
 // mods
 
+pub mod audit;
+pub mod batch;
+pub mod cancel;
+pub mod compression;
+pub mod content;
+pub mod diagnostics;
+#[cfg(feature = "encryption")]
+pub mod encryption;
 pub mod error;
+pub mod estimate;
+#[cfg(feature = "proto-summary")]
 pub mod event;
+#[cfg(feature = "proto-summary")]
+pub mod event_reader;
+#[cfg(feature = "proto-summary")]
 pub mod event_writer;
+#[cfg(feature = "proto-summary")]
+pub mod export;
+pub mod fingerprint;
+pub mod format;
+#[cfg(feature = "incremental")]
+pub mod incremental;
 pub mod indexer;
+pub mod inspect;
+pub mod integrity;
 pub mod io;
+pub mod join;
+pub mod latency;
+pub mod limits;
+pub mod manifest;
+pub mod memory;
+pub mod metadata;
+pub mod migrate;
+pub mod mix;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+pub mod pbtxt;
+pub mod prelude;
 pub mod protobuf;
 pub mod protobuf_ext;
+pub mod query;
 pub mod record;
 pub mod record_reader;
 pub mod record_writer;
+pub mod retention;
+pub mod reverse;
+#[cfg(feature = "testing")]
+pub mod samples;
+pub mod schema;
+#[cfg(feature = "with-serde")]
+pub mod serde_example;
+pub mod shardspec;
+#[cfg(feature = "async")]
+pub mod source;
+pub mod split;
+pub mod stats;
+pub mod subset;
+pub mod synth;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod trace;
 mod utils;
+pub mod validation;
 
 // re-exports
 
 pub use error::*;
+#[cfg(feature = "proto-summary")]
 pub use event::*;
+#[cfg(feature = "proto-summary")]
+pub use event_reader::*;
+#[cfg(feature = "proto-summary")]
 pub use event_writer::*;
-pub use protobuf::{Event, Example, Feature, HistogramProto, Summary};
+pub use integrity::IntegrityMode;
+pub use limits::Limits;
+#[cfg(feature = "proto-summary")]
+pub use protobuf::{Event, HistogramProto, Summary};
+pub use protobuf::{Example, Feature};
 pub use protobuf_ext::*;
 pub use record::*;
 pub use record_reader::*;
@@ -0,0 +1,119 @@
+//! Resource limits for untrusted input.
+//!
+//! A [Limits] is accepted by [RecordReaderConfig](crate::RecordReaderConfig),
+//! [RecordIndexerConfig](crate::indexer::RecordIndexerConfig) and the example decoders,
+//! and through them by the drivers aggregating across records, such as
+//! [incremental::update](crate::incremental::update) and
+//! [create_subset](crate::subset::create_subset), the readers holding several files
+//! open, such as [mix::weighted](crate::mix::weighted) and
+//! [iter_permuted](crate::indexer::iter_permuted), the parallel decoders of record
+//! streams and the [index cache](crate::indexer::load_paths_cached).
+//! Exceeding any cap results in [Error::LimitExceeded](crate::Error::LimitExceeded).
+//! The default limits accept anything the record framing can express, while
+//! [Limits::strict] is a conservative preset for untrusted data.
+//!
+//! # Checklist for new features
+//!
+//! Code that allocates or holds resources proportional to the input must consult [Limits]:
+//!
+//! - Add a cap field to [Limits] with values in both [Default] and [Limits::strict].
+//! - Check the cap before the allocation, not after, using the `check_*` methods.
+//! - Report violations by [Error::LimitExceeded](crate::Error::LimitExceeded) naming the field.
+//! - Add a test with a minimal violating input.
+
+use crate::error::{Error, Result};
+
+/// Caps on resource consumption when reading records.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Limits {
+    /// The maximum length in bytes of a record payload.
+    pub max_record_len: usize,
+    /// The maximum number of feature map entries in an example.
+    pub max_features: usize,
+    /// The maximum length in bytes of a feature key.
+    pub max_key_len: usize,
+    /// The maximum number of distinct keys held by an aggregation across records, such
+    /// as the values counted by a [VocabCounter](crate::incremental::VocabCounter) or the
+    /// strata of a [subset](crate::subset::Stratify).
+    pub max_distinct_keys: usize,
+    /// The maximum number of files held open at a time by a reader of several files.
+    pub max_open_files: usize,
+    /// The maximum total length in bytes of the record payloads read but not yet yielded,
+    /// such as the records being decoded in parallel.
+    pub max_in_flight_bytes: usize,
+    /// The maximum total length in bytes of the cached data loaded into memory, such as
+    /// the index sidecars read by [load_paths_cached](crate::indexer::load_paths_cached).
+    pub max_cache_bytes: usize,
+}
+
+impl Limits {
+    /// The conservative preset for untrusted data.
+    pub fn strict() -> Self {
+        Self {
+            max_record_len: 64 * 1024 * 1024,
+            max_features: 10_000,
+            max_key_len: 1024,
+            max_distinct_keys: 1_000_000,
+            max_open_files: 64,
+            max_in_flight_bytes: 256 * 1024 * 1024,
+            max_cache_bytes: 64 * 1024 * 1024,
+        }
+    }
+
+    /// Check the length of a record payload.
+    pub fn check_record_len(&self, len: usize) -> Result<()> {
+        check("max_record_len", self.max_record_len, len)
+    }
+
+    /// Check the number of feature map entries in an example.
+    pub fn check_features(&self, count: usize) -> Result<()> {
+        check("max_features", self.max_features, count)
+    }
+
+    /// Check the length of a feature key.
+    pub fn check_key_len(&self, len: usize) -> Result<()> {
+        check("max_key_len", self.max_key_len, len)
+    }
+
+    /// Check the number of distinct keys held by an aggregation.
+    pub fn check_distinct_keys(&self, count: usize) -> Result<()> {
+        check("max_distinct_keys", self.max_distinct_keys, count)
+    }
+
+    /// Check the number of files held open at a time.
+    pub fn check_open_files(&self, count: usize) -> Result<()> {
+        check("max_open_files", self.max_open_files, count)
+    }
+
+    /// Check the total length of the record payloads in flight.
+    pub fn check_in_flight_bytes(&self, len: usize) -> Result<()> {
+        check("max_in_flight_bytes", self.max_in_flight_bytes, len)
+    }
+
+    /// Check the total length of the cached data loaded into memory.
+    pub fn check_cache_bytes(&self, len: usize) -> Result<()> {
+        check("max_cache_bytes", self.max_cache_bytes, len)
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            // the maximum length of a record frame
+            max_record_len: usize::MAX,
+            max_features: usize::MAX,
+            max_key_len: usize::MAX,
+            max_distinct_keys: usize::MAX,
+            max_open_files: usize::MAX,
+            max_in_flight_bytes: usize::MAX,
+            max_cache_bytes: usize::MAX,
+        }
+    }
+}
+
+fn check(which: &'static str, limit: usize, observed: usize) -> Result<()> {
+    if observed > limit {
+        return Err(Error::limit_exceeded(which, limit, observed));
+    }
+    Ok(())
+}
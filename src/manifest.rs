@@ -0,0 +1,656 @@
+//! Dataset manifests listing files with their record counts, sizes and fingerprints.
+//!
+//! A [Manifest] travels with a dataset, so that the receiver can verify the files it
+//! got are the ones intended. It is [generated](generate) from the files, written next
+//! to them, and [loaded](Manifest::load_indexes) on the other side, which verifies the
+//! files by a [ManifestPolicy] and fails with [Error::ManifestMismatch] listing the
+//! differences per file.
+//!
+//! Files are identified by the [Layout](FingerprintLevel::Layout) fingerprint, which
+//! hashes the sizes and record indexes but not paths, so the dataset can be moved to
+//! another location. The [Content](FingerprintLevel::Content) fingerprints are optional
+//! since they read all payloads.
+//!
+//! The manifest is a text file with a header line followed by `key=value` lines, one
+//! `feature` line per schema entry and one `file` line per file in order. File lines
+//! hold `key=value` fields separated by tabs, shown as spaces below.
+//!
+//! ```text
+//! tfrecord-dataset-manifest
+//! version=1
+//! created_at=1700000000.000000000
+//! producer=tfrecord 0.14.0
+//! feature=label:int64
+//! file=path=part-00000.tfrecord records=100 bytes=5312 layout=04e1...
+//! ```
+//!
+//! Newer versions of the format only add keys. Unknown keys are preserved in `extra`,
+//! both in the manifest and in its files, and written back.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{
+//!     manifest::{self, Manifest, ManifestPolicy},
+//!     samples,
+//! };
+//!
+//! let dataset = samples::tiny_dataset(2, 5)?;
+//! let manifest_path = dataset.dir().join("MANIFEST");
+//! manifest::generate(dataset.paths(), Default::default())?.write_to(&manifest_path)?;
+//!
+//! let indexes = Manifest::read_from(&manifest_path)?
+//!     .load_indexes(ManifestPolicy::Verify, Default::default())?;
+//! assert_eq!(indexes.len(), 10);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    fingerprint::{self, Fingerprint, FingerprintLevel},
+    indexer::{self, PathOrder, RecordIndex, RecordIndexerConfig},
+    protobuf::{feature::Kind, Example},
+    trace::{AttributeValue, Operation, Span},
+};
+use prost::Message as _;
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The version of the manifest written by this crate.
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// The number of leading records of the first file inspected to infer the schema.
+pub const SCHEMA_SAMPLE_RECORDS: usize = 16;
+
+/// The first line of manifest files.
+const HEADER: &str = "tfrecord-dataset-manifest";
+
+/// The value type of a feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "with-serde", serde(rename_all = "lowercase"))]
+pub enum FeatureType {
+    Bytes,
+    Float,
+    Int64,
+}
+
+impl fmt::Display for FeatureType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Self::Bytes => "bytes",
+            Self::Float => "float",
+            Self::Int64 => "int64",
+        };
+        f.write_str(text)
+    }
+}
+
+impl FromStr for FeatureType {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        Ok(match text {
+            "bytes" => Self::Bytes,
+            "float" => Self::Float,
+            "int64" => Self::Int64,
+            _ => {
+                return Err(Error::conversion(format!(
+                    "invalid feature type '{}'",
+                    text
+                )))
+            }
+        })
+    }
+}
+
+/// The feature names and types of examples.
+pub type Schema = BTreeMap<String, FeatureType>;
+
+/// The manifest of a dataset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Manifest {
+    /// The version of the manifest format.
+    pub version: u32,
+    pub created_at: SystemTime,
+    /// The name and the version of the generator.
+    pub producer: String,
+    /// The schema of examples, if provided or inferred.
+    pub schema: Option<Schema>,
+    /// The files in order.
+    pub files: Vec<ManifestFile>,
+    /// The entries with unknown keys.
+    #[cfg_attr(feature = "with-serde", serde(flatten))]
+    pub extra: BTreeMap<String, String>,
+}
+
+/// A file listed in a [Manifest].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManifestFile {
+    /// The path, relative to the directory of the manifest once written.
+    pub path: PathBuf,
+    pub num_records: u64,
+    pub num_bytes: u64,
+    /// The [Layout](FingerprintLevel::Layout) fingerprint of the file.
+    pub layout: Fingerprint,
+    /// The [Content](FingerprintLevel::Content) fingerprint of the file, if computed.
+    pub content: Option<Fingerprint>,
+    /// The fields with unknown keys.
+    #[cfg_attr(feature = "with-serde", serde(flatten))]
+    pub extra: BTreeMap<String, String>,
+}
+
+/// Options for [generate].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ManifestOptions {
+    /// The schema to record. If unset, it is inferred from the leading examples of the
+    /// first non-empty file, and left out if the records are not examples.
+    pub schema: Option<Schema>,
+    /// If set, the [Content](FingerprintLevel::Content) fingerprints are computed.
+    pub content_fingerprints: bool,
+    /// The creation time to record instead of the current time.
+    pub created_at: Option<SystemTime>,
+    /// The configuration to index files.
+    pub indexer: RecordIndexerConfig,
+}
+
+/// The verification of files when loading a manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ManifestPolicy {
+    /// Load the listed files without verification.
+    Ignore,
+    /// Verify the record counts, the sizes and the layout fingerprints.
+    #[default]
+    Verify,
+    /// Verify the content fingerprints in addition, which the manifest must have.
+    VerifyContent,
+}
+
+/// A difference between a file and its manifest entry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FileDiff {
+    pub path: PathBuf,
+    pub mismatch: Mismatch,
+}
+
+/// The quantity of a file differing from its manifest entry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Mismatch {
+    /// The file does not exist.
+    Missing,
+    NumRecords {
+        expected: u64,
+        found: u64,
+    },
+    NumBytes {
+        expected: u64,
+        found: u64,
+    },
+    Layout {
+        expected: Fingerprint,
+        found: Fingerprint,
+    },
+    Content {
+        expected: Fingerprint,
+        found: Fingerprint,
+    },
+}
+
+impl fmt::Display for FileDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self.path.display();
+        match &self.mismatch {
+            Mismatch::Missing => write!(f, "{} is missing", path),
+            Mismatch::NumRecords { expected, found } => {
+                write!(f, "{} has {} records, expect {}", path, found, expected)
+            }
+            Mismatch::NumBytes { expected, found } => {
+                write!(f, "{} has {} bytes, expect {}", path, found, expected)
+            }
+            Mismatch::Layout { expected, found } => {
+                write!(f, "{} has layout {}, expect {}", path, found, expected)
+            }
+            Mismatch::Content { expected, found } => {
+                write!(f, "{} has content {}, expect {}", path, found, expected)
+            }
+        }
+    }
+}
+
+/// Generate the manifest of files.
+///
+/// The files are listed in the [path order](RecordIndexerConfig::path_order) of the
+/// indexer configuration.
+pub fn generate<'a, P, I>(paths: I, options: ManifestOptions) -> Result<Manifest>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Cow<'a, Path>>,
+{
+    let ManifestOptions {
+        schema,
+        content_fingerprints,
+        created_at,
+        indexer: config,
+    } = options;
+
+    let paths: Vec<PathBuf> = paths
+        .into_iter()
+        .map(|path| path.into().into_owned())
+        .collect();
+    let paths = indexer::sort_paths(paths, config.path_order)?;
+
+    let mut schema = schema;
+    let mut needs_schema = schema.is_none();
+    let files = paths
+        .into_iter()
+        .map(|path| {
+            let indexes = load_file(&path, &config)?;
+            if needs_schema && !indexes.is_empty() {
+                schema = infer_schema(&indexes[..indexes.len().min(SCHEMA_SAMPLE_RECORDS)]);
+                needs_schema = false;
+            }
+            let content = if content_fingerprints {
+                Some(fingerprint::fingerprint(
+                    &indexes,
+                    FingerprintLevel::Content,
+                )?)
+            } else {
+                None
+            };
+            Ok(ManifestFile {
+                num_records: indexes.len() as u64,
+                num_bytes: std::fs::metadata(&path)?.len(),
+                layout: fingerprint::fingerprint(&indexes, FingerprintLevel::Layout)?,
+                content,
+                path,
+                extra: BTreeMap::new(),
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(Manifest {
+        version: MANIFEST_VERSION,
+        created_at: created_at.unwrap_or_else(SystemTime::now),
+        producer: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+        schema,
+        files,
+        extra: BTreeMap::new(),
+    })
+}
+
+impl Manifest {
+    /// Parse the contents of a manifest file.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut lines = text.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(Error::conversion("the manifest header is missing"));
+        }
+
+        let mut version = None;
+        let mut created_at = None;
+        let mut producer = String::new();
+        let mut schema: Option<Schema> = None;
+        let mut files = vec![];
+        let mut extra = BTreeMap::new();
+
+        for line in lines.filter(|line| !line.is_empty()) {
+            let (key, value) = split_entry(line)?;
+            let parse_error =
+                || Error::conversion(format!("invalid manifest value '{}={}'", key, value));
+            match key {
+                "version" => version = Some(value.parse().map_err(|_| parse_error())?),
+                "created_at" => created_at = Some(parse_time(value).ok_or_else(parse_error)?),
+                "producer" => producer = value.to_string(),
+                "feature" => {
+                    let (name, kind) = value.rsplit_once(':').ok_or_else(parse_error)?;
+                    schema
+                        .get_or_insert_with(Schema::new)
+                        .insert(name.to_string(), kind.parse()?);
+                }
+                "file" => files.push(ManifestFile::parse(value)?),
+                _ => {
+                    extra.insert(key.to_string(), value.to_string());
+                }
+            }
+        }
+
+        Ok(Self {
+            version: version.ok_or_else(|| Error::conversion("the manifest version is missing"))?,
+            created_at: created_at
+                .ok_or_else(|| Error::conversion("the manifest creation time is missing"))?,
+            producer,
+            schema,
+            files,
+            extra,
+        })
+    }
+
+    /// Render the contents of a manifest file.
+    ///
+    /// It fails if a path or a value holds a tab or a line break.
+    pub fn to_text(&self) -> Result<String> {
+        let created_at = self.created_at.duration_since(UNIX_EPOCH).map_err(|_| {
+            Error::conversion("the manifest creation time is before the Unix epoch")
+        })?;
+        let mut text = format!(
+            "{}\nversion={}\ncreated_at={}.{:09}\n",
+            HEADER,
+            self.version,
+            created_at.as_secs(),
+            created_at.subsec_nanos()
+        );
+        push_entry(&mut text, "producer", &self.producer)?;
+        for (name, kind) in self.schema.iter().flatten() {
+            push_entry(&mut text, "feature", &format!("{}:{}", name, kind))?;
+        }
+        for file in &self.files {
+            push_entry(&mut text, "file", &file.to_text()?)?;
+        }
+        for (key, value) in &self.extra {
+            push_entry(&mut text, key, value)?;
+        }
+        Ok(text)
+    }
+
+    /// Read a manifest file, resolving the paths of files against its directory.
+    pub fn read_from<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let mut manifest = Self::parse(&std::fs::read_to_string(path)?)?;
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        for file in &mut manifest.files {
+            file.path = dir.join(&file.path);
+        }
+        Ok(manifest)
+    }
+
+    /// Write a manifest file. The paths of files under its directory are written
+    /// relative to the directory.
+    pub fn write_to<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let mut manifest = self.clone();
+        for file in &mut manifest.files {
+            if let Ok(relative) = file.path.strip_prefix(dir) {
+                file.path = relative.to_path_buf();
+            }
+        }
+        std::fs::write(path, manifest.to_text()?)?;
+        Ok(())
+    }
+
+    /// Verify the listed files by the policy, returning the differences.
+    pub fn verify(
+        &self,
+        policy: ManifestPolicy,
+        config: RecordIndexerConfig,
+    ) -> Result<Vec<FileDiff>> {
+        let (_, diffs) = self.load_and_verify(policy, &config)?;
+        Ok(diffs)
+    }
+
+    /// Load the record indexes of the listed files in order, verified by the policy.
+    ///
+    /// It fails with [Error::ManifestMismatch] listing the differences of all files.
+    pub fn load_indexes(
+        &self,
+        policy: ManifestPolicy,
+        config: RecordIndexerConfig,
+    ) -> Result<Vec<RecordIndex>> {
+        let (indexes, diffs) = self.load_and_verify(policy, &config)?;
+        if !diffs.is_empty() {
+            return Err(Error::ManifestMismatch { diffs });
+        }
+        Ok(indexes)
+    }
+
+    fn load_and_verify(
+        &self,
+        policy: ManifestPolicy,
+        config: &RecordIndexerConfig,
+    ) -> Result<(Vec<RecordIndex>, Vec<FileDiff>)> {
+        let mut indexes = vec![];
+        let mut diffs = vec![];
+        if policy == ManifestPolicy::Ignore {
+            for file in &self.files {
+                indexes.extend(load_file(&file.path, config)?);
+            }
+            return Ok((indexes, diffs));
+        }
+
+        let mut span = Span::start(
+            config.trace.as_ref(),
+            Operation::VerifyManifest,
+            None,
+            &[
+                ("files", AttributeValue::U64(self.files.len() as u64)),
+                ("policy", AttributeValue::Str(format!("{:?}", policy))),
+            ],
+        );
+        for (num_verified, file) in self.files.iter().enumerate() {
+            let mut file_span = Span::start(
+                config.trace.as_ref(),
+                Operation::VerifyFile,
+                span.as_ref().map(Span::handle),
+                &[("path", AttributeValue::Str(file.path.display().to_string()))],
+            );
+            let (file_indexes, mismatch) = match file.verify(policy, config) {
+                Ok(verified) => verified,
+                Err(err) => {
+                    for span in [&mut span, &mut file_span].into_iter().flatten() {
+                        span.fail(&err);
+                    }
+                    if let Some(span) = &mut span {
+                        span.set("files", AttributeValue::U64(num_verified as u64));
+                        span.set("mismatches", AttributeValue::U64(diffs.len() as u64));
+                    }
+                    return Err(err);
+                }
+            };
+            match mismatch {
+                Some(mismatch) => {
+                    let diff = FileDiff {
+                        path: file.path.clone(),
+                        mismatch,
+                    };
+                    if let Some(span) = &mut file_span {
+                        span.set("mismatch", AttributeValue::Str(diff.to_string()));
+                    }
+                    diffs.push(diff);
+                }
+                None => indexes.extend(file_indexes),
+            }
+        }
+        if let Some(span) = &mut span {
+            span.set("files", AttributeValue::U64(self.files.len() as u64));
+            span.set("mismatches", AttributeValue::U64(diffs.len() as u64));
+        }
+
+        Ok((indexes, diffs))
+    }
+}
+
+impl ManifestFile {
+    /// Verify the file by the policy, returning its indexes or the difference.
+    fn verify(
+        &self,
+        policy: ManifestPolicy,
+        config: &RecordIndexerConfig,
+    ) -> Result<(Vec<RecordIndex>, Option<Mismatch>)> {
+        if !self.path.exists() {
+            return Ok((vec![], Some(Mismatch::Missing)));
+        }
+
+        let num_bytes = std::fs::metadata(&self.path)?.len();
+        if num_bytes != self.num_bytes {
+            let mismatch = Mismatch::NumBytes {
+                expected: self.num_bytes,
+                found: num_bytes,
+            };
+            return Ok((vec![], Some(mismatch)));
+        }
+        let indexes = load_file(&self.path, config)?;
+        let num_records = indexes.len() as u64;
+        if num_records != self.num_records {
+            let mismatch = Mismatch::NumRecords {
+                expected: self.num_records,
+                found: num_records,
+            };
+            return Ok((vec![], Some(mismatch)));
+        }
+        let layout = fingerprint::fingerprint(&indexes, FingerprintLevel::Layout)?;
+        if layout != self.layout {
+            let mismatch = Mismatch::Layout {
+                expected: self.layout,
+                found: layout,
+            };
+            return Ok((vec![], Some(mismatch)));
+        }
+        if policy == ManifestPolicy::VerifyContent {
+            let expected = self.content.ok_or_else(|| {
+                Error::invalid_argument(format!(
+                    "the manifest has no content fingerprint of {}",
+                    self.path.display()
+                ))
+            })?;
+            let found = fingerprint::fingerprint(&indexes, FingerprintLevel::Content)?;
+            if found != expected {
+                return Ok((vec![], Some(Mismatch::Content { expected, found })));
+            }
+        }
+        Ok((indexes, None))
+    }
+
+    fn parse(text: &str) -> Result<Self> {
+        let mut path = None;
+        let mut num_records = None;
+        let mut num_bytes = None;
+        let mut layout = None;
+        let mut content = None;
+        let mut extra = BTreeMap::new();
+
+        for field in text.split('\t') {
+            let (key, value) = split_entry(field)?;
+            let parse_error =
+                || Error::conversion(format!("invalid manifest file field '{}={}'", key, value));
+            match key {
+                "path" => path = Some(PathBuf::from(value)),
+                "records" => num_records = Some(value.parse().map_err(|_| parse_error())?),
+                "bytes" => num_bytes = Some(value.parse().map_err(|_| parse_error())?),
+                "layout" => layout = Some(value.parse()?),
+                "content" => content = Some(value.parse()?),
+                _ => {
+                    extra.insert(key.to_string(), value.to_string());
+                }
+            }
+        }
+
+        let missing =
+            |key| Error::conversion(format!("the manifest file field '{}' is missing", key));
+        Ok(Self {
+            path: path.ok_or_else(|| missing("path"))?,
+            num_records: num_records.ok_or_else(|| missing("records"))?,
+            num_bytes: num_bytes.ok_or_else(|| missing("bytes"))?,
+            layout: layout.ok_or_else(|| missing("layout"))?,
+            content,
+            extra,
+        })
+    }
+
+    fn to_text(&self) -> Result<String> {
+        let path = self.path.to_str().ok_or_else(|| {
+            Error::conversion(format!("the path {} is not UTF-8", self.path.display()))
+        })?;
+        let mut fields = vec![
+            format!("path={}", path),
+            format!("records={}", self.num_records),
+            format!("bytes={}", self.num_bytes),
+            format!("layout={}", self.layout),
+        ];
+        if let Some(content) = self.content {
+            fields.push(format!("content={}", content));
+        }
+        fields.extend(
+            self.extra
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value)),
+        );
+        if fields.iter().any(|field| field.contains('\t')) {
+            return Err(Error::conversion(format!(
+                "the manifest file field of {} holds a tab",
+                path
+            )));
+        }
+        Ok(fields.join("\t"))
+    }
+}
+
+fn split_entry(line: &str) -> Result<(&str, &str)> {
+    line.split_once('=')
+        .ok_or_else(|| Error::conversion(format!("invalid manifest line '{}'", line)))
+}
+
+fn push_entry(text: &mut String, key: &str, value: &str) -> Result<()> {
+    if value.contains(['\n', '\r']) {
+        return Err(Error::conversion(format!(
+            "the manifest value of '{}' holds a line break",
+            key
+        )));
+    }
+    text.push_str(&format!("{}={}\n", key, value));
+    Ok(())
+}
+
+fn parse_time(text: &str) -> Option<SystemTime> {
+    let (secs, nanos) = text.split_once('.')?;
+    let elapsed = Duration::new(secs.parse().ok()?, nanos.parse().ok()?);
+    UNIX_EPOCH.checked_add(elapsed)
+}
+
+/// Index a file in its own order.
+fn load_file(path: &Path, config: &RecordIndexerConfig) -> Result<Vec<RecordIndex>> {
+    let config = RecordIndexerConfig {
+        path_order: PathOrder::AsGiven,
+        ..config.clone()
+    };
+    indexer::load_file(path, config)?.collect()
+}
+
+/// Infer the schema from examples, or `None` if the records are not examples or the
+/// types of a feature disagree.
+fn infer_schema(indexes: &[RecordIndex]) -> Option<Schema> {
+    let mut schema = Schema::new();
+    for index in indexes {
+        let bytes: Vec<u8> = index.load().ok()?;
+        let example = Example::decode(bytes.as_slice()).ok()?;
+        for (name, feature) in example
+            .features
+            .iter()
+            .flat_map(|features| &features.feature)
+        {
+            let kind = match &feature.kind {
+                Some(Kind::BytesList(_)) => FeatureType::Bytes,
+                Some(Kind::FloatList(_)) => FeatureType::Float,
+                Some(Kind::Int64List(_)) => FeatureType::Int64,
+                None => continue,
+            };
+            if *schema.entry(name.clone()).or_insert(kind) != kind {
+                return None;
+            }
+        }
+    }
+    Some(schema)
+}
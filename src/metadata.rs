@@ -0,0 +1,449 @@
+//! Shard metadata declaring properties of record files.
+//!
+//! Plain TFRecord files have no place for metadata that TensorFlow readers skip, so the
+//! [ShardMetadata] of a file is stored in a sidecar file next to it, named by appending
+//! [METADATA_SUFFIX] to the file name. For example, `train-0.tfrecord` is described by
+//! `train-0.tfrecord.meta`. The indexer never loads sidecar files as record files.
+//!
+//! Writers created by path write the sidecar when
+//! [sorted_features](crate::RecordWriterConfig::sorted_features) or
+//! [stamp_producer](crate::RecordWriterConfig::stamp_producer) is set, and remove a
+//! stale sidecar otherwise. Readers check the declaration with
+//! [shard_declares_sorted_keys] before enabling
+//! [sorted_keys](crate::FeatureProjection::sorted_keys) projections.
+//!
+//! The sidecar is a text file with a header line followed by `key=value` lines.
+//!
+//! ```text
+//! tfrecord-shard-metadata
+//! version=1
+//! sorted_features=true
+//! producer=rust-tfrecord
+//! producer_version=0.14.0
+//! producer.canonical_encoding=false
+//! producer.compression=none
+//! ```
+//!
+//! The `producer` lines record the [ProducerInfo] of the writer, read by
+//! [producer_info](crate::inspect::producer_info). Unknown keys are preserved in [extra](ShardMetadata::extra).
+//!
+//! Sidecars are also written by tools other than this crate, so reading them for
+//! optimizations is lenient. [ShardMetadata::load_for] reports a sidecar of an unknown
+//! major version, one larger than [MAX_METADATA_LEN] or a malformed one as a
+//! [MetadataWarning] instead of an error, and such a file is read as if it had no
+//! metadata. [DatasetMetadata] loads the metadata of every file of a dataset this way and
+//! counts the warnings. Record files are indexed and read without their sidecars, so
+//! metadata never fails reading data.
+
+use crate::{
+    compression::Compression,
+    error::{Error, Result},
+    inspect::ProducerInfo,
+    record_writer::RecordWriterConfig,
+};
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+/// The suffix appended to file names to name their sidecar files.
+pub const METADATA_SUFFIX: &str = ".meta";
+
+/// The version of the metadata written by this crate.
+pub const METADATA_VERSION: u32 = 1;
+
+/// The maximum length in bytes of sidecar files loaded by [ShardMetadata::load_for].
+pub const MAX_METADATA_LEN: u64 = 64 * 1024;
+
+/// The first line of sidecar files.
+const HEADER: &str = "tfrecord-shard-metadata";
+
+/// The properties declared by a record file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShardMetadata {
+    /// The major version of the metadata format. A minor version, written as
+    /// `version=1.2`, is accepted and dropped on parsing.
+    pub version: u32,
+    /// The feature map entries of every example are sorted by key without duplicates.
+    pub sorted_features: bool,
+    /// The writer that produced the file.
+    pub producer: Option<ProducerInfo>,
+    /// The entries with unknown keys.
+    pub extra: BTreeMap<String, String>,
+}
+
+impl Default for ShardMetadata {
+    fn default() -> Self {
+        Self {
+            version: METADATA_VERSION,
+            sorted_features: false,
+            producer: None,
+            extra: BTreeMap::new(),
+        }
+    }
+}
+
+impl ShardMetadata {
+    /// Parse the contents of a sidecar file.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut lines = text.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(Error::conversion("the shard metadata header is missing"));
+        }
+
+        let mut version = None;
+        let mut sorted_features = false;
+        let mut producer_name = None;
+        let mut producer_version = None;
+        let mut producer_options = BTreeMap::new();
+        let mut extra = BTreeMap::new();
+
+        for line in lines.filter(|line| !line.is_empty()) {
+            let (key, value) = line.split_once('=').ok_or_else(|| {
+                Error::conversion(format!("invalid shard metadata line '{}'", line))
+            })?;
+            let parse_error =
+                || Error::conversion(format!("invalid shard metadata value '{}={}'", key, value));
+            match key {
+                "version" => {
+                    let major = value.split_once('.').map_or(value, |(major, _)| major);
+                    version = Some(major.parse().map_err(|_| parse_error())?);
+                }
+                "sorted_features" => sorted_features = value.parse().map_err(|_| parse_error())?,
+                "producer" => producer_name = Some(value.to_string()),
+                "producer_version" => producer_version = Some(value.to_string()),
+                _ if key.starts_with("producer.") => {
+                    producer_options
+                        .insert(key["producer.".len()..].to_string(), value.to_string());
+                }
+                _ => {
+                    extra.insert(key.to_string(), value.to_string());
+                }
+            }
+        }
+
+        let version =
+            version.ok_or_else(|| Error::conversion("the shard metadata version is missing"))?;
+        let producer = match (producer_name, producer_version) {
+            (Some(name), Some(version)) => Some(ProducerInfo {
+                name,
+                version,
+                options: producer_options,
+            }),
+            (None, None) if producer_options.is_empty() => None,
+            _ => {
+                return Err(Error::conversion(
+                    "the shard metadata producer is incomplete",
+                ))
+            }
+        };
+        Ok(Self {
+            version,
+            sorted_features,
+            producer,
+            extra,
+        })
+    }
+
+    /// Render the contents of a sidecar file.
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "{}\nversion={}\nsorted_features={}\n",
+            HEADER, self.version, self.sorted_features
+        );
+        if let Some(producer) = &self.producer {
+            text.push_str(&format!(
+                "producer={}\nproducer_version={}\n",
+                producer.name, producer.version
+            ));
+            for (key, value) in &producer.options {
+                text.push_str(&format!("producer.{}={}\n", key, value));
+            }
+        }
+        for (key, value) in &self.extra {
+            text.push_str(&format!("{}={}\n", key, value));
+        }
+        text
+    }
+
+    /// Read the metadata of a record file from its sidecar file.
+    ///
+    /// It returns `None` if the sidecar file does not exist.
+    pub fn read_for<P>(path: P) -> Result<Option<Self>>
+    where
+        P: AsRef<Path>,
+    {
+        let text = match std::fs::read_to_string(metadata_path(path)) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Some(Self::parse(&text)?))
+    }
+
+    /// Load the metadata of a record file for reading, without failing.
+    ///
+    /// It returns `None` if the sidecar file does not exist. A sidecar which is
+    /// unreadable, longer than [MAX_METADATA_LEN], malformed or of a major version newer
+    /// than [METADATA_VERSION] is reported as a warning, and the file is to be read as
+    /// if it had no metadata.
+    pub fn load_for<P>(path: P) -> Option<std::result::Result<Self, MetadataWarning>>
+    where
+        P: AsRef<Path>,
+    {
+        let sidecar = metadata_path(path);
+        let len = match std::fs::metadata(&sidecar) {
+            Ok(stat) => stat.len(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return None,
+            Err(err) => {
+                return Some(Err(MetadataWarning::Unreadable {
+                    reason: err.to_string(),
+                }))
+            }
+        };
+        if len > MAX_METADATA_LEN {
+            return Some(Err(MetadataWarning::Oversized {
+                len,
+                limit: MAX_METADATA_LEN,
+            }));
+        }
+
+        Some(load_sidecar(&sidecar))
+    }
+
+    /// Write the metadata of a record file to its sidecar file.
+    pub fn write_for<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        std::fs::write(metadata_path(path), self.to_text())?;
+        Ok(())
+    }
+}
+
+/// The reason the sidecar file of a record file is ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum MetadataWarning {
+    /// The sidecar declares a major version newer than [METADATA_VERSION].
+    UnsupportedVersion { version: u32 },
+    /// The sidecar is longer than the limit.
+    Oversized { len: u64, limit: u64 },
+    /// The sidecar fails to parse.
+    Malformed { reason: String },
+    /// The sidecar exists but fails to read.
+    Unreadable { reason: String },
+}
+
+impl fmt::Display for MetadataWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedVersion { version } => write!(
+                f,
+                "unsupported shard metadata version {}, expect at most {}",
+                version, METADATA_VERSION
+            ),
+            Self::Oversized { len, limit } => write!(
+                f,
+                "shard metadata of {} bytes exceeds the limit of {} bytes",
+                len, limit
+            ),
+            Self::Malformed { reason } => write!(f, "malformed shard metadata: {}", reason),
+            Self::Unreadable { reason } => write!(f, "unreadable shard metadata: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for MetadataWarning {}
+
+/// The metadata of the files of a dataset, loaded by [ShardMetadata::load_for].
+///
+/// ```rust
+/// # fn main() -> tfrecord::Result<()> {
+/// use tfrecord::{metadata::DatasetMetadata, samples};
+///
+/// let dataset = samples::tiny_dataset(2, 3)?;
+/// let metadata = DatasetMetadata::load(dataset.paths());
+/// assert_eq!(metadata.len(), 2);
+/// assert!(metadata.shard_metadata(0).is_none());
+/// assert!(!metadata.declares_sorted_keys(0));
+/// assert_eq!(metadata.num_warnings(), 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatasetMetadata {
+    entries: Vec<Option<std::result::Result<ShardMetadata, MetadataWarning>>>,
+    num_warnings: usize,
+}
+
+impl DatasetMetadata {
+    /// Load the metadata of the record files, in the order of the paths.
+    pub fn load<P>(paths: impl IntoIterator<Item = P>) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        let entries: Vec<_> = paths.into_iter().map(ShardMetadata::load_for).collect();
+        let num_warnings = entries
+            .iter()
+            .filter(|entry| matches!(entry, Some(Err(_))))
+            .count();
+        Self {
+            entries,
+            num_warnings,
+        }
+    }
+
+    /// The number of files.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The metadata of the file at the index, `None` if it has no sidecar or the index
+    /// is out of range, or the warning if its sidecar is ignored.
+    pub fn shard_metadata(
+        &self,
+        file_idx: usize,
+    ) -> Option<std::result::Result<ShardMetadata, MetadataWarning>> {
+        self.entries.get(file_idx)?.clone()
+    }
+
+    /// The number of files whose sidecars are ignored.
+    pub fn num_warnings(&self) -> usize {
+        self.num_warnings
+    }
+
+    /// The warnings with the indexes of their files.
+    pub fn warnings(&self) -> impl Iterator<Item = (usize, &MetadataWarning)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| match entry {
+                Some(Err(warning)) => Some((index, warning)),
+                _ => None,
+            })
+    }
+
+    /// Returns true if the file at the index declares that the feature map entries of its
+    /// examples are sorted by key, and false if it has no usable metadata.
+    pub fn declares_sorted_keys(&self, file_idx: usize) -> bool {
+        matches!(
+            self.entries.get(file_idx),
+            Some(Some(Ok(ShardMetadata {
+                sorted_features: true,
+                ..
+            })))
+        )
+    }
+}
+
+fn load_sidecar(sidecar: &Path) -> std::result::Result<ShardMetadata, MetadataWarning> {
+    let bytes = std::fs::read(sidecar).map_err(|err| MetadataWarning::Unreadable {
+        reason: err.to_string(),
+    })?;
+    let text = String::from_utf8(bytes).map_err(|_| MetadataWarning::Malformed {
+        reason: "the sidecar is not UTF-8 text".into(),
+    })?;
+    let metadata = ShardMetadata::parse(&text).map_err(|err| {
+        let reason = match err {
+            Error::ConversionError { desc } => desc.into_owned(),
+            err => err.to_string(),
+        };
+        MetadataWarning::Malformed { reason }
+    })?;
+    if metadata.version > METADATA_VERSION {
+        return Err(MetadataWarning::UnsupportedVersion {
+            version: metadata.version,
+        });
+    }
+    Ok(metadata)
+}
+
+/// The path of the sidecar file of a record file.
+pub fn metadata_path<P>(path: P) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let mut file_name: OsString = path.file_name().unwrap_or_default().into();
+    file_name.push(METADATA_SUFFIX);
+    path.with_file_name(file_name)
+}
+
+/// Returns true if the path names a sidecar file.
+pub fn is_metadata_path<P>(path: P) -> bool
+where
+    P: AsRef<Path>,
+{
+    path.as_ref()
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().ends_with(METADATA_SUFFIX))
+}
+
+/// Returns true if the file declares that the feature map entries of its examples are
+/// sorted by key.
+///
+/// Missing metadata, and metadata ignored by [ShardMetadata::load_for], declares nothing,
+/// so the function returns false in these cases.
+pub fn shard_declares_sorted_keys<P>(path: P) -> bool
+where
+    P: AsRef<Path>,
+{
+    matches!(
+        ShardMetadata::load_for(path),
+        Some(Ok(ShardMetadata {
+            sorted_features: true,
+            ..
+        }))
+    )
+}
+
+/// The metadata of a record file written with the configuration, if it declares
+/// anything.
+fn writer_metadata(config: &RecordWriterConfig, compression: Compression) -> Option<ShardMetadata> {
+    let producer = config
+        .stamp_producer
+        .then(|| ProducerInfo::for_writer(config, compression));
+    (config.sorted_features || producer.is_some()).then(|| ShardMetadata {
+        sorted_features: config.sorted_features,
+        producer,
+        ..Default::default()
+    })
+}
+
+/// Write or remove the sidecar file of a record file created with the configuration.
+pub(crate) fn sync_sidecar(
+    path: &Path,
+    config: &RecordWriterConfig,
+    compression: Compression,
+) -> Result<()> {
+    if let Some(metadata) = writer_metadata(config, compression) {
+        metadata.write_for(path)?;
+    } else {
+        // a stale sidecar must not declare properties of the new contents
+        match std::fs::remove_file(metadata_path(path)) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok(())
+}
+
+/// Write or remove the sidecar file on a blocking task, as [sync_sidecar] does.
+#[cfg(feature = "async")]
+pub(crate) async fn sync_sidecar_async(
+    path: &Path,
+    config: &RecordWriterConfig,
+    compression: Compression,
+) -> Result<()> {
+    let path = path.to_owned();
+    let config = config.clone();
+    async_std::task::spawn_blocking(move || sync_sidecar(&path, &config, compression)).await
+}
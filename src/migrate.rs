@@ -0,0 +1,380 @@
+//! Upgrade existing shards to the conventions of this crate.
+//!
+//! [upgrade_shard] streams the records of a shard to a temporary file next to the
+//! output, attaches the [shard metadata](crate::metadata) stamped with
+//! [STAMP_KEY], and verifies the temporary file by reading it back and comparing it
+//! record by record with the input. Only a verified file replaces the output, by
+//! renaming, so that a failure at any step leaves the input and the output untouched.
+//! The output may be the input itself to upgrade in place.
+//!
+//! - [Passthrough](UpgradeMode::Passthrough) copies the payloads as is, and verifies
+//!   that the payloads are equal.
+//! - [Reencode](UpgradeMode::Reencode) decodes the records as examples and encodes
+//!   them with [sorted features](crate::RecordWriterConfig::sorted_features), and
+//!   verifies that the examples are equal with [bitwise](crate::FloatIdentity::Bitwise)
+//!   floats.
+//!
+//! [upgrade_manifest] upgrades the files listed in a [Manifest], reporting the outcome
+//! per file. Outputs stamped by an earlier run are skipped, so that an interrupted
+//! batch resumes where it stopped.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{
+//!     metadata::ShardMetadata,
+//!     migrate::{self, UpgradeMode, UpgradeOptions},
+//!     samples,
+//! };
+//!
+//! let dataset = samples::tiny_dataset(1, 4)?;
+//! let path = &dataset.paths()[0];
+//! let options = UpgradeOptions {
+//!     mode: UpgradeMode::Reencode,
+//!     ..Default::default()
+//! };
+//! let report = migrate::upgrade_shard(path, path, options.clone())?;
+//! assert_eq!(report.num_records, 4);
+//! assert!(ShardMetadata::read_for(path)?.unwrap().sorted_features);
+//! assert!(migrate::is_upgraded(path, &options.stamp));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    manifest::Manifest,
+    metadata::{metadata_path, ShardMetadata},
+    protobuf::Example,
+    protobuf_ext::FloatIdentity,
+    record::Record,
+    record_reader::{BytesIter, RecordReaderConfig},
+    record_writer::BytesWriter,
+    utils,
+};
+use std::{
+    borrow::Cow,
+    ffi::OsString,
+    fmt,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// The shard metadata key holding the stamp of the upgrade.
+pub const STAMP_KEY: &str = "upgraded_by";
+
+/// The default stamp, naming this crate and its version.
+pub const DEFAULT_STAMP: &str = concat!("tfrecord-", env!("CARGO_PKG_VERSION"));
+
+/// The suffix appended to output file names to name their temporary files.
+const TEMP_SUFFIX: &str = ".upgrading";
+
+/// The function called with the temporary output before verification.
+pub type BeforeVerifyFn = dyn Fn(&Path) -> Result<()> + Send + Sync;
+
+/// The conversion of payloads by an upgrade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum UpgradeMode {
+    /// Copy the payloads as is.
+    #[default]
+    Passthrough,
+    /// Re-encode examples with sorted features.
+    Reencode,
+}
+
+/// Options for [upgrade_shard].
+#[derive(Clone)]
+pub struct UpgradeOptions {
+    pub mode: UpgradeMode,
+    /// If set, nothing is written, and the report tells what would change.
+    pub dry_run: bool,
+    /// The value of [STAMP_KEY] in the metadata of outputs.
+    pub stamp: String,
+    /// The configuration to read the input and the output.
+    pub reader: RecordReaderConfig,
+    /// Called with the temporary output before verification, for example to audit it.
+    /// An error aborts the upgrade.
+    pub before_verify: Option<Arc<BeforeVerifyFn>>,
+}
+
+impl Default for UpgradeOptions {
+    fn default() -> Self {
+        Self {
+            mode: UpgradeMode::default(),
+            dry_run: false,
+            stamp: DEFAULT_STAMP.to_string(),
+            reader: RecordReaderConfig::default(),
+            before_verify: None,
+        }
+    }
+}
+
+impl fmt::Debug for UpgradeOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UpgradeOptions")
+            .field("mode", &self.mode)
+            .field("dry_run", &self.dry_run)
+            .field("stamp", &self.stamp)
+            .field("reader", &self.reader)
+            .field("before_verify", &self.before_verify.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+/// The changes made, or to be made in a dry run, by an upgrade.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpgradeReport {
+    pub num_records: u64,
+    /// The number of records whose payloads change.
+    pub num_reencoded: u64,
+    /// The metadata of the input, if any.
+    pub metadata_before: Option<ShardMetadata>,
+    /// The metadata of the output.
+    pub metadata_after: ShardMetadata,
+    /// Whether the upgrade was a dry run.
+    pub dry_run: bool,
+}
+
+/// The outcome of a file upgraded by [upgrade_manifest].
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum UpgradeOutcome {
+    Upgraded(UpgradeReport),
+    /// The output was stamped by an earlier upgrade.
+    Skipped,
+    /// The upgrade failed, and the input is kept.
+    Failed(Arc<Error>),
+}
+
+/// The report returned by [upgrade_manifest].
+#[derive(Debug, Clone)]
+#[must_use = "the report may contain failed files"]
+pub struct BatchUpgradeReport {
+    /// The input paths and their outcomes in the order of the manifest.
+    pub files: Vec<(PathBuf, UpgradeOutcome)>,
+}
+
+impl BatchUpgradeReport {
+    /// Returns true if no file failed.
+    pub fn is_success(&self) -> bool {
+        self.files
+            .iter()
+            .all(|(_, outcome)| !matches!(outcome, UpgradeOutcome::Failed(_)))
+    }
+}
+
+/// Returns true if the file exists and its metadata holds the stamp.
+pub fn is_upgraded<P>(path: P, stamp: &str) -> bool
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    path.is_file()
+        && matches!(
+            ShardMetadata::read_for(path),
+            Ok(Some(metadata)) if metadata.extra.get(STAMP_KEY).map(String::as_str) == Some(stamp)
+        )
+}
+
+/// Upgrade a shard to the output path, which may be the input path.
+///
+/// See the [module](self) documentation for the steps. The output is replaced only if
+/// the verification passes. A mismatch fails with [Error::VerificationFailed].
+pub fn upgrade_shard<P, Q>(input: P, output: Q, options: UpgradeOptions) -> Result<UpgradeReport>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let input = input.as_ref();
+    let output = output.as_ref();
+    let UpgradeOptions {
+        mode,
+        dry_run,
+        stamp,
+        reader,
+        before_verify,
+    } = options;
+
+    let metadata_before = ShardMetadata::read_for(input)?;
+    let mut metadata_after = metadata_before.clone().unwrap_or_default();
+    if mode == UpgradeMode::Reencode {
+        metadata_after.sorted_features = true;
+    }
+    metadata_after.extra.insert(STAMP_KEY.to_string(), stamp);
+
+    let temp = temp_path(output);
+    let result = (|| {
+        let mut writer = if dry_run {
+            None
+        } else {
+            Some(BytesWriter::create(&temp)?)
+        };
+        let mut num_records = 0;
+        let mut num_reencoded = 0;
+        for bytes in BytesIter::open(input, reader.clone())? {
+            let bytes = bytes?;
+            let upgraded = upgrade_record(mode, &bytes)?;
+            if upgraded != bytes {
+                num_reencoded += 1;
+            }
+            if let Some(writer) = &mut writer {
+                writer.send(upgraded)?;
+            }
+            num_records += 1;
+        }
+        let report = UpgradeReport {
+            num_records,
+            num_reencoded,
+            metadata_before,
+            metadata_after,
+            dry_run,
+        };
+        let writer = match writer {
+            Some(writer) => writer,
+            None => return Ok(report),
+        };
+
+        let file = writer
+            .into_inner()
+            .into_inner()
+            .map_err(|err| err.into_error())?;
+        file.sync_all()?;
+        drop(file);
+        report.metadata_after.write_for(&temp)?;
+        File::open(metadata_path(&temp))?.sync_all()?;
+
+        if let Some(before_verify) = &before_verify {
+            before_verify(&temp)?;
+        }
+        verify(input, &temp, mode, reader)?;
+
+        // the shard is renamed before its metadata, so that an interruption in between
+        // leaves an unstamped output to be upgraded again
+        fs::rename(&temp, output)?;
+        fs::rename(metadata_path(&temp), metadata_path(output))?;
+        let dir = match output.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        utils::sync_dir(dir)?;
+        Ok(report)
+    })();
+
+    if result.is_err() {
+        // the original error is more relevant than a failure to clean up
+        let _ = remove_if_exists(&temp);
+        let _ = remove_if_exists(&metadata_path(&temp));
+    }
+    result
+}
+
+/// Upgrade the files listed in a manifest, placing the outputs under the output
+/// directory at the same paths relative to the manifest directory.
+///
+/// Outputs [stamped](is_upgraded) with the stamp of the options are skipped. A failed
+/// file does not stop the upgrade of the other files. The output directory may be the
+/// manifest directory to upgrade in place.
+pub fn upgrade_manifest<P, Q>(
+    manifest_path: P,
+    output_dir: Q,
+    options: UpgradeOptions,
+) -> Result<BatchUpgradeReport>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let manifest_path = manifest_path.as_ref();
+    let output_dir = output_dir.as_ref();
+    let manifest = Manifest::read_from(manifest_path)?;
+    let manifest_dir = manifest_path.parent().unwrap_or_else(|| Path::new(""));
+
+    let files = manifest
+        .files
+        .into_iter()
+        .map(|file| {
+            let input = file.path;
+            let relative = input.strip_prefix(manifest_dir).unwrap_or(&input);
+            let output = output_dir.join(relative);
+
+            let outcome = if !options.dry_run && is_upgraded(&output, &options.stamp) {
+                UpgradeOutcome::Skipped
+            } else {
+                let result = match output.parent() {
+                    Some(dir) => fs::create_dir_all(dir).map_err(Error::from),
+                    None => Ok(()),
+                }
+                .and_then(|()| upgrade_shard(&input, &output, options.clone()));
+                match result {
+                    Ok(report) => UpgradeOutcome::Upgraded(report),
+                    Err(error) => UpgradeOutcome::Failed(Arc::new(error)),
+                }
+            };
+            (input, outcome)
+        })
+        .collect();
+    Ok(BatchUpgradeReport { files })
+}
+
+fn upgrade_record(mode: UpgradeMode, bytes: &[u8]) -> Result<Vec<u8>> {
+    Ok(match mode {
+        UpgradeMode::Passthrough => bytes.to_vec(),
+        UpgradeMode::Reencode => Example::to_bytes_canonical(Example::from_slice(bytes)?)?,
+    })
+}
+
+/// Compare the output with the input record by record.
+fn verify(
+    input: &Path,
+    output: &Path,
+    mode: UpgradeMode,
+    reader: RecordReaderConfig,
+) -> Result<()> {
+    let failed = |index: u64, reason: Cow<'static, str>| Error::VerificationFailed {
+        path: output.to_owned(),
+        index,
+        reason,
+    };
+
+    let mut expected = BytesIter::open(input, reader.clone())?;
+    let mut found = BytesIter::open(output, reader)?;
+    let mut index = 0;
+    loop {
+        let (expected, found) = match (expected.next().transpose()?, found.next().transpose()?) {
+            (None, None) => return Ok(()),
+            (Some(_), None) => return Err(failed(index, "the output has fewer records".into())),
+            (None, Some(_)) => return Err(failed(index, "the output has more records".into())),
+            (Some(expected), Some(found)) => (expected, found),
+        };
+        match mode {
+            UpgradeMode::Passthrough => {
+                if expected != found {
+                    return Err(failed(index, "the payloads differ".into()));
+                }
+            }
+            UpgradeMode::Reencode => {
+                let expected = Example::from_bytes(expected)?;
+                let found = Example::from_bytes(found)
+                    .map_err(|err| failed(index, err.to_string().into()))?;
+                if !expected.eq_with(&found, FloatIdentity::Bitwise) {
+                    return Err(failed(index, "the examples differ".into()));
+                }
+            }
+        }
+        index += 1;
+    }
+}
+
+fn temp_path(output: &Path) -> PathBuf {
+    let mut file_name: OsString = output.file_name().unwrap_or_default().into();
+    file_name.push(TEMP_SUFFIX);
+    output.with_file_name(file_name)
+}
+
+fn remove_if_exists(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
@@ -0,0 +1,350 @@
+//! Mix several datasets into one stream by sampling weights.
+//!
+//! [weighted] builds a [MixIter] over sources, each a list of files with a weight. At
+//! each step, the iterator picks a source with probability proportional to its weight
+//! among the sources still active, and yields the next record of that source along
+//! with the source index. The picks are determined by the seed, so a mix is
+//! reproducible.
+//!
+//! The [Exhaustion] policy decides what happens when a picked source has no more
+//! records. Every source reads one file at a time through a buffered reader, and at
+//! most [max_open_files](MixOptions::max_open_files) files are open across sources.
+//! Beyond the budget, the least recently read source closes its file and reopens it at
+//! the same offset when it is picked again.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{
+//!     mix::{self, Exhaustion, MixIter, MixOptions},
+//!     samples, Example,
+//! };
+//!
+//! let task_a = samples::tiny_dataset(2, 50)?;
+//! let task_b = samples::tiny_dataset(1, 10)?;
+//! let options = MixOptions {
+//!     exhaustion: Exhaustion::Repeat { reshuffle: true },
+//!     ..Default::default()
+//! };
+//! let mix: MixIter<Example> =
+//!     mix::weighted(vec![(task_a.paths(), 0.8), (task_b.paths(), 0.2)], 7, options)?;
+//! for mixed in mix.take(100) {
+//!     let mixed = mixed?;
+//!     let _weight = if mixed.source == 0 { 1.0 } else { 2.0 };
+//!     let _example = mixed.record;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::{ensure_argument, Error, Result},
+    record::Record,
+    record_reader::{BytesIter, RecordReaderConfig},
+    utils::SplitMix64,
+};
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{BufReader, Seek, SeekFrom},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+/// The handling of sources running out of records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Exhaustion {
+    /// End the stream when a picked source is exhausted.
+    StopWhenAnyExhausted,
+    /// Drop exhausted sources, renormalizing the weights of the others, and end the
+    /// stream when all sources are exhausted.
+    #[default]
+    StopWhenAllExhausted,
+    /// Restart exhausted sources from their first file, so the stream never ends. If
+    /// `reshuffle` is set, the order of the files of a source is shuffled on every
+    /// restart. Records within files keep their order.
+    ///
+    /// A source without records fails the stream when it is restarted.
+    Repeat { reshuffle: bool },
+}
+
+/// Options for [weighted].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MixOptions {
+    pub exhaustion: Exhaustion,
+    /// The maximum number of files open at a time across sources. It must be positive and
+    /// within [max_open_files](crate::Limits::max_open_files) of the reader limits.
+    pub max_open_files: usize,
+    /// The configuration of the readers of sources.
+    pub reader: RecordReaderConfig,
+}
+
+impl Default for MixOptions {
+    fn default() -> Self {
+        Self {
+            exhaustion: Exhaustion::default(),
+            max_open_files: 16,
+            reader: RecordReaderConfig::default(),
+        }
+    }
+}
+
+/// A record yielded by [MixIter] with the index of its source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mixed<T> {
+    /// The index of the source in the order given to [weighted].
+    pub source: usize,
+    pub record: T,
+}
+
+/// Mix sources of files by weights.
+///
+/// The weights must be finite and non-negative, and at least one must be positive.
+/// Sources of zero weight are never picked. The files of a source are read in the
+/// given order.
+pub fn weighted<'a, T, I, S, P>(sources: I, seed: u64, options: MixOptions) -> Result<MixIter<T>>
+where
+    T: Record,
+    I: IntoIterator<Item = (S, f64)>,
+    S: IntoIterator<Item = P>,
+    P: Into<Cow<'a, Path>>,
+{
+    let MixOptions {
+        exhaustion,
+        max_open_files,
+        reader,
+    } = options;
+    ensure_argument!(max_open_files > 0, "max_open_files must be positive");
+    reader.limits.check_open_files(max_open_files)?;
+
+    let sources: Vec<Source> = sources
+        .into_iter()
+        .map(|(paths, weight)| Source {
+            paths: paths
+                .into_iter()
+                .map(|path| path.into().into_owned())
+                .collect(),
+            weight,
+            file_index: 0,
+            offset: 0,
+            records: None,
+            last_read: 0,
+            records_in_pass: 0,
+            exhausted: false,
+        })
+        .collect();
+    for (index, source) in sources.iter().enumerate() {
+        ensure_argument!(
+            source.weight.is_finite() && source.weight >= 0.0,
+            "the weight of source {} must be finite and non-negative, but get {}",
+            index,
+            source.weight
+        );
+    }
+    ensure_argument!(
+        sources.iter().any(|source| source.weight > 0.0),
+        "at least one weight must be positive"
+    );
+
+    Ok(MixIter {
+        sources,
+        rng: SplitMix64(seed),
+        exhaustion,
+        max_open_files,
+        reader,
+        num_reads: 0,
+        done: false,
+        _phantom: PhantomData,
+    })
+}
+
+/// A source of a [MixIter].
+struct Source {
+    paths: Vec<PathBuf>,
+    weight: f64,
+    /// The index of the current file.
+    file_index: usize,
+    /// The offset of the next record in the current file.
+    offset: u64,
+    /// The reader of the current file, if open.
+    records: Option<BytesIter<BufReader<File>>>,
+    /// The read count of the mix at the last read, to close the least recently read
+    /// file.
+    last_read: u64,
+    /// The number of records read since the last restart.
+    records_in_pass: u64,
+    exhausted: bool,
+}
+
+/// Iterator of records mixed from several sources.
+///
+/// See the [module](self) documentation.
+pub struct MixIter<T>
+where
+    T: Record,
+{
+    sources: Vec<Source>,
+    rng: SplitMix64,
+    exhaustion: Exhaustion,
+    max_open_files: usize,
+    reader: RecordReaderConfig,
+    num_reads: u64,
+    done: bool,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> MixIter<T>
+where
+    T: Record,
+{
+    /// Drop the source indexes of the yielded records.
+    pub fn records(self) -> impl Iterator<Item = Result<T>> {
+        self.map(|mixed| mixed.map(|mixed| mixed.record))
+    }
+
+    /// Pick an active source by weight, or `None` if no source is active.
+    fn pick(&mut self) -> Option<usize> {
+        let active = |source: &Source| !source.exhausted && source.weight > 0.0;
+        let total: f64 = self
+            .sources
+            .iter()
+            .filter(|source| active(source))
+            .map(|source| source.weight)
+            .sum();
+        if total == 0.0 {
+            return None;
+        }
+        let mut target = self.rng.next_f64() * total;
+        let mut last = None;
+        for (index, source) in self.sources.iter().enumerate() {
+            if !active(source) {
+                continue;
+            }
+            if target < source.weight {
+                return Some(index);
+            }
+            target -= source.weight;
+            last = Some(index);
+        }
+        // rounding errors may leave a tiny remainder
+        last
+    }
+
+    /// Read the next record of the source, or `None` if the source is exhausted.
+    fn read(&mut self, index: usize) -> Result<Option<Vec<u8>>> {
+        loop {
+            let source = &self.sources[index];
+            if source.file_index >= source.paths.len() {
+                return Ok(None);
+            }
+            if source.records.is_none() {
+                self.open(index)?;
+            }
+
+            self.num_reads += 1;
+            let source = &mut self.sources[index];
+            source.last_read = self.num_reads;
+            let path = &source.paths[source.file_index];
+            match source.records.as_mut().unwrap().next() {
+                Some(bytes) => {
+                    let bytes =
+                        bytes.map_err(|err| err.with_io_context(path, Some(source.offset)))?;
+                    source.offset += bytes.len() as u64 + 16;
+                    source.records_in_pass += 1;
+                    return Ok(Some(bytes));
+                }
+                None => {
+                    source.records = None;
+                    source.file_index += 1;
+                    source.offset = 0;
+                }
+            }
+        }
+    }
+
+    /// Open the current file of the source at its offset, closing the least recently
+    /// read file if the budget is used up.
+    fn open(&mut self, index: usize) -> Result<()> {
+        let num_open = self
+            .sources
+            .iter()
+            .filter(|source| source.records.is_some())
+            .count();
+        if num_open >= self.max_open_files {
+            if let Some(lru) = self
+                .sources
+                .iter_mut()
+                .filter(|source| source.records.is_some())
+                .min_by_key(|source| source.last_read)
+            {
+                lru.records = None;
+            }
+        }
+
+        let source = &mut self.sources[index];
+        let path = &source.paths[source.file_index];
+        let with_path = |err| Error::from_io_with_context(err, path, None);
+        let mut file = File::open(path).map_err(with_path)?;
+        if source.offset > 0 {
+            file.seek(SeekFrom::Start(source.offset))
+                .map_err(with_path)?;
+        }
+        source.records = Some(BytesIter::from_reader(
+            BufReader::new(file),
+            self.reader.clone(),
+        ));
+        Ok(())
+    }
+
+    fn next_mixed(&mut self) -> Result<Option<Mixed<T>>> {
+        loop {
+            let index = match self.pick() {
+                Some(index) => index,
+                None => return Ok(None),
+            };
+            if let Some(bytes) = self.read(index)? {
+                let record = T::from_bytes_with_limits(bytes, &self.reader.limits)?;
+                return Ok(Some(Mixed {
+                    source: index,
+                    record,
+                }));
+            }
+
+            let source = &mut self.sources[index];
+            match self.exhaustion {
+                Exhaustion::StopWhenAnyExhausted => return Ok(None),
+                Exhaustion::StopWhenAllExhausted => source.exhausted = true,
+                Exhaustion::Repeat { reshuffle } => {
+                    ensure_argument!(
+                        source.records_in_pass > 0,
+                        "source {} has no records to repeat",
+                        index
+                    );
+                    source.file_index = 0;
+                    source.offset = 0;
+                    source.records_in_pass = 0;
+                    if reshuffle {
+                        self.rng.shuffle(&mut source.paths);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<T> Iterator for MixIter<T>
+where
+    T: Record,
+{
+    type Item = Result<Mixed<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.next_mixed().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.done = true;
+        }
+        result
+    }
+}
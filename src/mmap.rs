@@ -0,0 +1,313 @@
+//! Memory-mapped file output with preallocation.
+//!
+//! [RecordWriter::create_mmap](crate::RecordWriter::create_mmap) writes records into a
+//! shared memory mapping of the output file instead of going through write syscalls.
+//! The file is preallocated to the [capacity](MmapConfig::capacity) up front, which
+//! avoids fragmentation when the size of a shard is known in advance. If the records
+//! exceed the capacity, the file and the mapping grow by the
+//! [growth](MmapConfig::growth) increment. The file is truncated to the exact number
+//! of written bytes on [finish](MmapFile::finish) or drop, so the output is
+//! byte-identical to the one of [RecordWriter::create](crate::RecordWriter::create).
+//!
+//! Until then, the file is longer than the written records and the trailing bytes are
+//! zeros, so readers must not open it before the writer is finished.
+//!
+//! The mapping is supported on Unix. On other platforms, or if the file system refuses
+//! the mapping, the writer falls back to a buffered file transparently, which
+//! [is_mapped](MmapFile::is_mapped) reports.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{mmap::MmapConfig, samples, BytesIter, BytesWriter};
+//!
+//! let dataset = samples::tiny_dataset(0, 0)?;
+//! let path = dataset.dir().join("shard.tfrecord");
+//!
+//! let config = MmapConfig {
+//!     capacity: 1 << 20,
+//!     ..Default::default()
+//! };
+//! let mut writer = BytesWriter::create_mmap(&path, Default::default(), config)?;
+//! writer.send(b"record".to_vec())?;
+//! writer.finish()?;
+//!
+//! assert_eq!(std::fs::metadata(&path)?.len(), 6 + 16);
+//! assert_eq!(BytesIter::open(&path, Default::default())?.count(), 1);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{ensure_argument, Result};
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// The default capacity and growth increment, 64 MiB.
+pub const DEFAULT_GROWTH: u64 = 64 << 20;
+
+/// Configuration for [MmapFile].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MmapConfig {
+    /// The number of bytes preallocated on creation, which is usually the estimated
+    /// size of the output.
+    pub capacity: u64,
+    /// The number of bytes the file grows by each time the capacity is exceeded.
+    pub growth: u64,
+}
+
+impl Default for MmapConfig {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_GROWTH,
+            growth: DEFAULT_GROWTH,
+        }
+    }
+}
+
+/// A file written through a memory mapping, or a buffered file as the fallback.
+pub struct MmapFile {
+    backend: Backend,
+}
+
+enum Backend {
+    #[cfg(unix)]
+    Mapped(unix::Mapping),
+    Buffered(BufWriter<File>),
+}
+
+impl MmapFile {
+    /// Create a new file preallocated to the capacity.
+    pub fn create<P>(path: P, config: MmapConfig) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let MmapConfig { capacity, growth } = config;
+        ensure_argument!(growth > 0, "the growth of the mapping must be positive");
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+
+        #[cfg(unix)]
+        let file = match unix::Mapping::new(file, capacity.max(1), growth) {
+            Ok(mapping) => {
+                return Ok(Self {
+                    backend: Backend::Mapped(mapping),
+                })
+            }
+            Err((_, file)) => {
+                // undo the preallocation before falling back
+                file.set_len(0)?;
+                file
+            }
+        };
+        #[cfg(not(unix))]
+        let _ = capacity;
+
+        Ok(Self {
+            backend: Backend::Buffered(BufWriter::new(file)),
+        })
+    }
+
+    /// Returns true if the file is written through a mapping, or false if it falls
+    /// back to a buffered file.
+    pub fn is_mapped(&self) -> bool {
+        match self.backend {
+            #[cfg(unix)]
+            Backend::Mapped(_) => true,
+            Backend::Buffered(_) => false,
+        }
+    }
+
+    /// Unmap and truncate the file to the written bytes.
+    ///
+    /// Dropping the file does the same, but ignores errors.
+    pub fn finish(mut self) -> Result<()> {
+        match &mut self.backend {
+            #[cfg(unix)]
+            Backend::Mapped(mapping) => mapping.finish()?,
+            Backend::Buffered(writer) => writer.flush()?,
+        }
+        Ok(())
+    }
+}
+
+impl Write for MmapFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.backend {
+            #[cfg(unix)]
+            Backend::Mapped(mapping) => {
+                mapping.write_all(buf)?;
+                Ok(buf.len())
+            }
+            Backend::Buffered(writer) => writer.write(buf),
+        }
+    }
+
+    /// Schedule the write-back of the mapped pages, or flush the buffered file.
+    ///
+    /// Like flushing a buffered file, it hands the bytes to the operating system
+    /// without waiting for them to reach the disk.
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.backend {
+            #[cfg(unix)]
+            Backend::Mapped(mapping) => mapping.flush(),
+            Backend::Buffered(writer) => writer.flush(),
+        }
+    }
+}
+
+impl fmt::Debug for MmapFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.backend {
+            #[cfg(unix)]
+            Backend::Mapped(mapping) => f
+                .debug_struct("MmapFile")
+                .field("len", &mapping.len)
+                .field("capacity", &mapping.capacity)
+                .finish(),
+            Backend::Buffered(_) => f.debug_struct("MmapFile").field("mapped", &false).finish(),
+        }
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::{fs::File, io, os::unix::io::AsRawFd as _, ptr};
+
+    /// A file and its shared mapping.
+    pub(super) struct Mapping {
+        file: File,
+        /// The start of the mapping, or null after unmapping.
+        ptr: *mut u8,
+        pub(super) capacity: u64,
+        pub(super) len: u64,
+        growth: u64,
+        finished: bool,
+    }
+
+    // SAFETY: the mapping is exclusively owned and only accessed through `&mut self`.
+    unsafe impl Send for Mapping {}
+
+    impl Mapping {
+        pub(super) fn new(
+            file: File,
+            capacity: u64,
+            growth: u64,
+        ) -> Result<Self, (io::Error, File)> {
+            let ptr = match allocate(&file, capacity).and_then(|()| map(&file, capacity)) {
+                Ok(ptr) => ptr,
+                Err(err) => return Err((err, file)),
+            };
+            Ok(Self {
+                file,
+                ptr,
+                capacity,
+                len: 0,
+                growth,
+                finished: false,
+            })
+        }
+
+        pub(super) fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+            let end = self.len + buf.len() as u64;
+            if end > self.capacity || self.ptr.is_null() {
+                self.grow(end)?;
+            }
+            // SAFETY: the mapping covers `capacity` bytes, which is at least `end`.
+            unsafe {
+                ptr::copy_nonoverlapping(buf.as_ptr(), self.ptr.add(self.len as usize), buf.len());
+            }
+            self.len = end;
+            Ok(())
+        }
+
+        pub(super) fn flush(&mut self) -> io::Result<()> {
+            if self.ptr.is_null() || self.len == 0 {
+                return Ok(());
+            }
+            // SAFETY: the range is within the mapping.
+            let ret = unsafe { libc::msync(self.ptr.cast(), self.len as usize, libc::MS_ASYNC) };
+            if ret != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        }
+
+        pub(super) fn finish(&mut self) -> io::Result<()> {
+            self.finished = true;
+            self.unmap()?;
+            self.file.set_len(self.len)
+        }
+
+        /// Grow the file and the mapping to cover `end` bytes by whole increments.
+        fn grow(&mut self, end: u64) -> io::Result<()> {
+            let num_increments = (end.saturating_sub(self.capacity)).div_ceil(self.growth);
+            let capacity = self.capacity + num_increments * self.growth;
+            self.unmap()?;
+            allocate(&self.file, capacity)?;
+            self.ptr = map(&self.file, capacity)?;
+            self.capacity = capacity;
+            Ok(())
+        }
+
+        fn unmap(&mut self) -> io::Result<()> {
+            if self.ptr.is_null() {
+                return Ok(());
+            }
+            // SAFETY: the pointer and the length are the ones returned by mmap.
+            let ret = unsafe { libc::munmap(self.ptr.cast(), self.capacity as usize) };
+            self.ptr = ptr::null_mut();
+            if ret != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        }
+    }
+
+    impl Drop for Mapping {
+        fn drop(&mut self) {
+            if !self.finished {
+                let _ = self.finish();
+            }
+        }
+    }
+
+    /// Extend the file to `len` bytes, reserving the blocks where supported.
+    fn allocate(file: &File, len: u64) -> io::Result<()> {
+        #[cfg(target_os = "linux")]
+        {
+            // SAFETY: the descriptor is valid for the lifetime of the file.
+            let ret = unsafe { libc::posix_fallocate(file.as_raw_fd(), 0, len as libc::off_t) };
+            if ret == 0 {
+                return Ok(());
+            }
+        }
+        // a sparse extension on file systems without block reservation
+        file.set_len(len)
+    }
+
+    fn map(file: &File, len: u64) -> io::Result<*mut u8> {
+        // SAFETY: the file is open for reading and writing, and is at least `len` bytes.
+        let ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len as usize,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(ptr.cast())
+    }
+}
@@ -0,0 +1,625 @@
+//! Render messages in the ProtocolBuffer text format.
+//!
+//! The text format (pbtxt) is the human-readable form used by TensorFlow tooling,
+//! which makes fixture changes reviewable as plain text diffs. The output is accepted
+//! by `text_format.Parse` in Python and follows the conventions of its printer.
+//!
+//! - Fields are printed in field number order, one per line, indented by 2 spaces per level.
+//! - Scalar fields with default values are omitted, except for members of `oneof`s.
+//! - Map entries, such as the features of an example, are sorted by key.
+//! - Strings and bytes are escaped as C string literals. Non-ASCII bytes are printed
+//!   as octal escapes, so the output is always ASCII.
+//! - Floating point numbers are printed in the shortest form that round-trips.
+//!
+//! Parsing the text format is not supported.
+//!
+//! ```rust
+//! use tfrecord::{pbtxt::ToPbtxt, prelude::*};
+//!
+//! let example: Example = vec![("label".into(), Feature::from_i64_list(vec![1]))]
+//!     .into_iter()
+//!     .collect();
+//!
+//! let expect = r#"features {
+//!   feature {
+//!     key: "label"
+//!     value {
+//!       int64_list {
+//!         value: 1
+//!       }
+//!     }
+//!   }
+//! }
+//! "#;
+//! assert_eq!(example.to_pbtxt(), expect);
+//! ```
+
+#[cfg(feature = "proto-summary")]
+use crate::protobuf::{
+    event::What,
+    summary::{value::Value as SummaryValueKind, Audio, Image, Value as SummaryValue},
+    summary_metadata::PluginData,
+    DataClass, Event, HistogramProto, LogMessage, SessionLog, Summary, SummaryMetadata,
+    TaggedRunMetadata,
+};
+use crate::{
+    error::Result,
+    protobuf::{
+        feature::Kind, resource_handle_proto::DtypeAndShape, tensor_shape_proto::Dim, BytesList,
+        DataType, Example, Feature, FeatureList, FeatureLists, Features, FloatList, Int64List,
+        ResourceHandleProto, SequenceExample, TensorProto, TensorShapeProto,
+        VariantTensorDataProto,
+    },
+};
+use std::{
+    fmt::{self, Write as _},
+    io::{self, Write},
+};
+
+/// Messages printable in the text format.
+pub trait ToPbtxt {
+    /// Write the text format of the message to the writer.
+    ///
+    /// The message is printed while it is traversed, so huge messages are not
+    /// buffered in memory.
+    fn write_pbtxt<W>(&self, writer: W) -> Result<()>
+    where
+        W: Write;
+
+    /// Render the message in the text format.
+    fn to_pbtxt(&self) -> String {
+        let mut buf = vec![];
+        self.write_pbtxt(&mut buf)
+            .expect("writing to a Vec never fails");
+        String::from_utf8(buf).expect("the text format is always ASCII")
+    }
+}
+
+macro_rules! impl_to_pbtxt {
+    ($($ty:ty => $method:ident),* $(,)?) => {
+        $(
+            impl ToPbtxt for $ty {
+                fn write_pbtxt<W>(&self, writer: W) -> Result<()>
+                where
+                    W: Write,
+                {
+                    Printer::new(writer).$method(self)?;
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+impl_to_pbtxt! {
+    Example => example,
+    SequenceExample => sequence_example,
+    Features => features,
+    Feature => feature,
+    FeatureList => feature_list,
+    FeatureLists => feature_lists,
+    TensorProto => tensor,
+    TensorShapeProto => tensor_shape,
+}
+
+#[cfg(feature = "proto-summary")]
+impl_to_pbtxt! {
+    Summary => summary,
+    Event => event,
+    HistogramProto => histogram,
+}
+
+/// Write the text format of an example to the writer.
+///
+/// It is equivalent to [ToPbtxt::write_pbtxt] on the example.
+pub fn write_example<W>(writer: W, example: &Example) -> Result<()>
+where
+    W: Write,
+{
+    example.write_pbtxt(writer)
+}
+
+/// The printer tracking the indentation of nested messages.
+struct Printer<W> {
+    writer: W,
+    indent: usize,
+}
+
+impl<W> Printer<W>
+where
+    W: Write,
+{
+    fn new(writer: W) -> Self {
+        Self { writer, indent: 0 }
+    }
+
+    // primitives
+
+    fn line(&mut self, name: &str, value: impl fmt::Display) -> io::Result<()> {
+        writeln!(
+            self.writer,
+            "{:indent$}{}: {}",
+            "",
+            name,
+            value,
+            indent = self.indent
+        )
+    }
+
+    fn nested<F>(&mut self, name: &str, print_fields: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut Self) -> io::Result<()>,
+    {
+        writeln!(
+            self.writer,
+            "{:indent$}{} {{",
+            "",
+            name,
+            indent = self.indent
+        )?;
+        self.indent += 2;
+        print_fields(self)?;
+        self.indent -= 2;
+        writeln!(self.writer, "{:indent$}}}", "", indent = self.indent)
+    }
+
+    fn bytes(&mut self, name: &str, value: &[u8]) -> io::Result<()> {
+        self.line(name, Escaped(value))
+    }
+
+    fn string(&mut self, name: &str, value: &str) -> io::Result<()> {
+        self.bytes(name, value.as_bytes())
+    }
+
+    fn float(&mut self, name: &str, value: f32) -> io::Result<()> {
+        self.line(name, format_float(value as f64, format!("{:e}", value)))
+    }
+
+    fn double(&mut self, name: &str, value: f64) -> io::Result<()> {
+        self.line(name, format_float(value, format!("{:e}", value)))
+    }
+
+    // proto3 scalars omitted if set to defaults
+
+    fn opt_bytes(&mut self, name: &str, value: &[u8]) -> io::Result<()> {
+        if value.is_empty() {
+            return Ok(());
+        }
+        self.bytes(name, value)
+    }
+
+    fn opt_string(&mut self, name: &str, value: &str) -> io::Result<()> {
+        self.opt_bytes(name, value.as_bytes())
+    }
+
+    fn opt_int<T>(&mut self, name: &str, value: T) -> io::Result<()>
+    where
+        T: fmt::Display + Default + PartialEq,
+    {
+        if value == T::default() {
+            return Ok(());
+        }
+        self.line(name, value)
+    }
+
+    fn opt_bool(&mut self, name: &str, value: bool) -> io::Result<()> {
+        if !value {
+            return Ok(());
+        }
+        self.line(name, value)
+    }
+
+    // only summaries and events have optional floating point fields
+    #[cfg(feature = "proto-summary")]
+    fn opt_float(&mut self, name: &str, value: f32) -> io::Result<()> {
+        // negative zeros are distinguished from defaults as in the C++ implementation
+        if value.to_bits() == 0 {
+            return Ok(());
+        }
+        self.float(name, value)
+    }
+
+    #[cfg(feature = "proto-summary")]
+    fn opt_double(&mut self, name: &str, value: f64) -> io::Result<()> {
+        if value.to_bits() == 0 {
+            return Ok(());
+        }
+        self.double(name, value)
+    }
+
+    fn opt_enum(&mut self, name: &str, value: i32, enum_name: Option<String>) -> io::Result<()> {
+        if value == 0 {
+            return Ok(());
+        }
+        match enum_name {
+            Some(enum_name) => self.line(name, enum_name),
+            None => self.line(name, value),
+        }
+    }
+
+    // example types
+
+    fn example(&mut self, example: &Example) -> io::Result<()> {
+        if let Some(features) = &example.features {
+            self.nested("features", |p| p.features(features))?;
+        }
+        Ok(())
+    }
+
+    fn sequence_example(&mut self, example: &SequenceExample) -> io::Result<()> {
+        if let Some(context) = &example.context {
+            self.nested("context", |p| p.features(context))?;
+        }
+        if let Some(feature_lists) = &example.feature_lists {
+            self.nested("feature_lists", |p| p.feature_lists(feature_lists))?;
+        }
+        Ok(())
+    }
+
+    fn features(&mut self, features: &Features) -> io::Result<()> {
+        let mut entries: Vec<_> = features.feature.iter().collect();
+        entries.sort_unstable_by_key(|(key, _)| *key);
+        for (key, feature) in entries {
+            self.nested("feature", |p| {
+                p.string("key", key)?;
+                p.nested("value", |p| p.feature(feature))
+            })?;
+        }
+        Ok(())
+    }
+
+    fn feature_lists(&mut self, feature_lists: &FeatureLists) -> io::Result<()> {
+        let mut entries: Vec<_> = feature_lists.feature_list.iter().collect();
+        entries.sort_unstable_by_key(|(key, _)| *key);
+        for (key, feature_list) in entries {
+            self.nested("feature_list", |p| {
+                p.string("key", key)?;
+                p.nested("value", |p| p.feature_list(feature_list))
+            })?;
+        }
+        Ok(())
+    }
+
+    fn feature_list(&mut self, feature_list: &FeatureList) -> io::Result<()> {
+        for feature in &feature_list.feature {
+            self.nested("feature", |p| p.feature(feature))?;
+        }
+        Ok(())
+    }
+
+    fn feature(&mut self, feature: &Feature) -> io::Result<()> {
+        match &feature.kind {
+            Some(Kind::BytesList(BytesList { value })) => self.nested("bytes_list", |p| {
+                value.iter().try_for_each(|value| p.bytes("value", value))
+            }),
+            Some(Kind::FloatList(FloatList { value })) => self.nested("float_list", |p| {
+                value.iter().try_for_each(|&value| p.float("value", value))
+            }),
+            Some(Kind::Int64List(Int64List { value })) => self.nested("int64_list", |p| {
+                value.iter().try_for_each(|value| p.line("value", value))
+            }),
+            None => Ok(()),
+        }
+    }
+
+    // tensor types
+
+    fn tensor(&mut self, tensor: &TensorProto) -> io::Result<()> {
+        self.dtype("dtype", tensor.dtype)?;
+        if let Some(shape) = &tensor.tensor_shape {
+            self.nested("tensor_shape", |p| p.tensor_shape(shape))?;
+        }
+        self.opt_int("version_number", tensor.version_number)?;
+        self.opt_bytes("tensor_content", &tensor.tensor_content)?;
+        for &value in &tensor.float_val {
+            self.float("float_val", value)?;
+        }
+        for &value in &tensor.double_val {
+            self.double("double_val", value)?;
+        }
+        for value in &tensor.int_val {
+            self.line("int_val", value)?;
+        }
+        for value in &tensor.string_val {
+            self.bytes("string_val", value)?;
+        }
+        for &value in &tensor.scomplex_val {
+            self.float("scomplex_val", value)?;
+        }
+        for value in &tensor.int64_val {
+            self.line("int64_val", value)?;
+        }
+        for value in &tensor.bool_val {
+            self.line("bool_val", value)?;
+        }
+        for &value in &tensor.dcomplex_val {
+            self.double("dcomplex_val", value)?;
+        }
+        for value in &tensor.half_val {
+            self.line("half_val", value)?;
+        }
+        for handle in &tensor.resource_handle_val {
+            self.nested("resource_handle_val", |p| p.resource_handle(handle))?;
+        }
+        for variant in &tensor.variant_val {
+            self.nested("variant_val", |p| p.variant(variant))?;
+        }
+        for value in &tensor.uint32_val {
+            self.line("uint32_val", value)?;
+        }
+        for value in &tensor.uint64_val {
+            self.line("uint64_val", value)?;
+        }
+        Ok(())
+    }
+
+    fn tensor_shape(&mut self, shape: &TensorShapeProto) -> io::Result<()> {
+        for dim in &shape.dim {
+            self.nested("dim", |p| p.dim(dim))?;
+        }
+        self.opt_bool("unknown_rank", shape.unknown_rank)
+    }
+
+    fn dim(&mut self, dim: &Dim) -> io::Result<()> {
+        self.opt_int("size", dim.size)?;
+        self.opt_string("name", &dim.name)
+    }
+
+    fn dtype(&mut self, name: &str, dtype: i32) -> io::Result<()> {
+        self.opt_enum(name, dtype, DataType::from_i32(dtype).map(enum_name))
+    }
+
+    fn resource_handle(&mut self, handle: &ResourceHandleProto) -> io::Result<()> {
+        self.opt_string("device", &handle.device)?;
+        self.opt_string("container", &handle.container)?;
+        self.opt_string("name", &handle.name)?;
+        self.opt_int("hash_code", handle.hash_code)?;
+        self.opt_string("maybe_type_name", &handle.maybe_type_name)?;
+        for dtype_and_shape in &handle.dtypes_and_shapes {
+            self.nested("dtypes_and_shapes", |p| p.dtype_and_shape(dtype_and_shape))?;
+        }
+        Ok(())
+    }
+
+    fn dtype_and_shape(&mut self, dtype_and_shape: &DtypeAndShape) -> io::Result<()> {
+        self.dtype("dtype", dtype_and_shape.dtype)?;
+        if let Some(shape) = &dtype_and_shape.shape {
+            self.nested("shape", |p| p.tensor_shape(shape))?;
+        }
+        Ok(())
+    }
+
+    fn variant(&mut self, variant: &VariantTensorDataProto) -> io::Result<()> {
+        self.opt_string("type_name", &variant.type_name)?;
+        self.opt_bytes("metadata", &variant.metadata)?;
+        for tensor in &variant.tensors {
+            self.nested("tensors", |p| p.tensor(tensor))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "proto-summary")]
+impl<W> Printer<W>
+where
+    W: Write,
+{
+    // summary types
+
+    fn summary(&mut self, summary: &Summary) -> io::Result<()> {
+        for value in &summary.value {
+            self.nested("value", |p| p.summary_value(value))?;
+        }
+        Ok(())
+    }
+
+    fn summary_value(&mut self, value: &SummaryValue) -> io::Result<()> {
+        let SummaryValue {
+            node_name,
+            tag,
+            metadata,
+            value,
+        } = value;
+
+        self.opt_string("tag", tag)?;
+        match value {
+            Some(SummaryValueKind::SimpleValue(value)) => self.float("simple_value", *value)?,
+            Some(SummaryValueKind::ObsoleteOldStyleHistogram(bytes)) => {
+                self.bytes("obsolete_old_style_histogram", bytes)?
+            }
+            Some(SummaryValueKind::Image(image)) => self.nested("image", |p| p.image(image))?,
+            Some(SummaryValueKind::Histo(histo)) => self.nested("histo", |p| p.histogram(histo))?,
+            Some(SummaryValueKind::Audio(audio)) => self.nested("audio", |p| p.audio(audio))?,
+            Some(SummaryValueKind::Tensor(_)) | None => {}
+        }
+        self.opt_string("node_name", node_name)?;
+        if let Some(SummaryValueKind::Tensor(tensor)) = value {
+            self.nested("tensor", |p| p.tensor(tensor))?;
+        }
+        if let Some(metadata) = metadata {
+            self.nested("metadata", |p| p.summary_metadata(metadata))?;
+        }
+        Ok(())
+    }
+
+    fn image(&mut self, image: &Image) -> io::Result<()> {
+        self.opt_int("height", image.height)?;
+        self.opt_int("width", image.width)?;
+        self.opt_int("colorspace", image.colorspace)?;
+        self.opt_bytes("encoded_image_string", &image.encoded_image_string)
+    }
+
+    fn audio(&mut self, audio: &Audio) -> io::Result<()> {
+        self.opt_float("sample_rate", audio.sample_rate)?;
+        self.opt_int("num_channels", audio.num_channels)?;
+        self.opt_int("length_frames", audio.length_frames)?;
+        self.opt_bytes("encoded_audio_string", &audio.encoded_audio_string)?;
+        self.opt_string("content_type", &audio.content_type)
+    }
+
+    fn histogram(&mut self, histogram: &HistogramProto) -> io::Result<()> {
+        self.opt_double("min", histogram.min)?;
+        self.opt_double("max", histogram.max)?;
+        self.opt_double("num", histogram.num)?;
+        self.opt_double("sum", histogram.sum)?;
+        self.opt_double("sum_squares", histogram.sum_squares)?;
+        for &value in &histogram.bucket_limit {
+            self.double("bucket_limit", value)?;
+        }
+        for &value in &histogram.bucket {
+            self.double("bucket", value)?;
+        }
+        Ok(())
+    }
+
+    fn summary_metadata(&mut self, metadata: &SummaryMetadata) -> io::Result<()> {
+        if let Some(plugin_data) = &metadata.plugin_data {
+            self.nested("plugin_data", |p| p.plugin_data(plugin_data))?;
+        }
+        self.opt_string("display_name", &metadata.display_name)?;
+        self.opt_string("summary_description", &metadata.summary_description)?;
+        self.opt_enum(
+            "data_class",
+            metadata.data_class,
+            DataClass::from_i32(metadata.data_class)
+                .map(|data_class| format!("DATA_CLASS_{}", enum_name(data_class))),
+        )
+    }
+
+    fn plugin_data(&mut self, plugin_data: &PluginData) -> io::Result<()> {
+        self.opt_string("plugin_name", &plugin_data.plugin_name)?;
+        self.opt_bytes("content", &plugin_data.content)
+    }
+
+    // event types
+
+    fn event(&mut self, event: &Event) -> io::Result<()> {
+        self.opt_double("wall_time", event.wall_time)?;
+        self.opt_int("step", event.step)?;
+        match &event.what {
+            Some(What::FileVersion(version)) => self.string("file_version", version),
+            Some(What::GraphDef(bytes)) => self.bytes("graph_def", bytes),
+            Some(What::Summary(summary)) => self.nested("summary", |p| p.summary(summary)),
+            Some(What::LogMessage(message)) => {
+                self.nested("log_message", |p| p.log_message(message))
+            }
+            Some(What::SessionLog(log)) => self.nested("session_log", |p| p.session_log(log)),
+            Some(What::TaggedRunMetadata(metadata)) => {
+                self.nested("tagged_run_metadata", |p| p.tagged_run_metadata(metadata))
+            }
+            Some(What::MetaGraphDef(bytes)) => self.bytes("meta_graph_def", bytes),
+            None => Ok(()),
+        }
+    }
+
+    fn log_message(&mut self, message: &LogMessage) -> io::Result<()> {
+        use crate::protobuf::log_message::Level;
+
+        self.opt_enum(
+            "level",
+            message.level,
+            Level::from_i32(message.level).map(enum_name),
+        )?;
+        self.opt_string("message", &message.message)
+    }
+
+    fn session_log(&mut self, log: &SessionLog) -> io::Result<()> {
+        use crate::protobuf::session_log::SessionStatus;
+
+        self.opt_enum(
+            "status",
+            log.status,
+            SessionStatus::from_i32(log.status).map(enum_name),
+        )?;
+        self.opt_string("checkpoint_path", &log.checkpoint_path)?;
+        self.opt_string("msg", &log.msg)
+    }
+
+    fn tagged_run_metadata(&mut self, metadata: &TaggedRunMetadata) -> io::Result<()> {
+        self.opt_string("tag", &metadata.tag)?;
+        self.opt_bytes("run_metadata", &metadata.run_metadata)
+    }
+}
+
+/// Bytes quoted and escaped as a C string literal.
+struct Escaped<'a>(&'a [u8]);
+
+impl fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('"')?;
+        for &byte in self.0 {
+            match byte {
+                b'\n' => f.write_str(r"\n")?,
+                b'\r' => f.write_str(r"\r")?,
+                b'\t' => f.write_str(r"\t")?,
+                b'"' => f.write_str("\\\"")?,
+                b'\'' => f.write_str(r"\'")?,
+                b'\\' => f.write_str(r"\\")?,
+                0x20..=0x7e => f.write_char(byte as char)?,
+                _ => write!(f, "\\{:03o}", byte)?,
+            }
+        }
+        f.write_char('"')
+    }
+}
+
+/// The name of an enum value in the .proto file, such as `DT_FLOAT` for `DtFloat`.
+fn enum_name(value: impl fmt::Debug) -> String {
+    let debug = format!("{:?}", value);
+    let mut name = String::with_capacity(debug.len() + 4);
+    for (index, ch) in debug.chars().enumerate() {
+        if ch.is_ascii_uppercase() && index > 0 {
+            name.push('_');
+        }
+        name.push(ch.to_ascii_uppercase());
+    }
+    name
+}
+
+/// Format a number as the `repr()` of a Python float.
+///
+/// The `scientific` argument is the shortest round-trip digits formatted by `{:e}`.
+/// Numbers within `[1e-4, 1e16)` are printed in the fixed notation, and others in
+/// the scientific notation with at least 2 exponent digits.
+fn format_float(value: f64, scientific: String) -> String {
+    if value.is_nan() {
+        return "nan".into();
+    }
+    if value.is_infinite() {
+        return if value > 0.0 { "inf" } else { "-inf" }.into();
+    }
+
+    let (mantissa, exp) = scientific
+        .split_once('e')
+        .expect("the scientific notation must contain an exponent");
+    let exp: i32 = exp.parse().expect("the exponent must be an integer");
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+    let digits: String = mantissa.chars().filter(|&ch| ch != '.').collect();
+
+    if (-4..16).contains(&exp) {
+        if exp >= 0 {
+            let num_int_digits = exp as usize + 1;
+            if digits.len() > num_int_digits {
+                let (int, frac) = digits.split_at(num_int_digits);
+                format!("{}{}.{}", sign, int, frac)
+            } else {
+                let zeros = "0".repeat(num_int_digits - digits.len());
+                format!("{}{}{}.0", sign, digits, zeros)
+            }
+        } else {
+            let zeros = "0".repeat((-exp - 1) as usize);
+            format!("{}0.{}{}", sign, zeros, digits)
+        }
+    } else {
+        let (first, rest) = digits.split_at(1);
+        let exp_sign = if exp < 0 { '-' } else { '+' };
+        if rest.is_empty() {
+            format!("{}{}e{}{:02}", sign, first, exp_sign, exp.abs())
+        } else {
+            format!("{}{}.{}e{}{:02}", sign, first, rest, exp_sign, exp.abs())
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! The curated re-exports for common workflows.
+//!
+//! A single glob import brings in the example types, the readers and writers, the
+//! indexer types, the error type and every extension trait whose methods are used
+//! on those types.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::prelude::*;
+//!
+//! let example: Example = vec![("label".into(), Feature::from_i64_list(vec![1]))]
+//!     .into_iter()
+//!     .collect();
+//!
+//! let mut bytes = vec![];
+//! let mut writer = ExampleWriter::from_writer(&mut bytes)?;
+//! writer.send(example.clone())?;
+//! drop(writer);
+//!
+//! let examples: Vec<Example> = ExampleIter::from_reader(bytes.as_slice(), Default::default())
+//!     .collect::<Result<_>>()?;
+//! assert_eq!(examples, vec![example]);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # API stability
+//!
+//! The names in the prelude, and the modules it re-exports, are the stable API.
+//! Removing or renaming any of them is a breaking change, which is guarded by the
+//! `public_api` test. Items reachable only through other modules, such as the
+//! low-level [io](crate::io) functions, may change in minor releases.
+//!
+//! The generated ProtocolBuffer types beyond the common ones, such as the `oneof`
+//! enums of events, are reached through the re-exported [protobuf] module.
+
+// modules
+pub use crate::{indexer, protobuf};
+
+// errors and configurations
+pub use crate::{
+    error::{Error, Result},
+    integrity::IntegrityMode,
+    limits::Limits,
+    record_reader::RecordReaderConfig,
+    record_writer::RecordWriterConfig,
+};
+
+// protobuf types and helpers
+pub use crate::{
+    protobuf::{Example, Feature, Features, SequenceExample},
+    protobuf_ext::{FeatureKind, FeatureProjection, KeyCollisionPolicy},
+};
+
+// readers and writers
+pub use crate::{
+    indexer::{RecordIndex, RecordIndexerConfig},
+    memory::MemoryBuffer,
+    record_reader::{BytesIter, ExampleIter, RecordIter},
+    record_writer::{BytesWriter, ExampleWriter, RecordWriter},
+};
+
+#[cfg(feature = "async")]
+pub use crate::{
+    record_reader::{BytesStream, ExampleStream, RecordStream},
+    record_writer::{BytesAsyncWriter, ExampleAsyncWriter, RecordAsyncWriter},
+};
+
+// traits
+pub use crate::{
+    estimate::FramedLen,
+    pbtxt::ToPbtxt,
+    protobuf_ext::{IntoShape, TensorProtoElement},
+    record::Record,
+};
+
+// events and summaries
+#[cfg(feature = "proto-summary")]
+pub use crate::{
+    event::{EventClock, EventMeta, SystemClock, WallClock},
+    event_reader::{EventFileIter, EventReaderConfig},
+    event_writer::{EventWriter, EventWriterConfig},
+    protobuf::{Event, HistogramProto, Summary},
+    protobuf_ext::{IntoHistogram, IntoImageList},
+    record_reader::EventIter,
+};
+
+#[cfg(all(feature = "proto-summary", feature = "async"))]
+pub use crate::{
+    event_reader::EventFileStream, event_writer::EventAsyncWriter, record_reader::EventStream,
+};
@@ -2,15 +2,63 @@
 //!
 //! The types are provided by ProtocolBuffer documents from TensorFlow repository.
 //! They are used internally for {,de}serialization.
+//!
+//! The types are compiled in groups selected by cargo features. All groups share this
+//! module, so the paths of the types do not depend on the enabled features.
+//!
+//! - Examples, features and tensors are always compiled. The tensor types are included
+//!   because the other groups refer to them.
+//! - `proto-summary`: Events and summaries.
+//! - `proto-graph`: Graphs, functions, operations, attributes and variables.
+//! - `proto-runtime`: Devices, allocations, memory logs and step statistics.
+
+#![allow(clippy::large_enum_variant, clippy::doc_lazy_continuation)]
+
+macro_rules! include_protos {
+    ($group:literal) => {
+        #[allow(unused_imports)]
+        use super::*;
+
+        #[cfg(feature = "with-serde")]
+        include!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/prebuild_src/tensorflow_with_serde/",
+            $group,
+            ".rs"
+        ));
+
+        #[cfg(not(feature = "with-serde"))]
+        include!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/prebuild_src/tensorflow_without_serde/",
+            $group,
+            ".rs"
+        ));
+    };
+}
+
+mod example_protos {
+    include_protos!("example");
+}
+pub use example_protos::*;
+
+#[cfg(feature = "proto-summary")]
+mod summary_protos {
+    include_protos!("summary");
+}
+#[cfg(feature = "proto-summary")]
+pub use summary_protos::*;
+
+#[cfg(feature = "proto-graph")]
+mod graph_protos {
+    include_protos!("graph");
+}
+#[cfg(feature = "proto-graph")]
+pub use graph_protos::*;
 
-#[cfg(feature = "with-serde")]
-include!(concat!(
-    env!("CARGO_MANIFEST_DIR"),
-    "/prebuild_src/tensorflow_with_serde.rs"
-));
-
-#[cfg(not(feature = "with-serde"))]
-include!(concat!(
-    env!("CARGO_MANIFEST_DIR"),
-    "/prebuild_src/tensorflow_without_serde.rs"
-));
+#[cfg(feature = "proto-runtime")]
+mod runtime_protos {
+    include_protos!("runtime");
+}
+#[cfg(feature = "proto-runtime")]
+pub use runtime_protos::*;
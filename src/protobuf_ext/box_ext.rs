@@ -0,0 +1,264 @@
+use crate::{
+    error::{ensure_argument, Result},
+    protobuf::{
+        summary::{value, Image, Value},
+        Summary,
+    },
+};
+use image::{DynamicImage, Rgb, RgbaImage};
+
+/// An axis-aligned box drawn onto an image.
+///
+/// The coordinates are interpreted by [BoxStyle::coordinates]. The outline covers the
+/// rows and columns of the edges inclusively, so a box of zero width or height is
+/// drawn as a line, and a box of zero area as a point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Box2D {
+    pub xmin: f32,
+    pub ymin: f32,
+    pub xmax: f32,
+    pub ymax: f32,
+    pub color: Rgb<u8>,
+    /// The label drawn at the top-left corner of the box.
+    ///
+    /// Labels are drawn in a 3x5 bitmap font covering ASCII letters, digits and
+    /// `-.:_%/`. Other characters are drawn as spaces.
+    pub label: Option<String>,
+}
+
+/// The interpretation of [Box2D] coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BoxCoordinates {
+    /// Pixel indices, within `[0, width - 1]` and `[0, height - 1]`.
+    #[default]
+    Pixels,
+    /// Fractions of the image size within `[0, 1]`, scaled to pixel indices by
+    /// `width - 1` and `height - 1` as `tf.image.draw_bounding_boxes` does.
+    Normalized,
+}
+
+/// The style of drawn boxes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BoxStyle {
+    pub coordinates: BoxCoordinates,
+    /// The line thickness in pixels, grown towards the inside of boxes.
+    pub thickness: u32,
+    /// If set, the labels of boxes are drawn.
+    pub labels: bool,
+}
+
+impl Default for BoxStyle {
+    fn default() -> Self {
+        Self {
+            coordinates: BoxCoordinates::default(),
+            thickness: 1,
+            labels: true,
+        }
+    }
+}
+
+/// The boxes adjusted to the image bounds by [draw_boxes].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BoxDrawReport {
+    /// The number of boxes partially outside the image, drawn clamped to the bounds.
+    pub num_clamped: usize,
+    /// The number of boxes entirely outside the image, which are not drawn.
+    pub num_outside: usize,
+}
+
+/// Draw boxes onto a copy of the image.
+///
+/// The output is 8-bit RGBA if the image has an alpha channel, and 8-bit RGB
+/// otherwise. Coordinates beyond the image bounds are clamped and counted in the
+/// report rather than failing. Non-finite coordinates, inverted boxes and zero
+/// thickness are errors.
+pub fn draw_boxes(
+    image: &DynamicImage,
+    boxes: &[Box2D],
+    style: &BoxStyle,
+) -> Result<(DynamicImage, BoxDrawReport)> {
+    let BoxStyle {
+        coordinates,
+        thickness,
+        labels,
+    } = *style;
+    ensure_argument!(thickness > 0, "the thickness must be positive");
+    for (index, bbox) in boxes.iter().enumerate() {
+        let Box2D {
+            xmin,
+            ymin,
+            xmax,
+            ymax,
+            ..
+        } = *bbox;
+        ensure_argument!(
+            [xmin, ymin, xmax, ymax]
+                .iter()
+                .all(|value| value.is_finite()),
+            "the coordinates of box {} must be finite",
+            index
+        );
+        ensure_argument!(
+            xmin <= xmax && ymin <= ymax,
+            "box {} is inverted, with min coordinates beyond max coordinates",
+            index
+        );
+    }
+
+    let mut canvas: RgbaImage = image.to_rgba8();
+    let (width, height) = canvas.dimensions();
+    let mut report = BoxDrawReport::default();
+
+    for bbox in boxes {
+        let scale = |value: f32, size: u32| -> f64 {
+            match coordinates {
+                BoxCoordinates::Pixels => value as f64,
+                BoxCoordinates::Normalized => value as f64 * (size as f64 - 1.0),
+            }
+        };
+        let [x0, x1] = [bbox.xmin, bbox.xmax].map(|x| scale(x, width).round() as i64);
+        let [y0, y1] = [bbox.ymin, bbox.ymax].map(|y| scale(y, height).round() as i64);
+        let (max_x, max_y) = (width as i64 - 1, height as i64 - 1);
+
+        if x1 < 0 || y1 < 0 || x0 > max_x || y0 > max_y {
+            report.num_outside += 1;
+            continue;
+        }
+        if x0 < 0 || y0 < 0 || x1 > max_x || y1 > max_y {
+            report.num_clamped += 1;
+        }
+        let (x0, x1) = (x0.max(0), x1.min(max_x));
+        let (y0, y1) = (y0.max(0), y1.min(max_y));
+
+        let color = bbox.color.0;
+        let thickness = thickness as i64;
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let on_edge = y - y0 < thickness
+                    || y1 - y < thickness
+                    || x - x0 < thickness
+                    || x1 - x < thickness;
+                if on_edge {
+                    canvas.get_pixel_mut(x as u32, y as u32).0 =
+                        [color[0], color[1], color[2], 255];
+                }
+            }
+        }
+
+        if let (true, Some(label)) = (labels, &bbox.label) {
+            // above the box if there is room, inside otherwise
+            let (left, top) = if y0 > GLYPH_HEIGHT {
+                (x0, y0 - GLYPH_HEIGHT - 1)
+            } else {
+                (x0 + thickness + 1, y0 + thickness + 1)
+            };
+            draw_text(&mut canvas, left, top, label, color);
+        }
+    }
+
+    let output = if image.color().has_alpha() {
+        DynamicImage::ImageRgba8(canvas)
+    } else {
+        DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(canvas).to_rgb8())
+    };
+    Ok((output, report))
+}
+
+impl Summary {
+    /// Build an image summary with boxes drawn onto the image.
+    ///
+    /// See [draw_boxes] for details.
+    pub fn from_image_with_boxes(
+        tag: impl ToString,
+        image: &DynamicImage,
+        boxes: &[Box2D],
+        style: &BoxStyle,
+    ) -> Result<(Summary, BoxDrawReport)> {
+        let (image, report) = draw_boxes(image, boxes, style)?;
+        let summary = Summary {
+            value: vec![Value {
+                node_name: "".into(),
+                tag: tag.to_string(),
+                metadata: None,
+                value: Some(value::Value::Image(Image::try_from(&image)?)),
+            }],
+        };
+        Ok((summary, report))
+    }
+}
+
+const GLYPH_WIDTH: i64 = 3;
+const GLYPH_HEIGHT: i64 = 5;
+
+/// Draw text in the bitmap font, clipped to the image bounds.
+fn draw_text(canvas: &mut RgbaImage, left: i64, top: i64, text: &str, color: [u8; 3]) {
+    let (width, height) = canvas.dimensions();
+    for (index, ch) in text.chars().enumerate() {
+        let Some(rows) = glyph(ch) else {
+            continue;
+        };
+        let glyph_left = left + index as i64 * (GLYPH_WIDTH + 1);
+        for (dy, row) in rows.iter().enumerate() {
+            for dx in 0..GLYPH_WIDTH {
+                if row >> (GLYPH_WIDTH - 1 - dx) & 1 == 0 {
+                    continue;
+                }
+                let (x, y) = (glyph_left + dx, top + dy as i64);
+                if (0..width as i64).contains(&x) && (0..height as i64).contains(&y) {
+                    canvas.get_pixel_mut(x as u32, y as u32).0 =
+                        [color[0], color[1], color[2], 255];
+                }
+            }
+        }
+    }
+}
+
+/// The rows of the 3x5 glyph of a character, the leftmost pixel in the highest bit.
+fn glyph(ch: char) -> Option<[u8; 5]> {
+    let rows = match ch.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        _ => return None,
+    };
+    Some(rows)
+}
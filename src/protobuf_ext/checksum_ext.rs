@@ -0,0 +1,297 @@
+//! Feature-level checksums for end-to-end integrity.
+//!
+//! The framing CRC of records protects the bytes in storage, but not the values of
+//! features against bugs in the transforms of a pipeline. [add_feature_checksums]
+//! stores a checksum of each listed feature in the companion [CHECKSUMS_KEY] feature,
+//! and [verify_feature_checksums] tells which features changed at any later stage.
+//! A transform legitimately modifying a checksummed feature calls
+//! [recompute_feature_checksums] afterwards.
+//!
+//! The checksums and the companion feature are defined byte by byte in
+//! [feature_checksum] and [CHECKSUMS_KEY], so that other languages can implement them.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{
+//!     protobuf_ext::{add_feature_checksums, verify_feature_checksums},
+//!     samples, Feature,
+//! };
+//!
+//! let mut example = samples::example(3);
+//! add_feature_checksums(&mut example, ["id", "name"])?;
+//! assert!(verify_feature_checksums(&example)?.is_ok());
+//!
+//! let features = &mut example.features.as_mut().unwrap().feature;
+//! features.insert("id".into(), Feature::from_i64_list(vec![4]));
+//! assert_eq!(verify_feature_checksums(&example)?.mismatched, ["id"]);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::{ensure_argument, Error, Result},
+    protobuf::{feature::Kind, Example, Feature},
+};
+use std::collections::BTreeMap;
+use xxhash_rust::xxh3::Xxh3;
+
+/// The key of the companion feature holding the checksums.
+///
+/// The companion feature is a `BytesList` with a single value, the concatenated
+/// entries sorted by key without duplicates. An entry is the key length as a
+/// little-endian u32, the UTF-8 key and the [checksum](feature_checksum) as a
+/// little-endian u64.
+pub const CHECKSUMS_KEY: &str = "__checksums";
+
+/// The checksum of the canonical value bytes of a feature.
+///
+/// The checksum is the XXH3-64 digest with seed 0 of the canonical value bytes, which
+/// are, with integers in little-endian:
+///
+/// 1. the kind tag as one byte: 0 for an unset kind, 1 for `BytesList`, 2 for
+///    `FloatList` and 3 for `Int64List`, the field numbers of `Feature.kind`,
+/// 2. the number of values as u64, and
+/// 3. the values in order: bytes as a u64 length followed by the bytes, floats as
+///    their u32 bit patterns, and integers as i64.
+///
+/// Floats are hashed bitwise, so changing the sign of a zero or the payload of a NaN
+/// changes the checksum.
+pub fn feature_checksum(feature: &Feature) -> u64 {
+    let mut hasher = Xxh3::new();
+    match &feature.kind {
+        None => {
+            hasher.update(&[0]);
+            hasher.update(&0u64.to_le_bytes());
+        }
+        Some(Kind::BytesList(list)) => {
+            hasher.update(&[1]);
+            hasher.update(&(list.value.len() as u64).to_le_bytes());
+            for bytes in &list.value {
+                hasher.update(&(bytes.len() as u64).to_le_bytes());
+                hasher.update(bytes);
+            }
+        }
+        Some(Kind::FloatList(list)) => {
+            hasher.update(&[2]);
+            hasher.update(&(list.value.len() as u64).to_le_bytes());
+            for value in &list.value {
+                hasher.update(&value.to_bits().to_le_bytes());
+            }
+        }
+        Some(Kind::Int64List(list)) => {
+            hasher.update(&[3]);
+            hasher.update(&(list.value.len() as u64).to_le_bytes());
+            for value in &list.value {
+                hasher.update(&value.to_le_bytes());
+            }
+        }
+    }
+    hasher.digest()
+}
+
+/// The outcome of [verify_feature_checksums].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ChecksumReport {
+    /// Whether the example has the companion feature.
+    pub protected: bool,
+    /// The checksummed keys in order.
+    pub checked: Vec<String>,
+    /// The checksummed keys whose features changed or were removed, in order.
+    pub mismatched: Vec<String>,
+}
+
+impl ChecksumReport {
+    /// Returns true if the example has checksums and all of them match.
+    pub fn is_ok(&self) -> bool {
+        self.protected && self.mismatched.is_empty()
+    }
+}
+
+/// Store the checksums of the features of the keys in the companion feature.
+///
+/// Checksums of other keys already stored are kept. It fails if a key is missing from
+/// the example or is the companion key itself.
+pub fn add_feature_checksums<I, K>(example: &mut Example, keys: I) -> Result<()>
+where
+    I: IntoIterator<Item = K>,
+    K: AsRef<str>,
+{
+    let mut checksums = read_checksums(example)?.unwrap_or_default();
+    let features = &example
+        .features
+        .get_or_insert_with(Default::default)
+        .feature;
+    for key in keys {
+        let key = key.as_ref();
+        ensure_argument!(
+            key != CHECKSUMS_KEY,
+            "the checksums feature cannot be checksummed"
+        );
+        let feature = features
+            .get(key)
+            .ok_or_else(|| Error::invalid_argument(format!("the feature '{}' is missing", key)))?;
+        checksums.insert(key.to_string(), feature_checksum(feature));
+    }
+    write_checksums(example, &checksums);
+    Ok(())
+}
+
+/// Update the stored checksums of the features of the keys after a transform modified
+/// them.
+///
+/// It fails if a key has no stored checksum, so that a typo does not protect a new
+/// feature silently.
+pub fn recompute_feature_checksums<I, K>(example: &mut Example, keys: I) -> Result<()>
+where
+    I: IntoIterator<Item = K>,
+    K: AsRef<str>,
+{
+    let checksums = read_checksums(example)?.unwrap_or_default();
+    let keys: Vec<K> = keys.into_iter().collect();
+    for key in &keys {
+        let key = key.as_ref();
+        ensure_argument!(
+            checksums.contains_key(key),
+            "the feature '{}' has no stored checksum",
+            key
+        );
+    }
+    add_feature_checksums(example, keys)
+}
+
+/// Verify the stored checksums of an example.
+///
+/// An example without the companion feature is reported as not
+/// [protected](ChecksumReport::protected). It fails if the companion feature is
+/// malformed.
+pub fn verify_feature_checksums(example: &Example) -> Result<ChecksumReport> {
+    let checksums = match read_checksums(example)? {
+        Some(checksums) => checksums,
+        None => return Ok(ChecksumReport::default()),
+    };
+    let features = example.features.as_ref().map(|features| &features.feature);
+    let mismatched = checksums
+        .iter()
+        .filter(|(key, &checksum)| {
+            features
+                .and_then(|features| features.get(*key))
+                .is_none_or(|feature| feature_checksum(feature) != checksum)
+        })
+        .map(|(key, _)| key.clone())
+        .collect();
+    Ok(ChecksumReport {
+        protected: true,
+        checked: checksums.into_keys().collect(),
+        mismatched,
+    })
+}
+
+/// Verify the checksums of the examples of an iterator.
+///
+/// Examples with mismatching checksums are turned into
+/// [FeatureChecksumMismatch](Error::FeatureChecksumMismatch) errors. Examples without
+/// checksums are errors if `required` is set, and passed through otherwise.
+pub fn verify_feature_checksums_iter<I>(iter: I, required: bool) -> VerifyFeatureChecksums<I>
+where
+    I: Iterator<Item = Result<Example>>,
+{
+    VerifyFeatureChecksums { iter, required }
+}
+
+/// The iterator returned by [verify_feature_checksums_iter].
+#[derive(Debug, Clone)]
+pub struct VerifyFeatureChecksums<I> {
+    iter: I,
+    required: bool,
+}
+
+impl<I> Iterator for VerifyFeatureChecksums<I>
+where
+    I: Iterator<Item = Result<Example>>,
+{
+    type Item = Result<Example>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let example = match self.iter.next()? {
+            Ok(example) => example,
+            Err(err) => return Some(Err(err)),
+        };
+        let report = match verify_feature_checksums(&example) {
+            Ok(report) => report,
+            Err(err) => return Some(Err(err)),
+        };
+        if !report.protected && self.required {
+            return Some(Err(Error::conversion(format!(
+                "the example has no '{}' feature",
+                CHECKSUMS_KEY
+            ))));
+        }
+        if !report.mismatched.is_empty() {
+            return Some(Err(Error::FeatureChecksumMismatch {
+                keys: report.mismatched,
+            }));
+        }
+        Some(Ok(example))
+    }
+}
+
+/// Decode the companion feature, or `None` if it is absent.
+fn read_checksums(example: &Example) -> Result<Option<BTreeMap<String, u64>>> {
+    let feature = match example
+        .features
+        .as_ref()
+        .and_then(|features| features.feature.get(CHECKSUMS_KEY))
+    {
+        Some(feature) => feature,
+        None => return Ok(None),
+    };
+    let malformed = |reason: &str| {
+        Error::conversion(format!(
+            "the '{}' feature is malformed: {}",
+            CHECKSUMS_KEY, reason
+        ))
+    };
+    let mut bytes = match &feature.kind {
+        Some(Kind::BytesList(list)) if list.value.len() == 1 => list.value[0].as_slice(),
+        _ => return Err(malformed("expect a single bytes value")),
+    };
+
+    let mut checksums = BTreeMap::new();
+    let mut prev: Option<String> = None;
+    while !bytes.is_empty() {
+        let (len, rest) = bytes
+            .split_first_chunk::<4>()
+            .ok_or_else(|| malformed("truncated key length"))?;
+        let len = u32::from_le_bytes(*len) as usize;
+        if rest.len() < len + 8 {
+            return Err(malformed("truncated entry"));
+        }
+        let (key, rest) = rest.split_at(len);
+        let (checksum, rest) = rest.split_first_chunk::<8>().unwrap();
+        let key = std::str::from_utf8(key)
+            .map_err(|_| malformed("the key is not UTF-8"))?
+            .to_string();
+        if prev.as_ref().is_some_and(|prev| *prev >= key) {
+            return Err(malformed("the keys are not sorted or not unique"));
+        }
+        prev = Some(key.clone());
+        checksums.insert(key, u64::from_le_bytes(*checksum));
+        bytes = rest;
+    }
+    Ok(Some(checksums))
+}
+
+/// Encode the companion feature.
+fn write_checksums(example: &mut Example, checksums: &BTreeMap<String, u64>) {
+    let mut bytes = vec![];
+    for (key, checksum) in checksums {
+        bytes.extend_from_slice(&(key.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(key.as_bytes());
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+    }
+    example
+        .features
+        .get_or_insert_with(Default::default)
+        .feature
+        .insert(CHECKSUMS_KEY.into(), Feature::from_bytes_list(vec![bytes]));
+}
@@ -1,5 +1,56 @@
-use crate::protobuf::{Example, Feature, Features};
-use std::collections::HashMap;
+use crate::{
+    audit::{self, DecodeReport},
+    error::{Error, Result},
+    limits::Limits,
+    protobuf::{Example, Feature, Features},
+};
+use prost::{
+    bytes::Buf as _,
+    encoding::{self, DecodeContext, WireType},
+    Message,
+};
+use std::{
+    borrow::Cow,
+    collections::{BTreeSet, HashMap},
+};
+
+/// The set of feature keys to decode from serialized examples.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct FeatureProjection {
+    /// The keys to decode.
+    pub keys: BTreeSet<String>,
+    /// Assert that the features of every record are serialized in key order,
+    /// for example, written with [canonical_encoding](crate::RecordWriterConfig::canonical_encoding).
+    /// Files written with [sorted_features](crate::RecordWriterConfig::sorted_features)
+    /// declare it, which is checked by [shard_declares_sorted_keys](crate::metadata::shard_declares_sorted_keys).
+    ///
+    /// If set, decoding stops as soon as all requested keys are found or a key
+    /// beyond the last requested key is encountered. Otherwise, the whole record is scanned.
+    pub sorted_keys: bool,
+}
+
+impl FeatureProjection {
+    /// Build a projection on the keys without the sortedness assertion.
+    pub fn new<I, K>(keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        Self {
+            keys: keys.into_iter().map(Into::into).collect(),
+            sorted_keys: false,
+        }
+    }
+}
+
+/// The options of the typed accessors taking a configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct AccessorConfig {
+    /// If set, the `Int64List` accessors transparently decode the features encoded by
+    /// [Feature::from_sorted_indices]. Otherwise, such features are `BytesList`s and fail
+    /// as features of other kinds.
+    pub auto_decode_indices: bool,
+}
 
 impl Example {
     pub fn into_vec(self) -> Vec<(String, Feature)> {
@@ -20,6 +71,458 @@ impl Example {
     pub fn empty() -> Self {
         Self { features: None }
     }
+
+    /// Replace the `Int64List` feature under `key` with the compact form of
+    /// [Feature::from_sorted_indices].
+    pub fn encode_sorted_indices(&mut self, key: &str, sort: bool) -> Result<()> {
+        let feature = self.feature_mut(key)?;
+        let indices = feature.as_i64_list().ok_or_else(|| {
+            Error::conversion(format!("the feature '{}' is not an Int64List", key))
+        })?;
+        *feature = Feature::from_sorted_indices(indices, sort)?;
+        Ok(())
+    }
+
+    /// Restore the `Int64List` feature under `key` encoded by [Example::encode_sorted_indices].
+    pub fn decode_sorted_indices(&mut self, key: &str) -> Result<()> {
+        let feature = self.feature_mut(key)?;
+        *feature = Feature::from_i64_list(feature.to_sorted_indices()?);
+        Ok(())
+    }
+
+    /// Restore every feature encoded by [Example::encode_sorted_indices].
+    pub fn decode_all_sorted_indices(&mut self) -> Result<()> {
+        for feature in self
+            .features
+            .iter_mut()
+            .flat_map(|features| features.feature.values_mut())
+        {
+            if feature.is_sorted_indices() {
+                *feature = Feature::from_i64_list(feature.to_sorted_indices()?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Insert booleans packed by [Feature::from_bools].
+    pub fn push_bools(&mut self, key: impl Into<String>, values: &[bool]) -> Result<()> {
+        let feature = Feature::from_bools(values)?;
+        self.insert_feature(key.into(), feature);
+        Ok(())
+    }
+
+    /// Get booleans packed by [Example::push_bools].
+    ///
+    /// ```rust
+    /// # fn main() -> tfrecord::Result<()> {
+    /// use tfrecord::samples;
+    ///
+    /// let mut example = samples::example(7);
+    /// example.push_bools("mask", &[true, false, true])?;
+    /// assert_eq!(example.get_bools("mask")?, vec![true, false, true]);
+    ///
+    /// let label = example.into_hash_map().remove("label").unwrap();
+    /// assert_eq!(label.as_i64_list(), Some(&[7][..]));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_bools(&self, key: &str) -> Result<Vec<bool>> {
+        self.feature(key)?.to_bools()
+    }
+
+    /// Insert bytes stored by [Feature::from_u8s].
+    pub fn push_u8s(&mut self, key: impl Into<String>, values: &[u8]) {
+        self.insert_feature(key.into(), Feature::from_u8s(values));
+    }
+
+    /// Get bytes stored by [Example::push_u8s].
+    pub fn get_u8s(&self, key: &str) -> Result<&[u8]> {
+        self.feature(key)?.as_u8s()
+    }
+
+    /// Insert a `FloatList` feature.
+    pub fn push_f32s(&mut self, key: impl Into<String>, values: &[f32]) {
+        self.insert_feature(key.into(), Feature::from_f32_list(values));
+    }
+
+    /// Get the values of a `FloatList` feature.
+    ///
+    /// ```rust
+    /// # fn main() -> tfrecord::Result<()> {
+    /// use tfrecord::Example;
+    ///
+    /// let mut example = Example::empty();
+    /// example.push_f32s("age", &[31.0]);
+    /// assert_eq!(example.get_f32s("age")?, &[31.0]);
+    ///
+    /// // a missing feature and a feature of another kind are told apart
+    /// assert_eq!(example.try_get_f32s("height")?, None);
+    /// assert!(example.try_get_i64s("age").is_err());
+    ///
+    /// example.get_f32s_mut("age")?.push(32.0);
+    /// assert_eq!(example.get_f32s("age")?, &[31.0, 32.0]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_f32s(&self, key: &str) -> Result<&[f32]> {
+        self.feature(key)?
+            .as_f32_list()
+            .ok_or_else(|| kind_mismatch(key, "FloatList"))
+    }
+
+    /// Get the values of a `FloatList` feature, or `None` if the feature does not exist.
+    pub fn try_get_f32s(&self, key: &str) -> Result<Option<&[f32]>> {
+        self.features
+            .as_ref()
+            .map_or(Ok(None), |features| features.try_get_f32s(key))
+    }
+
+    /// Get the mutable values of a `FloatList` feature.
+    pub fn get_f32s_mut(&mut self, key: &str) -> Result<&mut Vec<f32>> {
+        self.feature_mut(key)?
+            .as_f32_list_mut()
+            .ok_or_else(|| kind_mismatch(key, "FloatList"))
+    }
+
+    /// Insert an `Int64List` feature.
+    pub fn push_i64s(&mut self, key: impl Into<String>, values: &[i64]) {
+        self.insert_feature(key.into(), Feature::from_i64_list(values));
+    }
+
+    /// Get the values of an `Int64List` feature.
+    pub fn get_i64s(&self, key: &str) -> Result<&[i64]> {
+        self.feature(key)?
+            .as_i64_list()
+            .ok_or_else(|| kind_mismatch(key, "Int64List"))
+    }
+
+    /// Get the values of an `Int64List` feature, or `None` if the feature does not exist.
+    pub fn try_get_i64s(&self, key: &str) -> Result<Option<&[i64]>> {
+        self.features
+            .as_ref()
+            .map_or(Ok(None), |features| features.try_get_i64s(key))
+    }
+
+    /// Get the mutable values of an `Int64List` feature.
+    pub fn get_i64s_mut(&mut self, key: &str) -> Result<&mut Vec<i64>> {
+        self.feature_mut(key)?
+            .as_i64_list_mut()
+            .ok_or_else(|| kind_mismatch(key, "Int64List"))
+    }
+
+    /// Get the values of an `Int64List` feature with the accessor options.
+    ///
+    /// ```rust
+    /// # fn main() -> tfrecord::Result<()> {
+    /// use tfrecord::{AccessorConfig, Example};
+    ///
+    /// let mut example = Example::empty();
+    /// example.push_i64s("ids", &[3, 8, 8, 20]);
+    /// example.encode_sorted_indices("ids", false)?;
+    /// assert!(example.get_i64s("ids").is_err());
+    ///
+    /// let config = AccessorConfig {
+    ///     auto_decode_indices: true,
+    /// };
+    /// assert_eq!(&*example.get_i64s_with("ids", &config)?, &[3, 8, 8, 20]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_i64s_with(&self, key: &str, config: &AccessorConfig) -> Result<Cow<'_, [i64]>> {
+        self.try_get_i64s_with(key, config)?
+            .ok_or_else(|| missing_feature(key))
+    }
+
+    /// Get the values of an `Int64List` feature with the accessor options, or `None` if
+    /// the feature does not exist.
+    pub fn try_get_i64s_with(
+        &self,
+        key: &str,
+        config: &AccessorConfig,
+    ) -> Result<Option<Cow<'_, [i64]>>> {
+        self.features
+            .as_ref()
+            .map_or(Ok(None), |features| features.try_get_i64s_with(key, config))
+    }
+
+    /// Insert a `BytesList` feature.
+    pub fn push_bytes(&mut self, key: impl Into<String>, values: Vec<Vec<u8>>) {
+        self.insert_feature(key.into(), Feature::from_bytes_list(values));
+    }
+
+    /// Get the values of a `BytesList` feature.
+    pub fn get_bytes(&self, key: &str) -> Result<&[Vec<u8>]> {
+        self.feature(key)?
+            .as_bytes_list()
+            .ok_or_else(|| kind_mismatch(key, "BytesList"))
+    }
+
+    /// Get the values of a `BytesList` feature, or `None` if the feature does not exist.
+    pub fn try_get_bytes(&self, key: &str) -> Result<Option<&[Vec<u8>]>> {
+        self.features
+            .as_ref()
+            .map_or(Ok(None), |features| features.try_get_bytes(key))
+    }
+
+    /// Get the mutable values of a `BytesList` feature.
+    pub fn get_bytes_mut(&mut self, key: &str) -> Result<&mut Vec<Vec<u8>>> {
+        self.feature_mut(key)?
+            .as_bytes_list_mut()
+            .ok_or_else(|| kind_mismatch(key, "BytesList"))
+    }
+
+    pub(crate) fn insert_feature(&mut self, key: String, feature: Feature) {
+        self.features
+            .get_or_insert_with(Features::default)
+            .feature
+            .insert(key, feature);
+    }
+
+    pub(crate) fn feature(&self, key: &str) -> Result<&Feature> {
+        self.features
+            .as_ref()
+            .and_then(|features| features.feature.get(key))
+            .ok_or_else(|| missing_feature(key))
+    }
+
+    fn feature_mut(&mut self, key: &str) -> Result<&mut Feature> {
+        self.features
+            .as_mut()
+            .and_then(|features| features.feature.get_mut(key))
+            .ok_or_else(|| missing_feature(key))
+    }
+
+    /// Decode a serialized example, rejecting it if it exceeds the limits.
+    ///
+    /// The feature map entries are counted on the wire before any of them is decoded.
+    pub fn decode_with_limits(bytes: &[u8], limits: &Limits) -> Result<Self> {
+        scan_feature_entries(bytes, limits, |_, _| Ok(false))?;
+        Ok(Self::decode(bytes)?)
+    }
+
+    /// Decode a serialized example like [decode_with_limits](Self::decode_with_limits),
+    /// counting its wire-level anomalies into the report at the record index.
+    ///
+    /// Examples failing to decode are not counted.
+    pub fn decode_with_report(
+        bytes: &[u8],
+        limits: &Limits,
+        report: &mut DecodeReport,
+        record: u64,
+    ) -> Result<Self> {
+        let example = Self::decode_with_limits(bytes, limits)?;
+        audit::scan_example(bytes, record, report)?;
+        report.num_records += 1;
+        Ok(example)
+    }
+
+    /// Check that the feature map entries of a serialized example are sorted by key
+    /// without duplicates, without decoding the values.
+    pub fn has_sorted_keys(bytes: &[u8]) -> Result<bool> {
+        let mut prev: Option<String> = None;
+        let mut sorted = true;
+        scan_feature_entries(bytes, &Limits::default(), |key, _| {
+            sorted = prev.as_deref().is_none_or(|prev| prev < key);
+            prev = Some(key.to_string());
+            Ok(!sorted)
+        })?;
+        Ok(sorted)
+    }
+
+    /// Decode a serialized example, keeping only the features in the projection.
+    ///
+    /// Features outside the projection are skipped without being decoded.
+    pub fn decode_projected(bytes: &[u8], projection: &FeatureProjection) -> Result<Self> {
+        Self::decode_projected_with_limits(bytes, projection, &Limits::default())
+    }
+
+    /// Decode a serialized example, keeping only the features in the projection
+    /// and rejecting it if it exceeds the limits.
+    pub fn decode_projected_with_limits(
+        bytes: &[u8],
+        projection: &FeatureProjection,
+        limits: &Limits,
+    ) -> Result<Self> {
+        let FeatureProjection {
+            ref keys,
+            sorted_keys,
+        } = *projection;
+        let last_key = keys.iter().next_back();
+        let mut feature = HashMap::new();
+
+        let has_features = scan_feature_entries(bytes, limits, |key, value| {
+            if keys.contains(key) {
+                let value = match value {
+                    Some(value) => Feature::decode(value)?,
+                    None => Feature::default(),
+                };
+                feature.insert(key.to_string(), value);
+            }
+
+            // stop early if the remaining keys are beyond the projection
+            let stop = sorted_keys
+                && (feature.len() == keys.len() || last_key.is_none_or(|last| key > last.as_str()));
+            Ok(stop)
+        })?;
+
+        Ok(Self {
+            features: has_features.then_some(Features { feature }),
+        })
+    }
+
+    /// Encode the example with map entries sorted by key.
+    ///
+    /// Unlike [encode_to_vec](Message::encode_to_vec), the output does not depend
+    /// on the iteration order of the feature map, so equal examples always produce
+    /// identical bytes. Floats are encoded as stored, which identifies them
+    /// [bitwise](crate::FloatIdentity::Bitwise).
+    pub fn encode_canonical_to_vec(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        if let Some(features) = &self.features {
+            encode_nested(1, &features.encode_canonical_to_vec(), &mut buf);
+        }
+        buf
+    }
+
+    /// The exact size in bytes of the example written in a TFRecord file.
+    ///
+    /// It is the encoded length plus the framing bytes, the length, the payload and their checksums.
+    pub fn encoded_len_framed(&self) -> u64 {
+        crate::estimate::framed_len(self.encoded_len())
+    }
+}
+
+impl Features {
+    /// Encode the features with map entries sorted by key.
+    pub fn encode_canonical_to_vec(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        encode_sorted_map(1, &self.feature, &mut buf);
+        buf
+    }
+
+    /// Insert a `FloatList` feature.
+    pub fn push_f32s(&mut self, key: impl Into<String>, values: &[f32]) {
+        self.feature
+            .insert(key.into(), Feature::from_f32_list(values));
+    }
+
+    /// Get the values of a `FloatList` feature.
+    ///
+    /// It works on the context of a [SequenceExample](crate::protobuf::SequenceExample)
+    /// as well.
+    pub fn get_f32s(&self, key: &str) -> Result<&[f32]> {
+        self.try_get_f32s(key)?.ok_or_else(|| missing_feature(key))
+    }
+
+    /// Get the values of a `FloatList` feature, or `None` if the feature does not exist.
+    pub fn try_get_f32s(&self, key: &str) -> Result<Option<&[f32]>> {
+        self.feature
+            .get(key)
+            .map(|feature| {
+                feature
+                    .as_f32_list()
+                    .ok_or_else(|| kind_mismatch(key, "FloatList"))
+            })
+            .transpose()
+    }
+
+    /// Get the mutable values of a `FloatList` feature.
+    pub fn get_f32s_mut(&mut self, key: &str) -> Result<&mut Vec<f32>> {
+        self.feature_mut(key)?
+            .as_f32_list_mut()
+            .ok_or_else(|| kind_mismatch(key, "FloatList"))
+    }
+
+    /// Insert an `Int64List` feature.
+    pub fn push_i64s(&mut self, key: impl Into<String>, values: &[i64]) {
+        self.feature
+            .insert(key.into(), Feature::from_i64_list(values));
+    }
+
+    /// Get the values of an `Int64List` feature.
+    pub fn get_i64s(&self, key: &str) -> Result<&[i64]> {
+        self.try_get_i64s(key)?.ok_or_else(|| missing_feature(key))
+    }
+
+    /// Get the values of an `Int64List` feature, or `None` if the feature does not exist.
+    pub fn try_get_i64s(&self, key: &str) -> Result<Option<&[i64]>> {
+        self.feature
+            .get(key)
+            .map(|feature| {
+                feature
+                    .as_i64_list()
+                    .ok_or_else(|| kind_mismatch(key, "Int64List"))
+            })
+            .transpose()
+    }
+
+    /// Get the mutable values of an `Int64List` feature.
+    pub fn get_i64s_mut(&mut self, key: &str) -> Result<&mut Vec<i64>> {
+        self.feature_mut(key)?
+            .as_i64_list_mut()
+            .ok_or_else(|| kind_mismatch(key, "Int64List"))
+    }
+
+    /// Get the values of an `Int64List` feature with the accessor options.
+    pub fn get_i64s_with(&self, key: &str, config: &AccessorConfig) -> Result<Cow<'_, [i64]>> {
+        self.try_get_i64s_with(key, config)?
+            .ok_or_else(|| missing_feature(key))
+    }
+
+    /// Get the values of an `Int64List` feature with the accessor options, or `None` if
+    /// the feature does not exist.
+    pub fn try_get_i64s_with(
+        &self,
+        key: &str,
+        config: &AccessorConfig,
+    ) -> Result<Option<Cow<'_, [i64]>>> {
+        let Some(feature) = self.feature.get(key) else {
+            return Ok(None);
+        };
+        if config.auto_decode_indices && feature.is_sorted_indices() {
+            return Ok(Some(Cow::Owned(feature.to_sorted_indices()?)));
+        }
+        let values = feature
+            .as_i64_list()
+            .ok_or_else(|| kind_mismatch(key, "Int64List"))?;
+        Ok(Some(Cow::Borrowed(values)))
+    }
+
+    /// Insert a `BytesList` feature.
+    pub fn push_bytes(&mut self, key: impl Into<String>, values: Vec<Vec<u8>>) {
+        self.feature
+            .insert(key.into(), Feature::from_bytes_list(values));
+    }
+
+    /// Get the values of a `BytesList` feature.
+    pub fn get_bytes(&self, key: &str) -> Result<&[Vec<u8>]> {
+        self.try_get_bytes(key)?.ok_or_else(|| missing_feature(key))
+    }
+
+    /// Get the values of a `BytesList` feature, or `None` if the feature does not exist.
+    pub fn try_get_bytes(&self, key: &str) -> Result<Option<&[Vec<u8>]>> {
+        self.feature
+            .get(key)
+            .map(|feature| {
+                feature
+                    .as_bytes_list()
+                    .ok_or_else(|| kind_mismatch(key, "BytesList"))
+            })
+            .transpose()
+    }
+
+    /// Get the mutable values of a `BytesList` feature.
+    pub fn get_bytes_mut(&mut self, key: &str) -> Result<&mut Vec<Vec<u8>>> {
+        self.feature_mut(key)?
+            .as_bytes_list_mut()
+            .ok_or_else(|| kind_mismatch(key, "BytesList"))
+    }
+
+    fn feature_mut(&mut self, key: &str) -> Result<&mut Feature> {
+        self.feature
+            .get_mut(key)
+            .ok_or_else(|| missing_feature(key))
+    }
 }
 
 impl FromIterator<(String, Feature)> for Example {
@@ -31,3 +534,110 @@ impl FromIterator<(String, Feature)> for Example {
         }
     }
 }
+
+fn missing_feature(key: &str) -> Error {
+    Error::conversion(format!("the feature '{}' does not exist", key))
+}
+
+fn kind_mismatch(key: &str, kind: &str) -> Error {
+    Error::conversion(format!("the feature '{}' is not a {}", key, kind))
+}
+
+/// Write a length-delimited field whose body is already encoded.
+pub(crate) fn encode_nested(tag: u32, body: &[u8], buf: &mut Vec<u8>) {
+    encoding::encode_key(tag, WireType::LengthDelimited, buf);
+    encoding::encode_varint(body.len() as u64, buf);
+    buf.extend_from_slice(body);
+}
+
+/// Encode a `map<string, message>` field in key order, byte-compatible with prost's map encoding.
+pub(crate) fn encode_sorted_map<V>(tag: u32, map: &HashMap<String, V>, buf: &mut Vec<u8>)
+where
+    V: Message + Default + PartialEq,
+{
+    let default_value = V::default();
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_unstable_by_key(|(key, _)| *key);
+
+    for (key, value) in entries {
+        let mut entry = vec![];
+        if !key.is_empty() {
+            encoding::string::encode(1, key, &mut entry);
+        }
+        if value != &default_value {
+            encoding::message::encode(2, value, &mut entry);
+        }
+        encode_nested(tag, &entry, buf);
+    }
+}
+
+/// Visit the feature map entries of a serialized [Example] without decoding the values.
+///
+/// The visitor receives the key and the encoded value, and returns true to stop the scan.
+/// The function returns whether the example has the features field.
+pub(crate) fn scan_feature_entries<F>(bytes: &[u8], limits: &Limits, mut visit: F) -> Result<bool>
+where
+    F: FnMut(&str, Option<&[u8]>) -> Result<bool>,
+{
+    let mut buf = bytes;
+    let mut has_features = false;
+    let mut num_entries = 0;
+
+    while buf.has_remaining() {
+        let (tag, wire_type) = encoding::decode_key(&mut buf)?;
+        if tag != 1 || wire_type != WireType::LengthDelimited {
+            encoding::skip_field(wire_type, tag, &mut buf, DecodeContext::default())?;
+            continue;
+        }
+        has_features = true;
+        let mut body = split_length_delimited(&mut buf)?;
+
+        while body.has_remaining() {
+            let (tag, wire_type) = encoding::decode_key(&mut body)?;
+            if tag != 1 || wire_type != WireType::LengthDelimited {
+                encoding::skip_field(wire_type, tag, &mut body, DecodeContext::default())?;
+                continue;
+            }
+            num_entries += 1;
+            limits.check_features(num_entries)?;
+
+            let mut entry = split_length_delimited(&mut body)?;
+            let mut key = "";
+            let mut value = None;
+            while entry.has_remaining() {
+                let (tag, wire_type) = encoding::decode_key(&mut entry)?;
+                match (tag, wire_type) {
+                    (1, WireType::LengthDelimited) => {
+                        let bytes = split_length_delimited(&mut entry)?;
+                        limits.check_key_len(bytes.len())?;
+                        key = std::str::from_utf8(bytes).map_err(|_| {
+                            Error::conversion("the feature key is not UTF-8 encoded")
+                        })?;
+                    }
+                    (2, WireType::LengthDelimited) => {
+                        value = Some(split_length_delimited(&mut entry)?);
+                    }
+                    _ => {
+                        encoding::skip_field(wire_type, tag, &mut entry, DecodeContext::default())?;
+                    }
+                }
+            }
+
+            if visit(key, value)? {
+                return Ok(has_features);
+            }
+        }
+    }
+
+    Ok(has_features)
+}
+
+pub(crate) fn split_length_delimited<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8]> {
+    let len = encoding::decode_varint(buf)? as usize;
+    if len > buf.len() {
+        return Err(Error::UnexpectedEof);
+    }
+    let (head, tail) = buf.split_at(len);
+    *buf = tail;
+    Ok(head)
+}
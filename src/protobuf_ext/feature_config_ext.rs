@@ -0,0 +1,26 @@
+use crate::{
+    error::Error,
+    protobuf::{DataType, FixedLenFeatureProto, VarLenFeatureProto},
+};
+
+impl FixedLenFeatureProto {
+    /// Get the data type of the feature.
+    ///
+    /// Unlike the generated `dtype()` getter, which silently falls back to
+    /// [DataType::DtInvalid], an unknown raw value is reported as an error.
+    pub fn try_dtype(&self) -> Result<DataType, Error> {
+        DataType::from_i32(self.dtype)
+            .ok_or_else(|| Error::unknown_enum_value("DataType", self.dtype))
+    }
+}
+
+impl VarLenFeatureProto {
+    /// Get the data type of the feature.
+    ///
+    /// Unlike the generated `dtype()` getter, which silently falls back to
+    /// [DataType::DtInvalid], an unknown raw value is reported as an error.
+    pub fn try_dtype(&self) -> Result<DataType, Error> {
+        DataType::from_i32(self.dtype)
+            .ok_or_else(|| Error::unknown_enum_value("DataType", self.dtype))
+    }
+}
@@ -1,6 +1,16 @@
-use crate::protobuf::{feature::Kind, BytesList, Feature, FloatList, Int64List};
+use crate::{
+    error::{Error, Result},
+    protobuf::{feature::Kind, BytesList, Feature, FloatList, Int64List},
+};
+use integer_encoding::VarInt;
 use std::borrow::Cow;
 
+/// The leading byte of a bitmap produced by [Feature::from_bools].
+pub const BOOLS_MARKER: u8 = b'b';
+
+/// The first bytes entry marking a feature produced by [Feature::from_sorted_indices].
+pub const SORTED_INDICES_MARKER: &[u8] = b"tfrecord.sorted_indices.v1";
+
 /// Enumeration of feature kinds returned from [Feature::into_kinds()]
 #[derive(Debug, Clone, PartialEq)]
 pub enum FeatureKind {
@@ -113,6 +123,30 @@ impl Feature {
         }
     }
 
+    pub fn as_bytes_list_mut(&mut self) -> Option<&mut Vec<Vec<u8>>> {
+        if let Some(Kind::BytesList(BytesList { value })) = &mut self.kind {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_f32_list_mut(&mut self) -> Option<&mut Vec<f32>> {
+        if let Some(Kind::FloatList(FloatList { value })) = &mut self.kind {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_i64_list_mut(&mut self) -> Option<&mut Vec<i64>> {
+        if let Some(Kind::Int64List(Int64List { value })) = &mut self.kind {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
     pub fn into_bytes_list(self) -> Result<Vec<Vec<u8>>, Self> {
         if let Some(Kind::BytesList(BytesList { value })) = self.kind {
             Ok(value)
@@ -137,3 +171,170 @@ impl Feature {
         }
     }
 }
+
+impl Feature {
+    /// Build a compact feature for sorted `Int64List` indices.
+    ///
+    /// The feature is a `BytesList` of two entries: the [SORTED_INDICES_MARKER] and
+    /// the varint-encoded deltas between consecutive indices. Duplicate indices are kept.
+    /// If `sort` is false, unsorted input is rejected with the first out-of-order position.
+    pub fn from_sorted_indices(indices: &[i64], sort: bool) -> Result<Self> {
+        let mut sorted;
+        let indices = match indices.windows(2).position(|pair| pair[0] > pair[1]) {
+            None => indices,
+            Some(_) if sort => {
+                sorted = indices.to_vec();
+                sorted.sort_unstable();
+                &sorted
+            }
+            Some(index) => {
+                return Err(Error::conversion(format!(
+                    "the index {} at position {} is less than its predecessor {}",
+                    indices[index + 1],
+                    index + 1,
+                    indices[index]
+                )));
+            }
+        };
+
+        let mut payload = vec![];
+        let mut buf = [0u8; 10];
+        let mut prev = None;
+        for &index in indices {
+            let len = match prev {
+                None => index.encode_var(&mut buf),
+                Some(prev) => (index.wrapping_sub(prev) as u64).encode_var(&mut buf),
+            };
+            payload.extend_from_slice(&buf[..len]);
+            prev = Some(index);
+        }
+
+        Ok(Self::from_bytes_list(vec![
+            SORTED_INDICES_MARKER.to_vec(),
+            payload,
+        ]))
+    }
+
+    /// Returns true if the feature is built by [Feature::from_sorted_indices].
+    pub fn is_sorted_indices(&self) -> bool {
+        matches!(
+            self.as_bytes_list(),
+            Some([marker, _]) if marker == SORTED_INDICES_MARKER
+        )
+    }
+
+    /// Restore the indices from a feature built by [Feature::from_sorted_indices].
+    pub fn to_sorted_indices(&self) -> Result<Vec<i64>> {
+        let payload = match self.as_bytes_list() {
+            Some([marker, payload]) if marker == SORTED_INDICES_MARKER => payload,
+            _ => {
+                return Err(Error::conversion(
+                    "the feature is not encoded by from_sorted_indices()",
+                ))
+            }
+        };
+
+        let mut indices: Vec<i64> = vec![];
+        let mut rest = payload.as_slice();
+        while !rest.is_empty() {
+            let (index, len) = match indices.last() {
+                None => i64::decode_var(rest),
+                Some(&prev) => {
+                    u64::decode_var(rest).map(|(delta, len)| (prev.wrapping_add(delta as i64), len))
+                }
+            }
+            .ok_or_else(|| Error::conversion("truncated varint in sorted indices"))?;
+            indices.push(index);
+            rest = &rest[len..];
+        }
+        Ok(indices)
+    }
+}
+
+impl Feature {
+    /// Pack booleans into a `BytesList` with a single bitmap value.
+    ///
+    /// The value is the [BOOLS_MARKER] byte, the number of booleans as little-endian u32,
+    /// and the bits in little-endian bit order, padded with zeros. It can be read in Python by
+    ///
+    /// ```python
+    /// n = int.from_bytes(value[1:5], "little")
+    /// bools = np.unpackbits(np.frombuffer(value[5:], np.uint8), bitorder="little")[:n].astype(bool)
+    /// ```
+    pub fn from_bools(values: &[bool]) -> Result<Self> {
+        let len: u32 = values
+            .len()
+            .try_into()
+            .map_err(|_| Error::conversion(format!("too many booleans: {}", values.len())))?;
+
+        let mut bytes = vec![BOOLS_MARKER];
+        bytes.extend_from_slice(&len.to_le_bytes());
+        bytes.extend(values.chunks(8).map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0u8, |byte, (index, &bit)| byte | ((bit as u8) << index))
+        }));
+        Ok(Self::from_bytes_list(vec![bytes]))
+    }
+
+    /// Unpack booleans from a feature built by [Feature::from_bools].
+    pub fn to_bools(&self) -> Result<Vec<bool>> {
+        let value = match self.as_bytes_list() {
+            Some([value]) => value,
+            _ => {
+                return Err(Error::conversion(
+                    "the packed booleans must be a BytesList with exactly one value",
+                ))
+            }
+        };
+        let (len, bitmap) = match value.as_slice() {
+            [BOOLS_MARKER, len @ ..] if len.len() >= 4 => {
+                let (len, bitmap) = len.split_at(4);
+                (u32::from_le_bytes(len.try_into().unwrap()) as usize, bitmap)
+            }
+            _ => {
+                return Err(Error::conversion(
+                    "the value does not start with the packed booleans marker and length",
+                ))
+            }
+        };
+
+        let expect_bitmap_len = len.div_ceil(8);
+        if bitmap.len() != expect_bitmap_len {
+            return Err(Error::conversion(format!(
+                "expect {} bitmap bytes for {} booleans, but found {}",
+                expect_bitmap_len,
+                len,
+                bitmap.len()
+            )));
+        }
+        if len % 8 != 0 && bitmap[len / 8] >> (len % 8) != 0 {
+            return Err(Error::conversion(
+                "the padding bits of packed booleans are not zero",
+            ));
+        }
+
+        let values = (0..len)
+            .map(|index| bitmap[index / 8] & (1 << (index % 8)) != 0)
+            .collect();
+        Ok(values)
+    }
+
+    /// Store bytes as a `BytesList` with a single value.
+    ///
+    /// It can be read in Python by `np.frombuffer(value, np.uint8)`.
+    pub fn from_u8s(values: &[u8]) -> Self {
+        Self::from_bytes_list(vec![values.to_vec()])
+    }
+
+    /// Get the bytes from a feature built by [Feature::from_u8s].
+    pub fn as_u8s(&self) -> Result<&[u8]> {
+        match self.as_bytes_list() {
+            Some([value]) => Ok(value),
+            _ => Err(Error::conversion(
+                "the u8 values must be a BytesList with exactly one value",
+            )),
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use crate::{
+    error::{Error, Result},
+    protobuf::{feature::Kind, Example, Feature},
+};
+use std::collections::{BTreeSet, HashMap};
+use xxhash_rust::xxh3::Xxh3;
+
+/// The policy telling whether two floats are the same value.
+///
+/// Comparisons, [content hashes](Feature::content_hash), [diffs](Example::diff),
+/// [dedup_examples] and [group_examples_by] take the policy explicitly.
+/// [HASHING](FloatIdentity::HASHING) is the convention of hashing paths, and
+/// [SEMANTIC](FloatIdentity::SEMANTIC) the one of semantic diffs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FloatIdentity {
+    /// Floats are the same if their bit patterns are. `0.0` and `-0.0` differ, and NaNs
+    /// are only the same with the same payload and signaling bit.
+    Bitwise,
+    /// Floats are compared by value, so `0.0` and `-0.0` are the same, and NaNs are the
+    /// same as each other. If `canonicalize_payload` is unset, NaNs are only the same
+    /// with the same bit pattern.
+    NanEqualNan { canonicalize_payload: bool },
+    /// Finite floats are the same if they differ by at most `abs`, or by at most `rel`
+    /// relative to the larger magnitude. Infinities are only the same as themselves,
+    /// and NaNs are the same as each other if `nan_equal` is set.
+    ///
+    /// The relation is not transitive, so it cannot be hashed.
+    Tolerance { abs: f32, rel: f32, nan_equal: bool },
+}
+
+impl FloatIdentity {
+    /// The policy of hashing paths.
+    pub const HASHING: Self = Self::Bitwise;
+
+    /// The policy of semantic diffs.
+    pub const SEMANTIC: Self = Self::NanEqualNan {
+        canonicalize_payload: true,
+    };
+
+    /// Returns true if the floats are the same under the policy.
+    pub fn eq_f32(&self, lhs: f32, rhs: f32) -> bool {
+        match *self {
+            Self::Bitwise => lhs.to_bits() == rhs.to_bits(),
+            Self::NanEqualNan {
+                canonicalize_payload,
+            } => match (lhs.is_nan(), rhs.is_nan()) {
+                (true, true) => canonicalize_payload || lhs.to_bits() == rhs.to_bits(),
+                (false, false) => lhs == rhs,
+                _ => false,
+            },
+            Self::Tolerance {
+                abs,
+                rel,
+                nan_equal,
+            } => match (lhs.is_nan(), rhs.is_nan()) {
+                (true, true) => nan_equal,
+                (false, false) if lhs == rhs => true,
+                (false, false) if lhs.is_infinite() || rhs.is_infinite() => false,
+                (false, false) => {
+                    let diff = (lhs - rhs).abs();
+                    diff <= abs || diff <= rel * lhs.abs().max(rhs.abs())
+                }
+                _ => false,
+            },
+        }
+    }
+
+    /// The bits hashed for the float, equal for floats the same under the policy.
+    ///
+    /// It fails for the [Tolerance](FloatIdentity::Tolerance) policy.
+    pub fn canonical_bits(&self, value: f32) -> Result<u32> {
+        self.check_hashable()?;
+        Ok(match *self {
+            Self::NanEqualNan {
+                canonicalize_payload: true,
+            } if value.is_nan() => f32::NAN.to_bits(),
+            Self::NanEqualNan { .. } if value == 0.0 => 0,
+            _ => value.to_bits(),
+        })
+    }
+
+    fn check_hashable(&self) -> Result<()> {
+        match self {
+            Self::Tolerance { .. } => Err(Error::invalid_argument(
+                "floats compared with a tolerance cannot be hashed",
+            )),
+            Self::Bitwise | Self::NanEqualNan { .. } => Ok(()),
+        }
+    }
+}
+
+impl Feature {
+    /// Returns true if the features have the same kind and values, comparing floats by
+    /// the policy.
+    pub fn eq_with(&self, other: &Feature, identity: FloatIdentity) -> bool {
+        match (&self.kind, &other.kind) {
+            (Some(Kind::FloatList(lhs)), Some(Kind::FloatList(rhs))) => {
+                lhs.value.len() == rhs.value.len()
+                    && lhs
+                        .value
+                        .iter()
+                        .zip(&rhs.value)
+                        .all(|(&lhs, &rhs)| identity.eq_f32(lhs, rhs))
+            }
+            (lhs, rhs) => lhs == rhs,
+        }
+    }
+
+    /// The 64-bit content hash of the feature, equal for features equal by
+    /// [eq_with](Feature::eq_with) under the same policy.
+    ///
+    /// The hash is stable across versions of this crate. It fails for the
+    /// [Tolerance](FloatIdentity::Tolerance) policy.
+    pub fn content_hash(&self, identity: FloatIdentity) -> Result<u64> {
+        identity.check_hashable()?;
+        let mut hasher = Xxh3::new();
+        hash_feature(&mut hasher, self, identity)?;
+        Ok(hasher.digest())
+    }
+}
+
+/// The change of a feature between two examples.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeatureChange {
+    /// The feature is only in the other example.
+    Added(Feature),
+    /// The feature is only in this example.
+    Removed(Feature),
+    Changed {
+        from: Feature,
+        to: Feature,
+    },
+}
+
+/// The difference of a feature between two examples.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureDiff {
+    pub key: String,
+    pub change: FeatureChange,
+}
+
+impl Example {
+    /// Returns true if the examples have the same features, comparing floats by the
+    /// policy.
+    pub fn eq_with(&self, other: &Example, identity: FloatIdentity) -> bool {
+        let lhs = self.features.as_ref().map(|features| &features.feature);
+        let rhs = other.features.as_ref().map(|features| &features.feature);
+        let len = |map: Option<&HashMap<String, Feature>>| map.map_or(0, |map| map.len());
+        len(lhs) == len(rhs)
+            && lhs.into_iter().flatten().all(|(key, feature)| {
+                rhs.and_then(|rhs| rhs.get(key))
+                    .is_some_and(|other| feature.eq_with(other, identity))
+            })
+    }
+
+    /// List the features differing from the other example, sorted by key.
+    ///
+    /// Floats are compared by the policy, usually [SEMANTIC](FloatIdentity::SEMANTIC),
+    /// so that NaNs do not show up as spurious changes.
+    pub fn diff(&self, other: &Example, identity: FloatIdentity) -> Vec<FeatureDiff> {
+        let empty = HashMap::new();
+        let lhs = self
+            .features
+            .as_ref()
+            .map_or(&empty, |features| &features.feature);
+        let rhs = other
+            .features
+            .as_ref()
+            .map_or(&empty, |features| &features.feature);
+        let keys: BTreeSet<&String> = lhs.keys().chain(rhs.keys()).collect();
+
+        keys.into_iter()
+            .filter_map(|key| {
+                let change = match (lhs.get(key), rhs.get(key)) {
+                    (Some(from), Some(to)) if from.eq_with(to, identity) => return None,
+                    (Some(from), Some(to)) => FeatureChange::Changed {
+                        from: from.clone(),
+                        to: to.clone(),
+                    },
+                    (Some(from), None) => FeatureChange::Removed(from.clone()),
+                    (None, Some(to)) => FeatureChange::Added(to.clone()),
+                    (None, None) => unreachable!(),
+                };
+                Some(FeatureDiff {
+                    key: key.clone(),
+                    change,
+                })
+            })
+            .collect()
+    }
+
+    /// The 64-bit content hash of the example, equal for examples equal by
+    /// [eq_with](Example::eq_with) under the same policy.
+    ///
+    /// Features are hashed in key order, so the hash does not depend on the map order.
+    /// The hash is stable across versions of this crate. It fails for the
+    /// [Tolerance](FloatIdentity::Tolerance) policy.
+    pub fn content_hash(&self, identity: FloatIdentity) -> Result<u64> {
+        let mut features: Vec<_> = self
+            .features
+            .iter()
+            .flat_map(|features| &features.feature)
+            .collect();
+        features.sort_unstable_by_key(|(key, _)| *key);
+
+        identity.check_hashable()?;
+        let mut hasher = Xxh3::new();
+        hasher.update(&(features.len() as u64).to_le_bytes());
+        for (key, feature) in features {
+            hash_bytes(&mut hasher, key.as_bytes());
+            hash_feature(&mut hasher, feature, identity)?;
+        }
+        Ok(hasher.digest())
+    }
+}
+
+/// Remove examples equal to earlier ones under the policy, keeping the order.
+///
+/// It fails for the [Tolerance](FloatIdentity::Tolerance) policy.
+pub fn dedup_examples<I>(examples: I, identity: FloatIdentity) -> Result<Vec<Example>>
+where
+    I: IntoIterator<Item = Example>,
+{
+    let mut output: Vec<Example> = vec![];
+    let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+    for example in examples {
+        let same = seen.entry(example.content_hash(identity)?).or_default();
+        if !same
+            .iter()
+            .any(|&index| output[index].eq_with(&example, identity))
+        {
+            same.push(output.len());
+            output.push(example);
+        }
+    }
+    Ok(output)
+}
+
+/// Group examples by the feature of the key under the policy, in the order of first
+/// appearance. Examples without the feature form the group of `None`.
+///
+/// It fails for the [Tolerance](FloatIdentity::Tolerance) policy.
+pub fn group_examples_by<I>(
+    examples: I,
+    key: &str,
+    identity: FloatIdentity,
+) -> Result<Vec<(Option<Feature>, Vec<Example>)>>
+where
+    I: IntoIterator<Item = Example>,
+{
+    let mut groups: Vec<(Option<Feature>, Vec<Example>)> = vec![];
+    let mut seen: HashMap<Option<u64>, Vec<usize>> = HashMap::new();
+    for example in examples {
+        let feature = example
+            .features
+            .as_ref()
+            .and_then(|features| features.feature.get(key))
+            .cloned();
+        let hash = feature
+            .as_ref()
+            .map(|feature| feature.content_hash(identity))
+            .transpose()?;
+        let same = seen.entry(hash).or_default();
+        let group = same
+            .iter()
+            .copied()
+            .find(|&index| match (&groups[index].0, &feature) {
+                (Some(lhs), Some(rhs)) => lhs.eq_with(rhs, identity),
+                (lhs, rhs) => lhs.is_none() && rhs.is_none(),
+            });
+        match group {
+            Some(index) => groups[index].1.push(example),
+            None => {
+                same.push(groups.len());
+                groups.push((feature, vec![example]));
+            }
+        }
+    }
+    Ok(groups)
+}
+
+fn hash_bytes(hasher: &mut Xxh3, bytes: &[u8]) {
+    hasher.update(&(bytes.len() as u64).to_le_bytes());
+    hasher.update(bytes);
+}
+
+/// Hash the kind, the length and the values of the feature, with floats in the
+/// canonical bits of the policy.
+fn hash_feature(hasher: &mut Xxh3, feature: &Feature, identity: FloatIdentity) -> Result<()> {
+    match &feature.kind {
+        None => hasher.update(&[0]),
+        Some(Kind::BytesList(list)) => {
+            hasher.update(&[1]);
+            hasher.update(&(list.value.len() as u64).to_le_bytes());
+            list.value
+                .iter()
+                .for_each(|bytes| hash_bytes(hasher, bytes));
+        }
+        Some(Kind::FloatList(list)) => {
+            hasher.update(&[2]);
+            hasher.update(&(list.value.len() as u64).to_le_bytes());
+            for &value in &list.value {
+                hasher.update(&identity.canonical_bits(value)?.to_le_bytes());
+            }
+        }
+        Some(Kind::Int64List(list)) => {
+            hasher.update(&[3]);
+            hasher.update(&(list.value.len() as u64).to_le_bytes());
+            list.value
+                .iter()
+                .for_each(|value| hasher.update(&value.to_le_bytes()));
+        }
+    }
+    Ok(())
+}
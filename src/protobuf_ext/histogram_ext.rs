@@ -17,6 +17,10 @@ impl HistogramProto {
     ///
     /// - i == 0:  [f64::MIN]..`limit[0]`
     /// - i > 0:  `limit[i-1]`..`limit[i]`
+    ///
+    /// where the upper limits are exclusive as in TensorFlow, so a value equal to
+    /// `limit[i]` falls in the bucket `i + 1`. Values at or above the last limit are not
+    /// counted.
     pub fn new<'a, L>(bucket_limit: L) -> Result<Self>
     where
         L: Into<Cow<'a, [f64]>>,
@@ -46,18 +50,23 @@ impl HistogramProto {
     }
 
     /// Create a histogram with TensorFlow's default bucket limits.
+    ///
+    /// The positive limits grow by 10% from `1e-12` up to `1e20`, and are closed by
+    /// [f64::MAX]. The negative limits mirror them, with `0` in between.
     pub fn tf_default() -> Self {
         let pos_limits: Vec<_> = iter::successors(Some(1e-12), |prev| {
             let curr = *prev * 1.1;
             let ok = curr < 1e20;
-            ok.then(|| curr)
+            ok.then_some(curr)
         })
         .collect();
 
         let limits: Vec<_> = chain!(
+            [f64::MIN],
             pos_limits.iter().cloned().map(Neg::neg).rev(),
             [0.0],
             pos_limits.iter().cloned(),
+            [f64::MAX],
         )
         .collect();
 
@@ -107,13 +116,8 @@ impl HistogramProto {
             "the lengths of bucket_limit and bucket fields must be equal"
         );
 
-        let index = match self
-            .bucket_limit
-            .binary_search_by_key(&r64(value), |&limit| r64(limit))
-        {
-            Ok(index) => index,
-            Err(index) => index,
-        };
+        // the upper bound of the value, as by TensorFlow
+        let index = self.bucket_limit.partition_point(|&limit| limit <= value);
 
         if index < self.bucket_limit.len() {
             self.bucket[index] += count;
@@ -151,12 +155,65 @@ impl HistogramProto {
     }
 
     /// Create a histogram from an iterator of values, with TensorFlow's default bucket limits.
+    ///
+    /// Non-finite values, such as NaN and infinity, are rejected with an error. An empty
+    /// iterator gives an empty histogram, which [min](HistogramProto::min) is [f64::MAX]
+    /// and [max](HistogramProto::max) is [f64::MIN] as in TensorFlow.
     pub fn try_from_iter<T, I>(iter: I) -> Result<Self, Error>
     where
         T: ToPrimitive,
         I: IntoIterator<Item = T>,
     {
-        let mut histogram = Self::tf_default();
+        Self::tf_default().try_extend(iter)
+    }
+
+    /// Create a histogram from an iterator of values, with the bucket limits.
+    ///
+    /// See [new](HistogramProto::new) for the bucket limits, and
+    /// [try_from_iter](HistogramProto::try_from_iter) for the values.
+    pub fn try_from_iter_with_limits<'a, L, T, I>(bucket_limit: L, iter: I) -> Result<Self, Error>
+    where
+        L: Into<Cow<'a, [f64]>>,
+        T: ToPrimitive,
+        I: IntoIterator<Item = T>,
+    {
+        Self::new(bucket_limit)?.try_extend(iter)
+    }
+
+    /// Collapse each run of empty buckets into its last bucket, as TensorFlow encodes
+    /// histograms in summaries.
+    ///
+    /// A histogram without counts keeps a single empty bucket up to [f64::MAX].
+    pub fn collapse_empty_buckets(&self) -> Result<Self> {
+        let mut bucket_limit = vec![];
+        let mut bucket = vec![];
+        let mut in_empty_run = false;
+        for (limit, count) in self.try_iter()? {
+            if count > 0.0 || !in_empty_run {
+                bucket_limit.push(limit);
+                bucket.push(count);
+            } else {
+                *bucket_limit.last_mut().unwrap() = limit;
+            }
+            in_empty_run = count <= 0.0;
+        }
+        if bucket.is_empty() {
+            bucket_limit.push(f64::MAX);
+            bucket.push(0.0);
+        }
+        Ok(Self {
+            bucket_limit,
+            bucket,
+            ..self.clone()
+        })
+    }
+
+    fn try_extend<T, I>(self, iter: I) -> Result<Self>
+    where
+        T: ToPrimitive,
+        I: IntoIterator<Item = T>,
+    {
+        let mut histogram = self;
         iter.into_iter().try_for_each(|value| -> Result<_> {
             let value = <f64 as NumCast>::from(value)
                 .ok_or_else(|| Error::invalid_argument("invalid value"))?;
@@ -167,6 +224,11 @@ impl HistogramProto {
     }
 }
 
+/// Collect values into a histogram with TensorFlow's default bucket limits.
+///
+/// # Panics
+/// It panics on non-finite values. Use [try_from_iter](HistogramProto::try_from_iter) to
+/// handle them as errors.
 impl<A> FromIterator<A> for HistogramProto
 where
     A: ToPrimitive,
@@ -324,25 +386,12 @@ mod with_ndarray {
 #[cfg(feature = "with-tch")]
 mod with_tch {
     use super::*;
-    use std::slice;
+    use crate::protobuf_ext::tch_ext::{check_tensor, tensor_to_vec};
     use tch::{Kind, Tensor};
 
-    macro_rules! tensor_to_vec {
-        ($tensor:ident, $ty:ident) => {
-            unsafe {
-                let numel = $tensor.numel();
-                let mut data: Vec<$ty> = Vec::with_capacity(numel);
-                let slice = slice::from_raw_parts_mut(data.as_mut_ptr(), numel);
-                $tensor.copy_data(slice, numel);
-                data.set_len(numel);
-                data
-            }
-        };
-    }
-
     macro_rules! tensor_to_r64_vec {
-        ($tensor:ident, $ty:ident) => {{
-            tensor_to_vec!($tensor, $ty)
+        ($tensor:ident, $info:ident, $ty:ident) => {{
+            tensor_to_vec::<$ty>($tensor, $info.numel)?
                 .into_iter()
                 .map(|value| {
                     let value = <R64 as NumCast>::from(value).ok_or_else(|| {
@@ -356,19 +405,19 @@ mod with_tch {
 
     impl IntoHistogram for &Tensor {
         fn try_into_histogram(self) -> Result<HistogramProto> {
-            let kind = self.f_kind()?;
-            let values = match kind {
-                Kind::Uint8 => tensor_to_r64_vec!(self, u8)?,
-                Kind::Int8 => tensor_to_r64_vec!(self, i8)?,
-                Kind::Int16 => tensor_to_r64_vec!(self, i16)?,
-                Kind::Int => tensor_to_r64_vec!(self, i32)?,
-                Kind::Int64 => tensor_to_r64_vec!(self, i64)?,
-                // Kind::Half => tensor_to_r64_vec!(self, f16)?,
-                Kind::Float => tensor_to_r64_vec!(self, f32)?,
-                Kind::Double => tensor_to_r64_vec!(self, f64)?,
-                _ => {
+            let info = check_tensor(self, &[], &[])?;
+            let values = match info.kind {
+                Kind::Uint8 => tensor_to_r64_vec!(self, info, u8)?,
+                Kind::Int8 => tensor_to_r64_vec!(self, info, i8)?,
+                Kind::Int16 => tensor_to_r64_vec!(self, info, i16)?,
+                Kind::Int => tensor_to_r64_vec!(self, info, i32)?,
+                Kind::Int64 => tensor_to_r64_vec!(self, info, i64)?,
+                // Kind::Half => tensor_to_r64_vec!(self, info, f16)?,
+                Kind::Float => tensor_to_r64_vec!(self, info, f32)?,
+                Kind::Double => tensor_to_r64_vec!(self, info, f64)?,
+                kind => {
                     return Err(Error::conversion(format!(
-                        "unsupported tensor kind {:?}",
+                        "tensor kind: unsupported kind {:?}",
                         kind
                     )))
                 }
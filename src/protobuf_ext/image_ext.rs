@@ -29,6 +29,37 @@ impl ColorSpace {
     }
 }
 
+impl TryFrom<i32> for ColorSpace {
+    type Error = Error;
+
+    fn try_from(from: i32) -> Result<Self, Self::Error> {
+        let color_space = match from {
+            1 => ColorSpace::Luma,
+            2 => ColorSpace::LumaA,
+            3 => ColorSpace::Rgb,
+            4 => ColorSpace::Rgba,
+            5 => ColorSpace::DigitalYuv,
+            6 => ColorSpace::Bgra,
+            _ => return Err(Error::unknown_enum_value("ColorSpace", from)),
+        };
+        Ok(color_space)
+    }
+}
+
+impl Image {
+    /// Get the color space of the image.
+    ///
+    /// It returns an error if the raw `colorspace` value is not a known [ColorSpace].
+    pub fn color_space(&self) -> Result<ColorSpace> {
+        ColorSpace::try_from(self.colorspace)
+    }
+
+    /// Set the color space of the image.
+    pub fn set_color_space(&mut self, color_space: ColorSpace) {
+        self.colorspace = color_space as i32;
+    }
+}
+
 pub use into_image_list::*;
 mod into_image_list {
     use super::*;
@@ -55,16 +86,133 @@ mod into_image_list {
     }
 }
 
+/// Convert an image error, keeping the I/O errors.
+#[cfg(feature = "with-image")]
+fn image_error(error: image::ImageError) -> Error {
+    match error {
+        image::ImageError::IoError(error) => error.into(),
+        error => Error::conversion(format!("{:?}", error)),
+    }
+}
+
+#[cfg(feature = "with-image")]
+pub use with_image::*;
 #[cfg(feature = "with-image")]
 mod with_image {
     use super::*;
-    use crate::{error::Error, protobuf::summary::Image};
+    use crate::{
+        error::{ensure_argument, Error},
+        protobuf::summary::Image,
+    };
     use image::{
-        codecs::png::PngEncoder, flat::SampleLayout, ColorType, DynamicImage, FlatSamples,
-        ImageBuffer, ImageEncoder as _, Pixel,
+        codecs::{jpeg::JpegEncoder, png::PngEncoder},
+        flat::SampleLayout,
+        ColorType, DynamicImage, FlatSamples, ImageBuffer, ImageEncoder as _, PixelWithColorType,
     };
     use std::{io::Cursor, ops::Deref};
 
+    /// The encoding of the `encoded_image_string` of an [Image].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub enum ImageEncoding {
+        /// Lossless PNG, which is used by the `TryFrom` conversions.
+        #[default]
+        Png,
+        /// Lossy JPEG of the quality in 1..=100.
+        ///
+        /// JPEG has no alpha channel. Images with alpha are rejected, unless
+        /// `drop_alpha` is set, in which case the alpha channel is discarded and the
+        /// image is stored as [Luma](ColorSpace::Luma) or [Rgb](ColorSpace::Rgb).
+        Jpeg { quality: u8, drop_alpha: bool },
+    }
+
+    impl ImageEncoding {
+        /// JPEG of the quality, rejecting images with alpha.
+        pub fn jpeg(quality: u8) -> Self {
+            Self::Jpeg {
+                quality,
+                drop_alpha: false,
+            }
+        }
+    }
+
+    pub use into_image::*;
+    mod into_image {
+        use super::*;
+
+        /// Conversion to an [Image] of a chosen encoding.
+        pub trait IntoImage {
+            fn try_into_image_with(self, encoding: ImageEncoding) -> Result<Image>;
+        }
+    }
+
+    /// Encode 8-bit row-major samples of the color type to an image.
+    pub(crate) fn encode_image(
+        samples: &[u8],
+        width: u32,
+        height: u32,
+        color_type: ColorType,
+        encoding: ImageEncoding,
+    ) -> Result<Image> {
+        let mut cursor = Cursor::new(vec![]);
+        let color_space = match encoding {
+            ImageEncoding::Png => {
+                PngEncoder::new(&mut cursor)
+                    .write_image(samples, width, height, color_type)
+                    .map_err(super::image_error)?;
+                ColorSpace::try_from(color_type)?
+            }
+            ImageEncoding::Jpeg {
+                quality,
+                drop_alpha,
+            } => {
+                ensure_argument!(
+                    (1..=100).contains(&quality),
+                    "jpeg quality: expect a value in 1..=100, but get {}",
+                    quality
+                );
+                let (opaque_type, num_channels) = match color_type {
+                    ColorType::L8 => (ColorType::L8, 1),
+                    ColorType::Rgb8 => (ColorType::Rgb8, 3),
+                    ColorType::La8 => (ColorType::L8, 2),
+                    ColorType::Rgba8 => (ColorType::Rgb8, 4),
+                    _ => {
+                        return Err(Error::invalid_argument(format!(
+                            "jpeg color type: {:?} is not supported",
+                            color_type
+                        )));
+                    }
+                };
+                let opaque_samples;
+                let samples = if opaque_type == color_type {
+                    samples
+                } else {
+                    ensure_argument!(
+                        drop_alpha,
+                        "jpeg color type: {:?} has an alpha channel, which requires drop_alpha",
+                        color_type
+                    );
+                    opaque_samples = samples
+                        .chunks_exact(num_channels)
+                        .flat_map(|pixel| &pixel[..num_channels - 1])
+                        .copied()
+                        .collect::<Vec<_>>();
+                    &opaque_samples
+                };
+                JpegEncoder::new_with_quality(&mut cursor, quality)
+                    .write_image(samples, width, height, opaque_type)
+                    .map_err(super::image_error)?;
+                ColorSpace::try_from(opaque_type)?
+            }
+        };
+
+        Ok(Image {
+            height: height as i32,
+            width: width as i32,
+            colorspace: color_space as i32,
+            encoded_image_string: cursor.into_inner(),
+        })
+    }
+
     impl TryFrom<ColorType> for ColorSpace {
         type Error = Error;
 
@@ -88,21 +236,30 @@ mod with_image {
 
     // DynamicImage to image
 
+    impl IntoImage for &DynamicImage {
+        fn try_into_image_with(self, encoding: ImageEncoding) -> Result<Image> {
+            use DynamicImage::*;
+            match self {
+                ImageLuma8(buffer) => buffer.try_into_image_with(encoding),
+                ImageLumaA8(buffer) => buffer.try_into_image_with(encoding),
+                ImageRgb8(buffer) => buffer.try_into_image_with(encoding),
+                ImageRgba8(buffer) => buffer.try_into_image_with(encoding),
+                _ => Err(Error::conversion("unsupported image type")),
+            }
+        }
+    }
+
+    impl IntoImage for DynamicImage {
+        fn try_into_image_with(self, encoding: ImageEncoding) -> Result<Image> {
+            (&self).try_into_image_with(encoding)
+        }
+    }
+
     impl TryFrom<&DynamicImage> for Image {
         type Error = Error;
 
         fn try_from(from: &DynamicImage) -> Result<Self, Self::Error> {
-            use DynamicImage::*;
-            let image = match from {
-                ImageLuma8(buffer) => Self::try_from(buffer)?,
-                ImageLumaA8(buffer) => Self::try_from(buffer)?,
-                ImageRgb8(buffer) => Self::try_from(buffer)?,
-                ImageRgba8(buffer) => Self::try_from(buffer)?,
-                _ => {
-                    return Err(Error::conversion("unsupported image type"));
-                }
-            };
-            Ok(image)
+            from.try_into_image_with(ImageEncoding::Png)
         }
     }
 
@@ -116,13 +273,11 @@ mod with_image {
 
     // FlatSamples to image
 
-    impl<B> TryFrom<&FlatSamples<B>> for Image
+    impl<B> IntoImage for &FlatSamples<B>
     where
         B: AsRef<[u8]>,
     {
-        type Error = Error;
-
-        fn try_from(from: &FlatSamples<B>) -> Result<Self, Self::Error> {
+        fn try_into_image_with(self, encoding: ImageEncoding) -> Result<Image> {
             let FlatSamples {
                 layout:
                     SampleLayout {
@@ -133,29 +288,35 @@ mod with_image {
                     },
                 color_hint,
                 ..
-            } = *from;
+            } = *self;
             let color_type =
                 color_hint.ok_or_else(|| Error::conversion("color_hint must not be None"))?;
-            let colorspace = ColorSpace::try_from(color_type)?;
+            ColorSpace::try_from(color_type)?;
             let samples = (0..height)
                 .flat_map(|y| (0..width).flat_map(move |x| (0..channels).map(move |c| (y, x, c))))
-                .map(|(y, x, c)| *from.get_sample(c, x, y).unwrap())
+                .map(|(y, x, c)| *self.get_sample(c, x, y).unwrap())
                 .collect::<Vec<_>>();
+            encode_image(&samples, width, height, color_type, encoding)
+        }
+    }
 
-            let encoded_image_string = {
-                let mut cursor = Cursor::new(vec![]);
-                PngEncoder::new(&mut cursor)
-                    .write_image(&samples, width, height, color_type)
-                    .map_err(|err| Error::conversion(format!("{:?}", err)))?;
-                cursor.into_inner()
-            };
+    impl<B> IntoImage for FlatSamples<B>
+    where
+        B: AsRef<[u8]>,
+    {
+        fn try_into_image_with(self, encoding: ImageEncoding) -> Result<Image> {
+            (&self).try_into_image_with(encoding)
+        }
+    }
 
-            Ok(Image {
-                height: height as i32,
-                width: width as i32,
-                colorspace: colorspace as i32,
-                encoded_image_string,
-            })
+    impl<B> TryFrom<&FlatSamples<B>> for Image
+    where
+        B: AsRef<[u8]>,
+    {
+        type Error = Error;
+
+        fn try_from(from: &FlatSamples<B>) -> Result<Self, Self::Error> {
+            from.try_into_image_with(ImageEncoding::Png)
         }
     }
 
@@ -172,15 +333,28 @@ mod with_image {
 
     // ImageBuffer to image
 
+    impl<P, C> IntoImage for &ImageBuffer<P, C>
+    where
+        P: 'static + PixelWithColorType<Subpixel = u8>,
+        C: Deref<Target = [P::Subpixel]> + AsRef<[P::Subpixel]>,
+    {
+        fn try_into_image_with(self, encoding: ImageEncoding) -> Result<Image> {
+            // the flat samples of buffers carry no color hint
+            let mut samples = self.as_flat_samples();
+            samples.color_hint = Some(P::COLOR_TYPE);
+            samples.try_into_image_with(encoding)
+        }
+    }
+
     impl<P, C> TryFrom<&ImageBuffer<P, C>> for Image
     where
-        P: 'static + Pixel<Subpixel = u8>,
+        P: 'static + PixelWithColorType<Subpixel = u8>,
         C: Deref<Target = [P::Subpixel]> + AsRef<[P::Subpixel]>,
     {
         type Error = Error;
 
         fn try_from(from: &ImageBuffer<P, C>) -> Result<Self, Self::Error> {
-            Self::try_from(from.as_flat_samples())
+            from.try_into_image_with(ImageEncoding::Png)
         }
     }
 }
@@ -189,29 +363,17 @@ mod with_image {
 pub use with_tch::*;
 #[cfg(feature = "with-tch")]
 mod with_tch {
+    use super::with_image::encode_image;
     use super::*;
     use crate::{
         error::{ensure_argument, Error},
         protobuf::summary::Image,
+        protobuf_ext::tch_ext::{check_tensor, tensor_to_vec},
     };
-    use image::{codecs::png::PngEncoder, ColorType, ImageEncoder as _};
+    use image::ColorType;
     use itertools::Itertools as _;
-    use std::{io::Cursor, slice};
     use tch::{Kind, Tensor};
 
-    macro_rules! tensor_to_vec {
-        ($tensor:ident, $ty:ident) => {
-            unsafe {
-                let numel = $tensor.numel();
-                let mut data: Vec<$ty> = Vec::with_capacity(numel);
-                let slice = slice::from_raw_parts_mut(data.as_mut_ptr(), numel);
-                $tensor.copy_data(slice, numel);
-                data.set_len(numel);
-                data
-            }
-        };
-    }
-
     /// The order of 3-dimensional channel dimension.
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
     pub enum TchChannelOrder {
@@ -221,10 +383,48 @@ mod with_tch {
         HWC,
     }
 
+    /// Validate an image tensor, or a batch of image tensors if the rank is 4, before
+    /// any data is read.
+    fn check_image_tensor(
+        tensor: &Tensor,
+        color_space: ColorSpace,
+        order: TchChannelOrder,
+        ranks: &[usize],
+    ) -> Result<()> {
+        let info = check_tensor(tensor, ranks, &[Kind::Uint8, Kind::Float, Kind::Double])?;
+        let image_shape = &info.shape[info.shape.len() - 3..];
+        let (sc, sh, sw) = match order {
+            TchChannelOrder::CHW => (image_shape[0], image_shape[1], image_shape[2]),
+            TchChannelOrder::HWC => (image_shape[2], image_shape[0], image_shape[1]),
+        };
+
+        ensure_argument!(
+            matches!(
+                color_space,
+                ColorSpace::Luma | ColorSpace::Rgb | ColorSpace::Rgba
+            ),
+            "image color space: {:?} is not supported",
+            color_space
+        );
+        ensure_argument!(
+            color_space.num_channels() == sc,
+            "image channels: the color space {:?} expects {} channels, but get {}",
+            color_space,
+            color_space.num_channels(),
+            sc
+        );
+        ensure_argument!(
+            (1..=u32::MAX as usize).contains(&sh) && (1..=u32::MAX as usize).contains(&sw),
+            "image size: the height and width must be in 1..={}, but get {}x{}",
+            u32::MAX,
+            sh,
+            sw
+        );
+        Ok(())
+    }
+
     pub use tch_tensor_as_image::*;
     mod tch_tensor_as_image {
-        use crate::ensure_argument;
-
         use super::*;
 
         /// [tch]'s [Tensor] with additional image properties.
@@ -236,26 +436,14 @@ mod with_tch {
         }
 
         impl TchTensorAsImage {
+            /// Validate a 3-dimensional CPU tensor of kind `Uint8`, `Float` or `Double`
+            /// with channels matching the color space.
             pub fn new(
                 color_space: ColorSpace,
                 order: TchChannelOrder,
                 tensor: Tensor,
             ) -> Result<Self, Error> {
-                let (s1, s2, s3) = tensor.size3().map_err(|_| {
-                    Error::invalid_argument(format!(
-                        "expect a 3-dimensional tensor, but get {} dimensions",
-                        tensor.dim()
-                    ))
-                })?;
-                let (sc, _sh, _sw) = match order {
-                    TchChannelOrder::CHW => (s1, s2, s3),
-                    TchChannelOrder::HWC => (s3, s1, s2),
-                };
-
-                ensure_argument!(
-                    color_space.num_channels() == sc as usize,
-                    "the color space and channel size mismatch"
-                );
+                check_image_tensor(&tensor, color_space, order, &[3])?;
 
                 Ok(Self {
                     color_space,
@@ -266,19 +454,25 @@ mod with_tch {
         }
 
         // to Image
-        impl TryFrom<TchTensorAsImage> for Image {
-            type Error = Error;
-
-            fn try_from(from: TchTensorAsImage) -> Result<Self, Self::Error> {
+        impl IntoImage for TchTensorAsImage {
+            fn try_into_image_with(self, encoding: ImageEncoding) -> Result<Image> {
                 use TchChannelOrder as O;
 
                 // CHW to HWC
-                let hwc_tensor = match from.order {
-                    O::HWC => from.tensor.shallow_clone(),
-                    O::CHW => from.tensor.f_permute(&[1, 2, 0])?,
+                let hwc_tensor = match self.order {
+                    O::HWC => self.tensor.shallow_clone(),
+                    O::CHW => self.tensor.f_permute(&[1, 2, 0])?,
                 };
 
-                super::hwc_tensor_to_image(&hwc_tensor, from.color_space)
+                super::hwc_tensor_to_image(&hwc_tensor, self.color_space, encoding)
+            }
+        }
+
+        impl TryFrom<TchTensorAsImage> for Image {
+            type Error = Error;
+
+            fn try_from(from: TchTensorAsImage) -> Result<Self, Self::Error> {
+                from.try_into_image_with(ImageEncoding::Png)
             }
         }
     }
@@ -296,21 +490,24 @@ mod with_tch {
         }
 
         impl TchTensorAsImageList {
+            /// Validate a 3-dimensional image tensor or a 4-dimensional batch of image
+            /// tensors, with the requirements of [TchTensorAsImage::new].
             pub fn new(
                 color_space: ColorSpace,
                 order: TchChannelOrder,
                 tensor: Tensor,
             ) -> Result<Self, Error> {
+                check_image_tensor(&tensor, color_space, order, &[3, 4])?;
+
                 Ok(Self {
                     color_space,
                     order,
                     tensor,
                 })
             }
-        }
 
-        impl IntoImageList for TchTensorAsImageList {
-            fn into_image_list(self) -> Result<Vec<Image>> {
+            /// Encode the images of the encoding.
+            pub fn into_image_list_with(self, encoding: ImageEncoding) -> Result<Vec<Image>> {
                 use TchChannelOrder as O;
 
                 let Self {
@@ -321,21 +518,11 @@ mod with_tch {
 
                 let images = match *tensor.size() {
                     [_, _, _] => {
-                        let image =
-                            TchTensorAsImage::new(color_space, order, tensor)?.try_into()?;
+                        let image = TchTensorAsImage::new(color_space, order, tensor)?
+                            .try_into_image_with(encoding)?;
                         vec![image]
                     }
-                    [bsize, s1, s2, s3] => {
-                        let (sc, _sh, _sw) = match order {
-                            TchChannelOrder::CHW => (s1, s2, s3),
-                            TchChannelOrder::HWC => (s3, s1, s2),
-                        };
-
-                        ensure_argument!(
-                            color_space.num_channels() == sc as usize,
-                            "the color space and channel size mismatch"
-                        );
-
+                    [bsize, _, _, _] => {
                         let bhwc_tensor = match order {
                             O::HWC => tensor.shallow_clone(),
                             O::CHW => tensor.f_permute(&[0, 2, 3, 1])?,
@@ -344,7 +531,8 @@ mod with_tch {
                         let images: Vec<Image> = (0..bsize)
                             .map(|bidx| -> Result<Image> {
                                 let hwc_tensor = bhwc_tensor.f_select(0, bidx)?;
-                                let image = super::hwc_tensor_to_image(&hwc_tensor, color_space)?;
+                                let image =
+                                    super::hwc_tensor_to_image(&hwc_tensor, color_space, encoding)?;
                                 Ok(image)
                             })
                             .try_collect()?;
@@ -352,7 +540,7 @@ mod with_tch {
                     }
                     _ => {
                         return Err(Error::invalid_argument(format!(
-                            "expect 3 or 4 dimensions, but get {} dimensions",
+                            "tensor rank: expect 3 or 4 dimensions, but get {} dimensions",
                             tensor.dim()
                         )));
                     }
@@ -361,44 +549,41 @@ mod with_tch {
                 Ok(images)
             }
         }
+
+        impl IntoImageList for TchTensorAsImageList {
+            fn into_image_list(self) -> Result<Vec<Image>> {
+                self.into_image_list_with(ImageEncoding::Png)
+            }
+        }
     }
 
-    fn hwc_tensor_to_image(hwc_tensor: &Tensor, color_space: ColorSpace) -> Result<Image> {
+    /// Encode a [checked](check_image_tensor) HWC tensor.
+    fn hwc_tensor_to_image(
+        hwc_tensor: &Tensor,
+        color_space: ColorSpace,
+        encoding: ImageEncoding,
+    ) -> Result<Image> {
         use ColorSpace as S;
 
-        debug_assert_eq!(hwc_tensor.dim(), 3);
-        let (nh, nw, _nc) = hwc_tensor.size3().unwrap();
+        let (nh, nw, _nc) = hwc_tensor.size3()?;
 
         // normalize values to [0, 255]
         let normalized_tensor = normalized_tensor(hwc_tensor)?;
 
         // encode image
-        let encoded_image_string = {
-            let samples = tensor_to_vec!(normalized_tensor, u8);
-            let color_type = match color_space {
-                S::Luma => ColorType::L8,
-                S::Rgb => ColorType::Rgb8,
-                S::Rgba => ColorType::Rgba8,
-                _ => {
-                    return Err(Error::invalid_argument(format!(
-                        "the color space {:?} is not supported",
-                        color_space
-                    )));
-                }
-            };
-            let mut cursor = Cursor::new(vec![]);
-            PngEncoder::new(&mut cursor)
-                .write_image(&samples, nw as u32, nh as u32, color_type)
-                .map_err(|err| Error::conversion(format!("{:?}", err)))?;
-            cursor.into_inner()
+        let samples: Vec<u8> = tensor_to_vec(&normalized_tensor, normalized_tensor.numel())?;
+        let color_type = match color_space {
+            S::Luma => ColorType::L8,
+            S::Rgb => ColorType::Rgb8,
+            S::Rgba => ColorType::Rgba8,
+            _ => {
+                return Err(Error::invalid_argument(format!(
+                    "the color space {:?} is not supported",
+                    color_space
+                )));
+            }
         };
-
-        Ok(Image {
-            height: nh as i32,
-            width: nw as i32,
-            colorspace: color_space as i32,
-            encoded_image_string,
-        })
+        encode_image(&samples, nw as u32, nh as u32, color_type, encoding)
     }
 
     fn normalized_tensor(tensor: &Tensor) -> Result<Tensor> {
@@ -410,11 +595,19 @@ mod with_tch {
                 // determine the scale and offset by min/max values
                 let valid_values_mask = tensor.f_isfinite()?;
                 let valid_values = tensor.f_masked_select(&valid_values_mask)?;
-                let min_value = f64::from(valid_values.f_min()?);
-                let max_value = f64::from(valid_values.f_max()?);
+                ensure_argument!(
+                    valid_values.numel() > 0,
+                    "tensor values: expect at least one finite value"
+                );
+                let min_value = valid_values.f_min()?.f_double_value(&[])?;
+                let max_value = valid_values.f_max()?.f_double_value(&[])?;
 
                 let (scale, offset) = if min_value >= 0.0 {
-                    let scale = 255.0 / max_value;
+                    let scale = if max_value > 0.0 {
+                        255.0 / max_value
+                    } else {
+                        0.0
+                    };
                     let offset = 0.0;
                     (scale, offset)
                 } else {
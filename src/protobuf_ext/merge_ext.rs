@@ -0,0 +1,65 @@
+use crate::{
+    error::{Error, Result},
+    protobuf::Example,
+};
+
+/// The policy to resolve keys present in both examples of [Example::merge] with
+/// different features.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum MergePolicy {
+    /// Return an error on collision.
+    #[default]
+    Error,
+    /// Keep the feature of the merged-into example.
+    KeepSelf,
+    /// Keep the feature of the other example.
+    KeepOther,
+    /// Insert all features of the other example under keys with the prefix. A
+    /// collision of the prefixed keys is an error.
+    PrefixOther(String),
+}
+
+impl Example {
+    /// Merge the features of another example into this one.
+    ///
+    /// Keys present in both examples with equal features, such as the key of a join,
+    /// are not collisions. Other collisions are resolved by the policy. On error, the
+    /// example is left unchanged.
+    pub fn merge(&mut self, other: Example, policy: &MergePolicy) -> Result<()> {
+        let features = &mut self.features.get_or_insert_with(Default::default).feature;
+        let other = other
+            .features
+            .map(|features| features.feature)
+            .unwrap_or_default();
+        let other: Vec<_> = match policy {
+            MergePolicy::PrefixOther(prefix) => other
+                .into_iter()
+                .map(|(key, feature)| (format!("{}{}", prefix, key), feature))
+                .collect(),
+            _ => other.into_iter().collect(),
+        };
+
+        let strict = matches!(policy, MergePolicy::Error | MergePolicy::PrefixOther(_));
+        if strict {
+            let mut collisions: Vec<_> = other
+                .iter()
+                .filter(|(key, feature)| features.get(key).is_some_and(|own| own != feature))
+                .map(|(key, _)| key.as_str())
+                .collect();
+            if !collisions.is_empty() {
+                collisions.sort_unstable();
+                return Err(Error::conversion(format!(
+                    "the merged examples have different features under keys {:?}",
+                    collisions
+                )));
+            }
+        }
+        for (key, feature) in other {
+            if *policy == MergePolicy::KeepSelf && features.contains_key(&key) {
+                continue;
+            }
+            features.insert(key, feature);
+        }
+        Ok(())
+    }
+}
@@ -1,13 +1,46 @@
 //! Extension to ProtocolBuffer types.
 
+#[cfg(feature = "with-image")]
+mod box_ext;
+mod checksum_ext;
 mod example_ext;
+mod feature_config_ext;
 mod feature_ext;
+mod float_identity_ext;
+#[cfg(feature = "proto-summary")]
 mod histogram_ext;
+#[cfg(feature = "proto-summary")]
 mod image_ext;
+mod merge_ext;
+mod namespace_ext;
+#[cfg(feature = "with-ndarray")]
+mod ndarray_ext;
+mod proto_ext;
+mod ragged_ext;
+mod sequence_example_ext;
+#[cfg(feature = "proto-summary")]
 mod summary_ext;
+#[cfg(feature = "with-tch")]
+mod tch_ext;
 mod tensor_ext;
+#[cfg(feature = "proto-graph")]
+mod variable_ext;
 
+#[cfg(feature = "with-image")]
+pub use box_ext::*;
+pub use checksum_ext::*;
+pub use example_ext::{AccessorConfig, FeatureProjection};
 pub use feature_ext::*;
+pub use float_identity_ext::*;
+#[cfg(feature = "proto-summary")]
 pub use histogram_ext::*;
+#[cfg(feature = "proto-summary")]
 pub use image_ext::*;
+pub use merge_ext::*;
+pub use namespace_ext::*;
+pub use proto_ext::*;
+pub use ragged_ext::*;
+pub use sequence_example_ext::*;
+#[cfg(feature = "with-tch")]
+pub use tch_ext::MAX_TENSOR_BYTES;
 pub use tensor_ext::*;
@@ -0,0 +1,351 @@
+use crate::{
+    error::{Error, Result},
+    protobuf::{feature::Kind, Example, Feature},
+};
+use std::{collections::BTreeMap, fmt::Write as _};
+
+/// The default delimiter of namespaced feature keys, such as `user/profile/age`.
+pub const DEFAULT_NAMESPACE_DELIMITER: char = '/';
+
+impl Example {
+    /// View the features under a namespace delimited by
+    /// [DEFAULT_NAMESPACE_DELIMITER].
+    ///
+    /// ```rust
+    /// # fn main() -> tfrecord::Result<()> {
+    /// use tfrecord::ExampleBuilder;
+    ///
+    /// let example = ExampleBuilder::new()
+    ///     .namespace("user/profile")
+    ///     .push_f32s("age", &[31.0])
+    ///     .build();
+    /// let profile = example.namespace("user/profile");
+    /// assert_eq!(profile.get_f32s("age")?, &[31.0]);
+    /// assert_eq!(example.get_f32s("user/profile/age")?, &[31.0]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn namespace(&self, prefix: &str) -> NamespaceView<'_> {
+        self.namespace_with_delimiter(prefix, DEFAULT_NAMESPACE_DELIMITER)
+    }
+
+    /// View the features under a namespace with a custom delimiter.
+    pub fn namespace_with_delimiter(&self, prefix: &str, delimiter: char) -> NamespaceView<'_> {
+        NamespaceView {
+            example: self,
+            prefix: namespace_prefix(prefix, delimiter),
+            delimiter,
+        }
+    }
+
+    /// Count the features per first-level namespace delimited by
+    /// [DEFAULT_NAMESPACE_DELIMITER].
+    ///
+    /// Keys without the delimiter belong to no namespace and are not counted.
+    pub fn namespaces(&self) -> BTreeMap<&str, usize> {
+        self.namespaces_with_delimiter(DEFAULT_NAMESPACE_DELIMITER)
+    }
+
+    /// Count the features per first-level namespace with a custom delimiter.
+    pub fn namespaces_with_delimiter(&self, delimiter: char) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for key in self.keys() {
+            if let Some((namespace, _)) = key.split_once(delimiter) {
+                *counts.entry(namespace).or_default() += 1;
+            }
+        }
+        counts
+    }
+
+    /// Format the features in JSON, nesting objects by the namespaces of keys.
+    ///
+    /// The key `user/profile/age` becomes `{"user": {"profile": {"age": ...}}}`.
+    /// Features are formatted as `{"int64_list": [...]}`, `{"float_list": [...]}` or
+    /// `{"bytes_list": [...]}`, and features without values as `null`. It fails if a
+    /// key is also a namespace of another key, or if a value is not representable in
+    /// JSON, namely non-finite floats and non-UTF-8 bytes.
+    pub fn restructure_to_nested_json(&self, delimiter: char) -> Result<String> {
+        let mut root = JsonNode::Object(BTreeMap::new());
+        for (key, feature) in self.features.iter().flat_map(|features| &features.feature) {
+            let mut node = &mut root;
+            let mut segments = key.split(delimiter).peekable();
+            while let Some(segment) = segments.next() {
+                let children = match node {
+                    JsonNode::Object(children) => children,
+                    JsonNode::Leaf(_) => return Err(json_conflict(key)),
+                };
+                if segments.peek().is_none() {
+                    if children.insert(segment, JsonNode::Leaf(feature)).is_some() {
+                        return Err(json_conflict(key));
+                    }
+                    break;
+                }
+                node = children
+                    .entry(segment)
+                    .or_insert_with(|| JsonNode::Object(BTreeMap::new()));
+            }
+        }
+
+        let mut json = String::new();
+        root.write(&mut json)?;
+        Ok(json)
+    }
+
+    fn keys(&self) -> impl Iterator<Item = &str> {
+        self.features
+            .iter()
+            .flat_map(|features| features.feature.keys())
+            .map(String::as_str)
+    }
+}
+
+/// The features of an [Example] under a namespace.
+///
+/// Keys given to the view are relative to the namespace, and resolved by joining the
+/// namespace and the key with the delimiter. The view never alters the resolved key,
+/// so keys with the delimiter at unexpected places, such as `a//b`, are reachable
+/// either from the raw accessors of [Example] or by the exact relative key.
+#[derive(Debug, Clone)]
+pub struct NamespaceView<'a> {
+    example: &'a Example,
+    /// The namespace with a trailing delimiter, or empty for the root.
+    prefix: String,
+    delimiter: char,
+}
+
+impl<'a> NamespaceView<'a> {
+    /// The resolved key of a relative key.
+    pub fn resolve(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+
+    /// View a nested namespace.
+    pub fn namespace(&self, prefix: &str) -> NamespaceView<'a> {
+        self.example
+            .namespace_with_delimiter(&self.resolve(prefix), self.delimiter)
+    }
+
+    /// Iterate the relative keys and features under the namespace.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, &'a Feature)> + '_ {
+        self.example
+            .features
+            .iter()
+            .flat_map(|features| &features.feature)
+            .filter_map(move |(key, feature)| {
+                Some((key.strip_prefix(self.prefix.as_str())?, feature))
+            })
+    }
+
+    /// Returns true if the namespace has no features.
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&'a Feature> {
+        self.example
+            .features
+            .as_ref()
+            .and_then(|features| features.feature.get(&self.resolve(key)))
+    }
+
+    pub fn get_f32s(&self, key: &str) -> Result<&'a [f32]> {
+        self.example.get_f32s(&self.resolve(key))
+    }
+
+    pub fn get_i64s(&self, key: &str) -> Result<&'a [i64]> {
+        self.example.get_i64s(&self.resolve(key))
+    }
+
+    pub fn get_bytes(&self, key: &str) -> Result<&'a [Vec<u8>]> {
+        self.example.get_bytes(&self.resolve(key))
+    }
+
+    pub fn get_bools(&self, key: &str) -> Result<Vec<bool>> {
+        self.example.get_bools(&self.resolve(key))
+    }
+
+    pub fn get_u8s(&self, key: &str) -> Result<&'a [u8]> {
+        self.example.get_u8s(&self.resolve(key))
+    }
+}
+
+/// The builder of [Example]s with namespaced keys.
+///
+/// Features are pushed under the current [namespace](ExampleBuilder::namespace), which
+/// is the root initially.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExampleBuilder {
+    example: Example,
+    prefix: String,
+    delimiter: char,
+}
+
+impl Default for ExampleBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExampleBuilder {
+    /// Build with the [DEFAULT_NAMESPACE_DELIMITER].
+    pub fn new() -> Self {
+        Self::with_delimiter(DEFAULT_NAMESPACE_DELIMITER)
+    }
+
+    pub fn with_delimiter(delimiter: char) -> Self {
+        Self {
+            example: Example::empty(),
+            prefix: String::new(),
+            delimiter,
+        }
+    }
+
+    /// Push subsequent features under the namespace. An empty namespace is the root.
+    pub fn namespace(mut self, prefix: &str) -> Self {
+        self.prefix = namespace_prefix(prefix, self.delimiter);
+        self
+    }
+
+    /// Push subsequent features under the root namespace.
+    pub fn root(self) -> Self {
+        self.namespace("")
+    }
+
+    pub fn push_feature(mut self, key: &str, feature: Feature) -> Self {
+        let key = format!("{}{}", self.prefix, key);
+        self.example.insert_feature(key, feature);
+        self
+    }
+
+    pub fn push_f32s(self, key: &str, values: &[f32]) -> Self {
+        self.push_feature(key, Feature::from_f32_list(values))
+    }
+
+    pub fn push_i64s(self, key: &str, values: &[i64]) -> Self {
+        self.push_feature(key, Feature::from_i64_list(values))
+    }
+
+    pub fn push_bytes(self, key: &str, values: Vec<Vec<u8>>) -> Self {
+        self.push_feature(key, Feature::from_bytes_list(values))
+    }
+
+    pub fn push_bools(self, key: &str, values: &[bool]) -> Result<Self> {
+        Ok(self.push_feature(key, Feature::from_bools(values)?))
+    }
+
+    pub fn push_u8s(self, key: &str, values: &[u8]) -> Self {
+        self.push_feature(key, Feature::from_u8s(values))
+    }
+
+    /// Push a ragged feature of rows by the default [RaggedConfig](crate::RaggedConfig).
+    pub fn push_ragged_f32s(self, key: &str, rows: &[&[f32]]) -> Self {
+        let (values, row_lengths) = super::ragged_ext::ragged_features(rows);
+        let lengths_key = crate::RaggedConfig::default().row_lengths_key(key);
+        self.push_feature(&lengths_key, row_lengths)
+            .push_feature(key, values)
+    }
+
+    pub fn build(self) -> Example {
+        self.example
+    }
+}
+
+/// The prefix of keys under a namespace. A trailing delimiter is appended unless
+/// given, so that `a/` and `a` are the same namespace.
+fn namespace_prefix(prefix: &str, delimiter: char) -> String {
+    let mut prefix = prefix.to_string();
+    if !prefix.is_empty() && !prefix.ends_with(delimiter) {
+        prefix.push(delimiter);
+    }
+    prefix
+}
+
+enum JsonNode<'a> {
+    Object(BTreeMap<&'a str, JsonNode<'a>>),
+    Leaf(&'a Feature),
+}
+
+impl JsonNode<'_> {
+    fn write(&self, json: &mut String) -> Result<()> {
+        match self {
+            Self::Object(children) => {
+                json.push('{');
+                for (index, (key, child)) in children.iter().enumerate() {
+                    if index > 0 {
+                        json.push(',');
+                    }
+                    write_json_string(json, key);
+                    json.push(':');
+                    child.write(json)?;
+                }
+                json.push('}');
+            }
+            Self::Leaf(feature) => match &feature.kind {
+                None => json.push_str("null"),
+                Some(Kind::Int64List(list)) => {
+                    json.push_str("{\"int64_list\":[");
+                    for (index, value) in list.value.iter().enumerate() {
+                        if index > 0 {
+                            json.push(',');
+                        }
+                        write!(json, "{}", value).unwrap();
+                    }
+                    json.push_str("]}");
+                }
+                Some(Kind::FloatList(list)) => {
+                    json.push_str("{\"float_list\":[");
+                    for (index, value) in list.value.iter().enumerate() {
+                        if !value.is_finite() {
+                            return Err(Error::conversion(format!(
+                                "the float {} is not representable in JSON",
+                                value
+                            )));
+                        }
+                        if index > 0 {
+                            json.push(',');
+                        }
+                        write!(json, "{}", value).unwrap();
+                    }
+                    json.push_str("]}");
+                }
+                Some(Kind::BytesList(list)) => {
+                    json.push_str("{\"bytes_list\":[");
+                    for (index, value) in list.value.iter().enumerate() {
+                        let value = std::str::from_utf8(value).map_err(|_| {
+                            Error::conversion("the bytes are not representable in JSON as UTF-8")
+                        })?;
+                        if index > 0 {
+                            json.push(',');
+                        }
+                        write_json_string(json, value);
+                    }
+                    json.push_str("]}");
+                }
+            },
+        }
+        Ok(())
+    }
+}
+
+fn write_json_string(json: &mut String, text: &str) {
+    json.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => write!(json, "\\u{:04x}", ch as u32).unwrap(),
+            ch => json.push(ch),
+        }
+    }
+    json.push('"');
+}
+
+fn json_conflict(key: &str) -> Error {
+    Error::conversion(format!(
+        "the key '{}' conflicts with a namespace of the same name",
+        key
+    ))
+}
@@ -0,0 +1,194 @@
+//! Conversions between features and [ndarray] arrays.
+//!
+//! Features are flat lists, so arrays are flattened in row-major order, and restored
+//! with a declared shape whose number of elements must match the length of the list.
+
+use crate::{
+    error::{Error, Result},
+    protobuf::{BytesList, Example, Feature, FloatList, Int64List},
+};
+use ndarray::{Array1, ArrayD, ArrayView, Dimension, IxDyn};
+use std::collections::HashMap;
+
+impl TryFrom<&FloatList> for Array1<f32> {
+    type Error = Error;
+
+    fn try_from(list: &FloatList) -> Result<Self, Self::Error> {
+        Ok(Array1::from(list.value.clone()))
+    }
+}
+
+impl TryFrom<&Int64List> for Array1<i64> {
+    type Error = Error;
+
+    fn try_from(list: &Int64List) -> Result<Self, Self::Error> {
+        Ok(Array1::from(list.value.clone()))
+    }
+}
+
+impl TryFrom<&BytesList> for Array1<Vec<u8>> {
+    type Error = Error;
+
+    fn try_from(list: &BytesList) -> Result<Self, Self::Error> {
+        Ok(Array1::from(list.value.clone()))
+    }
+}
+
+impl<D> TryFrom<ArrayView<'_, f32, D>> for Feature
+where
+    D: Dimension,
+{
+    type Error = Error;
+
+    fn try_from(array: ArrayView<'_, f32, D>) -> Result<Self, Self::Error> {
+        Ok(Feature::from_f32_iter(array.iter().copied()))
+    }
+}
+
+impl<D> TryFrom<ArrayView<'_, i64, D>> for Feature
+where
+    D: Dimension,
+{
+    type Error = Error;
+
+    fn try_from(array: ArrayView<'_, i64, D>) -> Result<Self, Self::Error> {
+        Ok(Feature::from_i64_iter(array.iter().copied()))
+    }
+}
+
+impl<D> TryFrom<ArrayView<'_, Vec<u8>, D>> for Feature
+where
+    D: Dimension,
+{
+    type Error = Error;
+
+    fn try_from(array: ArrayView<'_, Vec<u8>, D>) -> Result<Self, Self::Error> {
+        Ok(Feature::from_bytes_iter(array.iter().cloned()))
+    }
+}
+
+impl Feature {
+    /// Restore a `FloatList` feature to an array of the shape.
+    ///
+    /// ```rust
+    /// # fn main() -> tfrecord::Result<()> {
+    /// use ndarray::{array, ArrayD};
+    /// use tfrecord::Feature;
+    ///
+    /// let array = array![[1f32, 2.0, 3.0], [4.0, 5.0, 6.0]];
+    /// let feature = Feature::try_from(array.view())?;
+    /// assert_eq!(feature.as_f32_list(), Some(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0][..]));
+    ///
+    /// let restored: ArrayD<f32> = feature.to_f32_array(&[2, 3])?;
+    /// assert_eq!(restored, array.into_dyn());
+    ///
+    /// // the shape must hold as many elements as the list
+    /// assert!(feature.to_f32_array(&[4, 2]).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_f32_array(&self, shape: &[usize]) -> Result<ArrayD<f32>> {
+        let values = self
+            .as_f32_list()
+            .ok_or_else(|| Error::conversion("the feature is not a FloatList"))?;
+        to_array(values, shape, "FloatList")
+    }
+
+    /// Restore an `Int64List` feature to an array of the shape.
+    pub fn to_i64_array(&self, shape: &[usize]) -> Result<ArrayD<i64>> {
+        let values = self
+            .as_i64_list()
+            .ok_or_else(|| Error::conversion("the feature is not an Int64List"))?;
+        to_array(values, shape, "Int64List")
+    }
+
+    /// Restore a `BytesList` feature to an array of the shape.
+    pub fn to_bytes_array(&self, shape: &[usize]) -> Result<ArrayD<Vec<u8>>> {
+        let values = self
+            .as_bytes_list()
+            .ok_or_else(|| Error::conversion("the feature is not a BytesList"))?;
+        to_array(values, shape, "BytesList")
+    }
+}
+
+impl Example {
+    /// Restore the `FloatList` features named in the shape map to arrays of their shapes.
+    ///
+    /// Features absent from the map are ignored. Every feature in the map must exist.
+    pub fn to_f32_arrays(
+        &self,
+        shapes: &HashMap<String, Vec<usize>>,
+    ) -> Result<HashMap<String, ArrayD<f32>>> {
+        to_arrays(shapes, |key, shape| {
+            to_array(
+                self.get_f32s(key)?,
+                shape,
+                format_args!("feature '{}'", key),
+            )
+        })
+    }
+
+    /// Restore the `Int64List` features named in the shape map to arrays of their shapes.
+    ///
+    /// Features absent from the map are ignored. Every feature in the map must exist.
+    pub fn to_i64_arrays(
+        &self,
+        shapes: &HashMap<String, Vec<usize>>,
+    ) -> Result<HashMap<String, ArrayD<i64>>> {
+        to_arrays(shapes, |key, shape| {
+            to_array(
+                self.get_i64s(key)?,
+                shape,
+                format_args!("feature '{}'", key),
+            )
+        })
+    }
+
+    /// Restore the `BytesList` features named in the shape map to arrays of their shapes.
+    ///
+    /// Features absent from the map are ignored. Every feature in the map must exist.
+    pub fn to_bytes_arrays(
+        &self,
+        shapes: &HashMap<String, Vec<usize>>,
+    ) -> Result<HashMap<String, ArrayD<Vec<u8>>>> {
+        to_arrays(shapes, |key, shape| {
+            to_array(
+                self.get_bytes(key)?,
+                shape,
+                format_args!("feature '{}'", key),
+            )
+        })
+    }
+}
+
+fn to_arrays<T, F>(
+    shapes: &HashMap<String, Vec<usize>>,
+    mut convert: F,
+) -> Result<HashMap<String, ArrayD<T>>>
+where
+    F: FnMut(&str, &[usize]) -> Result<ArrayD<T>>,
+{
+    shapes
+        .iter()
+        .map(|(key, shape)| Ok((key.clone(), convert(key, shape)?)))
+        .collect()
+}
+
+/// Copy the values to an array of the shape, which must hold exactly as many elements.
+fn to_array<T>(values: &[T], shape: &[usize], what: impl std::fmt::Display) -> Result<ArrayD<T>>
+where
+    T: Clone,
+{
+    let numel = shape
+        .iter()
+        .try_fold(1usize, |numel, &dim| numel.checked_mul(dim));
+    if numel != Some(values.len()) {
+        return Err(Error::conversion(format!(
+            "the shape {:?} does not match the {} of {} values",
+            shape,
+            what,
+            values.len()
+        )));
+    }
+    Ok(ArrayD::from_shape_vec(IxDyn(shape), values.to_vec()).unwrap())
+}
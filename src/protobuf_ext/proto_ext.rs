@@ -0,0 +1,231 @@
+//! Bytes features holding serialized protobuf messages of other schemas.
+//!
+//! A proto feature `key` stores each message serialized as an element of a `BytesList`
+//! under `key`. A typed proto feature additionally stores the type URL of the messages,
+//! such as `type.googleapis.com/ads.AdFeatures`, as the single element of a `BytesList`
+//! under the companion key `key` followed by [TYPE_URL_SUFFIX], namely
+//! `<key>.type_url`. Reading a typed feature verifies the type URL before decoding, so
+//! messages of another schema are rejected even when they happen to decode.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{protobuf::BytesList, Example};
+//!
+//! let list = BytesList {
+//!     value: vec![b"a".to_vec()],
+//! };
+//! let mut example = Example::empty();
+//! example.push_proto("list", &list);
+//! assert_eq!(example.get_proto::<BytesList>("list")?, Some(list));
+//! assert_eq!(example.get_proto::<BytesList>("missing")?, None);
+//! # Ok(())
+//! # }
+//! ```
+
+use super::namespace_ext::{ExampleBuilder, NamespaceView};
+use crate::{
+    error::{Error, Result},
+    protobuf::{Example, Feature},
+};
+use prost::Message;
+
+/// The suffix of the companion key holding the type URL of a typed proto feature.
+pub const TYPE_URL_SUFFIX: &str = ".type_url";
+
+/// The companion key holding the type URL of the proto feature.
+pub fn type_url_key(key: &str) -> String {
+    format!("{}{}", key, TYPE_URL_SUFFIX)
+}
+
+impl Example {
+    /// Insert a message serialized as the single element of a `BytesList` feature.
+    pub fn push_proto<M>(&mut self, key: impl Into<String>, message: &M)
+    where
+        M: Message,
+    {
+        self.push_protos(key, std::slice::from_ref(message));
+    }
+
+    /// Insert messages serialized as the elements of a `BytesList` feature.
+    pub fn push_protos<M>(&mut self, key: impl Into<String>, messages: &[M])
+    where
+        M: Message,
+    {
+        self.insert_feature(key.into(), proto_feature(messages));
+    }
+
+    /// Insert messages as [push_protos](Example::push_protos), marked with the type URL.
+    pub fn push_typed_protos<M>(&mut self, key: impl Into<String>, type_url: &str, messages: &[M])
+    where
+        M: Message,
+    {
+        let key = key.into();
+        self.insert_feature(type_url_key(&key), type_url_feature(type_url));
+        self.insert_feature(key, proto_feature(messages));
+    }
+
+    /// Get the message serialized in a `BytesList` feature, or `None` if the feature does
+    /// not exist or is empty.
+    ///
+    /// It fails if the feature holds more than one element, or if the element fails to
+    /// decode, with [Error::FeatureDecodeFailed] carrying the key.
+    pub fn get_proto<M>(&self, key: &str) -> Result<Option<M>>
+    where
+        M: Message + Default,
+    {
+        single(key, self.get_protos(key)?)
+    }
+
+    /// Get the messages serialized in the elements of a `BytesList` feature, which are
+    /// none if the feature does not exist.
+    pub fn get_protos<M>(&self, key: &str) -> Result<Vec<M>>
+    where
+        M: Message + Default,
+    {
+        decode_protos(key, self.get_feature(key))
+    }
+
+    /// Get the message of a typed proto feature as [get_proto](Example::get_proto),
+    /// verifying the type URL first.
+    pub fn get_typed_proto<M>(&self, key: &str, type_url: &str) -> Result<Option<M>>
+    where
+        M: Message + Default,
+    {
+        single(key, self.get_typed_protos(key, type_url)?)
+    }
+
+    /// Get the messages of a typed proto feature as [get_protos](Example::get_protos),
+    /// verifying the type URL first.
+    ///
+    /// It fails if the feature exists without the type URL or with another one.
+    pub fn get_typed_protos<M>(&self, key: &str, type_url: &str) -> Result<Vec<M>>
+    where
+        M: Message + Default,
+    {
+        let feature = self.get_feature(key);
+        if feature.is_some() {
+            check_type_url(key, self.get_feature(&type_url_key(key)), type_url)?;
+        }
+        decode_protos(key, feature)
+    }
+
+    fn get_feature(&self, key: &str) -> Option<&Feature> {
+        self.features
+            .as_ref()
+            .and_then(|features| features.feature.get(key))
+    }
+}
+
+impl NamespaceView<'_> {
+    /// Get the message of a relative key as [Example::get_proto].
+    pub fn get_proto<M>(&self, key: &str) -> Result<Option<M>>
+    where
+        M: Message + Default,
+    {
+        single(&self.resolve(key), self.get_protos(key)?)
+    }
+
+    /// Get the messages of a relative key as [Example::get_protos].
+    pub fn get_protos<M>(&self, key: &str) -> Result<Vec<M>>
+    where
+        M: Message + Default,
+    {
+        decode_protos(&self.resolve(key), self.get(key))
+    }
+}
+
+impl ExampleBuilder {
+    pub fn push_proto<M>(self, key: &str, message: &M) -> Self
+    where
+        M: Message,
+    {
+        self.push_protos(key, std::slice::from_ref(message))
+    }
+
+    pub fn push_protos<M>(self, key: &str, messages: &[M]) -> Self
+    where
+        M: Message,
+    {
+        self.push_feature(key, proto_feature(messages))
+    }
+
+    /// Push messages marked with the type URL as [Example::push_typed_protos].
+    pub fn push_typed_protos<M>(self, key: &str, type_url: &str, messages: &[M]) -> Self
+    where
+        M: Message,
+    {
+        self.push_feature(&type_url_key(key), type_url_feature(type_url))
+            .push_feature(key, proto_feature(messages))
+    }
+}
+
+fn proto_feature<M>(messages: &[M]) -> Feature
+where
+    M: Message,
+{
+    let elements: Vec<_> = messages.iter().map(Message::encode_to_vec).collect();
+    Feature::from_bytes_list(elements)
+}
+
+fn type_url_feature(type_url: &str) -> Feature {
+    Feature::from_bytes_list(vec![type_url.as_bytes().to_vec()])
+}
+
+fn decode_protos<M>(key: &str, feature: Option<&Feature>) -> Result<Vec<M>>
+where
+    M: Message + Default,
+{
+    let Some(feature) = feature else {
+        return Ok(vec![]);
+    };
+    let elements = feature.as_bytes_list().ok_or_else(|| {
+        Error::conversion(format!(
+            "the feature '{}' is not a BytesList of messages",
+            key
+        ))
+    })?;
+    elements
+        .iter()
+        .enumerate()
+        .map(|(index, bytes)| {
+            M::decode(bytes.as_slice()).map_err(|source| Error::FeatureDecodeFailed {
+                key: key.to_string(),
+                index,
+                source,
+            })
+        })
+        .collect()
+}
+
+fn single<M>(key: &str, mut messages: Vec<M>) -> Result<Option<M>> {
+    if messages.len() > 1 {
+        return Err(Error::conversion(format!(
+            "the feature '{}' holds {} messages, but expect at most one",
+            key,
+            messages.len()
+        )));
+    }
+    Ok(messages.pop())
+}
+
+fn check_type_url(key: &str, marker: Option<&Feature>, expect: &str) -> Result<()> {
+    let found = match marker.and_then(Feature::as_bytes_list) {
+        Some([found]) => found,
+        _ => {
+            return Err(Error::conversion(format!(
+                "the feature '{}' has no type URL under '{}'",
+                key,
+                type_url_key(key)
+            )))
+        }
+    };
+    if found.as_slice() != expect.as_bytes() {
+        return Err(Error::conversion(format!(
+            "the feature '{}' holds messages of type '{}', but expect '{}'",
+            key,
+            String::from_utf8_lossy(found),
+            expect
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,142 @@
+//! Ragged 2-D float features.
+//!
+//! A ragged feature `key` is stored as the flattened values in a `FloatList` under
+//! `key`, along with the row lengths in an `Int64List` under the companion key. The
+//! companion key is `key` followed by [DEFAULT_ROW_LENGTHS_SUFFIX], namely
+//! `<key>.row_lengths`, unless configured by [RaggedConfig].
+
+use crate::{
+    error::{Error, Result},
+    protobuf::{Example, Feature},
+};
+
+/// The default suffix of the companion key holding row lengths.
+pub const DEFAULT_ROW_LENGTHS_SUFFIX: &str = ".row_lengths";
+
+/// The naming convention of ragged features.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RaggedConfig {
+    /// The suffix appended to the key of a ragged feature to name its row lengths.
+    pub row_lengths_suffix: String,
+}
+
+impl Default for RaggedConfig {
+    fn default() -> Self {
+        Self {
+            row_lengths_suffix: DEFAULT_ROW_LENGTHS_SUFFIX.to_string(),
+        }
+    }
+}
+
+impl RaggedConfig {
+    /// The companion key holding the row lengths of the feature.
+    pub fn row_lengths_key(&self, key: &str) -> String {
+        format!("{}{}", key, self.row_lengths_suffix)
+    }
+}
+
+/// Compute the row splits of row lengths, as in `tf.RaggedTensor`, which start with
+/// zero and accumulate the lengths.
+pub fn row_splits<I>(row_lengths: I) -> Vec<i64>
+where
+    I: IntoIterator<Item = usize>,
+{
+    let mut splits = vec![0];
+    let mut end = 0;
+    for len in row_lengths {
+        end += len as i64;
+        splits.push(end);
+    }
+    splits
+}
+
+impl Example {
+    /// Insert a ragged feature of rows by the default [RaggedConfig].
+    pub fn push_ragged_f32s(&mut self, key: impl Into<String>, rows: &[&[f32]]) {
+        self.push_ragged_f32s_with(key, rows, &RaggedConfig::default())
+    }
+
+    /// Insert a ragged feature of rows by the naming convention.
+    pub fn push_ragged_f32s_with(
+        &mut self,
+        key: impl Into<String>,
+        rows: &[&[f32]],
+        config: &RaggedConfig,
+    ) {
+        let key = key.into();
+        let (values, row_lengths) = ragged_features(rows);
+        self.insert_feature(config.row_lengths_key(&key), row_lengths);
+        self.insert_feature(key, values);
+    }
+
+    /// Get the rows of a ragged feature by the default [RaggedConfig].
+    pub fn get_ragged_f32s(&self, key: &str) -> Result<Vec<&[f32]>> {
+        self.get_ragged_f32s_with(key, &RaggedConfig::default())
+    }
+
+    /// Get the rows of a ragged feature by the naming convention.
+    ///
+    /// It fails if the row lengths are missing or negative, or do not sum to the number
+    /// of values.
+    pub fn get_ragged_f32s_with(&self, key: &str, config: &RaggedConfig) -> Result<Vec<&[f32]>> {
+        let values = self.get_f32s(key)?;
+        let lengths_key = config.row_lengths_key(key);
+        let row_lengths = self.get_i64s(&lengths_key)?;
+
+        if let Some(len) = row_lengths.iter().find(|&&len| len < 0) {
+            return Err(Error::conversion(format!(
+                "the feature '{}' has a negative row length {}",
+                lengths_key, len
+            )));
+        }
+        let total: i64 = row_lengths.iter().sum();
+        if total != values.len() as i64 {
+            return Err(Error::conversion(format!(
+                "the row lengths in '{}' sum to {}, but '{}' has {} values",
+                lengths_key,
+                total,
+                key,
+                values.len()
+            )));
+        }
+
+        let mut rest = values;
+        let rows = row_lengths
+            .iter()
+            .map(|&len| {
+                let (row, tail) = rest.split_at(len as usize);
+                rest = tail;
+                row
+            })
+            .collect();
+        Ok(rows)
+    }
+}
+
+/// The values and the row lengths features of rows.
+pub(crate) fn ragged_features(rows: &[&[f32]]) -> (Feature, Feature) {
+    let values = Feature::from_f32_iter(rows.iter().flat_map(|row| row.iter().copied()));
+    let row_lengths = Feature::from_i64_iter(rows.iter().map(|row| row.len() as i64));
+    (values, row_lengths)
+}
+
+#[cfg(feature = "with-tch")]
+mod with_tch {
+    use super::*;
+    use tch::Tensor;
+
+    impl Example {
+        /// Get a ragged feature as the flat values and the row splits tensors, which
+        /// build a `tf.RaggedTensor` by `from_row_splits`.
+        pub fn get_ragged_f32s_tensors(
+            &self,
+            key: &str,
+            config: &RaggedConfig,
+        ) -> Result<(Tensor, Tensor)> {
+            let rows = self.get_ragged_f32s_with(key, config)?;
+            let values = Tensor::f_of_slice(&rows.concat())?;
+            let splits = Tensor::f_of_slice(&row_splits(rows.iter().map(|row| row.len())))?;
+            Ok((values, splits))
+        }
+    }
+}
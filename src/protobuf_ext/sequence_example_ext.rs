@@ -0,0 +1,578 @@
+use super::example_ext::{
+    encode_nested, encode_sorted_map, scan_feature_entries, split_length_delimited,
+};
+use crate::{
+    error::{Error, Result},
+    limits::Limits,
+    protobuf::{
+        feature::Kind, Example, Feature, FeatureList, FeatureLists, Features, SequenceExample,
+    },
+};
+use prost::{
+    bytes::Buf as _,
+    encoding::{self, DecodeContext, WireType},
+    Message as _,
+};
+use std::{
+    borrow::Cow,
+    collections::{btree_map, BTreeMap, HashMap},
+};
+
+impl SequenceExample {
+    /// Encode the sequence example with map entries sorted by key.
+    ///
+    /// The output does not depend on the iteration order of the context and
+    /// feature list maps, so equal sequence examples always produce identical bytes.
+    pub fn encode_canonical_to_vec(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        if let Some(context) = &self.context {
+            encode_nested(1, &context.encode_canonical_to_vec(), &mut buf);
+        }
+        if let Some(feature_lists) = &self.feature_lists {
+            encode_nested(2, &feature_lists.encode_canonical_to_vec(), &mut buf);
+        }
+        buf
+    }
+}
+
+impl FeatureLists {
+    /// Encode the feature lists with map entries sorted by key.
+    pub fn encode_canonical_to_vec(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        encode_sorted_map(1, &self.feature_list, &mut buf);
+        buf
+    }
+}
+
+/// The policy to resolve keys present in both the context and the feature lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum KeyCollisionPolicy {
+    /// Return an error on collision.
+    #[default]
+    Error,
+    /// Keep the context feature.
+    PreferContext,
+    /// Keep the frame feature.
+    PreferFrame,
+    /// Keep both, exposing context keys under the [CONTEXT_NAMESPACE].
+    Namespaced,
+}
+
+/// The prefix of context keys under the [Namespaced](KeyCollisionPolicy::Namespaced) policy.
+pub const CONTEXT_NAMESPACE: &str = "context/";
+
+impl SequenceExample {
+    /// Split the sequence example into one [Example] per frame.
+    ///
+    /// The i-th example contains the i-th feature of every feature list merged with
+    /// the context features. Keys present in both are resolved by `policy`. All feature
+    /// lists must have the same length, otherwise an error listing the lengths is returned.
+    ///
+    /// A sequence example without feature lists yields no examples if its context is
+    /// empty, and fails otherwise, since the context would be lost. Feature lists of no
+    /// frames yield no examples.
+    pub fn explode(&self, policy: KeyCollisionPolicy) -> Result<Vec<Example>> {
+        let empty_context = HashMap::new();
+        let context = self
+            .context
+            .as_ref()
+            .map(|context| &context.feature)
+            .unwrap_or(&empty_context);
+        let mut lists: Vec<(&String, &Vec<Feature>)> = self
+            .feature_lists
+            .iter()
+            .flat_map(|lists| &lists.feature_list)
+            .map(|(key, list)| (key, &list.feature))
+            .collect();
+        lists.sort_unstable_by_key(|(key, _)| *key);
+
+        let num_frames = match lists.first() {
+            Some((_, features)) => features.len(),
+            None if context.is_empty() => return Ok(vec![]),
+            None => {
+                return Err(Error::conversion(
+                    "the sequence example has context features but no feature lists to explode",
+                ))
+            }
+        };
+        if lists
+            .iter()
+            .any(|(_, features)| features.len() != num_frames)
+        {
+            return Err(Error::conversion(describe_ragged(
+                lists
+                    .iter()
+                    .map(|(key, features)| (key.as_str(), features.len())),
+            )));
+        }
+
+        let context: HashMap<String, Feature> = match policy {
+            KeyCollisionPolicy::Error => {
+                if let Some((key, _)) = lists.iter().find(|(key, _)| context.contains_key(*key)) {
+                    return Err(collision_error(key));
+                }
+                context.clone()
+            }
+            KeyCollisionPolicy::Namespaced => {
+                let context: HashMap<_, _> = context
+                    .iter()
+                    .map(|(key, feature)| (namespaced(key), feature.clone()))
+                    .collect();
+                if let Some((key, _)) = lists.iter().find(|(key, _)| context.contains_key(*key)) {
+                    return Err(collision_error(key));
+                }
+                context
+            }
+            KeyCollisionPolicy::PreferContext | KeyCollisionPolicy::PreferFrame => context.clone(),
+        };
+
+        let examples = (0..num_frames)
+            .map(|index| {
+                let mut feature = context.clone();
+                for (key, features) in &lists {
+                    if policy == KeyCollisionPolicy::PreferContext && context.contains_key(*key) {
+                        continue;
+                    }
+                    feature.insert((*key).clone(), features[index].clone());
+                }
+                Example {
+                    features: Some(Features { feature }),
+                }
+            })
+            .collect();
+        Ok(examples)
+    }
+}
+
+impl Example {
+    /// Merge frame-level examples into a [SequenceExample], reversing [SequenceExample::explode].
+    ///
+    /// Features named in `context_keys` must be present and identical in every example,
+    /// and are moved to the context. The other features become feature lists ordered by
+    /// the input order. Every example must have the same set of frame keys.
+    pub fn implode<I, K>(examples: I, context_keys: &[K]) -> Result<SequenceExample>
+    where
+        I: IntoIterator<Item = Example>,
+        K: AsRef<str>,
+    {
+        let mut context: HashMap<String, Feature> = HashMap::new();
+        let mut feature_list: HashMap<String, FeatureList> = HashMap::new();
+        let mut num_frames = 0;
+
+        for (index, example) in examples.into_iter().enumerate() {
+            let mut feature = example.into_hash_map();
+
+            for key in context_keys {
+                let key = key.as_ref();
+                let value = feature.remove(key).ok_or_else(|| {
+                    Error::conversion(format!(
+                        "the context key '{}' is missing in example {}",
+                        key, index
+                    ))
+                })?;
+                match context.get(key) {
+                    Some(prev) if *prev != value => {
+                        return Err(Error::conversion(format!(
+                            "the context key '{}' differs between example 0 and example {}",
+                            key, index
+                        )));
+                    }
+                    Some(_) => {}
+                    None => {
+                        context.insert(key.to_string(), value);
+                    }
+                }
+            }
+
+            for (key, value) in feature {
+                feature_list.entry(key).or_default().feature.push(value);
+            }
+            num_frames += 1;
+        }
+
+        if feature_list
+            .values()
+            .any(|list| list.feature.len() != num_frames)
+        {
+            let mut lengths: Vec<_> = feature_list
+                .iter()
+                .map(|(key, list)| (key.as_str(), list.feature.len()))
+                .collect();
+            lengths.sort_unstable();
+            return Err(Error::conversion(format!(
+                "{} ({} examples)",
+                describe_ragged(lengths),
+                num_frames
+            )));
+        }
+
+        Ok(SequenceExample {
+            context: Some(Features { feature: context }),
+            feature_lists: Some(FeatureLists { feature_list }),
+        })
+    }
+}
+
+/// Options of the unified access to context features and feature lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SequenceAccessOptions {
+    /// The resolution of keys present in both the context and the feature lists.
+    pub on_collision: KeyCollisionPolicy,
+}
+
+/// A feature of a [SequenceExample] from either the context or the feature lists.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SequenceFeature<'a> {
+    Context(&'a Feature),
+    List(&'a FeatureList),
+}
+
+impl SequenceExample {
+    pub fn context_feature(&self, key: &str) -> Option<&Feature> {
+        self.context.as_ref()?.feature.get(key)
+    }
+
+    pub fn feature_list(&self, key: &str) -> Option<&FeatureList> {
+        self.feature_lists.as_ref()?.feature_list.get(key)
+    }
+
+    /// The keys present in both the context and the feature lists, sorted.
+    pub fn colliding_keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self
+            .feature_lists
+            .iter()
+            .flat_map(|lists| lists.feature_list.keys())
+            .filter(|key| self.context_feature(key).is_some())
+            .map(|key| key.as_str())
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    /// Get a feature by key from either the context or the feature lists.
+    ///
+    /// Keys present in both are resolved by the collision policy. Under the
+    /// [Namespaced](KeyCollisionPolicy::Namespaced) policy, context features are only
+    /// reachable by keys prefixed with [CONTEXT_NAMESPACE].
+    pub fn get(
+        &self,
+        key: &str,
+        options: SequenceAccessOptions,
+    ) -> Result<Option<SequenceFeature<'_>>> {
+        let context = |key| self.context_feature(key).map(SequenceFeature::Context);
+        let list = self.feature_list(key).map(SequenceFeature::List);
+
+        Ok(match options.on_collision {
+            KeyCollisionPolicy::Error => match (context(key), list) {
+                (Some(_), Some(_)) => return Err(collision_error(key)),
+                (context, list) => context.or(list),
+            },
+            KeyCollisionPolicy::PreferContext => context(key).or(list),
+            KeyCollisionPolicy::PreferFrame => list.or_else(|| context(key)),
+            KeyCollisionPolicy::Namespaced => {
+                let context = key.strip_prefix(CONTEXT_NAMESPACE).and_then(context);
+                match (context, list) {
+                    (Some(_), Some(_)) => return Err(collision_error(key)),
+                    (context, list) => context.or(list),
+                }
+            }
+        })
+    }
+
+    /// List the features of the context and the feature lists sorted by key.
+    ///
+    /// The keys and features agree with [get](SequenceExample::get) under the same
+    /// options.
+    pub fn features(
+        &self,
+        options: SequenceAccessOptions,
+    ) -> Result<Vec<(Cow<'_, str>, SequenceFeature<'_>)>> {
+        let policy = options.on_collision;
+        let mut features: BTreeMap<Cow<'_, str>, SequenceFeature<'_>> = self
+            .feature_lists
+            .iter()
+            .flat_map(|lists| &lists.feature_list)
+            .map(|(key, list)| (Cow::Borrowed(key.as_str()), SequenceFeature::List(list)))
+            .collect();
+
+        for (key, feature) in self.context.iter().flat_map(|context| &context.feature) {
+            let key = match policy {
+                KeyCollisionPolicy::Namespaced => Cow::Owned(namespaced(key)),
+                _ => Cow::Borrowed(key.as_str()),
+            };
+            match features.entry(key) {
+                btree_map::Entry::Vacant(entry) => {
+                    entry.insert(SequenceFeature::Context(feature));
+                }
+                btree_map::Entry::Occupied(mut entry) => match policy {
+                    KeyCollisionPolicy::Error | KeyCollisionPolicy::Namespaced => {
+                        return Err(collision_error(entry.key()));
+                    }
+                    KeyCollisionPolicy::PreferContext => {
+                        entry.insert(SequenceFeature::Context(feature));
+                    }
+                    KeyCollisionPolicy::PreferFrame => {}
+                },
+            }
+        }
+        Ok(features.into_iter().collect())
+    }
+}
+
+/// The builder of [SequenceExample]s.
+///
+/// ```rust
+/// # fn main() -> tfrecord::Result<()> {
+/// use tfrecord::SequenceExampleBuilder;
+///
+/// let sequence_example = SequenceExampleBuilder::new()
+///     .context_f32s("age", &[19.0])
+///     .context_bytes("locale", vec![b"pt_BR".to_vec()])
+///     .push_frame_f32s("movie_ratings", &[4.5])
+///     .push_frame_bytes("movie_names", vec![b"Fight Club".to_vec()])
+///     .push_frame_f32s("movie_ratings", &[5.0])
+///     .push_frame_bytes("movie_names", vec![b"Up".to_vec()])
+///     .build()?;
+/// assert_eq!(sequence_example.feature_list("movie_ratings").unwrap().feature.len(), 2);
+///
+/// // frames of a feature list must have the same kind
+/// let result = SequenceExampleBuilder::new()
+///     .push_frame_f32s("movie_ratings", &[4.5])
+///     .push_frame_i64s("movie_ratings", &[5])
+///     .build();
+/// assert!(result.is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SequenceExampleBuilder {
+    context: HashMap<String, Feature>,
+    feature_list: HashMap<String, FeatureList>,
+    reject_collisions: bool,
+}
+
+impl SequenceExampleBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fail to [build](SequenceExampleBuilder::build) if a key is present in both the
+    /// context and the feature lists.
+    pub fn reject_collisions(mut self) -> Self {
+        self.reject_collisions = true;
+        self
+    }
+
+    pub fn context(mut self, key: &str, feature: Feature) -> Self {
+        self.context.insert(key.to_string(), feature);
+        self
+    }
+
+    pub fn feature_list<I>(mut self, key: &str, features: I) -> Self
+    where
+        I: IntoIterator<Item = Feature>,
+    {
+        self.feature_list.insert(
+            key.to_string(),
+            FeatureList {
+                feature: features.into_iter().collect(),
+            },
+        );
+        self
+    }
+
+    pub fn context_f32s(self, key: &str, values: &[f32]) -> Self {
+        self.context(key, Feature::from_f32_list(values))
+    }
+
+    pub fn context_i64s(self, key: &str, values: &[i64]) -> Self {
+        self.context(key, Feature::from_i64_list(values))
+    }
+
+    pub fn context_bytes(self, key: &str, values: Vec<Vec<u8>>) -> Self {
+        self.context(key, Feature::from_bytes_list(values))
+    }
+
+    /// Append a feature to the feature list, creating the list if absent.
+    pub fn push_frame(mut self, key: &str, feature: Feature) -> Self {
+        self.feature_list
+            .entry(key.to_string())
+            .or_default()
+            .feature
+            .push(feature);
+        self
+    }
+
+    pub fn push_frame_f32s(self, key: &str, values: &[f32]) -> Self {
+        self.push_frame(key, Feature::from_f32_list(values))
+    }
+
+    pub fn push_frame_i64s(self, key: &str, values: &[i64]) -> Self {
+        self.push_frame(key, Feature::from_i64_list(values))
+    }
+
+    pub fn push_frame_bytes(self, key: &str, values: Vec<Vec<u8>>) -> Self {
+        self.push_frame(key, Feature::from_bytes_list(values))
+    }
+
+    /// Build the sequence example.
+    ///
+    /// It fails if the features of a feature list differ in kind, or if a key collides
+    /// while [rejecting collisions](SequenceExampleBuilder::reject_collisions).
+    pub fn build(self) -> Result<SequenceExample> {
+        let Self {
+            context,
+            feature_list,
+            reject_collisions,
+        } = self;
+
+        let mut keys: Vec<_> = feature_list.keys().collect();
+        keys.sort_unstable();
+        for key in keys {
+            let features = &feature_list[key].feature;
+            let Some(first) = features.first() else {
+                continue;
+            };
+            let kind = |feature: &Feature| feature.kind.as_ref().map(std::mem::discriminant);
+            let mismatch = features
+                .iter()
+                .enumerate()
+                .find(|(_, feature)| kind(feature) != kind(first));
+            if let Some((index, feature)) = mismatch {
+                return Err(Error::conversion(format!(
+                    "the feature list '{}' has a {} feature at frame {}, but a {} feature at frame 0",
+                    key,
+                    kind_name(feature),
+                    index,
+                    kind_name(first)
+                )));
+            }
+        }
+
+        if reject_collisions {
+            let mut colliding: Vec<_> = feature_list
+                .keys()
+                .filter(|key| context.contains_key(*key))
+                .collect();
+            colliding.sort_unstable();
+            if let Some(key) = colliding.first() {
+                return Err(collision_error(key));
+            }
+        }
+        Ok(SequenceExample {
+            context: Some(Features { feature: context }),
+            feature_lists: Some(FeatureLists { feature_list }),
+        })
+    }
+}
+
+/// The context of a serialized [SequenceExample], decoded without the feature lists.
+///
+/// The feature lists are skipped on the wire without being decoded or copied, and only
+/// their length is kept. Serialized fields may come in any order, and repeated context
+/// fields are merged as by the full decoding. Read it by
+/// [RecordIter](crate::RecordIter) or any other reader generic over
+/// [Record](crate::Record):
+///
+/// ```rust
+/// # fn main() -> tfrecord::Result<()> {
+/// use tfrecord::{ContextOnly, RecordIter, RecordWriter, SequenceExampleBuilder};
+///
+/// let sequence = SequenceExampleBuilder::new()
+///     .context_i64s("user", &[42])
+///     .push_frame_f32s("clicks", &[0.5; 1000])
+///     .build()?;
+/// let mut bytes = vec![];
+/// let mut writer = RecordWriter::from_writer(&mut bytes)?;
+/// writer.send(tfrecord::ProstRecord(sequence))?;
+/// drop(writer);
+///
+/// for record in RecordIter::<ContextOnly, _>::from_reader(bytes.as_slice(), Default::default()) {
+///     let record = record?;
+///     assert_eq!(record.context.get_i64s("user")?, &[42]);
+///     assert!(record.feature_lists_len > 4000);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ContextOnly {
+    /// The context features, empty if the sequence example has no context.
+    pub context: Features,
+    /// The total length in bytes of the skipped feature lists.
+    pub feature_lists_len: u64,
+}
+
+impl ContextOnly {
+    /// Decode the context of a serialized sequence example, skipping the feature lists.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        Self::decode_with_limits(bytes, &Limits::default())
+    }
+
+    /// Decode like [decode](Self::decode), rejecting contexts exceeding the limits.
+    pub fn decode_with_limits(bytes: &[u8], limits: &Limits) -> Result<Self> {
+        // the context is field 1 like the features of an example
+        scan_feature_entries(bytes, limits, |_, _| Ok(false))?;
+
+        let mut context = Features::default();
+        let mut feature_lists_len = 0;
+        let mut buf = bytes;
+        while buf.has_remaining() {
+            let (tag, wire_type) = encoding::decode_key(&mut buf)?;
+            match (tag, wire_type) {
+                (1, WireType::LengthDelimited) => {
+                    context.merge(split_length_delimited(&mut buf)?)?;
+                }
+                (2, WireType::LengthDelimited) => {
+                    feature_lists_len += split_length_delimited(&mut buf)?.len() as u64;
+                }
+                _ => encoding::skip_field(wire_type, tag, &mut buf, DecodeContext::default())?,
+            }
+        }
+        Ok(Self {
+            context,
+            feature_lists_len,
+        })
+    }
+
+    pub fn context_feature(&self, key: &str) -> Option<&Feature> {
+        self.context.feature.get(key)
+    }
+
+    /// Convert to a sequence example without feature lists.
+    pub fn into_sequence_example(self) -> SequenceExample {
+        SequenceExample {
+            context: Some(self.context),
+            feature_lists: None,
+        }
+    }
+}
+
+/// The name of the kind of a feature in error messages.
+fn kind_name(feature: &Feature) -> &'static str {
+    match feature.kind {
+        Some(Kind::BytesList(_)) => "BytesList",
+        Some(Kind::FloatList(_)) => "FloatList",
+        Some(Kind::Int64List(_)) => "Int64List",
+        None => "kindless",
+    }
+}
+
+fn namespaced(key: &str) -> String {
+    format!("{}{}", CONTEXT_NAMESPACE, key)
+}
+
+fn collision_error(key: &str) -> Error {
+    Error::conversion(format!(
+        "the key '{}' is present in both the context and the feature lists",
+        key
+    ))
+}
+
+fn describe_ragged<'a>(lengths: impl IntoIterator<Item = (&'a str, usize)>) -> String {
+    let lengths: Vec<_> = lengths
+        .into_iter()
+        .map(|(key, len)| format!("{}={}", key, len))
+        .collect();
+    format!("ragged feature lists: {}", lengths.join(", "))
+}
@@ -3,7 +3,7 @@ use crate::{
     error::Error,
     protobuf::{
         summary::{value, Audio, Image, Value},
-        Summary, TensorProto,
+        DataClass, Summary, SummaryMetadata, TensorProto,
     },
     protobuf_ext::IntoImageList,
 };
@@ -108,3 +108,14 @@ impl Summary {
         Ok(summary)
     }
 }
+
+impl SummaryMetadata {
+    /// Get the data class of the time series.
+    ///
+    /// Unlike the generated `data_class()` getter, which silently falls back to
+    /// [DataClass::Unknown], an unknown raw value is reported as an error.
+    pub fn try_data_class(&self) -> Result<DataClass, Error> {
+        DataClass::from_i32(self.data_class)
+            .ok_or_else(|| Error::unknown_enum_value("DataClass", self.data_class))
+    }
+}
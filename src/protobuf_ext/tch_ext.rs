@@ -0,0 +1,99 @@
+//! Validation shared by the conversions of [tch] tensors.
+//!
+//! The conversions of this crate never panic on user tensors. Every public conversion
+//! validates the tensor by [check_tensor] before touching its data, and reaches libtorch
+//! only through the fallible `f_*` APIs. The remaining infallible calls, `defined`,
+//! `dim`, `size`, `numel` and `device`, can only fail on undefined tensors, which
+//! [check_tensor] rejects first.
+
+use crate::error::{Error, Result};
+use tch::{kind::Element, Device, Kind, Tensor};
+
+/// The maximum size in bytes of the data of a converted tensor.
+///
+/// The converted data is stored in a ProtocolBuffer message, which cannot exceed 2GiB.
+/// Larger tensors are rejected with [Error::LimitExceeded] before any allocation.
+pub const MAX_TENSOR_BYTES: usize = i32::MAX as usize;
+
+/// The properties of a validated tensor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TensorInfo {
+    pub kind: Kind,
+    pub shape: Vec<usize>,
+    pub numel: usize,
+}
+
+/// Validate that the tensor is defined, lives on the CPU, has one of the ranks and
+/// one of the kinds, and that its data fits in [MAX_TENSOR_BYTES].
+///
+/// Empty `ranks` or `kinds` accept any rank or kind.
+pub(crate) fn check_tensor(tensor: &Tensor, ranks: &[usize], kinds: &[Kind]) -> Result<TensorInfo> {
+    if !tensor.defined() {
+        return Err(Error::invalid_argument(
+            "tensor: expect a defined tensor, but get an undefined one",
+        ));
+    }
+    let device = tensor.device();
+    if device != Device::Cpu {
+        return Err(Error::invalid_argument(format!(
+            "tensor device: expect a CPU tensor, but get {:?}",
+            device
+        )));
+    }
+    let kind = tensor.f_kind()?;
+    if !kinds.is_empty() && !kinds.contains(&kind) {
+        return Err(Error::invalid_argument(format!(
+            "tensor kind: expect one of {:?}, but get {:?}",
+            kinds, kind
+        )));
+    }
+
+    let size = tensor.size();
+    if !ranks.is_empty() && !ranks.contains(&size.len()) {
+        return Err(Error::invalid_argument(format!(
+            "tensor rank: expect {:?} dimensions, but get {} dimensions",
+            ranks,
+            size.len()
+        )));
+    }
+    let shape = size
+        .iter()
+        .map(|&dim| {
+            usize::try_from(dim).map_err(|_| {
+                Error::invalid_argument(format!("tensor shape: negative dimension in {:?}", size))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // saturate on overflow, which exceeds the limit anyway
+    let numel = shape
+        .iter()
+        .try_fold(1usize, |numel, &dim| numel.checked_mul(dim))
+        .unwrap_or(usize::MAX);
+    let num_bytes = numel.saturating_mul(kind.elt_size_in_bytes());
+    if num_bytes > MAX_TENSOR_BYTES {
+        return Err(Error::LimitExceeded {
+            which: "tensor bytes",
+            limit: MAX_TENSOR_BYTES as u64,
+            observed: num_bytes as u64,
+        });
+    }
+
+    Ok(TensorInfo { kind, shape, numel })
+}
+
+/// Copy the elements of a [checked](check_tensor) tensor in row-major order.
+///
+/// The element type must match the kind of the tensor.
+pub(crate) fn tensor_to_vec<T>(tensor: &Tensor, numel: usize) -> Result<Vec<T>>
+where
+    T: Element + Copy + Default,
+{
+    if numel == 0 {
+        return Ok(vec![]);
+    }
+    let tensor = tensor.f_contiguous()?;
+    let mut data = vec![T::default(); numel];
+    tensor.f_copy_data(&mut data, numel)?;
+    Ok(data)
+}
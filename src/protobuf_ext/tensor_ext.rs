@@ -5,27 +5,46 @@ use crate::{
 };
 use integer_encoding::VarInt;
 
-fn numel(dims: &[Dim]) -> usize {
-    dims.iter().map(|dim| dim.size as usize).product()
+/// The number of elements of a shape, failing on negative or overflowing sizes.
+fn numel(dims: &[Dim]) -> Result<usize, Error> {
+    dims.iter().try_fold(1usize, |numel, dim| {
+        let size = usize::try_from(dim.size)
+            .map_err(|_| Error::invalid_argument(format!("negative dimension {}", dim.size)))?;
+        numel
+            .checked_mul(size)
+            .ok_or_else(|| Error::invalid_argument("the number of elements overflows"))
+    })
 }
 
 impl TensorProto {
+    /// Get the element data type.
+    ///
+    /// Unlike the generated `dtype()` getter, which silently falls back to
+    /// [DataType::DtInvalid], an unknown raw value is reported as an error.
+    pub fn try_dtype(&self) -> Result<DataType, Error> {
+        DataType::from_i32(self.dtype)
+            .ok_or_else(|| Error::unknown_enum_value("DataType", self.dtype))
+    }
+
     pub fn from_slice<T, S>(shape: S, data: &[T]) -> Result<Self, Error>
     where
         S: IntoShape,
         T: TensorProtoElement,
     {
         let dims = shape.to_shape();
-        if numel(&dims) != data.len() {
-            todo!();
-        }
+        let numel = numel(&dims)?;
+        ensure_argument!(
+            numel == data.len(),
+            "the shape has {} elements, but get {} elements",
+            numel,
+            data.len()
+        );
 
         let dtype = T::DATA_TYPE as i32;
-        let tensor_content: Vec<u8> = data
-            .iter()
-            .flat_map(|elem| elem.to_bytes())
-            .cloned()
-            .collect();
+        let mut tensor_content = Vec::with_capacity(std::mem::size_of_val(data));
+        for elem in data {
+            tensor_content.extend_from_slice(elem.to_le_bytes().as_ref());
+        }
 
         Ok(TensorProto {
             dtype,
@@ -47,12 +66,19 @@ impl TensorProto {
         let dims = shape.to_shape();
 
         ensure_argument!(
-            numel(&dims) == data.len(),
+            numel(&dims)? == data.len(),
             "the shape and number of elements mismatch"
         );
+        ensure_argument!(
+            data.iter()
+                .all(|bytes| bytes.as_ref().len() <= u32::MAX as usize),
+            "the length of each element must not exceed {} bytes",
+            u32::MAX
+        );
+        // the lengths are unsigned varints, which signed integers would zigzag-encode
         let len_iter = data
             .iter()
-            .flat_map(|bytes| (bytes.as_ref().len() as i32).encode_var_vec());
+            .flat_map(|bytes| (bytes.as_ref().len() as u32).encode_var_vec());
         let bytes_iter = data.iter().flat_map(|bytes| bytes.as_ref().iter().cloned());
         let tensor_content: Vec<u8> = len_iter.chain(bytes_iter).collect();
 
@@ -67,6 +93,118 @@ impl TensorProto {
             ..Default::default()
         })
     }
+
+    /// Build a tensor from the values in row-major order.
+    ///
+    /// Numeric and boolean values are packed in `tensor_content`, and strings are stored in
+    /// `string_val`.
+    ///
+    /// ```rust
+    /// # fn main() -> tfrecord::Result<()> {
+    /// use tfrecord::protobuf::TensorProto;
+    ///
+    /// let tensor = TensorProto::from_vec([2usize, 2], vec![true, false, false, true])?;
+    /// assert_eq!(tensor.shape()?, vec![2, 2]);
+    /// assert_eq!(tensor.to_vec::<bool>()?, vec![true, false, false, true]);
+    ///
+    /// // the element type must match the data type of the tensor
+    /// assert!(tensor.to_vec::<u8>().is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_vec<T, S>(shape: S, values: Vec<T>) -> Result<Self, Error>
+    where
+        S: IntoShape,
+        T: TensorValue,
+    {
+        let dims = shape.to_shape();
+        let numel = numel(&dims)?;
+        ensure_argument!(
+            numel == values.len(),
+            "the shape has {} elements, but get {} elements",
+            numel,
+            values.len()
+        );
+
+        let mut tensor = TensorProto {
+            dtype: T::DATA_TYPE as i32,
+            tensor_shape: Some(TensorShapeProto {
+                dim: dims,
+                unknown_rank: false,
+            }),
+            version_number: 0,
+            ..Default::default()
+        };
+        T::store(values, &mut tensor);
+        Ok(tensor)
+    }
+
+    /// Get the shape, which is a scalar if the shape is absent.
+    pub fn shape(&self) -> Result<Vec<usize>, Error> {
+        let shape = match &self.tensor_shape {
+            Some(shape) => shape,
+            None => return Ok(vec![]),
+        };
+        ensure_argument!(!shape.unknown_rank, "the tensor has an unknown rank");
+        shape
+            .dim
+            .iter()
+            .map(|dim| {
+                usize::try_from(dim.size).map_err(|_| {
+                    Error::invalid_argument(format!("negative dimension {}", dim.size))
+                })
+            })
+            .collect()
+    }
+
+    /// Get the values in row-major order.
+    ///
+    /// The values are read from `tensor_content` if it is not empty, or from the `*_val`
+    /// field of the data type otherwise. As TensorFlow does for version 0, a single value
+    /// in the `*_val` field is repeated to fill the shape.
+    ///
+    /// The element type must match the data type of the tensor. Bytes are never
+    /// reinterpreted as another type.
+    pub fn to_vec<T>(&self) -> Result<Vec<T>, Error>
+    where
+        T: TensorValue,
+    {
+        let dtype = self.try_dtype()?;
+        ensure_argument!(
+            dtype == T::DATA_TYPE,
+            "expect a {:?} tensor, but get {:?}",
+            T::DATA_TYPE,
+            dtype
+        );
+        let numel = match &self.tensor_shape {
+            Some(shape) => {
+                ensure_argument!(!shape.unknown_rank, "the tensor has an unknown rank");
+                numel(&shape.dim)?
+            }
+            None => 1,
+        };
+
+        if !self.tensor_content.is_empty() {
+            let values = T::load_content(&self.tensor_content, numel)?;
+            ensure_argument!(
+                values.len() == numel,
+                "the shape has {} elements, but the tensor content has {} elements",
+                numel,
+                values.len()
+            );
+            return Ok(values);
+        }
+
+        let values = T::load_vals(self)?;
+        match values.len() {
+            len if len == numel => Ok(values),
+            1 if self.version_number == 0 => Ok(vec![values[0].clone(); numel]),
+            len => Err(Error::invalid_argument(format!(
+                "the shape has {} elements, but the tensor has {} values",
+                numel, len
+            ))),
+        }
+    }
 }
 
 pub use elem::*;
@@ -75,13 +213,20 @@ mod elem {
     use bytemuck::Pod;
 
     /// Element types of [TensorProto](crate::protobuf::TensorProto).
+    ///
+    /// The `tensor_content` of TensorFlow is little-endian regardless of the host, so
+    /// elements are serialized by [to_le_bytes](TensorProtoElement::to_le_bytes) rather
+    /// than by reinterpreting their memory.
     pub trait TensorProtoElement
     where
         Self: Pod,
     {
         const DATA_TYPE: DataType;
 
-        fn to_bytes(&self) -> &[u8];
+        /// The little-endian bytes, which is a byte array.
+        type LeBytes: AsRef<[u8]>;
+
+        fn to_le_bytes(&self) -> Self::LeBytes;
     }
 
     macro_rules! impl_to_le_bytes {
@@ -89,8 +234,10 @@ mod elem {
             impl TensorProtoElement for $ty {
                 const DATA_TYPE: DataType = $dtype;
 
-                fn to_bytes(&self) -> &[u8] {
-                    bytemuck::bytes_of(self)
+                type LeBytes = [u8; std::mem::size_of::<$ty>()];
+
+                fn to_le_bytes(&self) -> Self::LeBytes {
+                    <$ty>::to_le_bytes(*self)
                 }
             }
         };
@@ -108,6 +255,187 @@ mod elem {
     impl_to_le_bytes!(f64, DataType::DtDouble);
 }
 
+pub use value::*;
+mod value {
+    use super::*;
+
+    /// Value types converted from and to [TensorProto](crate::protobuf::TensorProto) by
+    /// [from_vec](crate::protobuf::TensorProto::from_vec) and
+    /// [to_vec](crate::protobuf::TensorProto::to_vec).
+    pub trait TensorValue
+    where
+        Self: Sized + Clone,
+    {
+        const DATA_TYPE: DataType;
+
+        /// Store the values in the tensor, whose data type and shape are already set.
+        fn store(values: Vec<Self>, tensor: &mut TensorProto);
+
+        /// Load the values packed in a non-empty `tensor_content` of a tensor of `numel`
+        /// elements.
+        fn load_content(content: &[u8], numel: usize) -> Result<Vec<Self>, Error>;
+
+        /// Load the values in the `*_val` field of the data type.
+        fn load_vals(tensor: &TensorProto) -> Result<Vec<Self>, Error>;
+    }
+
+    macro_rules! impl_numeric_value {
+        ($ty:ty, $field:ident, $convert:expr) => {
+            impl TensorValue for $ty {
+                const DATA_TYPE: DataType = <$ty as TensorProtoElement>::DATA_TYPE;
+
+                fn store(values: Vec<Self>, tensor: &mut TensorProto) {
+                    tensor.tensor_content = values
+                        .iter()
+                        .flat_map(|value| value.to_le_bytes())
+                        .collect();
+                }
+
+                fn load_content(content: &[u8], _numel: usize) -> Result<Vec<Self>, Error> {
+                    const SIZE: usize = std::mem::size_of::<$ty>();
+                    let chunks = content.chunks_exact(SIZE);
+                    ensure_argument!(
+                        chunks.remainder().is_empty(),
+                        "the tensor content of {} bytes is not a multiple of the {}-byte elements",
+                        content.len(),
+                        SIZE
+                    );
+                    let values = chunks
+                        .map(|bytes| <$ty>::from_le_bytes(bytes.try_into().unwrap()))
+                        .collect();
+                    Ok(values)
+                }
+
+                fn load_vals(tensor: &TensorProto) -> Result<Vec<Self>, Error> {
+                    tensor.$field.iter().map($convert).collect()
+                }
+            }
+        };
+        ($ty:ty, $field:ident) => {
+            impl_numeric_value!($ty, $field, |&value| Ok(value));
+        };
+    }
+
+    /// Narrow the `int_val` entries, which hold the integers of 32 bits or less.
+    macro_rules! narrow_int_val {
+        ($ty:ty) => {
+            |&value: &i32| {
+                <$ty>::try_from(value).map_err(|_| {
+                    Error::conversion(format!(
+                        "the value {} is out of the range of {}",
+                        value,
+                        stringify!($ty)
+                    ))
+                })
+            }
+        };
+    }
+
+    impl_numeric_value!(u8, int_val, narrow_int_val!(u8));
+    impl_numeric_value!(u16, int_val, narrow_int_val!(u16));
+    impl_numeric_value!(u32, uint32_val);
+    impl_numeric_value!(u64, uint64_val);
+    impl_numeric_value!(i8, int_val, narrow_int_val!(i8));
+    impl_numeric_value!(i16, int_val, narrow_int_val!(i16));
+    impl_numeric_value!(i32, int_val);
+    impl_numeric_value!(i64, int64_val);
+    impl_numeric_value!(f32, float_val);
+    impl_numeric_value!(f64, double_val);
+
+    impl TensorValue for bool {
+        const DATA_TYPE: DataType = DataType::DtBool;
+
+        fn store(values: Vec<Self>, tensor: &mut TensorProto) {
+            tensor.tensor_content = values.into_iter().map(u8::from).collect();
+        }
+
+        fn load_content(content: &[u8], _numel: usize) -> Result<Vec<Self>, Error> {
+            content
+                .iter()
+                .map(|&byte| match byte {
+                    0 => Ok(false),
+                    1 => Ok(true),
+                    _ => Err(Error::conversion(format!("invalid boolean byte {}", byte))),
+                })
+                .collect()
+        }
+
+        fn load_vals(tensor: &TensorProto) -> Result<Vec<Self>, Error> {
+            Ok(tensor.bool_val.clone())
+        }
+    }
+
+    impl TensorValue for Vec<u8> {
+        const DATA_TYPE: DataType = DataType::DtString;
+
+        fn store(values: Vec<Self>, tensor: &mut TensorProto) {
+            tensor.string_val = values;
+        }
+
+        /// Load the strings packed by [from_byte_slices](TensorProto::from_byte_slices),
+        /// which are the varint lengths of all strings followed by their bytes.
+        fn load_content(content: &[u8], numel: usize) -> Result<Vec<Self>, Error> {
+            let mut rest = content;
+            let lens = (0..numel)
+                .map(|_| {
+                    let (len, num_bytes) = u64::decode_var(rest)
+                        .ok_or_else(|| Error::conversion("truncated string length"))?;
+                    rest = &rest[num_bytes..];
+                    Ok(len)
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            let values = lens
+                .into_iter()
+                .map(|len| {
+                    let len = usize::try_from(len)
+                        .ok()
+                        .filter(|&len| len <= rest.len())
+                        .ok_or_else(|| Error::conversion("truncated string bytes"))?;
+                    let (bytes, remaining) = rest.split_at(len);
+                    rest = remaining;
+                    Ok(bytes.to_vec())
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            ensure_argument!(
+                rest.is_empty(),
+                "{} trailing bytes after the strings",
+                rest.len()
+            );
+            Ok(values)
+        }
+
+        fn load_vals(tensor: &TensorProto) -> Result<Vec<Self>, Error> {
+            Ok(tensor.string_val.clone())
+        }
+    }
+
+    impl TensorValue for String {
+        const DATA_TYPE: DataType = DataType::DtString;
+
+        fn store(values: Vec<Self>, tensor: &mut TensorProto) {
+            tensor.string_val = values.into_iter().map(String::into_bytes).collect();
+        }
+
+        fn load_content(content: &[u8], numel: usize) -> Result<Vec<Self>, Error> {
+            to_strings(Vec::<u8>::load_content(content, numel)?)
+        }
+
+        fn load_vals(tensor: &TensorProto) -> Result<Vec<Self>, Error> {
+            to_strings(tensor.string_val.clone())
+        }
+    }
+
+    fn to_strings(values: Vec<Vec<u8>>) -> Result<Vec<String>, Error> {
+        values
+            .into_iter()
+            .map(|bytes| {
+                String::from_utf8(bytes)
+                    .map_err(|err| Error::conversion(format!("invalid UTF-8 string: {}", err)))
+            })
+            .collect()
+    }
+}
+
 pub use to_shape::*;
 mod to_shape {
     use super::*;
@@ -131,7 +459,8 @@ mod to_shape {
         fn to_shape(&self) -> Vec<Dim> {
             self.iter()
                 .map(|&sz| {
-                    let size = <i64 as NumCast>::from(sz).expect("size is too large");
+                    // saturate, so that the element count check rejects the shape
+                    let size = <i64 as NumCast>::from(sz).unwrap_or(i64::MAX);
                     Dim {
                         size,
                         name: "".into(),
@@ -157,7 +486,8 @@ mod to_shape {
         fn to_shape(&self) -> Vec<Dim> {
             self.iter()
                 .map(|&sz| {
-                    let size = <i64 as NumCast>::from(sz).expect("size is too large");
+                    // saturate, so that the element count check rejects the shape
+                    let size = <i64 as NumCast>::from(sz).unwrap_or(i64::MAX);
                     Dim {
                         size,
                         name: "".into(),
@@ -335,7 +665,7 @@ mod with_image {
 #[cfg(feature = "with-ndarray")]
 mod with_ndarray {
     use super::*;
-    use ndarray::{ArrayBase, Data, Dimension, RawData};
+    use ndarray::{ArrayBase, ArrayD, Data, Dimension, IxDyn, RawData};
 
     impl<S, D, T> From<&ArrayBase<S, D>> for TensorProto
     where
@@ -361,6 +691,31 @@ mod with_ndarray {
             Self::from(&from)
         }
     }
+
+    impl TensorProto {
+        /// Build a tensor of the shape and the elements of the array.
+        ///
+        /// Unlike the [From] conversions, every [TensorValue] type is supported.
+        pub fn from_ndarray<S, D, T>(array: &ArrayBase<S, D>) -> Result<Self, Error>
+        where
+            D: Dimension,
+            S: Data<Elem = T>,
+            T: TensorValue,
+        {
+            Self::from_vec(array.shape(), array.iter().cloned().collect())
+        }
+
+        /// Get the values as an array of the shape of the tensor, following the rules of
+        /// [to_vec](TensorProto::to_vec).
+        pub fn to_ndarray<T>(&self) -> Result<ArrayD<T>, Error>
+        where
+            T: TensorValue,
+        {
+            let shape = self.shape()?;
+            let values = self.to_vec()?;
+            Ok(ArrayD::from_shape_vec(IxDyn(&shape), values).unwrap())
+        }
+    }
 }
 
 #[cfg(feature = "with-tch")]
@@ -368,27 +723,36 @@ pub use with_tch::*;
 #[cfg(feature = "with-tch")]
 mod with_tch {
     use super::*;
-    use std::slice;
+    use crate::{
+        protobuf::{Feature, FloatList, Int64List},
+        protobuf_ext::tch_ext::{check_tensor, tensor_to_vec},
+    };
     use tch::{Kind, Tensor};
 
-    macro_rules! tensor_to_vec {
-        ($tensor:ident, $ty:ident) => {
-            unsafe {
-                let numel = $tensor.numel();
-                let mut data: Vec<$ty> = Vec::with_capacity(numel);
-                let slice = slice::from_raw_parts_mut(data.as_mut_ptr(), numel);
-                $tensor.copy_data(slice, numel);
-                data.set_len(numel);
-                data
-            }
-        };
-    }
+    /// The tensor kinds converted to a [FloatList].
+    const FLOAT_KINDS: [Kind; 3] = [Kind::Half, Kind::Float, Kind::Double];
+
+    /// The tensor kinds converted to an [Int64List].
+    const INT_KINDS: [Kind; 6] = [
+        Kind::Uint8,
+        Kind::Int8,
+        Kind::Int16,
+        Kind::Int,
+        Kind::Int64,
+        Kind::Bool,
+    ];
 
     macro_rules! tensor_to_proto {
-        ($tensor:ident, $ty:ident) => {{
-            let values = tensor_to_vec!($tensor, $ty);
-            let shape: Vec<_> = $tensor.size().into_iter().map(|sz| sz as usize).collect();
-            TensorProto::from_slice(shape, &values)
+        ($tensor:ident, $info:ident, $ty:ident) => {{
+            let values: Vec<$ty> = tensor_to_vec($tensor, $info.numel)?;
+            TensorProto::from_slice($info.shape, &values)
+        }};
+    }
+
+    macro_rules! proto_to_tensor {
+        ($proto:ident, $shape:ident, $ty:ident) => {{
+            let values: Vec<$ty> = $proto.to_vec()?;
+            Tensor::f_of_slice(&values)?.f_reshape(&$shape)?
         }};
     }
 
@@ -396,26 +760,116 @@ mod with_tch {
         type Error = Error;
 
         fn try_from(from: &Tensor) -> Result<Self, Self::Error> {
-            let kind = from.f_kind()?;
-            match kind {
-                Kind::Uint8 => tensor_to_proto!(from, u8),
-                Kind::Int8 => tensor_to_proto!(from, i8),
-                Kind::Int16 => tensor_to_proto!(from, i16),
-                Kind::Int => tensor_to_proto!(from, i32),
-                Kind::Int64 => tensor_to_proto!(from, i64),
-                Kind::Float => tensor_to_proto!(from, f32),
-                Kind::Double => tensor_to_proto!(from, f64),
-                _ => {
+            let info = check_tensor(from, &[], &[])?;
+            match info.kind {
+                Kind::Uint8 => tensor_to_proto!(from, info, u8),
+                Kind::Int8 => tensor_to_proto!(from, info, i8),
+                Kind::Int16 => tensor_to_proto!(from, info, i16),
+                Kind::Int => tensor_to_proto!(from, info, i32),
+                Kind::Int64 => tensor_to_proto!(from, info, i64),
+                Kind::Float => tensor_to_proto!(from, info, f32),
+                Kind::Double => tensor_to_proto!(from, info, f64),
+                Kind::Bool => {
+                    let values: Vec<bool> = tensor_to_vec(from, info.numel)?;
+                    TensorProto::from_vec(info.shape, values)
+                }
+                kind => Err(Error::conversion(format!(
+                    "tensor kind: unsupported kind {:?}",
+                    kind
+                ))),
+            }
+        }
+    }
+
+    impl TryFrom<Tensor> for TensorProto {
+        type Error = Error;
+        fn try_from(from: Tensor) -> Result<Self, Self::Error> {
+            Self::try_from(&from)
+        }
+    }
+
+    impl TryFrom<&TensorProto> for Tensor {
+        type Error = Error;
+
+        /// Restore the shape and the kind of the tensor, whose values are read as by
+        /// [to_vec](TensorProto::to_vec).
+        fn try_from(from: &TensorProto) -> Result<Self, Self::Error> {
+            let shape = from
+                .shape()?
+                .into_iter()
+                .map(|dim| {
+                    i64::try_from(dim).map_err(|_| {
+                        Error::conversion(format!("tensor shape: dimension {} overflows", dim))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let tensor = match from.try_dtype()? {
+                DataType::DtUint8 => proto_to_tensor!(from, shape, u8),
+                DataType::DtInt8 => proto_to_tensor!(from, shape, i8),
+                DataType::DtInt16 => proto_to_tensor!(from, shape, i16),
+                DataType::DtInt32 => proto_to_tensor!(from, shape, i32),
+                DataType::DtInt64 => proto_to_tensor!(from, shape, i64),
+                DataType::DtFloat => proto_to_tensor!(from, shape, f32),
+                DataType::DtDouble => proto_to_tensor!(from, shape, f64),
+                DataType::DtBool => proto_to_tensor!(from, shape, bool),
+                dtype => {
                     return Err(Error::conversion(format!(
-                        "unsupported tensor kind {:?}",
-                        kind
+                        "tensor dtype: unsupported data type {:?}",
+                        dtype
                     )))
                 }
+            };
+            Ok(tensor)
+        }
+    }
+
+    impl TryFrom<TensorProto> for Tensor {
+        type Error = Error;
+        fn try_from(from: TensorProto) -> Result<Self, Self::Error> {
+            Self::try_from(&from)
+        }
+    }
+
+    impl TryFrom<&Tensor> for FloatList {
+        type Error = Error;
+
+        /// Convert a 1-D floating point tensor, narrowing the values to `f32`.
+        fn try_from(from: &Tensor) -> Result<Self, Self::Error> {
+            let info = check_tensor(from, &[1], &FLOAT_KINDS)?;
+            let tensor = from.f_to_kind(Kind::Float)?;
+            let value = tensor_to_vec(&tensor, info.numel)?;
+            Ok(FloatList { value })
+        }
+    }
+
+    impl TryFrom<&Tensor> for Int64List {
+        type Error = Error;
+
+        /// Convert a 1-D integer or boolean tensor, widening the values to `i64`.
+        fn try_from(from: &Tensor) -> Result<Self, Self::Error> {
+            let info = check_tensor(from, &[1], &INT_KINDS)?;
+            let tensor = from.f_to_kind(Kind::Int64)?;
+            let value = tensor_to_vec(&tensor, info.numel)?;
+            Ok(Int64List { value })
+        }
+    }
+
+    impl TryFrom<&Tensor> for Feature {
+        type Error = Error;
+
+        /// Convert a 1-D floating point tensor to a `FloatList` feature, or a 1-D
+        /// integer or boolean tensor to an `Int64List` feature.
+        fn try_from(from: &Tensor) -> Result<Self, Self::Error> {
+            let info = check_tensor(from, &[1], &[])?;
+            if FLOAT_KINDS.contains(&info.kind) {
+                Ok(Feature::from_f32_list(FloatList::try_from(from)?.value))
+            } else {
+                Ok(Feature::from_i64_list(Int64List::try_from(from)?.value))
             }
         }
     }
 
-    impl TryFrom<Tensor> for TensorProto {
+    impl TryFrom<Tensor> for Feature {
         type Error = Error;
         fn try_from(from: Tensor) -> Result<Self, Self::Error> {
             Self::try_from(&from)
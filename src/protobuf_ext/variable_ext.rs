@@ -0,0 +1,25 @@
+use crate::{
+    error::Error,
+    protobuf::{VariableAggregation, VariableDef, VariableSynchronization},
+};
+
+impl VariableDef {
+    /// Get the synchronization mode of the variable.
+    ///
+    /// Unlike the generated `synchronization()` getter, which silently falls back to
+    /// [VariableSynchronization::Auto], an unknown raw value is reported as an error.
+    pub fn try_synchronization(&self) -> Result<VariableSynchronization, Error> {
+        VariableSynchronization::from_i32(self.synchronization).ok_or_else(|| {
+            Error::unknown_enum_value("VariableSynchronization", self.synchronization)
+        })
+    }
+
+    /// Get the aggregation mode of the variable.
+    ///
+    /// Unlike the generated `aggregation()` getter, which silently falls back to
+    /// [VariableAggregation::None], an unknown raw value is reported as an error.
+    pub fn try_aggregation(&self) -> Result<VariableAggregation, Error> {
+        VariableAggregation::from_i32(self.aggregation)
+            .ok_or_else(|| Error::unknown_enum_value("VariableAggregation", self.aggregation))
+    }
+}
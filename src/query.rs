@@ -0,0 +1,396 @@
+//! Select, filter and limit examples of datasets.
+//!
+//! A [Query] composes a selection of features, predicates and a limit, and runs
+//! lazily against the files of a dataset in order.
+//!
+//! - [run_stream](Query::run_stream) yields the matching examples.
+//! - [run_to_files](Query::run_to_files) writes the matching examples to new files.
+//! - [count](Query::count) counts the matching examples.
+//!
+//! Only the selected features and the features declared by
+//! [filter_on](Query::filter_on) are decoded, using the
+//! [sorted keys](FeatureProjection::sorted_keys) of files declaring them. Predicates
+//! added by [filter](Query::filter) see whole examples, so the query falls back to
+//! decoding whole examples. Once the limit is reached, no further record is read.
+//!
+//! Failures to decode a record or to evaluate a predicate are reported as
+//! [Error::RecordFailed] with the file and the index of the record in the file.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{query::Query, samples};
+//!
+//! let dataset = samples::tiny_dataset(2, 10)?;
+//! let query = Query::new()
+//!     .select(["id"])
+//!     .filter_on(["label"], |example| {
+//!         example.get_i64s("label").is_ok_and(|label| label[0] == 1)
+//!     })
+//!     .limit(3);
+//! for example in query.run_stream(dataset.paths()) {
+//!     let example = example?;
+//!     assert_eq!(example.into_vec().len(), 1);
+//! }
+//! assert!(query.count(dataset.paths())? <= 3);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::{ensure_argument, Error, Result},
+    metadata::{metadata_path, shard_declares_sorted_keys},
+    protobuf::Example,
+    protobuf_ext::FeatureProjection,
+    record_reader::{BytesIter, RecordReaderConfig},
+    record_writer::{ExampleWriter, RecordWriterConfig},
+    shardspec,
+};
+use std::{
+    borrow::Cow,
+    collections::BTreeSet,
+    fmt,
+    fs::{self, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// The function deciding whether an example matches.
+pub type PredicateFn = dyn Fn(&Example) -> Result<bool> + Send + Sync;
+
+/// A predicate and the features it reads, or `None` if it reads whole examples.
+#[derive(Clone)]
+struct Filter {
+    keys: Option<BTreeSet<String>>,
+    predicate: Arc<PredicateFn>,
+}
+
+/// A lazily evaluated selection of examples.
+///
+/// See the [module](self) documentation.
+#[derive(Clone, Default)]
+pub struct Query {
+    select: Option<BTreeSet<String>>,
+    filters: Vec<Filter>,
+    limit: Option<u64>,
+    reader: RecordReaderConfig,
+}
+
+impl Query {
+    /// A query matching all examples with all features.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep only the features of the keys in matching examples.
+    pub fn select<I, K>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        self.select = Some(keys.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Keep examples accepted by the predicate on whole examples.
+    pub fn filter<F>(self, predicate: F) -> Self
+    where
+        F: 'static + Fn(&Example) -> bool + Send + Sync,
+    {
+        self.try_filter(move |example| Ok(predicate(example)))
+    }
+
+    /// Keep examples accepted by the fallible predicate on whole examples.
+    pub fn try_filter<F>(mut self, predicate: F) -> Self
+    where
+        F: 'static + Fn(&Example) -> Result<bool> + Send + Sync,
+    {
+        self.filters.push(Filter {
+            keys: None,
+            predicate: Arc::new(predicate),
+        });
+        self
+    }
+
+    /// Keep examples accepted by the predicate, which sees only the features of the
+    /// keys and the selected features.
+    pub fn filter_on<I, K, F>(mut self, keys: I, predicate: F) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+        F: 'static + Fn(&Example) -> bool + Send + Sync,
+    {
+        self.filters.push(Filter {
+            keys: Some(keys.into_iter().map(Into::into).collect()),
+            predicate: Arc::new(move |example| Ok(predicate(example))),
+        });
+        self
+    }
+
+    /// Stop after the number of matching examples.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Read files with the configuration.
+    pub fn with_reader_config(mut self, config: RecordReaderConfig) -> Self {
+        self.reader = config;
+        self
+    }
+
+    /// Iterate the matching examples of the files in order.
+    pub fn run_stream<'a, P, I>(&self, paths: I) -> QueryIter
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<Cow<'a, Path>>,
+    {
+        let paths: Vec<PathBuf> = paths
+            .into_iter()
+            .map(|path| path.into().into_owned())
+            .collect();
+        QueryIter {
+            query: self.clone(),
+            paths: paths.into_iter(),
+            file: None,
+            num_matched: 0,
+            done: false,
+        }
+    }
+
+    /// Count the matching examples of the files.
+    pub fn count<'a, P, I>(&self, paths: I) -> Result<u64>
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<Cow<'a, Path>>,
+    {
+        self.run_stream(paths)
+            .try_fold(0, |count, example| example.map(|_| count + 1))
+    }
+
+    /// Write the matching examples of the files to new files.
+    ///
+    /// The output files are named `{output_prefix}-{index:05}-of-{count:05}`, where the
+    /// count is determined by [records_per_shard](QueryOutput::records_per_shard). An
+    /// empty result is written to a single empty file. On failure, the partial outputs
+    /// are removed.
+    pub fn run_to_files<'a, P, I>(
+        &self,
+        paths: I,
+        output_prefix: &str,
+        output: QueryOutput,
+    ) -> Result<QueryReport>
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<Cow<'a, Path>>,
+    {
+        let QueryOutput {
+            records_per_shard,
+            writer: config,
+        } = output;
+        ensure_argument!(
+            records_per_shard != Some(0),
+            "records_per_shard must be positive"
+        );
+
+        // shards are written under partial names until the count is known
+        let partial_path =
+            |index: usize| PathBuf::from(format!("{}-{:05}.partial", output_prefix, index));
+        let mut created: Vec<PathBuf> = vec![];
+        let mut num_records = 0;
+        let write = || -> Result<()> {
+            created.push(partial_path(0));
+            let mut writer = ExampleWriter::create_with_config(&created[0], config.clone())?;
+            for example in self.run_stream(paths) {
+                let example = example?;
+                if records_per_shard
+                    .is_some_and(|per_shard| num_records > 0 && num_records % per_shard == 0)
+                {
+                    writer.flush()?;
+                    let path = partial_path(created.len());
+                    writer = ExampleWriter::create_with_config(&path, config.clone())?;
+                    created.push(path);
+                }
+                writer.send(example)?;
+                num_records += 1;
+            }
+            writer.flush()?;
+            Ok(())
+        };
+        if let Err(err) = write() {
+            for path in &created {
+                let _ = fs::remove_file(path);
+                let _ = fs::remove_file(metadata_path(path));
+            }
+            return Err(err);
+        }
+
+        let num_shards = created.len();
+        let paths = created
+            .iter()
+            .enumerate()
+            .map(|(index, partial)| {
+                let path = PathBuf::from(shardspec::shard_name(output_prefix, index, num_shards));
+                fs::rename(partial, &path)?;
+                if metadata_path(partial).exists() {
+                    fs::rename(metadata_path(partial), metadata_path(&path))?;
+                }
+                Ok(path)
+            })
+            .collect::<Result<_>>()?;
+        Ok(QueryReport { paths, num_records })
+    }
+}
+
+impl fmt::Debug for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Query")
+            .field("select", &self.select)
+            .field("num_filters", &self.filters.len())
+            .field("limit", &self.limit)
+            .field("reader", &self.reader)
+            .finish()
+    }
+}
+
+/// Options for [Query::run_to_files].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct QueryOutput {
+    /// The maximum number of records per output file, unbounded if not set.
+    pub records_per_shard: Option<u64>,
+    /// The configuration of the writers.
+    pub writer: RecordWriterConfig,
+}
+
+/// The outcome of [Query::run_to_files].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QueryReport {
+    /// The output files in order.
+    pub paths: Vec<PathBuf>,
+    /// The number of written records.
+    pub num_records: u64,
+}
+
+/// The file being read by a [QueryIter].
+struct QueryFile {
+    path: PathBuf,
+    records: BytesIter<BufReader<File>>,
+    /// The projection to decode, or `None` to decode whole examples.
+    projection: Option<FeatureProjection>,
+    /// The index of the next record in the file.
+    index: u64,
+}
+
+/// Iterator of the matching examples of a [Query].
+pub struct QueryIter {
+    query: Query,
+    paths: std::vec::IntoIter<PathBuf>,
+    file: Option<QueryFile>,
+    num_matched: u64,
+    done: bool,
+}
+
+impl QueryIter {
+    fn open(&self, path: PathBuf) -> Result<QueryFile> {
+        let Query {
+            select, filters, ..
+        } = &self.query;
+        let projection = match select {
+            Some(select) if filters.iter().all(|filter| filter.keys.is_some()) => {
+                let mut keys = select.clone();
+                for filter in filters {
+                    keys.extend(filter.keys.iter().flatten().cloned());
+                }
+                Some(FeatureProjection {
+                    keys,
+                    sorted_keys: shard_declares_sorted_keys(&path),
+                })
+            }
+            _ => None,
+        };
+        let file =
+            File::open(&path).map_err(|err| Error::from_io_with_context(err, &path, None))?;
+        let records = BytesIter::from_reader(BufReader::new(file), self.query.reader.clone());
+        Ok(QueryFile {
+            path,
+            records,
+            projection,
+            index: 0,
+        })
+    }
+
+    fn next_match(&mut self) -> Result<Option<Example>> {
+        loop {
+            if self
+                .query
+                .limit
+                .is_some_and(|limit| self.num_matched >= limit)
+            {
+                return Ok(None);
+            }
+            let file = match &mut self.file {
+                Some(file) => file,
+                None => match self.paths.next() {
+                    Some(path) => self.file.insert(self.open(path)?),
+                    None => return Ok(None),
+                },
+            };
+            let bytes = match file.records.next() {
+                Some(bytes) => bytes,
+                None => {
+                    self.file = None;
+                    continue;
+                }
+            };
+
+            let index = file.index;
+            file.index += 1;
+            let limits = &self.query.reader.limits;
+            let example = (|| {
+                let bytes = bytes?;
+                let mut example = match &file.projection {
+                    Some(projection) => {
+                        Example::decode_projected_with_limits(&bytes, projection, limits)?
+                    }
+                    None => Example::decode_with_limits(&bytes, limits)?,
+                };
+                for filter in &self.query.filters {
+                    if !(filter.predicate)(&example)? {
+                        return Ok(None);
+                    }
+                }
+                if let (Some(select), Some(features)) =
+                    (&self.query.select, example.features.as_mut())
+                {
+                    features.feature.retain(|key, _| select.contains(key));
+                }
+                Ok(Some(example))
+            })()
+            .map_err(|err| Error::RecordFailed {
+                path: file.path.clone(),
+                index,
+                source: Box::new(err),
+            })?;
+
+            if let Some(example) = example {
+                self.num_matched += 1;
+                return Ok(Some(example));
+            }
+        }
+    }
+}
+
+impl Iterator for QueryIter {
+    type Item = Result<Example>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.next_match().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.done = true;
+        }
+        result
+    }
+}
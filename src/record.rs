@@ -1,8 +1,19 @@
 //! Marker traits.
+//!
+//! The [Record] trait has owned and borrowed paths of serialization. Implementors
+//! must provide the owned [from_bytes](Record::from_bytes) and [to_bytes](Record::to_bytes),
+//! while readers and writers call the borrowed [from_slice](Record::from_slice) and
+//! [encode_into](Record::encode_into) with reused buffers. The borrowed methods default
+//! to the owned ones at the cost of a copy, and can be overridden to avoid it.
+//!
+//! There is no blanket implementation for [prost] messages, which would conflict with
+//! the implementation for [Vec<u8>](Vec). Wrap other messages in [ProstRecord] instead.
 
+#[cfg(feature = "proto-summary")]
+use crate::protobuf::Event;
 use crate::{
-    error::Error,
-    protobuf::{Event, Example},
+    error::Error, limits::Limits, protobuf::Example, protobuf_ext::ContextOnly,
+    record_writer::SortedIndicesEncoding,
 };
 use prost::Message as _;
 
@@ -15,6 +26,68 @@ where
     fn from_bytes(bytes: Vec<u8>) -> Result<Self, Error>;
     /// Serialze to bytes in TFRecord format.
     fn to_bytes(record: Self) -> Result<Vec<u8>, Error>;
+
+    /// Deserialze from borrowed bytes in TFRecord format.
+    ///
+    /// It defaults to copying the bytes to [from_bytes](Record::from_bytes).
+    fn from_slice(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_bytes(bytes.to_vec())
+    }
+
+    /// Serialze by appending to the buffer.
+    ///
+    /// It defaults to copying the output of [to_bytes](Record::to_bytes).
+    fn encode_into(record: Self, buf: &mut Vec<u8>) -> Result<(), Error> {
+        buf.extend_from_slice(&Self::to_bytes(record)?);
+        Ok(())
+    }
+
+    /// Deserialze from bytes in TFRecord format, rejecting records exceeding the limits.
+    ///
+    /// It defaults to [from_bytes](Record::from_bytes). The record length is checked
+    /// by readers before this call.
+    fn from_bytes_with_limits(bytes: Vec<u8>, limits: &Limits) -> Result<Self, Error> {
+        let _ = limits;
+        Self::from_bytes(bytes)
+    }
+
+    /// Deserialze from borrowed bytes in TFRecord format, rejecting records exceeding the limits.
+    ///
+    /// It defaults to [from_slice](Record::from_slice). The record length is checked
+    /// by readers before this call.
+    fn from_slice_with_limits(bytes: &[u8], limits: &Limits) -> Result<Self, Error> {
+        let _ = limits;
+        Self::from_slice(bytes)
+    }
+
+    /// Serialze to bytes whose content does not depend on map iteration order.
+    ///
+    /// It defaults to [to_bytes](Record::to_bytes) for types without map fields.
+    fn to_bytes_canonical(record: Self) -> Result<Vec<u8>, Error> {
+        Self::to_bytes(record)
+    }
+
+    /// Check that the map entries of serialized bytes are sorted by key without duplicates.
+    ///
+    /// It defaults to true for types without map fields.
+    fn has_sorted_keys(bytes: &[u8]) -> Result<bool, Error> {
+        let _ = bytes;
+        Ok(true)
+    }
+
+    /// Encode the sorted indices of a record, as configured by
+    /// [encode_sorted_indices](crate::RecordWriterConfig::encode_sorted_indices).
+    ///
+    /// It defaults to failing for types without features.
+    fn encode_sorted_indices(
+        record: Self,
+        encoding: &SortedIndicesEncoding,
+    ) -> Result<Self, Error> {
+        let _ = (record, encoding);
+        Err(Error::InvalidArgumentsError {
+            desc: "the record type has no features to encode sorted indices".into(),
+        })
+    }
 }
 
 impl Record for Vec<u8> {
@@ -25,6 +98,15 @@ impl Record for Vec<u8> {
     fn to_bytes(record: Self) -> Result<Vec<u8>, Error> {
         Ok(record)
     }
+
+    fn from_slice(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(bytes.to_vec())
+    }
+
+    fn encode_into(record: Self, buf: &mut Vec<u8>) -> Result<(), Error> {
+        buf.extend_from_slice(&record);
+        Ok(())
+    }
 }
 
 impl Record for Example {
@@ -38,8 +120,73 @@ impl Record for Example {
         Example::encode(&record, &mut bytes)?;
         Ok(bytes)
     }
+
+    fn from_slice(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Example::decode(bytes)?)
+    }
+
+    fn encode_into(record: Self, buf: &mut Vec<u8>) -> Result<(), Error> {
+        Example::encode(&record, buf)?;
+        Ok(())
+    }
+
+    fn from_bytes_with_limits(bytes: Vec<u8>, limits: &Limits) -> Result<Self, Error> {
+        Example::decode_with_limits(&bytes, limits)
+    }
+
+    fn from_slice_with_limits(bytes: &[u8], limits: &Limits) -> Result<Self, Error> {
+        Example::decode_with_limits(bytes, limits)
+    }
+
+    fn to_bytes_canonical(record: Self) -> Result<Vec<u8>, Error> {
+        Ok(record.encode_canonical_to_vec())
+    }
+
+    fn has_sorted_keys(bytes: &[u8]) -> Result<bool, Error> {
+        Example::has_sorted_keys(bytes)
+    }
+
+    fn encode_sorted_indices(
+        mut record: Self,
+        encoding: &SortedIndicesEncoding,
+    ) -> Result<Self, Error> {
+        for key in &encoding.keys {
+            if record.try_get_i64s(key)?.is_some() {
+                record.encode_sorted_indices(key, encoding.sort)?;
+            }
+        }
+        Ok(record)
+    }
 }
 
+impl Record for ContextOnly {
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, Error> {
+        ContextOnly::decode(&bytes)
+    }
+
+    /// Serialize as a sequence example without feature lists.
+    fn to_bytes(record: Self) -> Result<Vec<u8>, Error> {
+        Ok(record.into_sequence_example().encode_to_vec())
+    }
+
+    fn from_slice(bytes: &[u8]) -> Result<Self, Error> {
+        ContextOnly::decode(bytes)
+    }
+
+    fn from_bytes_with_limits(bytes: Vec<u8>, limits: &Limits) -> Result<Self, Error> {
+        ContextOnly::decode_with_limits(&bytes, limits)
+    }
+
+    fn from_slice_with_limits(bytes: &[u8], limits: &Limits) -> Result<Self, Error> {
+        ContextOnly::decode_with_limits(bytes, limits)
+    }
+
+    fn to_bytes_canonical(record: Self) -> Result<Vec<u8>, Error> {
+        Ok(record.into_sequence_example().encode_canonical_to_vec())
+    }
+}
+
+#[cfg(feature = "proto-summary")]
 impl Record for Event {
     fn from_bytes(bytes: Vec<u8>) -> Result<Self, Error> {
         let example = Event::decode(bytes.as_ref())?;
@@ -51,4 +198,39 @@ impl Record for Event {
         Event::encode(&record, &mut bytes)?;
         Ok(bytes)
     }
+
+    fn from_slice(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Event::decode(bytes)?)
+    }
+
+    fn encode_into(record: Self, buf: &mut Vec<u8>) -> Result<(), Error> {
+        Event::encode(&record, buf)?;
+        Ok(())
+    }
+}
+
+/// The [Record] wrapper of an arbitrary [prost] message.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ProstRecord<M>(pub M);
+
+impl<M> Record for ProstRecord<M>
+where
+    M: prost::Message + Default,
+{
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, Error> {
+        Self::from_slice(&bytes)
+    }
+
+    fn to_bytes(record: Self) -> Result<Vec<u8>, Error> {
+        Ok(record.0.encode_to_vec())
+    }
+
+    fn from_slice(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Self(M::decode(bytes)?))
+    }
+
+    fn encode_into(record: Self, buf: &mut Vec<u8>) -> Result<(), Error> {
+        record.0.encode(buf)?;
+        Ok(())
+    }
 }
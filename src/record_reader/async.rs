@@ -1,11 +1,16 @@
 use super::RecordReaderConfig;
 use crate::{
-    error::{Error, Result},
-    protobuf::{Event, Example},
+    compression::DecodedAsync,
+    error::{ensure_argument, Error, Result},
+    io::r#async::with_timeout,
+    protobuf::Example,
+    protobuf_ext::FeatureProjection,
     record::Record,
+    source::{RecordSource, SourceReader},
 };
 use async_std::{fs::File, io::BufReader, path::Path};
 use futures::{
+    future,
     io::AsyncRead,
     stream::{BoxStream, Stream, StreamExt},
 };
@@ -13,12 +18,58 @@ use pin_project::pin_project;
 use std::{
     marker::PhantomData,
     pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
     task::{Context, Poll},
 };
 
 pub type BytesStream<R> = RecordStream<Vec<u8>, R>;
 pub type ExampleStream<R> = RecordStream<Example, R>;
-pub type EventStream<R> = RecordStream<Event, R>;
+#[cfg(feature = "proto-summary")]
+pub type EventStream<R> = RecordStream<crate::protobuf::Event, R>;
+
+/// The reader, the number of records read, the look-ahead length of the next record, the
+/// buffer reused across records and the mapping of frames.
+type UnfoldState<R, F> = (
+    DecodedAsync<R>,
+    u64,
+    Option<Result<Option<usize>>>,
+    Vec<u8>,
+    F,
+);
+
+/// The order of the records decoded in parallel by
+/// [from_reader_parallel](RecordStream::from_reader_parallel).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DecodeOrder {
+    /// Yield the records in the order they are read.
+    #[default]
+    Ordered,
+    /// Yield the records as soon as they are decoded.
+    Unordered,
+}
+
+/// Configuration for decoding records in parallel.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParallelDecodeConfig {
+    /// The number of records decoded at once on blocking tasks. It must be positive.
+    ///
+    /// It defaults to the available parallelism.
+    pub num_workers: usize,
+    /// The order of the decoded records.
+    pub order: DecodeOrder,
+}
+
+impl Default for ParallelDecodeConfig {
+    fn default() -> Self {
+        Self {
+            num_workers: std::thread::available_parallelism().map_or(1, |num| num.get()),
+            order: DecodeOrder::Ordered,
+        }
+    }
+}
 
 /// Stream of record `T` from reader `R`.
 #[pin_project]
@@ -42,20 +93,182 @@ where
     where
         R: 'static + Unpin + Send,
     {
-        let RecordReaderConfig { check_integrity } = config;
-
-        let stream = futures::stream::try_unfold(reader, move |mut reader| async move {
-            let bytes = crate::io::r#async::try_read_record(&mut reader, check_integrity).await?;
-            let record = bytes.map(T::from_bytes).transpose()?;
-            Ok(record.map(|record| (record, reader)))
-        })
-        .boxed();
+        let integrity = config.integrity;
+        let limits = config.limits.clone();
+        let stream = read_frames(reader, config, move |buf, expect_cksum, check_data| {
+            integrity.verify_and_decode(buf, expect_cksum, check_data, &limits)
+        });
 
         Self {
             stream,
             _phantom: PhantomData,
         }
     }
+
+    /// Load records from a reader type with [AsyncRead] trait, decoding them on a pool of
+    /// blocking tasks.
+    ///
+    /// The records are read sequentially, while up to
+    /// [num_workers](ParallelDecodeConfig::num_workers) records are verified and decoded
+    /// at once. In [unordered](DecodeOrder::Unordered) order, a record decoded early is
+    /// yielded before the records read before it. The total length of the payloads read
+    /// but not yet yielded is capped by
+    /// [max_in_flight_bytes](crate::Limits::max_in_flight_bytes) of the reader limits. The
+    /// stream ends after the first error.
+    ///
+    /// ```rust
+    /// # async_std::task::block_on(async {
+    /// use futures::stream::TryStreamExt as _;
+    /// use tfrecord::{samples, ExampleStream, ParallelDecodeConfig};
+    ///
+    /// let dataset = samples::tiny_dataset(1, 8)?;
+    /// let file = async_std::fs::File::open(&dataset.paths()[0]).await?;
+    /// let config = ParallelDecodeConfig {
+    ///     num_workers: 4,
+    ///     ..Default::default()
+    /// };
+    /// let stream = ExampleStream::from_reader_parallel(file, Default::default(), config)?;
+    /// let examples: Vec<_> = stream.try_collect().await?;
+    /// assert_eq!(examples, (0..8).map(samples::example).collect::<Vec<_>>());
+    /// # tfrecord::Result::<()>::Ok(())
+    /// # }).unwrap();
+    /// ```
+    pub fn from_reader_parallel(
+        reader: R,
+        config: RecordReaderConfig,
+        parallel: ParallelDecodeConfig,
+    ) -> Result<Self>
+    where
+        T: 'static + Send,
+        R: 'static + Unpin + Send,
+    {
+        let ParallelDecodeConfig { num_workers, order } = parallel;
+        ensure_argument!(num_workers > 0, "the number of workers must be positive");
+
+        let integrity = config.integrity;
+        let limits = config.limits.clone();
+        // the payload bytes read but not yet yielded
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        // the buffer is handed over to the worker
+        let frames = read_frames(reader, config, |buf, expect_cksum, check_data| {
+            Ok((std::mem::take(buf), expect_cksum, check_data))
+        });
+        let decoded = frames.map({
+            let in_flight = in_flight.clone();
+            move |frame| {
+                let limits = limits.clone();
+                // count the frame as it is taken by the buffer, before it is decoded
+                let frame = frame.and_then(|(bytes, expect_cksum, check_data)| {
+                    let len = bytes.len();
+                    let total = in_flight.fetch_add(len, Ordering::SeqCst) + len;
+                    limits.check_in_flight_bytes(total)?;
+                    Ok((bytes, expect_cksum, check_data))
+                });
+                async move {
+                    let (bytes, expect_cksum, check_data) = frame?;
+                    let len = bytes.len();
+                    let record = async_std::task::spawn_blocking(move || {
+                        integrity.verify_and_decode(&bytes, expect_cksum, check_data, &limits)
+                    })
+                    .await;
+                    Ok((len, record?))
+                }
+            }
+        });
+        let decoded = match order {
+            DecodeOrder::Ordered => decoded.buffered(num_workers).boxed(),
+            DecodeOrder::Unordered => decoded.buffer_unordered(num_workers).boxed(),
+        };
+        // end after the first error as the sequential stream does
+        let stream = decoded
+            .scan(false, move |failed, record| {
+                let item = (!*failed).then(|| {
+                    *failed = record.is_err();
+                    record.map(|(len, record)| {
+                        in_flight.fetch_sub(len, Ordering::SeqCst);
+                        record
+                    })
+                });
+                future::ready(item)
+            })
+            .boxed();
+
+        Ok(Self {
+            stream,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+/// Read the frames of records, mapping the payload buffer, its expected checksum and
+/// whether to verify it.
+fn read_frames<R, U, F>(
+    reader: R,
+    config: RecordReaderConfig,
+    map: F,
+) -> BoxStream<'static, Result<U, Error>>
+where
+    R: 'static + AsyncRead + Unpin + Send,
+    F: 'static + FnMut(&mut Vec<u8>, u32, bool) -> Result<U> + Send,
+{
+    let RecordReaderConfig {
+        integrity,
+        limits,
+        op_timeout,
+        latency: _,
+        compression,
+        trace: _,
+    } = config;
+    // an unsupported compression fails the first record
+    let next_len = compression.check_supported().err().map(Err);
+    let reader = DecodedAsync::new(reader, compression);
+
+    let init: UnfoldState<R, F> = (reader, 0, next_len, vec![], map);
+    futures::stream::try_unfold(
+        init,
+        move |(mut reader, index, next_len, mut buf, mut map)| {
+            let limits = limits.clone();
+            async move {
+                let read_record = async {
+                    let len = match next_len {
+                        Some(len) => len?,
+                        None => {
+                            crate::io::r#async::try_read_len(&mut reader, integrity.checks_len())
+                                .await?
+                        }
+                    };
+                    let len = match len {
+                        Some(len) => len,
+                        None => return Ok(None),
+                    };
+                    limits.check_record_len(len)?;
+                    let expect_cksum =
+                        crate::io::r#async::try_read_record_data_into(&mut reader, len, &mut buf)
+                            .await?;
+
+                    // look ahead the next length to tell if the record is the last one
+                    let mut next_len = None;
+                    if integrity.needs_last() {
+                        next_len = Some(
+                            crate::io::r#async::try_read_len(&mut reader, integrity.checks_len())
+                                .await,
+                        );
+                    }
+                    let is_last = matches!(next_len, Some(Ok(None) | Err(_)));
+                    let check_data = integrity.checks_data(index, is_last);
+                    Ok(Some((expect_cksum, check_data, next_len)))
+                };
+                let (expect_cksum, check_data, next_len) =
+                    match with_timeout("read_record", op_timeout, read_record).await? {
+                        Some(record) => record,
+                        None => return Ok(None),
+                    };
+                let record = map(&mut buf, expect_cksum, check_data)?;
+                Ok(Some((record, (reader, index + 1, next_len, buf, map))))
+            }
+        },
+    )
+    .boxed()
 }
 
 impl<T> RecordStream<T, BufReader<File>>
@@ -68,10 +281,96 @@ where
         T: Record,
         P: AsRef<Path>,
     {
-        let reader = BufReader::new(File::open(path).await?);
+        let file = with_timeout("open", config.op_timeout, async {
+            Ok(File::open(path).await?)
+        })
+        .await?;
+        let reader = BufReader::new(file);
         let reader = Self::from_reader(reader, config);
         Ok(reader)
     }
+
+    /// Load records from a file, decoding them on a pool of blocking tasks as
+    /// [from_reader_parallel](RecordStream::from_reader_parallel) does.
+    pub async fn open_parallel<P>(
+        path: P,
+        config: RecordReaderConfig,
+        parallel: ParallelDecodeConfig,
+    ) -> Result<Self>
+    where
+        T: 'static + Send,
+        P: AsRef<Path>,
+    {
+        let file = with_timeout("open", config.op_timeout, async {
+            Ok(File::open(path).await?)
+        })
+        .await?;
+        Self::from_reader_parallel(BufReader::new(file), config, parallel)
+    }
+}
+
+impl<T, S> RecordStream<T, SourceReader<S>>
+where
+    T: Record,
+    S: RecordSource,
+{
+    /// Load records from a [RecordSource] read by a [SourceReader].
+    pub fn from_source(source: Arc<S>, config: RecordReaderConfig) -> Self {
+        Self::from_reader(SourceReader::new(source), config)
+    }
+}
+
+impl<R> RecordStream<Example, R>
+where
+    R: AsyncRead,
+{
+    /// Load examples from a reader type with [AsyncRead] trait, decoding only the features in the projection.
+    pub fn from_reader_projected(
+        reader: R,
+        config: RecordReaderConfig,
+        projection: FeatureProjection,
+    ) -> Self
+    where
+        R: 'static + Unpin + Send,
+    {
+        let limits = config.limits.clone();
+        let stream = BytesStream::from_reader(reader, config)
+            .map(move |bytes| Example::decode_projected_with_limits(&bytes?, &projection, &limits))
+            .boxed();
+
+        Self {
+            stream,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Convert the examples to values by [from_example](crate::serde_example::from_example).
+    #[cfg(feature = "with-serde")]
+    pub fn into_serde<T>(self) -> impl Stream<Item = Result<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.map(|example| crate::serde_example::from_example(&example?))
+    }
+}
+
+impl RecordStream<Example, BufReader<File>> {
+    /// Load examples from a file, decoding only the features in the projection.
+    pub async fn stream_projected<P>(
+        path: P,
+        config: RecordReaderConfig,
+        projection: FeatureProjection,
+    ) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let file = with_timeout("open", config.op_timeout, async {
+            Ok(File::open(path).await?)
+        })
+        .await?;
+        let reader = BufReader::new(file);
+        Ok(Self::from_reader_projected(reader, config, projection))
+    }
 }
 
 impl<T, R> Stream for RecordStream<T, R>
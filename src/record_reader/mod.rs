@@ -9,19 +9,61 @@ mod r#async;
 #[cfg(feature = "async")]
 pub use r#async::*;
 
+mod range;
 mod sync;
+pub use range::*;
 pub use sync::*;
 
+use crate::{
+    compression::Compression, integrity::IntegrityMode, io::OpTimeout, latency::LatencyRecorder,
+    limits::Limits, trace::TraceHooks,
+};
+
 /// Configuration for record reader.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct RecordReaderConfig {
-    pub check_integrity: bool,
+    /// The policy of verifying checksums.
+    pub integrity: IntegrityMode,
+    pub limits: Limits,
+    /// The deadline of each I/O operation. It only applies to async readers.
+    pub op_timeout: Option<OpTimeout>,
+    /// If set, the latencies of reads are recorded. It only applies to sync readers.
+    pub latency: Option<LatencyRecorder>,
+    /// The [compression](crate::compression) of the stream, which is decompressed before
+    /// reading records.
+    pub compression: Compression,
+    /// If set, a [Stream](crate::trace::Operation::Stream) span is reported to the hooks.
+    /// It only applies to [iter_from](crate::indexer::iter_from) and
+    /// [iter_permuted](crate::indexer::iter_permuted).
+    pub trace: Option<TraceHooks>,
 }
 
 impl Default for RecordReaderConfig {
     fn default() -> Self {
         Self {
-            check_integrity: true,
+            integrity: IntegrityMode::Full,
+            limits: Limits::default(),
+            op_timeout: None,
+            latency: None,
+            compression: Compression::None,
+            trace: None,
+        }
+    }
+}
+
+impl RecordReaderConfig {
+    /// Build the default configuration with the on/off switch of checksum verification.
+    ///
+    /// The `check_integrity` field is replaced by [integrity](RecordReaderConfig::integrity).
+    /// `true` maps to [IntegrityMode::Full] and `false` to [IntegrityMode::Off].
+    #[deprecated(
+        since = "0.14.0",
+        note = "set `integrity` to an `IntegrityMode`, such as `check_integrity.into()`"
+    )]
+    pub fn with_check_integrity(check_integrity: bool) -> Self {
+        Self {
+            integrity: check_integrity.into(),
+            ..Default::default()
         }
     }
 }
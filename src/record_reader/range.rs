@@ -0,0 +1,168 @@
+use super::RecordReaderConfig;
+use crate::{
+    compression::Compression,
+    error::{Error, Result},
+    integrity::IntegrityMode,
+    io::sync::{find_frame_start, try_read_len, try_read_record_data_into},
+    limits::Limits,
+    record::Record,
+};
+use std::{
+    fs::File,
+    io::{BufReader, Seek, SeekFrom},
+    marker::PhantomData,
+    ops::Range,
+    path::Path,
+};
+
+/// Iterator of the records of a file whose frames start in a byte range.
+///
+/// It lets workers split a file by byte ranges without indexing it first. The reader
+/// [resynchronizes](find_frame_start) to the first frame at or after the start of the
+/// range, and yields records until a frame starts at or after the end. A record
+/// straddling the end belongs to the range containing its frame start, so that
+/// adjacent ranges covering a file yield every record exactly once.
+///
+/// ```rust
+/// # fn main() -> tfrecord::Result<()> {
+/// use tfrecord::{samples, Example, SequentialRangeReader};
+///
+/// let dataset = samples::tiny_dataset(1, 10)?;
+/// let path = &dataset.paths()[0];
+/// let len = std::fs::metadata(path)?.len();
+///
+/// let mut count = 0;
+/// for start in (0..len).step_by(100) {
+///     let range = start..(start + 100).min(len);
+///     let reader = SequentialRangeReader::<Example>::new(path, range, Default::default())?;
+///     for example in reader {
+///         example?;
+///         count += 1;
+///     }
+/// }
+/// assert_eq!(count, 10);
+/// # Ok(())
+/// # }
+/// ```
+pub struct SequentialRangeReader<T>
+where
+    T: Record,
+{
+    reader: BufReader<File>,
+    range: Range<u64>,
+    file_len: u64,
+    /// The start of the next frame, or `None` before resynchronization.
+    pos: Option<u64>,
+    done: bool,
+    integrity: IntegrityMode,
+    limits: Limits,
+    /// The number of records read.
+    index: u64,
+    buf: Vec<u8>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> SequentialRangeReader<T>
+where
+    T: Record,
+{
+    /// Read the records of a file whose frames start in the byte range.
+    pub fn new<P>(path: P, byte_range: Range<u64>, config: RecordReaderConfig) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let RecordReaderConfig {
+            integrity,
+            limits,
+            op_timeout: _,
+            latency: _,
+            compression,
+            trace: _,
+        } = config;
+        if compression != Compression::None {
+            return Err(Error::Unsupported {
+                capability: "byte ranges of compressed files".into(),
+                feature: None,
+            });
+        }
+        let path = path.as_ref();
+        let file =
+            File::open(path).map_err(|error| Error::from_io_with_context(error, path, None))?;
+        let file_len = file.metadata()?.len();
+
+        Ok(Self {
+            reader: BufReader::new(file),
+            range: byte_range,
+            file_len,
+            pos: None,
+            done: false,
+            integrity,
+            limits,
+            index: 0,
+            buf: vec![],
+            _phantom: PhantomData,
+        })
+    }
+
+    /// The start of the next frame, once resynchronized.
+    pub fn position(&self) -> Option<u64> {
+        self.pos
+    }
+
+    fn next_record(&mut self) -> Result<Option<T>> {
+        let pos = match self.pos {
+            Some(pos) => pos,
+            None => {
+                if self.range.is_empty() {
+                    return Ok(None);
+                }
+                let pos = match find_frame_start(&mut self.reader, self.range.start)? {
+                    Some(pos) => pos,
+                    None => return Ok(None),
+                };
+                self.reader.seek(SeekFrom::Start(pos))?;
+                self.pos = Some(pos);
+                pos
+            }
+        };
+        if pos >= self.range.end {
+            return Ok(None);
+        }
+
+        let len = match try_read_len(&mut self.reader, self.integrity.checks_len())? {
+            Some(len) => len,
+            None => return Ok(None),
+        };
+        self.limits.check_record_len(len)?;
+        let expect_cksum = try_read_record_data_into(&mut self.reader, len, &mut self.buf)?;
+        let next = pos + len as u64 + 16;
+        self.pos = Some(next);
+
+        let check_data = self
+            .integrity
+            .checks_data(self.index, next == self.file_len);
+        self.index += 1;
+        let record =
+            self.integrity
+                .verify_and_decode(&self.buf, expect_cksum, check_data, &self.limits)?;
+        Ok(Some(record))
+    }
+}
+
+impl<T> Iterator for SequentialRangeReader<T>
+where
+    T: Record,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.next_record().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.done = true;
+        }
+        result
+    }
+}
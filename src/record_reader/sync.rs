@@ -1,19 +1,25 @@
 use super::RecordReaderConfig;
 use crate::{
+    compression::Decoded,
     error::Result,
-    protobuf::{Event, Example},
+    integrity::IntegrityMode,
+    latency::{self, LatencyRecorder, Phase},
+    limits::Limits,
+    protobuf::Example,
+    protobuf_ext::FeatureProjection,
     record::Record,
 };
 use std::{
     fs::File,
-    io::{prelude::*, BufReader},
+    io::{prelude::*, BufReader, Cursor, SeekFrom},
     marker::PhantomData,
     path::Path,
 };
 
 pub type BytesIter<R> = RecordIter<Vec<u8>, R>;
 pub type ExampleIter<R> = RecordIter<Example, R>;
-pub type EventIter<R> = RecordIter<Event, R>;
+#[cfg(feature = "proto-summary")]
+pub type EventIter<R> = RecordIter<crate::protobuf::Event, R>;
 
 /// Iterator of record `T` from reader `R`.
 pub struct RecordIter<T, R>
@@ -21,8 +27,16 @@ where
     T: Record,
     R: Read,
 {
-    reader: Option<R>,
-    check_integrity: bool,
+    reader: Option<Decoded<R>>,
+    integrity: IntegrityMode,
+    limits: Limits,
+    /// The number of records read.
+    index: u64,
+    /// The look-ahead length of the next record.
+    next_len: Option<Result<Option<usize>>>,
+    /// The buffer reused across records.
+    buf: Vec<u8>,
+    latency: Option<LatencyRecorder>,
     _phantom: PhantomData<T>,
 }
 
@@ -33,14 +47,149 @@ where
 {
     /// Read records from a reader implementing [Read](std::io::Read).
     pub fn from_reader(reader: R, config: RecordReaderConfig) -> Self {
-        let RecordReaderConfig { check_integrity } = config;
+        let RecordReaderConfig {
+            integrity,
+            limits,
+            op_timeout: _,
+            latency,
+            compression,
+            trace: _,
+        } = config;
+        // an unsupported compression fails the first record
+        let next_len = compression.check_supported().err().map(Err);
 
         Self {
-            reader: Some(reader),
-            check_integrity,
+            reader: Some(Decoded::new(reader, compression)),
+            integrity,
+            limits,
+            index: 0,
+            next_len,
+            buf: vec![],
+            latency,
             _phantom: PhantomData,
         }
     }
+
+    /// Read the raw bytes of the next record into the buffer, returning the record length,
+    /// or `Ok(None)` at the end of the reader.
+    ///
+    /// The buffer is resized to the record, so that reusing it across records saves the
+    /// allocation per record. The checksums are verified as by the iterator, and the bytes
+    /// can be decoded by [from_slice](Record::from_slice) without a copy.
+    ///
+    /// ```rust
+    /// # fn main() -> tfrecord::Result<()> {
+    /// use tfrecord::{samples, Example, ExampleIter, Record};
+    ///
+    /// let dataset = samples::tiny_dataset(1, 3)?;
+    /// let mut reader = ExampleIter::open(&dataset.paths()[0], Default::default())?;
+    /// let mut buf = vec![];
+    /// while let Some(len) = reader.read_into(&mut buf)? {
+    ///     assert_eq!(len, buf.len());
+    ///     let example = Example::from_slice(&buf)?;
+    ///     assert_eq!(example.get_i64s("id")?.len(), 1);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_into(&mut self, buf: &mut Vec<u8>) -> Result<Option<usize>> {
+        let (expect_cksum, check_data) = match self.read_frame(buf).transpose()? {
+            Some(frame) => frame,
+            None => return Ok(None),
+        };
+        if check_data {
+            crate::utils::verify_checksum(buf, expect_cksum)?;
+        }
+        Ok(Some(buf.len()))
+    }
+
+    /// Read the payload of the next record into the buffer, returning the expected
+    /// checksum and whether to verify it.
+    ///
+    /// The reader is dropped at the end or on the first failure.
+    fn read_frame(&mut self, buf: &mut Vec<u8>) -> Option<Result<(u32, bool)>> {
+        let reader = self.reader.as_mut()?;
+        let Self {
+            integrity, index, ..
+        } = *self;
+        let next_len = &mut self.next_len;
+        let limits = &self.limits;
+        let frame = latency::time(self.latency.as_ref(), Phase::Read, || {
+            let len = match next_len.take() {
+                Some(len) => len?,
+                None => crate::io::sync::try_read_len(reader, integrity.checks_len())?,
+            };
+            let len = match len {
+                Some(len) => len,
+                None => return Ok(None),
+            };
+            limits.check_record_len(len)?;
+            let expect_cksum = crate::io::sync::try_read_record_data_into(reader, len, buf)?;
+
+            // look ahead the next length to tell if the record is the last one
+            let is_last = integrity.needs_last() && {
+                let len = crate::io::sync::try_read_len(reader, integrity.checks_len());
+                let is_last = !matches!(len, Ok(Some(_)));
+                *next_len = Some(len);
+                is_last
+            };
+            let check_data = integrity.checks_data(index, is_last);
+            Ok(Some((expect_cksum, check_data)))
+        })
+        .transpose();
+
+        match frame {
+            Some(Ok(frame)) => {
+                self.index += 1;
+                Some(Ok(frame))
+            }
+            Some(Err(err)) => {
+                self.reader = None;
+                Some(Err(err))
+            }
+            None => {
+                self.reader = None;
+                None
+            }
+        }
+    }
+}
+
+impl<T, R> RecordIter<T, R>
+where
+    T: Record,
+    R: Read + Seek,
+{
+    /// Skip at most `n` records and return the number of skipped records.
+    ///
+    /// The payloads are skipped by seeking past them, without being read, checksummed or decoded.
+    /// The checksums of record lengths are verified if `check_len_checksum` is set.
+    /// The iterator continues with the record after the skipped ones.
+    pub fn skip_records(&mut self, n: usize, check_len_checksum: bool) -> Result<usize> {
+        let reader = match self.reader.as_mut() {
+            Some(reader) => reader,
+            None => return Ok(0),
+        };
+
+        for count in 0..n {
+            let len = match self.next_len.take() {
+                Some(len) => len,
+                None => crate::io::sync::try_read_len(reader, check_len_checksum),
+            };
+            let len = match len? {
+                Some(len) => len,
+                None => {
+                    self.reader = None;
+                    return Ok(count);
+                }
+            };
+            self.limits.check_record_len(len)?;
+            // skip the payload and its checksum
+            reader.seek(SeekFrom::Current(len as i64 + 4))?;
+            self.index += 1;
+        }
+        Ok(n)
+    }
 }
 
 impl<T> RecordIter<T, BufReader<File>>
@@ -48,6 +197,20 @@ where
     T: Record,
 {
     /// Read records from a file.
+    ///
+    /// An empty file is valid and yields no records.
+    ///
+    /// ```rust
+    /// # fn main() -> tfrecord::Result<()> {
+    /// use tfrecord::{samples, ExampleIter};
+    ///
+    /// let dataset = samples::tiny_dataset(1, 3)?;
+    /// let examples: Vec<_> =
+    ///     ExampleIter::open(&dataset.paths()[0], Default::default())?.collect::<Result<_, _>>()?;
+    /// assert_eq!(examples, (0..3).map(samples::example).collect::<Vec<_>>());
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn open<P>(path: P, config: RecordReaderConfig) -> Result<Self>
     where
         P: AsRef<Path>,
@@ -58,6 +221,60 @@ where
     }
 }
 
+impl<T> RecordIter<T, Cursor<Vec<u8>>>
+where
+    T: Record,
+{
+    /// Read records from bytes in memory, such as the contents of a
+    /// [MemoryBuffer](crate::memory::MemoryBuffer).
+    pub fn from_bytes<B>(bytes: B, config: RecordReaderConfig) -> Self
+    where
+        B: Into<Vec<u8>>,
+    {
+        Self::from_reader(Cursor::new(bytes.into()), config)
+    }
+}
+
+impl<R> RecordIter<Example, R>
+where
+    R: Read,
+{
+    /// Read examples from a reader, decoding only the features in the projection.
+    pub fn from_reader_projected(
+        reader: R,
+        config: RecordReaderConfig,
+        projection: FeatureProjection,
+    ) -> impl Iterator<Item = Result<Example>> {
+        let limits = config.limits.clone();
+        BytesIter::from_reader(reader, config)
+            .map(move |bytes| Example::decode_projected_with_limits(&bytes?, &projection, &limits))
+    }
+
+    /// Convert the examples to values by [from_example](crate::serde_example::from_example).
+    #[cfg(feature = "with-serde")]
+    pub fn into_serde<T>(self) -> impl Iterator<Item = Result<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.map(|example| crate::serde_example::from_example(&example?))
+    }
+}
+
+impl RecordIter<Example, BufReader<File>> {
+    /// Read examples from a file, decoding only the features in the projection.
+    pub fn open_projected<P>(
+        path: P,
+        config: RecordReaderConfig,
+        projection: FeatureProjection,
+    ) -> Result<impl Iterator<Item = Result<Example>>>
+    where
+        P: AsRef<Path>,
+    {
+        let reader = BufReader::new(File::open(path.as_ref())?);
+        Ok(Self::from_reader_projected(reader, config, projection))
+    }
+}
+
 impl<T, R> Iterator for RecordIter<T, R>
 where
     T: Record,
@@ -66,14 +283,15 @@ where
     type Item = Result<T>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let reader = self.reader.as_mut()?;
-        let bytes: Option<Result<_>> =
-            crate::io::sync::try_read_record(reader, self.check_integrity).transpose();
-
-        if bytes.is_none() {
-            self.reader = None;
-        }
-        let record = bytes?.and_then(T::from_bytes);
-        Some(record)
+        let mut buf = std::mem::take(&mut self.buf);
+        let record = self.read_frame(&mut buf).map(|frame| {
+            let (expect_cksum, check_data) = frame?;
+            latency::time(self.latency.as_ref(), Phase::Decode, || {
+                self.integrity
+                    .verify_and_decode(&buf, expect_cksum, check_data, &self.limits)
+            })
+        });
+        self.buf = buf;
+        record
     }
 }
@@ -0,0 +1,140 @@
+use crate::{
+    error::{Error, Result},
+    io::sync::HEADER_LEN,
+};
+use std::{
+    fs::{File, OpenOptions},
+    io::{prelude::*, BufReader, SeekFrom},
+    path::Path,
+};
+
+/// The size of the frame footer, the payload checksum.
+const FOOTER_LEN: u64 = 4;
+
+/// The existing contents of a file found by [RecordWriter::append](super::RecordWriter::append)
+/// before appending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct AppendReport {
+    /// The number of valid records kept in the file.
+    pub num_records: u64,
+    /// The length of the valid records, where appending starts.
+    pub valid_len: u64,
+    /// The number of bytes truncated after the valid records, such as a partially
+    /// written frame.
+    pub truncated_len: u64,
+}
+
+impl AppendReport {
+    /// Returns true if bytes after the valid records were truncated.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated_len > 0
+    }
+}
+
+/// Open a file for appending, creating it if missing, and truncate it after its last
+/// valid record.
+///
+/// A frame extending past the end of the file is a partial write and is truncated.
+/// A complete frame failing checksum verification fails with
+/// [Error::ChecksumMismatch], unless `force` is set, in which case the file is
+/// truncated at that frame.
+pub(crate) fn open_for_append(path: &Path, force: bool) -> Result<(File, AppendReport)> {
+    let with_path = |err| Error::from_io_with_context(err, path, None);
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(with_path)?;
+
+    let len = file.metadata().map_err(with_path)?.len();
+    let (num_records, valid_len) = match scan_valid_prefix(&mut file, len)? {
+        Scan::Complete {
+            num_records,
+            valid_len,
+        } => (num_records, valid_len),
+        Scan::Corrupt {
+            num_records,
+            valid_len,
+            error,
+        } => {
+            if !force {
+                return Err(error.with_checksum_context(path, valid_len));
+            }
+            (num_records, valid_len)
+        }
+    };
+
+    if valid_len < len {
+        file.set_len(valid_len).map_err(with_path)?;
+        file.sync_data().map_err(with_path)?;
+    }
+    file.seek(SeekFrom::Start(valid_len)).map_err(with_path)?;
+
+    let report = AppendReport {
+        num_records,
+        valid_len,
+        truncated_len: len - valid_len,
+    };
+    Ok((file, report))
+}
+
+enum Scan {
+    /// The file ends with a complete frame or a partial one.
+    Complete { num_records: u64, valid_len: u64 },
+    /// A complete frame at the valid length fails checksum verification.
+    Corrupt {
+        num_records: u64,
+        valid_len: u64,
+        error: Error,
+    },
+}
+
+/// Scan the frames of a file of the length forward, verifying their checksums.
+fn scan_valid_prefix(file: &mut File, len: u64) -> Result<Scan> {
+    file.seek(SeekFrom::Start(0))?;
+    let mut reader = BufReader::new(file);
+    let mut num_records = 0;
+    let mut pos = 0;
+    let mut buf = vec![];
+
+    loop {
+        let complete = Scan::Complete {
+            num_records,
+            valid_len: pos,
+        };
+        let corrupt = |error| Scan::Corrupt {
+            num_records,
+            valid_len: pos,
+            error,
+        };
+        if len - pos < HEADER_LEN {
+            return Ok(complete);
+        }
+
+        let mut header = [0; HEADER_LEN as usize];
+        reader.read_exact(&mut header)?;
+        let (len_buf, cksum_buf) = header.split_at(8);
+        let cksum = u32::from_le_bytes(cksum_buf.try_into().unwrap());
+        if let Err(error) = crate::utils::verify_checksum(len_buf, cksum) {
+            return Ok(corrupt(error));
+        }
+        let record_len = u64::from_le_bytes(len_buf.try_into().unwrap());
+        let frame_len = record_len
+            .checked_add(HEADER_LEN + FOOTER_LEN)
+            .filter(|&frame_len| frame_len <= len - pos);
+        let Some(frame_len) = frame_len else {
+            // the frame extends past the end of the file
+            return Ok(complete);
+        };
+
+        let expect =
+            crate::io::sync::try_read_record_data_into(&mut reader, record_len as usize, &mut buf)?;
+        if let Err(error) = crate::utils::verify_checksum(&buf, expect) {
+            return Ok(corrupt(error));
+        }
+        num_records += 1;
+        pos += frame_len;
+    }
+}
@@ -1,4 +1,8 @@
+use super::{
+    sync::interrupted, AppendReport, RecordWriterConfig, SortedIndicesEncoding, Unflushed,
+};
 use crate::{
+    compression::Compression,
     error::{Error, Result},
     protobuf::Example,
     record::Record,
@@ -8,6 +12,7 @@ use futures::{
     io::{AsyncWrite, AsyncWriteExt as _},
     sink,
     sink::Sink,
+    stream::{TryStream, TryStreamExt as _},
 };
 use std::marker::PhantomData;
 
@@ -18,12 +23,22 @@ pub type BytesAsyncWriter<W> = RecordAsyncWriter<Vec<u8>, W>;
 pub type ExampleAsyncWriter<W> = RecordAsyncWriter<Example, W>;
 
 /// The record writer.
+///
+/// The writer cannot flush on drop. Dropping it with records sent after the last
+/// [flush](RecordAsyncWriter::flush) reports the lost records as configured by
+/// [on_unflushed_drop](RecordWriterConfig::on_unflushed_drop).
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct RecordAsyncWriter<T, W>
 where
     T: Record,
 {
+    canonical_encoding: bool,
+    sorted_features: bool,
+    encode_sorted_indices: Option<SortedIndicesEncoding>,
     writer: W,
+    unflushed: Unflushed,
+    /// The buffer reused across records.
+    buf: Vec<u8>,
     _phantom: PhantomData<T>,
 }
 
@@ -36,8 +51,83 @@ where
     where
         P: AsRef<Path>,
     {
+        Self::create_with_config(path, Default::default()).await
+    }
+
+    /// Build a writer writing to a new file with custom configuration.
+    pub async fn create_with_config<P>(path: P, config: RecordWriterConfig) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
         let writer = BufWriter::new(File::create(path).await?);
-        Self::from_writer(writer)
+        crate::metadata::sync_sidecar_async(path.as_ref(), &config, Compression::None).await?;
+        Self::from_writer_with_config(writer, config)
+    }
+
+    /// Build a writer appending to a file, and the report of the records already in it.
+    ///
+    /// See [RecordWriter::append](crate::RecordWriter::append).
+    pub async fn append<P>(path: P) -> Result<(Self, AppendReport)>
+    where
+        P: AsRef<Path>,
+    {
+        Self::append_with_config(path, Default::default(), false).await
+    }
+
+    /// Build a writer appending to a file with custom configuration, and the report of
+    /// the records already in it.
+    ///
+    /// See [RecordWriter::append_with_config](crate::RecordWriter::append_with_config).
+    pub async fn append_with_config<P>(
+        path: P,
+        config: RecordWriterConfig,
+        force: bool,
+    ) -> Result<(Self, AppendReport)>
+    where
+        P: AsRef<Path>,
+    {
+        let path: std::path::PathBuf = path.as_ref().into();
+        let sidecar_config = (!config.sorted_features).then(|| config.clone());
+        let (file, report) = async_std::task::spawn_blocking(move || {
+            let (file, report) = super::open_for_append(&path, force)?;
+            if let Some(config) = sidecar_config {
+                crate::metadata::sync_sidecar(&path, &config, Compression::None)?;
+            }
+            Ok::<_, Error>((file, report))
+        })
+        .await?;
+        let writer = Self::from_writer_with_config(BufWriter::new(File::from(file)), config)?;
+        Ok((writer, report))
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl<T> RecordAsyncWriter<T, crate::compression::GzipAsyncWriter<BufWriter<File>>>
+where
+    T: Record,
+{
+    /// Build a writer writing a GZIP-compressed stream to a new file.
+    ///
+    /// See [RecordWriter::create_gzip](crate::RecordWriter::create_gzip). The writer must
+    /// be [finished](RecordAsyncWriter::finish) to end the stream.
+    pub async fn create_gzip<P>(path: P, config: RecordWriterConfig, level: u32) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let level = crate::compression::gzip_level(level)?;
+        let path = path.as_ref();
+        let writer = BufWriter::new(File::create(path).await?);
+        crate::metadata::sync_sidecar_async(path.as_ref(), &config, Compression::Gzip).await?;
+        let writer = crate::compression::GzipAsyncWriter::new(writer, level);
+        Self::from_writer_with_config(writer, config)
+    }
+
+    /// End the compressed stream and close the file.
+    pub async fn finish(mut self) -> Result<()> {
+        self.writer.close().await?;
+        self.unflushed.clear();
+        Ok(())
     }
 }
 
@@ -48,25 +138,108 @@ where
 {
     /// Build a writer from a writer with [AsyncWrite] trait.
     pub fn from_writer(writer: W) -> Result<Self> {
+        Self::from_writer_with_config(writer, Default::default())
+    }
+
+    /// Build a writer from a writer with [AsyncWrite] trait and custom configuration.
+    pub fn from_writer_with_config(writer: W, config: RecordWriterConfig) -> Result<Self> {
+        let unflushed = Unflushed::new(&config);
+        let RecordWriterConfig {
+            canonical_encoding,
+            sorted_features,
+            on_unflushed_drop: _,
+            panic_on_unflushed_drop: _,
+            encode_sorted_indices,
+            stamp_producer: _,
+        } = config;
+
         Ok(Self {
+            canonical_encoding,
+            sorted_features,
+            encode_sorted_indices,
             writer,
+            unflushed,
+            buf: vec![],
             _phantom: PhantomData,
         })
     }
 
     /// Write a record.
     pub async fn send(&mut self, record: T) -> Result<()> {
-        let bytes = T::to_bytes(record)?;
-        crate::io::r#async::try_write_record(&mut self.writer, bytes).await?;
+        let record = match &self.encode_sorted_indices {
+            Some(encoding) => T::encode_sorted_indices(record, encoding)?,
+            None => record,
+        };
+        let len = if self.canonical_encoding || self.sorted_features {
+            let bytes = T::to_bytes_canonical(record)?;
+            debug_assert!(
+                !self.sorted_features || T::has_sorted_keys(&bytes)?,
+                "the canonical encoding produced unsorted feature keys"
+            );
+            crate::io::r#async::try_write_record_slice(&mut self.writer, &bytes).await?;
+            bytes.len()
+        } else {
+            self.buf.clear();
+            T::encode_into(record, &mut self.buf)?;
+            crate::io::r#async::try_write_record_slice(&mut self.writer, &self.buf).await?;
+            self.buf.len()
+        };
+        self.unflushed.add(len);
         Ok(())
     }
 
     /// Flush the output stream asynchronously.
     pub async fn flush(&mut self) -> Result<()> {
         self.writer.flush().await?;
+        self.unflushed.clear();
         Ok(())
     }
 
+    /// The framed bytes of the records sent since the last flush.
+    pub(crate) fn unflushed_bytes(&self) -> u64 {
+        self.unflushed.num_bytes()
+    }
+
+    /// Write the records of a stream and flush, returning the number of written records.
+    ///
+    /// The stream is polled for the next record only after the previous one is written,
+    /// so a fast producer is held back by the writer. On a failure of the stream or of
+    /// the writer, the records written before are flushed, and the error is
+    /// [Error::WriteInterrupted] carrying the number of records committed by this call.
+    /// See [RecordWriter::write_all](crate::RecordWriter::write_all).
+    pub async fn write_stream<S>(&mut self, records: S) -> Result<u64>
+    where
+        S: TryStream<Ok = T>,
+        Error: From<S::Error>,
+    {
+        let records = records.into_stream();
+        futures::pin_mut!(records);
+        let mut num_sent = 0;
+        loop {
+            let result = match records.try_next().await {
+                Ok(Some(record)) => self.send(record).await,
+                Ok(None) => break,
+                Err(err) => Err(err.into()),
+            };
+            if let Err(err) = result {
+                let committed = if self.flush().await.is_ok() {
+                    num_sent
+                } else {
+                    0
+                };
+                return Err(interrupted(committed, err));
+            }
+            num_sent += 1;
+        }
+        self.flush().await.map_err(|err| interrupted(0, err))?;
+        Ok(num_sent)
+    }
+
+    /// Drop the writer without flushing or reporting unflushed records.
+    pub fn abandon(mut self) {
+        self.unflushed.clear();
+    }
+
     /// Convert into a [Sink].
     pub fn into_sink(self) -> impl Sink<T, Error = Error> {
         sink::unfold(self, |mut writer, record| async move {
@@ -75,3 +248,29 @@ where
         })
     }
 }
+
+#[cfg(feature = "with-serde")]
+impl<W> RecordAsyncWriter<Example, W>
+where
+    W: AsyncWrite + Unpin,
+{
+    /// Convert a value to an example by [to_example](crate::serde_example::to_example)
+    /// and write it.
+    pub async fn send_serde<S>(&mut self, value: &S) -> Result<()>
+    where
+        S: serde::Serialize + ?Sized,
+    {
+        self.send(crate::serde_example::to_example(value)?).await
+    }
+}
+
+impl<T, W> Drop for RecordAsyncWriter<T, W>
+where
+    T: Record,
+{
+    fn drop(&mut self) {
+        if !self.unflushed.is_empty() {
+            self.unflushed.report("RecordAsyncWriter", None);
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use super::{RecordWriter, RecordWriterConfig};
+use crate::{
+    error::{ensure_argument, Error, Result},
+    fingerprint::{self, FingerprintLevel},
+    record::Record,
+    utils,
+};
+use std::{
+    ffi::OsString,
+    fmt,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// The default suffix appended to shard file names to name their completion markers.
+pub const DONE_SUFFIX: &str = ".done";
+
+/// The function observing the steps of a commit and the paths they apply to.
+pub type CommitStepFn = dyn Fn(CommitStep, &Path) + Send + Sync;
+
+/// A step of [CommittedWriter::commit], in the order of execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommitStep {
+    /// The buffered records are written to the shard.
+    Finish,
+    /// The shard is synced to the storage.
+    SyncShard,
+    /// The directory of the shard is synced. It happens before the marker is written,
+    /// making the shard entry durable, and after the marker is published, making the
+    /// marker entry durable.
+    SyncDir,
+    /// The marker is written to a temporary file.
+    WriteMarker,
+    /// The temporary marker is synced to the storage.
+    SyncMarker,
+    /// The temporary marker is renamed to the marker path, making it visible.
+    PublishMarker,
+}
+
+/// The contents of completion markers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MarkerFormat {
+    /// An empty file.
+    #[default]
+    Empty,
+    /// A JSON object with the number of records and the fingerprint of the shard at the
+    /// level, such as `{"num_records": 3, "fingerprint": "03…"}`.
+    Json { fingerprint: FingerprintLevel },
+}
+
+/// Configuration for [CommittedWriter].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CommittedWriterConfig {
+    /// The suffix appended to the shard file name to name the marker.
+    pub marker_suffix: String,
+    /// The contents of the marker.
+    pub marker: MarkerFormat,
+    /// The configuration of the underlying writer.
+    pub writer: RecordWriterConfig,
+}
+
+impl Default for CommittedWriterConfig {
+    fn default() -> Self {
+        Self {
+            marker_suffix: DONE_SUFFIX.to_string(),
+            marker: MarkerFormat::default(),
+            writer: RecordWriterConfig::default(),
+        }
+    }
+}
+
+impl CommittedWriterConfig {
+    /// The path of the marker of a shard.
+    pub fn marker_path<P>(&self, path: P) -> PathBuf
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let mut file_name: OsString = path.file_name().unwrap_or_default().into();
+        file_name.push(&self.marker_suffix);
+        path.with_file_name(file_name)
+    }
+}
+
+/// A shard writer publishing the shard with a completion marker.
+///
+/// Consumers polling a directory pick up a shard once its marker exists. The
+/// [commit](CommittedWriter::commit) method makes the shard durable before the marker
+/// becomes visible, in the order of [CommitStep]s:
+///
+/// 1. finish writing the shard,
+/// 2. sync the shard,
+/// 3. sync the directory, a no-op on non-unix platforms,
+/// 4. write the marker to a temporary file next to it,
+/// 5. sync the temporary marker,
+/// 6. rename the temporary marker to the marker path, and
+/// 7. sync the directory again.
+///
+/// Since the marker appears by a rename, consumers never see it empty or partially
+/// written. A stale marker is removed when the writer is created, followed by a sync of
+/// the directory, so that a shard abandoned before the commit, for example by a crash,
+/// never has a marker. Committing consumes
+/// the writer, so no record can be sent afterwards.
+///
+/// ```rust
+/// # fn main() -> tfrecord::Result<()> {
+/// use tfrecord::{samples, CommittedWriter, Example};
+///
+/// let dataset = samples::tiny_dataset(0, 0)?;
+/// let path = dataset.dir().join("shard-0.tfrecord");
+///
+/// let mut writer = CommittedWriter::<Example>::create(&path, Default::default())?;
+/// writer.send(samples::example(0))?;
+/// let marker = writer.commit()?;
+/// assert_eq!(marker, dataset.dir().join("shard-0.tfrecord.done"));
+/// # Ok(())
+/// # }
+/// ```
+pub struct CommittedWriter<T>
+where
+    T: Record,
+{
+    path: PathBuf,
+    marker_path: PathBuf,
+    marker: MarkerFormat,
+    writer: RecordWriter<T, BufWriter<File>>,
+    num_records: u64,
+    on_step: Option<Arc<CommitStepFn>>,
+}
+
+impl<T> CommittedWriter<T>
+where
+    T: Record,
+{
+    /// Build a writer writing to a new shard, removing the stale marker if any.
+    pub fn create<P>(path: P, config: CommittedWriterConfig) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let marker_path = config.marker_path(path);
+        let CommittedWriterConfig {
+            marker_suffix,
+            marker,
+            writer,
+        } = config;
+        ensure_argument!(
+            !marker_suffix.is_empty(),
+            "the marker suffix must not be empty"
+        );
+
+        match fs::remove_file(&marker_path) {
+            Ok(()) => {
+                let dir = parent_dir(&marker_path);
+                utils::sync_dir(dir).map_err(|err| Error::from_io_with_context(err, dir, None))?;
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(Error::from_io_with_context(err, marker_path, None)),
+        }
+        let writer = RecordWriter::create_with_config(path, writer)?;
+
+        Ok(Self {
+            path: path.to_owned(),
+            marker_path,
+            marker,
+            writer,
+            num_records: 0,
+            on_step: None,
+        })
+    }
+
+    /// Observe the steps of the commit, called after each step is done.
+    pub fn with_step_hook<F>(mut self, hook: F) -> Self
+    where
+        F: 'static + Fn(CommitStep, &Path) + Send + Sync,
+    {
+        self.on_step = Some(Arc::new(hook));
+        self
+    }
+
+    /// Write a record.
+    pub fn send(&mut self, record: T) -> Result<()> {
+        self.writer.send(record)?;
+        self.num_records += 1;
+        Ok(())
+    }
+
+    /// The path of the shard.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The path of the marker written by the commit.
+    pub fn marker_path(&self) -> &Path {
+        &self.marker_path
+    }
+
+    /// The number of records sent.
+    pub fn num_records(&self) -> u64 {
+        self.num_records
+    }
+
+    /// Make the shard durable and write the marker, returning the marker path.
+    pub fn commit(self) -> Result<PathBuf> {
+        let Self {
+            path,
+            marker_path,
+            marker,
+            writer,
+            num_records,
+            on_step,
+        } = self;
+        let step = |step: CommitStep, path: &Path| {
+            if let Some(on_step) = &on_step {
+                on_step(step, path);
+            }
+        };
+        let with_path = |err: io::Error, path: &Path| Error::from_io_with_context(err, path, None);
+
+        let file = writer
+            .into_inner()
+            .into_inner()
+            .map_err(|err| with_path(err.into_error(), &path))?;
+        step(CommitStep::Finish, &path);
+
+        file.sync_all().map_err(|err| with_path(err, &path))?;
+        drop(file);
+        step(CommitStep::SyncShard, &path);
+
+        let dir = parent_dir(&path);
+        utils::sync_dir(dir).map_err(|err| with_path(err, dir))?;
+        step(CommitStep::SyncDir, dir);
+
+        let contents = match marker {
+            MarkerFormat::Empty => String::new(),
+            MarkerFormat::Json { fingerprint: level } => {
+                let fingerprint =
+                    fingerprint::fingerprint_paths([path.as_path()], level, Default::default())?;
+                format!(
+                    "{{\"num_records\": {}, \"fingerprint\": \"{}\"}}\n",
+                    num_records, fingerprint
+                )
+            }
+        };
+        let temp_path = temp_marker_path(&marker_path);
+        let mut file = File::create(&temp_path).map_err(|err| with_path(err, &temp_path))?;
+        file.write_all(contents.as_bytes())
+            .map_err(|err| with_path(err, &temp_path))?;
+        step(CommitStep::WriteMarker, &temp_path);
+
+        file.sync_all().map_err(|err| with_path(err, &temp_path))?;
+        drop(file);
+        step(CommitStep::SyncMarker, &temp_path);
+
+        fs::rename(&temp_path, &marker_path).map_err(|err| with_path(err, &marker_path))?;
+        step(CommitStep::PublishMarker, &marker_path);
+
+        utils::sync_dir(dir).map_err(|err| with_path(err, dir))?;
+        step(CommitStep::SyncDir, dir);
+
+        Ok(marker_path)
+    }
+}
+
+/// The directory containing the path.
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    }
+}
+
+/// The hidden temporary file the marker is written to before it is published.
+fn temp_marker_path(marker_path: &Path) -> PathBuf {
+    let mut file_name = OsString::from(".");
+    file_name.push(marker_path.file_name().unwrap_or_default());
+    file_name.push(".tmp");
+    marker_path.with_file_name(file_name)
+}
+
+impl<T> fmt::Debug for CommittedWriter<T>
+where
+    T: Record,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CommittedWriter")
+            .field("path", &self.path)
+            .field("marker_path", &self.marker_path)
+            .field("marker", &self.marker)
+            .field("num_records", &self.num_records)
+            .finish()
+    }
+}
@@ -0,0 +1,380 @@
+use super::{FlushCoordinator, FlushPolicy, FlushStats, RecordWriter};
+use crate::{
+    error::{Error, Result},
+    record::Record,
+};
+use std::{io::Write, sync::Arc, time::Instant};
+
+/// The outcome of an underlying writer of a composite writer.
+#[derive(Debug, Clone)]
+pub enum ShardOutcome {
+    /// The writer is flushed successfully.
+    Finished {
+        /// The number of records written.
+        num_records: u64,
+    },
+    /// The writer failed. Records sent to it may be lost.
+    Failed {
+        /// The number of records accepted before the failure.
+        num_records: u64,
+        error: Arc<Error>,
+    },
+}
+
+impl ShardOutcome {
+    /// Returns true if the writer is finished successfully.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Finished { .. })
+    }
+
+    /// The number of records accepted by the writer.
+    pub fn num_records(&self) -> u64 {
+        match *self {
+            Self::Finished { num_records } => num_records,
+            Self::Failed { num_records, .. } => num_records,
+        }
+    }
+}
+
+/// The report returned by finishing a composite writer.
+#[derive(Debug, Clone)]
+#[must_use = "the report may contain failed writers"]
+pub struct FinishReport {
+    /// The outcomes indexed by the underlying writer.
+    pub outcomes: Vec<ShardOutcome>,
+}
+
+impl FinishReport {
+    /// Returns true if all underlying writers are finished successfully.
+    pub fn is_success(&self) -> bool {
+        self.outcomes.iter().all(ShardOutcome::is_finished)
+    }
+
+    /// Get the numbers of records per writer, or the first error if any writer failed.
+    pub fn into_result(self) -> Result<Vec<u64>> {
+        self.outcomes
+            .into_iter()
+            .map(|outcome| match outcome {
+                ShardOutcome::Finished { num_records } => Ok(num_records),
+                ShardOutcome::Failed { error, .. } => {
+                    Err(Error::WriterPoisoned { original: error })
+                }
+            })
+            .collect()
+    }
+}
+
+/// An underlying writer of a composite writer, either healthy or failed.
+#[derive(Debug)]
+pub(crate) struct Shard<W> {
+    pub(crate) writer: std::result::Result<W, Arc<Error>>,
+    pub(crate) num_records: u64,
+    /// The number of records acknowledged by a successful flush.
+    pub(crate) num_flushed: u64,
+    pub(crate) last_flush: Instant,
+}
+
+impl<W> Shard<W> {
+    pub(crate) fn new(writer: W) -> Self {
+        Self {
+            writer: Ok(writer),
+            num_records: 0,
+            num_flushed: 0,
+            last_flush: Instant::now(),
+        }
+    }
+
+    /// Acknowledge the records sent so far as flushed.
+    pub(crate) fn flushed(&mut self) {
+        self.num_flushed = self.num_records;
+        self.last_flush = Instant::now();
+    }
+}
+
+/// The failure state shared by composite writers.
+///
+/// The first error of any underlying writer poisons the composite writer.
+#[derive(Debug, Default)]
+pub(crate) struct Poison {
+    original: Option<Arc<Error>>,
+}
+
+impl Poison {
+    /// Fail if the composite writer is poisoned.
+    pub(crate) fn check(&self) -> Result<()> {
+        match &self.original {
+            Some(original) => Err(Error::WriterPoisoned {
+                original: original.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Run an operation on a healthy shard, marking the shard failed and poisoning the
+    /// composite writer on error.
+    pub(crate) fn run<W, F>(&mut self, shard: &mut Shard<W>, op: F) -> Result<()>
+    where
+        F: FnOnce(&mut W) -> Result<()>,
+    {
+        self.check()?;
+        let writer = match &mut shard.writer {
+            Ok(writer) => writer,
+            Err(error) => {
+                return Err(Error::WriterPoisoned {
+                    original: error.clone(),
+                })
+            }
+        };
+
+        match op(writer) {
+            Ok(()) => Ok(()),
+            Err(error) => Err(self.fail(shard, error)),
+        }
+    }
+
+    /// Mark the shard failed by the error, poisoning the composite writer if it is not
+    /// yet.
+    pub(crate) fn fail<W>(&mut self, shard: &mut Shard<W>, error: Error) -> Error {
+        let error = Arc::new(error);
+        shard.writer = Err(error.clone());
+        self.original.get_or_insert_with(|| error.clone());
+        Error::WriterPoisoned { original: error }
+    }
+}
+
+/// Flush the healthy shards at the ascending indexes in batches of at most
+/// `max_concurrent` threads, returning the first error after flushing all of them.
+pub(crate) fn sweep_shards<T, W>(
+    shards: &mut [Shard<RecordWriter<T, W>>],
+    indexes: &[usize],
+    max_concurrent: usize,
+    poison: &mut Poison,
+) -> Result<()>
+where
+    T: Record + Send,
+    W: Write + Send,
+{
+    let mut selected: Vec<_> = shards
+        .iter_mut()
+        .enumerate()
+        .filter(|(index, shard)| indexes.binary_search(index).is_ok() && shard.writer.is_ok())
+        .map(|(_, shard)| shard)
+        .collect();
+
+    let mut first_error = None;
+    for batch in selected.chunks_mut(max_concurrent) {
+        let results: Vec<Result<()>> = std::thread::scope(|scope| {
+            let workers: Vec<_> = batch
+                .iter_mut()
+                .map(|shard| {
+                    let writer = shard.writer.as_mut().unwrap();
+                    scope.spawn(move || writer.flush())
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("the flush thread panicked"))
+                .collect()
+        });
+        for (shard, result) in batch.iter_mut().zip(results) {
+            match result {
+                Ok(()) => shard.flushed(),
+                Err(error) => {
+                    let error = poison.fail(shard, error);
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+    }
+    first_error.map_or(Ok(()), Err)
+}
+
+/// Finish shards by flushing the healthy writers.
+pub(crate) fn finish_shards<W, F>(shards: &mut [Shard<W>], mut flush: F) -> FinishReport
+where
+    F: FnMut(&mut W) -> Result<()>,
+{
+    let outcomes = shards
+        .iter_mut()
+        .map(|shard| {
+            let num_records = shard.num_records;
+            let result = match &mut shard.writer {
+                Ok(writer) => flush(writer).map_err(Arc::new),
+                Err(error) => Err(error.clone()),
+            };
+            match result {
+                Ok(()) => ShardOutcome::Finished { num_records },
+                Err(error) => {
+                    shard.writer = Err(error.clone());
+                    ShardOutcome::Failed { num_records, error }
+                }
+            }
+        })
+        .collect();
+    FinishReport { outcomes }
+}
+
+/// The writer routing records to one of several [RecordWriter]s.
+///
+/// The route function maps each record to the index of an underlying writer.
+/// See the [module documentation](crate::record_writer#failure-semantics-of-composite-writers)
+/// for the behavior on errors.
+pub struct RoutingWriter<T, W>
+where
+    T: Record,
+{
+    shards: Vec<Shard<RecordWriter<T, W>>>,
+    route: Box<dyn FnMut(&T) -> usize + Send>,
+    poison: Poison,
+    flush_policy: FlushPolicy,
+    coordinator: Option<FlushCoordinator>,
+}
+
+impl<T, W> RoutingWriter<T, W>
+where
+    T: Record + Send,
+    W: Write + Send,
+{
+    /// Build from underlying writers and a route function.
+    pub fn new<F>(writers: Vec<RecordWriter<T, W>>, route: F) -> Result<Self>
+    where
+        F: 'static + FnMut(&T) -> usize + Send,
+    {
+        crate::error::ensure_argument!(!writers.is_empty(), "at least one writer is required");
+        Ok(Self {
+            shards: writers.into_iter().map(Shard::new).collect(),
+            route: Box::new(route),
+            poison: Poison::default(),
+            flush_policy: FlushPolicy::default(),
+            coordinator: None,
+        })
+    }
+
+    /// Flush each underlying writer by the policy on its own unflushed records.
+    ///
+    /// The policy is ignored while a [coordinator](RoutingWriter::with_flush_coordinator)
+    /// is attached.
+    pub fn with_flush_policy(mut self, policy: FlushPolicy) -> Self {
+        self.flush_policy = policy;
+        self
+    }
+
+    /// Coalesce the flushes of the underlying writers into the sweeps of the coordinator.
+    pub fn with_flush_coordinator(mut self, coordinator: FlushCoordinator) -> Self {
+        self.coordinator = Some(coordinator);
+        self
+    }
+
+    /// The counts of flushes of the attached coordinator.
+    pub fn flush_stats(&self) -> Option<FlushStats> {
+        self.coordinator.as_ref().map(FlushCoordinator::stats)
+    }
+
+    /// The number of records per writer acknowledged by a successful flush.
+    ///
+    /// A record is acknowledged only after the flush covering it returns, so the
+    /// acknowledged records have reached the destinations of the writers.
+    pub fn flushed_records(&self) -> Vec<u64> {
+        self.shards.iter().map(|shard| shard.num_flushed).collect()
+    }
+
+    /// The number of underlying writers.
+    pub fn num_writers(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Returns true if an underlying writer has failed.
+    pub fn is_poisoned(&self) -> bool {
+        self.poison.check().is_err()
+    }
+
+    /// Write a record to the routed writer.
+    ///
+    /// It fails with [Error::WriterPoisoned] once any underlying writer has failed.
+    pub fn send(&mut self, record: T) -> Result<()> {
+        self.poison.check()?;
+        let num_shards = self.shards.len();
+        let index = (self.route)(&record);
+        crate::error::ensure_argument!(
+            index < num_shards,
+            "the route index {} is out of range of {} writers",
+            index,
+            num_shards
+        );
+
+        let shard = &mut self.shards[index];
+        let mut num_bytes = 0;
+        self.poison.run(shard, |writer| {
+            let before = writer.unflushed_bytes();
+            writer.send(record)?;
+            num_bytes = writer.unflushed_bytes() - before;
+            Ok(())
+        })?;
+        shard.num_records += 1;
+
+        match &mut self.coordinator {
+            Some(coordinator) => {
+                coordinator.mark_dirty(index, num_bytes);
+                if coordinator.is_due() {
+                    self.sweep()?;
+                }
+            }
+            None => {
+                let due = shard.writer.as_ref().is_ok_and(|writer| {
+                    self.flush_policy
+                        .is_due(writer.unflushed_bytes(), shard.last_flush)
+                });
+                if due {
+                    self.poison.run(shard, |writer| writer.flush())?;
+                    shard.flushed();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Flush all underlying writers, the same as [flush_all](RoutingWriter::flush_all).
+    pub fn flush(&mut self) -> Result<()> {
+        self.flush_all()
+    }
+
+    /// Flush all underlying writers, such as for a checkpoint.
+    ///
+    /// With a coordinator, the writers with unflushed records are flushed in a sweep.
+    pub fn flush_all(&mut self) -> Result<()> {
+        self.poison.check()?;
+        if self.coordinator.is_some() {
+            return self.sweep();
+        }
+        for shard in &mut self.shards {
+            self.poison.run(shard, |writer| writer.flush())?;
+            shard.flushed();
+        }
+        Ok(())
+    }
+
+    /// Flush the healthy writers and report the outcome per writer.
+    ///
+    /// With a coordinator, it waits for a final sweep of the writers with unflushed
+    /// records.
+    pub fn finish(mut self) -> FinishReport {
+        if self.coordinator.is_some() {
+            // failures are reported in the outcomes
+            let _ = self.sweep();
+            return finish_shards(&mut self.shards, |_| Ok(()));
+        }
+        finish_shards(&mut self.shards, |writer| writer.flush())
+    }
+
+    /// Unwraps the underlying writers, where failed writers are replaced by their errors.
+    pub fn into_parts(self) -> Vec<std::result::Result<RecordWriter<T, W>, Arc<Error>>> {
+        self.shards.into_iter().map(|shard| shard.writer).collect()
+    }
+
+    fn sweep(&mut self) -> Result<()> {
+        let coordinator = self.coordinator.as_mut().unwrap();
+        let max_concurrent = coordinator.max_concurrent_flushes();
+        let indexes = coordinator.start_sweep();
+        sweep_shards(&mut self.shards, &indexes, max_concurrent, &mut self.poison)
+    }
+}
@@ -0,0 +1,176 @@
+use super::RecordWriterConfig;
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    io,
+    sync::Arc,
+};
+
+/// The records of a writer dropped without being flushed.
+///
+/// See [on_unflushed_drop](RecordWriterConfig::on_unflushed_drop).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UnflushedDrop {
+    /// The type name of the writer.
+    pub writer: &'static str,
+    /// The number of records sent since the last flush.
+    pub num_records: u64,
+    /// The size of the record frames sent since the last flush in bytes.
+    pub num_bytes: u64,
+    /// The error of the flush on drop, for writers flushing on drop.
+    pub flush_error: Option<String>,
+}
+
+impl fmt::Display for UnflushedDrop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} dropped with {} unflushed records ({} bytes)",
+            self.writer, self.num_records, self.num_bytes
+        )?;
+        match &self.flush_error {
+            Some(error) => write!(f, ", and the flush on drop failed: {}", error),
+            None => write!(f, ", which are lost; call flush() before dropping the writer or abandon() to discard them"),
+        }
+    }
+}
+
+/// A shared handler of [UnflushedDrop] reports.
+///
+/// Two handlers are equal if they point to the same function.
+#[derive(Clone)]
+pub struct UnflushedDropHandler(Arc<dyn Fn(&UnflushedDrop) + Send + Sync>);
+
+impl UnflushedDropHandler {
+    /// Create from a function.
+    pub fn new<F>(handler: F) -> Self
+    where
+        F: 'static + Fn(&UnflushedDrop) + Send + Sync,
+    {
+        Self(Arc::new(handler))
+    }
+}
+
+impl fmt::Debug for UnflushedDropHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("UnflushedDropHandler").finish()
+    }
+}
+
+impl PartialEq for UnflushedDropHandler {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for UnflushedDropHandler {}
+
+impl Hash for UnflushedDropHandler {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (Arc::as_ptr(&self.0) as *const () as usize).hash(state);
+    }
+}
+
+/// The records sent to a writer since the last flush.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub(crate) struct Unflushed {
+    num_records: u64,
+    num_bytes: u64,
+    handler: Option<UnflushedDropHandler>,
+    panic: bool,
+}
+
+impl Unflushed {
+    pub(crate) fn new(config: &RecordWriterConfig) -> Self {
+        Self {
+            num_records: 0,
+            num_bytes: 0,
+            handler: config.on_unflushed_drop.clone(),
+            panic: config.panic_on_unflushed_drop,
+        }
+    }
+
+    /// Count a record of the payload length.
+    pub(crate) fn add(&mut self, len: usize) {
+        self.num_records += 1;
+        self.num_bytes += len as u64 + 16;
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.num_records = 0;
+        self.num_bytes = 0;
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.num_records == 0
+    }
+
+    /// The framed bytes of the records.
+    pub(crate) fn num_bytes(&self) -> u64 {
+        self.num_bytes
+    }
+
+    /// Report the unflushed records of the dropped writer.
+    pub(crate) fn report(&self, writer: &'static str, flush_error: Option<&io::Error>) {
+        let report = UnflushedDrop {
+            writer,
+            num_records: self.num_records,
+            num_bytes: self.num_bytes,
+            flush_error: flush_error.map(|error| error.to_string()),
+        };
+        match &self.handler {
+            Some(handler) => (handler.0)(&report),
+            None => eprintln!("tfrecord: {}", report),
+        }
+        // a panic while unwinding aborts the process
+        if self.panic && cfg!(debug_assertions) && !std::thread::panicking() {
+            panic!("{}", report);
+        }
+    }
+}
+
+/// The flush of a writer type, callable in [Drop] impls without a [Write](io::Write)
+/// bound.
+///
+/// All values for a writer type are [Write::flush](io::Write::flush), so they
+/// compare equal.
+pub(crate) struct FlushFn<W>(fn(&mut W) -> io::Result<()>);
+
+impl<W> FlushFn<W>
+where
+    W: io::Write,
+{
+    pub(crate) fn new() -> Self {
+        Self(W::flush)
+    }
+}
+
+impl<W> FlushFn<W> {
+    pub(crate) fn call(&self, writer: &mut W) -> io::Result<()> {
+        (self.0)(writer)
+    }
+}
+
+impl<W> Clone for FlushFn<W> {
+    fn clone(&self) -> Self {
+        Self(self.0)
+    }
+}
+
+impl<W> fmt::Debug for FlushFn<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FlushFn").finish()
+    }
+}
+
+impl<W> PartialEq for FlushFn<W> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl<W> Eq for FlushFn<W> {}
+
+impl<W> Hash for FlushFn<W> {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
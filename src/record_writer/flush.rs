@@ -0,0 +1,135 @@
+use crate::error::{ensure_argument, Result};
+use std::{
+    collections::BTreeSet,
+    time::{Duration, Instant},
+};
+
+/// The condition to flush unflushed records, met if any of the set bounds is reached.
+///
+/// The default policy never flushes on its own.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct FlushPolicy {
+    /// Flush once the framed bytes of the unflushed records reach the number.
+    pub max_unflushed_bytes: Option<u64>,
+    /// Flush once the time since the last flush reaches the duration.
+    pub max_interval: Option<Duration>,
+}
+
+impl FlushPolicy {
+    /// Returns true if records are unflushed and a bound is reached.
+    pub(crate) fn is_due(&self, unflushed_bytes: u64, last_flush: Instant) -> bool {
+        unflushed_bytes > 0
+            && (self
+                .max_unflushed_bytes
+                .is_some_and(|max| unflushed_bytes >= max)
+                || self
+                    .max_interval
+                    .is_some_and(|max| last_flush.elapsed() >= max))
+    }
+}
+
+/// Configuration for [FlushCoordinator].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FlushCoordinatorConfig {
+    /// The condition to sweep, on the bytes unflushed across all writers and the time
+    /// since the last sweep.
+    pub policy: FlushPolicy,
+    /// The maximum number of writers flushed at the same time in a sweep.
+    pub max_concurrent_flushes: usize,
+}
+
+impl Default for FlushCoordinatorConfig {
+    fn default() -> Self {
+        Self {
+            policy: FlushPolicy::default(),
+            max_concurrent_flushes: 8,
+        }
+    }
+}
+
+/// The counts of flushes by a [FlushCoordinator].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FlushStats {
+    /// The number of sweeps, including explicit ones.
+    pub num_sweeps: u64,
+    /// The number of flushes of underlying writers.
+    pub num_flushes: u64,
+}
+
+/// The coordinator coalescing the flushes of the writers of a composite writer.
+///
+/// Without a coordinator, each writer of a composite writer flushes by its own
+/// [FlushPolicy], so many writers receiving records at a steady rate flush often with
+/// few bytes each. A coordinator instead tracks the writers with unflushed records and
+/// sweeps them together once its policy, applied to the whole composite writer, is met.
+/// A sweep flushes the writers in batches of at most
+/// [max_concurrent_flushes](FlushCoordinatorConfig::max_concurrent_flushes) threads.
+///
+/// The policy is checked when records are sent, so an idle composite writer does not
+/// sweep until it is flushed or finished.
+#[derive(Debug)]
+pub struct FlushCoordinator {
+    policy: FlushPolicy,
+    max_concurrent_flushes: usize,
+    /// The indexes of the writers with unflushed records.
+    dirty: BTreeSet<usize>,
+    unflushed_bytes: u64,
+    last_sweep: Instant,
+    stats: FlushStats,
+}
+
+impl FlushCoordinator {
+    /// Build a coordinator.
+    pub fn new(config: FlushCoordinatorConfig) -> Result<Self> {
+        let FlushCoordinatorConfig {
+            policy,
+            max_concurrent_flushes,
+        } = config;
+        ensure_argument!(
+            max_concurrent_flushes > 0,
+            "max_concurrent_flushes must be positive"
+        );
+        Ok(Self {
+            policy,
+            max_concurrent_flushes,
+            dirty: BTreeSet::new(),
+            unflushed_bytes: 0,
+            last_sweep: Instant::now(),
+            stats: FlushStats::default(),
+        })
+    }
+
+    /// The counts of flushes so far.
+    pub fn stats(&self) -> FlushStats {
+        self.stats
+    }
+
+    /// The number of writers with unflushed records.
+    pub fn num_dirty(&self) -> usize {
+        self.dirty.len()
+    }
+
+    pub(crate) fn max_concurrent_flushes(&self) -> usize {
+        self.max_concurrent_flushes
+    }
+
+    /// Register records sent to the writer.
+    pub(crate) fn mark_dirty(&mut self, index: usize, num_bytes: u64) {
+        self.dirty.insert(index);
+        self.unflushed_bytes += num_bytes;
+    }
+
+    pub(crate) fn is_due(&self) -> bool {
+        self.policy.is_due(self.unflushed_bytes, self.last_sweep)
+    }
+
+    /// Start a sweep, returning the writers to flush in ascending order.
+    pub(crate) fn start_sweep(&mut self) -> Vec<usize> {
+        self.unflushed_bytes = 0;
+        self.last_sweep = Instant::now();
+        self.stats.num_sweeps += 1;
+        let dirty: Vec<_> = std::mem::take(&mut self.dirty).into_iter().collect();
+        self.stats.num_flushes += dirty.len() as u64;
+        dirty
+    }
+}
@@ -0,0 +1,435 @@
+use crate::{
+    cancel::Progress,
+    error::{ensure_argument, Error, Result},
+    record::Record,
+};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt,
+    fs::File,
+    future::Future,
+    io::Write,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    task::{Context, Poll, Waker},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Configuration for [JournalWriter].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct JournalConfig {
+    /// The time a record waits for others to share its commit. It bounds the latency
+    /// added by batching.
+    pub commit_window: Duration,
+    /// The number of pending records committing a batch before the window elapses.
+    pub max_batch_records: usize,
+    /// If set, records are serialized by [to_bytes_canonical](crate::record::Record::to_bytes_canonical).
+    pub canonical_encoding: bool,
+}
+
+impl Default for JournalConfig {
+    fn default() -> Self {
+        Self {
+            commit_window: Duration::from_millis(5),
+            max_batch_records: 256,
+            canonical_encoding: false,
+        }
+    }
+}
+
+/// The counts of commits by a [JournalWriter].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct JournalStats {
+    /// The number of write and sync pairs.
+    pub num_commits: u64,
+    /// The number of durable records.
+    pub num_records: u64,
+    /// The number of records of the largest commit.
+    pub max_batch_records: u64,
+}
+
+/// A writer making each record durable with a low latency by group commit.
+///
+/// Records are sent from any number of threads through a shared reference. A committer
+/// thread writes the pending records at once and syncs the file, which amortizes the
+/// cost of the sync over the records arriving within the
+/// [commit window](JournalConfig::commit_window) of the first pending one, or up to
+/// [max_batch_records](JournalConfig::max_batch_records) records.
+///
+/// [send_durable](JournalWriter::send_durable) returns after the record is synced.
+/// [submit](JournalWriter::submit) returns a [DurableTicket] instead, which is waited on
+/// or awaited as a future. Once a record is durable, so are the records submitted before
+/// it, and the file holds them in the order of their sequence numbers.
+///
+/// If a write or sync fails, the writer is poisoned. Records not yet durable and every
+/// later submission fail with [Error::WriterPoisoned] preserving the original error.
+///
+/// ```rust
+/// # fn main() -> tfrecord::Result<()> {
+/// use tfrecord::{samples, Example, ExampleIter, JournalWriter};
+///
+/// let dataset = samples::tiny_dataset(0, 0)?;
+/// let path = dataset.dir().join("journal.tfrecord");
+///
+/// let writer = JournalWriter::<Example>::create(&path, Default::default())?;
+/// let seq = writer.send_durable(samples::example(0))?;
+/// assert_eq!(seq, 0);
+///
+/// // the record is in the file before the writer is closed
+/// assert_eq!(ExampleIter::open(&path, Default::default())?.count(), 1);
+/// writer.close()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct JournalWriter<T>
+where
+    T: Record,
+{
+    canonical_encoding: bool,
+    shared: Arc<Shared>,
+    committer: Option<JoinHandle<()>>,
+    _phantom: PhantomData<fn(T)>,
+}
+
+impl<T> JournalWriter<T>
+where
+    T: Record,
+{
+    /// Build a writer writing to a new file.
+    pub fn create<P>(path: P, config: JournalConfig) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file =
+            File::create(path).map_err(|err| Error::from_io_with_context(err, path, None))?;
+        Self::build(file, Some(path.to_owned()), config)
+    }
+
+    /// Build a writer appending to an open file.
+    pub fn from_file(file: File, config: JournalConfig) -> Result<Self> {
+        Self::build(file, None, config)
+    }
+
+    fn build(file: File, path: Option<PathBuf>, config: JournalConfig) -> Result<Self> {
+        let JournalConfig {
+            commit_window,
+            max_batch_records,
+            canonical_encoding,
+        } = config;
+        ensure_argument!(max_batch_records > 0, "max_batch_records must be positive");
+
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State::default()),
+            pending_changed: Condvar::new(),
+            durable_changed: Condvar::new(),
+        });
+        let committer = {
+            let shared = shared.clone();
+            thread::Builder::new()
+                .name("tfrecord-journal".into())
+                .spawn(move || shared.run_committer(file, path, commit_window, max_batch_records))?
+        };
+
+        Ok(Self {
+            canonical_encoding,
+            shared,
+            committer: Some(committer),
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Write a record and return its sequence number after it is durable.
+    pub fn send_durable(&self, record: T) -> Result<u64> {
+        self.submit(record)?.wait()
+    }
+
+    /// Submit a record for the next commit, returning the ticket of its durability.
+    pub fn submit(&self, record: T) -> Result<DurableTicket> {
+        let bytes = if self.canonical_encoding {
+            T::to_bytes_canonical(record)?
+        } else {
+            T::to_bytes(record)?
+        };
+
+        let mut state = self.shared.lock();
+        if let Some(original) = &state.failure {
+            return Err(Error::WriterPoisoned {
+                original: original.clone(),
+            });
+        }
+        crate::io::sync::try_write_record_slice(&mut state.pending, &bytes)?;
+        if state.num_pending == 0 {
+            state.first_pending = Some(Instant::now());
+        }
+        state.num_pending += 1;
+        let seq = state.num_submitted;
+        state.num_submitted += 1;
+        drop(state);
+        self.shared.pending_changed.notify_all();
+
+        Ok(DurableTicket {
+            seq,
+            shared: self.shared.clone(),
+        })
+    }
+
+    /// The number of durable records.
+    pub fn num_durable(&self) -> u64 {
+        self.shared.lock().num_durable
+    }
+
+    /// The counts of commits so far.
+    pub fn stats(&self) -> JournalStats {
+        self.shared.lock().stats
+    }
+
+    /// Commit the pending records and stop the committer, returning the number of
+    /// durable records.
+    pub fn close(mut self) -> Result<u64> {
+        self.stop(true);
+        let state = self.shared.lock();
+        match &state.failure {
+            Some(original) => Err(Error::WriterPoisoned {
+                original: original.clone(),
+            }),
+            None => Ok(state.num_durable),
+        }
+    }
+
+    /// Stop the committer without committing the pending records, as if the process
+    /// exited. Records not yet durable fail with [Error::Cancelled] wrapped in
+    /// [Error::WriterPoisoned].
+    pub fn abandon(mut self) {
+        self.stop(false);
+    }
+
+    fn stop(&mut self, commit_pending: bool) {
+        let Some(committer) = self.committer.take() else {
+            return;
+        };
+        {
+            let mut state = self.shared.lock();
+            state.closing = true;
+            if !commit_pending {
+                state.pending.clear();
+                state.num_pending = 0;
+                let progress = Progress::new(0, state.num_durable);
+                state
+                    .failure
+                    .get_or_insert_with(|| Arc::new(Error::Cancelled { progress }));
+            }
+        }
+        self.shared.pending_changed.notify_all();
+        let _ = committer.join();
+        self.shared.notify_durable();
+    }
+}
+
+impl<T> Drop for JournalWriter<T>
+where
+    T: Record,
+{
+    fn drop(&mut self) {
+        self.stop(true);
+    }
+}
+
+impl<T> fmt::Debug for JournalWriter<T>
+where
+    T: Record,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JournalWriter")
+            .field("canonical_encoding", &self.canonical_encoding)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+/// The durability of a record submitted to a [JournalWriter].
+///
+/// It resolves to the sequence number of the record once the record is durable, either
+/// by [wait](DurableTicket::wait) or as a future.
+#[derive(Debug)]
+pub struct DurableTicket {
+    seq: u64,
+    shared: Arc<Shared>,
+}
+
+impl DurableTicket {
+    /// The sequence number of the record, its position in the file.
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// Returns true if the record is durable.
+    pub fn is_durable(&self) -> bool {
+        self.shared.lock().num_durable > self.seq
+    }
+
+    /// Block until the record is durable.
+    pub fn wait(self) -> Result<u64> {
+        let mut state = self.shared.lock();
+        loop {
+            if let Some(result) = state.outcome(self.seq) {
+                return result;
+            }
+            state = self
+                .shared
+                .durable_changed
+                .wait(state)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+    }
+}
+
+impl Future for DurableTicket {
+    type Output = Result<u64>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.shared.lock();
+        match state.outcome(self.seq) {
+            Some(result) => Poll::Ready(result),
+            None => {
+                // keep one waker per ticket, so that repeated polls do not pile up wakers
+                match state.wakers.entry(self.seq) {
+                    Entry::Occupied(mut entry) => {
+                        if !entry.get().will_wake(cx.waker()) {
+                            entry.insert(cx.waker().clone());
+                        }
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(cx.waker().clone());
+                    }
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    /// Notified when records are submitted or the writer is closing.
+    pending_changed: Condvar,
+    /// Notified after each commit.
+    durable_changed: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// The framed bytes of the pending records.
+    pending: Vec<u8>,
+    num_pending: u64,
+    /// The submission time of the first pending record.
+    first_pending: Option<Instant>,
+    num_submitted: u64,
+    num_durable: u64,
+    failure: Option<Arc<Error>>,
+    closing: bool,
+    /// The wakers of pending tickets by sequence number.
+    wakers: HashMap<u64, Waker>,
+    stats: JournalStats,
+}
+
+impl State {
+    /// The outcome of the record, or `None` if it is still pending.
+    fn outcome(&self, seq: u64) -> Option<Result<u64>> {
+        if self.num_durable > seq {
+            Some(Ok(seq))
+        } else {
+            self.failure.as_ref().map(|original| {
+                Err(Error::WriterPoisoned {
+                    original: original.clone(),
+                })
+            })
+        }
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn notify_durable(&self) {
+        let wakers = std::mem::take(&mut self.lock().wakers);
+        self.durable_changed.notify_all();
+        wakers.into_values().for_each(Waker::wake);
+    }
+
+    fn run_committer(
+        &self,
+        mut file: File,
+        path: Option<PathBuf>,
+        commit_window: Duration,
+        max_batch_records: usize,
+    ) {
+        let mut batch = vec![];
+        loop {
+            let num_records = {
+                let mut state = self.lock();
+                // wait for the batch to fill, the window to elapse or the writer to close
+                loop {
+                    if state.failure.is_some() || (state.closing && state.num_pending == 0) {
+                        return;
+                    }
+                    if let Some(first_pending) = state.first_pending {
+                        let deadline = first_pending + commit_window;
+                        let now = Instant::now();
+                        if state.closing
+                            || state.num_pending >= max_batch_records as u64
+                            || now >= deadline
+                        {
+                            break;
+                        }
+                        state = self
+                            .pending_changed
+                            .wait_timeout(state, deadline - now)
+                            .unwrap_or_else(|poisoned| poisoned.into_inner())
+                            .0;
+                    } else {
+                        state = self
+                            .pending_changed
+                            .wait(state)
+                            .unwrap_or_else(|poisoned| poisoned.into_inner());
+                    }
+                }
+                std::mem::swap(&mut batch, &mut state.pending);
+                state.first_pending = None;
+                std::mem::take(&mut state.num_pending)
+            };
+
+            let result = file.write_all(&batch).and_then(|()| file.sync_data());
+            batch.clear();
+
+            {
+                let mut state = self.lock();
+                match result {
+                    Ok(()) => {
+                        state.num_durable += num_records;
+                        state.stats.num_commits += 1;
+                        state.stats.num_records += num_records;
+                        state.stats.max_batch_records =
+                            state.stats.max_batch_records.max(num_records);
+                    }
+                    Err(err) => {
+                        let err = match &path {
+                            Some(path) => Error::from_io_with_context(err, path, None),
+                            None => err.into(),
+                        };
+                        state.failure = Some(Arc::new(err));
+                    }
+                }
+            }
+            self.notify_durable();
+        }
+    }
+}
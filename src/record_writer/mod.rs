@@ -16,11 +16,139 @@
 //! | [BytesAsyncWriter](async::BytesAsyncWriter)           | [Vec<u8>](Vec)                  |
 //! | [ExampleAsyncWriter](async::ExampleAsyncWriter)       | [Example](crate::Example)       |
 //! | [RecordAsyncWriter](async::RecordAsyncWriter)         | Type that implements [Record](crate::record::Record) |
+//!
+//! The writers [append](RecordWriter::append) to existing files, truncating a partially
+//! written record at the end.
+//!
+//! The [CommittedWriter] writes a shard to a file and publishes it with a completion
+//! marker after the shard is durable.
+//!
+//! The [ShardedWriter] rotates records over shard files named `<prefix>-%05d-of-%05d`.
+//!
+//! The [JournalWriter] makes each record durable with a low latency, coalescing the
+//! records of concurrent senders into group commits.
+//!
+//! The [ValidatingWriter] rejects examples not conforming to a
+//! [schema](crate::schema).
+//!
+//! The [RecordSinkWriter](sink::RecordSinkWriter) writes records in parts to an
+//! [AsyncRecordSink](sink::AsyncRecordSink), such as a multipart upload to object storage.
+//!
+//! # Failure semantics of composite writers
+//!
+//! Composite writers, such as [RoutingWriter] and [ShardedWriter], own several underlying writers and
+//! share the same failure semantics.
+//!
+//! - After any underlying writer errors, the composite writer is poisoned. The failing
+//!   call and every subsequent `send` return [Error::WriterPoisoned](crate::Error::WriterPoisoned)
+//!   preserving the original error. Records are never silently accepted by a failed writer.
+//! - `finish()` flushes the healthy writers and returns a [FinishReport] with a
+//!   [ShardOutcome] per writer.
+//! - `into_parts()` returns the underlying writers, so that healthy ones can be salvaged.
+//!
+//! The underlying writers flush by a per-writer [FlushPolicy], or, with a
+//! [FlushCoordinator] attached, in periodic sweeps coalescing the flushes of all
+//! writers.
 
 #[cfg(feature = "async")]
 mod r#async;
 #[cfg(feature = "async")]
 pub use r#async::*;
 
+#[cfg(feature = "async")]
+mod sink;
+#[cfg(feature = "async")]
+pub use sink::*;
+
+mod append;
+pub(crate) use append::open_for_append;
+pub use append::AppendReport;
+
+mod committed;
+pub use committed::*;
+
+mod composite;
+pub use composite::*;
+
+mod flush;
+pub use flush::*;
+
+mod journal;
+pub use journal::*;
+
+mod drop_check;
+pub(crate) use drop_check::{FlushFn, Unflushed};
+pub use drop_check::{UnflushedDrop, UnflushedDropHandler};
+
+mod sharded;
+pub use sharded::*;
+
 mod sync;
 pub use sync::*;
+
+mod validating;
+pub use validating::*;
+
+use std::collections::BTreeSet;
+
+/// Configuration for record writer.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RecordWriterConfig {
+    /// If set, records are serialized by [to_bytes_canonical](crate::record::Record::to_bytes_canonical),
+    /// so that the output bytes do not depend on map iteration order.
+    pub canonical_encoding: bool,
+    /// If set, the feature map entries of examples are sorted by key as with
+    /// [canonical_encoding](RecordWriterConfig::canonical_encoding), and files created by
+    /// path declare the sortedness in their [shard metadata](crate::metadata).
+    ///
+    /// Writers built from other writers do not write the metadata. Debug builds assert
+    /// the sortedness of every encoded record.
+    pub sorted_features: bool,
+    /// The handler of the reports of writers dropped with unflushed records. If unset,
+    /// reports are printed to stderr.
+    ///
+    /// Synchronous writers flush on drop and report only if the flush fails.
+    /// Asynchronous writers cannot flush on drop, so they report whenever records
+    /// were sent after the last flush. [abandon](RecordWriter::abandon) drops a writer
+    /// without reporting.
+    pub on_unflushed_drop: Option<UnflushedDropHandler>,
+    /// If set, dropping a writer with unflushed records panics after reporting in
+    /// debug builds, unless the thread is already panicking.
+    pub panic_on_unflushed_drop: bool,
+    /// If set, the features of examples under the keys are replaced by the compact form
+    /// of [Feature::from_sorted_indices](crate::Feature::from_sorted_indices) before they
+    /// are written. Records of types without features fail to be sent with
+    /// [Error::InvalidArgumentsError](crate::Error::InvalidArgumentsError).
+    pub encode_sorted_indices: Option<SortedIndicesEncoding>,
+    /// If set, files created by path are stamped with the
+    /// [ProducerInfo](crate::inspect::ProducerInfo) of the writer in their
+    /// [shard metadata](crate::metadata), recording the options above which affect the
+    /// file contents. It is enabled by default.
+    ///
+    /// Writers built from other writers do not write the metadata.
+    pub stamp_producer: bool,
+}
+
+impl Default for RecordWriterConfig {
+    fn default() -> Self {
+        Self {
+            canonical_encoding: false,
+            sorted_features: false,
+            on_unflushed_drop: None,
+            panic_on_unflushed_drop: false,
+            encode_sorted_indices: None,
+            stamp_producer: true,
+        }
+    }
+}
+
+/// The features encoded by a writer with
+/// [encode_sorted_indices](RecordWriterConfig::encode_sorted_indices).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SortedIndicesEncoding {
+    /// The keys of the `Int64List` features to encode. Examples without some of the
+    /// keys are written as they are.
+    pub keys: BTreeSet<String>,
+    /// If set, unsorted indices are sorted instead of rejected.
+    pub sort: bool,
+}
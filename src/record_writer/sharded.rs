@@ -0,0 +1,330 @@
+use super::{
+    composite::{Poison, Shard},
+    RecordWriter, RecordWriterConfig,
+};
+use crate::{
+    compression::Compression,
+    error::{ensure_argument, Error, Result},
+    metadata,
+    record::Record,
+    shardspec,
+};
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::PathBuf,
+};
+
+/// The assignment of records to the shards of a [ShardedWriter].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ShardAssignment {
+    /// Fill each shard up to the caps before starting the next one.
+    #[default]
+    Sequential,
+    /// Send the records to the fixed number of shards in turn.
+    RoundRobin,
+}
+
+/// Configuration for [ShardedWriter].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ShardedWriterConfig {
+    /// The number of shards, or `None` if it is known after the last record.
+    ///
+    /// Shards of an unknown count are written to temporary names and renamed by
+    /// [finalize](ShardedWriter::finalize).
+    pub num_shards: Option<usize>,
+    /// The number of records closing a shard in sequential assignment.
+    pub max_records_per_shard: Option<u64>,
+    /// The framed bytes closing a shard in sequential assignment.
+    ///
+    /// A shard is closed once it holds at least the bytes, so it exceeds the cap by less
+    /// than the frame of its last record.
+    pub max_bytes_per_shard: Option<u64>,
+    /// The assignment of records to shards.
+    pub assignment: ShardAssignment,
+    /// The configuration of the underlying writers.
+    pub writer: RecordWriterConfig,
+}
+
+/// A shard written by a [ShardedWriter].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WrittenShard {
+    /// The final path of the shard.
+    pub path: PathBuf,
+    /// The number of records in the shard.
+    pub num_records: u64,
+    /// The framed bytes of the records in the shard.
+    pub num_bytes: u64,
+}
+
+/// A shard file and its writer, which is dropped once the shard is closed.
+struct ShardFile<T>
+where
+    T: Record,
+{
+    path: PathBuf,
+    num_bytes: u64,
+    shard: Shard<Option<RecordWriter<T, BufWriter<File>>>>,
+}
+
+/// The writer rotating records over the shard files `<prefix>-%05d-of-%05d`.
+///
+/// In [sequential](ShardAssignment::Sequential) assignment, a shard is closed once it
+/// reaches [max_records_per_shard](ShardedWriterConfig::max_records_per_shard) or
+/// [max_bytes_per_shard](ShardedWriterConfig::max_bytes_per_shard), and the records
+/// continue in the next shard. If the number of shards is fixed, exceeding it fails
+/// with [Error::LimitExceeded], and the unused shards are written empty. If it is not,
+/// the shards are written to `<prefix>-%05d.partial` and renamed once
+/// [finalize](ShardedWriter::finalize) knows the count.
+///
+/// In [round-robin](ShardAssignment::RoundRobin) assignment, the records are sent to
+/// the fixed number of shards in turn.
+///
+/// The writer is poisoned by the first error as described in the
+/// [module documentation](crate::record_writer#failure-semantics-of-composite-writers),
+/// and a poisoned writer fails to finalize. Dropping the writer without finalizing it
+/// leaves the temporary names in place.
+///
+/// ```rust
+/// # fn main() -> tfrecord::Result<()> {
+/// use tfrecord::{samples, Example, ShardedWriter, ShardedWriterConfig};
+///
+/// let dataset = samples::tiny_dataset(0, 0)?;
+/// let prefix = dataset.dir().join("train");
+/// let config = ShardedWriterConfig {
+///     max_records_per_shard: Some(4),
+///     ..Default::default()
+/// };
+///
+/// let mut writer = ShardedWriter::<Example>::create(prefix.to_str().unwrap(), config)?;
+/// for index in 0..10 {
+///     writer.send(samples::example(index))?;
+/// }
+/// let shards = writer.finalize()?;
+/// assert_eq!(shards.len(), 3);
+/// assert_eq!(shards[2].path, dataset.dir().join("train-00002-of-00003"));
+/// assert_eq!(shards[2].num_records, 2);
+/// # Ok(())
+/// # }
+/// ```
+pub struct ShardedWriter<T>
+where
+    T: Record,
+{
+    prefix: String,
+    config: ShardedWriterConfig,
+    shards: Vec<ShardFile<T>>,
+    next_shard: usize,
+    poison: Poison,
+}
+
+impl<T> ShardedWriter<T>
+where
+    T: Record,
+{
+    /// Build a writer of the shards named after the prefix, which may contain
+    /// directories.
+    ///
+    /// Sequential assignment of an unknown number of shards needs a cap, and round-robin
+    /// assignment needs the number of shards and takes no caps.
+    pub fn create(prefix: &str, config: ShardedWriterConfig) -> Result<Self> {
+        let ShardedWriterConfig {
+            num_shards,
+            max_records_per_shard,
+            max_bytes_per_shard,
+            assignment,
+            writer: _,
+        } = &config;
+        ensure_argument!(!prefix.is_empty(), "the shard prefix is empty");
+        ensure_argument!(num_shards != &Some(0), "the number of shards is zero");
+        ensure_argument!(
+            max_records_per_shard != &Some(0) && max_bytes_per_shard != &Some(0),
+            "the shard caps must be positive"
+        );
+        let has_cap = max_records_per_shard.is_some() || max_bytes_per_shard.is_some();
+        match assignment {
+            ShardAssignment::Sequential => ensure_argument!(
+                num_shards.is_some() || has_cap,
+                "sequential assignment of an unknown number of shards needs a cap"
+            ),
+            ShardAssignment::RoundRobin => ensure_argument!(
+                num_shards.is_some() && !has_cap,
+                "round-robin assignment needs the number of shards and takes no caps"
+            ),
+        }
+
+        let mut writer = Self {
+            prefix: prefix.to_string(),
+            config,
+            shards: vec![],
+            next_shard: 0,
+            poison: Poison::default(),
+        };
+        if writer.config.assignment == ShardAssignment::RoundRobin {
+            for _ in 0..writer.config.num_shards.unwrap() {
+                writer.open_shard()?;
+            }
+        }
+        Ok(writer)
+    }
+
+    /// Returns true if an underlying writer has failed.
+    pub fn is_poisoned(&self) -> bool {
+        self.poison.check().is_err()
+    }
+
+    /// The number of shards started so far.
+    pub fn num_shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Write a record to the current shard, starting the next shard as assigned.
+    ///
+    /// It fails with [Error::WriterPoisoned] once any underlying writer has failed.
+    pub fn send(&mut self, record: T) -> Result<()> {
+        self.poison.check()?;
+        let index = match self.config.assignment {
+            ShardAssignment::RoundRobin => {
+                let index = self.next_shard;
+                self.next_shard = (index + 1) % self.shards.len();
+                index
+            }
+            ShardAssignment::Sequential => {
+                if self.shards.last().is_none_or(|shard| self.is_full(shard)) {
+                    self.close_last()?;
+                    self.open_shard()?;
+                }
+                self.shards.len() - 1
+            }
+        };
+
+        let shard = &mut self.shards[index];
+        let mut num_bytes = 0;
+        self.poison.run(&mut shard.shard, |writer| {
+            let writer = writer.as_mut().unwrap();
+            let before = writer.unflushed_bytes();
+            writer.send(record)?;
+            num_bytes = writer.unflushed_bytes() - before;
+            Ok(())
+        })?;
+        shard.shard.num_records += 1;
+        shard.num_bytes += num_bytes;
+        Ok(())
+    }
+
+    /// Flush and close all shards, and name them by the final number of shards.
+    ///
+    /// It returns the shards in order with their record counts. Unused shards of a fixed
+    /// count are written empty, and an unknown count of no records yields one empty
+    /// shard.
+    pub fn finalize(mut self) -> Result<Vec<WrittenShard>> {
+        self.poison.check()?;
+        for index in 0..self.shards.len() {
+            self.close(index)?;
+        }
+
+        let num_shards = self.config.num_shards;
+        let count = num_shards.unwrap_or(self.shards.len()).max(1);
+        while self.shards.len() < count {
+            self.open_shard()?;
+            self.close_last()?;
+        }
+
+        let writer_config = &self.config.writer;
+        self.shards
+            .iter()
+            .enumerate()
+            .map(|(index, shard)| {
+                let path = PathBuf::from(shardspec::shard_name(&self.prefix, index, count));
+                if num_shards.is_none() {
+                    fs::rename(&shard.path, &path)
+                        .map_err(|err| Error::from_io_with_context(err, &shard.path, None))?;
+                    metadata::sync_sidecar(&path, writer_config, Compression::None)?;
+                    remove_if_exists(&metadata::metadata_path(&shard.path))?;
+                }
+                Ok(WrittenShard {
+                    path,
+                    num_records: shard.shard.num_records,
+                    num_bytes: shard.num_bytes,
+                })
+            })
+            .collect()
+    }
+
+    fn is_full(&self, shard: &ShardFile<T>) -> bool {
+        let ShardedWriterConfig {
+            max_records_per_shard,
+            max_bytes_per_shard,
+            ..
+        } = self.config;
+        max_records_per_shard.is_some_and(|max| shard.shard.num_records >= max)
+            || max_bytes_per_shard.is_some_and(|max| shard.num_bytes >= max)
+    }
+
+    /// Create the file of the next shard.
+    fn open_shard(&mut self) -> Result<()> {
+        let index = self.shards.len();
+        let path = match self.config.num_shards {
+            Some(num_shards) => {
+                if index >= num_shards {
+                    return Err(self.poison_with(Error::limit_exceeded(
+                        "num_shards",
+                        num_shards,
+                        index + 1,
+                    )));
+                }
+                shardspec::shard_name(&self.prefix, index, num_shards)
+            }
+            None => format!("{}-{:05}.partial", self.prefix, index),
+        };
+        let path = PathBuf::from(path);
+        let writer = RecordWriter::create_with_config(&path, self.config.writer.clone())
+            .map_err(|err| err.with_io_context(&path, None))
+            .map_err(|err| self.poison_with(err))?;
+        self.shards.push(ShardFile {
+            path,
+            num_bytes: 0,
+            shard: Shard::new(Some(writer)),
+        });
+        Ok(())
+    }
+
+    fn close_last(&mut self) -> Result<()> {
+        match self.shards.len() {
+            0 => Ok(()),
+            len => self.close(len - 1),
+        }
+    }
+
+    /// Flush the shard and drop its writer.
+    fn close(&mut self, index: usize) -> Result<()> {
+        let shard = &mut self.shards[index];
+        if matches!(shard.shard.writer, Ok(None)) {
+            return Ok(());
+        }
+        let path = shard.path.clone();
+        self.poison.run(&mut shard.shard, |writer| {
+            writer
+                .take()
+                .unwrap()
+                .flush()
+                .map_err(|err| err.with_io_context(&path, None))
+        })?;
+        shard.shard.flushed();
+        Ok(())
+    }
+
+    /// Poison the writer by an error not attributed to a shard.
+    fn poison_with(&mut self, error: Error) -> Error {
+        let mut failed = Shard::new(None::<RecordWriter<T, BufWriter<File>>>);
+        self.poison.fail(&mut failed, error)
+    }
+}
+
+fn remove_if_exists(path: &std::path::Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
@@ -0,0 +1,278 @@
+use crate::{
+    error::{Error, Result},
+    record::Record,
+};
+use futures::{
+    future::BoxFuture,
+    io::{AsyncWrite, AsyncWriteExt as _},
+};
+use std::{marker::PhantomData, sync::Arc};
+
+/// A chunk of whole records handed to an [AsyncRecordSink].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SinkPart {
+    /// The framed record bytes. They are shared with the writer, which takes them back
+    /// to retry a failed part.
+    pub bytes: Arc<Vec<u8>>,
+    /// The number of records in the part.
+    pub num_records: u64,
+}
+
+/// The position where an interrupted upload session continues.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ResumePoint {
+    /// The number of bytes already committed by the sink.
+    pub offset: u64,
+    /// The number of records already committed by the sink.
+    pub num_records: u64,
+}
+
+/// A multipart destination for [RecordSinkWriter].
+///
+/// The trait is meant to be implemented for object storage uploads, where data is
+/// committed in parts and an interrupted session can be resumed later.
+/// Any [AsyncWrite] can be used through the [AsyncWriteSink] adapter.
+pub trait AsyncRecordSink {
+    /// The identifier of a committed part.
+    type PartId;
+    /// The state required to resume an interrupted session.
+    type Session;
+
+    /// Commit a part and return its identifier.
+    fn write_part(&mut self, part: SinkPart) -> BoxFuture<'_, Result<Self::PartId>>;
+
+    /// Reattach to an interrupted session and report the committed position.
+    fn resume(&mut self, session: Self::Session) -> BoxFuture<'_, Result<ResumePoint>>;
+
+    /// Finalize the upload.
+    fn complete(&mut self) -> BoxFuture<'_, Result<()>>;
+
+    /// Discard the upload.
+    fn abort(&mut self) -> BoxFuture<'_, Result<()>>;
+
+    /// Called when the writer is dropped without being finished or aborted.
+    ///
+    /// It runs inside [Drop] and must not block, since the writer is usually dropped
+    /// on an executor thread. The default implementation does nothing, leaving the
+    /// incomplete upload to the remote side, for example, to a lifecycle rule on the bucket.
+    /// Sinks that need a prompt cleanup should override it, for example, by spawning the
+    /// abort request on a background task, or abort explicitly with
+    /// [RecordSinkWriter::abort].
+    fn abort_on_drop(&mut self) {}
+}
+
+/// The [AsyncRecordSink] adapter for types with [AsyncWrite] trait.
+///
+/// Parts are written to the underlying writer in order. The adapter cannot resume sessions.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AsyncWriteSink<W> {
+    writer: W,
+    num_parts: usize,
+}
+
+impl<W> AsyncWriteSink<W>
+where
+    W: AsyncWrite + Unpin + Send,
+{
+    /// Build from a writer with [AsyncWrite] trait.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            num_parts: 0,
+        }
+    }
+
+    /// Unwraps the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W> AsyncRecordSink for AsyncWriteSink<W>
+where
+    W: AsyncWrite + Unpin + Send,
+{
+    type PartId = usize;
+    type Session = ();
+
+    fn write_part(&mut self, part: SinkPart) -> BoxFuture<'_, Result<usize>> {
+        Box::pin(async move {
+            self.writer.write_all(&part.bytes).await?;
+            let part_id = self.num_parts;
+            self.num_parts += 1;
+            Ok(part_id)
+        })
+    }
+
+    fn resume(&mut self, _session: ()) -> BoxFuture<'_, Result<ResumePoint>> {
+        Box::pin(async move {
+            Err(Error::invalid_argument(
+                "AsyncWriteSink does not support resuming sessions",
+            ))
+        })
+    }
+
+    fn complete(&mut self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            self.writer.flush().await?;
+            Ok(())
+        })
+    }
+
+    fn abort(&mut self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move { Ok(()) })
+    }
+
+    fn abort_on_drop(&mut self) {}
+}
+
+/// Configuration for [RecordSinkWriter].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RecordSinkWriterConfig {
+    /// The minimum size in bytes of a part except the last one.
+    pub part_size: usize,
+}
+
+impl Default for RecordSinkWriterConfig {
+    fn default() -> Self {
+        Self {
+            part_size: 8 * 1024 * 1024,
+        }
+    }
+}
+
+/// The record writer targeting an [AsyncRecordSink].
+///
+/// Records are buffered and handed to the sink in parts of at least
+/// [part_size](RecordSinkWriterConfig::part_size) bytes. A record never straddles
+/// two parts. If a part fails, its records stay buffered and are sent again by the
+/// next [send](RecordSinkWriter::send) or [finish](RecordSinkWriter::finish).
+/// Dropping the writer before [finish](RecordSinkWriter::finish) calls
+/// [abort_on_drop](AsyncRecordSink::abort_on_drop) on the sink.
+pub struct RecordSinkWriter<T, S>
+where
+    T: Record,
+    S: AsyncRecordSink,
+{
+    sink: Option<S>,
+    part_size: usize,
+    buffer: Vec<u8>,
+    /// The buffer reused to encode records.
+    record_buf: Vec<u8>,
+    num_buffered_records: u64,
+    num_committed_records: u64,
+    part_ids: Vec<S::PartId>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T, S> RecordSinkWriter<T, S>
+where
+    T: Record,
+    S: AsyncRecordSink,
+{
+    /// Build a writer starting a new upload on the sink.
+    pub fn new(sink: S, config: RecordSinkWriterConfig) -> Self {
+        let RecordSinkWriterConfig { part_size } = config;
+        Self {
+            sink: Some(sink),
+            part_size,
+            buffer: vec![],
+            record_buf: vec![],
+            num_buffered_records: 0,
+            num_committed_records: 0,
+            part_ids: vec![],
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Build a writer continuing an interrupted upload.
+    ///
+    /// The caller should skip the first [num_committed_records](RecordSinkWriter::num_committed_records)
+    /// records of its input before sending more.
+    pub async fn resume(
+        mut sink: S,
+        session: S::Session,
+        config: RecordSinkWriterConfig,
+    ) -> Result<Self> {
+        let ResumePoint { num_records, .. } = sink.resume(session).await?;
+        let mut writer = Self::new(sink, config);
+        writer.num_committed_records = num_records;
+        Ok(writer)
+    }
+
+    /// Write a record.
+    pub async fn send(&mut self, record: T) -> Result<()> {
+        self.record_buf.clear();
+        T::encode_into(record, &mut self.record_buf)?;
+        crate::io::sync::try_write_record_slice(&mut self.buffer, &self.record_buf)?;
+        self.num_buffered_records += 1;
+
+        if self.buffer.len() >= self.part_size {
+            self.write_buffered_part().await?;
+        }
+        Ok(())
+    }
+
+    /// The number of records committed to the sink, including those before resuming.
+    pub fn num_committed_records(&self) -> u64 {
+        self.num_committed_records
+    }
+
+    /// The identifiers of parts committed by this writer.
+    pub fn part_ids(&self) -> &[S::PartId] {
+        &self.part_ids
+    }
+
+    /// Commit the buffered records and complete the upload.
+    pub async fn finish(mut self) -> Result<S> {
+        self.write_buffered_part().await?;
+        let mut sink = self.sink.take().unwrap();
+        sink.complete().await?;
+        Ok(sink)
+    }
+
+    /// Discard the buffered records and abort the upload.
+    pub async fn abort(mut self) -> Result<S> {
+        let mut sink = self.sink.take().unwrap();
+        sink.abort().await?;
+        Ok(sink)
+    }
+
+    async fn write_buffered_part(&mut self) -> Result<()> {
+        if self.num_buffered_records == 0 {
+            return Ok(());
+        }
+
+        let bytes = Arc::new(std::mem::take(&mut self.buffer));
+        let part = SinkPart {
+            bytes: bytes.clone(),
+            num_records: self.num_buffered_records,
+        };
+        let sink = self.sink.as_mut().unwrap();
+        let part_id = match sink.write_part(part).await {
+            Ok(part_id) => part_id,
+            Err(err) => {
+                // restore the buffered records, so that the part can be retried
+                self.buffer = Arc::try_unwrap(bytes).unwrap_or_else(|bytes| (*bytes).clone());
+                return Err(err);
+            }
+        };
+
+        self.part_ids.push(part_id);
+        self.num_committed_records += self.num_buffered_records;
+        self.num_buffered_records = 0;
+        Ok(())
+    }
+}
+
+impl<T, S> Drop for RecordSinkWriter<T, S>
+where
+    T: Record,
+    S: AsyncRecordSink,
+{
+    fn drop(&mut self) {
+        if let Some(sink) = self.sink.as_mut() {
+            sink.abort_on_drop();
+        }
+    }
+}
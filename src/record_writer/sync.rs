@@ -1,4 +1,13 @@
-use crate::{error::Result, protobuf::Example, record::Record};
+use super::{AppendReport, FlushFn, RecordWriterConfig, SortedIndicesEncoding, Unflushed};
+#[cfg(feature = "mmap")]
+use crate::mmap::{MmapConfig, MmapFile};
+use crate::{
+    compression::Compression,
+    error::{Error, Result},
+    memory::MemoryBuffer,
+    protobuf::Example,
+    record::Record,
+};
 use std::{
     fs::File,
     io::{BufWriter, Write},
@@ -13,12 +22,23 @@ pub type BytesWriter<W> = RecordWriter<Vec<u8>, W>;
 pub type ExampleWriter<W> = RecordWriter<Example, W>;
 
 /// The record writer.
+///
+/// The writer flushes on drop, and reports a failure of the flush as configured by
+/// [on_unflushed_drop](RecordWriterConfig::on_unflushed_drop).
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct RecordWriter<T, W>
 where
     T: Record,
 {
-    writer: W,
+    canonical_encoding: bool,
+    sorted_features: bool,
+    encode_sorted_indices: Option<SortedIndicesEncoding>,
+    /// The underlying writer, taken by [into_inner](RecordWriter::into_inner).
+    writer: Option<W>,
+    flush_on_drop: FlushFn<W>,
+    unflushed: Unflushed,
+    /// The buffer reused across records.
+    buf: Vec<u8>,
     _phantom: PhantomData<T>,
 }
 
@@ -27,12 +47,177 @@ where
     T: Record,
 {
     /// Build a writer writing to a new file.
+    ///
+    /// A file without records is empty, which readers accept as a valid file.
+    ///
+    /// ```rust
+    /// # fn main() -> tfrecord::Result<()> {
+    /// use tfrecord::{samples, ExampleIter, ExampleWriter};
+    ///
+    /// let dataset = samples::tiny_dataset(0, 0)?;
+    /// let path = dataset.dir().join("written.tfrecord");
+    ///
+    /// let mut writer = ExampleWriter::create(&path)?;
+    /// for index in 0..4 {
+    ///     writer.send(samples::example(index))?;
+    /// }
+    /// writer.flush()?;
+    ///
+    /// let count = ExampleIter::open(&path, Default::default())?.count();
+    /// assert_eq!(count, 4);
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn create<P>(path: P) -> Result<Self>
     where
         P: AsRef<Path>,
     {
+        Self::create_with_config(path, Default::default())
+    }
+
+    /// Build a writer writing to a new file with custom configuration.
+    pub fn create_with_config<P>(path: P, config: RecordWriterConfig) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let writer = BufWriter::new(File::create(path)?);
+        crate::metadata::sync_sidecar(path, &config, Compression::None)?;
+        Self::from_writer_with_config(writer, config)
+    }
+
+    /// Build a writer appending to a file, and the report of the records already in it.
+    ///
+    /// The file is created if missing. Its frames are verified forward, and a partially
+    /// written frame at the end, such as after a crash, is truncated before appending.
+    /// A complete frame failing checksum verification fails with
+    /// [Error::ChecksumMismatch]. See [append_with_config](RecordWriter::append_with_config)
+    /// to truncate the file there instead.
+    ///
+    /// ```rust
+    /// # fn main() -> tfrecord::Result<()> {
+    /// use tfrecord::{samples, ExampleIter, ExampleWriter};
+    ///
+    /// let dataset = samples::tiny_dataset(1, 3)?;
+    /// let path = &dataset.paths()[0];
+    ///
+    /// let (mut writer, report) = ExampleWriter::append(path)?;
+    /// assert_eq!(report.num_records, 3);
+    /// writer.send(samples::example(3))?;
+    /// writer.flush()?;
+    ///
+    /// let count = ExampleIter::open(path, Default::default())?.count();
+    /// assert_eq!(count, 4);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn append<P>(path: P) -> Result<(Self, AppendReport)>
+    where
+        P: AsRef<Path>,
+    {
+        Self::append_with_config(path, Default::default(), false)
+    }
+
+    /// Build a writer appending to a file with custom configuration, and the report of
+    /// the records already in it.
+    ///
+    /// If `force` is set, the file is truncated at the first frame failing checksum
+    /// verification, dropping the records after it, instead of failing.
+    ///
+    /// The [shard metadata](crate::metadata) of the file is kept only if
+    /// [sorted_features](RecordWriterConfig::sorted_features) is set, since the
+    /// appended records may break the declared properties otherwise.
+    pub fn append_with_config<P>(
+        path: P,
+        config: RecordWriterConfig,
+        force: bool,
+    ) -> Result<(Self, AppendReport)>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let (file, report) = super::open_for_append(path, force)?;
+        if !config.sorted_features {
+            crate::metadata::sync_sidecar(path, &config, Compression::None)?;
+        }
+        let writer = Self::from_writer_with_config(BufWriter::new(file), config)?;
+        Ok((writer, report))
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl<T> RecordWriter<T, MmapFile>
+where
+    T: Record,
+{
+    /// Build a writer writing to a new file through a preallocated memory mapping.
+    ///
+    /// See the [mmap](crate::mmap) module for details. The writer must be
+    /// [finished](RecordWriter::finish) to truncate the file to the written records.
+    pub fn create_mmap<P>(path: P, config: RecordWriterConfig, mmap: MmapConfig) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let writer = MmapFile::create(path, mmap)?;
+        crate::metadata::sync_sidecar(path, &config, Compression::None)?;
+        Self::from_writer_with_config(writer, config)
+    }
+
+    /// Unmap and truncate the file to the written records.
+    pub fn finish(self) -> Result<()> {
+        self.into_inner().finish()
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl<T> RecordWriter<T, flate2::write::GzEncoder<BufWriter<File>>>
+where
+    T: Record,
+{
+    /// Build a writer writing a GZIP-compressed stream to a new file.
+    ///
+    /// The records are framed before compression, so the file is read with
+    /// [Compression::Gzip](crate::compression::Compression::Gzip) or by TensorFlow with
+    /// `compression_type="GZIP"`. The level ranges from 0, storing uncompressed, to 9,
+    /// compressing best. The writer must be [finished](RecordWriter::finish) to end the
+    /// stream. Dropping it ends the stream as well, but ignores the errors.
+    pub fn create_gzip<P>(path: P, config: RecordWriterConfig, level: u32) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let level = crate::compression::gzip_level(level)?;
+        let path = path.as_ref();
         let writer = BufWriter::new(File::create(path)?);
-        Self::from_writer(writer)
+        crate::metadata::sync_sidecar(path, &config, Compression::Gzip)?;
+        Self::from_writer_with_config(flate2::write::GzEncoder::new(writer, level), config)
+    }
+
+    /// End the compressed stream and flush the file.
+    pub fn finish(self) -> Result<()> {
+        self.into_inner().finish()?.flush()?;
+        Ok(())
+    }
+}
+
+impl<T> RecordWriter<T, BufWriter<MemoryBuffer>>
+where
+    T: Record,
+{
+    /// Build a writer writing to memory, and the handle to the written bytes.
+    ///
+    /// The writer is buffered like the one writing to a file, so the bytes become
+    /// visible in the [MemoryBuffer] after [flush](RecordWriter::flush) or drop.
+    pub fn in_memory() -> Result<(Self, MemoryBuffer)> {
+        Self::in_memory_with_config(Default::default())
+    }
+
+    /// Build a writer writing to memory with custom configuration, and the handle to
+    /// the written bytes.
+    pub fn in_memory_with_config(config: RecordWriterConfig) -> Result<(Self, MemoryBuffer)> {
+        let buffer = MemoryBuffer::new();
+        let writer = Self::from_writer_with_config(BufWriter::new(buffer.clone()), config)?;
+        Ok((writer, buffer))
     }
 }
 
@@ -43,8 +228,29 @@ where
 {
     /// Build a writer from a writer with [Write] trait.
     pub fn from_writer(writer: W) -> Result<Self> {
+        Self::from_writer_with_config(writer, Default::default())
+    }
+
+    /// Build a writer from a writer with [Write] trait and custom configuration.
+    pub fn from_writer_with_config(writer: W, config: RecordWriterConfig) -> Result<Self> {
+        let unflushed = Unflushed::new(&config);
+        let RecordWriterConfig {
+            canonical_encoding,
+            sorted_features,
+            on_unflushed_drop: _,
+            panic_on_unflushed_drop: _,
+            encode_sorted_indices,
+            stamp_producer: _,
+        } = config;
+
         Ok(Self {
-            writer,
+            canonical_encoding,
+            sorted_features,
+            encode_sorted_indices,
+            writer: Some(writer),
+            flush_on_drop: FlushFn::new(),
+            unflushed,
+            buf: vec![],
             _phantom: PhantomData,
         })
     }
@@ -53,14 +259,139 @@ where
     ///
     /// The method is enabled if the underlying writer implements [Write].
     pub fn send(&mut self, record: T) -> Result<()> {
-        let bytes = T::to_bytes(record)?;
-        crate::io::sync::try_write_record(&mut self.writer, bytes)?;
+        let record = match &self.encode_sorted_indices {
+            Some(encoding) => T::encode_sorted_indices(record, encoding)?,
+            None => record,
+        };
+        let writer = self.writer.as_mut().unwrap();
+        let len = if self.canonical_encoding || self.sorted_features {
+            let bytes = T::to_bytes_canonical(record)?;
+            debug_assert!(
+                !self.sorted_features || T::has_sorted_keys(&bytes)?,
+                "the canonical encoding produced unsorted feature keys"
+            );
+            crate::io::sync::try_write_record_slice(writer, &bytes)?;
+            bytes.len()
+        } else {
+            self.buf.clear();
+            T::encode_into(record, &mut self.buf)?;
+            crate::io::sync::try_write_record_slice(writer, &self.buf)?;
+            self.buf.len()
+        };
+        self.unflushed.add(len);
         Ok(())
     }
 
     /// Flush the output stream.
     pub fn flush(&mut self) -> Result<()> {
-        self.writer.flush()?;
+        self.writer.as_mut().unwrap().flush()?;
+        self.unflushed.clear();
         Ok(())
     }
+
+    /// Write the records of an iterator and flush, returning the number of written
+    /// records.
+    ///
+    /// Records are pulled from the iterator one at a time as they are written, so a lazy
+    /// iterator is written in constant memory. On a failure, the records written before
+    /// are flushed, and the error is [Error::WriteInterrupted] carrying the number of
+    /// records committed by this call, so that the caller can resume the iterator after
+    /// them. A record failing with an I/O error may leave a partial frame after the
+    /// committed records.
+    ///
+    /// ```rust
+    /// # fn main() -> tfrecord::Result<()> {
+    /// use tfrecord::{samples, ExampleIter, ExampleWriter};
+    ///
+    /// let (mut writer, buffer) = ExampleWriter::in_memory()?;
+    /// let num_records = writer.write_all((0..1000).map(samples::example))?;
+    /// assert_eq!(num_records, 1000);
+    ///
+    /// let count = ExampleIter::from_bytes(buffer.to_vec(), Default::default()).count();
+    /// assert_eq!(count, 1000);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_all<I>(&mut self, records: I) -> Result<u64>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut num_sent = 0;
+        for record in records {
+            if let Err(err) = self.send(record) {
+                return Err(self.interrupted(num_sent, err));
+            }
+            num_sent += 1;
+        }
+        self.flush().map_err(|err| interrupted(0, err))?;
+        Ok(num_sent)
+    }
+
+    /// Flush the records sent before a failure, and wrap the failure with the number of
+    /// committed records.
+    fn interrupted(&mut self, num_sent: u64, err: Error) -> Error {
+        let committed = if self.flush().is_ok() { num_sent } else { 0 };
+        interrupted(committed, err)
+    }
+
+    /// The framed bytes of the records sent since the last flush.
+    pub(crate) fn unflushed_bytes(&self) -> u64 {
+        self.unflushed.num_bytes()
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        self.writer.as_ref().unwrap()
+    }
+
+    /// Unwraps the underlying writer, which becomes responsible for flushing.
+    pub fn into_inner(mut self) -> W {
+        self.unflushed.clear();
+        self.writer.take().unwrap()
+    }
+
+    /// Drop the writer without flushing or reporting unflushed records.
+    ///
+    /// The underlying writer may still flush on drop, such as [BufWriter].
+    pub fn abandon(mut self) {
+        self.unflushed.clear();
+    }
+}
+
+#[cfg(feature = "with-serde")]
+impl<W> RecordWriter<Example, W>
+where
+    W: Write,
+{
+    /// Convert a value to an example by [to_example](crate::serde_example::to_example)
+    /// and write it.
+    pub fn send_serde<S>(&mut self, value: &S) -> Result<()>
+    where
+        S: serde::Serialize + ?Sized,
+    {
+        self.send(crate::serde_example::to_example(value)?)
+    }
+}
+
+pub(super) fn interrupted(committed: u64, err: Error) -> Error {
+    Error::WriteInterrupted {
+        committed,
+        source: Box::new(err),
+    }
+}
+
+impl<T, W> Drop for RecordWriter<T, W>
+where
+    T: Record,
+{
+    fn drop(&mut self) {
+        if self.unflushed.is_empty() {
+            return;
+        }
+        if let Some(writer) = &mut self.writer {
+            if let Err(error) = self.flush_on_drop.call(writer) {
+                self.unflushed.report("RecordWriter", Some(&error));
+            }
+        }
+    }
 }
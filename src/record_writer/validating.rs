@@ -0,0 +1,159 @@
+use super::{ExampleWriter, RecordWriter, RecordWriterConfig};
+use crate::{
+    error::Result,
+    protobuf::Example,
+    schema::{self, FeatureSpec, SchemaProvider, SchemaSource, SchemaUsage},
+};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    sync::Arc,
+};
+
+/// Configuration for [ValidatingWriter].
+#[derive(Debug, Clone, Default)]
+pub struct ValidatingWriterConfig {
+    /// The source of the specs examples are checked against. Examples are written
+    /// unchecked if unset.
+    pub schema: Option<SchemaSource>,
+    /// The configuration of the underlying writer.
+    pub writer: RecordWriterConfig,
+}
+
+/// An example writer rejecting examples not conforming to a schema.
+///
+/// The [schema source](SchemaSource) is resolved when the writer is built, so a missing
+/// schema fails before any file is created. Each example is checked by
+/// [validate](schema::validate) before it is written, and a nonconforming example is
+/// returned as the error of [send](ValidatingWriter::send) without being written.
+/// [finish](ValidatingWriter::finish) reports the [SchemaUsage] to the provider of the
+/// schema, if any.
+///
+/// ```rust
+/// # fn main() -> tfrecord::Result<()> {
+/// use tfrecord::{
+///     samples,
+///     schema::{FeatureSpec, SchemaSource, ValueCount, ValueType},
+///     ValidatingWriter, ValidatingWriterConfig,
+/// };
+///
+/// let dataset = samples::tiny_dataset(0, 0)?;
+/// let config = ValidatingWriterConfig {
+///     schema: Some(SchemaSource::Inline(vec![FeatureSpec::new(
+///         "id",
+///         ValueType::I64,
+///         ValueCount::Fixed(1),
+///     )])),
+///     ..Default::default()
+/// };
+/// let mut writer = ValidatingWriter::create(dataset.dir().join("valid.tfrecord"), config)?;
+/// writer.send(samples::example(0))?;
+/// assert!(writer.send(Default::default()).is_err());
+///
+/// let usage = writer.finish()?;
+/// assert_eq!((usage.num_accepted, usage.num_rejected), (1, 1));
+/// # Ok(())
+/// # }
+/// ```
+pub struct ValidatingWriter<W>
+where
+    W: Write,
+{
+    writer: ExampleWriter<W>,
+    specs: Vec<FeatureSpec>,
+    provider: Option<(Arc<dyn SchemaProvider>, String)>,
+    usage: SchemaUsage,
+}
+
+impl ValidatingWriter<BufWriter<File>> {
+    /// Resolve the schema and build a writer writing to a new file.
+    pub fn create<P>(path: P, config: ValidatingWriterConfig) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let ValidatingWriterConfig { schema, writer } = config;
+        let (specs, provider) = resolve(schema)?;
+        let writer = RecordWriter::create_with_config(path, writer)?;
+        Ok(Self::new(writer, specs, provider))
+    }
+}
+
+impl<W> ValidatingWriter<W>
+where
+    W: Write,
+{
+    /// Resolve the schema and build a writer from a writer with [Write] trait.
+    pub fn from_writer(writer: W, config: ValidatingWriterConfig) -> Result<Self> {
+        let ValidatingWriterConfig {
+            schema,
+            writer: config,
+        } = config;
+        let (specs, provider) = resolve(schema)?;
+        let writer = RecordWriter::from_writer_with_config(writer, config)?;
+        Ok(Self::new(writer, specs, provider))
+    }
+
+    fn new(
+        writer: ExampleWriter<W>,
+        specs: Vec<FeatureSpec>,
+        provider: Option<(Arc<dyn SchemaProvider>, String)>,
+    ) -> Self {
+        Self {
+            writer,
+            specs,
+            provider,
+            usage: SchemaUsage::default(),
+        }
+    }
+
+    /// Check the example and write it if it conforms.
+    pub fn send(&mut self, example: Example) -> Result<()> {
+        if let Err(err) = schema::validate(&self.specs, &example) {
+            self.usage.num_rejected += 1;
+            return Err(err);
+        }
+        self.writer.send(example)?;
+        self.usage.num_accepted += 1;
+        Ok(())
+    }
+
+    /// Flush the output stream.
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()
+    }
+
+    /// The resolved specs.
+    pub fn specs(&self) -> &[FeatureSpec] {
+        &self.specs
+    }
+
+    /// The number of examples accepted and rejected so far.
+    pub fn usage(&self) -> SchemaUsage {
+        self.usage
+    }
+
+    /// Flush the output stream and report the usage to the provider of the schema.
+    pub fn finish(mut self) -> Result<SchemaUsage> {
+        self.writer.flush()?;
+        if let Some((provider, schema_id)) = &self.provider {
+            provider.report_usage(schema_id, &self.usage)?;
+        }
+        Ok(self.usage)
+    }
+}
+
+type Resolved = (Vec<FeatureSpec>, Option<(Arc<dyn SchemaProvider>, String)>);
+
+fn resolve(schema: Option<SchemaSource>) -> Result<Resolved> {
+    let schema = match schema {
+        Some(schema) => schema,
+        None => return Ok((vec![], None)),
+    };
+    let specs = schema.resolve()?;
+    let provider = match schema {
+        SchemaSource::Provider(provider, schema_id) => Some((provider, schema_id)),
+        SchemaSource::Inline(_) | SchemaSource::File(_) => None,
+    };
+    Ok((specs, provider))
+}
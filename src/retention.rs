@@ -0,0 +1,250 @@
+//! Drop expired records by a timestamp feature.
+//!
+//! [apply] copies the records of input files to output files, dropping the examples
+//! whose timestamp feature is older than the [maximum age](RetentionPolicy::max_age)
+//! at [now](RetentionPolicy::now). The timestamp is the first element of an
+//! `Int64List` feature. Only the timestamp feature is decoded, and surviving payloads
+//! are copied verbatim, so every output record is a byte-identical copy of an input
+//! record.
+//!
+//! A record is expired if its timestamp is strictly before the cutoff `now - max_age`,
+//! so a record exactly at the cutoff is kept. The current time is a parameter rather
+//! than the wall clock, so that a pass is reproducible.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use std::time::{Duration, UNIX_EPOCH};
+//! use tfrecord::{
+//!     retention::{self, MissingTimestamp, RetentionPolicy, TimeUnit},
+//!     samples,
+//! };
+//!
+//! // the "id" feature of samples serves as the timestamp in seconds
+//! let dataset = samples::tiny_dataset(2, 10)?;
+//! let policy = RetentionPolicy {
+//!     timestamp_feature: "id".into(),
+//!     unit: TimeUnit::Seconds,
+//!     max_age: Duration::from_secs(10),
+//!     now: UNIX_EPOCH + Duration::from_secs(15),
+//!     missing: MissingTimestamp::Error,
+//! };
+//! let prefix = dataset.dir().join("retained");
+//! let report = retention::apply(dataset.paths(), prefix.to_str().unwrap(), &policy)?;
+//! assert_eq!(report.num_kept(), 15);
+//! assert_eq!(report.num_dropped(), 5);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    protobuf::Example,
+    protobuf_ext::FeatureProjection,
+    record_reader::BytesIter,
+    record_writer::BytesWriter,
+    shardspec,
+};
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The unit of timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimeUnit {
+    /// Seconds since UNIX epoch.
+    Seconds,
+    /// Milliseconds since UNIX epoch.
+    Millis,
+}
+
+impl TimeUnit {
+    fn of(self, duration: Duration) -> i128 {
+        match self {
+            Self::Seconds => duration.as_secs() as i128,
+            Self::Millis => duration.as_millis() as i128,
+        }
+    }
+}
+
+/// The handling of records without the timestamp feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MissingTimestamp {
+    /// Keep the record.
+    Keep,
+    /// Drop the record.
+    Drop,
+    /// Fail the pass.
+    #[default]
+    Error,
+}
+
+/// The retention policy of a pass.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RetentionPolicy {
+    /// The key of the `Int64List` feature holding the timestamp as its first element.
+    pub timestamp_feature: String,
+    pub unit: TimeUnit,
+    /// The maximum age of kept records.
+    pub max_age: Duration,
+    /// The current time, against which ages are measured.
+    pub now: SystemTime,
+    /// The handling of records without the timestamp feature or with an empty list.
+    pub missing: MissingTimestamp,
+}
+
+impl RetentionPolicy {
+    /// The earliest kept timestamp in the unit of the policy.
+    pub fn cutoff(&self) -> i128 {
+        // times before UNIX epoch are negative
+        let now = match self.now.duration_since(UNIX_EPOCH) {
+            Ok(elapsed) => self.unit.of(elapsed),
+            Err(err) => -self.unit.of(err.duration()),
+        };
+        now - self.unit.of(self.max_age)
+    }
+}
+
+/// The outcome of [apply].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RetentionReport {
+    pub policy: RetentionPolicy,
+    /// The reports per input file in order.
+    pub files: Vec<FileRetention>,
+}
+
+impl RetentionReport {
+    /// The number of kept records in all files.
+    pub fn num_kept(&self) -> u64 {
+        self.files.iter().map(|file| file.kept).sum()
+    }
+
+    /// The number of dropped records in all files.
+    pub fn num_dropped(&self) -> u64 {
+        self.files.iter().map(|file| file.dropped).sum()
+    }
+}
+
+/// The retention of an input file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileRetention {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    /// The number of kept records, including the kept records without timestamps.
+    pub kept: u64,
+    /// The number of dropped records, including the dropped records without
+    /// timestamps.
+    pub dropped: u64,
+    /// The number of records without timestamps.
+    pub missing: u64,
+}
+
+/// Copy the unexpired records of input files to output files.
+///
+/// Every input file has an output file named `{output_prefix}-{index:05}-of-{count:05}`,
+/// where the count is the number of inputs. Records are processed in input order. On
+/// failure, the outputs written so far are removed.
+pub fn apply<'a, P, I>(
+    inputs: I,
+    output_prefix: &str,
+    policy: &RetentionPolicy,
+) -> Result<RetentionReport>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Cow<'a, Path>>,
+{
+    let inputs: Vec<PathBuf> = inputs
+        .into_iter()
+        .map(|path| path.into().into_owned())
+        .collect();
+    let cutoff = policy.cutoff();
+    let projection = FeatureProjection::new([policy.timestamp_feature.as_str()]);
+
+    let mut files = vec![];
+    let mut created: Vec<PathBuf> = vec![];
+    let mut retain = || -> Result<()> {
+        for (index, input) in inputs.iter().enumerate() {
+            let output = PathBuf::from(shardspec::shard_name(output_prefix, index, inputs.len()));
+            let mut writer = BytesWriter::create(&output)?;
+            created.push(output.clone());
+
+            let mut file = FileRetention {
+                input: input.clone(),
+                output,
+                kept: 0,
+                dropped: 0,
+                missing: 0,
+            };
+            for (record_index, bytes) in BytesIter::open(input, Default::default())?.enumerate() {
+                let bytes = bytes?;
+                let timestamp =
+                    timestamp(&bytes, &projection, &policy.timestamp_feature).map_err(|err| {
+                        Error::RecordFailed {
+                            path: input.clone(),
+                            index: record_index as u64,
+                            source: Box::new(err),
+                        }
+                    })?;
+                let keep = match timestamp {
+                    Some(timestamp) => timestamp as i128 >= cutoff,
+                    None => {
+                        file.missing += 1;
+                        match policy.missing {
+                            MissingTimestamp::Keep => true,
+                            MissingTimestamp::Drop => false,
+                            MissingTimestamp::Error => {
+                                return Err(Error::RecordFailed {
+                                    path: input.clone(),
+                                    index: record_index as u64,
+                                    source: Box::new(Error::conversion(format!(
+                                        "the timestamp feature '{}' is missing",
+                                        policy.timestamp_feature
+                                    ))),
+                                })
+                            }
+                        }
+                    }
+                };
+                if keep {
+                    writer.send(bytes)?;
+                    file.kept += 1;
+                } else {
+                    file.dropped += 1;
+                }
+            }
+            writer.flush()?;
+            files.push(file);
+        }
+        Ok(())
+    };
+
+    if let Err(err) = retain() {
+        for path in &created {
+            let _ = std::fs::remove_file(path);
+        }
+        return Err(err);
+    }
+    Ok(RetentionReport {
+        policy: policy.clone(),
+        files,
+    })
+}
+
+/// The timestamp of a record, or `None` if the feature is missing or empty.
+fn timestamp(bytes: &[u8], projection: &FeatureProjection, key: &str) -> Result<Option<i64>> {
+    let example = Example::decode_projected(bytes, projection)?;
+    if example
+        .features
+        .as_ref()
+        .is_none_or(|features| !features.feature.contains_key(key))
+    {
+        return Ok(None);
+    }
+    Ok(example.get_i64s(key)?.first().copied())
+}
@@ -0,0 +1,261 @@
+//! Iterate over the records of a file backwards.
+//!
+//! Frames have no back links, so the start of the frame before a known frame start is
+//! found by scanning backwards. A candidate start is accepted if its length checksum is
+//! valid, its frame ends exactly where the known frame starts, and its payload checksum
+//! is valid. A payload may embed bytes forming such a frame, so the candidate must be
+//! further preceded by a chain of [chain_depth](ReverseConfig::chain_depth) frames
+//! found in the same way, unless the chain reaches the start of the file first. A
+//! deeper chain rules out payloads embedding more nested fake frames, at the cost of
+//! scanning more.
+//!
+//! A file ending with a partially written frame, such as after a crash, is iterated from
+//! the last complete frame, and [trailing_len](ReverseIterator::trailing_len) reports
+//! the bytes after it.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{reverse, samples, Example};
+//!
+//! let dataset = samples::tiny_dataset(1, 10)?;
+//! let records = reverse::last_records::<Example>(&dataset.paths()[0], 3)?;
+//! let examples: Vec<_> = records.into_iter().map(|(_, example)| example).collect();
+//! assert_eq!(examples, (7..10).map(samples::example).collect::<Vec<_>>());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    io::sync::{parse_header, HEADER_LEN},
+    record::Record,
+};
+use std::{
+    fs::File,
+    io::{prelude::*, SeekFrom},
+    marker::PhantomData,
+    path::Path,
+};
+
+/// The size of the frame footer, the payload checksum.
+const FOOTER_LEN: u64 = 4;
+
+/// The number of candidate frame starts read at a time when scanning backwards.
+const SCAN_CHUNK: u64 = 64 * 1024;
+
+/// The last `n` records of a file with the offsets of their frames, in the order of the
+/// file.
+pub fn last_records<T>(path: impl AsRef<Path>, n: usize) -> Result<Vec<(u64, T)>>
+where
+    T: Record,
+{
+    let mut records = ReverseIterator::open(path, Default::default())?
+        .take(n)
+        .collect::<Result<Vec<_>>>()?;
+    records.reverse();
+    Ok(records)
+}
+
+/// Configuration for [ReverseIterator].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReverseConfig {
+    /// The number of frames that must precede a candidate frame to accept it, unless the
+    /// start of the file is reached first. Zero accepts the first candidate found.
+    pub chain_depth: usize,
+    /// The maximum number of bytes scanned backwards for a frame start, which bounds the
+    /// payload length of the frames found, including the frames of verification chains.
+    pub scan_window: u64,
+}
+
+impl Default for ReverseConfig {
+    fn default() -> Self {
+        Self {
+            chain_depth: 2,
+            scan_window: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// Iterator of the records of a file from the last one to the first one, with the
+/// offsets of their frames.
+///
+/// Iteration stops at the first error finding a frame. A record failing to decode is
+/// yielded as an error, and iteration continues before it.
+pub struct ReverseIterator<T, R = File>
+where
+    T: Record,
+    R: Read + Seek,
+{
+    reader: R,
+    /// The start of the last yielded frame, or the end of the last complete frame.
+    pos: u64,
+    trailing_len: u64,
+    chain_depth: usize,
+    scan_window: u64,
+    done: bool,
+    _phantom: PhantomData<T>,
+}
+
+/// A verified frame.
+struct Frame {
+    start: u64,
+    end: u64,
+    payload: Vec<u8>,
+}
+
+impl<T> ReverseIterator<T, File>
+where
+    T: Record,
+{
+    /// Open a file and find its last complete frame.
+    pub fn open<P>(path: P, config: ReverseConfig) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|err| Error::from_io_with_context(err, path, None))?;
+        Self::from_reader(file, config).map_err(|err| err.with_io_context(path, None))
+    }
+}
+
+impl<T, R> ReverseIterator<T, R>
+where
+    T: Record,
+    R: Read + Seek,
+{
+    /// Find the last complete frame of the reader.
+    pub fn from_reader(mut reader: R, config: ReverseConfig) -> Result<Self> {
+        let ReverseConfig {
+            chain_depth,
+            scan_window,
+        } = config;
+        let len = reader.seek(SeekFrom::End(0))?;
+        let mut iter = Self {
+            reader,
+            pos: len,
+            trailing_len: 0,
+            chain_depth,
+            scan_window,
+            done: false,
+            _phantom: PhantomData,
+        };
+        let end = iter.find_last_end(len)?;
+        iter.pos = end;
+        iter.trailing_len = len - end;
+        Ok(iter)
+    }
+
+    /// The number of bytes after the last complete frame.
+    pub fn trailing_len(&self) -> u64 {
+        self.trailing_len
+    }
+
+    fn find_last_end(&mut self, len: u64) -> Result<u64> {
+        if len == 0 || self.find_frame(len, true, self.chain_depth)?.is_some() {
+            return Ok(len);
+        }
+        if let Some(frame) = self.find_frame(len, false, self.chain_depth)? {
+            return Ok(frame.end);
+        }
+        if len > self.scan_window.saturating_add(HEADER_LEN + FOOTER_LEN) {
+            return Err(Error::conversion(format!(
+                "no complete frame found in the last {} bytes",
+                self.scan_window
+            )));
+        }
+        // the whole file is a partial frame
+        Ok(0)
+    }
+
+    fn next_record(&mut self) -> Result<(u64, T)> {
+        let frame = match self.find_frame(self.pos, true, self.chain_depth)? {
+            Some(frame) => frame,
+            None => {
+                return Err(Error::conversion(format!(
+                    "no frame ends at offset {} within the scan window",
+                    self.pos
+                )))
+            }
+        };
+        self.pos = frame.start;
+        Ok((frame.start, T::from_bytes(frame.payload)?))
+    }
+
+    /// Find the last frame ending at the limit, or before it if not `exact`, and preceded
+    /// by a chain of `depth` frames.
+    fn find_frame(&mut self, limit: u64, exact: bool, depth: usize) -> Result<Option<Frame>> {
+        let overhead = HEADER_LEN + FOOTER_LEN;
+        if limit < overhead {
+            return Ok(None);
+        }
+        let lowest = limit.saturating_sub(overhead.saturating_add(self.scan_window));
+        let mut high = limit - overhead;
+        let mut chunk = vec![];
+
+        loop {
+            let low = high.saturating_sub(SCAN_CHUNK - 1).max(lowest);
+            chunk.resize((high - low + HEADER_LEN) as usize, 0);
+            self.reader.seek(SeekFrom::Start(low))?;
+            self.reader.read_exact(&mut chunk)?;
+
+            for start in (low..=high).rev() {
+                let header = &chunk[(start - low) as usize..][..HEADER_LEN as usize];
+                let len = u64::from_le_bytes(header[..8].try_into().unwrap());
+                let end = match (start + overhead).checked_add(len) {
+                    Some(end) if end == limit || (!exact && end < limit) => end,
+                    _ => continue,
+                };
+                if parse_header(header).is_none() {
+                    continue;
+                }
+                let payload = match self.read_payload(start, len)? {
+                    Some(payload) => payload,
+                    None => continue,
+                };
+                if start == 0 || depth == 0 || self.find_frame(start, true, depth - 1)?.is_some() {
+                    return Ok(Some(Frame {
+                        start,
+                        end,
+                        payload,
+                    }));
+                }
+            }
+
+            if low == lowest {
+                return Ok(None);
+            }
+            high = low - 1;
+        }
+    }
+
+    /// Read the payload of the frame if its checksum is valid.
+    fn read_payload(&mut self, start: u64, len: u64) -> Result<Option<Vec<u8>>> {
+        let mut buf = vec![0; (len + FOOTER_LEN) as usize];
+        self.reader.seek(SeekFrom::Start(start + HEADER_LEN))?;
+        self.reader.read_exact(&mut buf)?;
+        let cksum_buf = buf.split_off(len as usize);
+        let expect_cksum = u32::from_le_bytes(cksum_buf.try_into().unwrap());
+        Ok((crate::utils::checksum(&buf) == expect_cksum).then_some(buf))
+    }
+}
+
+impl<T, R> Iterator for ReverseIterator<T, R>
+where
+    T: Record,
+    R: Read + Seek,
+{
+    type Item = Result<(u64, T)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.pos == 0 {
+            return None;
+        }
+        let pos = self.pos;
+        let result = self.next_record();
+        // a record failing to decode has moved the position, so iteration continues
+        if result.is_err() && self.pos == pos {
+            self.done = true;
+        }
+        Some(result)
+    }
+}
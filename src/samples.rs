@@ -0,0 +1,141 @@
+//! Deterministic sample data for examples and doc tests.
+//!
+//! [example] generates the `i`-th sample [Example], and [tiny_dataset] writes the
+//! samples into record files under a temporary directory, which is removed when the
+//! returned [TempDataset] is dropped. The contents depend only on the arguments, so
+//! assertions on the samples can be concrete.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{samples, ExampleIter};
+//!
+//! let dataset = samples::tiny_dataset(2, 3)?;
+//! assert_eq!(dataset.paths().len(), 2);
+//!
+//! // the second file starts with the fourth sample
+//! let mut iter = ExampleIter::open(&dataset.paths()[1], Default::default())?;
+//! assert_eq!(iter.next().transpose()?, Some(samples::example(3)));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::Result,
+    protobuf::{Example, Feature},
+    record_writer::{ExampleWriter, RecordWriterConfig},
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The number of distinct labels of the samples.
+pub const NUM_LABELS: usize = 10;
+
+/// Generate the `index`-th sample example.
+///
+/// It has the features
+/// - `id`: `[index]` as an `Int64List`.
+/// - `label`: `[index % 10]` as an `Int64List`.
+/// - `score`: `[index / 2]` as a `FloatList`.
+/// - `name`: `["sample-{index}"]` as a `BytesList`.
+pub fn example(index: usize) -> Example {
+    vec![
+        ("id".to_string(), Feature::from_i64_list(vec![index as i64])),
+        (
+            "label".to_string(),
+            Feature::from_i64_list(vec![(index % NUM_LABELS) as i64]),
+        ),
+        (
+            "score".to_string(),
+            Feature::from_f32_list(vec![index as f32 / 2.0]),
+        ),
+        (
+            "name".to_string(),
+            Feature::from_bytes_list(vec![format!("sample-{}", index).into_bytes()]),
+        ),
+    ]
+    .into_iter()
+    .collect()
+}
+
+/// Write sample examples into `num_files` files in a new temporary directory.
+///
+/// The files are named `part-00000.tfrecord`, `part-00001.tfrecord` and so on. The
+/// `j`-th record of the `i`-th file is the sample `example(i * records_per_file + j)`
+/// generated by [example]. The features are written in key order, so the files are
+/// byte-identical across calls. The files are written without
+/// [shard metadata](crate::metadata) sidecars, as plain TFRecord files.
+pub fn tiny_dataset(num_files: usize, records_per_file: usize) -> Result<TempDataset> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.subsec_nanos())
+        .unwrap_or(0);
+    let dir = std::env::temp_dir().join(format!(
+        "tfrecord-samples-{}-{}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst),
+        nanos
+    ));
+    std::fs::create_dir_all(&dir)?;
+
+    // the directory is removed on errors below
+    let mut dataset = TempDataset {
+        dir,
+        paths: vec![],
+        num_records: num_files * records_per_file,
+    };
+    for file_index in 0..num_files {
+        let path = dataset.dir.join(format!("part-{:05}.tfrecord", file_index));
+        let mut writer = ExampleWriter::create_with_config(
+            &path,
+            RecordWriterConfig {
+                canonical_encoding: true,
+                stamp_producer: false,
+                ..Default::default()
+            },
+        )?;
+        for record_index in 0..records_per_file {
+            writer.send(example(file_index * records_per_file + record_index))?;
+        }
+        writer.flush()?;
+        dataset.paths.push(path);
+    }
+    Ok(dataset)
+}
+
+/// Record files in a temporary directory created by [tiny_dataset].
+///
+/// The directory and the files are removed on drop.
+#[derive(Debug)]
+pub struct TempDataset {
+    dir: PathBuf,
+    paths: Vec<PathBuf>,
+    num_records: usize,
+}
+
+impl TempDataset {
+    /// The temporary directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The paths of the files in order.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// The total number of records.
+    pub fn num_records(&self) -> usize {
+        self.num_records
+    }
+}
+
+impl Drop for TempDataset {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
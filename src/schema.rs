@@ -0,0 +1,367 @@
+//! Declare the features of examples and check examples against the declarations.
+//!
+//! A [FeatureSpec] declares the key, the [value type](ValueType) and the
+//! [number of values](ValueCount) of a feature. [validate] checks an example against a
+//! list of specs, failing with the first nonconforming feature. Features of the example
+//! not declared by any spec are accepted.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{
+//!     samples,
+//!     schema::{self, FeatureSpec, ValueCount, ValueType},
+//! };
+//!
+//! let specs = [
+//!     FeatureSpec::new("id", ValueType::I64, ValueCount::Fixed(1)),
+//!     FeatureSpec::new("name", ValueType::Bytes, ValueCount::Fixed(1)),
+//! ];
+//! schema::validate(&specs, &samples::example(0))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Schema sources
+//!
+//! A [ValidatingWriter](crate::ValidatingWriter) checks every example it writes against
+//! the specs of a [SchemaSource], resolved when the writer is built. The specs are given
+//! inline, loaded from a file by [load_specs], or fetched by id from a [SchemaProvider].
+//! Organizations governing schemas in a central registry implement the trait for their
+//! registry client. The crate provides the [StaticSchemaProvider] in memory and the
+//! [FileSchemaProvider] reading files from a directory.
+//!
+//! Schema files are JSON or TOML, by the file extension, holding a `features` list in
+//! the serde representation of [FeatureSpec]. Loading them requires the `schema-file`
+//! feature.
+//!
+//! ```toml
+//! [[features]]
+//! key = "id"
+//! value_type = "I64"
+//! count = { Fixed = 1 }
+//! required = true
+//!
+//! [[features]]
+//! key = "tags"
+//! value_type = "Bytes"
+//! count = { Var = { min = 0 } }
+//! required = false
+//! ```
+
+use crate::{
+    error::{ensure_argument, Error, Result},
+    protobuf::{feature::Kind, Example, Feature},
+};
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+/// The type of the values of a feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ValueType {
+    Bytes,
+    F32,
+    I64,
+}
+
+impl ValueType {
+    /// The value type of the feature, or `None` if the kind is not set.
+    pub fn of(feature: &Feature) -> Option<Self> {
+        let value_type = match feature.kind.as_ref()? {
+            Kind::BytesList(_) => Self::Bytes,
+            Kind::FloatList(_) => Self::F32,
+            Kind::Int64List(_) => Self::I64,
+        };
+        Some(value_type)
+    }
+}
+
+/// The number of values of a feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ValueCount {
+    /// Exactly the number of values, as a `FixedLenFeature`.
+    Fixed(usize),
+    /// Any number of values within the bounds, as a `VarLenFeature`.
+    Var {
+        min: usize,
+        /// The maximum number, unbounded if unset.
+        max: Option<usize>,
+    },
+}
+
+impl ValueCount {
+    /// Returns true if the number of values is accepted.
+    pub fn accepts(&self, count: usize) -> bool {
+        match *self {
+            Self::Fixed(len) => count == len,
+            Self::Var { min, max } => count >= min && max.is_none_or(|max| count <= max),
+        }
+    }
+}
+
+/// The declaration of a feature.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeatureSpec {
+    pub key: String,
+    pub value_type: ValueType,
+    pub count: ValueCount,
+    /// If set, examples without the feature are rejected.
+    pub required: bool,
+}
+
+impl FeatureSpec {
+    /// Declare a required feature.
+    pub fn new(key: impl Into<String>, value_type: ValueType, count: ValueCount) -> Self {
+        Self {
+            key: key.into(),
+            value_type,
+            count,
+            required: true,
+        }
+    }
+
+    /// Check the feature of the example declared by the spec.
+    pub fn check(&self, example: &Example) -> Result<()> {
+        let feature = example
+            .features
+            .as_ref()
+            .and_then(|features| features.feature.get(&self.key));
+        let feature = match feature {
+            Some(feature) => feature,
+            None if self.required => {
+                return Err(Error::conversion(format!(
+                    "the required feature '{}' is missing",
+                    self.key
+                )))
+            }
+            None => return Ok(()),
+        };
+
+        let found = ValueType::of(feature);
+        if found != Some(self.value_type) {
+            return Err(Error::conversion(format!(
+                "the feature '{}' expects {:?} values, but found {:?}",
+                self.key, self.value_type, found
+            )));
+        }
+        let count = match &feature.kind {
+            Some(Kind::BytesList(list)) => list.value.len(),
+            Some(Kind::FloatList(list)) => list.value.len(),
+            Some(Kind::Int64List(list)) => list.value.len(),
+            None => 0,
+        };
+        if !self.count.accepts(count) {
+            return Err(Error::conversion(format!(
+                "the feature '{}' expects {:?} values, but found {}",
+                self.key, self.count, count
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Check the example against the specs, failing with the first nonconforming feature.
+pub fn validate(specs: &[FeatureSpec], example: &Example) -> Result<()> {
+    specs.iter().try_for_each(|spec| spec.check(example))
+}
+
+/// The source of the specs of a [ValidatingWriter](crate::ValidatingWriter).
+#[derive(Clone)]
+pub enum SchemaSource {
+    /// The specs themselves.
+    Inline(Vec<FeatureSpec>),
+    /// A JSON or TOML file loaded by [load_specs].
+    File(PathBuf),
+    /// The schema id fetched from the provider, which also receives the usage.
+    Provider(Arc<dyn SchemaProvider>, String),
+}
+
+impl SchemaSource {
+    /// Resolve the specs.
+    ///
+    /// A failure of the provider results in [Error::SchemaUnavailable] wrapping it.
+    pub fn resolve(&self) -> Result<Vec<FeatureSpec>> {
+        match self {
+            Self::Inline(specs) => Ok(specs.clone()),
+            Self::File(path) => load_specs(path),
+            Self::Provider(provider, schema_id) => {
+                provider
+                    .fetch(schema_id)
+                    .map_err(|source| Error::SchemaUnavailable {
+                        schema_id: schema_id.clone(),
+                        source: Box::new(source),
+                    })
+            }
+        }
+    }
+}
+
+impl fmt::Debug for SchemaSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Inline(specs) => f.debug_tuple("Inline").field(specs).finish(),
+            Self::File(path) => f.debug_tuple("File").field(path).finish(),
+            Self::Provider(_, schema_id) => f.debug_tuple("Provider").field(schema_id).finish(),
+        }
+    }
+}
+
+/// The number of examples a writer checked against a schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SchemaUsage {
+    /// The number of conforming examples, which were written.
+    pub num_accepted: u64,
+    /// The number of nonconforming examples, which were rejected.
+    pub num_rejected: u64,
+}
+
+/// A source of schemas by id, such as the client of a schema registry.
+pub trait SchemaProvider: Send + Sync {
+    /// Fetch the specs of the schema.
+    fn fetch(&self, schema_id: &str) -> Result<Vec<FeatureSpec>>;
+
+    /// Receive the usage of the schema by a writer when the writer finishes.
+    ///
+    /// It does nothing by default.
+    fn report_usage(&self, schema_id: &str, usage: &SchemaUsage) -> Result<()> {
+        let _ = (schema_id, usage);
+        Ok(())
+    }
+}
+
+/// A provider of schemas held in memory, accumulating the reported usage.
+#[derive(Debug, Default)]
+pub struct StaticSchemaProvider {
+    schemas: HashMap<String, Vec<FeatureSpec>>,
+    usage: Mutex<HashMap<String, SchemaUsage>>,
+}
+
+impl StaticSchemaProvider {
+    /// Build a provider without schemas.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the schema of the id, replacing an existing one.
+    pub fn with_schema(mut self, schema_id: impl Into<String>, specs: Vec<FeatureSpec>) -> Self {
+        self.schemas.insert(schema_id.into(), specs);
+        self
+    }
+
+    /// The usage of the schema summed over the reports, or `None` if never reported.
+    pub fn usage(&self, schema_id: &str) -> Option<SchemaUsage> {
+        self.usage.lock().unwrap().get(schema_id).copied()
+    }
+}
+
+impl SchemaProvider for StaticSchemaProvider {
+    fn fetch(&self, schema_id: &str) -> Result<Vec<FeatureSpec>> {
+        self.schemas
+            .get(schema_id)
+            .cloned()
+            .ok_or_else(|| Error::invalid_argument(format!("unknown schema '{}'", schema_id)))
+    }
+
+    fn report_usage(&self, schema_id: &str, usage: &SchemaUsage) -> Result<()> {
+        let mut reported = self.usage.lock().unwrap();
+        let total = reported.entry(schema_id.to_string()).or_default();
+        total.num_accepted += usage.num_accepted;
+        total.num_rejected += usage.num_rejected;
+        Ok(())
+    }
+}
+
+/// A provider of schemas stored as files named `<schema_id>.json` or
+/// `<schema_id>.toml` in a directory.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FileSchemaProvider {
+    dir: PathBuf,
+}
+
+impl FileSchemaProvider {
+    /// Build a provider reading the directory.
+    pub fn new<P>(dir: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl SchemaProvider for FileSchemaProvider {
+    fn fetch(&self, schema_id: &str) -> Result<Vec<FeatureSpec>> {
+        ensure_argument!(
+            Path::new(schema_id)
+                .file_name()
+                .and_then(|name| name.to_str())
+                == Some(schema_id),
+            "the schema id '{}' is not a file name",
+            schema_id
+        );
+        ["json", "toml"]
+            .into_iter()
+            .map(|extension| self.dir.join(format!("{}.{}", schema_id, extension)))
+            .find(|path| path.is_file())
+            .map(load_specs)
+            .unwrap_or_else(|| {
+                let error = std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("no {0}.json or {0}.toml", schema_id),
+                );
+                Err(Error::from_io_with_context(error, &self.dir, None))
+            })
+    }
+}
+
+/// Load specs from a JSON or TOML file, by the file extension.
+///
+/// It requires the `schema-file` feature, or fails with [Error::Unsupported].
+pub fn load_specs<P>(path: P) -> Result<Vec<FeatureSpec>>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+
+    #[cfg(feature = "schema-file")]
+    {
+        #[derive(serde::Deserialize)]
+        struct SchemaFile {
+            features: Vec<FeatureSpec>,
+        }
+
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| Error::from_io_with_context(err, path, None))?;
+        let invalid = |err: &dyn fmt::Display| {
+            Error::conversion(format!("invalid schema file {}: {}", path.display(), err))
+        };
+        let file: SchemaFile = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => serde_json::from_str(&text).map_err(|err| invalid(&err))?,
+            Some("toml") => toml::from_str(&text).map_err(|err| invalid(&err))?,
+            _ => {
+                return Err(Error::invalid_argument(format!(
+                    "schema file {} is neither .json nor .toml",
+                    path.display()
+                )))
+            }
+        };
+        Ok(file.features)
+    }
+
+    #[cfg(not(feature = "schema-file"))]
+    {
+        let _ = path;
+        Err(Error::Unsupported {
+            capability: "schema files".into(),
+            feature: Some("schema-file"),
+        })
+    }
+}
@@ -0,0 +1,1268 @@
+//! Convert [serde](https://crates.io/crates/serde) types to and from examples.
+//!
+//! A struct is converted to an [Example] with a feature per field, keyed by the field
+//! name. The field values are mapped to features as below.
+//!
+//! | Field type                                        | Feature                          |
+//! |---------------------------------------------------|----------------------------------|
+//! | `bool`, `i8` to `i64`, `u16` to `u64`             | `Int64List` with one value       |
+//! | `f32`, `f64`                                      | `FloatList` with one value       |
+//! | `char`, [String], unit enum variants              | `BytesList` with one value       |
+//! | `u8` sequences, such as `Vec<u8>`                 | `BytesList` with one value       |
+//! | Sequences, tuples and arrays of the types above   | The list of the values           |
+//! | [Option]                                          | The value, or no feature if `None` |
+//!
+//! `u8` sequences are stored as with [Feature::from_u8s], so sequences of them, such
+//! as `Vec<Vec<u8>>`, are `BytesList` features with a value per sequence. `u64` values
+//! beyond [i64::MAX] and `f64` values are converted with a failure and a precision loss
+//! respectively. Empty sequences are features without a kind, since their element type
+//! is unknown.
+//!
+//! Nested structs, maps and enum variants with data are not supported and fail the
+//! conversion. Fields can be left out by `#[serde(skip)]`, which takes their default
+//! value when converting back. Features of an example not matching a field are
+//! ignored, and missing features convert to `None` for [Option] fields.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use serde::{Deserialize, Serialize};
+//! use tfrecord::serde_example;
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Sample {
+//!     label: i64,
+//!     name: String,
+//!     embedding: Vec<f32>,
+//!     note: Option<String>,
+//!     #[serde(skip)]
+//!     cached: u32,
+//! }
+//!
+//! let sample = Sample {
+//!     label: 3,
+//!     name: "cat".into(),
+//!     embedding: vec![0.5, 1.5],
+//!     note: None,
+//!     cached: 0,
+//! };
+//! let example = serde_example::to_example(&sample)?;
+//! assert_eq!(example.get_i64s("label")?, &[3]);
+//! assert_eq!(example.get_f32s("embedding")?, &[0.5, 1.5]);
+//! assert_eq!(example.try_get_f32s("note")?, None);
+//!
+//! let converted: Sample = serde_example::from_example(&example)?;
+//! assert_eq!(converted, sample);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    protobuf::{feature::Kind, Example, Feature, Features},
+};
+use serde::{
+    de::{self, value::SeqDeserializer, DeserializeOwned, IntoDeserializer, Visitor},
+    ser::{self, Impossible},
+    Serialize,
+};
+use std::collections::HashMap;
+
+/// Convert a struct to an example.
+pub fn to_example<T>(value: &T) -> Result<Example>
+where
+    T: Serialize + ?Sized,
+{
+    value.serialize(ExampleSerializer)
+}
+
+/// Convert an example to a struct.
+pub fn from_example<T>(example: &Example) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(ExampleDeserializer { example })
+}
+
+fn unsupported(key: &str, what: &str) -> Error {
+    Error::conversion(format!(
+        "the field '{}' is {}, which is not supported",
+        key, what
+    ))
+}
+
+/// Define serializer methods failing with an error built from `self`.
+macro_rules! reject_serialize {
+    ($error:ident; $($method:ident($($arg:ty),*) -> $ok:ty, $what:literal;)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ok> {
+                Err(self.$error($what))
+            }
+        )*
+    };
+}
+
+/// Define deserializer methods forwarding to the deserializer returned by `self.$to()`.
+macro_rules! forward_deserialize {
+    ($to:ident; $($method:ident)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value>
+            where
+                V: Visitor<'de>,
+            {
+                self.$to()?.$method(visitor)
+            }
+        )*
+    };
+}
+
+// serialization
+
+struct ExampleSerializer;
+
+impl ExampleSerializer {
+    fn not_struct(&self, what: &str) -> Error {
+        Error::conversion(format!(
+            "only structs are converted to examples, but found {}",
+            what
+        ))
+    }
+}
+
+impl ser::Serializer for ExampleSerializer {
+    type Ok = Example;
+    type Error = Error;
+    type SerializeSeq = Impossible<Example, Error>;
+    type SerializeTuple = Impossible<Example, Error>;
+    type SerializeTupleStruct = Impossible<Example, Error>;
+    type SerializeTupleVariant = Impossible<Example, Error>;
+    type SerializeMap = Impossible<Example, Error>;
+    type SerializeStruct = StructSerializer;
+    type SerializeStructVariant = Impossible<Example, Error>;
+
+    reject_serialize! {
+        not_struct;
+        serialize_bool(bool) -> Example, "a bool";
+        serialize_i8(i8) -> Example, "an integer";
+        serialize_i16(i16) -> Example, "an integer";
+        serialize_i32(i32) -> Example, "an integer";
+        serialize_i64(i64) -> Example, "an integer";
+        serialize_u8(u8) -> Example, "an integer";
+        serialize_u16(u16) -> Example, "an integer";
+        serialize_u32(u32) -> Example, "an integer";
+        serialize_u64(u64) -> Example, "an integer";
+        serialize_f32(f32) -> Example, "a float";
+        serialize_f64(f64) -> Example, "a float";
+        serialize_char(char) -> Example, "a char";
+        serialize_str(&str) -> Example, "a string";
+        serialize_bytes(&[u8]) -> Example, "bytes";
+        serialize_none() -> Example, "an option";
+        serialize_unit() -> Example, "a unit";
+        serialize_unit_struct(&'static str) -> Example, "a unit struct";
+        serialize_unit_variant(&'static str, u32, &'static str) -> Example, "an enum";
+        serialize_seq(Option<usize>) -> Self::SerializeSeq, "a sequence";
+        serialize_tuple(usize) -> Self::SerializeTuple, "a tuple";
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct, "a tuple struct";
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant, "an enum";
+        serialize_map(Option<usize>) -> Self::SerializeMap, "a map";
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant, "an enum";
+    }
+
+    fn serialize_some<T>(self, _: &T) -> Result<Example>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(self.not_struct("an option"))
+    }
+
+    fn serialize_newtype_struct<T>(self, _: &'static str, value: &T) -> Result<Example>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Example>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(self.not_struct("an enum"))
+    }
+
+    fn serialize_struct(self, _: &'static str, len: usize) -> Result<StructSerializer> {
+        Ok(StructSerializer {
+            features: HashMap::with_capacity(len),
+        })
+    }
+}
+
+struct StructSerializer {
+    features: HashMap<String, Feature>,
+}
+
+impl ser::SerializeStruct for StructSerializer {
+    type Ok = Example;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        if let Some(feature) = value.serialize(FieldSerializer { key })? {
+            self.features.insert(key.to_owned(), feature);
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<Example> {
+        Ok(Example {
+            features: Some(Features {
+                feature: self.features,
+            }),
+        })
+    }
+}
+
+/// Serialize a field value to a feature, or to `None` for a missing optional value.
+struct FieldSerializer {
+    key: &'static str,
+}
+
+impl FieldSerializer {
+    fn unsupported(&self, what: &str) -> Error {
+        unsupported(self.key, what)
+    }
+
+    fn int(v: i64) -> Result<Option<Feature>> {
+        Ok(Some(Feature::from_i64_list(vec![v])))
+    }
+
+    fn float(v: f32) -> Result<Option<Feature>> {
+        Ok(Some(Feature::from_f32_list(vec![v])))
+    }
+
+    fn bytes(v: &[u8]) -> Result<Option<Feature>> {
+        Ok(Some(Feature::from_u8s(v)))
+    }
+}
+
+impl ser::Serializer for FieldSerializer {
+    type Ok = Option<Feature>;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = Impossible<Option<Feature>, Error>;
+    type SerializeMap = Impossible<Option<Feature>, Error>;
+    type SerializeStruct = Impossible<Option<Feature>, Error>;
+    type SerializeStructVariant = Impossible<Option<Feature>, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
+        Self::int(v as i64)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
+        Self::int(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
+        Self::int(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
+        Self::int(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
+        Self::int(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
+        Self::int(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
+        Self::int(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
+        Self::int(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
+        Self::int(to_i64(self.key, v)?)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
+        Self::float(v)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
+        Self::float(v as f32)
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok> {
+        Self::bytes(v.encode_utf8(&mut [0; 4]).as_bytes())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok> {
+        Self::bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
+        Self::bytes(v)
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok> {
+        Self::bytes(variant.as_bytes())
+    }
+
+    fn serialize_newtype_struct<T>(self, _: &'static str, value: &T) -> Result<Self::Ok>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Self::Ok>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(self.unsupported("an enum variant with data"))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer> {
+        Ok(SeqSerializer::new(self.key, len.unwrap_or(0)))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer> {
+        Ok(SeqSerializer::new(self.key, len))
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, len: usize) -> Result<SeqSerializer> {
+        Ok(SeqSerializer::new(self.key, len))
+    }
+
+    reject_serialize! {
+        unsupported;
+        serialize_unit() -> Self::Ok, "a unit";
+        serialize_unit_struct(&'static str) -> Self::Ok, "a unit struct";
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant, "an enum variant with data";
+        serialize_map(Option<usize>) -> Self::SerializeMap, "a map";
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct, "a nested struct";
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant, "an enum variant with data";
+    }
+}
+
+fn to_i64(key: &str, v: u64) -> Result<i64> {
+    i64::try_from(v).map_err(|_| {
+        Error::conversion(format!(
+            "the value {} of the field '{}' exceeds the range of int64",
+            v, key
+        ))
+    })
+}
+
+/// A value in a sequence field.
+enum Element {
+    Int(i64),
+    Float(f32),
+    Byte(u8),
+    Bytes(Vec<u8>),
+}
+
+/// Serialize the values of a sequence field to a list feature.
+struct SeqSerializer {
+    key: &'static str,
+    elements: Vec<Element>,
+}
+
+impl SeqSerializer {
+    fn new(key: &'static str, len: usize) -> Self {
+        Self {
+            key,
+            elements: Vec::with_capacity(len),
+        }
+    }
+
+    fn push<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        let element = value.serialize(ElementSerializer { key: self.key })?;
+        self.elements.push(element);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Option<Feature>> {
+        let Self { key, elements } = self;
+        let mixed = || {
+            Error::conversion(format!(
+                "the sequence of the field '{}' mixes value types",
+                key
+            ))
+        };
+        macro_rules! collect {
+            ($variant:ident) => {
+                elements
+                    .into_iter()
+                    .map(|element| match element {
+                        Element::$variant(value) => Ok(value),
+                        _ => Err(mixed()),
+                    })
+                    .collect::<Result<Vec<_>>>()?
+            };
+        }
+
+        let feature = match elements.first() {
+            None => Feature::empty(),
+            Some(Element::Int(_)) => Feature::from_i64_list(collect!(Int)),
+            Some(Element::Float(_)) => Feature::from_f32_list(collect!(Float)),
+            Some(Element::Byte(_)) => Feature::from_bytes_list(vec![collect!(Byte)]),
+            Some(Element::Bytes(_)) => Feature::from_bytes_list(collect!(Bytes)),
+        };
+        Ok(Some(feature))
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Option<Feature>;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Option<Feature>;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Option<Feature>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+/// Serialize a value in a sequence field.
+struct ElementSerializer {
+    key: &'static str,
+}
+
+impl ElementSerializer {
+    fn unsupported(&self, what: &str) -> Error {
+        unsupported(self.key, what)
+    }
+}
+
+impl ser::Serializer for ElementSerializer {
+    type Ok = Element;
+    type Error = Error;
+    type SerializeSeq = ByteSeqSerializer;
+    type SerializeTuple = ByteSeqSerializer;
+    type SerializeTupleStruct = Impossible<Element, Error>;
+    type SerializeTupleVariant = Impossible<Element, Error>;
+    type SerializeMap = Impossible<Element, Error>;
+    type SerializeStruct = Impossible<Element, Error>;
+    type SerializeStructVariant = Impossible<Element, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Element> {
+        Ok(Element::Int(v as i64))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Element> {
+        Ok(Element::Int(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Element> {
+        Ok(Element::Int(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Element> {
+        Ok(Element::Int(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Element> {
+        Ok(Element::Int(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Element> {
+        Ok(Element::Byte(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Element> {
+        Ok(Element::Int(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Element> {
+        Ok(Element::Int(v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Element> {
+        Ok(Element::Int(to_i64(self.key, v)?))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Element> {
+        Ok(Element::Float(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Element> {
+        Ok(Element::Float(v as f32))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Element> {
+        Ok(Element::Bytes(v.to_string().into_bytes()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Element> {
+        Ok(Element::Bytes(v.as_bytes().to_vec()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Element> {
+        Ok(Element::Bytes(v.to_vec()))
+    }
+
+    fn serialize_some<T>(self, _: &T) -> Result<Element>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(self.unsupported("a sequence of options"))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Element> {
+        Ok(Element::Bytes(variant.as_bytes().to_vec()))
+    }
+
+    fn serialize_newtype_struct<T>(self, _: &'static str, value: &T) -> Result<Element>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Element>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(self.unsupported("a sequence of enum variants with data"))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<ByteSeqSerializer> {
+        Ok(ByteSeqSerializer::new(self.key, len.unwrap_or(0)))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<ByteSeqSerializer> {
+        Ok(ByteSeqSerializer::new(self.key, len))
+    }
+
+    reject_serialize! {
+        unsupported;
+        serialize_none() -> Element, "a sequence of options";
+        serialize_unit() -> Element, "a sequence of units";
+        serialize_unit_struct(&'static str) -> Element, "a sequence of unit structs";
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct, "a nested sequence";
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant, "a sequence of enum variants with data";
+        serialize_map(Option<usize>) -> Self::SerializeMap, "a sequence of maps";
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct, "a sequence of structs";
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant, "a sequence of enum variants with data";
+    }
+}
+
+/// Serialize a `u8` sequence nested in a sequence field to a bytes value.
+struct ByteSeqSerializer {
+    key: &'static str,
+    bytes: Vec<u8>,
+}
+
+impl ByteSeqSerializer {
+    fn new(key: &'static str, len: usize) -> Self {
+        Self {
+            key,
+            bytes: Vec::with_capacity(len),
+        }
+    }
+
+    fn push<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        match value.serialize(ElementSerializer { key: self.key })? {
+            Element::Byte(byte) => {
+                self.bytes.push(byte);
+                Ok(())
+            }
+            _ => Err(unsupported(self.key, "a nested sequence not of u8")),
+        }
+    }
+}
+
+impl ser::SerializeSeq for ByteSeqSerializer {
+    type Ok = Element;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Element> {
+        Ok(Element::Bytes(self.bytes))
+    }
+}
+
+impl ser::SerializeTuple for ByteSeqSerializer {
+    type Ok = Element;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Element> {
+        Ok(Element::Bytes(self.bytes))
+    }
+}
+
+// deserialization
+
+struct ExampleDeserializer<'a> {
+    example: &'a Example,
+}
+
+impl<'de> de::Deserializer<'de> for ExampleDeserializer<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, _: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::conversion("examples are only converted to structs"))
+    }
+
+    fn deserialize_newtype_struct<V>(self, _: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let features = self
+            .example
+            .features
+            .as_ref()
+            .map(|features| &features.feature);
+        visitor.visit_map(StructAccess {
+            features,
+            fields: fields.iter(),
+            next: None,
+        })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// Visit the features of the fields present in the example.
+struct StructAccess<'a> {
+    features: Option<&'a HashMap<String, Feature>>,
+    fields: std::slice::Iter<'static, &'static str>,
+    next: Option<(&'static str, &'a Feature)>,
+}
+
+impl<'de> de::MapAccess<'de> for StructAccess<'_> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        let Some(features) = self.features else {
+            return Ok(None);
+        };
+        for &key in self.fields.by_ref() {
+            if let Some(feature) = features.get(key) {
+                self.next = Some((key, feature));
+                return seed.deserialize(key.into_deserializer()).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let (key, feature) = self
+            .next
+            .take()
+            .expect("next_value_seed is called after next_key_seed");
+        seed.deserialize(FieldDeserializer { key, feature })
+    }
+}
+
+/// Deserialize a field value from a feature.
+struct FieldDeserializer<'a> {
+    key: &'a str,
+    feature: &'a Feature,
+}
+
+impl<'a> FieldDeserializer<'a> {
+    fn num_values(&self) -> usize {
+        match &self.feature.kind {
+            Some(Kind::BytesList(list)) => list.value.len(),
+            Some(Kind::FloatList(list)) => list.value.len(),
+            Some(Kind::Int64List(list)) => list.value.len(),
+            None => 0,
+        }
+    }
+
+    /// The only value of the feature.
+    fn single(&self) -> Result<Value<'a>> {
+        let value = match &self.feature.kind {
+            Some(Kind::BytesList(list)) if list.value.len() == 1 => Value::Bytes(&list.value[0]),
+            Some(Kind::FloatList(list)) if list.value.len() == 1 => Value::Float(list.value[0]),
+            Some(Kind::Int64List(list)) if list.value.len() == 1 => Value::Int(list.value[0]),
+            _ => {
+                return Err(Error::conversion(format!(
+                    "the field '{}' expects a feature with exactly one value, but found {} values",
+                    self.key,
+                    self.num_values()
+                )))
+            }
+        };
+        Ok(value)
+    }
+
+    fn unsupported(&self, what: &str) -> Error {
+        unsupported(self.key, what)
+    }
+}
+
+impl<'de> de::Deserializer<'de> for FieldDeserializer<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if self.num_values() == 1 {
+            self.single()?.deserialize_any(visitor)
+        } else {
+            self.deserialize_seq(visitor)
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match &self.feature.kind {
+            Some(Kind::BytesList(list)) => {
+                let mut access = BytesSeqAccess {
+                    key: self.key,
+                    values: &list.value,
+                    mode: BytesMode::Unknown,
+                };
+                let value = visitor.visit_seq(&mut access)?;
+                access.end()?;
+                Ok(value)
+            }
+            Some(Kind::FloatList(list)) => {
+                visit_values(list.value.iter().map(|&v| Value::Float(v)), visitor)
+            }
+            Some(Kind::Int64List(list)) => {
+                visit_values(list.value.iter().map(|&v| Value::Int(v)), visitor)
+            }
+            None => visit_values(std::iter::empty(), visitor),
+        }
+    }
+
+    fn deserialize_tuple<V>(self, _: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(self, _: &'static str, _: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.single()?.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_unit<V>(self, _: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(self.unsupported("a unit"))
+    }
+
+    fn deserialize_unit_struct<V>(self, _: &'static str, _: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(self.unsupported("a unit struct"))
+    }
+
+    fn deserialize_map<V>(self, _: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(self.unsupported("a map"))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        _: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(self.unsupported("a nested struct"))
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    forward_deserialize! {
+        single;
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_f32
+        deserialize_f64 deserialize_char deserialize_str deserialize_string
+        deserialize_bytes deserialize_byte_buf deserialize_identifier
+    }
+}
+
+fn visit_values<'de, 'a, I, V>(values: I, visitor: V) -> Result<V::Value>
+where
+    I: Iterator<Item = Value<'a>>,
+    V: Visitor<'de>,
+{
+    let mut access = SeqDeserializer::new(values);
+    let value = visitor.visit_seq(&mut access)?;
+    access.end()?;
+    Ok(value)
+}
+
+/// A value of a feature.
+#[derive(Clone, Copy)]
+enum Value<'a> {
+    Int(i64),
+    Float(f32),
+    Bytes(&'a [u8]),
+}
+
+impl<'a> Value<'a> {
+    fn as_str(self) -> Result<&'a str> {
+        match self {
+            Value::Bytes(bytes) => std::str::from_utf8(bytes)
+                .map_err(|err| Error::conversion(format!("invalid UTF-8 string: {}", err))),
+            _ => Err(self.mismatch("a string")),
+        }
+    }
+
+    fn mismatch(self, expect: &str) -> Error {
+        let found = match self {
+            Value::Int(_) => "an Int64List value",
+            Value::Float(_) => "a FloatList value",
+            Value::Bytes(_) => "a BytesList value",
+        };
+        Error::conversion(format!("expect {}, but found {}", expect, found))
+    }
+}
+
+impl<'de, 'a> IntoDeserializer<'de, Error> for Value<'a> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Value<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Int(v) => visitor.visit_i64(v),
+            Value::Float(v) => visitor.visit_f32(v),
+            Value::Bytes(v) => visitor.visit_bytes(v),
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Int(0) => visitor.visit_bool(false),
+            Value::Int(1) => visitor.visit_bool(true),
+            _ => Err(self.mismatch("a bool of 0 or 1")),
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_str(self.as_str()?)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Bytes(bytes) => {
+                let mut access = SeqDeserializer::<_, Error>::new(bytes.iter().copied());
+                let value = visitor.visit_seq(&mut access)?;
+                access.end()?;
+                Ok(value)
+            }
+            _ => Err(self.mismatch("a u8 sequence")),
+        }
+    }
+
+    fn deserialize_tuple<V>(self, _: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let variant: de::value::StrDeserializer<'_, Error> = self.as_str()?.into_deserializer();
+        visitor.visit_enum(variant)
+    }
+
+    serde::forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64
+        bytes byte_buf option unit unit_struct
+        tuple_struct map struct ignored_any
+    }
+}
+
+/// How the values of a `BytesList` feature are visited as a sequence, decided by the
+/// first element.
+enum BytesMode {
+    Unknown,
+    /// The values are the elements, such as strings.
+    Values {
+        next: usize,
+    },
+    /// The bytes of the only value are the elements of a `u8` sequence.
+    Flat {
+        next: usize,
+    },
+}
+
+struct BytesSeqAccess<'a> {
+    key: &'a str,
+    values: &'a [Vec<u8>],
+    mode: BytesMode,
+}
+
+impl BytesSeqAccess<'_> {
+    fn end(&self) -> Result<()> {
+        let (num_visited, num_elements) = match self.mode {
+            BytesMode::Unknown => (0, self.values.len()),
+            BytesMode::Values { next } => (next, self.values.len()),
+            BytesMode::Flat { next } => (next, self.values[0].len()),
+        };
+        if num_visited < num_elements {
+            return Err(Error::conversion(format!(
+                "the field '{}' expects {} elements, but found {}",
+                self.key, num_visited, num_elements
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for BytesSeqAccess<'_> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.mode {
+            BytesMode::Unknown if self.values.is_empty() => Ok(None),
+            BytesMode::Unknown => seed.deserialize(FirstBytesElement { seq: self }).map(Some),
+            BytesMode::Values { next } => match self.values.get(next) {
+                Some(value) => {
+                    self.mode = BytesMode::Values { next: next + 1 };
+                    seed.deserialize(Value::Bytes(value)).map(Some)
+                }
+                None => Ok(None),
+            },
+            BytesMode::Flat { next } => match self.values[0].get(next) {
+                Some(&byte) => {
+                    self.mode = BytesMode::Flat { next: next + 1 };
+                    seed.deserialize(byte.into_deserializer()).map(Some)
+                }
+                None => Ok(None),
+            },
+        }
+    }
+}
+
+/// The first element of a `BytesList` sequence, which decides how the sequence is
+/// visited by the requested type.
+struct FirstBytesElement<'s, 'a> {
+    seq: &'s mut BytesSeqAccess<'a>,
+}
+
+impl<'a> FirstBytesElement<'_, 'a> {
+    /// Visit the values as the elements.
+    fn first_value(self) -> Result<Value<'a>> {
+        self.seq.mode = BytesMode::Values { next: 1 };
+        Ok(Value::Bytes(&self.seq.values[0]))
+    }
+}
+
+impl<'de> de::Deserializer<'de> for FirstBytesElement<'_, '_> {
+    type Error = Error;
+
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.seq.values {
+            [value] if !value.is_empty() => {
+                self.seq.mode = BytesMode::Flat { next: 1 };
+                visitor.visit_u8(value[0])
+            }
+            _ => Err(Error::conversion(format!(
+                "the u8 values of the field '{}' must be a BytesList with exactly one value",
+                self.seq.key
+            ))),
+        }
+    }
+
+    forward_deserialize! {
+        first_value;
+        deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32
+        deserialize_i64 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_f32
+        deserialize_f64 deserialize_char deserialize_str deserialize_string
+        deserialize_bytes deserialize_byte_buf deserialize_option deserialize_unit
+        deserialize_seq deserialize_map deserialize_identifier deserialize_ignored_any
+    }
+
+    fn deserialize_unit_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.first_value()?.deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.first_value()?
+            .deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.first_value()?.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.first_value()?
+            .deserialize_tuple_struct(name, len, visitor)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.first_value()?
+            .deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.first_value()?
+            .deserialize_enum(name, variants, visitor)
+    }
+}
@@ -0,0 +1,152 @@
+//! Sharded file names in the conventions of TensorFlow.
+//!
+//! The shard `index` of `num_shards` files with the base name `base` is named
+//! `<base>-<index>-of-<num_shards>`, where each number is zero-padded to at least five
+//! digits as by `%05d`. A number needing more digits is written in full, so the shards of
+//! 100000 files are named `train-00000-of-100000` to `train-99999-of-100000`.
+//!
+//! The shorthand spec `<base>@<num_shards>`, such as `train@10`, refers to all the shards
+//! at once. The base may contain directories, such as `data/train@10`.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use std::path::PathBuf;
+//! use tfrecord::shardspec;
+//!
+//! let spec = shardspec::parse_spec("train@10")?;
+//! let paths = spec.paths("data");
+//! assert_eq!(paths.len(), 10);
+//! assert_eq!(paths[3], PathBuf::from("data/train-00003-of-00010"));
+//!
+//! assert_eq!(
+//!     shardspec::parse_filename("train-00003-of-00010"),
+//!     Some(("train".to_string(), 3, 10))
+//! );
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{ensure_argument, Error, Result};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// The spec of a sharded file set, written as `<base>@<num_shards>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShardSpec {
+    /// The base name of the files, which may contain directories.
+    pub base: String,
+    /// The number of files.
+    pub num_shards: usize,
+}
+
+impl ShardSpec {
+    /// The name of the shard, which is a path if the base contains directories.
+    pub fn file_name(&self, index: usize) -> String {
+        shard_name(&self.base, index, self.num_shards)
+    }
+
+    /// The paths of all shards under the directory, in the order of shards.
+    pub fn paths<P>(&self, base_dir: P) -> Vec<PathBuf>
+    where
+        P: AsRef<Path>,
+    {
+        let base_dir = base_dir.as_ref();
+        (0..self.num_shards)
+            .map(|index| base_dir.join(self.file_name(index)))
+            .collect()
+    }
+
+    /// The paths of the existing shards under the directory, in the order of shards.
+    ///
+    /// It fails with [Error::MissingShards] listing the missing ones, unless
+    /// [allow_missing](ShardSpecOptions::allow_missing) is set.
+    pub fn resolve<P>(&self, base_dir: P, options: ShardSpecOptions) -> Result<Vec<PathBuf>>
+    where
+        P: AsRef<Path>,
+    {
+        let ShardSpecOptions { allow_missing } = options;
+        let (existing, missing): (Vec<_>, Vec<_>) = self
+            .paths(base_dir)
+            .into_iter()
+            .partition(|path| path.is_file());
+        if !missing.is_empty() && !allow_missing {
+            return Err(Error::MissingShards {
+                spec: self.to_string(),
+                num_shards: self.num_shards,
+                missing,
+            });
+        }
+        Ok(existing)
+    }
+}
+
+impl fmt::Display for ShardSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.base, self.num_shards)
+    }
+}
+
+impl FromStr for ShardSpec {
+    type Err = Error;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        parse_spec(spec)
+    }
+}
+
+/// Options for resolving a [ShardSpec] to existing files.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ShardSpecOptions {
+    /// If set, missing shards are skipped instead of failing the whole spec.
+    pub allow_missing: bool,
+}
+
+/// Parse a spec written as `<base>@<num_shards>`, such as `train@10`.
+pub fn parse_spec(spec: &str) -> Result<ShardSpec> {
+    let (base, count) = spec
+        .rsplit_once('@')
+        .ok_or_else(|| Error::invalid_argument(format!("'{}' has no '@' shard count", spec)))?;
+    ensure_argument!(!base.is_empty(), "'{}' has an empty base name", spec);
+    let num_shards = parse_digits(count)
+        .ok_or_else(|| Error::invalid_argument(format!("'{}' has an invalid shard count", spec)))?;
+    ensure_argument!(num_shards > 0, "'{}' has no shards", spec);
+    Ok(ShardSpec {
+        base: base.to_string(),
+        num_shards,
+    })
+}
+
+/// The name of the shard `index` of `num_shards` files, `<base>-<index>-of-<num_shards>`.
+///
+/// Writers naming sharded outputs use this function, so the names always parse back by
+/// [parse_filename].
+pub fn shard_name(base: &str, index: usize, num_shards: usize) -> String {
+    format!("{}-{:05}-of-{:05}", base, index, num_shards)
+}
+
+/// Parse a shard name into the base name, the index and the number of shards.
+///
+/// It returns `None` unless the name is exactly as generated by [shard_name] with the
+/// index less than the number of shards, so `train-3-of-10` and `train-00010-of-00010`
+/// are rejected.
+pub fn parse_filename(name: &str) -> Option<(String, usize, usize)> {
+    let (rest, total) = name.rsplit_once("-of-")?;
+    let (base, index) = rest.rsplit_once('-')?;
+    let index_value = parse_digits(index)?;
+    let total_value = parse_digits(total)?;
+    let is_canonical = !base.is_empty()
+        && index_value < total_value
+        && index == format!("{:05}", index_value)
+        && total == format!("{:05}", total_value);
+    is_canonical.then(|| (base.to_string(), index_value, total_value))
+}
+
+fn parse_digits(text: &str) -> Option<usize> {
+    if text.is_empty() || !text.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
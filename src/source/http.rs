@@ -0,0 +1,235 @@
+use super::RecordSource;
+use crate::error::{Error, Result};
+use futures::future::BoxFuture;
+use std::{
+    borrow::Cow,
+    fmt,
+    io::{self, Read as _},
+    ops::Range,
+    sync::Arc,
+    time::Duration,
+};
+
+/// The headers of a request computed from its `Range` header.
+pub(crate) type SignFn = dyn Fn(&str) -> Vec<(String, String)> + Send + Sync;
+
+/// Configuration for [HttpSource].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HttpSourceConfig {
+    /// The number of retries of a request after a transient failure, which is a failed
+    /// connection, a 5xx status or a 429 status.
+    pub max_retries: usize,
+    /// The delay before the first retry, which is doubled on each retry.
+    pub retry_delay: Duration,
+    /// The timeout of each request.
+    pub timeout: Option<Duration>,
+    /// The number of bytes of each range request.
+    pub block_size: usize,
+    /// Headers sent with every request, such as an authorization header.
+    pub headers: Vec<(String, String)>,
+}
+
+impl Default for HttpSourceConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            retry_delay: Duration::from_millis(100),
+            timeout: Some(Duration::from_secs(60)),
+            block_size: 8 << 20,
+            headers: vec![],
+        }
+    }
+}
+
+/// A record file served over HTTP(S), read by range requests.
+///
+/// Servers ignoring the `Range` header are supported by skipping the leading bytes of
+/// the response. The requests are blocking and run on blocking tasks.
+#[derive(Clone)]
+pub struct HttpSource {
+    url: String,
+    config: HttpSourceConfig,
+    agent: ureq::Agent,
+    sign: Option<Arc<SignFn>>,
+}
+
+impl fmt::Debug for HttpSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpSource")
+            .field("url", &self.url)
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl HttpSource {
+    pub fn new(url: impl Into<String>, config: HttpSourceConfig) -> Self {
+        let mut agent = ureq::AgentBuilder::new();
+        if let Some(timeout) = config.timeout {
+            agent = agent.timeout(timeout);
+        }
+        Self {
+            url: url.into(),
+            config,
+            agent: agent.build(),
+            sign: None,
+        }
+    }
+
+    /// Attach the headers computed by the function from the `Range` header of each
+    /// request.
+    pub(crate) fn with_sign(mut self, sign: Arc<SignFn>) -> Self {
+        self.sign = Some(sign);
+        self
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn config(&self) -> &HttpSourceConfig {
+        &self.config
+    }
+
+    /// Request the bytes in the inclusive range of the `Range` header, retrying on
+    /// transient failures. A 416 status is returned rather than failing.
+    fn get(&self, range: &str) -> Result<Fetched> {
+        let HttpSourceConfig {
+            max_retries,
+            retry_delay,
+            ref headers,
+            ..
+        } = self.config;
+
+        let mut attempt = 0;
+        loop {
+            let mut request = self.agent.get(&self.url).set("Range", range);
+            for (name, value) in headers {
+                request = request.set(name, value);
+            }
+            if let Some(sign) = &self.sign {
+                for (name, value) in sign(range) {
+                    request = request.set(&name, &value);
+                }
+            }
+
+            let result = match request.call() {
+                Ok(response) | Err(ureq::Error::Status(416, response)) => {
+                    Fetched::read(response).map_err(ureq::Error::from)
+                }
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok(fetched) => return Ok(fetched),
+                Err(err) if attempt < max_retries && is_transient(&err) => {
+                    std::thread::sleep(retry_delay.saturating_mul(1 << attempt.min(16)));
+                    attempt += 1;
+                }
+                Err(err) => {
+                    return Err(io::Error::other(format!("GET {}: {}", self.url, err)).into())
+                }
+            }
+        }
+    }
+
+    fn fetch_size(&self) -> Result<u64> {
+        let fetched = self.get("bytes=0-0")?;
+        match fetched.status {
+            // the Content-Range header is "bytes 0-0/<size>" or "bytes */<size>"
+            206 | 416 => fetched
+                .content_range
+                .as_deref()
+                .and_then(|range| range.rsplit_once('/'))
+                .and_then(|(_, size)| size.parse().ok())
+                .ok_or_else(|| self.invalid_response("missing the size in Content-Range")),
+            _ => Ok(fetched.body.len() as u64),
+        }
+    }
+
+    fn fetch_range(&self, range: Range<u64>) -> Result<Vec<u8>> {
+        if range.is_empty() {
+            return Ok(vec![]);
+        }
+        let len = range.end - range.start;
+        let fetched = self.get(&format!("bytes={}-{}", range.start, range.end - 1))?;
+        let mut body = match fetched.status {
+            206 => fetched.body,
+            416 => vec![],
+            // the range is ignored by the server
+            _ => fetched
+                .body
+                .get(range.start.min(usize::MAX as u64) as usize..)
+                .unwrap_or_default()
+                .to_vec(),
+        };
+        if body.len() as u64 > len {
+            return Err(self.invalid_response("more bytes than requested"));
+        }
+        body.truncate(len as usize);
+        Ok(body)
+    }
+
+    fn invalid_response(&self, desc: &str) -> Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("GET {}: invalid response: {}", self.url, desc),
+        )
+        .into()
+    }
+}
+
+impl RecordSource for HttpSource {
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.url)
+    }
+
+    fn size(&self) -> BoxFuture<'_, Result<u64>> {
+        let source = self.clone();
+        Box::pin(async_std::task::spawn_blocking(move || source.fetch_size()))
+    }
+
+    fn read_range(&self, range: Range<u64>) -> BoxFuture<'_, Result<Vec<u8>>> {
+        let source = self.clone();
+        Box::pin(async_std::task::spawn_blocking(move || {
+            source.fetch_range(range)
+        }))
+    }
+
+    fn block_size(&self) -> usize {
+        self.config.block_size
+    }
+}
+
+/// The parts of a response used by range reads.
+struct Fetched {
+    status: u16,
+    content_range: Option<String>,
+    body: Vec<u8>,
+}
+
+impl Fetched {
+    fn read(response: ureq::Response) -> io::Result<Self> {
+        let status = response.status();
+        let content_range = response.header("Content-Range").map(str::to_string);
+        let mut body = vec![];
+        response.into_reader().read_to_end(&mut body)?;
+        Ok(Self {
+            status,
+            content_range,
+            body,
+        })
+    }
+}
+
+fn is_transient(err: &ureq::Error) -> bool {
+    match err {
+        ureq::Error::Status(status, _) => *status == 429 || (500..600).contains(status),
+        ureq::Error::Transport(transport) => matches!(
+            transport.kind(),
+            ureq::ErrorKind::Dns
+                | ureq::ErrorKind::ConnectionFailed
+                | ureq::ErrorKind::Io
+                | ureq::ErrorKind::ProxyConnect
+        ),
+    }
+}
@@ -0,0 +1,410 @@
+//! Byte sources of record files beyond the local file system.
+//!
+//! A [RecordSource] reads byte ranges of a record file, such as an object in a remote
+//! store. The [SourceReader] adapts a source to [AsyncRead] and [AsyncSeek] by reading
+//! blocks of [block_size](RecordSource::block_size) bytes, so that sources are indexed by
+//! [load_sources_async](crate::indexer::load_sources_async) and streamed by
+//! [RecordStream::from_source](crate::RecordStream::from_source) as files are.
+//!
+//! Sources retry transient failures themselves. A [ConnectionLimit] bounds the number of
+//! concurrent requests across the sources it [limits](ConnectionLimit::limit).
+//!
+//! ```rust
+//! # async_std::task::block_on(async {
+//! use futures::stream::TryStreamExt as _;
+//! use std::sync::Arc;
+//! use tfrecord::{
+//!     indexer,
+//!     samples,
+//!     source::{ConnectionLimit, FileSource},
+//!     Example, ExampleStream,
+//! };
+//!
+//! let dataset = samples::tiny_dataset(2, 3)?;
+//! let limit = ConnectionLimit::new(4)?;
+//! let sources = dataset
+//!     .paths()
+//!     .iter()
+//!     .map(|path| limit.limit(FileSource::new(path)));
+//!
+//! let indexes: Vec<_> = indexer::load_sources_async(sources, Default::default())
+//!     .try_collect()
+//!     .await?;
+//! assert_eq!(indexes.len(), 6);
+//! let example: Example = indexes[4].load_async().await?;
+//! assert_eq!(example, samples::example(4));
+//!
+//! let source = Arc::new(FileSource::new(&dataset.paths()[0]));
+//! let examples: Vec<Example> = ExampleStream::from_source(source, Default::default())
+//!     .try_collect()
+//!     .await?;
+//! assert_eq!(examples.len(), 3);
+//! # tfrecord::Result::<()>::Ok(())
+//! # }).unwrap();
+//! ```
+
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "s3")]
+mod s3;
+
+#[cfg(feature = "http")]
+pub use http::*;
+#[cfg(feature = "s3")]
+pub use s3::*;
+
+use crate::{ensure_argument, error::Result};
+use async_std::channel::{self, Receiver, Sender};
+use futures::{
+    future::BoxFuture,
+    io::{AsyncRead, AsyncReadExt as _, AsyncSeek, AsyncSeekExt as _},
+    ready,
+};
+use std::{
+    borrow::Cow,
+    hash::{Hash, Hasher},
+    io::{self, SeekFrom},
+    ops::Range,
+    path::PathBuf,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+/// The default number of bytes of each read by [SourceReader].
+pub const DEFAULT_BLOCK_SIZE: usize = 1 << 20;
+
+/// A byte source of a record file read by ranges.
+pub trait RecordSource
+where
+    Self: 'static + Send + Sync,
+{
+    /// The name of the source, such as its path or URL, which is attached to errors.
+    fn name(&self) -> Cow<'_, str>;
+
+    /// The size of the source in bytes.
+    fn size(&self) -> BoxFuture<'_, Result<u64>>;
+
+    /// Read the bytes in the range. Fewer bytes are returned only at the end of the
+    /// source, and none beyond the end.
+    fn read_range(&self, range: Range<u64>) -> BoxFuture<'_, Result<Vec<u8>>>;
+
+    /// The number of bytes of each read by [SourceReader].
+    fn block_size(&self) -> usize {
+        DEFAULT_BLOCK_SIZE
+    }
+}
+
+impl<S> RecordSource for Box<S>
+where
+    S: RecordSource + ?Sized,
+{
+    fn name(&self) -> Cow<'_, str> {
+        (**self).name()
+    }
+
+    fn size(&self) -> BoxFuture<'_, Result<u64>> {
+        (**self).size()
+    }
+
+    fn read_range(&self, range: Range<u64>) -> BoxFuture<'_, Result<Vec<u8>>> {
+        (**self).read_range(range)
+    }
+
+    fn block_size(&self) -> usize {
+        (**self).block_size()
+    }
+}
+
+/// A record file on the local file system.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FileSource {
+    pub path: PathBuf,
+}
+
+impl FileSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl RecordSource for FileSource {
+    fn name(&self) -> Cow<'_, str> {
+        self.path.to_string_lossy()
+    }
+
+    fn size(&self) -> BoxFuture<'_, Result<u64>> {
+        Box::pin(async move { Ok(async_std::fs::metadata(&self.path).await?.len()) })
+    }
+
+    fn read_range(&self, range: Range<u64>) -> BoxFuture<'_, Result<Vec<u8>>> {
+        Box::pin(async move {
+            let mut file = async_std::fs::File::open(&self.path).await?;
+            file.seek(SeekFrom::Start(range.start)).await?;
+            let mut bytes = vec![];
+            file.take(range.end.saturating_sub(range.start))
+                .read_to_end(&mut bytes)
+                .await?;
+            Ok(bytes)
+        })
+    }
+}
+
+/// The limit of concurrent requests shared by sources.
+///
+/// Clones share the same limit. Limits are compared by identity.
+#[derive(Debug, Clone)]
+pub struct ConnectionLimit(Arc<(Sender<()>, Receiver<()>)>);
+
+impl ConnectionLimit {
+    /// Allow at most `max_connections` concurrent requests, which must be positive.
+    pub fn new(max_connections: usize) -> Result<Self> {
+        ensure_argument!(
+            max_connections > 0,
+            "the number of connections must be positive"
+        );
+        let (sender, receiver) = channel::bounded(max_connections);
+        for _ in 0..max_connections {
+            sender.try_send(()).unwrap();
+        }
+        Ok(Self(Arc::new((sender, receiver))))
+    }
+
+    /// The number of requests that can start without waiting.
+    pub fn available(&self) -> usize {
+        self.0 .1.len()
+    }
+
+    /// Apply the limit to the requests of the source.
+    pub fn limit<S>(&self, source: S) -> LimitedSource<S>
+    where
+        S: RecordSource,
+    {
+        LimitedSource {
+            source,
+            limit: self.clone(),
+        }
+    }
+
+    async fn acquire(&self) -> Permit<'_> {
+        // the channel is never closed as the limit holds the sender
+        self.0 .1.recv().await.unwrap();
+        Permit(self)
+    }
+}
+
+impl PartialEq for ConnectionLimit {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for ConnectionLimit {}
+
+impl Hash for ConnectionLimit {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).hash(state);
+    }
+}
+
+/// Returns the permit to the limit on drop.
+struct Permit<'a>(&'a ConnectionLimit);
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        let _ = self.0 .0 .0.try_send(());
+    }
+}
+
+/// A source whose requests are bounded by a [ConnectionLimit].
+#[derive(Debug, Clone)]
+pub struct LimitedSource<S> {
+    source: S,
+    limit: ConnectionLimit,
+}
+
+impl<S> LimitedSource<S> {
+    pub fn get_ref(&self) -> &S {
+        &self.source
+    }
+
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+}
+
+impl<S> RecordSource for LimitedSource<S>
+where
+    S: RecordSource,
+{
+    fn name(&self) -> Cow<'_, str> {
+        self.source.name()
+    }
+
+    fn size(&self) -> BoxFuture<'_, Result<u64>> {
+        Box::pin(async move {
+            let _permit = self.limit.acquire().await;
+            self.source.size().await
+        })
+    }
+
+    fn read_range(&self, range: Range<u64>) -> BoxFuture<'_, Result<Vec<u8>>> {
+        Box::pin(async move {
+            let _permit = self.limit.acquire().await;
+            self.source.read_range(range).await
+        })
+    }
+
+    fn block_size(&self) -> usize {
+        self.source.block_size()
+    }
+}
+
+/// The [AsyncRead] and [AsyncSeek] reader over a [RecordSource].
+///
+/// The bytes are read in blocks of [block_size](RecordSource::block_size) bytes, and
+/// the last block is kept for reads and seeks within it.
+pub struct SourceReader<S>
+where
+    S: RecordSource,
+{
+    source: Arc<S>,
+    pos: u64,
+    /// The size of the source once known from the source or a short read.
+    size: Option<u64>,
+    block: Vec<u8>,
+    block_start: u64,
+    pending: Pending,
+}
+
+enum Pending {
+    None,
+    Read {
+        range: Range<u64>,
+        future: BoxFuture<'static, Result<Vec<u8>>>,
+    },
+    Size(BoxFuture<'static, Result<u64>>),
+}
+
+impl<S> SourceReader<S>
+where
+    S: RecordSource,
+{
+    pub fn new(source: Arc<S>) -> Self {
+        Self {
+            source,
+            pos: 0,
+            size: None,
+            block: vec![],
+            block_start: 0,
+            pending: Pending::None,
+        }
+    }
+
+    pub fn source(&self) -> &Arc<S> {
+        &self.source
+    }
+}
+
+impl<S> AsyncRead for SourceReader<S>
+where
+    S: RecordSource,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        loop {
+            let cached = this
+                .pos
+                .checked_sub(this.block_start)
+                .filter(|&offset| offset < this.block.len() as u64);
+            if let Some(offset) = cached {
+                let available = &this.block[offset as usize..];
+                let len = available.len().min(buf.len());
+                buf[..len].copy_from_slice(&available[..len]);
+                this.pos += len as u64;
+                return Poll::Ready(Ok(len));
+            }
+            if buf.is_empty() || this.size.is_some_and(|size| this.pos >= size) {
+                return Poll::Ready(Ok(0));
+            }
+
+            if !matches!(&this.pending, Pending::Read { range, .. } if range.start == this.pos) {
+                let start = this.pos;
+                let end = start.saturating_add(this.source.block_size().max(1) as u64);
+                let end = this.size.map_or(end, |size| end.min(size));
+                let source = this.source.clone();
+                let range = start..end;
+                this.pending = Pending::Read {
+                    range: range.clone(),
+                    future: Box::pin(async move { source.read_range(range).await }),
+                };
+            }
+            let Pending::Read { range, future } = &mut this.pending else {
+                unreachable!();
+            };
+            let result = ready!(future.as_mut().poll(cx));
+            let range = range.clone();
+            this.pending = Pending::None;
+
+            let mut bytes = result?;
+            let num_requested = range.end - range.start;
+            bytes.truncate(num_requested as usize);
+            if (bytes.len() as u64) < num_requested {
+                this.size = Some(range.start + bytes.len() as u64);
+            }
+            this.block = bytes;
+            this.block_start = range.start;
+        }
+    }
+}
+
+impl<S> AsyncSeek for SourceReader<S>
+where
+    S: RecordSource,
+{
+    fn poll_seek(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        pos: SeekFrom,
+    ) -> Poll<io::Result<u64>> {
+        let this = self.get_mut();
+        let (base, offset) = match pos {
+            SeekFrom::Start(pos) => {
+                this.pos = pos;
+                return Poll::Ready(Ok(pos));
+            }
+            SeekFrom::Current(offset) => (this.pos, offset),
+            SeekFrom::End(offset) => {
+                let size = match this.size {
+                    Some(size) => size,
+                    None => {
+                        if !matches!(this.pending, Pending::Size(_)) {
+                            let source = this.source.clone();
+                            this.pending =
+                                Pending::Size(Box::pin(async move { source.size().await }));
+                        }
+                        let Pending::Size(future) = &mut this.pending else {
+                            unreachable!();
+                        };
+                        let result = ready!(future.as_mut().poll(cx));
+                        this.pending = Pending::None;
+                        *this.size.insert(result?)
+                    }
+                };
+                (size, offset)
+            }
+        };
+        let pos = base.checked_add_signed(offset).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        this.pos = pos;
+        Poll::Ready(Ok(pos))
+    }
+}
@@ -0,0 +1,293 @@
+use super::{HttpSource, HttpSourceConfig, RecordSource};
+use crate::{
+    ensure_argument,
+    error::{Error, Result},
+};
+use futures::future::BoxFuture;
+use ring::{digest, hmac};
+use std::{
+    borrow::Cow,
+    fmt,
+    fmt::Write as _,
+    ops::Range,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The SHA-256 digest of the empty payload of GET requests.
+const EMPTY_PAYLOAD_SHA256: &str =
+    "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+/// The credentials signing the requests of [S3Source] by AWS Signature Version 4.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct S3Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// The token of temporary credentials.
+    pub session_token: Option<String>,
+}
+
+impl fmt::Debug for S3Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("S3Credentials")
+            .field("access_key_id", &self.access_key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl S3Credentials {
+    /// Read the credentials from the `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and
+    /// optional `AWS_SESSION_TOKEN` environment variables.
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        let (access_key_id, secret_access_key) =
+            match (var("AWS_ACCESS_KEY_ID"), var("AWS_SECRET_ACCESS_KEY")) {
+                (Some(access_key_id), Some(secret_access_key)) => {
+                    (access_key_id, secret_access_key)
+                }
+                _ => {
+                    return Err(Error::invalid_argument(
+                        "AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY must be set",
+                    ))
+                }
+            };
+        Ok(Self {
+            access_key_id,
+            secret_access_key,
+            session_token: var("AWS_SESSION_TOKEN"),
+        })
+    }
+
+    /// The headers signing a GET request of the `Range` header at the time.
+    ///
+    /// The path must be URI-encoded as it is sent.
+    pub fn signed_headers(
+        &self,
+        region: &str,
+        host: &str,
+        path: &str,
+        range: &str,
+        time: SystemTime,
+    ) -> Vec<(String, String)> {
+        let amz_date = format_amz_date(time);
+        let date = &amz_date[..8];
+
+        let mut headers = vec![
+            ("host".to_string(), host.to_string()),
+            ("range".to_string(), range.to_string()),
+            (
+                "x-amz-content-sha256".to_string(),
+                EMPTY_PAYLOAD_SHA256.to_string(),
+            ),
+            ("x-amz-date".to_string(), amz_date.clone()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token".to_string(), token.clone()));
+        }
+        let signed_names = headers
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+            .collect();
+        let canonical_request = format!(
+            "GET\n{}\n\n{}\n{}\n{}",
+            path, canonical_headers, signed_names, EMPTY_PAYLOAD_SHA256
+        );
+
+        let scope = format!("{}/{}/s3/aws4_request", date, region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(digest::digest(&digest::SHA256, canonical_request.as_bytes()).as_ref())
+        );
+        let key = [date, region, "s3", "aws4_request"].iter().fold(
+            format!("AWS4{}", self.secret_access_key).into_bytes(),
+            |key, part| hmac_sha256(&key, part.as_bytes()),
+        );
+        let signature = hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+
+        // the host and range headers are sent by the HTTP source
+        headers.drain(..2);
+        headers.push((
+            "Authorization".to_string(),
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                self.access_key_id, scope, signed_names, signature
+            ),
+        ));
+        headers
+    }
+}
+
+/// Configuration for [S3Source].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct S3SourceConfig {
+    pub region: String,
+    /// The endpoint of an S3-compatible store, such as `http://localhost:9000`, whose
+    /// objects are addressed by `<endpoint>/<bucket>/<key>`.
+    ///
+    /// If not set, AWS objects are addressed by
+    /// `https://<bucket>.s3.<region>.amazonaws.com/<key>`.
+    pub endpoint: Option<String>,
+    pub http: HttpSourceConfig,
+}
+
+impl Default for S3SourceConfig {
+    fn default() -> Self {
+        Self {
+            region: "us-east-1".into(),
+            endpoint: None,
+            http: Default::default(),
+        }
+    }
+}
+
+/// A record file stored as an S3 object, read by ranged GetObject requests.
+///
+/// The requests are signed by AWS Signature Version 4 and retried as
+/// [HttpSource] does.
+#[derive(Debug, Clone)]
+pub struct S3Source {
+    bucket: String,
+    key: String,
+    http: HttpSource,
+}
+
+impl S3Source {
+    pub fn new(
+        bucket: impl Into<String>,
+        key: impl Into<String>,
+        credentials: S3Credentials,
+        config: S3SourceConfig,
+    ) -> Result<Self> {
+        let bucket = bucket.into();
+        let key = key.into();
+        let S3SourceConfig {
+            region,
+            endpoint,
+            http,
+        } = config;
+        ensure_argument!(!bucket.is_empty(), "the bucket name must not be empty");
+
+        let encoded_key = uri_encode_path(key.trim_start_matches('/'));
+        let (url, host, path) = match endpoint {
+            Some(endpoint) => {
+                let endpoint = endpoint.trim_end_matches('/');
+                let host = endpoint
+                    .split_once("://")
+                    .map(|(_, rest)| rest)
+                    .filter(|host| !host.is_empty() && !host.contains('/'))
+                    .ok_or_else(|| {
+                        Error::invalid_argument(format!(
+                            "the endpoint '{}' must be a scheme and a host",
+                            endpoint
+                        ))
+                    })?;
+                let path = format!("/{}/{}", uri_encode_path(&bucket), encoded_key);
+                (format!("{}{}", endpoint, path), host.to_string(), path)
+            }
+            None => {
+                let host = format!("{}.s3.{}.amazonaws.com", bucket, region);
+                let path = format!("/{}", encoded_key);
+                (format!("https://{}{}", host, path), host, path)
+            }
+        };
+
+        let sign = move |range: &str| {
+            credentials.signed_headers(&region, &host, &path, range, SystemTime::now())
+        };
+        let http = HttpSource::new(url, http).with_sign(Arc::new(sign));
+        Ok(Self { bucket, key, http })
+    }
+
+    pub fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl RecordSource for S3Source {
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("s3://{}/{}", self.bucket, self.key))
+    }
+
+    fn size(&self) -> BoxFuture<'_, Result<u64>> {
+        self.http.size()
+    }
+
+    fn read_range(&self, range: Range<u64>) -> BoxFuture<'_, Result<Vec<u8>>> {
+        self.http.read_range(range)
+    }
+
+    fn block_size(&self) -> usize {
+        self.http.block_size()
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, key);
+    hmac::sign(&key, data).as_ref().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        write!(hex, "{:02x}", byte).unwrap();
+        hex
+    })
+}
+
+/// Encode the path by the URI encoding of AWS, keeping the slashes.
+fn uri_encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for &byte in path.as_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => write!(encoded, "%{:02X}", byte).unwrap(),
+        }
+    }
+    encoded
+}
+
+/// Format the time as `YYYYMMDD'T'HHMMSS'Z'` in UTC.
+fn format_amz_date(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let (days, secs_of_day) = (secs / 86400, secs % 86400);
+
+    // convert the days since the epoch to the civil date
+    let days = days as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
@@ -0,0 +1,228 @@
+//! Split datasets by record content hashes.
+//!
+//! Splitting by position reassigns records between splits whenever files are added or
+//! removed. [by_hash] instead hashes the [key](SplitKey) of every record into one of
+//! [NUM_BUCKETS] buckets, and a record in bucket `b` belongs to the `i`-th split if `b`
+//! lies in `[f_0 + .. + f_(i-1), f_0 + .. + f_i) * NUM_BUCKETS` for the fractions `f`.
+//! The assignment of a record depends on its key and the [seed](HashSplitOptions::seed)
+//! only, so it never changes regardless of what else is in the dataset.
+//!
+//! The buckets are computed in a single sequential pass over the records by [classify].
+//! The resulting [HashClassification] is independent of the fractions, and can be
+//! stored with the record indexes to [assign](HashClassification::assign) splits again
+//! without reading the records.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{
+//!     indexer, samples,
+//!     split::{self, SplitKey},
+//! };
+//!
+//! let dataset = samples::tiny_dataset(2, 50)?;
+//! let indexes: Vec<_> =
+//!     indexer::load_paths(dataset.paths(), Default::default()).collect::<Result<_, _>>()?;
+//! let splits = split::by_hash(&indexes, SplitKey::Feature("id".into()), &[0.8, 0.2])?;
+//! assert_eq!(splits[0].len() + splits[1].len(), 100);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::{ensure_argument, Error, Result},
+    indexer::{self, RecordIndex},
+    protobuf::Example,
+};
+use prost::Message as _;
+use xxhash_rust::xxh3::xxh3_64_with_seed;
+
+/// The number of hash buckets records are assigned to.
+pub const NUM_BUCKETS: u16 = 10_000;
+
+/// The part of a record hashed to assign it to a split.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SplitKey {
+    /// The record payload.
+    FullPayload,
+    /// The canonical encoding of the named feature of the example in the payload.
+    ///
+    /// Records sharing the feature value, such as the examples of one user, always
+    /// belong to the same split.
+    Feature(String),
+}
+
+/// The treatment of examples without the [key feature](SplitKey::Feature).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MissingKeyPolicy {
+    /// Fail the classification.
+    #[default]
+    Fail,
+    /// Leave the example out of all splits.
+    Skip,
+    /// Hash the payload of the example instead.
+    HashPayload,
+}
+
+/// Options for [by_hash_with_options] and [classify].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HashSplitOptions {
+    /// The part of a record hashed.
+    pub key: SplitKey,
+    /// The treatment of examples without the key feature.
+    pub missing: MissingKeyPolicy,
+    /// The seed of the hash. Different seeds give independent assignments.
+    pub seed: u64,
+}
+
+impl HashSplitOptions {
+    /// Hash the key with the default options.
+    pub fn new(key: SplitKey) -> Self {
+        Self {
+            key,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for HashSplitOptions {
+    fn default() -> Self {
+        Self {
+            key: SplitKey::FullPayload,
+            missing: MissingKeyPolicy::Fail,
+            seed: 0,
+        }
+    }
+}
+
+/// The hash buckets of records, in the order of their indexes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HashClassification {
+    /// The part of records hashed.
+    pub key: SplitKey,
+    /// The seed of the hash.
+    pub seed: u64,
+    /// The bucket of each record, or `None` for skipped records.
+    pub buckets: Vec<Option<u16>>,
+}
+
+impl HashClassification {
+    /// Assign the records of the indexes to splits of the fractions.
+    ///
+    /// The indexes must be the ones the classification was computed from. Records in
+    /// buckets beyond the sum of fractions belong to no split.
+    pub fn assign(
+        &self,
+        indexes: &[RecordIndex],
+        fractions: &[f64],
+    ) -> Result<Vec<Vec<RecordIndex>>> {
+        ensure_argument!(
+            indexes.len() == self.buckets.len(),
+            "the classification of {} records does not match {} indexes",
+            self.buckets.len(),
+            indexes.len()
+        );
+        let thresholds = thresholds(fractions)?;
+        let mut splits = vec![vec![]; fractions.len()];
+        for (index, bucket) in indexes.iter().zip(&self.buckets) {
+            let split = bucket.and_then(|bucket| thresholds.iter().position(|&end| bucket < end));
+            if let Some(split) = split {
+                splits[split].push(index.clone());
+            }
+        }
+        Ok(splits)
+    }
+}
+
+/// Split the records of the indexes by the hash of the key, failing on examples without
+/// the key feature.
+pub fn by_hash(
+    indexes: &[RecordIndex],
+    key: SplitKey,
+    fractions: &[f64],
+) -> Result<Vec<Vec<RecordIndex>>> {
+    by_hash_with_options(indexes, HashSplitOptions::new(key), fractions)
+}
+
+/// Split the records of the indexes by the hash of the key with options.
+pub fn by_hash_with_options(
+    indexes: &[RecordIndex],
+    options: HashSplitOptions,
+    fractions: &[f64],
+) -> Result<Vec<Vec<RecordIndex>>> {
+    // validate fractions before reading records
+    thresholds(fractions)?;
+    classify(indexes, options)?.assign(indexes, fractions)
+}
+
+/// Compute the hash buckets of the records of the indexes in one sequential pass.
+pub fn classify(indexes: &[RecordIndex], options: HashSplitOptions) -> Result<HashClassification> {
+    let HashSplitOptions { key, missing, seed } = options;
+    let buckets = indexer::iter_from::<Vec<u8>>(indexes, 0, Default::default())
+        .map(|record| {
+            let (_, payload) = record?;
+            bucket(&payload, &key, missing, seed)
+        })
+        .collect::<Result<_>>()?;
+    Ok(HashClassification { key, seed, buckets })
+}
+
+/// The hash bucket of a payload, or `None` if skipped.
+pub fn bucket(
+    payload: &[u8],
+    key: &SplitKey,
+    missing: MissingKeyPolicy,
+    seed: u64,
+) -> Result<Option<u16>> {
+    let hash = match key {
+        SplitKey::FullPayload => xxh3_64_with_seed(payload, seed),
+        SplitKey::Feature(name) => {
+            let mut example = Example::decode(payload)?;
+            match (
+                example
+                    .features
+                    .as_mut()
+                    .and_then(|features| features.feature.remove(name)),
+                missing,
+            ) {
+                (Some(feature), _) => xxh3_64_with_seed(&feature.encode_to_vec(), seed),
+                (None, MissingKeyPolicy::HashPayload) => xxh3_64_with_seed(payload, seed),
+                (None, MissingKeyPolicy::Skip) => return Ok(None),
+                (None, MissingKeyPolicy::Fail) => {
+                    return Err(Error::conversion(format!(
+                        "the example has no feature named '{}' to split by",
+                        name
+                    )))
+                }
+            }
+        }
+    };
+    Ok(Some((hash % NUM_BUCKETS as u64) as u16))
+}
+
+/// The exclusive end bucket of each split.
+fn thresholds(fractions: &[f64]) -> Result<Vec<u16>> {
+    ensure_argument!(
+        fractions
+            .iter()
+            .all(|fraction| (0.0..=1.0).contains(fraction)),
+        "split fractions must be within [0, 1], but get {:?}",
+        fractions
+    );
+    let total: f64 = fractions.iter().sum();
+    ensure_argument!(
+        (0.0..=1.0 + 1e-9).contains(&total),
+        "split fractions must sum to at most 1, but get {}",
+        total
+    );
+    let mut sum = 0.0;
+    Ok(fractions
+        .iter()
+        .map(|fraction| {
+            sum += fraction;
+            ((sum * NUM_BUCKETS as f64).round() as u16).min(NUM_BUCKETS)
+        })
+        .collect())
+}
@@ -0,0 +1,255 @@
+//! Count records and measure their lengths without decoding them.
+//!
+//! The statistics are collected from the record indexes, so only the frame headers
+//! are read, and the payloads whose checksums are verified by the
+//! [integrity](crate::indexer::RecordIndexerConfig::integrity) mode of the indexer
+//! configuration. With [IntegrityMode::Off](crate::IntegrityMode::Off), no payload
+//! is read at all.
+//!
+//! [inspect] skips records failing checksum verification and counts them as
+//! [corrupt](FileStats::num_corrupt), as
+//! [skip_corrupt](crate::indexer::RecordIndexerConfig::skip_corrupt) does. The async
+//! indexer does not skip records, so [inspect_async] fails with
+//! [Error::ChecksumMismatch](crate::Error::ChecksumMismatch) on them instead, and with
+//! [Error::InvalidArgumentsError](crate::Error::InvalidArgumentsError) if
+//! [skip_corrupt](crate::indexer::RecordIndexerConfig::skip_corrupt) is set.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{samples, stats};
+//!
+//! let dataset = samples::tiny_dataset(3, 4)?;
+//! assert_eq!(stats::count_records(&dataset.paths()[0], Default::default())?, 4);
+//!
+//! let dataset_stats = stats::inspect_paths(dataset.paths(), Default::default(), 2)?;
+//! assert_eq!(dataset_stats.files.len(), 3);
+//! assert_eq!(dataset_stats.total.num_records, 12);
+//! assert!(dataset_stats.total.checksums_valid());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::{ensure_argument, Error, Result},
+    indexer::{self, RecordIndexerConfig},
+};
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+
+/// The statistics of the records of a file, or of files merged together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileStats {
+    /// The number of records, excluding corrupt ones.
+    pub num_records: u64,
+    /// The length of the shortest payload, or `None` without records.
+    pub min_len: Option<usize>,
+    /// The length of the longest payload, or `None` without records.
+    pub max_len: Option<usize>,
+    /// The total length of payloads, excluding the frame headers and footers.
+    pub total_len: u64,
+    /// The number of records skipped for failing checksum verification.
+    pub num_corrupt: u64,
+}
+
+impl FileStats {
+    /// Add a record of the payload length.
+    pub fn push(&mut self, len: usize) {
+        self.num_records += 1;
+        self.min_len = Some(self.min_len.map_or(len, |min| min.min(len)));
+        self.max_len = Some(self.max_len.map_or(len, |max| max.max(len)));
+        self.total_len += len as u64;
+    }
+
+    /// Add the statistics of other records.
+    pub fn merge(&mut self, other: FileStats) {
+        self.num_records += other.num_records;
+        self.min_len = self.min_len.into_iter().chain(other.min_len).min();
+        self.max_len = self.max_len.into_iter().chain(other.max_len).max();
+        self.total_len += other.total_len;
+        self.num_corrupt += other.num_corrupt;
+    }
+
+    /// The mean payload length, or `None` without records.
+    pub fn mean_len(&self) -> Option<f64> {
+        (self.num_records > 0).then(|| self.total_len as f64 / self.num_records as f64)
+    }
+
+    /// Returns true if no verified checksum failed.
+    ///
+    /// Records not verified by the integrity mode are not accounted for.
+    pub fn checksums_valid(&self) -> bool {
+        self.num_corrupt == 0
+    }
+}
+
+/// The statistics of the files of a dataset.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DatasetStats {
+    /// The statistics of each file, in the path order of the indexer configuration.
+    pub files: Vec<(PathBuf, FileStats)>,
+    /// The statistics of all files merged together.
+    pub total: FileStats,
+}
+
+impl DatasetStats {
+    fn from_files(files: Vec<(PathBuf, FileStats)>) -> Self {
+        let mut total = FileStats::default();
+        for (_, stats) in &files {
+            total.merge(*stats);
+        }
+        Self { files, total }
+    }
+}
+
+/// Count the records of a file.
+pub fn count_records<'a, P>(path: P, config: RecordIndexerConfig) -> Result<u64>
+where
+    P: Into<Cow<'a, Path>>,
+{
+    Ok(inspect(path, config)?.num_records)
+}
+
+/// Collect the statistics of the records of a file.
+///
+/// Records failing checksum verification are skipped and counted, whether or not
+/// [skip_corrupt](RecordIndexerConfig::skip_corrupt) is set.
+pub fn inspect<'a, P>(path: P, config: RecordIndexerConfig) -> Result<FileStats>
+where
+    P: Into<Cow<'a, Path>>,
+{
+    let config = RecordIndexerConfig {
+        skip_corrupt: true,
+        ..config
+    };
+
+    let mut stats = FileStats::default();
+    let (indexes, report) = indexer::load_file_with_report(path, config)?;
+    for index in indexes {
+        stats.push(index?.len);
+    }
+    stats.num_corrupt = report.len() as u64;
+    Ok(stats)
+}
+
+/// Collect the statistics of the records of files, inspecting up to
+/// `max_parallel_files` files at a time on threads.
+///
+/// The files are listed in the [path order](RecordIndexerConfig::path_order) of the
+/// configuration.
+pub fn inspect_paths<'a, I, P>(
+    paths: I,
+    config: RecordIndexerConfig,
+    max_parallel_files: usize,
+) -> Result<DatasetStats>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Cow<'a, Path>>,
+{
+    ensure_argument!(
+        max_parallel_files > 0,
+        "the number of parallel files must be positive"
+    );
+    let paths: Vec<PathBuf> = paths
+        .into_iter()
+        .map(|path| path.into().into_owned())
+        .collect();
+    let paths = indexer::sort_paths(paths, config.path_order)?;
+
+    let num_workers = max_parallel_files.min(paths.len()).max(1);
+    let chunk_size = paths.len().div_ceil(num_workers).max(1);
+    let files = std::thread::scope(|scope| {
+        let workers: Vec<_> = paths
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let config = &config;
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|path| Ok((path.clone(), inspect(path, config.clone())?)))
+                        .collect::<Result<Vec<_>>>()
+                })
+            })
+            .collect();
+        let mut files = Vec::with_capacity(paths.len());
+        for worker in workers {
+            files.extend(worker.join().expect("the inspection thread panicked")?);
+        }
+        Ok::<_, Error>(files)
+    })?;
+    Ok(DatasetStats::from_files(files))
+}
+
+/// Count the records of a file asynchronously.
+#[cfg(feature = "async")]
+pub async fn count_records_async<'a, P>(path: P, config: RecordIndexerConfig) -> Result<u64>
+where
+    P: Into<Cow<'a, Path>>,
+{
+    Ok(inspect_async(path, config).await?.num_records)
+}
+
+/// Collect the statistics of the records of a file asynchronously.
+///
+/// Records failing checksum verification fail with
+/// [Error::ChecksumMismatch](crate::Error::ChecksumMismatch), so the
+/// [num_corrupt](FileStats::num_corrupt) is always zero.
+#[cfg(feature = "async")]
+pub async fn inspect_async<'a, P>(path: P, config: RecordIndexerConfig) -> Result<FileStats>
+where
+    P: Into<Cow<'a, Path>>,
+{
+    use futures::stream::TryStreamExt as _;
+
+    indexer::load_file_async(path, config)
+        .await?
+        .try_fold(FileStats::default(), |mut stats, index| async move {
+            stats.push(index.len);
+            Ok(stats)
+        })
+        .await
+}
+
+/// Collect the statistics of the records of files asynchronously, inspecting up to
+/// `max_parallel_files` files concurrently.
+///
+/// The files are listed in the [path order](RecordIndexerConfig::path_order) of the
+/// configuration.
+#[cfg(feature = "async")]
+pub async fn inspect_paths_async<'a, I, P>(
+    paths: I,
+    config: RecordIndexerConfig,
+    max_parallel_files: usize,
+) -> Result<DatasetStats>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Cow<'a, Path>>,
+{
+    use futures::stream::{self, StreamExt as _, TryStreamExt as _};
+
+    ensure_argument!(
+        max_parallel_files > 0,
+        "the number of parallel files must be positive"
+    );
+    let paths: Vec<PathBuf> = paths
+        .into_iter()
+        .map(|path| path.into().into_owned())
+        .collect();
+    let paths = indexer::sort_paths(paths, config.path_order)?;
+
+    let files: Vec<_> = stream::iter(paths)
+        .map(|path| {
+            let config = config.clone();
+            async move {
+                let stats = inspect_async(path.as_path(), config).await?;
+                Ok::<_, Error>((path, stats))
+            }
+        })
+        .buffered(max_parallel_files)
+        .try_collect()
+        .await?;
+    Ok(DatasetStats::from_files(files))
+}
@@ -0,0 +1,490 @@
+//! Cut datasets down to small representative subsets.
+//!
+//! [create_subset] copies a random subset of records within a [Budget] to new files,
+//! optionally [stratified](Stratify) by a key extracted from each record. It makes two
+//! passes over the inputs.
+//!
+//! 1. Count the records and bytes per key, and allocate a quota of records to every key.
+//!    Quotas start at the [minimum](KeyQuota::min) of each key, and the rest of the
+//!    budget is allocated in proportion to the frequencies of keys, up to the
+//!    [maximum](KeyQuota::max) of each key.
+//! 2. Sample exactly the quota of each key by selection sampling while streaming, and
+//!    write the selected records in input order. Knowing the number of records per key
+//!    from the first pass, selection sampling picks a uniform sample without holding a
+//!    reservoir in memory.
+//!
+//! The selection is determined by the [seed](SubsetSpec::seed) and the order of inputs.
+//! Payloads are copied verbatim, so every output record is a byte-identical copy of an
+//! input record. The [SubsetReport] compares the requested and achieved counts per key.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use prost::Message as _;
+//! use tfrecord::{
+//!     samples,
+//!     subset::{self, Budget, KeyQuota, Stratify, SubsetSpec},
+//!     Example,
+//! };
+//!
+//! let dataset = samples::tiny_dataset(2, 50)?;
+//! let label = |bytes: &[u8]| {
+//!     let example = Example::decode(bytes)?;
+//!     let label = example.into_hash_map().remove("label");
+//!     Ok(format!("{:?}", label.as_ref().and_then(|label| label.as_i64_list())))
+//! };
+//! let spec = SubsetSpec {
+//!     stratify: Some(Stratify::new(label).with_default_quota(KeyQuota {
+//!         min: 2,
+//!         max: Some(3),
+//!     })),
+//!     ..SubsetSpec::new(Budget::Records(25))
+//! };
+//! let report = subset::create_subset(
+//!     dataset.paths(),
+//!     dataset.dir().join("dev").to_str().unwrap(),
+//!     spec,
+//! )?;
+//! assert_eq!(report.num_records, 25);
+//! assert!(report.keys.iter().all(|key| (2..=3).contains(&key.achieved)));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    cancel::{self, CancelFlag},
+    error::{ensure_argument, Error, Result},
+    limits::Limits,
+    record_reader::{BytesIter, RecordReaderConfig},
+    record_writer::BytesWriter,
+    shardspec,
+    utils::SplitMix64,
+};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// The overhead of a record frame in bytes, namely the length, the length checksum and
+/// the payload checksum.
+const FRAME_OVERHEAD: u64 = 16;
+
+/// The function extracting the stratification key from a record payload.
+pub type KeyFn = dyn Fn(&[u8]) -> Result<String> + Send + Sync;
+
+/// The size limit of a subset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Budget {
+    /// The total number of records.
+    Records(u64),
+    /// The total size of output files in bytes.
+    ///
+    /// It is converted to a number of records by the mean frame length of the inputs,
+    /// so the size of a subset is approximately the budget.
+    Bytes(u64),
+}
+
+/// The bounds on the number of records of a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct KeyQuota {
+    /// The number of records reserved before the proportional allocation.
+    pub min: u64,
+    /// The maximum number of records, unbounded if not set.
+    pub max: Option<u64>,
+}
+
+/// The stratification of a subset.
+#[derive(Clone)]
+pub struct Stratify {
+    /// The function extracting the key of a record.
+    pub key: Arc<KeyFn>,
+    /// The quota of keys absent in [quotas](Stratify::quotas).
+    pub default_quota: KeyQuota,
+    /// The quotas of specific keys.
+    pub quotas: HashMap<String, KeyQuota>,
+}
+
+impl Stratify {
+    /// Stratify by the key function, allocating the budget in proportion to the
+    /// frequencies of keys.
+    pub fn new<F>(key: F) -> Self
+    where
+        F: 'static + Fn(&[u8]) -> Result<String> + Send + Sync,
+    {
+        Self {
+            key: Arc::new(key),
+            default_quota: KeyQuota::default(),
+            quotas: HashMap::new(),
+        }
+    }
+
+    /// Set the quota of keys without specific quotas.
+    pub fn with_default_quota(mut self, quota: KeyQuota) -> Self {
+        self.default_quota = quota;
+        self
+    }
+
+    /// Set the quota of a specific key.
+    pub fn with_quota<K>(mut self, key: K, quota: KeyQuota) -> Self
+    where
+        K: Into<String>,
+    {
+        self.quotas.insert(key.into(), quota);
+        self
+    }
+
+    fn quota(&self, key: &str) -> KeyQuota {
+        self.quotas.get(key).copied().unwrap_or(self.default_quota)
+    }
+}
+
+impl fmt::Debug for Stratify {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stratify")
+            .field("default_quota", &self.default_quota)
+            .field("quotas", &self.quotas)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The specification of a subset.
+#[derive(Debug, Clone)]
+pub struct SubsetSpec {
+    pub budget: Budget,
+    /// If set, records are sampled per key, otherwise uniformly from all records.
+    pub stratify: Option<Stratify>,
+    /// The seed of the random selection.
+    pub seed: u64,
+    /// The maximum number of records per output file, unbounded if not set.
+    pub records_per_shard: Option<u64>,
+    /// If set, the operation is cancelled once the flag is raised, removing the partial
+    /// outputs.
+    pub cancel: Option<CancelFlag>,
+    /// The limits of reading the inputs. The number of distinct stratification keys is
+    /// capped by [max_distinct_keys](Limits::max_distinct_keys).
+    pub limits: Limits,
+}
+
+impl SubsetSpec {
+    /// Sample uniformly within the budget into a single output file.
+    pub fn new(budget: Budget) -> Self {
+        Self {
+            budget,
+            stratify: None,
+            seed: 0,
+            records_per_shard: None,
+            cancel: None,
+            limits: Limits::default(),
+        }
+    }
+}
+
+/// The outcome of [create_subset].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SubsetReport {
+    /// The output files in order.
+    pub paths: Vec<PathBuf>,
+    /// The number of written records.
+    pub num_records: u64,
+    /// The total size of written record frames in bytes.
+    pub num_bytes: u64,
+    /// The reports per key, sorted by key. Unstratified subsets have a single key
+    /// of the empty string.
+    pub keys: Vec<KeyReport>,
+}
+
+/// The requested and achieved counts of a key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeyReport {
+    pub key: String,
+    /// The number of input records of the key.
+    pub available: u64,
+    /// The number of records allocated to the key.
+    pub requested: u64,
+    /// The number of written records of the key.
+    pub achieved: u64,
+    /// The reason of achieving less than requested, if any.
+    pub shortfall: Option<Shortfall>,
+}
+
+/// The reason of a key achieving less records than requested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Shortfall {
+    /// The key has fewer records than its quota.
+    KeyTooRare,
+}
+
+/// Create a subset of records from input files.
+///
+/// The output files are named `{output_prefix}-{index:05}-of-{count:05}`, where the
+/// count is determined by [records_per_shard](SubsetSpec::records_per_shard). An
+/// empty subset is written to a single empty file.
+pub fn create_subset<'a, P, I>(
+    inputs: I,
+    output_prefix: &str,
+    spec: SubsetSpec,
+) -> Result<SubsetReport>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<Cow<'a, Path>>,
+{
+    let SubsetSpec {
+        budget,
+        stratify,
+        seed,
+        records_per_shard,
+        cancel,
+        limits,
+    } = spec;
+    let cancel = cancel.as_ref();
+    let reader = RecordReaderConfig {
+        limits,
+        ..Default::default()
+    };
+    ensure_argument!(
+        records_per_shard != Some(0),
+        "records_per_shard must be positive"
+    );
+    let inputs: Vec<PathBuf> = inputs
+        .into_iter()
+        .map(|path| path.into().into_owned())
+        .collect();
+    let key_of = |bytes: &[u8]| -> Result<String> {
+        match &stratify {
+            Some(stratify) => (stratify.key)(bytes),
+            None => Ok(String::new()),
+        }
+    };
+
+    // first pass: count records per key
+    let mut counts: BTreeMap<String, u64> = BTreeMap::new();
+    let mut total_bytes = 0;
+    let mut files_done = 0;
+    let mut records_processed = 0;
+    for path in &inputs {
+        cancel::check(cancel, files_done, records_processed)?;
+        for bytes in BytesIter::open(path, reader.clone())? {
+            cancel::check_periodically(cancel, files_done, records_processed)?;
+            records_processed += 1;
+            let bytes = bytes?;
+            *counts.entry(key_of(&bytes)?).or_default() += 1;
+            reader.limits.check_distinct_keys(counts.len())?;
+            total_bytes += bytes.len() as u64 + FRAME_OVERHEAD;
+        }
+        files_done += 1;
+    }
+    let total_records: u64 = counts.values().sum();
+
+    let budget = match budget {
+        Budget::Records(num_records) => num_records,
+        Budget::Bytes(_) if total_records == 0 => 0,
+        Budget::Bytes(num_bytes) => {
+            (num_bytes as u128 * total_records as u128 / total_bytes as u128) as u64
+        }
+    };
+    let quotas: Vec<KeyQuota> = counts
+        .keys()
+        .map(|key| match &stratify {
+            Some(stratify) => stratify.quota(key),
+            None => KeyQuota::default(),
+        })
+        .collect();
+    let available: Vec<u64> = counts.values().copied().collect();
+    let (requested, achieved) = allocate(budget, &available, &quotas)?;
+
+    // second pass: select records and write
+    let num_selected: u64 = achieved.iter().sum();
+    let num_shards = match records_per_shard {
+        Some(per_shard) => num_selected.div_ceil(per_shard).max(1),
+        None => 1,
+    };
+    let paths: Vec<PathBuf> = (0..num_shards)
+        .map(|index| {
+            shardspec::shard_name(output_prefix, index as usize, num_shards as usize).into()
+        })
+        .collect();
+
+    let key_indexes: HashMap<&str, usize> = counts
+        .keys()
+        .enumerate()
+        .map(|(index, key)| (key.as_str(), index))
+        .collect();
+    let mut unseen = available.clone();
+    let mut unselected = achieved.clone();
+    let mut rng = SplitMix64(seed);
+
+    let mut shards = paths.iter();
+    let mut created: Vec<PathBuf> = vec![];
+    let mut num_records = 0;
+    let mut num_bytes = 0;
+
+    let mut write_subset = || -> Result<()> {
+        let path = shards.next().unwrap();
+        let mut writer = BytesWriter::create(path)?;
+        created.push(path.clone());
+
+        for path in &inputs {
+            cancel::check(cancel, files_done, records_processed)?;
+            for bytes in BytesIter::open(path, reader.clone())? {
+                cancel::check_periodically(cancel, files_done, records_processed)?;
+                records_processed += 1;
+                let bytes = bytes?;
+                let key = key_of(&bytes)?;
+                let index = *key_indexes
+                    .get(key.as_str())
+                    .ok_or_else(|| changed_inputs(&key))?;
+                if unseen[index] == 0 {
+                    return Err(changed_inputs(&key));
+                }
+
+                // select with the probability of the remaining quota over the unseen records
+                let selected = rng.next_below(unseen[index]) < unselected[index];
+                unseen[index] -= 1;
+                if !selected {
+                    continue;
+                }
+                unselected[index] -= 1;
+
+                if records_per_shard
+                    .is_some_and(|per_shard| num_records > 0 && num_records % per_shard == 0)
+                {
+                    writer.flush()?;
+                    let path = shards.next().unwrap();
+                    writer = BytesWriter::create(path)?;
+                    created.push(path.clone());
+                }
+                num_bytes += bytes.len() as u64 + FRAME_OVERHEAD;
+                num_records += 1;
+                writer.send(bytes)?;
+            }
+            files_done += 1;
+        }
+        writer.flush()?;
+        Ok(())
+    };
+
+    match write_subset() {
+        Ok(()) => {}
+        Err(Error::Cancelled { mut progress }) => {
+            // remove the partial outputs
+            let num_removed = created
+                .iter()
+                .filter(|path| std::fs::remove_file(path).is_ok())
+                .count();
+            progress.cleaned_up = num_removed == created.len();
+            progress.partial_outputs = created;
+            return Err(Error::Cancelled { progress });
+        }
+        Err(err) => return Err(err),
+    }
+
+    let keys = counts
+        .into_keys()
+        .zip(available)
+        .zip(requested.into_iter().zip(achieved))
+        .map(|((key, available), (requested, achieved))| KeyReport {
+            key,
+            available,
+            requested,
+            achieved,
+            shortfall: (achieved < requested).then_some(Shortfall::KeyTooRare),
+        })
+        .collect();
+
+    Ok(SubsetReport {
+        paths,
+        num_records,
+        num_bytes,
+        keys,
+    })
+}
+
+fn changed_inputs(key: &str) -> Error {
+    Error::invalid_argument(format!(
+        "unexpected record of key '{}' in the second pass, the key function must be \
+         deterministic and the inputs must not change",
+        key
+    ))
+}
+
+/// Allocate the budget to keys, returning the requested and achievable counts per key.
+fn allocate(budget: u64, available: &[u64], quotas: &[KeyQuota]) -> Result<(Vec<u64>, Vec<u64>)> {
+    for quota in quotas {
+        ensure_argument!(
+            quota.max.is_none_or(|max| max >= quota.min),
+            "the minimum {} exceeds the maximum {:?} of a key quota",
+            quota.min,
+            quota.max
+        );
+    }
+    // reserve the minimums, as far as the keys have records
+    let mut requested: Vec<u64> = quotas.iter().map(|quota| quota.min).collect();
+    let mut achieved: Vec<u64> = requested
+        .iter()
+        .zip(available)
+        .map(|(&min, &available)| min.min(available))
+        .collect();
+    let reserved: u64 = achieved.iter().sum();
+    ensure_argument!(
+        reserved <= budget,
+        "the minimum quotas of {} records exceed the budget of {} records",
+        reserved,
+        budget
+    );
+
+    // distribute the rest in proportion to frequencies among keys having room
+    let room = |index: usize, achieved: &[u64]| {
+        let max = quotas[index].max.unwrap_or(u64::MAX).min(available[index]);
+        max.saturating_sub(achieved[index])
+    };
+    let mut remaining = budget - reserved;
+    while remaining > 0 {
+        let weights: Vec<u64> = (0..available.len())
+            .map(|index| {
+                if room(index, &achieved) > 0 {
+                    available[index]
+                } else {
+                    0
+                }
+            })
+            .collect();
+        if weights.iter().all(|&weight| weight == 0) {
+            break;
+        }
+
+        let shares = apportion(remaining, &weights);
+        for (index, share) in shares.into_iter().enumerate() {
+            let share = share.min(room(index, &achieved));
+            requested[index] += share;
+            achieved[index] += share;
+            remaining -= share;
+        }
+    }
+
+    Ok((requested, achieved))
+}
+
+/// Split the total in proportion to the weights by the largest remainder method,
+/// breaking ties by index.
+fn apportion(total: u64, weights: &[u64]) -> Vec<u64> {
+    let sum: u128 = weights.iter().map(|&weight| weight as u128).sum();
+    let mut shares: Vec<u64> = vec![];
+    let mut remainders: Vec<(u128, usize)> = vec![];
+    for (index, &weight) in weights.iter().enumerate() {
+        let product = total as u128 * weight as u128;
+        shares.push((product / sum) as u64);
+        if weight > 0 {
+            remainders.push((product % sum, index));
+        }
+    }
+
+    let assigned: u64 = shares.iter().sum();
+    remainders
+        .sort_by(|(lhs, lhs_index), (rhs, rhs_index)| rhs.cmp(lhs).then(lhs_index.cmp(rhs_index)));
+    for &(_, index) in remainders.iter().take((total - assigned) as usize) {
+        shares[index] += 1;
+    }
+    shares
+}
@@ -0,0 +1,568 @@
+//! Generate synthetic examples conforming to feature specs.
+//!
+//! [generate] produces examples whose features are declared by [FeatureSpec]s, with the
+//! values of each feature drawn from a [Distribution] and, for variable-length features,
+//! the number of values drawn from a [CountDistribution]. It serves load tests and
+//! fixtures which need data of a realistic shape rather than of realistic content.
+//!
+//! The examples are a function of the specs and the [options](GenOptions) alone. The
+//! random numbers come from a SplitMix64 generator, and the logarithms and
+//! exponentials needed by the non-uniform distributions are computed from exactly
+//! rounded arithmetic, so the same seed yields the same examples on every platform.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{
+//!     schema::{self, FeatureSpec, ValueCount, ValueType},
+//!     synth::{self, Distribution, GenOptions},
+//! };
+//!
+//! let specs = [
+//!     FeatureSpec::new("label", ValueType::I64, ValueCount::Fixed(1)),
+//!     FeatureSpec::new("embedding", ValueType::F32, ValueCount::Fixed(8)),
+//! ];
+//! let mut options = GenOptions {
+//!     records: 100,
+//!     seed: 7,
+//!     ..Default::default()
+//! };
+//! options.distributions.insert(
+//!     "embedding".into(),
+//!     Distribution::NormalF32 {
+//!         mean: 0.0,
+//!         std_dev: 1.0,
+//!     },
+//! );
+//!
+//! for example in synth::generate(&specs, options)? {
+//!     schema::validate(&specs, &example)?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::{ensure_argument, Error, Result},
+    protobuf::Example,
+    record_writer::{ExampleWriter, RecordWriterConfig, RoutingWriter},
+    schema::{FeatureSpec, ValueCount, ValueType},
+    utils::SplitMix64,
+};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// The maximum number of ranks of a [Zipf](Distribution::Zipf) distribution.
+pub const MAX_ZIPF_RANKS: u64 = 1 << 20;
+
+/// The distribution of the values of a feature.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Distribution {
+    /// Floats uniform in `[low, high)`.
+    UniformF32 { low: f64, high: f64 },
+    /// Normally distributed floats.
+    NormalF32 { mean: f64, std_dev: f64 },
+    /// Integers uniform in `[low, high]`.
+    UniformI64 { low: i64, high: i64 },
+    /// Integers in `[1, ranks]`, where the probability of `k` is proportional to
+    /// `1 / k^exponent`.
+    Zipf { ranks: u64, exponent: f64 },
+    /// Random bytes of a length uniform in `[min_len, max_len]`.
+    RandomBytes { min_len: usize, max_len: usize },
+    /// Entries of the dictionary, sampled uniformly.
+    Dictionary(Vec<Vec<u8>>),
+}
+
+impl Distribution {
+    /// The type of the values drawn from the distribution.
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Self::UniformF32 { .. } | Self::NormalF32 { .. } => ValueType::F32,
+            Self::UniformI64 { .. } | Self::Zipf { .. } => ValueType::I64,
+            Self::RandomBytes { .. } | Self::Dictionary(_) => ValueType::Bytes,
+        }
+    }
+
+    /// The distribution of features without a configured one.
+    fn default_for(value_type: ValueType) -> Self {
+        match value_type {
+            ValueType::F32 => Self::UniformF32 {
+                low: 0.0,
+                high: 1.0,
+            },
+            ValueType::I64 => Self::UniformI64 { low: 0, high: 99 },
+            ValueType::Bytes => Self::RandomBytes {
+                min_len: 1,
+                max_len: 16,
+            },
+        }
+    }
+}
+
+/// The distribution of the number of values of a variable-length feature.
+///
+/// Drawn numbers are clamped to the bounds of the [spec](ValueCount::Var).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CountDistribution {
+    /// Numbers uniform in `[min, max]`.
+    Uniform { min: usize, max: usize },
+    /// Geometrically distributed numbers with the mean, starting at zero.
+    Geometric { mean: f64 },
+}
+
+/// Options for [generate].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenOptions {
+    /// The number of generated examples.
+    pub records: u64,
+    pub seed: u64,
+    /// The distributions of values by feature key. Features without one draw floats
+    /// uniform in `[0, 1)`, integers uniform in `[0, 99]` and random bytes of 1 to 16
+    /// bytes.
+    pub distributions: HashMap<String, Distribution>,
+    /// The distributions of the numbers of values of variable-length features by key.
+    /// Features without one draw numbers uniform within the bounds of the spec, or up
+    /// to 8 more than the minimum if unbounded.
+    pub counts: HashMap<String, CountDistribution>,
+}
+
+impl Default for GenOptions {
+    fn default() -> Self {
+        Self {
+            records: 1000,
+            seed: 0,
+            distributions: HashMap::new(),
+            counts: HashMap::new(),
+        }
+    }
+}
+
+/// Generate examples conforming to the specs.
+///
+/// It fails if a configured distribution does not match the value type of its feature
+/// or has invalid parameters.
+pub fn generate(specs: &[FeatureSpec], options: GenOptions) -> Result<SynthIter> {
+    let GenOptions {
+        records,
+        seed,
+        mut distributions,
+        mut counts,
+    } = options;
+
+    let features = specs
+        .iter()
+        .map(|spec| {
+            let distribution = distributions
+                .remove(&spec.key)
+                .unwrap_or_else(|| Distribution::default_for(spec.value_type));
+            let count = counts.remove(&spec.key);
+            FeatureGen::new(spec, distribution, count)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let unknown: Vec<_> = distributions.keys().chain(counts.keys()).collect();
+    ensure_argument!(
+        unknown.is_empty(),
+        "distributions are configured for undeclared features {:?}",
+        unknown
+    );
+
+    Ok(SynthIter {
+        features,
+        rng: SplitMix64(seed),
+        remaining: records,
+    })
+}
+
+/// Generate examples conforming to the specs into `num_shards` files in the directory,
+/// returning the paths of the files.
+///
+/// The files are named `part-00000.tfrecord` and so on, and the examples are assigned
+/// to them in turn. Examples are written in the canonical encoding, so the files are
+/// the same for the same options.
+pub fn write_sharded<P>(
+    specs: &[FeatureSpec],
+    options: GenOptions,
+    dir: P,
+    num_shards: usize,
+) -> Result<Vec<PathBuf>>
+where
+    P: AsRef<Path>,
+{
+    ensure_argument!(num_shards > 0, "num_shards must be positive");
+    let dir = dir.as_ref();
+    let examples = generate(specs, options)?;
+
+    std::fs::create_dir_all(dir).map_err(|err| Error::from_io_with_context(err, dir, None))?;
+    let paths: Vec<_> = (0..num_shards)
+        .map(|index| dir.join(format!("part-{:05}.tfrecord", index)))
+        .collect();
+    let writers = paths
+        .iter()
+        .map(|path| {
+            let config = RecordWriterConfig {
+                canonical_encoding: true,
+                ..Default::default()
+            };
+            ExampleWriter::create_with_config(path, config)
+                .map_err(|err| err.with_io_context(path, None))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut next = 0;
+    let mut writer = RoutingWriter::new(writers, move |_: &Example| {
+        let index = next;
+        next = (next + 1) % num_shards;
+        index
+    })?;
+    for example in examples {
+        writer.send(example)?;
+    }
+    writer.finish().into_result()?;
+    Ok(paths)
+}
+
+/// Iterator of synthetic examples, returned by [generate].
+#[derive(Debug)]
+pub struct SynthIter {
+    features: Vec<FeatureGen>,
+    rng: SplitMix64,
+    remaining: u64,
+}
+
+impl Iterator for SynthIter {
+    type Item = Example;
+
+    fn next(&mut self) -> Option<Example> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let mut example = Example::empty();
+        for feature in &self.features {
+            feature.push_into(&mut example, &mut self.rng);
+        }
+        Some(example)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = usize::try_from(self.remaining).unwrap_or(usize::MAX);
+        (remaining, usize::try_from(self.remaining).ok())
+    }
+}
+
+/// The generator of a feature.
+#[derive(Debug)]
+struct FeatureGen {
+    key: String,
+    values: ValueGen,
+    count: CountGen,
+}
+
+#[derive(Debug)]
+enum ValueGen {
+    UniformF32 {
+        low: f64,
+        high: f64,
+    },
+    NormalF32 {
+        mean: f64,
+        std_dev: f64,
+    },
+    UniformI64 {
+        low: i64,
+        high: i64,
+    },
+    /// The cumulative weights of the ranks.
+    Zipf {
+        cumulative: Vec<f64>,
+    },
+    RandomBytes {
+        min_len: usize,
+        max_len: usize,
+    },
+    Dictionary(Vec<Vec<u8>>),
+}
+
+#[derive(Debug)]
+enum CountGen {
+    Fixed(usize),
+    Uniform { min: usize, max: usize },
+    Geometric { min: usize, max: usize, mean: f64 },
+}
+
+impl FeatureGen {
+    fn new(
+        spec: &FeatureSpec,
+        distribution: Distribution,
+        count: Option<CountDistribution>,
+    ) -> Result<Self> {
+        let key = &spec.key;
+        ensure_argument!(
+            distribution.value_type() == spec.value_type,
+            "the distribution of the feature '{}' draws {:?} values, but the spec declares {:?}",
+            key,
+            distribution.value_type(),
+            spec.value_type
+        );
+
+        let values = match distribution {
+            Distribution::UniformF32 { low, high } => {
+                ensure_argument!(
+                    low.is_finite() && high.is_finite() && low <= high,
+                    "invalid uniform bounds [{}, {}) of the feature '{}'",
+                    low,
+                    high,
+                    key
+                );
+                ValueGen::UniformF32 { low, high }
+            }
+            Distribution::NormalF32 { mean, std_dev } => {
+                ensure_argument!(
+                    mean.is_finite() && std_dev.is_finite() && std_dev >= 0.0,
+                    "invalid normal parameters mean={} std_dev={} of the feature '{}'",
+                    mean,
+                    std_dev,
+                    key
+                );
+                ValueGen::NormalF32 { mean, std_dev }
+            }
+            Distribution::UniformI64 { low, high } => {
+                ensure_argument!(
+                    low <= high,
+                    "invalid uniform bounds [{}, {}] of the feature '{}'",
+                    low,
+                    high,
+                    key
+                );
+                ValueGen::UniformI64 { low, high }
+            }
+            Distribution::Zipf { ranks, exponent } => {
+                ensure_argument!(
+                    (1..=MAX_ZIPF_RANKS).contains(&ranks),
+                    "the Zipf ranks of the feature '{}' must be in [1, {}]",
+                    key,
+                    MAX_ZIPF_RANKS
+                );
+                ensure_argument!(
+                    exponent.is_finite() && exponent >= 0.0,
+                    "invalid Zipf exponent {} of the feature '{}'",
+                    exponent,
+                    key
+                );
+                let cumulative = (1..=ranks)
+                    .scan(0.0, |total, rank| {
+                        *total += exp(-exponent * ln(rank as f64));
+                        Some(*total)
+                    })
+                    .collect();
+                ValueGen::Zipf { cumulative }
+            }
+            Distribution::RandomBytes { min_len, max_len } => {
+                ensure_argument!(
+                    min_len <= max_len,
+                    "invalid length bounds [{}, {}] of the feature '{}'",
+                    min_len,
+                    max_len,
+                    key
+                );
+                ValueGen::RandomBytes { min_len, max_len }
+            }
+            Distribution::Dictionary(entries) => {
+                ensure_argument!(
+                    !entries.is_empty(),
+                    "the dictionary of the feature '{}' is empty",
+                    key
+                );
+                ValueGen::Dictionary(entries)
+            }
+        };
+
+        let count = match (spec.count, count) {
+            (ValueCount::Fixed(len), None) => CountGen::Fixed(len),
+            (ValueCount::Fixed(_), Some(_)) => {
+                return Err(Error::invalid_argument(format!(
+                    "the feature '{}' has a fixed number of values",
+                    key
+                )))
+            }
+            (ValueCount::Var { min, max }, count) => {
+                let max = max.unwrap_or(min + 8);
+                match count {
+                    None => CountGen::Uniform { min, max },
+                    Some(CountDistribution::Uniform {
+                        min: lower,
+                        max: upper,
+                    }) => {
+                        ensure_argument!(
+                            lower <= upper,
+                            "invalid count bounds [{}, {}] of the feature '{}'",
+                            lower,
+                            upper,
+                            key
+                        );
+                        CountGen::Uniform {
+                            min: lower.clamp(min, max),
+                            max: upper.clamp(min, max),
+                        }
+                    }
+                    Some(CountDistribution::Geometric { mean }) => {
+                        ensure_argument!(
+                            mean.is_finite() && mean >= 0.0,
+                            "invalid geometric mean {} of the feature '{}'",
+                            mean,
+                            key
+                        );
+                        CountGen::Geometric { min, max, mean }
+                    }
+                }
+            }
+        };
+
+        Ok(Self {
+            key: key.clone(),
+            values,
+            count,
+        })
+    }
+
+    fn push_into(&self, example: &mut Example, rng: &mut SplitMix64) {
+        let count = self.count.draw(rng);
+        let key = self.key.clone();
+        match &self.values {
+            ValueGen::UniformF32 { low, high } => {
+                let values: Vec<_> = (0..count)
+                    .map(|_| (low + (high - low) * rng.next_f64()) as f32)
+                    .collect();
+                example.push_f32s(key, &values);
+            }
+            ValueGen::NormalF32 { mean, std_dev } => {
+                let values: Vec<_> = (0..count)
+                    .map(|_| (mean + std_dev * standard_normal(rng)) as f32)
+                    .collect();
+                example.push_f32s(key, &values);
+            }
+            ValueGen::UniformI64 { low, high } => {
+                let span = high.abs_diff(*low).wrapping_add(1);
+                let values: Vec<_> = (0..count)
+                    .map(|_| match span {
+                        // the full range of i64
+                        0 => rng.next_u64() as i64,
+                        span => low.wrapping_add(rng.next_below(span) as i64),
+                    })
+                    .collect();
+                example.push_i64s(key, &values);
+            }
+            ValueGen::Zipf { cumulative } => {
+                let total = cumulative[cumulative.len() - 1];
+                let values: Vec<_> = (0..count)
+                    .map(|_| {
+                        let target = rng.next_f64() * total;
+                        let rank = cumulative.partition_point(|&weight| weight <= target);
+                        rank.min(cumulative.len() - 1) as i64 + 1
+                    })
+                    .collect();
+                example.push_i64s(key, &values);
+            }
+            ValueGen::RandomBytes { min_len, max_len } => {
+                let values: Vec<_> = (0..count)
+                    .map(|_| {
+                        let len = min_len + rng.next_below((max_len - min_len) as u64 + 1) as usize;
+                        (0..len).map(|_| rng.next_u64() as u8).collect()
+                    })
+                    .collect();
+                example.push_bytes(key, values);
+            }
+            ValueGen::Dictionary(entries) => {
+                let values: Vec<_> = (0..count)
+                    .map(|_| entries[rng.next_below(entries.len() as u64) as usize].clone())
+                    .collect();
+                example.push_bytes(key, values);
+            }
+        }
+    }
+}
+
+impl CountGen {
+    fn draw(&self, rng: &mut SplitMix64) -> usize {
+        match *self {
+            Self::Fixed(len) => len,
+            Self::Uniform { min, max } => min + rng.next_below((max - min) as u64 + 1) as usize,
+            Self::Geometric { min, max, mean } => {
+                if mean == 0.0 {
+                    return min;
+                }
+                // the number of failures before the first success by inversion
+                let failure = mean / (1.0 + mean);
+                let uniform = 1.0 - rng.next_f64();
+                let count = (ln(uniform) / ln(failure)).floor();
+                (count.min(max as f64) as usize).clamp(min, max)
+            }
+        }
+    }
+}
+
+/// A standard normal number by the Marsaglia polar method.
+fn standard_normal(rng: &mut SplitMix64) -> f64 {
+    loop {
+        let u = 2.0 * rng.next_f64() - 1.0;
+        let v = 2.0 * rng.next_f64() - 1.0;
+        let s = u * u + v * v;
+        if s > 0.0 && s < 1.0 {
+            return u * (-2.0 * ln(s) / s).sqrt();
+        }
+    }
+}
+
+/// The natural logarithm of a positive normal number, computed from exactly rounded
+/// operations only.
+///
+/// The standard library delegates to the platform math library, whose results may
+/// differ in the last bits across platforms.
+fn ln(x: f64) -> f64 {
+    debug_assert!(x.is_normal() && x > 0.0);
+    // x = m * 2^e with m in [sqrt(1/2), sqrt(2))
+    let bits = x.to_bits();
+    let mut exponent = ((bits >> 52) & 0x7ff) as i64 - 1023;
+    let mut mantissa = f64::from_bits((bits & ((1 << 52) - 1)) | (1023 << 52));
+    if mantissa > std::f64::consts::SQRT_2 {
+        mantissa /= 2.0;
+        exponent += 1;
+    }
+    // ln(m) = 2 atanh(s) with |s| < 0.172
+    let s = (mantissa - 1.0) / (mantissa + 1.0);
+    let s2 = s * s;
+    let mut term = s;
+    let mut sum = 0.0;
+    for k in 0..24 {
+        sum += term / (2 * k + 1) as f64;
+        term *= s2;
+    }
+    2.0 * sum + exponent as f64 * std::f64::consts::LN_2
+}
+
+/// The exponential function computed from exactly rounded operations only.
+fn exp(x: f64) -> f64 {
+    if x < -745.0 {
+        return 0.0;
+    }
+    if x > 709.0 {
+        return f64::INFINITY;
+    }
+    // x = n ln(2) + r with |r| <= ln(2) / 2
+    let n = (x / std::f64::consts::LN_2).round();
+    let r = x - n * std::f64::consts::LN_2;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for k in 1..24 {
+        term *= r / k as f64;
+        sum += term;
+    }
+    // scale by 2^n in two steps, so that subnormal results are reachable
+    let half = (n as i64) / 2;
+    let pow2 = |e: i64| f64::from_bits(((e + 1023) as u64) << 52);
+    sum * pow2(half) * pow2(n as i64 - half)
+}
@@ -0,0 +1,182 @@
+//! Utilities for snapshot testing of written files.
+//!
+//! Files written by this crate may differ between runs for reasons unrelated to
+//! their content: event wall times default to the current time, and features of
+//! an [Example] are serialized in hash map order. The module provides the hooks
+//! to remove the nondeterminism.
+//!
+//! - Fixed clocks, such as [FixedClock] and [StepClock], are set to
+//!   [EventWriterConfig::with_clock](crate::EventWriterConfig::with_clock) to generate
+//!   deterministic wall times. They require the `proto-summary` feature.
+//! - The [canonical_encoding](crate::RecordWriterConfig::canonical_encoding) option
+//!   of record writers sorts the features by key.
+//! - [normalize_for_snapshot] re-frames an existing file in canonical form for comparison.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{
+//!     samples,
+//!     testing::{normalize_for_snapshot, FixedClock, SnapshotOptions, SnapshotRecordKind},
+//!     EventClock, EventWriter, EventWriterConfig, Example, ExampleWriter, Feature,
+//!     RecordWriterConfig,
+//! };
+//!
+//! // an empty temporary directory removed on drop
+//! let dataset = samples::tiny_dataset(0, 0)?;
+//! let dir = dataset.dir();
+//!
+//! let run = |name: &str| -> tfrecord::Result<(Vec<u8>, Vec<u8>)> {
+//!     // examples
+//!     let mut bytes = vec![];
+//!     let mut writer = ExampleWriter::from_writer_with_config(
+//!         &mut bytes,
+//!         RecordWriterConfig {
+//!             canonical_encoding: true,
+//!             ..Default::default()
+//!         },
+//!     )?;
+//!     let example: Example = (0..16)
+//!         .map(|index| (format!("feature_{}", index), Feature::from_i64_list(vec![index])))
+//!         .collect();
+//!     writer.send(example)?;
+//!     drop(writer);
+//!
+//!     // events
+//!     let path = dir.join(name);
+//!     let mut writer = EventWriter::create(
+//!         &path,
+//!         EventWriterConfig::default().with_clock(EventClock::new(FixedClock(1.0))),
+//!     )?;
+//!     writer.write_scalar("loss", 0, 0.5)?;
+//!     writer.write_scalar("loss", 1, 0.25)?;
+//!     drop(writer);
+//!
+//!     let events = normalize_for_snapshot(
+//!         &path,
+//!         SnapshotOptions {
+//!             record_kind: SnapshotRecordKind::Event,
+//!             zero_wall_time: false,
+//!         },
+//!     )?;
+//!     Ok((bytes, events))
+//! };
+//!
+//! assert_eq!(run("first")?, run("second")?);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{error::Result, protobuf::Example, record::Record, record_reader::BytesIter};
+#[cfg(feature = "proto-summary")]
+use crate::{event::WallClock, protobuf::Event};
+use std::path::Path;
+#[cfg(feature = "proto-summary")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "proto-summary")]
+/// A [WallClock] that always returns the same time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedClock(pub f64);
+
+#[cfg(feature = "proto-summary")]
+impl WallClock for FixedClock {
+    fn wall_time(&self) -> f64 {
+        self.0
+    }
+}
+
+#[cfg(feature = "proto-summary")]
+/// A [WallClock] that advances by a fixed step every time it is read.
+#[derive(Debug)]
+pub struct StepClock {
+    start: f64,
+    step: f64,
+    count: AtomicU64,
+}
+
+#[cfg(feature = "proto-summary")]
+impl StepClock {
+    /// Create a clock returning `start`, `start + step`, `start + 2 * step`, and so on.
+    pub fn new(start: f64, step: f64) -> Self {
+        Self {
+            start,
+            step,
+            count: AtomicU64::new(0),
+        }
+    }
+}
+
+#[cfg(feature = "proto-summary")]
+impl WallClock for StepClock {
+    fn wall_time(&self) -> f64 {
+        let count = self.count.fetch_add(1, Ordering::SeqCst);
+        self.start + self.step * count as f64
+    }
+}
+
+/// The record type assumed by [normalize_for_snapshot].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SnapshotRecordKind {
+    /// Keep the payload bytes untouched.
+    Bytes,
+    /// Decode records as [Example] and re-encode them canonically.
+    Example,
+    /// Decode records as [Event] and re-encode them.
+    #[cfg(feature = "proto-summary")]
+    Event,
+}
+
+/// Options for [normalize_for_snapshot].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SnapshotOptions {
+    pub record_kind: SnapshotRecordKind,
+    /// If set, the wall times of events are set to zero.
+    pub zero_wall_time: bool,
+}
+
+impl Default for SnapshotOptions {
+    fn default() -> Self {
+        Self {
+            record_kind: SnapshotRecordKind::Bytes,
+            zero_wall_time: false,
+        }
+    }
+}
+
+/// Re-frame the records of a file in a deterministic form.
+///
+/// Records are decoded according to the [record kind](SnapshotOptions::record_kind),
+/// re-encoded canonically and framed again. The returned bytes are suitable for
+/// byte-wise comparison against a stored snapshot.
+#[cfg_attr(not(feature = "proto-summary"), allow(unused_variables))]
+pub fn normalize_for_snapshot<P>(path: P, options: SnapshotOptions) -> Result<Vec<u8>>
+where
+    P: AsRef<Path>,
+{
+    let SnapshotOptions {
+        record_kind,
+        zero_wall_time,
+    } = options;
+    let mut output = vec![];
+
+    for bytes in BytesIter::open(path, Default::default())? {
+        let bytes = bytes?;
+        let bytes = match record_kind {
+            SnapshotRecordKind::Bytes => bytes,
+            SnapshotRecordKind::Example => {
+                Example::to_bytes_canonical(Example::from_bytes(bytes)?)?
+            }
+            #[cfg(feature = "proto-summary")]
+            SnapshotRecordKind::Event => {
+                let mut event = Event::from_bytes(bytes)?;
+                if zero_wall_time {
+                    event.wall_time = 0.0;
+                }
+                Event::to_bytes_canonical(event)?
+            }
+        };
+        crate::io::sync::try_write_record(&mut output, bytes)?;
+    }
+
+    Ok(output)
+}
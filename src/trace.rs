@@ -0,0 +1,257 @@
+//! Callback hooks tracing dataset operations as spans.
+//!
+//! [TraceHooks] are attached to operations by
+//! [RecordIndexerConfig::trace](crate::indexer::RecordIndexerConfig::trace) and
+//! [RecordReaderConfig::trace](crate::record_reader::RecordReaderConfig::trace). Each
+//! operation calls [on_operation_start](TraceHooks::on_operation_start) with its
+//! [Operation], the handle of its parent span and the start attributes, and calls
+//! [on_operation_end](TraceHooks::on_operation_end) with the returned handle and the
+//! outcome attributes once it finishes, fails or is dropped. The hooks are free to map
+//! spans onto any tracing system, such as OpenTelemetry.
+//!
+//! The operations, their nesting and their attributes are a stable contract.
+//!
+//! | Operation | Parent | Start attributes | Outcome attributes |
+//! |-----------|--------|------------------|--------------------|
+//! | [IndexDataset](Operation::IndexDataset) | - | `files` | `files`, `records`, `bytes` |
+//! | [IndexFile](Operation::IndexFile) | [IndexDataset](Operation::IndexDataset) | `path` | `records`, `bytes` |
+//! | [Stream](Operation::Stream) | - | `records` | `records`, `bytes` |
+//! | [VerifyManifest](Operation::VerifyManifest) | - | `files`, `policy` | `files`, `mismatches` |
+//! | [VerifyFile](Operation::VerifyFile) | [VerifyManifest](Operation::VerifyManifest) | `path` | `mismatch` |
+//!
+//! `files` and `records` count files and records, and `bytes` counts payload bytes.
+//! Outcome counts cover the work done before the span ended. A failed operation adds
+//! the `error` attribute with the message and the `error.code` attribute with the
+//! [ErrorCode](crate::ErrorCode) of the error. A verified file with a difference adds
+//! the `mismatch` attribute describing it.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use std::sync::{
+//!     atomic::{AtomicU64, Ordering},
+//!     Arc,
+//! };
+//! use tfrecord::{
+//!     indexer::{self, RecordIndexerConfig},
+//!     samples,
+//!     trace::{SpanHandle, TraceHooks},
+//! };
+//!
+//! let dataset = samples::tiny_dataset(2, 5)?;
+//! let next_id = Arc::new(AtomicU64::new(1));
+//! let hooks = TraceHooks {
+//!     on_operation_start: Some(Arc::new(move |operation, parent, attributes| {
+//!         let handle = SpanHandle(next_id.fetch_add(1, Ordering::Relaxed));
+//!         println!("start {} {:?} under {:?}: {:?}", operation, handle, parent, attributes);
+//!         handle
+//!     })),
+//!     on_operation_end: Some(Arc::new(|handle, attributes| {
+//!         println!("end {:?}: {:?}", handle, attributes);
+//!     })),
+//! };
+//! let config = RecordIndexerConfig {
+//!     trace: Some(hooks),
+//!     ..Default::default()
+//! };
+//! let indexes: Vec<_> = indexer::load_paths(dataset.paths(), config).collect::<Result<_, _>>()?;
+//! assert_eq!(indexes.len(), 10);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::Error;
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+/// A traced operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// Loading the record indexes of files by [load_paths](crate::indexer::load_paths)
+    /// and the functions built on it, or by their async counterparts.
+    IndexDataset,
+    /// Loading the record indexes of one file within a dataset.
+    IndexFile,
+    /// Reading records by [iter_from](crate::indexer::iter_from) or
+    /// [iter_permuted](crate::indexer::iter_permuted).
+    Stream,
+    /// Verifying the files of a [manifest](crate::manifest::Manifest).
+    VerifyManifest,
+    /// Verifying one file of a manifest.
+    VerifyFile,
+}
+
+impl Operation {
+    /// The name of the operation, prefixed by `tfrecord.`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::IndexDataset => "tfrecord.index_dataset",
+            Self::IndexFile => "tfrecord.index_file",
+            Self::Stream => "tfrecord.stream",
+            Self::VerifyManifest => "tfrecord.verify_manifest",
+            Self::VerifyFile => "tfrecord.verify_file",
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The value of a span attribute.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AttributeValue {
+    U64(u64),
+    Str(String),
+}
+
+impl fmt::Display for AttributeValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::U64(value) => write!(f, "{}", value),
+            Self::Str(value) => f.write_str(value),
+        }
+    }
+}
+
+/// A named span attribute.
+pub type Attribute = (&'static str, AttributeValue);
+
+/// The handle of a span returned by [on_operation_start](TraceHooks::on_operation_start).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SpanHandle(pub u64);
+
+/// The callback starting a span with the operation, the parent span and the start
+/// attributes.
+pub type StartHook =
+    dyn Fn(Operation, Option<SpanHandle>, &[Attribute]) -> SpanHandle + Send + Sync;
+
+/// The callback ending a span with the outcome attributes.
+pub type EndHook = dyn Fn(SpanHandle, &[Attribute]) + Send + Sync;
+
+/// The callbacks tracing operations.
+///
+/// Every started span is ended exactly once. Without a start hook, spans get the default
+/// handle. Hooks are called on the thread driving the operation, and are compared by
+/// identity.
+#[derive(Clone, Default)]
+pub struct TraceHooks {
+    pub on_operation_start: Option<Arc<StartHook>>,
+    pub on_operation_end: Option<Arc<EndHook>>,
+}
+
+impl fmt::Debug for TraceHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TraceHooks")
+            .field("on_operation_start", &self.on_operation_start.is_some())
+            .field("on_operation_end", &self.on_operation_end.is_some())
+            .finish()
+    }
+}
+
+impl PartialEq for TraceHooks {
+    fn eq(&self, other: &Self) -> bool {
+        addr(&self.on_operation_start) == addr(&other.on_operation_start)
+            && addr(&self.on_operation_end) == addr(&other.on_operation_end)
+    }
+}
+
+impl Eq for TraceHooks {}
+
+impl Hash for TraceHooks {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        addr(&self.on_operation_start).hash(state);
+        addr(&self.on_operation_end).hash(state);
+    }
+}
+
+/// The address of a hook, which identifies it.
+fn addr<T: ?Sized>(hook: &Option<Arc<T>>) -> Option<*const ()> {
+    hook.as_ref().map(|hook| Arc::as_ptr(hook) as *const ())
+}
+
+/// A started span, ended with its counts when dropped.
+pub(crate) struct Span {
+    hooks: TraceHooks,
+    handle: SpanHandle,
+    records: Option<(u64, u64)>,
+    attributes: Vec<Attribute>,
+    error: Option<(String, String)>,
+}
+
+impl Span {
+    /// Start a span if there are hooks.
+    pub(crate) fn start(
+        hooks: Option<&TraceHooks>,
+        operation: Operation,
+        parent: Option<SpanHandle>,
+        attributes: &[Attribute],
+    ) -> Option<Self> {
+        let hooks = hooks?;
+        let handle = match &hooks.on_operation_start {
+            Some(hook) => hook(operation, parent, attributes),
+            None => SpanHandle::default(),
+        };
+        Some(Self {
+            hooks: hooks.clone(),
+            handle,
+            records: None,
+            attributes: vec![],
+            error: None,
+        })
+    }
+
+    pub(crate) fn handle(&self) -> SpanHandle {
+        self.handle
+    }
+
+    /// Count a record of the payload length, reporting `records` and `bytes` on end.
+    pub(crate) fn count_record(&mut self, len: usize) {
+        let (records, bytes) = self.records.get_or_insert((0, 0));
+        *records += 1;
+        *bytes += len as u64;
+    }
+
+    /// Report the `records` and `bytes` counts on end, even if no record is counted.
+    pub(crate) fn with_counts(mut self) -> Self {
+        self.records.get_or_insert((0, 0));
+        self
+    }
+
+    /// Set an outcome attribute.
+    pub(crate) fn set(&mut self, name: &'static str, value: AttributeValue) {
+        match self.attributes.iter_mut().find(|(key, _)| *key == name) {
+            Some((_, prev)) => *prev = value,
+            None => self.attributes.push((name, value)),
+        }
+    }
+
+    /// Record the failure of the operation. Only the first error is kept.
+    pub(crate) fn fail(&mut self, error: &Error) {
+        self.error
+            .get_or_insert_with(|| (error.to_string(), error.code().to_string()));
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let Some(hook) = &self.hooks.on_operation_end else {
+            return;
+        };
+        let mut attributes = vec![];
+        if let Some((records, bytes)) = self.records {
+            attributes.push(("records", AttributeValue::U64(records)));
+            attributes.push(("bytes", AttributeValue::U64(bytes)));
+        }
+        attributes.append(&mut self.attributes);
+        if let Some((message, code)) = self.error.take() {
+            attributes.push(("error", AttributeValue::Str(message)));
+            attributes.push(("error.code", AttributeValue::Str(code)));
+        }
+        hook(self.handle, &attributes);
+    }
+}
@@ -1,6 +1,7 @@
 use std::{
     borrow::Cow,
     ffi::{OsStr, OsString},
+    io,
     path::{Path, PathBuf, MAIN_SEPARATOR},
 };
 
@@ -10,7 +11,7 @@ use crc::Crc;
 pub fn checksum(buf: &[u8]) -> u32 {
     const CASTAGNOLI: Crc<u32> = Crc::<u32>::new(&crc::CRC_32_ISCSI);
     let cksum = CASTAGNOLI.checksum(buf);
-    ((cksum >> 15) | (cksum << 17)).wrapping_add(0xa282ead8u32)
+    cksum.rotate_right(15).wrapping_add(0xa282ead8u32)
 }
 
 pub fn verify_checksum(buf: &[u8], expect: u32) -> Result<(), Error> {
@@ -18,10 +19,27 @@ pub fn verify_checksum(buf: &[u8], expect: u32) -> Result<(), Error> {
     if expect == found {
         Ok(())
     } else {
-        Err(Error::ChecksumMismatch { expect, found })
+        Err(Error::ChecksumMismatch {
+            expect,
+            found,
+            path: None,
+            offset: None,
+        })
     }
 }
 
+/// Sync a directory, making the entries in it durable.
+#[cfg(unix)]
+pub fn sync_dir(dir: &Path) -> io::Result<()> {
+    std::fs::File::open(dir)?.sync_all()
+}
+
+/// Directories cannot be synced on the platform, so it does nothing.
+#[cfg(not(unix))]
+pub fn sync_dir(_dir: &Path) -> io::Result<()> {
+    Ok(())
+}
+
 pub fn split_prefix<'a>(prefix: impl Into<Cow<'a, str>>) -> (PathBuf, OsString) {
     let prefix = prefix.into();
     if prefix.ends_with(MAIN_SEPARATOR) {
@@ -34,3 +52,41 @@ pub fn split_prefix<'a>(prefix: impl Into<Cow<'a, str>>) -> (PathBuf, OsString)
         (dir.to_owned(), file_name_prefix.to_owned())
     }
 }
+
+/// The SplitMix64 generator, which is stable across versions and platforms.
+#[derive(Debug, Clone)]
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A uniform integer in `0..bound` by Lemire's multiply-shift with rejection.
+    pub(crate) fn next_below(&mut self, bound: u64) -> u64 {
+        let threshold = bound.wrapping_neg() % bound;
+        loop {
+            let product = self.next_u64() as u128 * bound as u128;
+            if product as u64 >= threshold {
+                return (product >> 64) as u64;
+            }
+        }
+    }
+
+    /// A uniform float in `[0, 1)` with 53 random bits.
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Shuffle the slice by Fisher-Yates.
+    pub(crate) fn shuffle<T>(&mut self, slice: &mut [T]) {
+        for index in (1..slice.len()).rev() {
+            let other = self.next_below(index as u64 + 1) as usize;
+            slice.swap(index, other);
+        }
+    }
+}
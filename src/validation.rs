@@ -0,0 +1,630 @@
+//! Check examples and sequence examples against the conformance rules of the protos.
+//!
+//! The documentation of `example.proto` declares a data set conformant if
+//!
+//! - the features of a key have the same kind in every record,
+//! - the frames of a feature list have the same kind, and
+//! - for fixed-length feature lists, the frames have the same number of values.
+//!
+//! An [ExampleValidator] checks the features of examples, and a
+//! [SequenceExampleValidator] checks the context and the feature lists of sequence
+//! examples. Both expect kinds per key, and check keys without an expected kind for
+//! consistency within the record only. Validation fails with a [ValidationError] naming
+//! the key, the frame and the expected and found kinds.
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{
+//!     protobuf::{FeatureList, FeatureLists, SequenceExample},
+//!     schema::ValueType,
+//!     validation::{Location, SequenceExampleValidator, ValidationError},
+//!     Feature,
+//! };
+//!
+//! let audio = FeatureList {
+//!     feature: vec![
+//!         Feature::from_f32_list(vec![0.1, 0.2]),
+//!         Feature::from_i64_list(vec![3, 4]),
+//!     ],
+//! };
+//! let sequence_example = SequenceExample {
+//!     context: None,
+//!     feature_lists: Some(FeatureLists {
+//!         feature_list: [("audio".to_string(), audio)].into_iter().collect(),
+//!     }),
+//! };
+//!
+//! let validator = SequenceExampleValidator::new();
+//! assert_eq!(
+//!     validator.validate(&sequence_example),
+//!     Err(ValidationError::KindMismatch {
+//!         key: "audio".into(),
+//!         location: Location::Frame(1),
+//!         expected: ValueType::F32,
+//!         found: Some(ValueType::I64),
+//!     })
+//! );
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Data sets
+//!
+//! [validate_records], [validate_files] and, with the `async` feature,
+//! [validate_stream] check every record of a data set in order. Keys without an
+//! expected kind are expected to keep the kind first seen, so that inconsistencies
+//! across records are found. The violations are collected with their record indexes in
+//! a [ValidationReport].
+//!
+//! ```rust
+//! # fn main() -> tfrecord::Result<()> {
+//! use tfrecord::{
+//!     samples,
+//!     validation::{self, ExampleValidator, ValidationOptions},
+//! };
+//!
+//! let dataset = samples::tiny_dataset(2, 5)?;
+//! let report = validation::validate_files(
+//!     &ExampleValidator::new(),
+//!     dataset.paths(),
+//!     ValidationOptions::default(),
+//! )?;
+//! assert_eq!(report.num_records, 10);
+//! assert!(report.is_valid());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    limits::Limits,
+    protobuf::{feature::Kind, Example, Feature, SequenceExample},
+    record_reader::{BytesIter, RecordReaderConfig},
+    schema::ValueType,
+};
+use prost::Message as _;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+/// The place of a feature in a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Location {
+    /// A feature of an example.
+    Feature,
+    /// A context feature of a sequence example.
+    Context,
+    /// The frame at the index of a feature list of a sequence example.
+    Frame(usize),
+}
+
+/// A violation of a conformance rule.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ValidationError {
+    /// A feature has another kind than expected, or no kind.
+    KindMismatch {
+        key: String,
+        location: Location,
+        expected: ValueType,
+        found: Option<ValueType>,
+    },
+    /// A frame has another number of values than the first frame of its feature list.
+    FrameSizeMismatch {
+        key: String,
+        frame: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl ValidationError {
+    /// The key of the offending feature or feature list.
+    pub fn key(&self) -> &str {
+        match self {
+            Self::KindMismatch { key, .. } | Self::FrameSizeMismatch { key, .. } => key,
+        }
+    }
+
+    /// The index of the offending frame, or `None` outside feature lists.
+    pub fn frame(&self) -> Option<usize> {
+        match *self {
+            Self::KindMismatch {
+                location: Location::Frame(frame),
+                ..
+            }
+            | Self::FrameSizeMismatch { frame, .. } => Some(frame),
+            Self::KindMismatch { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::KindMismatch {
+                key,
+                location,
+                expected,
+                found,
+            } => {
+                match location {
+                    Location::Feature => write!(f, "the feature '{}'", key)?,
+                    Location::Context => write!(f, "the context feature '{}'", key)?,
+                    Location::Frame(frame) => {
+                        write!(f, "the frame {} of the feature list '{}'", frame, key)?
+                    }
+                }
+                match found {
+                    Some(found) => {
+                        write!(f, " expects {:?} values, but found {:?}", expected, found)
+                    }
+                    None => write!(f, " expects {:?} values, but found no kind", expected),
+                }
+            }
+            Self::FrameSizeMismatch {
+                key,
+                frame,
+                expected,
+                found,
+            } => write!(
+                f,
+                "the frame {} of the feature list '{}' has {} values, but the first frame has {}",
+                frame, key, found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// A validator of records of a data set, used by [validate_records] and
+/// [validate_files].
+pub trait Validator {
+    type Record;
+
+    /// Decode a serialized record, rejecting it if it exceeds the limits.
+    fn decode(bytes: &[u8], limits: &Limits) -> Result<Self::Record>;
+
+    /// All violations of the record, in the order of sorted keys and frames.
+    fn violations(&self, record: &Self::Record) -> Vec<ValidationError>;
+
+    /// Expect the kinds found in the record for keys without an expected kind.
+    fn adopt_kinds(&mut self, record: &Self::Record);
+}
+
+/// The validator of [Example]s checking the kinds of features.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ExampleValidator {
+    /// The expected kinds of features by key.
+    pub kinds: HashMap<String, ValueType>,
+}
+
+impl ExampleValidator {
+    /// A validator without expected kinds, which accepts every example.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expect the kind of the feature of the key.
+    pub fn with_kind<K>(mut self, key: K, kind: ValueType) -> Self
+    where
+        K: Into<String>,
+    {
+        self.kinds.insert(key.into(), kind);
+        self
+    }
+
+    /// Check the example, failing with the first violation in the order of keys.
+    pub fn validate(&self, example: &Example) -> Result<(), ValidationError> {
+        first(self.violations(example))
+    }
+
+    /// All violations of the example, in the order of keys.
+    pub fn violations(&self, example: &Example) -> Vec<ValidationError> {
+        let mut violations = vec![];
+        for (key, feature) in sorted(example.features.as_ref().map(|f| &f.feature)) {
+            check_kind(
+                &self.kinds,
+                key,
+                Location::Feature,
+                feature,
+                &mut violations,
+            );
+        }
+        violations
+    }
+}
+
+impl Validator for ExampleValidator {
+    type Record = Example;
+
+    fn decode(bytes: &[u8], limits: &Limits) -> Result<Example> {
+        Example::decode_with_limits(bytes, limits)
+    }
+
+    fn violations(&self, example: &Example) -> Vec<ValidationError> {
+        ExampleValidator::violations(self, example)
+    }
+
+    fn adopt_kinds(&mut self, example: &Example) {
+        let features = example.features.iter().flat_map(|f| &f.feature);
+        adopt(
+            &mut self.kinds,
+            features.map(|(key, f)| (key, ValueType::of(f))),
+        );
+    }
+}
+
+/// The validator of [SequenceExample]s checking the kinds of context features and
+/// feature lists, and optionally the sizes of frames.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SequenceExampleValidator {
+    /// The expected kinds of context features by key.
+    pub context_kinds: HashMap<String, ValueType>,
+    /// The expected kinds of the frames of feature lists by key.
+    pub feature_list_kinds: HashMap<String, ValueType>,
+    /// If set, the frames of each feature list must have the same number of values.
+    pub same_frame_sizes: bool,
+}
+
+impl SequenceExampleValidator {
+    /// A validator without expected kinds, which checks that the frames of each
+    /// feature list have the same kind.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expect the kind of the context feature of the key.
+    pub fn with_context_kind<K>(mut self, key: K, kind: ValueType) -> Self
+    where
+        K: Into<String>,
+    {
+        self.context_kinds.insert(key.into(), kind);
+        self
+    }
+
+    /// Expect the kind of the frames of the feature list of the key.
+    pub fn with_feature_list_kind<K>(mut self, key: K, kind: ValueType) -> Self
+    where
+        K: Into<String>,
+    {
+        self.feature_list_kinds.insert(key.into(), kind);
+        self
+    }
+
+    /// Require the frames of each feature list to have the same number of values.
+    pub fn with_same_frame_sizes(mut self, same_frame_sizes: bool) -> Self {
+        self.same_frame_sizes = same_frame_sizes;
+        self
+    }
+
+    /// Check the sequence example, failing with the first violation.
+    ///
+    /// The context is checked before the feature lists, in the order of keys.
+    pub fn validate(&self, sequence_example: &SequenceExample) -> Result<(), ValidationError> {
+        first(self.violations(sequence_example))
+    }
+
+    /// All violations of the sequence example, in the order of
+    /// [validate](Self::validate).
+    pub fn violations(&self, sequence_example: &SequenceExample) -> Vec<ValidationError> {
+        let mut violations = vec![];
+        let context = sequence_example.context.as_ref().map(|f| &f.feature);
+        for (key, feature) in sorted(context) {
+            check_kind(
+                &self.context_kinds,
+                key,
+                Location::Context,
+                feature,
+                &mut violations,
+            );
+        }
+
+        let lists = sequence_example
+            .feature_lists
+            .as_ref()
+            .map(|lists| &lists.feature_list);
+        for (key, list) in sorted(lists) {
+            let frames = &list.feature;
+            // frames are expected to have the kind of the first frame with a kind
+            let expected = self
+                .feature_list_kinds
+                .get(key)
+                .copied()
+                .or_else(|| frames.iter().find_map(ValueType::of));
+            let first_size = frames.first().map(num_values);
+
+            for (index, frame) in frames.iter().enumerate() {
+                let found = ValueType::of(frame);
+                if let Some(expected) = expected {
+                    if found != Some(expected) {
+                        violations.push(ValidationError::KindMismatch {
+                            key: key.clone(),
+                            location: Location::Frame(index),
+                            expected,
+                            found,
+                        });
+                        continue;
+                    }
+                }
+                if let (true, Some(first_size)) = (self.same_frame_sizes, first_size) {
+                    let size = num_values(frame);
+                    if size != first_size {
+                        violations.push(ValidationError::FrameSizeMismatch {
+                            key: key.clone(),
+                            frame: index,
+                            expected: first_size,
+                            found: size,
+                        });
+                    }
+                }
+            }
+        }
+        violations
+    }
+}
+
+impl Validator for SequenceExampleValidator {
+    type Record = SequenceExample;
+
+    fn decode(bytes: &[u8], _limits: &Limits) -> Result<SequenceExample> {
+        Ok(SequenceExample::decode(bytes)?)
+    }
+
+    fn violations(&self, sequence_example: &SequenceExample) -> Vec<ValidationError> {
+        SequenceExampleValidator::violations(self, sequence_example)
+    }
+
+    fn adopt_kinds(&mut self, sequence_example: &SequenceExample) {
+        let context = sequence_example.context.iter().flat_map(|f| &f.feature);
+        adopt(
+            &mut self.context_kinds,
+            context.map(|(key, f)| (key, ValueType::of(f))),
+        );
+        let lists = sequence_example
+            .feature_lists
+            .iter()
+            .flat_map(|lists| &lists.feature_list);
+        adopt(
+            &mut self.feature_list_kinds,
+            lists.map(|(key, list)| (key, list.feature.iter().find_map(ValueType::of))),
+        );
+    }
+}
+
+/// A violation found in a data set.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Violation {
+    /// The file of the record, set by [validate_files].
+    pub path: Option<PathBuf>,
+    /// The index of the record, counted per file by [validate_files].
+    pub record: u64,
+    pub error: ValidationError,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{}: ", path.display())?;
+        }
+        write!(f, "record {}: {}", self.record, self.error)
+    }
+}
+
+/// The violations of the records of a data set.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidationReport {
+    /// The number of checked records.
+    pub num_records: u64,
+    /// The number of violations, including those not kept.
+    pub num_violations: u64,
+    /// The first violations in the order found.
+    pub violations: Vec<Violation>,
+    /// The maximum number of violations kept.
+    pub max_violations: usize,
+}
+
+impl ValidationReport {
+    /// An empty report keeping up to `max_violations` violations.
+    pub fn new(max_violations: usize) -> Self {
+        Self {
+            num_records: 0,
+            num_violations: 0,
+            violations: vec![],
+            max_violations,
+        }
+    }
+
+    /// Returns true if no violation is found.
+    pub fn is_valid(&self) -> bool {
+        self.num_violations == 0
+    }
+
+    fn push<V>(&mut self, validator: &mut V, record: &V::Record, path: Option<&Path>, index: u64)
+    where
+        V: Validator,
+    {
+        let errors = validator.violations(record);
+        validator.adopt_kinds(record);
+        self.num_records += 1;
+        self.num_violations += errors.len() as u64;
+        let num_kept = self.max_violations.saturating_sub(self.violations.len());
+        self.violations
+            .extend(errors.into_iter().take(num_kept).map(|error| Violation {
+                path: path.map(Path::to_path_buf),
+                record: index,
+                error,
+            }));
+    }
+}
+
+impl Default for ValidationReport {
+    fn default() -> Self {
+        Self::new(100)
+    }
+}
+
+/// Options for [validate_files].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ValidationOptions {
+    /// The maximum number of violations kept across the data set.
+    pub max_violations: usize,
+    /// The configuration of the readers, whose limits also apply to decoding.
+    pub reader: RecordReaderConfig,
+}
+
+impl Default for ValidationOptions {
+    fn default() -> Self {
+        Self {
+            max_violations: 100,
+            reader: RecordReaderConfig::default(),
+        }
+    }
+}
+
+/// Check the records in order, keeping up to `max_violations` violations.
+///
+/// Keys without an expected kind are expected to keep the kind first seen. Errors of
+/// the records fail the validation.
+pub fn validate_records<V, I>(
+    validator: &V,
+    records: I,
+    max_violations: usize,
+) -> Result<ValidationReport>
+where
+    V: Validator + Clone,
+    I: IntoIterator<Item = Result<V::Record>>,
+{
+    let mut validator = validator.clone();
+    let mut report = ValidationReport::new(max_violations);
+    for (index, record) in records.into_iter().enumerate() {
+        report.push(&mut validator, &record?, None, index as u64);
+    }
+    Ok(report)
+}
+
+/// Check the records of a stream in order, like [validate_records].
+#[cfg(feature = "async")]
+pub async fn validate_stream<V, S>(
+    validator: &V,
+    records: S,
+    max_violations: usize,
+) -> Result<ValidationReport>
+where
+    V: Validator + Clone,
+    S: futures::stream::Stream<Item = Result<V::Record>>,
+{
+    use futures::stream::StreamExt as _;
+
+    let mut validator = validator.clone();
+    let mut report = ValidationReport::new(max_violations);
+    let mut records = Box::pin(records.enumerate());
+    while let Some((index, record)) = records.next().await {
+        report.push(&mut validator, &record?, None, index as u64);
+    }
+    Ok(report)
+}
+
+/// Check the records of the files in the order of files.
+///
+/// Keys without an expected kind are expected to keep the kind first seen in any file.
+/// Records failing to decode fail the validation with [Error::RecordFailed].
+pub fn validate_files<'a, V, I, P>(
+    validator: &V,
+    paths: I,
+    options: ValidationOptions,
+) -> Result<ValidationReport>
+where
+    V: Validator + Clone,
+    I: IntoIterator<Item = P>,
+    P: Into<Cow<'a, Path>>,
+{
+    let ValidationOptions {
+        max_violations,
+        reader,
+    } = options;
+    let mut validator = validator.clone();
+    let mut report = ValidationReport::new(max_violations);
+
+    for path in paths {
+        let path = path.into();
+        let file =
+            File::open(&path).map_err(|err| Error::from_io_with_context(err, &*path, None))?;
+        let records = BytesIter::from_reader(BufReader::new(file), reader.clone());
+        for (index, bytes) in records.enumerate() {
+            let index = index as u64;
+            let record = bytes
+                .and_then(|bytes| V::decode(&bytes, &reader.limits))
+                .map_err(|err| Error::RecordFailed {
+                    path: path.to_path_buf(),
+                    index,
+                    source: Box::new(err.with_io_context(&path, None)),
+                })?;
+            report.push(&mut validator, &record, Some(&path), index);
+        }
+    }
+    Ok(report)
+}
+
+fn first(violations: Vec<ValidationError>) -> Result<(), ValidationError> {
+    match violations.into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+fn sorted<V>(map: Option<&HashMap<String, V>>) -> Vec<(&String, &V)> {
+    let mut entries: Vec<_> = map.into_iter().flatten().collect();
+    entries.sort_unstable_by_key(|(key, _)| *key);
+    entries
+}
+
+fn check_kind(
+    kinds: &HashMap<String, ValueType>,
+    key: &str,
+    location: Location,
+    feature: &Feature,
+    violations: &mut Vec<ValidationError>,
+) {
+    let Some(&expected) = kinds.get(key) else {
+        return;
+    };
+    let found = ValueType::of(feature);
+    if found != Some(expected) {
+        violations.push(ValidationError::KindMismatch {
+            key: key.to_string(),
+            location,
+            expected,
+            found,
+        });
+    }
+}
+
+fn adopt<'a>(
+    kinds: &mut HashMap<String, ValueType>,
+    found: impl Iterator<Item = (&'a String, Option<ValueType>)>,
+) {
+    for (key, kind) in found {
+        if let Some(kind) = kind {
+            kinds.entry(key.clone()).or_insert(kind);
+        }
+    }
+}
+
+fn num_values(feature: &Feature) -> usize {
+    match &feature.kind {
+        Some(Kind::BytesList(list)) => list.value.len(),
+        Some(Kind::FloatList(list)) => list.value.len(),
+        Some(Kind::Int64List(list)) => list.value.len(),
+        None => 0,
+    }
+}
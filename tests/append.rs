@@ -0,0 +1,145 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::{
+    fs::{self, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+};
+use tfrecord::{indexer, samples, Example, ExampleIter, ExampleWriter};
+
+/// The size of the frame header preceding the payload offsets of record indexes.
+const HEADER_LEN: u64 = 12;
+
+fn read_examples(path: &std::path::Path) -> Result<Vec<Example>> {
+    Ok(ExampleIter::open(path, Default::default())?.collect::<Result<_, _>>()?)
+}
+
+#[test]
+fn append_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 3)?;
+    let path = &dataset.paths()[0];
+
+    let (mut writer, report) = ExampleWriter::append(path)?;
+    assert_eq!(report.num_records, 3);
+    assert!(!report.is_truncated());
+    assert_eq!(report.valid_len, fs::metadata(path)?.len());
+    writer.write_all((3..6).map(samples::example))?;
+    drop(writer);
+    assert_eq!(
+        read_examples(path)?,
+        (0..6).map(samples::example).collect::<Vec<_>>()
+    );
+
+    // a missing file is created
+    let created = dataset.dir().join("created.tfrecord");
+    let (mut writer, report) = ExampleWriter::append(&created)?;
+    assert_eq!(report, Default::default());
+    writer.send(samples::example(0))?;
+    drop(writer);
+    assert_eq!(read_examples(&created)?, vec![samples::example(0)]);
+    Ok(())
+}
+
+#[test]
+fn torn_write_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(0, 0)?;
+    let path = &dataset.dir().join("torn.tfrecord");
+    let write_records = || -> Result<()> {
+        let mut writer = ExampleWriter::create(path)?;
+        writer.write_all((0..4).map(samples::example))?;
+        Ok(())
+    };
+    write_records()?;
+    let indexes: Vec<_> =
+        indexer::load_file(path, Default::default())?.collect::<Result<_, _>>()?;
+    let full_len = fs::metadata(path)?.len();
+
+    // tear the last frame in its header, its payload and its footer
+    let last_start = indexes[3].offset - HEADER_LEN;
+    for torn_len in [last_start + 5, last_start + 14, full_len - 1] {
+        write_records()?;
+        OpenOptions::new()
+            .write(true)
+            .open(path)?
+            .set_len(torn_len)?;
+
+        let (mut writer, report) = ExampleWriter::append(path)?;
+        assert_eq!(report.num_records, 3);
+        assert_eq!(report.valid_len, last_start);
+        assert_eq!(report.truncated_len, torn_len - last_start);
+        writer.write_all((10..12).map(samples::example))?;
+        drop(writer);
+
+        let expect: Vec<_> = [0, 1, 2, 10, 11]
+            .into_iter()
+            .map(samples::example)
+            .collect();
+        assert_eq!(read_examples(path)?, expect);
+    }
+    Ok(())
+}
+
+#[test]
+fn corrupt_append_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 4)?;
+    let path = &dataset.paths()[0];
+    let indexes: Vec<_> =
+        indexer::load_file(path, Default::default())?.collect::<Result<_, _>>()?;
+    let len = fs::metadata(path)?.len();
+
+    // flip a payload byte of the second record
+    {
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+        let offset = indexes[1].offset + 1;
+        let mut byte = [0];
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut byte)?;
+        file.seek(SeekFrom::Start(offset))?;
+        file.write_all(&[byte[0] ^ 0xff])?;
+    }
+
+    let result = ExampleWriter::append(path);
+    assert!(matches!(
+        result,
+        Err(tfrecord::Error::ChecksumMismatch { offset: Some(offset), .. }) if offset == indexes[1].offset - HEADER_LEN
+    ));
+    assert_eq!(fs::metadata(path)?.len(), len);
+
+    // forcing truncates the file at the corrupt record
+    let (mut writer, report) = ExampleWriter::append_with_config(path, Default::default(), true)?;
+    assert_eq!(report.num_records, 1);
+    assert_eq!(report.truncated_len, len - indexes[1].offset + HEADER_LEN);
+    writer.send(samples::example(7))?;
+    drop(writer);
+    assert_eq!(
+        read_examples(path)?,
+        vec![samples::example(0), samples::example(7)]
+    );
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn async_append_test() -> Result<()> {
+    use tfrecord::ExampleAsyncWriter;
+
+    let dataset = samples::tiny_dataset(1, 3)?;
+    let path = &dataset.paths()[0];
+    let len = fs::metadata(path)?.len();
+    OpenOptions::new()
+        .write(true)
+        .open(path)?
+        .set_len(len - 2)?;
+
+    let (mut writer, report) = ExampleAsyncWriter::append(path).await?;
+    assert_eq!(report.num_records, 2);
+    assert!(report.is_truncated());
+    writer.send(samples::example(5)).await?;
+    writer.flush().await?;
+
+    let expect: Vec<_> = [0, 1, 5].into_iter().map(samples::example).collect();
+    assert_eq!(read_examples(path)?, expect);
+    Ok(())
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::{
+    fs::OpenOptions,
+    io::{Seek, SeekFrom, Write},
+    sync::{Arc, Mutex},
+};
+use tfrecord::{
+    indexer::{self, BatchSummary, ErrorPolicy, GuardedIndexes, RecordIndex, RecordResult},
+    samples::{self, TempDataset},
+    Error, Example,
+};
+
+const CORRUPTED: usize = 5;
+
+/// Write a file of 10 records and flip a payload byte of the record at [CORRUPTED].
+fn corrupted_dataset() -> Result<(TempDataset, Vec<RecordIndex>)> {
+    let dataset = samples::tiny_dataset(1, 10)?;
+    let indexes: Vec<_> =
+        indexer::load_paths(dataset.paths(), Default::default()).collect::<Result<_, _>>()?;
+
+    let index = &indexes[CORRUPTED];
+    let mut file = OpenOptions::new().write(true).open(&*index.path)?;
+    file.seek(SeekFrom::Start(index.offset + 1))?;
+    file.write_all(&[0xff])?;
+    Ok((dataset, indexes))
+}
+
+#[test]
+fn get_many_detailed() -> Result<()> {
+    let (_dataset, indexes) = corrupted_dataset()?;
+    let requests = [9, CORRUPTED, 100, 0, 4, 6];
+    let batch = indexer::get_many_detailed::<Example>(&indexes, &requests, Default::default());
+
+    assert_eq!(
+        batch.summary,
+        BatchSummary {
+            num_ok: 4,
+            num_missing: 1,
+            num_failed: 1,
+        }
+    );
+    for (&request, record) in requests.iter().zip(batch.records) {
+        match record {
+            RecordResult::Ok(example) => assert_eq!(example, samples::example(request)),
+            RecordResult::Missing => assert_eq!(request, 100),
+            RecordResult::Failed(error) => {
+                assert_eq!(request, CORRUPTED);
+                assert!(matches!(error, Error::ChecksumMismatch { .. }));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn guarded_get_many_detailed() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 10)?;
+    let mut indexes =
+        GuardedIndexes::load_paths(dataset.paths(), Default::default(), Default::default())?;
+    let batch = indexes.get_many_detailed::<Vec<u8>>(&[3, 10, 2]);
+    assert_eq!(
+        batch.summary,
+        BatchSummary {
+            num_ok: 2,
+            num_missing: 1,
+            num_failed: 0,
+        }
+    );
+    assert!(matches!(batch.records[1], RecordResult::Missing));
+    Ok(())
+}
+
+#[test]
+fn stream_error_policy() -> Result<()> {
+    let (_dataset, indexes) = corrupted_dataset()?;
+
+    // skip the corrupted record and report its index
+    let skipped = Arc::new(Mutex::new(vec![]));
+    let policy = {
+        let skipped = skipped.clone();
+        ErrorPolicy::skip(move |index, _| skipped.lock().unwrap().push(index))
+    };
+    let records: Vec<(usize, Example)> =
+        indexer::iter_from_with_policy(&indexes, 0, Default::default(), policy)
+            .collect::<Result<_, _>>()?;
+    assert_eq!(*skipped.lock().unwrap(), [CORRUPTED]);
+    assert_eq!(records.len(), 9);
+    for (index, example) in records {
+        assert_ne!(index, CORRUPTED);
+        assert_eq!(example, samples::example(index));
+    }
+
+    // end the stream at the corrupted record
+    let results: Vec<_> = indexer::iter_from_with_policy::<Example>(
+        &indexes,
+        2,
+        Default::default(),
+        ErrorPolicy::Fail,
+    )
+    .collect();
+    assert_eq!(results.len(), CORRUPTED - 2 + 1);
+    assert!(results[..CORRUPTED - 2].iter().all(|result| result.is_ok()));
+    assert!(results.last().unwrap().is_err());
+    Ok(())
+}
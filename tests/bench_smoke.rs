@@ -0,0 +1,142 @@
+//! Runs the benchmark workloads at a tiny scale, so that the bench code cannot rot.
+
+mod common;
+#[path = "../benches/workloads/mod.rs"]
+mod workloads;
+
+use common::*;
+use std::fs;
+use tfrecord::IntegrityMode;
+use workloads::{ensure_total, Scale};
+
+#[test]
+fn workloads_smoke_test() -> Result<()> {
+    let scale = Scale::smoke();
+    let dir = DATA_DIR.join("bench_smoke");
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+
+    // write
+    let records = workloads::records(scale.num_large_records, scale.large_record_len, 0);
+    let len = workloads::write_sequential(&records)?;
+    ensure_total(len, scale.num_large_records * (scale.large_record_len + 16))?;
+
+    // the generator is deterministic
+    assert_eq!(workloads::records(4, 13, 7), workloads::records(4, 13, 7));
+    assert_ne!(workloads::records(4, 13, 7), workloads::records(4, 13, 8));
+
+    // read
+    let paths = workloads::dataset(
+        &dir.join("read"),
+        1,
+        scale.num_small_records,
+        scale.small_record_len,
+        1,
+    )?;
+    let expect = scale.num_small_records * scale.small_record_len;
+    for integrity in [IntegrityMode::Full, IntegrityMode::Off] {
+        ensure_total(workloads::read_sequential(&paths, integrity)?, expect)?;
+        ensure_total(
+            workloads::read_sequential_reused(&paths, integrity)?,
+            expect,
+        )?;
+    }
+
+    // index and random access
+    let paths = workloads::dataset(
+        &dir.join("many_files"),
+        scale.num_small_files,
+        scale.records_per_small_file,
+        scale.small_record_len,
+        2,
+    )?;
+    let indexes = workloads::build_index(&paths)?;
+    assert_eq!(
+        indexes.len(),
+        scale.num_small_files * scale.records_per_small_file
+    );
+    let total = workloads::random_gets(&indexes, scale.num_random_gets, 4)?;
+    ensure_total(total, scale.num_random_gets * scale.small_record_len)?;
+
+    // synthetic examples
+    let total = workloads::synth_examples(scale.num_synth_records, 0)?;
+    ensure_total(total, scale.num_synth_records * 4)?;
+
+    // durable logging
+    fs::create_dir_all(dir.join("journal"))?;
+    let path = dir.join("journal").join("journal.tfrecord");
+    let count = workloads::journal_burst(
+        &path,
+        scale.num_journal_threads,
+        scale.journal_records_per_thread,
+    )?;
+    ensure_total(
+        count as usize,
+        scale.num_journal_threads * scale.journal_records_per_thread,
+    )?;
+    workloads::journal_trickle(&path, 2, std::time::Duration::ZERO)?;
+
+    // context-only decoding
+    let records =
+        workloads::sequence_records(scale.num_sequence_records, scale.frames_per_sequence, 0)?;
+    let total = workloads::decode_sequences(&records)?;
+    ensure_total(total, scale.num_sequence_records * 2)?;
+    ensure_total(workloads::decode_contexts(&records)?, total)?;
+
+    // columnar batches
+    let records = workloads::batch_records(scale.num_batch_records, 0)?;
+    let total = workloads::decode_hash_maps(&records, scale.batch_size)?;
+    ensure_total(
+        workloads::decode_columnar(&records, scale.batch_size)?,
+        total,
+    )?;
+
+    #[cfg(feature = "async")]
+    {
+        let total = async_std::task::block_on(workloads::stream_prefetch(&indexes, 4))?;
+        ensure_total(total, indexes.len() * scale.small_record_len)?;
+
+        let path = dir.join("examples").join("examples.tfrecord");
+        workloads::example_file(&path, scale.num_decode_records, 5)?;
+        for num_workers in [1, 4] {
+            let count = async_std::task::block_on(workloads::decode_parallel(&path, num_workers))?;
+            ensure_total(count, scale.num_decode_records)?;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn markdown_table_test() -> Result<()> {
+    let dir = DATA_DIR.join("bench_smoke_criterion");
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    for (id, mean) in [("write/small", 1500.0), ("read/full", 2.5e6)] {
+        let result_dir = dir.join(id).join("new");
+        fs::create_dir_all(&result_dir)?;
+        fs::write(
+            result_dir.join("benchmark.json"),
+            format!(r#"{{"full_id": "{}"}}"#, id),
+        )?;
+        fs::write(
+            result_dir.join("estimates.json"),
+            format!(
+                r#"{{"mean": {{"point_estimate": {}}}, "std_dev": {{"point_estimate": 12.0}}}}"#,
+                mean
+            ),
+        )?;
+    }
+    fs::create_dir_all(dir.join("report"))?;
+
+    let table = workloads::markdown_table(&dir)?;
+    assert_eq!(
+        table,
+        "| benchmark | mean | std. dev. |\n\
+         |---|---:|---:|\n\
+         | read/full | 2.50 ms | 12.00 ns |\n\
+         | write/small | 1.50 µs | 12.00 ns |\n"
+    );
+    Ok(())
+}
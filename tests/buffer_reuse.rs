@@ -0,0 +1,81 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use tfrecord::{
+    indexer::{self, RecordIndex},
+    samples, BytesIter, BytesWriter, Error, Example, ExampleIter, IntegrityMode, Record,
+    RecordReaderConfig,
+};
+
+#[test]
+fn read_into_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 5)?;
+    let path = &dataset.paths()[0];
+
+    let mut reader = ExampleIter::open(path, Default::default())?;
+    let mut buf = Vec::with_capacity(1024);
+    let ptr = buf.as_ptr();
+    let mut examples = vec![];
+    while let Some(len) = reader.read_into(&mut buf)? {
+        assert_eq!(len, buf.len());
+        examples.push(Example::from_slice(&buf)?);
+    }
+    // the buffer is not reallocated for records within its capacity
+    assert_eq!(buf.as_ptr(), ptr);
+    assert_eq!(examples, (0..5).map(samples::example).collect::<Vec<_>>());
+    assert_eq!(reader.read_into(&mut buf)?, None);
+
+    // the raw reads interleave with the iterator
+    let mut reader = BytesIter::open(path, Default::default())?;
+    let first = reader.next().unwrap()?;
+    assert_eq!(reader.read_into(&mut buf)?, Some(buf.len()));
+    assert_eq!(Example::from_slice(&buf)?, samples::example(1));
+    assert_eq!(Example::from_slice(&first)?, samples::example(0));
+    assert_eq!(reader.count(), 3);
+    Ok(())
+}
+
+#[test]
+fn read_into_checksum_test() -> Result<()> {
+    let mut bytes = vec![];
+    {
+        let mut writer = BytesWriter::from_writer(&mut bytes)?;
+        writer.send(b"first".to_vec())?;
+        writer.send(b"second".to_vec())?;
+    }
+    // corrupt the first payload
+    bytes[12] ^= 0xff;
+
+    let mut buf = vec![];
+    let mut reader = BytesIter::from_reader(bytes.as_slice(), Default::default());
+    let err = reader.read_into(&mut buf).unwrap_err();
+    assert!(matches!(err, Error::ChecksumMismatch { .. }), "{}", err);
+    // the reader continues after a checksum mismatch
+    assert_eq!(reader.read_into(&mut buf)?, Some(6));
+    assert_eq!(buf, b"second");
+
+    let config = RecordReaderConfig {
+        integrity: IntegrityMode::Off,
+        ..Default::default()
+    };
+    let mut reader = BytesIter::from_reader(bytes.as_slice(), config);
+    assert_eq!(reader.read_into(&mut buf)?, Some(5));
+    Ok(())
+}
+
+#[test]
+fn load_with_buf_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(2, 3)?;
+    let indexes: Vec<RecordIndex> =
+        indexer::load_paths(dataset.paths(), Default::default()).collect::<Result<_, _>>()?;
+
+    let mut buf = vec![];
+    for index in indexes.iter().rev() {
+        let example: Example = index.load_with_buf(&mut buf)?;
+        assert_eq!(example, index.load::<Example>()?);
+        assert_eq!(buf.len(), index.len);
+    }
+    Ok(())
+}
@@ -0,0 +1,173 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+};
+use tfrecord::{
+    cancel::{CancelFlag, Progress, CHECK_INTERVAL},
+    fingerprint::{self, FingerprintLevel},
+    indexer::{self, RecordIndexerConfig},
+    samples,
+    subset::{self, Budget, Stratify, SubsetSpec},
+    Error,
+};
+
+/// Raise the flag from another thread.
+fn cancel_from_thread(cancel: &CancelFlag) {
+    let cancel = cancel.clone();
+    thread::spawn(move || cancel.cancel()).join().unwrap();
+}
+
+fn expect_cancelled<T>(result: tfrecord::Result<T>) -> Result<Progress> {
+    match result {
+        Err(Error::Cancelled { progress }) => Ok(progress),
+        Err(err) => Err(format_err!("expect a cancellation, but found {:?}", err)),
+        Ok(_) => Err(format_err!(
+            "expect a cancellation, but the operation succeeded"
+        )),
+    }
+}
+
+#[test]
+fn cancel_indexing_at_file_boundary() -> Result<()> {
+    let dataset = samples::tiny_dataset(3, 100)?;
+    let cancel = CancelFlag::new();
+    let config = RecordIndexerConfig {
+        cancel: Some(cancel.clone()),
+        ..Default::default()
+    };
+
+    let mut indexes = indexer::load_paths(dataset.paths(), config);
+    for _ in 0..10 {
+        indexes.next().unwrap()?;
+    }
+    cancel_from_thread(&cancel);
+
+    // the rest of the first file is indexed before the check at the next file
+    let rest: Vec<_> = indexes.collect();
+    assert_eq!(rest.len(), 91);
+    assert!(rest[..90].iter().all(|index| index.is_ok()));
+    let progress = expect_cancelled(rest.into_iter().last().unwrap())?;
+    assert_eq!(
+        progress,
+        Progress {
+            files_done: 1,
+            records_processed: 100,
+            partial_outputs: vec![],
+            cleaned_up: true,
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn cancel_indexing_within_file() -> Result<()> {
+    let num_records = CHECK_INTERVAL * 2 + 10;
+    let dataset = samples::tiny_dataset(2, num_records as usize)?;
+    let cancel = CancelFlag::new();
+    let config = RecordIndexerConfig {
+        cancel: Some(cancel.clone()),
+        ..Default::default()
+    };
+
+    // raise the flag in the middle of the second file
+    let mut indexes = indexer::load_paths(dataset.paths(), config.clone());
+    for _ in 0..num_records + 5 {
+        indexes.next().unwrap()?;
+    }
+    cancel_from_thread(&cancel);
+    let progress = expect_cancelled(indexes.last().unwrap())?;
+    assert_eq!(progress.files_done, 1);
+    assert_eq!(progress.records_processed, num_records + CHECK_INTERVAL);
+
+    // an already raised flag cancels before any file
+    let progress = expect_cancelled(indexer::load_paths_filtered(
+        dataset.paths(),
+        config.clone(),
+        &[],
+    ))?;
+    assert_eq!((progress.files_done, progress.records_processed), (0, 0));
+    let progress = expect_cancelled(fingerprint::fingerprint_paths(
+        dataset.paths(),
+        FingerprintLevel::Content,
+        config,
+    ))?;
+    assert_eq!(progress.records_processed, 0);
+    Ok(())
+}
+
+#[test]
+fn cancel_subset_removes_partial_outputs() -> Result<()> {
+    let num_records = CHECK_INTERVAL * 2;
+    let dataset = samples::tiny_dataset(2, num_records as usize)?;
+    let cancel = CancelFlag::new();
+
+    // raise the flag from the key function in the middle of the second pass
+    let num_calls = Arc::new(AtomicU64::new(0));
+    let key = {
+        let cancel = cancel.clone();
+        let num_calls = num_calls.clone();
+        move |_: &[u8]| {
+            if num_calls.fetch_add(1, Ordering::SeqCst) == num_records * 2 + 100 {
+                cancel_from_thread(&cancel);
+            }
+            Ok(String::new())
+        }
+    };
+    let spec = SubsetSpec {
+        stratify: Some(Stratify::new(key)),
+        records_per_shard: Some(10),
+        cancel: Some(cancel),
+        ..SubsetSpec::new(Budget::Records(num_records))
+    };
+    let prefix = dataset.dir().join("dev");
+    let progress = expect_cancelled(subset::create_subset(
+        dataset.paths(),
+        prefix.to_str().unwrap(),
+        spec,
+    ))?;
+
+    // the second pass is cancelled at the next check in the first file
+    assert_eq!(progress.files_done, 2);
+    assert_eq!(progress.records_processed, num_records * 2 + CHECK_INTERVAL);
+    assert!(progress.cleaned_up);
+    assert!(!progress.partial_outputs.is_empty());
+    for path in &progress.partial_outputs {
+        assert!(path.starts_with(dataset.dir()));
+        assert!(!path.exists(), "{} is not removed", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn cancel_async_indexing() -> Result<()> {
+    use futures::stream::StreamExt as _;
+
+    let dataset = samples::tiny_dataset(3, 100)?;
+    let cancel = CancelFlag::new();
+    let config = RecordIndexerConfig {
+        cancel: Some(cancel.clone()),
+        ..Default::default()
+    };
+
+    let indexes = indexer::load_paths_async(dataset.paths(), config);
+    futures::pin_mut!(indexes);
+    for _ in 0..150 {
+        indexes.next().await.unwrap()?;
+    }
+    cancel_from_thread(&cancel);
+
+    let rest: Vec<_> = indexes.collect().await;
+    assert_eq!(rest.len(), 51);
+    let progress = expect_cancelled(rest.into_iter().last().unwrap())?;
+    assert_eq!((progress.files_done, progress.records_processed), (2, 200));
+    Ok(())
+}
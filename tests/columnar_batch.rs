@@ -0,0 +1,196 @@
+mod common;
+
+use common::*;
+use tfrecord::{
+    batch::{BatchDecoder, ColumnValues, RowLayout},
+    Example, ExampleIter, ExampleWriter, Feature,
+};
+
+fn example(index: usize) -> Example {
+    let mut example = Example::empty();
+    example.push_f32s("age", &[index as f32]);
+    example.push_f32s("position", &[index as f32, -(index as f32)]);
+    example.push_i64s("movie_ids", &(0..index as i64).collect::<Vec<_>>());
+    example.push_bytes("name", vec![format!("user-{}", index).into_bytes()]);
+    example
+}
+
+/// Replace or remove a feature of an example.
+fn with_feature(example: Example, key: &str, feature: Option<Feature>) -> Example {
+    let mut features = example.into_hash_map();
+    match feature {
+        Some(feature) => features.insert(key.into(), feature),
+        None => features.remove(key),
+    };
+    Example::from_iter(features)
+}
+
+fn decoder() -> BatchDecoder {
+    BatchDecoder::new()
+        .key::<f32>("age")
+        .key::<f32>("position")
+        .var_key::<i64>("movie_ids")
+        .key::<Vec<u8>>("name")
+}
+
+#[test]
+fn columnar_batch_test() -> Result<()> {
+    let (mut writer, buffer) = ExampleWriter::in_memory()?;
+    writer.write_all((0..7).map(example))?;
+    writer.flush()?;
+
+    let examples = ExampleIter::from_bytes(buffer.to_vec(), Default::default());
+    let batches: Vec<_> = decoder().collect(examples, 3)?.collect::<Result<_, _>>()?;
+    assert_eq!(
+        batches
+            .iter()
+            .map(|batch| (batch.num_rows, batch.partial))
+            .collect::<Vec<_>>(),
+        vec![(3, false), (3, false), (1, true)]
+    );
+
+    let batch = &batches[1];
+    let names: Vec<_> = batch.columns.iter().map(|column| &column.name).collect();
+    assert_eq!(names, ["age", "position", "movie_ids", "name"]);
+
+    let position = batch.column("position").unwrap();
+    assert_eq!(position.layout, RowLayout::Fixed { row_len: 2 });
+    assert_eq!(
+        position.f32s(),
+        Some(&[3.0, -3.0, 4.0, -4.0, 5.0, -5.0][..])
+    );
+    assert_eq!(position.row(2), Some(4..6));
+    assert_eq!(position.row(3), None);
+
+    let movie_ids = batch.column("movie_ids").unwrap();
+    assert_eq!(
+        movie_ids.layout,
+        RowLayout::Variable {
+            row_splits: vec![0, 3, 7, 12]
+        }
+    );
+    assert_eq!(movie_ids.values.len(), 12);
+    let row = movie_ids.row(1).unwrap();
+    assert_eq!(&movie_ids.i64s().unwrap()[row], &[0, 1, 2, 3]);
+    assert_eq!(movie_ids.f32s(), None);
+
+    let names = batch.column("name").unwrap().bytes().unwrap();
+    assert_eq!(
+        names.iter().collect::<Vec<_>>(),
+        vec![&b"user-3"[..], b"user-4", b"user-5"]
+    );
+    assert_eq!(names.get(2), Some(&b"user-5"[..]));
+    assert_eq!(names.get(3), None);
+    assert_eq!(names.data(), b"user-3user-4user-5");
+    assert_eq!(names.splits(), &[0, 6, 12, 18]);
+
+    // the first batch starts from the first row
+    let movie_ids = batches[0].column("movie_ids").unwrap();
+    assert_eq!(movie_ids.values, ColumnValues::I64(vec![0, 0, 1]));
+
+    // a full last batch is not partial
+    let batches: Vec<_> = decoder()
+        .collect((0..4).map(|index| Ok(example(index))), 2)?
+        .collect::<Result<_, _>>()?;
+    assert_eq!(batches.len(), 2);
+    assert!(batches.iter().all(|batch| !batch.partial));
+
+    // no examples, no batches
+    assert_eq!(decoder().collect(vec![], 2)?.count(), 0);
+    Ok(())
+}
+
+#[test]
+fn missing_feature_test() -> Result<()> {
+    let without_ids = with_feature(example(2), "movie_ids", None);
+    let batch = decoder()
+        .collect([Ok(example(1)), Ok(without_ids)], 4)?
+        .next()
+        .unwrap()?;
+    assert_eq!(
+        batch.column("movie_ids").unwrap().layout,
+        RowLayout::Variable {
+            row_splits: vec![0, 1, 1]
+        }
+    );
+
+    // fixed-length features are required
+    let without_age = with_feature(example(2), "age", None);
+    let err = decoder()
+        .collect([Ok(example(1)), Ok(without_age)], 4)?
+        .next()
+        .unwrap()
+        .unwrap_err();
+    assert!(err.to_string().contains("row 1"), "{}", err);
+    assert!(err.to_string().contains("'age' is missing"), "{}", err);
+    Ok(())
+}
+
+#[test]
+fn mismatch_test() -> Result<()> {
+    // a kind differing from the declared type
+    let other_kind = with_feature(
+        example(4),
+        "movie_ids",
+        Some(Feature::from_f32_list(vec![1.0])),
+    );
+    let mut batches = decoder().collect(
+        (0..3)
+            .map(|index| Ok(example(index)))
+            .chain([Ok(other_kind)]),
+        2,
+    )?;
+    assert!(batches.next().unwrap().is_ok());
+    let err = batches.next().unwrap().unwrap_err();
+    assert!(err.to_string().contains("row 3"), "{}", err);
+    assert!(err.to_string().contains("Int64List"), "{}", err);
+    // the first error ends the batches
+    assert!(batches.next().is_none());
+
+    // fixed-length features keep the number of values across batches
+    let longer = with_feature(
+        example(5),
+        "position",
+        Some(Feature::from_f32_list(vec![1.0, 2.0, 3.0])),
+    );
+    let results: Vec<_> = decoder()
+        .collect([Ok(example(0)), Ok(example(1)), Ok(longer)], 2)?
+        .collect();
+    assert!(results[0].is_ok());
+    let err = results[1].as_ref().unwrap_err();
+    assert!(
+        err.to_string().contains("expects 2 values, but get 3"),
+        "{}",
+        err
+    );
+
+    // errors of the examples are passed through
+    let results: Vec<_> = decoder()
+        .collect(
+            [Ok(example(0)), Err(std::io::Error::other("broken").into())],
+            2,
+        )?
+        .collect();
+    assert_eq!(results.len(), 1);
+    assert!(results[0].is_err());
+
+    // invalid declarations
+    assert!(decoder().collect(vec![], 0).is_err());
+    assert!(decoder().key::<i64>("age").collect(vec![], 1).is_err());
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn columnar_stream_test() -> Result<()> {
+    use futures::stream::{self, TryStreamExt as _};
+
+    let examples = stream::iter((0..5).map(|index| Ok(example(index))));
+    let batches: Vec<_> = decoder().collect_stream(examples, 2)?.try_collect().await?;
+    let expect: Vec<_> = decoder()
+        .collect((0..5).map(|index| Ok(example(index))), 2)?
+        .collect::<Result<_, _>>()?;
+    assert_eq!(batches, expect);
+    assert!(batches[2].partial);
+    Ok(())
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use common::*;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use tfrecord::{
+    fingerprint::{self, FingerprintLevel},
+    BytesIter, CommitStep, CommittedWriter, CommittedWriterConfig, MarkerFormat,
+};
+
+fn shard_path(name: &str) -> Result<PathBuf> {
+    let dir = make_temp_dir(&format!("committed_writer/{}", name))?;
+    Ok(dir.join(format!("{}.tfrecord", name)))
+}
+
+#[test]
+fn committed_writer_json_marker() -> Result<()> {
+    let path = shard_path("json")?;
+    let config = CommittedWriterConfig {
+        marker: MarkerFormat::Json {
+            fingerprint: FingerprintLevel::Content,
+        },
+        ..Default::default()
+    };
+    let mut writer = CommittedWriter::<Vec<u8>>::create(&path, config)?;
+    for index in 0..3u8 {
+        writer.send(vec![index; 10])?;
+    }
+    assert_eq!(writer.num_records(), 3);
+    let marker = writer.commit()?;
+    assert_eq!(marker, path.with_file_name("json.tfrecord.done"));
+
+    let expect_fingerprint = fingerprint::fingerprint_paths(
+        [path.as_path()],
+        FingerprintLevel::Content,
+        Default::default(),
+    )?;
+    let contents = fs::read_to_string(&marker)?;
+    assert_eq!(
+        contents,
+        format!(
+            "{{\"num_records\": 3, \"fingerprint\": \"{}\"}}\n",
+            expect_fingerprint
+        )
+    );
+    assert_eq!(BytesIter::open(&path, Default::default())?.count(), 3);
+    Ok(())
+}
+
+#[test]
+fn committed_writer_step_order() -> Result<()> {
+    let path = shard_path("order")?;
+    let config = CommittedWriterConfig {
+        marker_suffix: ".ready".into(),
+        ..Default::default()
+    };
+    let steps: Arc<Mutex<Vec<(CommitStep, PathBuf, bool)>>> = Default::default();
+    let marker_path = config.marker_path(&path);
+
+    let mut writer = {
+        let steps = steps.clone();
+        let marker_path = marker_path.clone();
+        CommittedWriter::<Vec<u8>>::create(&path, config)?.with_step_hook(
+            move |step, path: &Path| {
+                let marker_exists = marker_path.exists();
+                steps
+                    .lock()
+                    .unwrap()
+                    .push((step, path.to_owned(), marker_exists));
+            },
+        )
+    };
+    writer.send(b"record".to_vec())?;
+    assert_eq!(writer.commit()?, marker_path);
+    assert_eq!(fs::read(&marker_path)?, b"");
+
+    // the marker is written to a temporary file and renamed into place
+    let dir = path.parent().unwrap().to_owned();
+    let temp_path = dir.join(".order.tfrecord.ready.tmp");
+    let expect = vec![
+        (CommitStep::Finish, path.clone(), false),
+        (CommitStep::SyncShard, path.clone(), false),
+        (CommitStep::SyncDir, dir.clone(), false),
+        (CommitStep::WriteMarker, temp_path.clone(), false),
+        (CommitStep::SyncMarker, temp_path.clone(), false),
+        (CommitStep::PublishMarker, marker_path.clone(), true),
+        (CommitStep::SyncDir, dir.clone(), true),
+    ];
+    assert_eq!(*steps.lock().unwrap(), expect);
+    assert!(!temp_path.exists());
+    // the shard, its sidecar and the marker
+    assert_eq!(fs::read_dir(&dir)?.count(), 3);
+    Ok(())
+}
+
+#[test]
+fn committed_writer_without_commit_has_no_marker() -> Result<()> {
+    let path = shard_path("crash")?;
+    let marker_path = CommittedWriterConfig::default().marker_path(&path);
+
+    // a marker left by a previous run is removed before writing again
+    fs::write(&marker_path, b"")?;
+    let mut writer = CommittedWriter::<Vec<u8>>::create(&path, Default::default())?;
+    assert!(!marker_path.exists());
+    writer.send(b"record".to_vec())?;
+
+    // dropping the writer simulates a crash before the commit
+    drop(writer);
+    assert!(path.exists());
+    assert!(!marker_path.exists());
+
+    let config = CommittedWriterConfig {
+        marker_suffix: String::new(),
+        ..Default::default()
+    };
+    assert!(CommittedWriter::<Vec<u8>>::create(&path, config).is_err());
+    Ok(())
+}
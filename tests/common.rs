@@ -1,3 +1,4 @@
+#[allow(unused_imports)]
 pub use anyhow::{ensure, format_err, Error, Result};
 
 use once_cell::sync::Lazy;
@@ -22,12 +23,29 @@ pub static IMAGE_URLS: Lazy<Vec<String>> = Lazy::new(|| {
     .unwrap()
 });
 
+#[allow(dead_code)]
 pub static DATA_DIR: Lazy<&Path> = Lazy::new(|| {
     let path = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/test_data"));
     fs::create_dir_all(path).unwrap();
     path
 });
 
+/// Create an empty directory for a test under the system temp directory.
+///
+/// Directories are separated by the process id, so concurrent test runs do not collide.
+#[allow(dead_code)]
+pub fn make_temp_dir(name: &str) -> Result<PathBuf> {
+    let dir = std::env::temp_dir()
+        .join(format!("tfrecord-test-{}", std::process::id()))
+        .join(name);
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+#[allow(dead_code)]
 pub static INPUT_TFRECORD_PATH: Lazy<PathBuf> = Lazy::new(|| {
     (move || {
         let url = include_str!("tfrecord_link.txt");
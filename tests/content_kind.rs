@@ -0,0 +1,149 @@
+#![cfg(all(feature = "testing", feature = "proto-summary"))]
+
+mod common;
+
+use common::*;
+use std::{collections::HashMap, fs, path::PathBuf};
+use tfrecord::{
+    content::{self, ContentKind, KindMismatch},
+    indexer::{self, RecordIndexerConfig, SkipReason},
+    protobuf::{FeatureList, FeatureLists, SequenceExample},
+    samples::{self, TempDataset},
+    BytesWriter, Error, EventWriter, EventWriterConfig, Feature,
+};
+
+/// A dataset of two example files with an events file and a sequence example file
+/// written to the same directory.
+fn mixed_dataset() -> Result<(TempDataset, PathBuf, PathBuf)> {
+    let dataset = samples::tiny_dataset(2, 3)?;
+
+    let events_path = dataset.dir().join("events.out.tfevents.0");
+    let mut writer = EventWriter::create(&events_path, EventWriterConfig::default())?;
+    for step in 0..3 {
+        writer.write_scalar("loss", step, 1.0 / (step + 1) as f32)?;
+    }
+    drop(writer);
+
+    let sequence_path = dataset.dir().join("sequences.tfrecord");
+    let mut writer = BytesWriter::create(&sequence_path)?;
+    for index in 0..2 {
+        let frames = (0..3).map(|frame| Feature::from_i64_list(vec![index, frame]));
+        let sequence = SequenceExample {
+            context: samples::example(index as usize).features,
+            feature_lists: Some(FeatureLists {
+                feature_list: HashMap::from([(
+                    "frames".to_string(),
+                    FeatureList {
+                        feature: frames.collect(),
+                    },
+                )]),
+            }),
+        };
+        writer.send(sequence.encode_canonical_to_vec())?;
+    }
+    drop(writer);
+
+    Ok((dataset, events_path, sequence_path))
+}
+
+#[test]
+fn content_kind_of_mixed_directory() -> Result<()> {
+    let (dataset, events_path, sequence_path) = mixed_dataset()?;
+
+    for path in dataset.paths() {
+        assert_eq!(content::content_kind(path)?, ContentKind::ExampleData);
+    }
+    assert_eq!(
+        content::content_kind(&events_path)?,
+        ContentKind::EventsFile
+    );
+    assert_eq!(
+        content::content_kind(&sequence_path)?,
+        ContentKind::SequenceData
+    );
+
+    let empty_path = dataset.dir().join("empty.tfrecord");
+    fs::write(&empty_path, b"")?;
+    assert_eq!(content::content_kind(&empty_path)?, ContentKind::Unknown);
+
+    // raw bytes are neither examples nor events
+    let raw_path = dataset.dir().join("raw.tfrecord");
+    let mut writer = BytesWriter::create(&raw_path)?;
+    writer.send(b"not a protobuf message".to_vec())?;
+    drop(writer);
+    assert_eq!(content::content_kind(&raw_path)?, ContentKind::Unknown);
+    Ok(())
+}
+
+#[test]
+fn expect_kind_rejects_mismatched_files() -> Result<()> {
+    let (dataset, events_path, _) = mixed_dataset()?;
+    let mut paths = dataset.paths().to_vec();
+    paths.push(events_path.clone());
+
+    let config = RecordIndexerConfig {
+        expect_kind: Some(ContentKind::ExampleData),
+        ..Default::default()
+    };
+    let result: Result<Vec<_>, _> = indexer::load_paths(&paths, config.clone()).collect();
+    match result {
+        Err(Error::ContentKindMismatch {
+            path,
+            expected,
+            found,
+        }) => {
+            assert_eq!(path, events_path);
+            assert_eq!(expected, ContentKind::ExampleData);
+            assert_eq!(found, ContentKind::EventsFile);
+        }
+        result => panic!("expect a content kind mismatch, but found {:?}", result),
+    }
+
+    let result = indexer::load_paths_filtered(&paths, config, &[]);
+    assert!(matches!(result, Err(Error::ContentKindMismatch { .. })));
+    Ok(())
+}
+
+#[test]
+fn expect_kind_skips_mismatched_files() -> Result<()> {
+    let (dataset, events_path, sequence_path) = mixed_dataset()?;
+    let mut paths = dataset.paths().to_vec();
+    paths.push(events_path.clone());
+    paths.push(sequence_path.clone());
+
+    let config = RecordIndexerConfig {
+        expect_kind: Some(ContentKind::ExampleData),
+        skip_mismatched_kind: true,
+        ..Default::default()
+    };
+    let indexes: Vec<_> = indexer::load_paths(&paths, config.clone()).collect::<Result<_, _>>()?;
+    assert_eq!(indexes.len(), dataset.num_records());
+
+    let filtered = indexer::load_paths_filtered(&paths, config, &[])?;
+    assert_eq!(filtered.indexes, indexes);
+    let skipped: Vec<_> = filtered
+        .skipped
+        .iter()
+        .map(|skipped| (skipped.path.to_path_buf(), skipped.reason.clone()))
+        .collect();
+    assert_eq!(
+        skipped,
+        [
+            (
+                events_path,
+                SkipReason::KindMismatch(KindMismatch {
+                    expected: ContentKind::ExampleData,
+                    found: ContentKind::EventsFile,
+                })
+            ),
+            (
+                sequence_path,
+                SkipReason::KindMismatch(KindMismatch {
+                    expected: ContentKind::ExampleData,
+                    found: ContentKind::SequenceData,
+                })
+            ),
+        ]
+    );
+    Ok(())
+}
@@ -0,0 +1,187 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use prost::Message as _;
+use tfrecord::{
+    indexer::{self, RecordIndex},
+    protobuf::SequenceExample,
+    samples, ContextOnly, Error, Limits, ProstRecord, Record, RecordIter, RecordWriter,
+    SequenceExampleBuilder,
+};
+
+fn make_sequence_example(index: usize, num_frames: usize) -> Result<SequenceExample> {
+    let mut builder = SequenceExampleBuilder::new()
+        .context_i64s("user", &[index as i64])
+        .context_bytes("country", vec![format!("c{}", index % 3).into_bytes()]);
+    for frame in 0..num_frames {
+        builder = builder
+            .push_frame_f32s("clicks", &[frame as f32; 16])
+            .push_frame_i64s("items", &[frame as i64, index as i64]);
+    }
+    Ok(builder.build()?)
+}
+
+/// The length-delimited field of the tag.
+fn field(tag: u32, body: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![];
+    prost::encoding::encode_key(tag, prost::encoding::WireType::LengthDelimited, &mut bytes);
+    prost::encoding::encode_varint(body.len() as u64, &mut bytes);
+    bytes.extend_from_slice(body);
+    bytes
+}
+
+fn assert_matches_full(bytes: &[u8]) -> Result<()> {
+    let full = SequenceExample::decode(bytes)?;
+    let context_only = ContextOnly::decode(bytes)?;
+    assert_eq!(context_only.context, full.context.unwrap_or_default());
+    let feature_lists_len = full
+        .feature_lists
+        .map_or(0, |feature_lists| feature_lists.encoded_len());
+    assert_eq!(context_only.feature_lists_len, feature_lists_len as u64);
+    Ok(())
+}
+
+#[test]
+fn context_only_matches_full_decode_test() -> Result<()> {
+    for (index, num_frames) in [(0, 0), (1, 1), (2, 100)] {
+        let sequence = make_sequence_example(index, num_frames)?;
+        assert_matches_full(&sequence.encode_to_vec())?;
+        assert_matches_full(&sequence.encode_canonical_to_vec())?;
+    }
+    assert_matches_full(&[])?;
+
+    let sequence = make_sequence_example(7, 10)?;
+    let record = ContextOnly::decode(&sequence.encode_to_vec())?;
+    assert_eq!(record.context.get_i64s("user")?, &[7]);
+    assert_eq!(
+        record.context_feature("country"),
+        sequence.context_feature("country")
+    );
+    assert_eq!(record.context_feature("clicks"), None);
+
+    // encoding keeps the context only
+    let bytes = ContextOnly::to_bytes(record.clone())?;
+    let decoded = SequenceExample::decode(bytes.as_slice())?;
+    assert_eq!(decoded.context, sequence.context);
+    assert_eq!(decoded.feature_lists, None);
+    assert_eq!(ContextOnly::decode(&bytes)?.context, record.context);
+    Ok(())
+}
+
+#[test]
+fn context_only_field_order_test() -> Result<()> {
+    let sequence = make_sequence_example(3, 5)?;
+    let context = sequence.context.as_ref().unwrap().encode_to_vec();
+    let feature_lists = sequence.feature_lists.as_ref().unwrap().encode_to_vec();
+
+    // feature lists before the context
+    let reversed = [field(2, &feature_lists), field(1, &context)].concat();
+    assert_matches_full(&reversed)?;
+    assert_eq!(
+        ContextOnly::decode(&reversed)?.context,
+        sequence.context.clone().unwrap()
+    );
+
+    // split fields are merged, and unknown fields are skipped
+    let (head, tail) = sequence.context.as_ref().unwrap().feature.iter().fold(
+        (vec![], vec![]),
+        |(mut head, mut tail), (key, feature)| {
+            let features = tfrecord::protobuf::Features {
+                feature: [(key.clone(), feature.clone())].into_iter().collect(),
+            };
+            if key == "user" {
+                head = features.encode_to_vec();
+            } else {
+                tail = features.encode_to_vec();
+            }
+            (head, tail)
+        },
+    );
+    let split = [
+        field(1, &head),
+        field(2, &feature_lists),
+        field(9, b"unknown"),
+        field(1, &tail),
+        field(2, &feature_lists),
+    ]
+    .concat();
+    let record = ContextOnly::decode(&split)?;
+    assert_eq!(
+        Some(&record.context),
+        SequenceExample::decode(split.as_slice())?.context.as_ref()
+    );
+    assert_eq!(record.context, sequence.context.unwrap());
+    // every skipped occurrence is accounted
+    assert_eq!(record.feature_lists_len, 2 * feature_lists.len() as u64);
+
+    // truncated records fail
+    let truncated = &reversed[..reversed.len() - 1];
+    assert!(ContextOnly::decode(truncated).is_err());
+    Ok(())
+}
+
+#[test]
+fn context_only_limits_test() -> Result<()> {
+    let sequence = make_sequence_example(0, 3)?;
+    let bytes = sequence.encode_to_vec();
+    let limits = Limits {
+        max_features: 1,
+        ..Default::default()
+    };
+    let err = ContextOnly::decode_with_limits(&bytes, &limits).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            Error::LimitExceeded {
+                which: "max_features",
+                ..
+            }
+        ),
+        "{}",
+        err
+    );
+    // frames do not count as context features
+    let limits = Limits {
+        max_features: 2,
+        ..Default::default()
+    };
+    ContextOnly::decode_with_limits(&bytes, &limits)?;
+    Ok(())
+}
+
+#[test]
+fn context_only_stream_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 1)?;
+    let path = dataset.dir().join("sequences.tfrecord");
+    let sequences: Vec<_> = (0..10)
+        .map(|index| make_sequence_example(index, 20))
+        .collect::<Result<_>>()?;
+    let mut writer = RecordWriter::create(&path)?;
+    for sequence in &sequences {
+        writer.send(ProstRecord(sequence.clone()))?;
+    }
+    writer.flush()?;
+    drop(writer);
+
+    let records: Vec<ContextOnly> =
+        RecordIter::open(&path, Default::default())?.collect::<Result<_, _>>()?;
+    assert_eq!(records.len(), sequences.len());
+    for (record, sequence) in records.iter().zip(&sequences) {
+        assert_eq!(Some(&record.context), sequence.context.as_ref());
+    }
+
+    // records are streamed from indexes like any other record type
+    let indexes: Vec<RecordIndex> =
+        indexer::load_paths([&path], Default::default()).collect::<Result<_, _>>()?;
+    let streamed: Vec<(usize, ContextOnly)> =
+        indexer::iter_from(&indexes, 4, Default::default()).collect::<Result<_, _>>()?;
+    assert_eq!(streamed.len(), 6);
+    for (index, record) in streamed {
+        assert_eq!(record, records[index]);
+    }
+    let record: ContextOnly = indexes[9].load()?;
+    assert_eq!(record, records[9]);
+    Ok(())
+}
@@ -0,0 +1,244 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::{
+    fs::{self, OpenOptions},
+    io::{Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+use tfrecord::{
+    indexer::{self, RecordIndex, RecordIndexerConfig, SkippedRecord},
+    io::frame,
+    samples::{self, TempDataset},
+    Error, Example,
+};
+
+/// The offset of the frame of the index, which starts at the length field.
+fn frame_offset(index: &RecordIndex) -> u64 {
+    index.offset - 12
+}
+
+fn frame_len(index: &RecordIndex) -> u64 {
+    index.len as u64 + 16
+}
+
+fn flip_byte(path: &Path, offset: u64) -> Result<()> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut byte = [0];
+    std::io::Read::read_exact(&mut file, &mut byte)?;
+    file.seek(SeekFrom::Start(offset))?;
+    file.write_all(&[byte[0] ^ 0xff])?;
+    Ok(())
+}
+
+/// The layout of the frame of the index in the file.
+fn annotate(index: &RecordIndex) -> Result<frame::FrameLayout> {
+    let bytes = fs::read(&*index.path)?;
+    let frame = frame_offset(index) as usize;
+    Ok(frame::annotate(
+        &bytes[frame..frame + frame_len(index) as usize],
+    )?)
+}
+
+/// Two files of 5 records with a corrupted payload in record 2 of the first file, and a
+/// corrupted length checksum in record 1 of the second file.
+fn corrupted_dataset() -> Result<(TempDataset, Vec<RecordIndex>)> {
+    let dataset = samples::tiny_dataset(2, 5)?;
+    let indexes: Vec<_> =
+        indexer::load_paths(dataset.paths(), Default::default()).collect::<Result<_, _>>()?;
+    flip_byte(&indexes[2].path, indexes[2].offset + 1)?;
+    flip_byte(&indexes[6].path, frame_offset(&indexes[6]) + 8)?;
+    Ok((dataset, indexes))
+}
+
+fn skip_config() -> RecordIndexerConfig {
+    RecordIndexerConfig {
+        skip_corrupt: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn checksum_context_test() -> Result<()> {
+    let (dataset, indexes) = corrupted_dataset()?;
+    let layout = annotate(&indexes[2])?;
+
+    let err = indexer::load_paths(dataset.paths(), Default::default())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap_err();
+    let Error::ChecksumMismatch {
+        expect,
+        found,
+        path,
+        offset,
+    } = &err
+    else {
+        panic!("unexpected error: {}", err);
+    };
+    assert_eq!(path.as_deref(), Some(dataset.paths()[0].as_path()));
+    assert_eq!(*offset, Some(frame_offset(&indexes[2])));
+    assert_eq!(*expect, layout.payload_crc.value);
+    assert_eq!(*found, layout.payload_crc.computed);
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "[TFR0001] checksum mismatch error in {} at offset {}: expect {:#010x}, but found {:#010x}",
+            dataset.paths()[0].display(),
+            frame_offset(&indexes[2]),
+            expect,
+            found
+        )
+    );
+
+    // a corrupted length is located at its frame
+    let err = indexer::load_file(&dataset.paths()[1], Default::default())?
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap_err();
+    let layout = annotate(&indexes[6])?;
+    assert!(
+        matches!(
+            err,
+            Error::ChecksumMismatch { expect, found, offset: Some(offset), .. }
+                if offset == frame_offset(&indexes[6])
+                    && expect == layout.length_crc.value
+                    && found == layout.length_crc.computed
+        ),
+        "{}",
+        err
+    );
+
+    // so are the records read by the indexes
+    let err = indexer::iter_from::<Example>(&indexes, 0, Default::default())
+        .find_map(Result::err)
+        .unwrap();
+    assert!(
+        matches!(
+            &err,
+            Error::ChecksumMismatch { path: Some(path), offset: Some(offset), .. }
+                if path == &*indexes[2].path && *offset == frame_offset(&indexes[2])
+        ),
+        "{}",
+        err
+    );
+
+    // readers without files keep the bare error
+    let bytes = fs::read(&dataset.paths()[0])?;
+    let err = indexer::load_reader(std::io::Cursor::new(bytes), Default::default())
+        .find_map(Result::err)
+        .unwrap();
+    assert!(matches!(
+        err,
+        Error::ChecksumMismatch {
+            path: None,
+            offset: None,
+            ..
+        }
+    ));
+    Ok(())
+}
+
+#[test]
+fn skip_corrupt_test() -> Result<()> {
+    let (dataset, indexes) = corrupted_dataset()?;
+    let (loaded, report) = indexer::load_paths_with_report(dataset.paths(), skip_config());
+    let loaded: Vec<_> = loaded.collect::<Result<_, _>>()?;
+
+    let expect: Vec<_> = indexes
+        .iter()
+        .enumerate()
+        .filter(|&(index, _)| index != 2 && index != 6)
+        .map(|(_, index)| index.clone())
+        .collect();
+    assert_eq!(loaded, expect);
+
+    let payload_layout = annotate(&indexes[2])?;
+    let length_layout = annotate(&indexes[6])?;
+    assert_eq!(
+        report.records(),
+        [
+            SkippedRecord {
+                path: Some(PathBuf::from(&*indexes[2].path)),
+                offset: frame_offset(&indexes[2]),
+                num_bytes: frame_len(&indexes[2]),
+                expect: payload_layout.payload_crc.value,
+                found: payload_layout.payload_crc.computed,
+            },
+            // the next frame is found by scanning
+            SkippedRecord {
+                path: Some(PathBuf::from(&*indexes[6].path)),
+                offset: frame_offset(&indexes[6]),
+                num_bytes: frame_len(&indexes[6]),
+                expect: length_layout.length_crc.value,
+                found: length_layout.length_crc.computed,
+            },
+        ]
+    );
+
+    // the remaining records are intact
+    let examples: Vec<Example> = loaded
+        .iter()
+        .map(|index| index.load())
+        .collect::<Result<_, _>>()?;
+    assert_eq!(examples.len(), 8);
+    Ok(())
+}
+
+#[test]
+fn skip_corrupt_tail_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 3)?;
+    let path = &dataset.paths()[0];
+    let indexes: Vec<_> =
+        indexer::load_file(path, Default::default())?.collect::<Result<_, _>>()?;
+    // the length of the last record cannot be trusted, so the rest of the file is skipped
+    flip_byte(path, frame_offset(&indexes[2]) + 1)?;
+
+    let (loaded, report) = indexer::load_file_with_report(path, skip_config())?;
+    let loaded: Vec<_> = loaded.collect::<Result<_, _>>()?;
+    assert_eq!(loaded, indexes[..2]);
+    let skipped = report.records();
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0].offset, frame_offset(&indexes[2]));
+    assert_eq!(skipped[0].num_bytes, frame_len(&indexes[2]));
+
+    // records are skipped without a report as well
+    let loaded: Vec<_> = indexer::load_file(path, skip_config())?.collect::<Result<_, _>>()?;
+    assert_eq!(loaded, indexes[..2]);
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn checksum_context_async_test() -> Result<()> {
+    use futures::stream::TryStreamExt as _;
+
+    let (dataset, indexes) = corrupted_dataset()?;
+    let err = indexer::load_file_async(&dataset.paths()[1], Default::default())
+        .await?
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            &err,
+            Error::ChecksumMismatch { path: Some(path), offset: Some(offset), .. }
+                if path == &*indexes[6].path && *offset == frame_offset(&indexes[6])
+        ),
+        "{}",
+        err
+    );
+
+    // the async indexer does not skip records
+    let err = indexer::load_file_async(&dataset.paths()[1], skip_config())
+        .await
+        .err()
+        .unwrap();
+    assert!(
+        matches!(err, Error::InvalidArgumentsError { .. }),
+        "{}",
+        err
+    );
+    Ok(())
+}
@@ -0,0 +1,240 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use flate2::{write::GzEncoder, Compression};
+use std::{fs, io, io::Write as _, path::PathBuf, sync::Arc, time::Duration};
+use tfrecord::{
+    audit::{self, AuditOptions},
+    cancel::Progress,
+    content::ContentKind,
+    diagnostics,
+    indexer::{self, FileIdentity},
+    samples, Error, ErrorCode,
+};
+
+fn identity() -> FileIdentity {
+    FileIdentity {
+        len: 0,
+        modified: None,
+        inode: None,
+    }
+}
+
+#[test]
+fn error_codes_are_stable_test() {
+    let cases = [
+        (
+            Error::ChecksumMismatch {
+                expect: 0,
+                found: 1,
+                path: None,
+                offset: None,
+            },
+            "TFR0001",
+        ),
+        (Error::UnexpectedEof, "TFR0002"),
+        (
+            Error::ExampleDecodeError(prost::DecodeError::new("bad")),
+            "TFR0003",
+        ),
+        (Error::IoError(io::Error::other("bad")), "TFR0005"),
+        (
+            Error::from_io_with_context(io::Error::other("bad"), "a.tfrecord", None),
+            "TFR0005",
+        ),
+        (Error::ConversionError { desc: "bad".into() }, "TFR0006"),
+        (
+            Error::InvalidArgumentsError { desc: "bad".into() },
+            "TFR0007",
+        ),
+        (
+            Error::LimitExceeded {
+                which: "max_record_len",
+                limit: 1,
+                observed: 2,
+            },
+            "TFR0008",
+        ),
+        (
+            Error::Timeout {
+                operation: "read",
+                elapsed: Duration::from_secs(1),
+            },
+            "TFR0009",
+        ),
+        (
+            Error::WriterPoisoned {
+                original: Arc::new(Error::UnexpectedEof),
+            },
+            "TFR0010",
+        ),
+        (
+            Error::UnknownEnumValue {
+                name: "DataType",
+                value: -1,
+            },
+            "TFR0011",
+        ),
+        (
+            Error::Unsupported {
+                capability: "gzip".into(),
+                feature: None,
+            },
+            "TFR0012",
+        ),
+        (
+            Error::FileChanged {
+                path: "a.tfrecord".into(),
+                expected: identity(),
+                found: identity(),
+            },
+            "TFR0013",
+        ),
+        (
+            Error::ContentKindMismatch {
+                path: "a.tfrecord".into(),
+                expected: ContentKind::ExampleData,
+                found: ContentKind::EventsFile,
+            },
+            "TFR0014",
+        ),
+        (
+            Error::Cancelled {
+                progress: Progress::default(),
+            },
+            "TFR0015",
+        ),
+        (Error::ManifestMismatch { diffs: vec![] }, "TFR0016"),
+        (
+            Error::RecordFailed {
+                path: "a.tfrecord".into(),
+                index: 0,
+                source: Box::new(Error::UnexpectedEof),
+            },
+            "TFR0017",
+        ),
+        (
+            Error::VerificationFailed {
+                path: "a.tfrecord".into(),
+                index: 0,
+                reason: "bad".into(),
+            },
+            "TFR0018",
+        ),
+        (
+            Error::JoinBuildTooLarge {
+                limit: 1,
+                num_keys: 1,
+            },
+            "TFR0019",
+        ),
+        (
+            Error::FeatureChecksumMismatch {
+                keys: vec!["x".into()],
+            },
+            "TFR0020",
+        ),
+        (
+            Error::StaleAccumulator {
+                removed: vec![],
+                changed: vec![],
+            },
+            "TFR0021",
+        ),
+        (
+            Error::FeatureDecodeFailed {
+                key: "x".into(),
+                index: 0,
+                source: prost::DecodeError::new("bad"),
+            },
+            "TFR0024",
+        ),
+        (
+            Error::MissingShards {
+                spec: "train@2".into(),
+                num_shards: 2,
+                missing: vec![PathBuf::from("train-00001-of-00002")],
+            },
+            "TFR0025",
+        ),
+        (
+            Error::SchemaUnavailable {
+                schema_id: "clicks".into(),
+                source: Box::new(Error::UnexpectedEof),
+            },
+            "TFR0026",
+        ),
+        (
+            Error::WriteInterrupted {
+                committed: 3,
+                source: Box::new(Error::UnexpectedEof),
+            },
+            "TFR0027",
+        ),
+    ];
+    for (error, code) in cases {
+        assert_eq!(error.code().as_str(), code);
+        assert_eq!(error.code().to_string(), code);
+        assert_eq!(format!("TFR{:04}", error.code().number()), code);
+        let message = error.to_string();
+        assert!(message.starts_with(&format!("[{}] ", code)), "{}", message);
+    }
+}
+
+#[test]
+fn explain_gzip_file_test() -> Result<()> {
+    let dir = make_temp_dir("diagnostics_gzip")?;
+    let path = dir.join("compressed.tfrecord.gz");
+    let dataset = samples::tiny_dataset(1, 5)?;
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    encoder.write_all(&fs::read(&dataset.paths()[0])?)?;
+    fs::write(&path, encoder.finish()?)?;
+
+    let error = audit::wire_anomalies([path.as_path()], AuditOptions::default()).unwrap_err();
+    assert!(
+        matches!(error, Error::RecordFailed { index: 0, .. }),
+        "{}",
+        error
+    );
+    let diagnosis = diagnostics::explain(&error);
+    assert_eq!(diagnosis.code, ErrorCode::ChecksumMismatch);
+    assert!(diagnosis.likely_causes[0].contains("GZIP"));
+    assert!(diagnosis.suggested_actions[0].contains("decompress"));
+    Ok(())
+}
+
+#[test]
+fn explain_common_failures_test() -> Result<()> {
+    // a missing path
+    let path = PathBuf::from("/nonexistent/file.tfrecord");
+    let error = indexer::load_file(&path, Default::default()).err().unwrap();
+    let diagnosis = diagnostics::explain(&error);
+    assert_eq!(diagnosis.code, ErrorCode::Io);
+    assert_eq!(diagnosis.summary, "a file or directory does not exist");
+
+    // a truncated file
+    let diagnosis = diagnostics::explain(&Error::UnexpectedEof);
+    assert!(diagnosis.likely_causes[0].contains("truncated"));
+
+    // an events file read as examples
+    let diagnosis =
+        diagnostics::explain(&Error::ExampleDecodeError(prost::DecodeError::new("bad")));
+    assert!(diagnosis.likely_causes[0].contains("events file"));
+
+    // a record exceeding the length limit, wrapped with its location
+    let error = Error::RecordFailed {
+        path: "/nonexistent/file.tfrecord".into(),
+        index: 3,
+        source: Box::new(Error::LimitExceeded {
+            which: "max_record_len",
+            limit: 1,
+            observed: 2,
+        }),
+    };
+    let diagnosis = diagnostics::explain(&error);
+    assert_eq!(diagnosis.code, ErrorCode::LimitExceeded);
+    assert!(diagnosis.suggested_actions[0].contains("raise the limit"));
+    Ok(())
+}
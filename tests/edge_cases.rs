@@ -0,0 +1,201 @@
+//! Empty and tiny files across the API surface.
+
+#![cfg(all(feature = "testing", feature = "proto-summary"))]
+
+mod common;
+
+use common::*;
+use std::{fs, path::PathBuf};
+use tfrecord::{
+    content::{self, ContentKind},
+    fingerprint::{self, FingerprintLevel},
+    indexer::{self, GuardedIndexes, StableIndexes},
+    inspect,
+    samples::{self, TempDataset},
+    subset::{self, Budget, SubsetSpec},
+    BytesIter, BytesWriter, EventIter, EventWriter, EventWriterConfig, Example, ExampleIter,
+    ExampleWriter,
+};
+
+/// A dataset of an empty file, a file of one record and another empty file.
+fn sparse_dataset() -> Result<(TempDataset, Vec<PathBuf>)> {
+    let dataset = samples::tiny_dataset(1, 1)?;
+    let empty = |name: &str| -> Result<PathBuf> {
+        let path = dataset.dir().join(name);
+        fs::write(&path, b"")?;
+        Ok(path)
+    };
+    let paths = vec![
+        empty("empty-0.tfrecord")?,
+        dataset.paths()[0].clone(),
+        empty("empty-1.tfrecord")?,
+    ];
+    Ok((dataset, paths))
+}
+
+fn empty_dataset() -> Result<(TempDataset, Vec<PathBuf>)> {
+    let dataset = samples::tiny_dataset(0, 0)?;
+    let paths = (0..3)
+        .map(|index| {
+            let path = dataset.dir().join(format!("empty-{}.tfrecord", index));
+            fs::write(&path, b"")?;
+            Ok(path)
+        })
+        .collect::<Result<_>>()?;
+    Ok((dataset, paths))
+}
+
+#[test]
+fn empty_file_is_valid() -> Result<()> {
+    let (_dataset, paths) = empty_dataset()?;
+    let path = &paths[0];
+
+    assert_eq!(ExampleIter::open(path, Default::default())?.count(), 0);
+    assert_eq!(indexer::load_file(path, Default::default())?.count(), 0);
+    assert_eq!(content::content_kind(path)?, ContentKind::Unknown);
+    assert_eq!(inspect::producer_info(path)?, None);
+    Ok(())
+}
+
+#[test]
+fn all_empty_dataset_terminates() -> Result<()> {
+    let (_dataset, paths) = empty_dataset()?;
+
+    assert_eq!(indexer::load_paths(&paths, Default::default()).count(), 0);
+    let filtered = indexer::load_paths_filtered(&paths, Default::default(), &[])?;
+    assert_eq!(filtered.num_records(), 0);
+    let guarded = GuardedIndexes::load_paths(&paths, Default::default(), Default::default())?;
+    assert!(guarded.is_empty());
+    let stable = StableIndexes::load_paths(&paths, Default::default())?;
+    assert!(stable.is_empty());
+    assert_eq!(stable.iter::<Example>(Default::default()).count(), 0);
+    assert_eq!(
+        indexer::iter_from::<Example>(&[], 0, Default::default()).count(),
+        0
+    );
+
+    // empty files contribute nothing to the fingerprints
+    for level in [FingerprintLevel::Structure, FingerprintLevel::Content] {
+        let lhs = fingerprint::fingerprint_paths(&paths, level, Default::default())?;
+        let rhs = fingerprint::fingerprint(&[], level)?;
+        assert_eq!(lhs.level(), rhs.level());
+    }
+    Ok(())
+}
+
+#[test]
+fn sparse_dataset_skips_empty_files() -> Result<()> {
+    let (_dataset, paths) = sparse_dataset()?;
+
+    let indexes: Vec<_> =
+        indexer::load_paths(&paths, Default::default()).collect::<Result<_, _>>()?;
+    assert_eq!(indexes.len(), 1);
+    assert_eq!(*indexes[0].path, paths[1]);
+    let records: Vec<_> =
+        indexer::iter_from::<Example>(&indexes, 0, Default::default()).collect::<Result<_, _>>()?;
+    assert_eq!(records, [(0, samples::example(0))]);
+
+    let mut guarded = GuardedIndexes::load_paths(&paths, Default::default(), Default::default())?;
+    assert_eq!(guarded.len(), 1);
+    assert_eq!(guarded.get::<Example>(0)?, samples::example(0));
+    assert!(guarded.get::<Example>(1).is_err());
+
+    let stable = StableIndexes::load_paths(&paths, Default::default())?;
+    assert_eq!(
+        stable.find_by_stable_id(&stable.stable_id(0).unwrap()),
+        Some(0)
+    );
+    assert_eq!(stable.file_id(&paths[0]), None);
+    Ok(())
+}
+
+#[test]
+fn file_smaller_than_one_frame_is_truncated() -> Result<()> {
+    let dataset = samples::tiny_dataset(0, 0)?;
+    let path = dataset.dir().join("tiny.tfrecord");
+    fs::write(&path, [1, 0, 0])?;
+
+    assert!(BytesIter::open(&path, Default::default())?
+        .next()
+        .unwrap()
+        .is_err());
+    assert!(indexer::load_file(&path, Default::default())?
+        .next()
+        .unwrap()
+        .is_err());
+    assert_eq!(content::content_kind(&path)?, ContentKind::Unknown);
+    assert_eq!(inspect::producer_info(&path)?, None);
+    Ok(())
+}
+
+#[test]
+fn single_record_file() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 1)?;
+    let path = &dataset.paths()[0];
+
+    assert_eq!(content::content_kind(path)?, ContentKind::ExampleData);
+    assert_eq!(inspect::producer_info(path)?, None);
+    let records: Vec<_> = ExampleIter::open(path, Default::default())?.collect::<Result<_, _>>()?;
+    assert_eq!(records, [samples::example(0)]);
+    Ok(())
+}
+
+#[test]
+fn writers_without_records() -> Result<()> {
+    let dataset = samples::tiny_dataset(0, 0)?;
+
+    // record writers write an empty file
+    let path = dataset.dir().join("empty.tfrecord");
+    ExampleWriter::create(&path)?.flush()?;
+    assert_eq!(fs::metadata(&path)?.len(), 0);
+    assert_eq!(ExampleIter::open(&path, Default::default())?.count(), 0);
+
+    let (mut writer, buffer) = BytesWriter::in_memory()?;
+    writer.flush()?;
+    assert!(buffer.is_empty());
+
+    // event writers write the file version and the producer stamp only
+    let path = dataset.dir().join("events.tfevents");
+    let mut writer = EventWriter::create(&path, Default::default())?;
+    writer.flush()?;
+    drop(writer);
+    assert_eq!(EventIter::open(&path, Default::default())?.count(), 2);
+    assert!(inspect::producer_info(&path)?.is_some());
+    assert_eq!(content::content_kind(&path)?, ContentKind::EventsFile);
+
+    let path = dataset.dir().join("unstamped.tfevents");
+    let config = EventWriterConfig::default()
+        .with_file_version(false)
+        .with_stamp_producer(false);
+    EventWriter::create(&path, config)?.flush()?;
+    assert_eq!(fs::metadata(&path)?.len(), 0);
+    Ok(())
+}
+
+#[test]
+fn subset_of_empty_dataset() -> Result<()> {
+    let (dataset, paths) = empty_dataset()?;
+    let prefix = dataset.dir().join("subset");
+    let prefix = prefix.to_str().unwrap();
+
+    // a single empty shard is written
+    let report = subset::create_subset(&paths, prefix, SubsetSpec::new(Budget::Records(10)))?;
+    assert_eq!(report.num_records, 0);
+    assert_eq!(
+        report.paths,
+        [PathBuf::from(format!("{}-00000-of-00001", prefix))]
+    );
+    assert_eq!(fs::metadata(&report.paths[0])?.len(), 0);
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn all_empty_dataset_terminates_async() -> Result<()> {
+    use futures::stream::StreamExt as _;
+
+    let (_dataset, paths) = empty_dataset()?;
+    let indexes = indexer::load_paths_async(&paths, Default::default());
+    assert_eq!(indexes.count().await, 0);
+    Ok(())
+}
@@ -0,0 +1,160 @@
+#![cfg(feature = "encryption")]
+
+mod common;
+
+use common::*;
+use std::io::Cursor;
+use tfrecord::{
+    encryption::{
+        EncryptedReader, EncryptedWriter, EncryptionConfig, EncryptionKey, MAX_CHUNK_SIZE,
+    },
+    indexer, BytesIter, BytesWriter, Example, ExampleIter, ExampleWriter, Feature,
+};
+
+fn lookup(key_id: &str) -> tfrecord::Result<EncryptionKey> {
+    match key_id {
+        "key-1" => Ok(EncryptionKey::new([1; 32])),
+        "key-2" => Ok(EncryptionKey::new([2; 32])),
+        _ => Err(tfrecord::Error::ConversionError {
+            desc: format!("unknown key id {}", key_id).into(),
+        }),
+    }
+}
+
+fn make_records() -> Vec<Vec<u8>> {
+    (0..50).map(|index| vec![index as u8; index * 7]).collect()
+}
+
+fn encrypt(records: &[Vec<u8>], chunk_size: usize) -> Result<Vec<u8>> {
+    let writer = EncryptedWriter::new(
+        vec![],
+        "key-1",
+        &lookup("key-1")?,
+        EncryptionConfig { chunk_size },
+    )?;
+    let mut writer = BytesWriter::from_writer(writer)?;
+    for record in records {
+        writer.send(record.clone())?;
+    }
+    Ok(writer.into_inner().finish()?)
+}
+
+fn decrypt(bytes: &[u8]) -> tfrecord::Result<Vec<Vec<u8>>> {
+    let reader = EncryptedReader::new(Cursor::new(bytes), lookup)?;
+    BytesIter::from_reader(reader, Default::default()).collect()
+}
+
+#[test]
+fn encryption_round_trip_test() -> Result<()> {
+    let examples: Vec<Example> = (0..20)
+        .map(|index| {
+            [("value".to_string(), Feature::from_i64_list(vec![index]))]
+                .into_iter()
+                .collect()
+        })
+        .collect();
+    let path = make_temp_dir("encryption_round_trip")?.join("examples.tfrecord.enc");
+
+    {
+        let writer = EncryptedWriter::create(
+            &path,
+            "key-2",
+            &lookup("key-2")?,
+            EncryptionConfig { chunk_size: 100 },
+        )?;
+        let mut writer = ExampleWriter::from_writer(writer)?;
+        for example in &examples {
+            writer.send(example.clone())?;
+        }
+        writer.into_inner().finish()?;
+    }
+
+    let reader = EncryptedReader::open(&path, lookup)?;
+    assert_eq!(reader.key_id(), "key-2");
+    let output: Vec<_> =
+        ExampleIter::from_reader(reader, Default::default()).collect::<Result<_, _>>()?;
+    assert_eq!(output, examples);
+
+    // the framed records are not visible in the file
+    let bytes = std::fs::read(&path)?;
+    assert!(
+        ExampleIter::from_reader(bytes.as_slice(), Default::default())
+            .next()
+            .unwrap()
+            .is_err()
+    );
+
+    // empty stream
+    let bytes = encrypt(&[], 100)?;
+    assert!(decrypt(&bytes)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn encryption_tamper_test() -> Result<()> {
+    let records = make_records();
+    let bytes = encrypt(&records, 256)?;
+    assert_eq!(decrypt(&bytes)?, records);
+
+    // flip a byte in a middle chunk
+    let mut tampered = bytes.clone();
+    let index = tampered.len() / 2;
+    tampered[index] ^= 1;
+    assert!(decrypt(&tampered).is_err());
+
+    // drop the last chunk
+    let truncated = &bytes[..(bytes.len() - 100)];
+    assert!(decrypt(truncated).is_err());
+
+    // unknown key id
+    let mut bytes = bytes;
+    let key_id_pos = bytes.windows(5).position(|w| w == b"key-1").unwrap();
+    bytes[key_id_pos + 4] = b'9';
+    assert!(EncryptedReader::new(Cursor::new(&bytes), lookup).is_err());
+    Ok(())
+}
+
+#[test]
+fn encryption_oversized_chunk_test() -> Result<()> {
+    let bytes = encrypt(&make_records(), 256)?;
+
+    // the chunk size follows the magic and the version
+    let mut crafted = bytes;
+    crafted[9..13].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(matches!(
+        EncryptedReader::new(Cursor::new(&crafted), lookup),
+        Err(tfrecord::Error::LimitExceeded {
+            which: "chunk_size",
+            ..
+        })
+    ));
+
+    // the writer refuses it as well
+    assert!(EncryptedWriter::new(
+        vec![],
+        "key-1",
+        &lookup("key-1")?,
+        EncryptionConfig {
+            chunk_size: MAX_CHUNK_SIZE + 1
+        },
+    )
+    .is_err());
+    Ok(())
+}
+
+#[test]
+fn encryption_random_access_test() -> Result<()> {
+    let records = make_records();
+    let bytes = encrypt(&records, 64)?;
+
+    let mut reader = EncryptedReader::new(Cursor::new(&bytes), lookup)?;
+    let positions: Vec<_> =
+        indexer::load_reader(&mut reader, Default::default()).collect::<Result<_, _>>()?;
+    assert_eq!(positions.len(), records.len());
+
+    for (position, record) in positions.iter().zip(&records).rev() {
+        let output: Vec<u8> = position.load_from(&mut reader)?;
+        assert_eq!(&output, record);
+    }
+    Ok(())
+}
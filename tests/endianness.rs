@@ -0,0 +1,151 @@
+//! Wire fixtures of every byte-level encoder, hard-coded in little-endian.
+//!
+//! The fixtures do not depend on the host, so any native-endian assumption in the
+//! encoders fails these tests on big-endian machines, such as s390x.
+
+mod common;
+
+use common::*;
+use prost::Message as _;
+use tfrecord::{
+    protobuf::TensorProto, BytesIter, BytesWriter, Feature, TensorProtoElement, BOOLS_MARKER,
+};
+
+/// The record `b"abc"` framed by its length, the masked CRC-32C of the length bytes,
+/// the payload and the masked CRC-32C of the payload.
+const ABC_RECORD: [u8; 19] = [
+    3, 0, 0, 0, 0, 0, 0, 0, // length
+    0xb0, 0x99, 0x49, 0x0e, // length checksum
+    b'a', b'b', b'c', // payload
+    0x6e, 0x57, 0xf1, 0x21, // payload checksum
+];
+
+#[test]
+fn record_framing_test() -> Result<()> {
+    let mut bytes = vec![];
+    let mut writer = BytesWriter::from_writer(&mut bytes)?;
+    writer.send(b"abc".to_vec())?;
+    drop(writer);
+    assert_eq!(bytes, ABC_RECORD);
+
+    let records: Vec<_> =
+        BytesIter::from_reader(&ABC_RECORD[..], Default::default()).collect::<Result<_, _>>()?;
+    assert_eq!(records, [b"abc".to_vec()]);
+
+    // the length spans multiple bytes
+    let mut bytes = vec![];
+    let mut writer = BytesWriter::from_writer(&mut bytes)?;
+    writer.send(vec![0; 0x0102])?;
+    drop(writer);
+    assert_eq!(bytes[..8], [0x02, 0x01, 0, 0, 0, 0, 0, 0]);
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn async_record_framing_test() -> Result<()> {
+    use futures::TryStreamExt as _;
+    use tfrecord::{BytesAsyncWriter, BytesStream};
+
+    let mut bytes = vec![];
+    let mut writer = BytesAsyncWriter::from_writer(&mut bytes)?;
+    writer.send(b"abc".to_vec()).await?;
+    writer.flush().await?;
+    drop(writer);
+    assert_eq!(bytes, ABC_RECORD);
+
+    let records: Vec<_> = BytesStream::from_reader(&ABC_RECORD[..], Default::default())
+        .try_collect()
+        .await?;
+    assert_eq!(records, [b"abc".to_vec()]);
+    Ok(())
+}
+
+fn tensor_content<T: TensorProtoElement>(values: &[T]) -> Result<Vec<u8>> {
+    Ok(TensorProto::from_slice([values.len()], values)?.tensor_content)
+}
+
+#[test]
+fn tensor_content_test() -> Result<()> {
+    assert_eq!(tensor_content(&[1u8, 0xfe])?, [0x01, 0xfe]);
+    assert_eq!(tensor_content(&[-2i8])?, [0xfe]);
+    assert_eq!(tensor_content(&[0x0102u16])?, [0x02, 0x01]);
+    assert_eq!(tensor_content(&[-2i16])?, [0xfe, 0xff]);
+    assert_eq!(tensor_content(&[0x01020304u32])?, [0x04, 0x03, 0x02, 0x01]);
+    assert_eq!(tensor_content(&[-2i32])?, [0xfe, 0xff, 0xff, 0xff]);
+    assert_eq!(
+        tensor_content(&[0x0102030405060708u64])?,
+        [0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01]
+    );
+    assert_eq!(
+        tensor_content(&[-2i64])?,
+        [0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+    );
+    assert_eq!(
+        tensor_content(&[1.0f32, -2.0])?,
+        [0, 0, 0x80, 0x3f, 0, 0, 0, 0xc0]
+    );
+    assert_eq!(tensor_content(&[1.0f64])?, [0, 0, 0, 0, 0, 0, 0xf0, 0x3f]);
+    Ok(())
+}
+
+#[test]
+fn byte_slices_test() -> Result<()> {
+    // the lengths are varints followed by the contents
+    let proto = TensorProto::from_byte_slices([2usize], &[vec![b'x'; 300], b"ab".to_vec()])?;
+    assert_eq!(proto.tensor_content[..3], [0xac, 0x02, 0x02]);
+    assert_eq!(proto.tensor_content.len(), 3 + 302);
+    Ok(())
+}
+
+#[test]
+fn packed_bools_test() -> Result<()> {
+    let values: Vec<_> = (0..10).map(|index| index % 3 == 0).collect();
+    let feature = Feature::from_bools(&values)?;
+    assert_eq!(
+        feature.as_bytes_list().unwrap(),
+        [vec![BOOLS_MARKER, 10, 0, 0, 0, 0b0100_1001, 0b0000_0010]]
+    );
+    assert_eq!(feature.to_bools()?, values);
+    Ok(())
+}
+
+#[test]
+fn feature_wire_test() -> Result<()> {
+    // packed fixed32 floats
+    let feature = Feature::from_f32_list(vec![1.0, -2.0]);
+    let bytes = feature.encode_to_vec();
+    assert_eq!(
+        bytes,
+        [0x12, 0x0a, 0x0a, 0x08, 0, 0, 0x80, 0x3f, 0, 0, 0, 0xc0]
+    );
+    assert_eq!(Feature::decode(bytes.as_slice())?, feature);
+
+    // packed varint integers
+    let feature = Feature::from_i64_list(vec![1, 300]);
+    assert_eq!(
+        feature.encode_to_vec(),
+        [0x1a, 0x05, 0x0a, 0x03, 0x01, 0xac, 0x02]
+    );
+    Ok(())
+}
+
+#[cfg(feature = "encryption")]
+#[test]
+fn encryption_header_test() -> Result<()> {
+    use tfrecord::encryption::{EncryptedWriter, EncryptionConfig, EncryptionKey};
+
+    let writer = EncryptedWriter::new(
+        vec![],
+        "key",
+        &EncryptionKey::new([1; 32]),
+        EncryptionConfig { chunk_size: 0x0102 },
+    )?;
+    let bytes = writer.finish()?;
+
+    // the magic, the version and the chunk size
+    assert_eq!(bytes[..13], *b"TFRECENC\x01\x02\x01\x00\x00");
+    // the key id length after the 12-byte nonce, and the key id
+    assert_eq!(bytes[25..30], *b"\x03\x00key");
+    Ok(())
+}
@@ -0,0 +1,122 @@
+#![cfg(feature = "proto-summary")]
+
+use prost::Message as _;
+use tfrecord::{
+    protobuf::{
+        summary::Image, DataClass, DataType, FixedLenFeatureProto, SummaryMetadata, TensorProto,
+        VarLenFeatureProto, VariableAggregation, VariableDef, VariableSynchronization,
+    },
+    ColorSpace, Error,
+};
+
+#[test]
+fn tensor_dtype_test() {
+    let mut tensor = TensorProto::from_slice([2u32], &[1f32, 2.0]).unwrap();
+    assert_eq!(tensor.try_dtype().unwrap(), DataType::DtFloat);
+
+    tensor.set_dtype(DataType::DtDouble);
+    let tensor = TensorProto::decode(tensor.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(tensor.try_dtype().unwrap(), DataType::DtDouble);
+
+    let tensor = TensorProto {
+        dtype: 12345,
+        ..Default::default()
+    };
+    let tensor = TensorProto::decode(tensor.encode_to_vec().as_slice()).unwrap();
+    assert!(matches!(
+        tensor.try_dtype(),
+        Err(Error::UnknownEnumValue {
+            name: "DataType",
+            value: 12345
+        })
+    ));
+}
+
+#[test]
+fn feature_config_dtype_test() {
+    let mut fixed_len = FixedLenFeatureProto::default();
+    fixed_len.set_dtype(DataType::DtInt64);
+    assert_eq!(fixed_len.try_dtype().unwrap(), DataType::DtInt64);
+    fixed_len.dtype = -1;
+    assert!(matches!(
+        fixed_len.try_dtype(),
+        Err(Error::UnknownEnumValue { value: -1, .. })
+    ));
+
+    let mut var_len = VarLenFeatureProto::default();
+    var_len.set_dtype(DataType::DtString);
+    assert_eq!(var_len.try_dtype().unwrap(), DataType::DtString);
+    var_len.dtype = 999;
+    assert!(matches!(
+        var_len.try_dtype(),
+        Err(Error::UnknownEnumValue { value: 999, .. })
+    ));
+}
+
+#[test]
+fn summary_metadata_data_class_test() {
+    let mut metadata = SummaryMetadata::default();
+    metadata.set_data_class(DataClass::BlobSequence);
+    assert_eq!(metadata.try_data_class().unwrap(), DataClass::BlobSequence);
+    metadata.data_class = 4;
+    assert!(matches!(
+        metadata.try_data_class(),
+        Err(Error::UnknownEnumValue {
+            name: "DataClass",
+            value: 4
+        })
+    ));
+}
+
+#[test]
+fn variable_def_test() {
+    let mut variable = VariableDef::default();
+    variable.set_synchronization(VariableSynchronization::OnRead);
+    variable.set_aggregation(VariableAggregation::Mean);
+    assert_eq!(
+        variable.try_synchronization().unwrap(),
+        VariableSynchronization::OnRead
+    );
+    assert_eq!(
+        variable.try_aggregation().unwrap(),
+        VariableAggregation::Mean
+    );
+
+    variable.synchronization = 100;
+    variable.aggregation = 100;
+    assert!(variable.try_synchronization().is_err());
+    assert!(variable.try_aggregation().is_err());
+}
+
+#[test]
+fn image_color_space_test() {
+    let color_spaces = [
+        ColorSpace::Luma,
+        ColorSpace::LumaA,
+        ColorSpace::Rgb,
+        ColorSpace::Rgba,
+        ColorSpace::DigitalYuv,
+        ColorSpace::Bgra,
+    ];
+
+    for color_space in color_spaces {
+        let mut image = Image::default();
+        image.set_color_space(color_space);
+        let image = Image::decode(image.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(image.color_space().unwrap(), color_space);
+    }
+
+    for value in [0, 7, -3] {
+        let image = Image {
+            colorspace: value,
+            ..Default::default()
+        };
+        assert!(matches!(
+            image.color_space(),
+            Err(Error::UnknownEnumValue {
+                name: "ColorSpace",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,99 @@
+use flate2::{write::GzEncoder, Compression};
+use rand::{rngs::StdRng, Rng as _, SeedableRng as _};
+use std::io::Write as _;
+use tfrecord::{
+    estimate::{self, CompressionCodec, FramedLen as _, FramedSize, FRAME_OVERHEAD},
+    Example, ExampleWriter, Feature, Record as _,
+};
+
+struct Gzip;
+
+impl CompressionCodec for Gzip {
+    fn compressed_len(&self, bytes: &[u8]) -> tfrecord::Result<u64> {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(bytes)?;
+        Ok(encoder.finish()?.len() as u64)
+    }
+}
+
+fn make_examples() -> Vec<Example> {
+    let mut rng = StdRng::seed_from_u64(0);
+    (0..100)
+        .map(|index| {
+            let values: Vec<f32> = (0..index % 7).map(|_| rng.gen_range(0.0..1.0)).collect();
+            vec![
+                ("index".into(), Feature::from_i64_list(vec![index])),
+                ("values".into(), Feature::from_f32_list(values)),
+                (
+                    "name".into(),
+                    Feature::from_bytes_list(vec![b"sample".to_vec()]),
+                ),
+            ]
+            .into_iter()
+            .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn framed_size_test() -> tfrecord::Result<()> {
+    let examples = make_examples();
+    let path = std::env::temp_dir()
+        .join("tfrecord-estimate-test")
+        .join("examples.tfrecord");
+    std::fs::create_dir_all(path.parent().unwrap())?;
+
+    {
+        let mut writer = ExampleWriter::create(&path)?;
+        for example in examples.iter().cloned() {
+            writer.send(example)?;
+        }
+        writer.flush()?;
+    }
+    let file_size = std::fs::metadata(&path)?.len();
+
+    // exact sizes
+    assert_eq!(estimate::framed_size(&examples), file_size);
+    let mut size = FramedSize::default();
+    for example in &examples {
+        size.push(example);
+        let bytes = Example::to_bytes(example.clone())?;
+        assert_eq!(
+            example.encoded_len_framed(),
+            bytes.len() as u64 + FRAME_OVERHEAD
+        );
+        assert_eq!(bytes.encoded_len_framed(), example.encoded_len_framed());
+    }
+    assert_eq!(size.num_records, 100);
+    assert_eq!(size.num_bytes, file_size);
+    Ok(())
+}
+
+#[test]
+fn compression_estimate_test() -> tfrecord::Result<()> {
+    let records: Vec<_> = make_examples()
+        .into_iter()
+        .map(Example::to_bytes)
+        .collect::<Result<_, _>>()?;
+    let framed_size = estimate::framed_size(&records);
+
+    let mut framed = vec![];
+    for record in &records {
+        tfrecord::io::sync::try_write_record(&mut framed, record.clone())?;
+    }
+    let actual = Gzip.compressed_len(&framed)?;
+
+    // the estimate from a sample is close to the actual size
+    let estimate = estimate::estimate_with_codec(&records[..50], &Gzip)?;
+    assert!(estimate.ratio > 0.0 && estimate.ratio < 1.0);
+    assert!((0.0..=1.0).contains(&estimate.confidence));
+    let compressed_size = estimate.compressed_size(framed_size);
+    assert!(compressed_size > actual / 2 && compressed_size < actual * 2);
+
+    // a single record gives no confidence
+    let estimate = estimate::estimate_with_codec(&records[..1], &Gzip)?;
+    assert_eq!(estimate.confidence, 0.0);
+
+    assert!(estimate::estimate_with_codec(Vec::<Vec<u8>>::new(), &Gzip).is_err());
+    Ok(())
+}
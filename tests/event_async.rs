@@ -0,0 +1,102 @@
+#![cfg(all(feature = "async", feature = "testing"))]
+
+mod common;
+
+use async_std::{fs::File, io::BufWriter, sync::Mutex};
+use common::*;
+use std::{sync::Arc, time::Duration};
+use tfrecord::{
+    protobuf::event::What, samples, EventAsyncWriter, EventIter, EventWriterConfig, FlushPolicy,
+};
+
+const NUM_TASKS: usize = 4;
+const NUM_STEPS: i64 = 50;
+
+fn buffered() -> EventWriterConfig {
+    EventWriterConfig::default().with_auto_flush(false)
+}
+
+#[test]
+fn event_async_writer_is_send() {
+    fn assert_send<T: Send>() {}
+    assert_send::<EventAsyncWriter<BufWriter<File>>>();
+}
+
+#[async_std::test]
+async fn concurrent_tasks_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(0, 0)?;
+    let path = dataset.dir().join("concurrent.tfevents");
+    let writer = EventAsyncWriter::create(&path, buffered())
+        .await?
+        .with_flush_policy(FlushPolicy {
+            max_interval: Some(Duration::from_millis(5)),
+            ..Default::default()
+        });
+    let writer = Arc::new(Mutex::new(writer));
+
+    let tasks: Vec<_> = (0..NUM_TASKS)
+        .map(|task| {
+            let writer = writer.clone();
+            async_std::task::spawn(async move {
+                for step in 0..NUM_STEPS {
+                    let tag = format!("task-{}", task);
+                    writer
+                        .lock()
+                        .await
+                        .write_scalar(tag, step, step as f32)
+                        .await?;
+                    async_std::task::yield_now().await;
+                }
+                Ok::<_, tfrecord::Error>(())
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await?;
+    }
+    writer.lock().await.flush().await?;
+
+    // every step of every task is written, in the order of wall times
+    let mut steps = vec![vec![]; NUM_TASKS];
+    let mut last_wall_time = 0.0;
+    for event in EventIter::open(&path, Default::default())? {
+        let event = event?;
+        assert!(event.wall_time >= last_wall_time);
+        last_wall_time = event.wall_time;
+        if let Some(What::Summary(summary)) = &event.what {
+            let tag = &summary.value[0].tag;
+            let task: usize = tag.strip_prefix("task-").unwrap().parse()?;
+            steps[task].push(event.step);
+        }
+    }
+    for task_steps in steps {
+        assert_eq!(task_steps, (0..NUM_STEPS).collect::<Vec<_>>());
+    }
+    Ok(())
+}
+
+#[async_std::test]
+async fn flush_policy_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(0, 0)?;
+
+    // buffered events are written on explicit flushes
+    let path = dataset.dir().join("buffered.tfevents");
+    let mut writer = EventAsyncWriter::create(&path, buffered()).await?;
+    writer.write_scalar("loss", 0, 0.5).await?;
+    assert_eq!(std::fs::metadata(&path)?.len(), 0);
+    writer.flush().await?;
+    assert_eq!(EventIter::open(&path, Default::default())?.count(), 3);
+
+    // or once the policy is due
+    let path = dataset.dir().join("policy.tfevents");
+    let mut writer = EventAsyncWriter::create(&path, buffered())
+        .await?
+        .with_flush_policy(FlushPolicy {
+            max_unflushed_bytes: Some(1),
+            ..Default::default()
+        });
+    writer.write_scalar("loss", 0, 0.5).await?;
+    assert_eq!(EventIter::open(&path, Default::default())?.count(), 3);
+    writer.finish().await?;
+    Ok(())
+}
@@ -0,0 +1,56 @@
+#![cfg(all(feature = "testing", feature = "proto-summary"))]
+
+mod common;
+
+use common::*;
+use tfrecord::{
+    content::{self, ContentKind},
+    protobuf::event::What,
+    samples, EventIter, EventWriter, EventWriterConfig, FILE_VERSION,
+};
+
+#[test]
+fn event_file_convention_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(0, 0)?;
+    let log_dir = dataset.dir().join("log_dir");
+    let prefix = format!("{}{}", log_dir.display(), std::path::MAIN_SEPARATOR);
+    let mut writer = EventWriter::from_prefix(prefix, "", Default::default())?;
+    writer.write_scalar("loss", 0, 0.5)?;
+    writer.write_histogram("weights", 0, vec![0.1f64, 0.2, 0.2, 0.9])?;
+    drop(writer);
+
+    // events.out.tfevents.<unix_time>.<hostname>
+    let entries: Vec<_> = std::fs::read_dir(&log_dir)?.collect::<Result<_, _>>()?;
+    assert_eq!(entries.len(), 1);
+    let file_name = entries[0].file_name().into_string().unwrap();
+    let rest = file_name.strip_prefix("events.out.tfevents.").unwrap();
+    let (time, host_name) = rest.split_once('.').unwrap();
+    assert!(
+        time.bytes().all(|byte| byte.is_ascii_digit()),
+        "{}",
+        file_name
+    );
+    assert_eq!(host_name, hostname::get()?.to_string_lossy());
+
+    // the file version comes first
+    let path = entries[0].path();
+    let events: Vec<_> = EventIter::open(&path, Default::default())?.collect::<Result<_, _>>()?;
+    assert_eq!(events.len(), 4);
+    assert_eq!(events[0].what, Some(What::FileVersion(FILE_VERSION.into())));
+    assert_eq!(FILE_VERSION, "brain.Event:2");
+    assert_eq!(events[0].step, 0);
+    assert!(events[0].wall_time > 0.0);
+    assert!(matches!(events[3].what, Some(What::Summary(_))));
+    assert_eq!(content::content_kind(&path)?, ContentKind::EventsFile);
+
+    // the file version can be disabled
+    let config = EventWriterConfig::default().with_file_version(false);
+    let (mut writer, buffer) = EventWriter::in_memory(config)?;
+    writer.write_scalar("loss", 0, 0.5)?;
+    drop(writer);
+    let events: Vec<_> =
+        EventIter::from_bytes(buffer.to_vec(), Default::default()).collect::<Result<_, _>>()?;
+    assert_eq!(events.len(), 2);
+    assert!(matches!(events[0].what, Some(What::LogMessage(_))));
+    Ok(())
+}
@@ -0,0 +1,222 @@
+#![cfg(feature = "proto-summary")]
+
+mod common;
+
+use common::*;
+use tfrecord::{
+    export::ScalarSeries,
+    protobuf::{
+        event::What,
+        summary::{value::Value, Value as SummaryValue},
+        summary_metadata::PluginData,
+        DataClass, SummaryMetadata, TensorProto,
+    },
+    Error, Event, EventFileIter, EventReaderConfig, EventWriter, EventWriterConfig, Summary,
+};
+
+/// A scalar event written by TensorFlow 2.x, in a rank-0 `DT_FLOAT` tensor.
+fn tensor_scalar_event(tag: &str, step: i64, tensor: TensorProto) -> Event {
+    let value = SummaryValue {
+        tag: tag.into(),
+        metadata: Some(SummaryMetadata {
+            plugin_data: Some(PluginData {
+                plugin_name: "scalars".into(),
+                content: vec![],
+            }),
+            data_class: DataClass::Scalar as i32,
+            ..Default::default()
+        }),
+        value: Some(Value::Tensor(tensor)),
+        ..Default::default()
+    };
+    Event {
+        wall_time: 1000.0 + step as f64,
+        step,
+        what: Some(What::Summary(Summary { value: vec![value] })),
+    }
+}
+
+/// An event file of TensorFlow 1.x and 2.x scalars, a histogram and a non-scalar tensor.
+fn event_file() -> Result<Vec<u8>> {
+    let (mut writer, buffer) = EventWriter::in_memory(Default::default())?;
+    writer.write_scalar("train/loss", 0, 1.0)?;
+    writer.write_event(tensor_scalar_event(
+        "train/loss",
+        1,
+        TensorProto::from_vec(Vec::<usize>::new(), vec![0.5f32])?,
+    ))?;
+    writer.write_event(tensor_scalar_event(
+        "train/loss",
+        2,
+        TensorProto::from_slice(Vec::<usize>::new(), &[0.25f32])?,
+    ))?;
+    writer.write_scalar("eval/loss", 2, 0.75)?;
+    writer.write_histogram("train/weights", 2, vec![0.1f64, 0.2, 0.9])?;
+    writer.write_tensor(
+        "train/vector",
+        2,
+        TensorProto::from_vec([2usize], vec![1f32, 2.0])?,
+    )?;
+    drop(writer);
+    Ok(buffer.to_vec())
+}
+
+#[test]
+fn event_reader_test() -> Result<()> {
+    let bytes = event_file()?;
+
+    let mut events = EventFileIter::from_bytes(bytes.clone(), Default::default());
+    assert_eq!(events.file_version(), None);
+    let first = events.next().unwrap()?;
+    assert_eq!(first.what, Some(What::FileVersion("brain.Event:2".into())));
+    assert_eq!(events.file_version(), Some("brain.Event:2"));
+    assert_eq!(events.by_ref().count(), 7);
+    assert!(events.corrupt_tail().is_none());
+
+    // both scalar encodings, filtered by the tag prefix
+    let points: Vec<_> = EventFileIter::from_bytes(bytes.clone(), Default::default())
+        .scalars("train/")
+        .collect::<Result<_, _>>()?;
+    let points: Vec<_> = points
+        .into_iter()
+        .map(|(tag, point)| (tag, point.step, point.value))
+        .collect();
+    assert_eq!(
+        points,
+        vec![
+            ("train/loss".into(), 0, 1.0),
+            ("train/loss".into(), 1, 0.5),
+            ("train/loss".into(), 2, 0.25),
+        ]
+    );
+    let (_, point) = EventFileIter::from_bytes(bytes.clone(), Default::default())
+        .scalars("train/")
+        .nth(1)
+        .unwrap()?;
+    assert_eq!(point.wall_time, 1001.0);
+
+    let histograms: Vec<_> = EventFileIter::from_bytes(bytes.clone(), Default::default())
+        .histograms("")
+        .collect::<Result<_, _>>()?;
+    assert_eq!(histograms.len(), 1);
+    let (tag, point) = &histograms[0];
+    assert_eq!(tag, "train/weights");
+    assert_eq!(point.step, 2);
+    assert_eq!(point.histogram.num, 3.0);
+
+    // the export reads the tensor scalars as well
+    let series = ScalarSeries::from_events(EventFileIter::from_bytes(bytes, Default::default()))?;
+    let lengths: Vec<_> = series
+        .iter()
+        .map(|series| (series.tag.as_str(), series.points.len()))
+        .collect();
+    assert_eq!(lengths, vec![("train/loss", 3), ("eval/loss", 1)]);
+    Ok(())
+}
+
+#[test]
+fn corrupt_tail_test() -> Result<()> {
+    let bytes = event_file()?;
+    let num_events = EventFileIter::from_bytes(bytes.clone(), Default::default()).count();
+
+    // a truncated record ends the events
+    let truncated = bytes[..bytes.len() - 5].to_vec();
+    let mut events = EventFileIter::from_bytes(truncated.clone(), Default::default());
+    let events_read: Vec<_> = events.by_ref().collect::<Result<_, _>>()?;
+    assert_eq!(events_read.len(), num_events - 1);
+    assert!(events.corrupt_tail().is_some());
+    assert!(events.next().is_none());
+
+    // or fails the reader if asked to
+    let config = EventReaderConfig {
+        fail_on_corrupt: true,
+        ..Default::default()
+    };
+    let results: Vec<_> = EventFileIter::from_bytes(truncated, config.clone()).collect();
+    assert_eq!(results.len(), num_events);
+    assert!(results[..num_events - 1]
+        .iter()
+        .all(|result| result.is_ok()));
+    assert!(results[num_events - 1].is_err());
+
+    // a damaged payload fails the checksum
+    let mut damaged = bytes;
+    let len = damaged.len();
+    damaged[len - 6] ^= 0xff;
+    let mut events = EventFileIter::from_bytes(damaged.clone(), Default::default());
+    assert_eq!(events.by_ref().count(), num_events - 1);
+    assert!(matches!(
+        events.corrupt_tail(),
+        Some(Error::ChecksumMismatch { .. })
+    ));
+    let last = EventFileIter::from_bytes(damaged, config)
+        .last()
+        .unwrap()
+        .unwrap_err();
+    assert!(matches!(last, Error::ChecksumMismatch { .. }));
+    Ok(())
+}
+
+#[test]
+fn file_version_test() -> Result<()> {
+    let config = EventWriterConfig::default().with_file_version(false);
+    let (mut writer, buffer) = EventWriter::in_memory(config)?;
+    writer.write_scalar("loss", 0, 0.5)?;
+    drop(writer);
+    let bytes = buffer.to_vec();
+
+    let results: Vec<_> = EventFileIter::from_bytes(bytes.clone(), Default::default()).collect();
+    assert_eq!(results.len(), 1);
+    let err = results[0].as_ref().unwrap_err();
+    assert!(err.to_string().contains("file version"), "{}", err);
+
+    // the check can be disabled
+    let config = EventReaderConfig {
+        check_file_version: false,
+        ..Default::default()
+    };
+    let mut events = EventFileIter::from_bytes(bytes, config);
+    assert_eq!(events.by_ref().count(), 2);
+    assert_eq!(events.file_version(), None);
+
+    // an empty file has no events
+    assert_eq!(
+        EventFileIter::from_bytes(vec![], Default::default()).count(),
+        0
+    );
+    Ok(())
+}
+
+#[cfg(all(feature = "async", feature = "testing"))]
+#[async_std::test]
+async fn event_stream_test() -> Result<()> {
+    use futures::stream::{StreamExt as _, TryStreamExt as _};
+    use tfrecord::{samples, EventFileStream};
+
+    let bytes = event_file()?;
+    let dataset = samples::tiny_dataset(0, 0)?;
+    let path = dataset.dir().join("events.out.tfevents");
+    std::fs::write(&path, &bytes[..bytes.len() - 5])?;
+
+    let mut events = EventFileStream::open(&path, Default::default()).await?;
+    let mut count = 0;
+    while let Some(event) = events.next().await {
+        event?;
+        count += 1;
+    }
+    assert_eq!(count, 7);
+    assert_eq!(events.file_version(), Some("brain.Event:2"));
+    assert!(events.corrupt_tail().is_some());
+
+    let points: Vec<_> = EventFileStream::open(&path, Default::default())
+        .await?
+        .scalars("train/")
+        .try_collect()
+        .await?;
+    let expect: Vec<_> = EventFileIter::open(&path, Default::default())?
+        .scalars("train/")
+        .collect::<Result<_, _>>()?;
+    assert_eq!(points, expect);
+    assert_eq!(points.len(), 3);
+    Ok(())
+}
@@ -0,0 +1,126 @@
+#![cfg(feature = "proto-summary")]
+
+use std::f32::consts::PI;
+use tfrecord::{
+    export::{
+        scalars_to_csv, scalars_to_json, series_from_csv, series_from_json, CsvLayout, ScalarPoint,
+        ScalarSeries,
+    },
+    EventMeta, Summary,
+};
+
+fn make_series(tag: &str) -> ScalarSeries {
+    let points = (0..100)
+        .map(|step| ScalarPoint {
+            wall_time: 1_650_000_000.0 + step as f64 * 0.123456789,
+            step,
+            value: match step {
+                7 => f32::NAN,
+                8 => f32::INFINITY,
+                9 => f32::NEG_INFINITY,
+                10 => f32::MIN_POSITIVE,
+                _ => (step as f32 * PI).sin() / (step as f32 + 1.0),
+            },
+        })
+        .collect();
+    ScalarSeries {
+        tag: tag.to_string(),
+        points,
+    }
+}
+
+/// Compare bitwise so that NaN values are considered equal.
+fn assert_series_eq(lhs: &ScalarSeries, rhs: &ScalarSeries) {
+    assert_eq!(lhs.tag, rhs.tag);
+    assert_eq!(lhs.points.len(), rhs.points.len());
+    for (lhs, rhs) in lhs.points.iter().zip(&rhs.points) {
+        assert_eq!(lhs.wall_time.to_bits(), rhs.wall_time.to_bits());
+        assert_eq!(lhs.step, rhs.step);
+        assert_eq!(lhs.value.to_bits(), rhs.value.to_bits());
+    }
+}
+
+#[test]
+fn csv_round_trip_test() -> tfrecord::Result<()> {
+    let series = make_series("");
+    let mut csv = vec![];
+    scalars_to_csv(std::slice::from_ref(&series), CsvLayout::PerTag, &mut csv)?;
+    let output = series_from_csv(csv.as_slice())?;
+    assert_eq!(output.len(), 1);
+    assert_series_eq(&output[0], &series);
+
+    let series = vec![
+        make_series("loss"),
+        make_series("eval/acc, top-1"),
+        make_series("\"quoted\""),
+    ];
+    let mut csv = vec![];
+    scalars_to_csv(&series, CsvLayout::Long, &mut csv)?;
+    let output = series_from_csv(csv.as_slice())?;
+    assert_eq!(output.len(), 3);
+    for (output, series) in output.iter().zip(&series) {
+        assert_series_eq(output, series);
+    }
+
+    assert!(scalars_to_csv(&series, CsvLayout::PerTag, vec![]).is_err());
+    Ok(())
+}
+
+#[test]
+fn json_round_trip_test() -> tfrecord::Result<()> {
+    let series = make_series("loss");
+    let mut json = vec![];
+    scalars_to_json(&series, &mut json)?;
+    assert_series_eq(&series_from_json(json.as_slice(), "loss")?, &series);
+
+    let empty = ScalarSeries::default();
+    let mut json = vec![];
+    scalars_to_json(&empty, &mut json)?;
+    assert_eq!(json, b"[]\n");
+    assert_eq!(series_from_json(json.as_slice(), "")?, empty);
+    Ok(())
+}
+
+#[test]
+fn csv_golden_test() -> tfrecord::Result<()> {
+    let golden = include_str!("scalars_golden.csv");
+    let series = series_from_csv(golden.as_bytes())?;
+    assert_eq!(series.len(), 1);
+    assert_eq!(
+        series[0].points[3],
+        ScalarPoint {
+            wall_time: 1650000004.0,
+            step: 30,
+            value: -0.1
+        }
+    );
+
+    let mut csv = vec![];
+    scalars_to_csv(&series, CsvLayout::PerTag, &mut csv)?;
+    assert_eq!(String::from_utf8(csv).unwrap(), golden);
+    Ok(())
+}
+
+#[test]
+fn series_from_events_test() -> tfrecord::Result<()> {
+    let events = (0..4).map(|step| {
+        let mut summary = Summary::from_scalar("loss", step as f32)?;
+        summary
+            .value
+            .extend(Summary::from_scalar("acc", 1.0 - step as f32)?.value);
+        Ok(EventMeta::new(step, step as f64).build_with_summary(summary))
+    });
+    let series = ScalarSeries::from_events(events)?;
+    assert_eq!(series.len(), 2);
+    assert_eq!(series[0].tag, "loss");
+    assert_eq!(series[1].tag, "acc");
+    assert_eq!(
+        series[1].points[2],
+        ScalarPoint {
+            wall_time: 2.0,
+            step: 2,
+            value: -1.0
+        }
+    );
+    Ok(())
+}
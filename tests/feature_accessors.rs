@@ -0,0 +1,79 @@
+mod common;
+
+use common::*;
+use tfrecord::{
+    protobuf::{Features, SequenceExample},
+    Error, Example, Feature,
+};
+
+#[test]
+fn example_accessors_test() -> Result<()> {
+    let mut example = Example::empty();
+    example.push_bytes("movie", vec![b"up".to_vec()]);
+    example.push_f32s("age", &[31.0, 32.0]);
+    example.push_i64s("label", &[1]);
+
+    assert_eq!(example.get_bytes("movie")?, &[b"up".to_vec()]);
+    assert_eq!(example.get_f32s("age")?, &[31.0, 32.0]);
+    assert_eq!(example.get_i64s("label")?, &[1]);
+
+    // missing features are `None`, and features of other kinds are errors
+    assert_eq!(example.try_get_f32s("age")?, Some(&[31.0, 32.0][..]));
+    assert_eq!(example.try_get_i64s("missing")?, None);
+    assert_eq!(Example::empty().try_get_bytes("movie")?, None);
+    let err = example.try_get_i64s("age").unwrap_err();
+    assert!(matches!(err, Error::ConversionError { .. }), "{}", err);
+    assert!(
+        err.to_string().contains("'age' is not a Int64List"),
+        "{}",
+        err
+    );
+    let err = example.get_bytes("missing").unwrap_err();
+    assert!(
+        err.to_string().contains("'missing' does not exist"),
+        "{}",
+        err
+    );
+
+    // values are modified in place
+    example.get_bytes_mut("movie")?.push(b"cars".to_vec());
+    example.get_f32s_mut("age")?.clear();
+    example.get_i64s_mut("label")?[0] = 0;
+    assert_eq!(example.get_bytes("movie")?.len(), 2);
+    assert_eq!(example.get_f32s("age")?, &[] as &[f32]);
+    assert_eq!(example.get_i64s("label")?, &[0]);
+    assert!(example.get_f32s_mut("label").is_err());
+    assert!(example.get_i64s_mut("missing").is_err());
+    Ok(())
+}
+
+#[test]
+fn features_accessors_test() -> Result<()> {
+    let mut context = Features::default();
+    context.push_i64s("user", &[42]);
+    context.push_bytes("country", vec![b"tw".to_vec()]);
+    context.push_f32s("score", &[0.5]);
+    let sequence = SequenceExample {
+        context: Some(context),
+        feature_lists: None,
+    };
+
+    let context = sequence.context.as_ref().unwrap();
+    assert_eq!(context.get_i64s("user")?, &[42]);
+    assert_eq!(
+        context.try_get_bytes("country")?,
+        Some(&[b"tw".to_vec()][..])
+    );
+    assert_eq!(context.try_get_f32s("missing")?, None);
+    assert!(context.try_get_f32s("user").is_err());
+    assert!(context.get_i64s("missing").is_err());
+
+    let mut context = context.clone();
+    context.get_f32s_mut("score")?.push(1.5);
+    assert_eq!(context.get_f32s("score")?, &[0.5, 1.5]);
+    assert_eq!(
+        context.feature["score"],
+        Feature::from_f32_list(&[0.5, 1.5])
+    );
+    Ok(())
+}
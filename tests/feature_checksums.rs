@@ -0,0 +1,128 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use tfrecord::{
+    protobuf_ext::{
+        add_feature_checksums, feature_checksum, recompute_feature_checksums,
+        verify_feature_checksums, verify_feature_checksums_iter, CHECKSUMS_KEY,
+    },
+    samples, Error, Example, Feature,
+};
+
+fn protected_example() -> Result<Example> {
+    let mut example = samples::example(5);
+    add_feature_checksums(&mut example, ["id", "score", "name"])?;
+    Ok(example)
+}
+
+fn set(example: &mut Example, key: &str, feature: Feature) {
+    example
+        .features
+        .as_mut()
+        .unwrap()
+        .feature
+        .insert(key.into(), feature);
+}
+
+#[test]
+fn detect_tampering_test() -> Result<()> {
+    let example = protected_example()?;
+    let report = verify_feature_checksums(&example)?;
+    assert!(report.is_ok());
+    assert_eq!(report.checked, ["id", "name", "score"]);
+
+    let tampered = [
+        ("id", Feature::from_i64_list(vec![6])),
+        ("score", Feature::from_f32_list(vec![2.5 * 1.0001])),
+        ("name", Feature::from_bytes_list(vec![b"sample-6".to_vec()])),
+        // the same values in another kind
+        ("id", Feature::from_f32_list(vec![5.0])),
+    ];
+    for (key, feature) in tampered {
+        let mut example = example.clone();
+        set(&mut example, key, feature);
+        let report = verify_feature_checksums(&example)?;
+        assert!(!report.is_ok());
+        assert_eq!(report.mismatched, [key]);
+    }
+
+    // floats are compared bitwise
+    let mut zero = samples::example(0);
+    add_feature_checksums(&mut zero, ["score"])?;
+    set(&mut zero, "score", Feature::from_f32_list(vec![-0.0]));
+    assert_eq!(verify_feature_checksums(&zero)?.mismatched, ["score"]);
+
+    // removed features and unlisted features
+    let mut example = example.clone();
+    example.features.as_mut().unwrap().feature.remove("name");
+    set(&mut example, "label", Feature::from_i64_list(vec![9]));
+    assert_eq!(verify_feature_checksums(&example)?.mismatched, ["name"]);
+    Ok(())
+}
+
+#[test]
+fn recompute_test() -> Result<()> {
+    let mut example = protected_example()?;
+    set(&mut example, "score", Feature::from_f32_list(vec![5.0]));
+    assert!(!verify_feature_checksums(&example)?.is_ok());
+
+    recompute_feature_checksums(&mut example, ["score"])?;
+    assert!(verify_feature_checksums(&example)?.is_ok());
+
+    // keys without stored checksums are rejected
+    assert!(recompute_feature_checksums(&mut example, ["label"]).is_err());
+    assert!(add_feature_checksums(&mut example, ["missing"]).is_err());
+    assert!(add_feature_checksums(&mut example, [CHECKSUMS_KEY]).is_err());
+    Ok(())
+}
+
+#[test]
+fn absent_checksums_test() -> Result<()> {
+    let report = verify_feature_checksums(&samples::example(0))?;
+    assert!(!report.protected);
+    assert!(!report.is_ok());
+    assert!(report.checked.is_empty());
+
+    let examples = || {
+        vec![
+            Ok(protected_example().unwrap()),
+            Ok(samples::example(1)),
+            Ok({
+                let mut example = protected_example().unwrap();
+                set(&mut example, "id", Feature::from_i64_list(vec![0]));
+                example
+            }),
+        ]
+        .into_iter()
+    };
+    let results: Vec<_> = verify_feature_checksums_iter(examples(), false).collect();
+    assert!(results[0].is_ok() && results[1].is_ok());
+    assert!(matches!(
+        &results[2],
+        Err(Error::FeatureChecksumMismatch { keys }) if keys == &["id"]
+    ));
+    let results: Vec<_> = verify_feature_checksums_iter(examples(), true).collect();
+    assert!(results[1].is_err());
+
+    // malformed companion features are errors
+    let mut example = samples::example(0);
+    set(
+        &mut example,
+        CHECKSUMS_KEY,
+        Feature::from_bytes_list(vec![vec![1, 0]]),
+    );
+    assert!(verify_feature_checksums(&example).is_err());
+    Ok(())
+}
+
+#[test]
+fn checksum_golden_test() {
+    // pinned, since other languages implement the same definition: the XXH3-64 of
+    // 03 | 02 00 00 00 00 00 00 00 | 01 00 .. 00 | fe ff .. ff
+    assert_eq!(
+        feature_checksum(&Feature::from_i64_list(vec![1, -2])),
+        0xd78d07d785c8c3b4
+    );
+}
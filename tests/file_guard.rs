@@ -0,0 +1,126 @@
+mod common;
+
+use common::*;
+use std::{fs, num::NonZeroUsize, path::PathBuf};
+use tfrecord::{
+    indexer::{FileChangePolicy, FileIdentity, GuardConfig, GuardedIndexes},
+    BytesWriter, Error,
+};
+
+fn write_file(path: &PathBuf, tag: u8, num_records: usize) -> Result<()> {
+    let mut writer = BytesWriter::create(path)?;
+    for index in 0..num_records {
+        writer.send(vec![tag, index as u8])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn make_dataset(name: &str) -> Result<Vec<PathBuf>> {
+    let dir = make_temp_dir(&format!("file_guard/{}", name))?;
+
+    (0..2)
+        .map(|file_index| {
+            let path = dir.join(format!("{}.tfrecord", file_index));
+            write_file(&path, file_index, 5)?;
+            Ok(path)
+        })
+        .collect()
+}
+
+#[test]
+fn unchanged_test() -> Result<()> {
+    let paths = make_dataset("unchanged")?;
+    let mut indexes = GuardedIndexes::load_paths(&paths, Default::default(), Default::default())?;
+    assert_eq!(indexes.len(), 10);
+    for index in 0..10 {
+        let record: Vec<u8> = indexes.get(index)?;
+        assert_eq!(record, [index as u8 / 5, index as u8 % 5]);
+    }
+    assert!(indexes.get::<Vec<u8>>(10).is_err());
+
+    let identities: Vec<_> = indexes.identities().collect();
+    assert_eq!(
+        identities[0],
+        (paths[0].as_path(), FileIdentity::of(&paths[0])?)
+    );
+    assert_eq!(indexes.to_indexes()[7].load::<Vec<u8>>()?, [1, 2]);
+    Ok(())
+}
+
+#[test]
+fn changed_before_open_test() -> Result<()> {
+    let paths = make_dataset("before_open")?;
+    let mut indexes = GuardedIndexes::load_paths(&paths, Default::default(), Default::default())?;
+    let expected = FileIdentity::of(&paths[1])?;
+
+    // replace the file with another one of the same size
+    let replacement = paths[1].with_extension("tmp");
+    write_file(&replacement, 9, 5)?;
+    fs::rename(&replacement, &paths[1])?;
+
+    let error = indexes.get::<Vec<u8>>(6).unwrap_err();
+    match error {
+        Error::FileChanged {
+            path,
+            expected: error_expected,
+            found,
+        } => {
+            assert_eq!(path, paths[1]);
+            assert_eq!(error_expected, expected);
+            assert_eq!(found, FileIdentity::of(&paths[1])?);
+            assert_ne!(found, expected);
+        }
+        error => panic!("unexpected error: {}", error),
+    }
+
+    // the other file is still served
+    let record: Vec<u8> = indexes.get(1)?;
+    assert_eq!(record, [0, 1]);
+    Ok(())
+}
+
+#[test]
+fn changed_while_open_test() -> Result<()> {
+    let paths = make_dataset("while_open")?;
+    let guard = GuardConfig {
+        check_interval: Some(NonZeroUsize::new(2).unwrap()),
+        ..Default::default()
+    };
+    let mut indexes = GuardedIndexes::load_paths(&paths, Default::default(), guard)?;
+    let _: Vec<u8> = indexes.get(0)?;
+
+    // truncate the open file in place
+    write_file(&paths[0], 7, 2)?;
+
+    // the second read is not due for a check
+    let _: Vec<u8> = indexes.get(1)?;
+    assert!(matches!(
+        indexes.get::<Vec<u8>>(2),
+        Err(Error::FileChanged { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn reindex_test() -> Result<()> {
+    let paths = make_dataset("reindex")?;
+    let guard = GuardConfig {
+        on_change: FileChangePolicy::Reindex,
+        ..Default::default()
+    };
+    let mut indexes = GuardedIndexes::load_paths(&paths, Default::default(), guard)?;
+    write_file(&paths[0], 7, 3)?;
+
+    // the first file is reindexed on open, shifting the indexes of the second file
+    let record: Vec<u8> = indexes.get(2)?;
+    assert_eq!(record, [7, 2]);
+    assert_eq!(indexes.len(), 8);
+    let record: Vec<u8> = indexes.get(3)?;
+    assert_eq!(record, [1, 0]);
+    assert_eq!(
+        indexes.identities().next().unwrap().1,
+        FileIdentity::of(&paths[0])?
+    );
+    Ok(())
+}
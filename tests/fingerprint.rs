@@ -0,0 +1,147 @@
+mod common;
+
+use common::*;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{Seek, SeekFrom, Write},
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+use tfrecord::{
+    fingerprint::{self, Fingerprint, FingerprintLevel},
+    indexer::{self, RecordIndex},
+    BytesWriter, RecordWriter,
+};
+
+fn make_dataset(name: &str) -> Result<Vec<PathBuf>> {
+    let dir = make_temp_dir(&format!("fingerprint/{}", name))?;
+
+    (0..3)
+        .map(|file_index| {
+            let path = dir.join(format!("{}.tfrecord", file_index));
+            let mut writer = BytesWriter::create(&path)?;
+            for record_index in 0..4 {
+                writer
+                    .send(format!("record {} of file {}", record_index, file_index).into_bytes())?;
+            }
+            writer.flush()?;
+            Ok(path)
+        })
+        .collect()
+}
+
+fn load_indexes(paths: &[PathBuf]) -> Result<Vec<RecordIndex>> {
+    let indexes = indexer::load_paths(paths, Default::default()).collect::<Result<_, _>>()?;
+    Ok(indexes)
+}
+
+fn fingerprint_paths(paths: &[PathBuf], level: FingerprintLevel) -> Result<Fingerprint> {
+    Ok(fingerprint::fingerprint_paths(
+        paths,
+        level,
+        Default::default(),
+    )?)
+}
+
+#[test]
+fn levels_test() -> Result<()> {
+    let paths = make_dataset("levels")?;
+    let indexes = load_indexes(&paths)?;
+
+    let levels = [
+        FingerprintLevel::Metadata,
+        FingerprintLevel::Structure,
+        FingerprintLevel::Content,
+    ];
+    let fingerprints: Vec<Fingerprint> = levels
+        .iter()
+        .map(|&level| -> Result<_> {
+            let fingerprint = fingerprint::fingerprint(&indexes, level)?;
+            assert_eq!(fingerprint.level(), level);
+            assert_eq!(fingerprint, fingerprint_paths(&paths, level)?);
+            Ok(fingerprint)
+        })
+        .collect::<Result<_>>()?;
+
+    // levels never share digests
+    assert_ne!(fingerprints[0], fingerprints[1]);
+    assert_ne!(fingerprints[1], fingerprints[2]);
+    assert_ne!(fingerprints[0], fingerprints[2]);
+
+    // formatting round-trips
+    for fingerprint in fingerprints {
+        let text = fingerprint.to_string();
+        assert_eq!(text.len(), 32);
+        assert_eq!(text.parse::<Fingerprint>()?, fingerprint);
+    }
+    assert!("00".repeat(16).parse::<Fingerprint>().is_err());
+    assert!("not a fingerprint".parse::<Fingerprint>().is_err());
+    Ok(())
+}
+
+#[test]
+fn structure_test() -> Result<()> {
+    let paths = make_dataset("structure")?;
+    let before = fingerprint_paths(&paths, FingerprintLevel::Structure)?;
+    let metadata_before = fingerprint_paths(&paths, FingerprintLevel::Metadata)?;
+
+    // changing the modification time only changes the metadata fingerprint
+    let file = File::options().write(true).open(&paths[1])?;
+    file.set_modified(SystemTime::now() + Duration::from_secs(3600))?;
+    drop(file);
+    assert_eq!(
+        fingerprint_paths(&paths, FingerprintLevel::Structure)?,
+        before
+    );
+    assert_ne!(
+        fingerprint_paths(&paths, FingerprintLevel::Metadata)?,
+        metadata_before
+    );
+
+    // appending a record changes the structure
+    {
+        let file = OpenOptions::new().append(true).open(&paths[1])?;
+        let mut writer: RecordWriter<Vec<u8>, _> = RecordWriter::from_writer(file)?;
+        writer.send(b"appended".to_vec())?;
+        writer.flush()?;
+    }
+    assert_ne!(
+        fingerprint_paths(&paths, FingerprintLevel::Structure)?,
+        before
+    );
+    Ok(())
+}
+
+#[test]
+fn content_test() -> Result<()> {
+    let paths = make_dataset("content")?;
+    let indexes = load_indexes(&paths)?;
+    let before = fingerprint::fingerprint(&indexes, FingerprintLevel::Content)?;
+    let structure = fingerprint::fingerprint(&indexes, FingerprintLevel::Structure)?;
+
+    // the content does not depend on the location
+    let copies = make_dataset("content-copy")?;
+    assert_eq!(
+        fingerprint_paths(&copies, FingerprintLevel::Content)?,
+        before
+    );
+
+    // flip a single payload byte
+    let RecordIndex { path, offset, .. } = &indexes[5];
+    {
+        let mut file = File::options().read(true).write(true).open(&**path)?;
+        let mut bytes = fs::read(&**path)?;
+        bytes[*offset as usize] ^= 1;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&bytes)?;
+    }
+    assert_eq!(
+        fingerprint::fingerprint(&indexes, FingerprintLevel::Structure)?,
+        structure
+    );
+    assert_ne!(
+        fingerprint::fingerprint(&indexes, FingerprintLevel::Content)?,
+        before
+    );
+    Ok(())
+}
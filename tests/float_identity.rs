@@ -0,0 +1,183 @@
+use tfrecord::{
+    dedup_examples, group_examples_by, Error, Example, Feature, FeatureChange, FloatIdentity,
+};
+
+const QUIET_NAN: f32 = f32::NAN;
+
+fn signaling_nan() -> f32 {
+    f32::from_bits(0x7fa0_0000)
+}
+
+fn payload_nan() -> f32 {
+    f32::from_bits(0x7fc0_0001)
+}
+
+const NAN_EQUAL: FloatIdentity = FloatIdentity::NanEqualNan {
+    canonicalize_payload: true,
+};
+const NAN_EQUAL_PAYLOAD: FloatIdentity = FloatIdentity::NanEqualNan {
+    canonicalize_payload: false,
+};
+const TOLERANCE: FloatIdentity = FloatIdentity::Tolerance {
+    abs: 1e-6,
+    rel: 1e-3,
+    nan_equal: true,
+};
+
+fn example(values: &[f32]) -> Example {
+    Example::from_iter([
+        ("x".to_string(), Feature::from_f32_list(values)),
+        ("id".to_string(), Feature::from_i64_list(vec![1])),
+    ])
+}
+
+#[test]
+fn float_identity_eq() {
+    let cases = [
+        // (lhs, rhs, bitwise, nan equal, nan equal by payload, tolerance)
+        (QUIET_NAN, QUIET_NAN, true, true, true, true),
+        (QUIET_NAN, signaling_nan(), false, true, false, true),
+        (QUIET_NAN, payload_nan(), false, true, false, true),
+        (QUIET_NAN, 1.0, false, false, false, false),
+        (0.0, -0.0, false, true, true, true),
+        (f32::INFINITY, f32::INFINITY, true, true, true, true),
+        (f32::INFINITY, f32::NEG_INFINITY, false, false, false, false),
+        (f32::INFINITY, f32::MAX, false, false, false, false),
+        (1.0, 1.0005, false, false, false, true),
+        (1.0, 1.01, false, false, false, false),
+    ];
+    for (lhs, rhs, bitwise, nan_equal, nan_equal_payload, tolerance) in cases {
+        let expect = [
+            (FloatIdentity::Bitwise, bitwise),
+            (NAN_EQUAL, nan_equal),
+            (NAN_EQUAL_PAYLOAD, nan_equal_payload),
+            (TOLERANCE, tolerance),
+        ];
+        for (identity, expect) in expect {
+            assert_eq!(
+                identity.eq_f32(lhs, rhs),
+                expect,
+                "{:?} {} {}",
+                identity,
+                lhs,
+                rhs
+            );
+            assert_eq!(identity.eq_f32(rhs, lhs), expect);
+        }
+    }
+
+    let tolerance = FloatIdentity::Tolerance {
+        abs: 0.0,
+        rel: 0.0,
+        nan_equal: false,
+    };
+    assert!(!tolerance.eq_f32(QUIET_NAN, QUIET_NAN));
+}
+
+#[test]
+fn float_identity_hash() -> Result<(), Error> {
+    let hash = |values: &[f32], identity| example(values).content_hash(identity);
+
+    // bitwise hashing tells NaN payloads and zero signs apart
+    let bitwise = FloatIdentity::HASHING;
+    assert_eq!(bitwise, FloatIdentity::Bitwise);
+    assert_eq!(hash(&[QUIET_NAN], bitwise)?, hash(&[QUIET_NAN], bitwise)?);
+    assert_ne!(
+        hash(&[QUIET_NAN], bitwise)?,
+        hash(&[signaling_nan()], bitwise)?
+    );
+    assert_ne!(hash(&[0.0], bitwise)?, hash(&[-0.0], bitwise)?);
+    assert_ne!(
+        hash(&[f32::INFINITY], bitwise)?,
+        hash(&[f32::NEG_INFINITY], bitwise)?
+    );
+
+    // hashes agree with the equality of the policy
+    assert_eq!(
+        hash(&[QUIET_NAN], NAN_EQUAL)?,
+        hash(&[signaling_nan()], NAN_EQUAL)?
+    );
+    assert_eq!(
+        hash(&[QUIET_NAN], NAN_EQUAL)?,
+        hash(&[payload_nan()], NAN_EQUAL)?
+    );
+    assert_eq!(hash(&[0.0], NAN_EQUAL)?, hash(&[-0.0], NAN_EQUAL)?);
+    assert_ne!(
+        hash(&[QUIET_NAN], NAN_EQUAL_PAYLOAD)?,
+        hash(&[payload_nan()], NAN_EQUAL_PAYLOAD)?
+    );
+    assert_eq!(
+        hash(&[0.0], NAN_EQUAL_PAYLOAD)?,
+        hash(&[-0.0], NAN_EQUAL_PAYLOAD)?
+    );
+    assert_ne!(hash(&[1.0], NAN_EQUAL)?, hash(&[1.0, 1.0], NAN_EQUAL)?);
+
+    // the hash does not depend on the map order and is stable across versions
+    let lhs = example(&[1.0, QUIET_NAN]);
+    let rhs: Example = lhs.clone().into_vec().into_iter().rev().collect();
+    assert_eq!(lhs.content_hash(bitwise)?, rhs.content_hash(bitwise)?);
+    assert_eq!(lhs.content_hash(bitwise)?, 0xb390_d9f0_ee88_4014);
+
+    assert!(hash(&[1.0], TOLERANCE).is_err());
+    assert!(Feature::from_i64_list(vec![1])
+        .content_hash(TOLERANCE)
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn float_identity_diff() {
+    let lhs = example(&[QUIET_NAN, 0.0, f32::INFINITY]);
+    let rhs = example(&[signaling_nan(), -0.0, f32::INFINITY]);
+
+    assert!(lhs.diff(&rhs, FloatIdentity::SEMANTIC).is_empty());
+    assert!(lhs.eq_with(&rhs, FloatIdentity::SEMANTIC));
+    assert!(lhs.diff(&rhs, TOLERANCE).is_empty());
+
+    let diffs = lhs.diff(&rhs, FloatIdentity::Bitwise);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].key, "x");
+    assert!(matches!(diffs[0].change, FeatureChange::Changed { .. }));
+
+    let diffs = lhs.diff(&rhs, NAN_EQUAL_PAYLOAD);
+    assert_eq!(diffs.len(), 1);
+
+    // added and removed features
+    let mut other = example(&[QUIET_NAN, 0.0, f32::INFINITY]);
+    other.features.as_mut().unwrap().feature.remove("id");
+    other.push_i64s("label", &[2]);
+    let diffs = lhs.diff(&other, FloatIdentity::SEMANTIC);
+    let keys: Vec<_> = diffs.iter().map(|diff| diff.key.as_str()).collect();
+    assert_eq!(keys, ["id", "label"]);
+    assert!(matches!(diffs[0].change, FeatureChange::Removed(_)));
+    assert!(matches!(diffs[1].change, FeatureChange::Added(_)));
+    assert!(!lhs.eq_with(&other, FloatIdentity::SEMANTIC));
+}
+
+#[test]
+fn float_identity_dedup_and_group() -> Result<(), Error> {
+    let examples = vec![
+        example(&[QUIET_NAN]),
+        example(&[signaling_nan()]),
+        example(&[0.0]),
+        example(&[-0.0]),
+        example(&[QUIET_NAN]),
+    ];
+
+    assert_eq!(
+        dedup_examples(examples.clone(), FloatIdentity::Bitwise)?.len(),
+        4
+    );
+    let deduped = dedup_examples(examples.clone(), FloatIdentity::SEMANTIC)?;
+    assert_eq!(deduped.len(), 2);
+    assert_eq!(deduped[1].get_f32s("x")?[0].to_bits(), 0.0f32.to_bits());
+    assert!(dedup_examples(examples.clone(), TOLERANCE).is_err());
+
+    let groups = group_examples_by(examples.clone(), "x", FloatIdentity::SEMANTIC)?;
+    let sizes: Vec<_> = groups.iter().map(|(_, group)| group.len()).collect();
+    assert_eq!(sizes, [3, 2]);
+    let groups = group_examples_by(examples, "missing", FloatIdentity::Bitwise)?;
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].0, None);
+    Ok(())
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::*;
+use tfrecord::io::{
+    frame::{self, FrameError, Region},
+    sync::try_write_record_slice,
+};
+
+fn frame_bytes(payload: &[u8]) -> Result<Vec<u8>> {
+    let mut bytes = vec![];
+    try_write_record_slice(&mut bytes, payload)?;
+    Ok(bytes)
+}
+
+#[test]
+fn layout_round_trip_test() -> Result<()> {
+    for payload in [&b""[..], b"x", b"hello", &[0xab; 1000]] {
+        let bytes = frame_bytes(payload)?;
+        let layout = frame::layout(payload);
+        assert_eq!(layout.frame_len(), bytes.len() as u64);
+        assert!(layout.is_valid());
+        assert_eq!(frame::annotate(&bytes)?, layout);
+
+        // the regions tile the frame and hold the written bytes
+        let mut end = 0;
+        for region in [
+            Region::Length,
+            Region::LengthCrc,
+            Region::Payload,
+            Region::PayloadCrc,
+        ] {
+            let range = layout.range(region);
+            assert_eq!(range.start, end);
+            end = range.end;
+        }
+        assert_eq!(end, layout.frame_len());
+        let payload_range = layout.payload.start as usize..layout.payload.end as usize;
+        assert_eq!(&bytes[payload_range], payload);
+        assert_eq!(&bytes[..8], &layout.length.bytes);
+        assert_eq!(&bytes[bytes.len() - 4..], &layout.payload_crc.bytes);
+    }
+    Ok(())
+}
+
+#[test]
+fn pinned_layout_test() -> Result<()> {
+    let layout = frame::layout(b"tfrecord");
+    assert_eq!(layout.length.offset, 0);
+    assert_eq!(layout.length.value, 8);
+    assert_eq!(layout.length.bytes, [8, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(layout.length_crc.offset, 8);
+    assert_eq!(layout.length_crc.value, layout.length_crc.computed);
+    assert_eq!(layout.payload, 12..20);
+    assert_eq!(layout.payload_crc.offset, 20);
+    assert_eq!(layout.frame_len(), 24);
+
+    // the masked CRC-32C of "hello", where CRC-32C("hello") is 0x9a71bb4c
+    let layout = frame::layout(b"hello");
+    assert_eq!(layout.payload_crc.value, 0x191c1fbb);
+    assert_eq!(layout.payload_crc.bytes, [0xbb, 0x1f, 0x1c, 0x19]);
+    assert_eq!(layout.payload_crc.offset, 17);
+
+    // long payloads are abbreviated
+    let layout = frame::layout(&[0xab; 1000]);
+    let payload_line = layout.to_string().lines().nth(3).unwrap().to_string();
+    assert_eq!(
+        payload_line,
+        "    12  1000  payload      ab ab ab ab ab ab ab ab .."
+    );
+    Ok(())
+}
+
+#[test]
+fn corrupted_annotation_test() -> Result<()> {
+    let bytes = frame_bytes(b"hello")?;
+
+    // a corrupted payload keeps the layout with the payload checksum invalid
+    let mut corrupted = bytes.clone();
+    corrupted[12] = b'j';
+    let layout = frame::annotate(&corrupted)?;
+    assert_eq!(layout.invalid_regions(), [Region::PayloadCrc]);
+    assert_eq!(layout.payload, 12..17);
+    assert_ne!(layout.payload_crc.value, layout.payload_crc.computed);
+    assert!(layout.to_string().contains("invalid, computed"));
+
+    // so does a corrupted length checksum
+    let mut corrupted = bytes.clone();
+    corrupted[9] ^= 0x01;
+    let layout = frame::annotate(&corrupted)?;
+    assert_eq!(layout.invalid_regions(), [Region::LengthCrc]);
+    assert!(!layout.is_valid());
+
+    // a corrupted length leaves the bytes short of the frame
+    let mut corrupted = bytes.clone();
+    corrupted[1] = 0x01;
+    let err = frame::annotate(&corrupted).unwrap_err();
+    assert_eq!(err.region(), Some(Region::Payload));
+    assert_eq!(
+        err,
+        FrameError::Truncated {
+            region: Region::Payload,
+            needed: 12 + 261,
+            available: 21,
+        }
+    );
+
+    for (len, region) in [
+        (0, Region::Length),
+        (7, Region::Length),
+        (11, Region::LengthCrc),
+        (16, Region::Payload),
+        (20, Region::PayloadCrc),
+    ] {
+        let err = frame::annotate(&bytes[..len]).unwrap_err();
+        assert_eq!(err.region(), Some(region), "{}", err);
+    }
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    let err = frame::annotate(&trailing).unwrap_err();
+    assert_eq!(err.region(), None);
+    assert_eq!(err.to_string(), "1 bytes follow the frame of 21 bytes");
+    Ok(())
+}
@@ -0,0 +1,247 @@
+#![cfg(all(feature = "gzip", feature = "testing"))]
+
+mod common;
+
+use common::*;
+use flate2::write::GzEncoder;
+use std::{
+    fs,
+    io::{Read as _, Write as _},
+    path::PathBuf,
+};
+use tfrecord::{
+    compression::Compression,
+    indexer::{self, RecordIndexerConfig},
+    samples, Error, Example, ExampleIter, ExampleWriter, RecordReaderConfig, RecordWriterConfig,
+    SequentialRangeReader,
+};
+
+fn gzip(bytes: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(vec![], flate2::Compression::default());
+    encoder.write_all(bytes)?;
+    Ok(encoder.finish()?)
+}
+
+fn reader_config() -> RecordReaderConfig {
+    RecordReaderConfig {
+        compression: Compression::Gzip,
+        ..Default::default()
+    }
+}
+
+fn indexer_config() -> RecordIndexerConfig {
+    RecordIndexerConfig {
+        compression: Compression::Gzip,
+        ..Default::default()
+    }
+}
+
+/// Compress the files of a sample dataset, keeping the second file plain.
+fn compressed_dataset(name: &str) -> Result<(samples::TempDataset, Vec<PathBuf>)> {
+    let dataset = samples::tiny_dataset(3, 5)?;
+    let paths = dataset
+        .paths()
+        .iter()
+        .enumerate()
+        .map(|(index, path)| {
+            if index == 1 {
+                return Ok(path.clone());
+            }
+            let compressed = path.with_file_name(format!("{}-{}.tfrecord.gz", name, index));
+            fs::write(&compressed, gzip(&fs::read(path)?)?)?;
+            Ok(compressed)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok((dataset, paths))
+}
+
+#[test]
+fn read_gzip_test() -> Result<()> {
+    let (_dataset, paths) = compressed_dataset("read")?;
+
+    let examples: Vec<Example> =
+        ExampleIter::open(&paths[0], reader_config())?.collect::<Result<_, _>>()?;
+    assert_eq!(examples, (0..5).map(samples::example).collect::<Vec<_>>());
+
+    // concatenated members are one stream
+    let plain = fs::read(samples::tiny_dataset(1, 6)?.paths()[0].clone())?;
+    let mut compressed = gzip(&plain[..plain.len() / 2])?;
+    compressed.extend(gzip(&plain[plain.len() / 2..])?);
+    let examples: Vec<Example> =
+        ExampleIter::from_bytes(compressed, reader_config()).collect::<Result<_, _>>()?;
+    assert_eq!(examples, (0..6).map(samples::example).collect::<Vec<_>>());
+
+    // skipping records decompresses past them
+    let mut records = ExampleIter::open(&paths[0], reader_config())?;
+    assert_eq!(records.skip_records(3, true)?, 3);
+    assert_eq!(records.next().transpose()?, Some(samples::example(3)));
+
+    // a truncated stream fails
+    let compressed = fs::read(&paths[0])?;
+    let truncated = compressed[..compressed.len() / 2].to_vec();
+    let result: Result<Vec<Example>, _> =
+        ExampleIter::from_bytes(truncated, reader_config()).collect();
+    assert!(result.is_err());
+
+    // byte ranges are meaningless in compressed files
+    let result = SequentialRangeReader::<Example>::new(&paths[0], 0..10, reader_config());
+    assert!(matches!(result, Err(Error::Unsupported { .. })));
+    Ok(())
+}
+
+#[test]
+fn index_gzip_test() -> Result<()> {
+    let (_dataset, paths) = compressed_dataset("index")?;
+    let expect: Vec<_> = (0..15).map(samples::example).collect();
+
+    // compressed files are rejected by default
+    let error = indexer::load_paths(&paths, Default::default())
+        .find_map(Result::err)
+        .unwrap();
+    assert!(matches!(error, Error::Unsupported { .. }), "{}", error);
+
+    // plain and compressed files are indexed together
+    let indexes: Vec<_> =
+        indexer::load_paths(&paths, indexer_config()).collect::<Result<_, _>>()?;
+    assert_eq!(indexes.len(), 15);
+    let examples: Vec<Example> = indexes
+        .iter()
+        .map(|index| index.load())
+        .collect::<Result<_, _>>()?;
+    assert_eq!(examples, expect);
+
+    // random access in reverse, and sequential iteration from the middle
+    for (index, example) in indexes.iter().zip(&expect).rev() {
+        assert_eq!(&index.load::<Example>()?, example);
+    }
+    let examples: Vec<(usize, Example)> =
+        indexer::iter_from(&indexes, 2, Default::default()).collect::<Result<_, _>>()?;
+    assert_eq!(examples.len(), 13);
+    for (index, example) in examples {
+        assert_eq!(example, expect[index]);
+    }
+    Ok(())
+}
+
+fn gunzip(bytes: &[u8]) -> Result<Vec<u8>> {
+    let mut decompressed = vec![];
+    flate2::read::MultiGzDecoder::new(bytes).read_to_end(&mut decompressed)?;
+    Ok(decompressed)
+}
+
+fn canonical() -> RecordWriterConfig {
+    RecordWriterConfig {
+        canonical_encoding: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn write_gzip_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(0, 0)?;
+    let expect: Vec<_> = (0..20).map(samples::example).collect();
+
+    let (mut writer, buffer) = ExampleWriter::in_memory_with_config(canonical())?;
+    for example in &expect {
+        writer.send(example.clone())?;
+    }
+    writer.flush()?;
+    let plain = buffer.to_vec();
+
+    for level in [0, 6, 9] {
+        let path = dataset.dir().join(format!("written-{}.tfrecord.gz", level));
+        let mut writer = ExampleWriter::create_gzip(&path, canonical(), level)?;
+        for example in &expect {
+            writer.send(example.clone())?;
+        }
+        writer.finish()?;
+
+        // the records are framed before compression
+        let compressed = fs::read(&path)?;
+        assert_eq!(gunzip(&compressed)?, plain);
+        let examples: Vec<Example> =
+            ExampleIter::open(&path, reader_config())?.collect::<Result<_, _>>()?;
+        assert_eq!(examples, expect);
+    }
+
+    // dropping the writer ends the stream
+    let path = dataset.dir().join("dropped.tfrecord.gz");
+    let mut writer = ExampleWriter::create_gzip(&path, canonical(), 1)?;
+    writer.send(samples::example(0))?;
+    drop(writer);
+    let examples: Vec<Example> =
+        ExampleIter::open(&path, reader_config())?.collect::<Result<_, _>>()?;
+    assert_eq!(examples, vec![samples::example(0)]);
+
+    let result = ExampleWriter::create_gzip(dataset.dir().join("invalid.gz"), canonical(), 10);
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn async_write_gzip_test() -> Result<()> {
+    use tfrecord::ExampleAsyncWriter;
+
+    let dataset = samples::tiny_dataset(0, 0)?;
+    let path = dataset.dir().join("written.tfrecord.gz");
+    let expect: Vec<_> = (0..20).map(samples::example).collect();
+
+    let mut writer = ExampleAsyncWriter::create_gzip(&path, Default::default(), 6).await?;
+    for (index, example) in expect.iter().enumerate() {
+        writer.send(example.clone()).await?;
+        // flushing in the middle keeps the stream valid
+        if index == 9 {
+            writer.flush().await?;
+        }
+    }
+    writer.finish().await?;
+
+    let examples: Vec<Example> =
+        ExampleIter::open(&path, reader_config())?.collect::<Result<_, _>>()?;
+    assert_eq!(examples, expect);
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn index_gzip_async_test() -> Result<()> {
+    let (_dataset, paths) = compressed_dataset("index_async")?;
+
+    // the async indexer rejects the compression instead of indexing compressed bytes
+    for path in &paths {
+        let error = indexer::load_file_async(path, indexer_config())
+            .await
+            .err()
+            .unwrap();
+        assert!(
+            matches!(error, Error::InvalidArgumentsError { .. }),
+            "{}",
+            error
+        );
+    }
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn stream_gzip_test() -> Result<()> {
+    use futures::TryStreamExt as _;
+    use tfrecord::ExampleStream;
+
+    let (_dataset, paths) = compressed_dataset("stream")?;
+    let examples: Vec<Example> = ExampleStream::open(&paths[0], reader_config())
+        .await?
+        .try_collect()
+        .await?;
+    assert_eq!(examples, (0..5).map(samples::example).collect::<Vec<_>>());
+
+    // a truncated stream fails
+    let compressed = fs::read(&paths[0])?;
+    let truncated = futures::io::Cursor::new(compressed[..compressed.len() / 2].to_vec());
+    let result: Result<Vec<Example>, _> = ExampleStream::from_reader(truncated, reader_config())
+        .try_collect()
+        .await;
+    assert!(result.is_err());
+    Ok(())
+}
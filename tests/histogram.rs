@@ -0,0 +1,130 @@
+#![cfg(feature = "proto-summary")]
+
+mod common;
+
+use common::*;
+use tfrecord::{Error, HistogramProto};
+
+/// The collapsed buckets of the values as `(limit, count)`.
+fn buckets(values: &[f64]) -> Result<Vec<(f64, f64)>> {
+    let histogram = HistogramProto::try_from_iter(values.iter().copied())?;
+    Ok(histogram.collapse_empty_buckets()?.iter().collect())
+}
+
+#[test]
+fn tf_default_limits_test() {
+    let limits = HistogramProto::tf_default().bucket_limit;
+    // 774 limits on each side of zero, including the closing f64::MAX
+    assert_eq!(limits.len(), 1551);
+    assert_eq!(limits[0], f64::MIN);
+    assert_eq!(limits[775], 0.0);
+    assert_eq!(limits[776], 1e-12);
+    assert_eq!(limits[777], 1e-12 * 1.1);
+    assert_eq!(limits[1549], 9.920775621859783e19);
+    assert_eq!(limits[1550], f64::MAX);
+    for (neg, pos) in limits[..775].iter().zip(limits[776..].iter().rev()) {
+        assert_eq!(*neg, -pos);
+    }
+}
+
+/// The expected buckets follow the histogram summaries of TensorFlow, which fill the
+/// buckets by the upper bound of each value and drop runs of empty buckets but the last.
+#[test]
+fn tf_compatible_buckets_test() -> Result<()> {
+    let histogram = HistogramProto::try_from_iter([-2.5, 0.0, 0.5, 1.0, 3.0, 1e3])?;
+    assert_eq!(histogram.num, 6.0);
+    assert_eq!(histogram.min, -2.5);
+    assert_eq!(histogram.max, 1000.0);
+    assert_eq!(histogram.sum, 1002.0);
+    assert_eq!(histogram.sum_squares, 1000016.5);
+    assert_eq!(
+        histogram
+            .collapse_empty_buckets()?
+            .iter()
+            .collect::<Vec<_>>(),
+        [
+            (-2.6170109961884593, 0.0),
+            (-2.379100905625872, 1.0),
+            (0.0, 0.0),
+            (1e-12, 1.0),
+            (0.47069243095356195, 0.0),
+            (0.5177616740489182, 1.0),
+            (0.917246389039776, 0.0),
+            (1.0089710279437536, 1.0),
+            (2.8787120958073054, 0.0),
+            (3.1665833053880363, 1.0),
+            (964.166476569037, 0.0),
+            (1060.5831242259408, 1.0),
+            (f64::MAX, 0.0),
+        ]
+    );
+
+    // identical values fill a single bucket
+    let histogram = HistogramProto::try_from_iter([1.0; 3])?;
+    assert_eq!((histogram.min, histogram.max), (1.0, 1.0));
+    assert_eq!(histogram.sum_squares, 3.0);
+    assert_eq!(
+        buckets(&[1.0; 3])?,
+        [
+            (0.917246389039776, 0.0),
+            (1.0089710279437536, 3.0),
+            (f64::MAX, 0.0)
+        ]
+    );
+
+    // zero falls in the bucket above the zero limit
+    assert_eq!(
+        buckets(&[0.0])?,
+        [(0.0, 0.0), (1e-12, 1.0), (f64::MAX, 0.0)]
+    );
+
+    // huge finite values are counted in the closing buckets
+    assert_eq!(
+        buckets(&[-1e30, 1e30])?,
+        [
+            (f64::MIN, 0.0),
+            (-9.920775621859783e19, 1.0),
+            (9.920775621859783e19, 0.0),
+            (f64::MAX, 1.0)
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn empty_histogram_test() -> Result<()> {
+    let histogram = HistogramProto::try_from_iter(Vec::<f64>::new())?;
+    assert_eq!(histogram.num, 0.0);
+    assert_eq!(histogram.min, f64::MAX);
+    assert_eq!(histogram.max, f64::MIN);
+    assert_eq!(buckets(&[])?, [(f64::MAX, 0.0)]);
+    Ok(())
+}
+
+#[test]
+fn explicit_limits_test() -> Result<()> {
+    let histogram =
+        HistogramProto::try_from_iter_with_limits(vec![0.0, 1.0, 10.0], [-1, 0, 1, 5, 9, 10])?;
+    // the value 10 is not below the last limit
+    assert_eq!(histogram.bucket, [1.0, 1.0, 3.0]);
+    assert_eq!(histogram.num, 5.0);
+    assert_eq!(histogram.max, 9.0);
+
+    let err = HistogramProto::try_from_iter_with_limits(vec![1.0, 0.0], [0.5]).unwrap_err();
+    assert!(matches!(err, Error::ConversionError { .. }), "{}", err);
+    Ok(())
+}
+
+#[test]
+fn non_finite_values_test() {
+    for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        let err = HistogramProto::try_from_iter([1.0, value]).unwrap_err();
+        assert!(
+            err.to_string().contains("inserted value must be finite"),
+            "{}",
+            err
+        );
+    }
+    let result = std::panic::catch_unwind(|| [f64::NAN].into_iter().collect::<HistogramProto>());
+    assert!(result.is_err());
+}
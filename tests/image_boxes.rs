@@ -0,0 +1,136 @@
+#![cfg(feature = "with-image")]
+
+mod common;
+
+use common::*;
+use image::{DynamicImage, GenericImageView as _, Rgb, RgbImage, Rgba};
+use tfrecord::{
+    protobuf::{event::What, summary::value::Value},
+    protobuf_ext::{draw_boxes, Box2D, BoxCoordinates, BoxDrawReport, BoxStyle},
+    EventIter, EventWriter, EventWriterConfig,
+};
+
+const GRAY: Rgba<u8> = Rgba([50, 50, 50, 255]);
+const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+const GREEN: Rgba<u8> = Rgba([0, 255, 0, 255]);
+
+fn make_box(coords: [f32; 4], color: Rgba<u8>, label: Option<&str>) -> Box2D {
+    let [xmin, ymin, xmax, ymax] = coords;
+    Box2D {
+        xmin,
+        ymin,
+        xmax,
+        ymax,
+        color: Rgb([color[0], color[1], color[2]]),
+        label: label.map(Into::into),
+    }
+}
+
+fn gray_image() -> DynamicImage {
+    DynamicImage::ImageRgb8(RgbImage::from_pixel(32, 24, Rgb([50, 50, 50])))
+}
+
+#[test]
+fn write_image_with_boxes_test() -> Result<()> {
+    let boxes = [
+        make_box([2.0, 3.0, 10.0, 12.0], RED, Some("A")),
+        // partially outside the image
+        make_box([16.0, 12.0, 40.0, 23.0], GREEN, None),
+    ];
+    let style = BoxStyle {
+        thickness: 2,
+        ..Default::default()
+    };
+
+    let (mut writer, buffer) =
+        EventWriter::in_memory(EventWriterConfig::default().with_stamp_producer(false))?;
+    let report = writer.write_image_with_boxes("boxes", 0, &gray_image(), &boxes, &style)?;
+    drop(writer);
+    assert_eq!(
+        report,
+        BoxDrawReport {
+            num_clamped: 1,
+            num_outside: 0
+        }
+    );
+
+    let event = EventIter::from_bytes(buffer.to_vec(), Default::default())
+        .last()
+        .unwrap()?;
+    let image = match event.what {
+        Some(What::Summary(summary)) => match &summary.value[0].value {
+            Some(Value::Image(image)) => image.clone(),
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    };
+    assert_eq!((image.width, image.height), (32, 24));
+    let image = image::load_from_memory(&image.encoded_image_string)?;
+
+    // the red box with two-pixel lines
+    assert_eq!(image.get_pixel(2, 3), RED);
+    assert_eq!(image.get_pixel(6, 4), RED);
+    assert_eq!(image.get_pixel(10, 12), RED);
+    assert_eq!(image.get_pixel(9, 11), RED);
+    assert_eq!(image.get_pixel(6, 5), GRAY);
+    assert_eq!(image.get_pixel(8, 10), GRAY);
+    assert_eq!(image.get_pixel(11, 12), GRAY);
+    // the label inside the box, since there is no room above
+    assert_eq!(image.get_pixel(6, 6), RED);
+    assert_eq!(image.get_pixel(5, 6), GRAY);
+
+    // the green box clamped to the right edge
+    assert_eq!(image.get_pixel(16, 12), GREEN);
+    assert_eq!(image.get_pixel(31, 20), GREEN);
+    assert_eq!(image.get_pixel(20, 18), GRAY);
+    Ok(())
+}
+
+#[test]
+fn normalized_and_degenerate_boxes_test() -> Result<()> {
+    let boxes = [
+        make_box([0.5, 0.5, 1.0, 1.0], RED, None),
+        // zero area
+        make_box([0.0, 0.0, 0.0, 0.0], GREEN, None),
+        // zero height
+        make_box([0.0, 0.25, 0.25, 0.25], GREEN, None),
+        // entirely outside
+        make_box([1.5, 0.0, 2.0, 1.0], GREEN, None),
+    ];
+    let style = BoxStyle {
+        coordinates: BoxCoordinates::Normalized,
+        ..Default::default()
+    };
+    let (image, report) = draw_boxes(&gray_image(), &boxes, &style)?;
+    assert_eq!(
+        report,
+        BoxDrawReport {
+            num_clamped: 0,
+            num_outside: 1
+        }
+    );
+
+    // 0.5 * 31 and 0.5 * 23 round to 16 and 12
+    assert_eq!(image.get_pixel(16, 12), RED);
+    assert_eq!(image.get_pixel(31, 23), RED);
+    assert_eq!(image.get_pixel(17, 13), GRAY);
+    assert_eq!(image.get_pixel(0, 0), GREEN);
+    assert_eq!(image.get_pixel(1, 1), GRAY);
+    // 0.25 * 23 rounds to 6
+    assert_eq!(image.get_pixel(0, 6), GREEN);
+    assert_eq!(image.get_pixel(8, 6), GREEN);
+    assert_eq!(image.get_pixel(9, 6), GRAY);
+    assert_eq!(image.get_pixel(4, 7), GRAY);
+
+    // invalid boxes and styles
+    let inverted = make_box([0.5, 0.0, 0.25, 1.0], RED, None);
+    assert!(draw_boxes(&gray_image(), &[inverted], &style).is_err());
+    let nan = make_box([f32::NAN, 0.0, 0.25, 1.0], RED, None);
+    assert!(draw_boxes(&gray_image(), &[nan], &style).is_err());
+    let thin = BoxStyle {
+        thickness: 0,
+        ..Default::default()
+    };
+    assert!(draw_boxes(&gray_image(), &[], &thin).is_err());
+    Ok(())
+}
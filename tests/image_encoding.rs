@@ -0,0 +1,130 @@
+#![cfg(feature = "with-image")]
+
+mod common;
+
+use common::*;
+use image::{DynamicImage, GrayAlphaImage, ImageFormat, RgbImage, RgbaImage};
+use tfrecord::{
+    protobuf::summary::Image,
+    protobuf_ext::{ColorSpace, ImageEncoding, IntoImage},
+};
+
+fn gradient(width: u32, height: u32) -> RgbImage {
+    RgbImage::from_fn(width, height, |x, y| {
+        image::Rgb([(x * 8) as u8, (y * 8) as u8, ((x + y) * 4) as u8])
+    })
+}
+
+fn decode(image: &Image) -> Result<DynamicImage> {
+    Ok(image::load_from_memory(&image.encoded_image_string)?)
+}
+
+#[test]
+fn jpeg_encoding_test() -> Result<()> {
+    let rgb = gradient(32, 24);
+
+    // the TryFrom conversions keep PNG
+    let png = Image::try_from(&rgb)?;
+    assert_eq!(png, (&rgb).try_into_image_with(ImageEncoding::Png)?);
+    assert_eq!(
+        image::guess_format(&png.encoded_image_string)?,
+        ImageFormat::Png
+    );
+
+    let jpeg = (&rgb).try_into_image_with(ImageEncoding::jpeg(90))?;
+    assert_eq!(
+        image::guess_format(&jpeg.encoded_image_string)?,
+        ImageFormat::Jpeg
+    );
+    assert_eq!((jpeg.width, jpeg.height), (32, 24));
+    assert_eq!(jpeg.color_space()?, ColorSpace::Rgb);
+    let decoded = decode(&jpeg)?.to_rgb8();
+    assert_eq!(decoded.dimensions(), (32, 24));
+
+    // lower qualities are smaller
+    let small = DynamicImage::ImageRgb8(rgb).try_into_image_with(ImageEncoding::jpeg(10))?;
+    assert!(small.encoded_image_string.len() < jpeg.encoded_image_string.len());
+
+    // the quality is in 1..=100
+    let rgb = gradient(4, 4);
+    for quality in [0, 101] {
+        assert!((&rgb)
+            .try_into_image_with(ImageEncoding::jpeg(quality))
+            .is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn jpeg_alpha_test() -> Result<()> {
+    let rgba = RgbaImage::from_fn(8, 8, |x, y| {
+        image::Rgba([x as u8 * 30, y as u8 * 30, 0, 128])
+    });
+    let gray_alpha = GrayAlphaImage::from_pixel(8, 8, image::LumaA([200, 0]));
+
+    // images with alpha are rejected unless the alpha is dropped explicitly
+    let err = (&rgba)
+        .try_into_image_with(ImageEncoding::jpeg(80))
+        .unwrap_err();
+    assert!(err.to_string().contains("alpha"), "{}", err);
+    assert!((&gray_alpha)
+        .try_into_image_with(ImageEncoding::jpeg(80))
+        .is_err());
+
+    let drop_alpha = ImageEncoding::Jpeg {
+        quality: 80,
+        drop_alpha: true,
+    };
+    let image = (&rgba).try_into_image_with(drop_alpha)?;
+    assert_eq!(image.color_space()?, ColorSpace::Rgb);
+    assert_eq!(decode(&image)?.color(), image::ColorType::Rgb8);
+
+    let image = DynamicImage::ImageLumaA8(gray_alpha).try_into_image_with(drop_alpha)?;
+    assert_eq!(image.color_space()?, ColorSpace::Luma);
+    let decoded = decode(&image)?.to_luma8();
+    assert!(decoded.pixels().all(|pixel| pixel.0[0].abs_diff(200) <= 2));
+
+    // PNG keeps the alpha
+    let image = Image::try_from(&rgba)?;
+    assert_eq!(image.color_space()?, ColorSpace::Rgba);
+    Ok(())
+}
+
+#[cfg(feature = "with-tch")]
+#[test]
+fn tch_jpeg_test() -> Result<()> {
+    use tch::{Device, Kind, Tensor};
+    use tfrecord::protobuf_ext::{
+        IntoImageList, TchChannelOrder, TchTensorAsImage, TchTensorAsImageList,
+    };
+
+    let tensor = Tensor::f_rand(&[3, 16, 16], (Kind::Float, Device::Cpu))?;
+    let image = TchTensorAsImage::new(ColorSpace::Rgb, TchChannelOrder::CHW, tensor)?
+        .try_into_image_with(ImageEncoding::jpeg(75))?;
+    assert_eq!(
+        image::guess_format(&image.encoded_image_string)?,
+        ImageFormat::Jpeg
+    );
+
+    let batch = Tensor::f_rand(&[2, 16, 16, 4], (Kind::Float, Device::Cpu))?;
+    let list = TchTensorAsImageList::new(ColorSpace::Rgba, TchChannelOrder::HWC, batch)?;
+    assert!(list.into_image_list_with(ImageEncoding::jpeg(75)).is_err());
+
+    let batch = Tensor::f_rand(&[2, 16, 16, 4], (Kind::Float, Device::Cpu))?;
+    let images = TchTensorAsImageList::new(ColorSpace::Rgba, TchChannelOrder::HWC, batch)?
+        .into_image_list_with(ImageEncoding::Jpeg {
+            quality: 75,
+            drop_alpha: true,
+        })?;
+    assert_eq!(images.len(), 2);
+    assert!(images
+        .iter()
+        .all(|image| image.colorspace == ColorSpace::Rgb as i32));
+
+    // the default list conversion keeps PNG
+    let batch = Tensor::f_rand(&[1, 16, 16, 4], (Kind::Float, Device::Cpu))?;
+    let images = TchTensorAsImageList::new(ColorSpace::Rgba, TchChannelOrder::HWC, batch)?
+        .into_image_list()?;
+    assert_eq!(images[0].colorspace, ColorSpace::Rgba as i32);
+    Ok(())
+}
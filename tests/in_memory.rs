@@ -0,0 +1,117 @@
+#![cfg(feature = "proto-summary")]
+
+mod common;
+
+use common::*;
+use tfrecord::{
+    protobuf::event::What, BytesIter, BytesWriter, EventIter, EventWriter, EventWriterConfig,
+    Example, ExampleIter, ExampleWriter, Feature, RecordWriterConfig, Summary,
+};
+
+fn make_example(index: i64) -> Example {
+    vec![
+        ("index".into(), Feature::from_i64_list(vec![index])),
+        (
+            "name".into(),
+            Feature::from_bytes_list(vec![format!("example-{}", index).into_bytes()]),
+        ),
+    ]
+    .into_iter()
+    .collect()
+}
+
+#[test]
+fn example_round_trip_test() -> Result<()> {
+    let (mut writer, buffer) = ExampleWriter::in_memory()?;
+    for index in 0..10 {
+        writer.send(make_example(index))?;
+    }
+    writer.flush()?;
+
+    let examples: Vec<Example> =
+        ExampleIter::from_bytes(buffer.to_vec(), Default::default()).collect::<Result<_, _>>()?;
+    assert_eq!(examples.len(), 10);
+    for (index, example) in examples.into_iter().enumerate() {
+        assert_eq!(example, make_example(index as i64));
+        let features = example.into_hash_map();
+        assert_eq!(features["index"].as_i64_list(), Some(&[index as i64][..]));
+        assert_eq!(
+            features["name"].as_bytes_list(),
+            Some(&[format!("example-{}", index).into_bytes()][..])
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn same_bytes_as_file_test() -> Result<()> {
+    let config = RecordWriterConfig {
+        canonical_encoding: true,
+        ..Default::default()
+    };
+
+    let path = make_temp_dir("in_memory_same_bytes")?.join("examples.tfrecord");
+    let mut writer = ExampleWriter::create_with_config(&path, config.clone())?;
+    let (mut memory_writer, buffer) = ExampleWriter::in_memory_with_config(config)?;
+    for index in 0..5 {
+        writer.send(make_example(index))?;
+        memory_writer.send(make_example(index))?;
+    }
+    drop(writer);
+    drop(memory_writer);
+
+    assert_eq!(buffer.to_vec(), std::fs::read(&path)?);
+    Ok(())
+}
+
+#[test]
+fn buffered_until_flush_test() -> Result<()> {
+    let (mut writer, buffer) = BytesWriter::in_memory()?;
+    writer.send(b"record".to_vec())?;
+    assert!(buffer.is_empty());
+
+    writer.flush()?;
+    assert_eq!(buffer.len(), 8 + 4 + 6 + 4);
+
+    // the taken bytes are no longer in the buffer
+    let bytes = buffer.take();
+    assert!(buffer.is_empty());
+    let records: Vec<_> =
+        BytesIter::from_bytes(bytes, Default::default()).collect::<Result<_, _>>()?;
+    assert_eq!(records, [b"record".to_vec()]);
+    Ok(())
+}
+
+#[test]
+fn truncated_bytes_test() -> Result<()> {
+    let (mut writer, buffer) = BytesWriter::in_memory()?;
+    writer.send(b"record".to_vec())?;
+    drop(writer);
+
+    let mut bytes = buffer.to_vec();
+    bytes.pop();
+    let result: Result<Vec<_>, _> = BytesIter::from_bytes(bytes, Default::default()).collect();
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[test]
+fn event_round_trip_test() -> Result<()> {
+    let (mut writer, buffer) = EventWriter::in_memory(EventWriterConfig::default())?;
+    writer.write_scalar("loss", 0, 0.5)?;
+    writer.write_scalar("loss", 1, 0.25)?;
+    drop(writer);
+
+    let events: Vec<_> =
+        EventIter::from_bytes(buffer.to_vec(), Default::default()).collect::<Result<_, _>>()?;
+    // the file version, the producer stamp and two summaries
+    assert_eq!(events.len(), 4);
+    assert!(matches!(events[0].what, Some(What::FileVersion(_))));
+    assert!(matches!(events[1].what, Some(What::LogMessage(_))));
+    assert_eq!(events[3].step, 1);
+    assert_eq!(
+        events[3].what,
+        Some(What::Summary(Summary::from_scalar("loss", 0.25)?))
+    );
+    Ok(())
+}
@@ -0,0 +1,166 @@
+#![cfg(all(feature = "testing", feature = "incremental"))]
+
+mod common;
+
+use common::*;
+use std::path::{Path, PathBuf};
+use tfrecord::{
+    incremental::{
+        self, Accumulated, Accumulator, NumericStats, OnChange, UpdateOptions, VocabCounter,
+    },
+    samples, Error, ExampleWriter,
+};
+
+/// Accumulate the files from scratch.
+fn from_scratch<A>(paths: &[PathBuf], empty: A) -> Result<A>
+where
+    A: Accumulator,
+{
+    let mut accumulator = empty;
+    for path in paths {
+        for example in tfrecord::ExampleIter::open(path, Default::default())? {
+            accumulator.update(&example?)?;
+        }
+    }
+    Ok(accumulator)
+}
+
+fn update_both(
+    state_dir: &Path,
+    paths: &[PathBuf],
+    options: UpdateOptions,
+) -> Result<(VocabCounter, NumericStats)> {
+    let vocab = incremental::update(
+        state_dir.join("name.vocab.json"),
+        paths,
+        VocabCounter::new("name"),
+        options.clone(),
+    )?;
+    let stats = incremental::update(
+        state_dir.join("score.stats.json"),
+        paths,
+        NumericStats::new("score"),
+        options,
+    )?;
+    Ok((vocab.accumulated.accumulator, stats.accumulated.accumulator))
+}
+
+#[test]
+fn day_by_day_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(5, 8)?;
+    let paths = dataset.paths();
+    let state_dir = dataset.dir();
+
+    // day 1 covers 3 files, day 2 appends 2 more
+    update_both(state_dir, &paths[..3], Default::default())?;
+    let state_path = state_dir.join("score.stats.json");
+    let day1 = Accumulated::<NumericStats>::load(&state_path)?;
+    assert_eq!(day1.files.len(), 3);
+    assert_eq!(day1.accumulator.count, 24);
+
+    let updated = incremental::update(
+        &state_path,
+        paths,
+        NumericStats::new("ignored"),
+        Default::default(),
+    )?;
+    assert_eq!(updated.new_files, &paths[3..]);
+    assert!(!updated.recomputed);
+    let (vocab, _) = update_both(state_dir, paths, Default::default())?;
+
+    let stats = updated.accumulated.accumulator;
+    assert_eq!(stats, from_scratch(paths, NumericStats::new("score"))?);
+    assert_eq!(stats.count, 40);
+    assert_eq!(stats.min, Some(0.0));
+    assert_eq!(stats.max, Some(19.5));
+    assert_eq!(vocab, from_scratch(paths, VocabCounter::new("name"))?);
+    assert_eq!(vocab.counts.len(), 40);
+
+    // the saved state equals the returned one
+    assert_eq!(
+        Accumulated::load(&state_path)?,
+        Accumulated {
+            version: incremental::ACCUMULATED_VERSION,
+            files: updated.accumulated.files,
+            accumulator: stats,
+        }
+    );
+
+    // nothing new to read
+    let again = incremental::update(
+        &state_path,
+        paths,
+        NumericStats::new("score"),
+        Default::default(),
+    )?;
+    assert!(again.new_files.is_empty());
+    assert_eq!(again.accumulated.accumulator.count, 40);
+    Ok(())
+}
+
+#[test]
+fn changed_files_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(3, 4)?;
+    let paths = dataset.paths();
+    let state_path = dataset.dir().join("score.stats.json");
+    incremental::update(
+        &state_path,
+        paths,
+        NumericStats::new("score"),
+        Default::default(),
+    )?;
+
+    // rewrite a covered file with more records
+    let mut writer = ExampleWriter::create(&paths[1])?;
+    for index in 100..106 {
+        writer.send(samples::example(index))?;
+    }
+    writer.flush()?;
+    drop(writer);
+
+    let err = incremental::update(
+        &state_path,
+        paths,
+        NumericStats::new("score"),
+        Default::default(),
+    )
+    .unwrap_err();
+    assert!(matches!(
+        &err,
+        Error::StaleAccumulator { removed, changed } if removed.is_empty() && changed == &paths[1..2]
+    ));
+    // the saved state is kept on failure
+    assert_eq!(
+        Accumulated::<NumericStats>::load(&state_path)?
+            .accumulator
+            .count,
+        12
+    );
+
+    // a removed file
+    let err = incremental::update(
+        &state_path,
+        [&paths[0], &paths[1]],
+        NumericStats::new("score"),
+        Default::default(),
+    )
+    .unwrap_err();
+    assert!(matches!(
+        &err,
+        Error::StaleAccumulator { removed, .. } if removed == &paths[2..]
+    ));
+
+    let recompute = UpdateOptions {
+        on_change: OnChange::Recompute,
+        ..Default::default()
+    };
+    let updated = incremental::update(&state_path, paths, NumericStats::new("score"), recompute)?;
+    assert!(updated.recomputed);
+    assert_eq!(updated.new_files, paths);
+    assert_eq!(
+        updated.accumulated.accumulator,
+        from_scratch(paths, NumericStats::new("score"))?
+    );
+    assert_eq!(updated.accumulated.accumulator.count, 14);
+    Ok(())
+}
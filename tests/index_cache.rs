@@ -0,0 +1,118 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::{
+    fs::{self, OpenOptions},
+    time::{Duration, SystemTime},
+};
+use tfrecord::{
+    indexer::{self, RecordIndex},
+    samples, Example, ExampleWriter,
+};
+
+fn load_examples(indexes: &[RecordIndex]) -> Result<Vec<Example>> {
+    Ok(indexes
+        .iter()
+        .map(|index| index.load())
+        .collect::<Result<_, _>>()?)
+}
+
+#[test]
+fn cached_indexes_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(3, 4)?;
+    let scanned: Vec<_> =
+        indexer::load_paths(dataset.paths(), Default::default()).collect::<Result<_, _>>()?;
+
+    let first = indexer::load_paths_cached(dataset.paths(), Default::default())?;
+    assert_eq!((first.num_cached, first.num_scanned), (0, 3));
+    for path in dataset.paths() {
+        assert!(indexer::index_path(path).exists());
+    }
+
+    let cached = indexer::load_paths_cached(dataset.paths(), Default::default())?;
+    assert_eq!((cached.num_cached, cached.num_scanned), (3, 0));
+    assert_eq!(cached.indexes, scanned);
+    assert_eq!(load_examples(&cached.indexes)?, load_examples(&scanned)?);
+
+    // sidecars are not record files
+    let prefixed: Vec<_> = indexer::load_prefix(
+        format!("{}{}", dataset.dir().display(), std::path::MAIN_SEPARATOR),
+        Default::default(),
+    )?
+    .collect::<Result<_, _>>()?;
+    assert_eq!(prefixed, scanned);
+    Ok(())
+}
+
+#[test]
+fn stale_index_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(2, 3)?;
+    indexer::load_paths_cached(dataset.paths(), Default::default())?;
+
+    // rewrite the first file with more records
+    {
+        let mut writer = ExampleWriter::create(&dataset.paths()[0])?;
+        for index in 10..15 {
+            writer.send(samples::example(index))?;
+        }
+        writer.flush()?;
+    }
+    let reloaded = indexer::load_paths_cached(dataset.paths(), Default::default())?;
+    assert_eq!((reloaded.num_cached, reloaded.num_scanned), (1, 1));
+    let examples = load_examples(&reloaded.indexes)?;
+    assert_eq!(examples.len(), 8);
+    assert_eq!(examples[0], samples::example(10));
+    assert_eq!(examples[5], samples::example(3));
+
+    // a file of the same size with another modification time is stale
+    let file = OpenOptions::new().write(true).open(&dataset.paths()[1])?;
+    file.set_modified(SystemTime::now() + Duration::from_secs(3600))?;
+    let reloaded = indexer::load_paths_cached(dataset.paths(), Default::default())?;
+    assert_eq!((reloaded.num_cached, reloaded.num_scanned), (1, 1));
+    let reloaded = indexer::load_paths_cached(dataset.paths(), Default::default())?;
+    assert_eq!((reloaded.num_cached, reloaded.num_scanned), (2, 0));
+    Ok(())
+}
+
+#[test]
+fn corrupt_index_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 5)?;
+    let path = &dataset.paths()[0];
+    let scanned = indexer::load_paths_cached([path], Default::default())?.indexes;
+    let sidecar = indexer::index_path(path);
+    let intact = fs::read(&sidecar)?;
+
+    // a flipped offset, a truncated sidecar and garbage are all rejected
+    let mut flipped = intact.clone();
+    flipped[40] ^= 0x01;
+    let corruptions = [
+        flipped,
+        intact[..intact.len() - 7].to_vec(),
+        b"not an index".to_vec(),
+        vec![],
+    ];
+    for bytes in corruptions {
+        fs::write(&sidecar, bytes)?;
+        let reloaded = indexer::load_paths_cached([path], Default::default())?;
+        assert_eq!(reloaded.num_scanned, 1);
+        assert_eq!(reloaded.indexes, scanned);
+        // the sidecar is regenerated
+        assert_eq!(fs::read(&sidecar)?, intact);
+    }
+    Ok(())
+}
+
+#[test]
+fn save_indexes_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(2, 3)?;
+    let indexes: Vec<_> =
+        indexer::load_paths(dataset.paths(), Default::default()).collect::<Result<_, _>>()?;
+    indexer::save_indexes(&indexes)?;
+
+    let cached = indexer::load_paths_cached(dataset.paths(), Default::default())?;
+    assert_eq!(cached.num_cached, 2);
+    assert_eq!(cached.indexes, indexes);
+    Ok(())
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use common::*;
+use std::path::PathBuf;
+use tfrecord::{
+    indexer::{self, IndexFilter, RecordIndex},
+    BytesWriter,
+};
+
+fn make_dataset(name: &str) -> Result<Vec<PathBuf>> {
+    let dir = make_temp_dir(&format!("index_filter/{}", name))?;
+
+    ["train-0.tfrecord", "train-1.tfrecord", "valid-0.tfrecord"]
+        .iter()
+        .enumerate()
+        .map(|(file_index, file_name)| {
+            let path = dir.join(file_name);
+            let mut writer = BytesWriter::create(&path)?;
+            for len in 1..=10 {
+                writer.send(vec![file_index as u8; len])?;
+            }
+            writer.flush()?;
+            Ok(path)
+        })
+        .collect()
+}
+
+fn load_all(paths: &[PathBuf]) -> Result<Vec<RecordIndex>> {
+    let indexes = indexer::load_paths(paths, Default::default()).collect::<Result<_, _>>()?;
+    Ok(indexes)
+}
+
+#[test]
+fn len_filter_test() -> Result<()> {
+    let paths = make_dataset("len")?;
+    let all = load_all(&paths)?;
+
+    let filters = [IndexFilter::MinLen(3), IndexFilter::MaxLen(5)];
+    let filtered = indexer::load_paths_filtered(&paths, Default::default(), &filters)?;
+
+    // the filtered indexes are the subset of all indexes
+    let expect: Vec<_> = all
+        .iter()
+        .filter(|index| (3..=5).contains(&index.len))
+        .cloned()
+        .collect();
+    assert_eq!(filtered.indexes, expect);
+    assert_eq!(filtered.num_records(), 9);
+    assert_eq!(filtered.num_excluded(), 21);
+    for stats in &filtered.files {
+        assert_eq!(stats.num_kept, 3);
+        assert_eq!(stats.num_excluded, 7);
+    }
+
+    // indexes refer to the filtered sequence
+    let record: Vec<u8> = filtered.indexes[4].load()?;
+    assert_eq!(record, vec![1u8; 4]);
+    Ok(())
+}
+
+#[test]
+fn custom_filter_test() -> Result<()> {
+    let paths = make_dataset("custom")?;
+    let all = load_all(&paths)?;
+
+    let filter = IndexFilter::custom(|path, _offset, len| {
+        path.file_name().unwrap() != "train-1.tfrecord" && len % 2 == 0
+    });
+    let filtered = indexer::load_paths_filtered(&paths, Default::default(), &[filter])?;
+
+    let expect: Vec<_> = all
+        .iter()
+        .filter(|index| index.path.file_name().unwrap() != "train-1.tfrecord" && index.len % 2 == 0)
+        .cloned()
+        .collect();
+    assert_eq!(filtered.indexes, expect);
+
+    let excluded: Vec<_> = filtered
+        .files
+        .iter()
+        .map(|stats| stats.num_excluded)
+        .collect();
+    assert_eq!(excluded, [5, 10, 5]);
+    Ok(())
+}
+
+#[cfg(feature = "glob")]
+#[test]
+fn file_matches_filter_test() -> Result<()> {
+    let paths = make_dataset("file_matches")?;
+    let all = load_all(&paths)?;
+
+    let filter = IndexFilter::file_matches("**/train-*.tfrecord")?;
+    let filtered = indexer::load_paths_filtered(&paths, Default::default(), &[filter])?;
+
+    assert_eq!(filtered.indexes, all[..20]);
+    assert_eq!(filtered.files[2].num_kept, 0);
+    assert_eq!(filtered.files[2].num_excluded, 10);
+
+    assert!(IndexFilter::file_matches("[").is_err());
+    Ok(())
+}
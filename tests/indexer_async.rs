@@ -1,4 +1,4 @@
-#![cfg(all(feature = "async"))]
+#![cfg(feature = "async")]
 
 mod common;
 
@@ -0,0 +1,223 @@
+use std::io::Cursor;
+use tfrecord::{
+    indexer::{self, RecordIndexerConfig},
+    BytesIter, BytesWriter, Error, Example, ExampleIter, Feature, IntegrityMode,
+    RecordReaderConfig,
+};
+
+const PAYLOAD_LEN: usize = 20;
+
+fn frame(payloads: &[Vec<u8>]) -> Vec<u8> {
+    let mut bytes = vec![];
+    {
+        let mut writer = BytesWriter::from_writer(&mut bytes).unwrap();
+        for payload in payloads {
+            writer.send(payload.clone()).unwrap();
+        }
+    }
+    bytes
+}
+
+/// The offset of the payload of the `index`-th record of [PAYLOAD_LEN] bytes.
+fn payload_offset(index: usize) -> usize {
+    index * (PAYLOAD_LEN + 16) + 12
+}
+
+/// Frame 8 records and flip a payload byte of the given records.
+fn make_corrupted(corrupted: &[usize]) -> Vec<u8> {
+    let payloads: Vec<_> = (0..8).map(|index| vec![index as u8; PAYLOAD_LEN]).collect();
+    let mut bytes = frame(&payloads);
+    for &index in corrupted {
+        bytes[payload_offset(index) + 1] ^= 0xff;
+    }
+    bytes
+}
+
+/// Read all records and return the indexes of checksum mismatches.
+fn read_mismatches(bytes: &[u8], integrity: IntegrityMode) -> Vec<usize> {
+    let config = RecordReaderConfig {
+        integrity,
+        ..Default::default()
+    };
+    let results: Vec<_> = BytesIter::from_reader(bytes, config).collect();
+    assert_eq!(results.len(), 8);
+    results
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, result)| match result {
+            Ok(_) => None,
+            Err(Error::ChecksumMismatch { .. }) => Some(index),
+            Err(err) => panic!("unexpected error: {}", err),
+        })
+        .collect()
+}
+
+fn make_example_bytes() -> Vec<u8> {
+    let example: Example = vec![("value".into(), Feature::from_i64_list(vec![1, 2, 3]))]
+        .into_iter()
+        .collect();
+    let mut bytes = vec![];
+    prost::Message::encode(&example, &mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn on_decode_error_test() {
+    let example = make_example_bytes();
+    let wrong_type = vec![0xff; 4];
+    let mut bytes = frame(&[example.clone(), example.clone(), wrong_type, example]);
+    // make the first tag of the second record undecodable
+    let second_offset = 12 + make_example_bytes().len() + 4 + 12;
+    bytes[second_offset] = 0x07;
+
+    let read = |integrity| -> Vec<_> {
+        let config = RecordReaderConfig {
+            integrity,
+            ..Default::default()
+        };
+        ExampleIter::from_reader(bytes.as_slice(), config).collect()
+    };
+
+    // corrupted bytes are reported as checksum mismatches
+    let results = read(IntegrityMode::OnDecodeError);
+    assert_eq!(results.len(), 4);
+    assert!(results[0].is_ok());
+    assert!(matches!(results[1], Err(Error::ChecksumMismatch { .. })));
+    assert!(matches!(results[2], Err(Error::ExampleDecodeError(_))));
+    assert!(results[3].is_ok());
+
+    // without checking, both are decoding errors
+    let results = read(IntegrityMode::Off);
+    assert!(matches!(results[1], Err(Error::ExampleDecodeError(_))));
+    assert!(matches!(results[2], Err(Error::ExampleDecodeError(_))));
+}
+
+#[test]
+fn sample_test() -> tfrecord::Result<()> {
+    let corrupted = [0, 3, 4, 7];
+    let bytes = make_corrupted(&corrupted);
+
+    // full sampling equals full checking
+    assert_eq!(read_mismatches(&bytes, IntegrityMode::Full), corrupted);
+    assert_eq!(
+        read_mismatches(&bytes, IntegrityMode::sample(1.0, 42)?),
+        corrupted
+    );
+    assert_eq!(
+        read_mismatches(&bytes, IntegrityMode::sample(0.0, 42)?),
+        [0; 0]
+    );
+    assert_eq!(read_mismatches(&bytes, IntegrityMode::Off), [0; 0]);
+
+    // the sampled records are reproducible
+    let bytes = make_corrupted(&(0..8).collect::<Vec<_>>());
+    let mode = IntegrityMode::sample(0.5, 7)?;
+    let sampled = read_mismatches(&bytes, mode);
+    assert_eq!(read_mismatches(&bytes, mode), sampled);
+    assert!(sampled.len() < 8);
+
+    assert!(IntegrityMode::sample(1.5, 0).is_err());
+    Ok(())
+}
+
+#[test]
+fn first_and_last_per_file_test() {
+    let bytes = make_corrupted(&[0, 3, 7]);
+    assert_eq!(
+        read_mismatches(&bytes, IntegrityMode::FirstAndLastPerFile),
+        [0, 7]
+    );
+}
+
+#[test]
+fn indexer_integrity_test() -> tfrecord::Result<()> {
+    let load = |bytes: &[u8], integrity| {
+        let config = RecordIndexerConfig {
+            integrity,
+            ..Default::default()
+        };
+        indexer::load_reader(Cursor::new(bytes), config).collect::<tfrecord::Result<Vec<_>>>()
+    };
+
+    // payloads are checked by the sampling
+    let bytes = make_corrupted(&[5]);
+    assert!(load(&bytes, IntegrityMode::Full).is_err());
+    assert!(load(&bytes, IntegrityMode::sample(1.0, 0)?).is_err());
+    assert_eq!(load(&bytes, IntegrityMode::sample(0.0, 0)?)?.len(), 8);
+    assert_eq!(load(&bytes, IntegrityMode::FirstAndLastPerFile)?.len(), 8);
+    assert!(load(&make_corrupted(&[7]), IntegrityMode::FirstAndLastPerFile).is_err());
+
+    // lengths are always checked unless turned off
+    let mut bytes = make_corrupted(&[]);
+    bytes[payload_offset(2) - 12] ^= 0x01;
+    assert!(matches!(
+        load(&bytes, IntegrityMode::sample(0.0, 0)?),
+        Err(Error::ChecksumMismatch { .. })
+    ));
+    assert!(matches!(
+        load(&bytes, IntegrityMode::OnDecodeError),
+        Err(Error::ChecksumMismatch { .. })
+    ));
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn stream_integrity_test() -> tfrecord::Result<()> {
+    use futures::stream::StreamExt as _;
+    use tfrecord::BytesStream;
+
+    let bytes = make_corrupted(&[0, 3, 7]);
+    let read = |integrity| {
+        let config = RecordReaderConfig {
+            integrity,
+            ..Default::default()
+        };
+        BytesStream::from_reader(futures::io::Cursor::new(bytes.clone()), config)
+    };
+
+    // the stream stops at the first error
+    let first = read(IntegrityMode::sample(1.0, 0)?).next().await.unwrap();
+    assert!(matches!(first, Err(Error::ChecksumMismatch { .. })));
+
+    let records: Vec<_> = read(IntegrityMode::FirstAndLastPerFile)
+        .skip(1)
+        .collect()
+        .await;
+    assert_eq!(records.len(), 0);
+
+    let records: Vec<_> = read(IntegrityMode::sample(0.0, 0)?).collect().await;
+    assert_eq!(records.len(), 8);
+    assert!(records.iter().all(Result::is_ok));
+    Ok(())
+}
+
+#[test]
+#[allow(deprecated)]
+fn check_integrity_migration_test() {
+    assert_eq!(
+        RecordReaderConfig::with_check_integrity(true).integrity,
+        IntegrityMode::Full
+    );
+    assert_eq!(
+        RecordReaderConfig::with_check_integrity(false),
+        RecordReaderConfig {
+            integrity: IntegrityMode::Off,
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        RecordIndexerConfig::with_check_integrity(false).integrity,
+        IntegrityMode::Off
+    );
+
+    // the unchecked bytes are read as they are
+    let bytes = make_corrupted(&[0]);
+    let records: Vec<_> = BytesIter::from_reader(
+        bytes.as_slice(),
+        RecordReaderConfig::with_check_integrity(false),
+    )
+    .collect::<Result<_, _>>()
+    .unwrap();
+    assert_eq!(records.len(), 8);
+}
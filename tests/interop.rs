@@ -0,0 +1,320 @@
+//! Interop regression suite against fixtures written by TensorFlow.
+//!
+//! The fixtures are listed in `tests/interop_fixtures/manifest.json`, one generation per
+//! TensorFlow version, along with the decoded contents expected from each file. They are
+//! generated offline by the script referenced in the manifest, so the suite needs no
+//! Python. Every fixture is read through the record reader, the indexer and, for event
+//! files, the event file reader and the scalar export.
+//!
+//! Fixtures which the crate fails to read carry a `known_failure` note. They are skipped
+//! by the default run and checked by the ignored `interop_known_failures` test, so the
+//! gaps stay visible.
+
+#![cfg(feature = "proto-summary")]
+
+mod common;
+
+use common::*;
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+use tfrecord::{
+    compression::Compression,
+    export::ScalarSeries,
+    indexer::{self, RecordIndexerConfig},
+    protobuf::event::What,
+    EventFileIter, EventIter, EventReaderConfig, Example, ExampleIter, Feature, RecordReaderConfig,
+};
+
+const FIXTURE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/interop_fixtures");
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    generations: Vec<Generation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Generation {
+    name: String,
+    tensorflow: String,
+    status: Status,
+    fixtures: Vec<Fixture>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum Status {
+    KnownGood,
+    KnownGaps,
+    NotGenerated,
+}
+
+#[derive(Debug, Deserialize)]
+struct Fixture {
+    file: String,
+    compression: String,
+    #[serde(default)]
+    known_failure: Option<String>,
+    #[serde(flatten)]
+    contents: Contents,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Contents {
+    Examples {
+        records: Vec<BTreeMap<String, FeatureValue>>,
+    },
+    Events {
+        file_version: String,
+        scalars: Vec<Scalar>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+enum FeatureValue {
+    #[serde(rename = "int64_list")]
+    Int64(Vec<i64>),
+    #[serde(rename = "float_list")]
+    Float(Vec<f32>),
+    #[serde(rename = "bytes_list")]
+    Bytes(Vec<String>),
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Scalar {
+    step: i64,
+    tag: String,
+    value: f32,
+}
+
+fn load_manifest() -> Result<Manifest> {
+    let text = fs::read_to_string(Path::new(FIXTURE_DIR).join("manifest.json"))?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+fn to_example(record: &BTreeMap<String, FeatureValue>) -> Example {
+    record
+        .iter()
+        .map(|(key, value)| {
+            let feature = match value {
+                FeatureValue::Int64(values) => Feature::from_i64_list(values.clone()),
+                FeatureValue::Float(values) => Feature::from_f32_list(values.clone()),
+                FeatureValue::Bytes(values) => Feature::from_bytes_list(
+                    values
+                        .iter()
+                        .map(|value| value.as_bytes().to_vec())
+                        .collect::<Vec<_>>(),
+                ),
+            };
+            (key.clone(), feature)
+        })
+        .collect()
+}
+
+/// Read a fixture through every reading path and compare with the manifest.
+fn check_fixture(path: &Path, fixture: &Fixture) -> Result<()> {
+    match &fixture.contents {
+        Contents::Examples { records } => {
+            let expect: Vec<_> = records.iter().map(to_example).collect();
+
+            // ZLIB is not supported, so such fixtures carry a known_failure note
+            let compression = match fixture.compression.as_str() {
+                "GZIP" => Compression::Gzip,
+                _ => Compression::None,
+            };
+            let config = RecordReaderConfig {
+                compression,
+                ..Default::default()
+            };
+            let examples: Vec<Example> =
+                ExampleIter::open(path, config)?.collect::<Result<_, _>>()?;
+            ensure!(examples == expect, "the reader decoded unexpected examples");
+
+            let config = RecordIndexerConfig {
+                compression,
+                ..Default::default()
+            };
+            let examples: Vec<Example> = indexer::load_paths([path], config)
+                .map(|index| index?.load())
+                .collect::<Result<_, _>>()?;
+            ensure!(examples == expect, "the indexer loaded unexpected examples");
+        }
+        Contents::Events {
+            file_version,
+            scalars,
+        } => {
+            let events: Vec<_> =
+                EventIter::open(path, Default::default())?.collect::<Result<_, _>>()?;
+            let found_version = events.first().and_then(|event| match &event.what {
+                Some(What::FileVersion(version)) => Some(version.as_str()),
+                _ => None,
+            });
+            ensure!(
+                found_version == Some(file_version.as_str()),
+                "expect the file version {}, but found {:?}",
+                file_version,
+                found_version
+            );
+
+            // the event file reader finds the same scalars, from either encoding
+            let config = EventReaderConfig {
+                fail_on_corrupt: true,
+                ..Default::default()
+            };
+            let found: Vec<_> = EventFileIter::open(path, config)?
+                .scalars("")
+                .map(|point| {
+                    let (tag, point) = point?;
+                    Ok(Scalar {
+                        step: point.step,
+                        tag,
+                        value: point.value,
+                    })
+                })
+                .collect::<Result<_>>()?;
+            ensure!(
+                &found == scalars,
+                "expect the scalars {:?}, but the event file reader found {:?}",
+                scalars,
+                found
+            );
+
+            let found: Vec<_> = ScalarSeries::from_events(events.into_iter().map(Ok))?
+                .into_iter()
+                .flat_map(|series| {
+                    let tag = series.tag;
+                    series.points.into_iter().map(move |point| Scalar {
+                        step: point.step,
+                        tag: tag.clone(),
+                        value: point.value,
+                    })
+                })
+                .collect();
+            ensure!(
+                &found == scalars,
+                "expect the scalars {:?}, but found {:?}",
+                scalars,
+                found
+            );
+        }
+    }
+    Ok(())
+}
+
+fn fixture_path(generation: &Generation, fixture: &Fixture) -> PathBuf {
+    Path::new(FIXTURE_DIR)
+        .join(&generation.name)
+        .join(&fixture.file)
+}
+
+#[test]
+fn interop_manifest_is_consistent() -> Result<()> {
+    let manifest = load_manifest()?;
+    let mut names = BTreeSet::new();
+
+    for generation in &manifest.generations {
+        ensure!(
+            names.insert(&generation.name),
+            "the generation {} is listed twice",
+            generation.name
+        );
+
+        let num_failures = generation
+            .fixtures
+            .iter()
+            .filter(|fixture| fixture.known_failure.is_some())
+            .count();
+        let expect_status = match (generation.fixtures.is_empty(), num_failures) {
+            (true, _) => Status::NotGenerated,
+            (false, 0) => Status::KnownGood,
+            (false, _) => Status::KnownGaps,
+        };
+        ensure!(
+            generation.status == expect_status,
+            "the generation {} must have the status {:?}",
+            generation.name,
+            expect_status
+        );
+
+        // every checked-in file is covered by the manifest
+        let dir = Path::new(FIXTURE_DIR).join(&generation.name);
+        let listed: BTreeSet<_> = generation
+            .fixtures
+            .iter()
+            .map(|fixture| fixture.file.as_str())
+            .collect();
+        if dir.exists() {
+            for entry in fs::read_dir(&dir)? {
+                let file_name = entry?.file_name();
+                let file_name = file_name.to_string_lossy();
+                ensure!(
+                    listed.contains(&*file_name),
+                    "the file {} is not listed in the manifest",
+                    dir.join(&*file_name).display()
+                );
+            }
+        }
+        for fixture in &generation.fixtures {
+            ensure!(
+                ["NONE", "GZIP", "ZLIB"].contains(&fixture.compression.as_str()),
+                "unknown compression {} of {}",
+                fixture.compression,
+                fixture.file
+            );
+            let path = fixture_path(generation, fixture);
+            ensure!(path.is_file(), "the fixture {} is missing", path.display());
+        }
+
+        println!(
+            "TensorFlow {} ({}): {:?}, {} fixtures, {} known failures",
+            generation.tensorflow,
+            generation.name,
+            generation.status,
+            generation.fixtures.len(),
+            num_failures
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn interop_fixtures() -> Result<()> {
+    let manifest = load_manifest()?;
+    for generation in &manifest.generations {
+        for fixture in &generation.fixtures {
+            if let Some(note) = &fixture.known_failure {
+                println!("skip {}/{}: {}", generation.name, fixture.file, note);
+                continue;
+            }
+            check_fixture(&fixture_path(generation, fixture), fixture).map_err(|err| {
+                format_err!(
+                    "unable to read the fixture {}/{}: {:#}",
+                    generation.name,
+                    fixture.file,
+                    err
+                )
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Check the fixtures with known failures. Once a gap is fixed, remove the
+/// `known_failure` note of the fixture in the manifest.
+#[test]
+#[ignore = "known interop gaps, see the known_failure notes in tests/interop_fixtures/manifest.json"]
+fn interop_known_failures() -> Result<()> {
+    let manifest = load_manifest()?;
+    for generation in &manifest.generations {
+        for fixture in &generation.fixtures {
+            if fixture.known_failure.is_some() {
+                check_fixture(&fixture_path(generation, fixture), fixture)?;
+            }
+        }
+    }
+    Ok(())
+}
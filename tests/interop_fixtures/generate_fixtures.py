@@ -0,0 +1,131 @@
+#!/usr/bin/env python3
+"""Generate the interop fixtures of a TensorFlow version.
+
+Run it offline in an environment with the TensorFlow version to be covered, for example
+
+    pip install tensorflow==2.9
+    python3 tests/interop_fixtures/generate_fixtures.py --out tests/interop_fixtures/tf-2.9
+
+The fixture files are written to the output directory and the generation entry of
+manifest.json is printed to stdout. The examples match `tfrecord::samples::example`.
+"""
+
+import argparse
+import glob
+import json
+import os
+import shutil
+
+import tensorflow as tf
+
+NUM_EXAMPLES = 4
+NUM_STEPS = 3
+
+
+def sample(index):
+    return {
+        "id": {"int64_list": [index]},
+        "label": {"int64_list": [index % 10]},
+        "score": {"float_list": [index / 2.0]},
+        "name": {"bytes_list": ["sample-{}".format(index)]},
+    }
+
+
+def to_example(features):
+    def to_feature(value):
+        (kind, values), = value.items()
+        if kind == "int64_list":
+            return tf.train.Feature(int64_list=tf.train.Int64List(value=values))
+        if kind == "float_list":
+            return tf.train.Feature(float_list=tf.train.FloatList(value=values))
+        return tf.train.Feature(
+            bytes_list=tf.train.BytesList(value=[v.encode() for v in values])
+        )
+
+    return tf.train.Example(
+        features=tf.train.Features(
+            feature={key: to_feature(value) for key, value in features.items()}
+        )
+    )
+
+
+def write_examples(out_dir, compression):
+    suffix = "" if compression == "NONE" else "." + compression.lower()
+    file_name = "examples{}.tfrecord".format(suffix)
+    records = [sample(index) for index in range(NUM_EXAMPLES)]
+
+    options = tf.io.TFRecordOptions(compression_type=compression)
+    with tf.io.TFRecordWriter(os.path.join(out_dir, file_name), options) as writer:
+        for features in records:
+            writer.write(to_example(features).SerializeToString())
+
+    return {
+        "file": file_name,
+        "kind": "examples",
+        "compression": compression,
+        "records": records,
+    }
+
+
+def scalar(step):
+    return 1.0 / (step + 1)
+
+
+def write_events(out_dir):
+    log_dir = os.path.join(out_dir, "logs")
+    if tf.__version__.startswith("1."):
+        # TF 1.x stores scalars in simple_value
+        writer = tf.compat.v1.summary.FileWriter(log_dir)
+        for step in range(NUM_STEPS):
+            summary = tf.compat.v1.Summary(
+                value=[tf.compat.v1.Summary.Value(tag="loss", simple_value=scalar(step))]
+            )
+            writer.add_summary(summary, step)
+        writer.close()
+    else:
+        # TF 2.x stores scalars in tensors
+        writer = tf.summary.create_file_writer(log_dir)
+        with writer.as_default():
+            for step in range(NUM_STEPS):
+                tf.summary.scalar("loss", scalar(step), step=step)
+        writer.close()
+
+    # keep the file name chosen by TensorFlow, which varies across versions
+    (path,) = glob.glob(os.path.join(log_dir, "events.out.tfevents.*"))
+    file_name = os.path.basename(path)
+    shutil.move(path, os.path.join(out_dir, file_name))
+    shutil.rmtree(log_dir)
+
+    return {
+        "file": file_name,
+        "kind": "events",
+        "compression": "NONE",
+        "file_version": "brain.Event:2",
+        "scalars": [
+            {"step": step, "tag": "loss", "value": scalar(step)}
+            for step in range(NUM_STEPS)
+        ],
+    }
+
+
+def main():
+    parser = argparse.ArgumentParser()
+    parser.add_argument("--out", required=True)
+    args = parser.parse_args()
+
+    os.makedirs(args.out, exist_ok=True)
+    version = tf.__version__
+    fixtures = [write_examples(args.out, compression) for compression in ["NONE", "GZIP", "ZLIB"]]
+    fixtures.append(write_events(args.out))
+
+    generation = {
+        "name": os.path.basename(os.path.normpath(args.out)),
+        "tensorflow": version,
+        "status": "known-good",
+        "fixtures": fixtures,
+    }
+    print(json.dumps(generation, indent=2))
+
+
+if __name__ == "__main__":
+    main()
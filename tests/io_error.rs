@@ -0,0 +1,217 @@
+#![cfg(feature = "testing")]
+
+use std::{
+    io::{self, ErrorKind, Read},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+use tfrecord::{
+    cancel::Progress,
+    indexer::{self, RecordIndex},
+    samples, BytesIter, Error, Example,
+};
+
+#[test]
+fn io_error_round_trip() {
+    let error = Error::ChecksumMismatch {
+        expect: 1,
+        found: 2,
+        path: None,
+        offset: None,
+    };
+    let io_error = io::Error::from(error);
+    assert_eq!(io_error.kind(), ErrorKind::InvalidData);
+
+    // the original error is recovered by downcasting
+    let inner = io_error.get_ref().unwrap().downcast_ref::<Error>().unwrap();
+    assert!(matches!(
+        inner,
+        Error::ChecksumMismatch {
+            expect: 1,
+            found: 2,
+            path: None,
+            offset: None,
+        }
+    ));
+
+    // the reverse conversion unwraps instead of nesting
+    let error = Error::from(io_error);
+    assert!(matches!(
+        error,
+        Error::ChecksumMismatch {
+            expect: 1,
+            found: 2,
+            path: None,
+            offset: None,
+        }
+    ));
+
+    // plain I/O errors pass through both ways
+    let io_error = io::Error::new(ErrorKind::PermissionDenied, "denied");
+    let io_error = io::Error::from(Error::from(io_error));
+    assert_eq!(io_error.kind(), ErrorKind::PermissionDenied);
+    assert_eq!(io_error.to_string(), "denied");
+}
+
+#[test]
+fn io_error_kinds() {
+    let cases = [
+        (Error::UnexpectedEof, ErrorKind::UnexpectedEof),
+        (
+            Error::ChecksumMismatch {
+                expect: 0,
+                found: 1,
+                path: None,
+                offset: None,
+            },
+            ErrorKind::InvalidData,
+        ),
+        (
+            Error::ExampleDecodeError(prost::DecodeError::new("bad")),
+            ErrorKind::InvalidData,
+        ),
+        (
+            Error::ConversionError { desc: "bad".into() },
+            ErrorKind::InvalidData,
+        ),
+        (
+            Error::InvalidArgumentsError { desc: "bad".into() },
+            ErrorKind::InvalidInput,
+        ),
+        (
+            Error::LimitExceeded {
+                which: "record_len",
+                limit: 1,
+                observed: 2,
+            },
+            ErrorKind::InvalidData,
+        ),
+        (
+            Error::Timeout {
+                operation: "read",
+                elapsed: Duration::from_secs(1),
+            },
+            ErrorKind::TimedOut,
+        ),
+        (
+            Error::Unsupported {
+                capability: "gzip".into(),
+                feature: None,
+            },
+            ErrorKind::Unsupported,
+        ),
+        (
+            Error::Cancelled {
+                progress: Progress::default(),
+            },
+            ErrorKind::Interrupted,
+        ),
+        (
+            Error::WriterPoisoned {
+                original: Arc::new(Error::UnexpectedEof),
+            },
+            ErrorKind::UnexpectedEof,
+        ),
+        (
+            Error::from_io_with_context(
+                io::Error::new(ErrorKind::NotFound, "missing"),
+                "a.tfrecord",
+                Some(12),
+            ),
+            ErrorKind::NotFound,
+        ),
+    ];
+    for (error, kind) in cases {
+        let description = error.to_string();
+        assert_eq!(error.io_error_kind(), kind, "{}", description);
+        let io_error = io::Error::from(error);
+        assert_eq!(io_error.kind(), kind, "{}", description);
+        assert_eq!(io_error.to_string(), description);
+    }
+}
+
+#[test]
+fn io_errors_carry_file_context() {
+    let path = PathBuf::from("/nonexistent/file.tfrecord");
+    let index = RecordIndex {
+        path: Arc::new(path.clone()),
+        offset: 12,
+        len: 4,
+    };
+    let error = index.load::<Vec<u8>>().unwrap_err();
+    match &error {
+        Error::IoErrorWithContext {
+            path: error_path,
+            offset: None,
+            source,
+        } => {
+            assert_eq!(*error_path, path);
+            assert_eq!(source.kind(), ErrorKind::NotFound);
+        }
+        error => panic!("unexpected error {:?}", error),
+    }
+    assert!(error.to_string().contains("file.tfrecord"));
+    assert!(std::error::Error::source(&error).is_some());
+
+    // reading past the end of a truncated file reports the offset
+    let dataset = samples::tiny_dataset(1, 2).unwrap();
+    let path = &dataset.paths()[0];
+    let index = RecordIndex {
+        path: Arc::new(path.clone()),
+        offset: 1_000_000,
+        len: 4,
+    };
+    match indexer::iter_from::<Example>(&[index], 0, Default::default()).next() {
+        Some(Err(Error::IoErrorWithContext {
+            offset: Some(1_000_000),
+            source,
+            ..
+        })) => assert_eq!(source.kind(), ErrorKind::UnexpectedEof),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+/// A [Read] adapter over a record iterator, yielding the concatenated payloads.
+struct PayloadReader<R: Read> {
+    records: BytesIter<R>,
+    pending: io::Cursor<Vec<u8>>,
+}
+
+impl<R: Read> Read for PayloadReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let len = self.pending.read(buf)?;
+            if len > 0 || buf.is_empty() {
+                return Ok(len);
+            }
+            match self.records.next() {
+                Some(record) => self.pending = io::Cursor::new(record?),
+                None => return Ok(0),
+            }
+        }
+    }
+}
+
+#[test]
+fn io_adapter_preserves_errors() {
+    // a corrupted payload checksum surfaces through the adapter
+    let (mut writer, buffer) = tfrecord::BytesWriter::in_memory().unwrap();
+    writer.send(b"hello".to_vec()).unwrap();
+    writer.flush().unwrap();
+    let mut bytes = buffer.to_vec();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xff;
+
+    let mut reader = PayloadReader {
+        records: BytesIter::from_reader(io::Cursor::new(bytes), Default::default()),
+        pending: Default::default(),
+    };
+    let mut output = vec![];
+    let io_error = reader.read_to_end(&mut output).unwrap_err();
+    assert_eq!(io_error.kind(), ErrorKind::InvalidData);
+    assert!(matches!(
+        Error::from(io_error),
+        Error::ChecksumMismatch { .. }
+    ));
+}
@@ -0,0 +1,192 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::path::{Path, PathBuf};
+use tfrecord::{
+    join::{self, DuplicateKeys, JoinOptions, JoinType, MissingMatch},
+    protobuf_ext::MergePolicy,
+    samples::{self, TempDataset},
+    Error, Example, ExampleIter, ExampleWriter,
+};
+
+/// Write attributes of the ids to a build file.
+fn write_attributes(dir: &Path, name: &str, ids: &[i64]) -> Result<PathBuf> {
+    let path = dir.join(name);
+    let mut writer = ExampleWriter::create(&path)?;
+    for (nth, &id) in ids.iter().enumerate() {
+        let mut example = Example::empty();
+        example.push_i64s("id", &[id]);
+        example.push_i64s("attribute", &[id * 100 + nth as i64]);
+        writer.send(example)?;
+    }
+    writer.flush()?;
+    Ok(path)
+}
+
+/// The ids and attributes of joined examples.
+fn ids_and_attributes(joined: Vec<tfrecord::Result<Example>>) -> Result<Vec<(i64, Option<i64>)>> {
+    joined
+        .into_iter()
+        .map(|example| {
+            let example = example?;
+            let attribute = example.get_i64s("attribute").ok().map(|values| values[0]);
+            Ok((example.get_i64s("id")?[0], attribute))
+        })
+        .collect()
+}
+
+/// Join the probe dataset with the build file on `id`, collecting the results.
+fn join_with(
+    probe: &TempDataset,
+    build: &Path,
+    options: JoinOptions,
+) -> tfrecord::Result<Vec<tfrecord::Result<Example>>> {
+    let joined = join::hash_join(
+        probe.paths(),
+        [build],
+        join::feature_key("id"),
+        join::feature_key("id"),
+        options,
+    )?;
+    Ok(joined.collect())
+}
+
+#[test]
+fn join_types_test() -> Result<()> {
+    // ids 0..6 on the probe side, even ids on the build side
+    let probe = samples::tiny_dataset(2, 3)?;
+    let build = write_attributes(probe.dir(), "build.tfrecord", &[0, 2, 4, 8])?;
+
+    let inner = ids_and_attributes(join_with(&probe, &build, JoinOptions::default())?)?;
+    assert_eq!(inner, [(0, Some(0)), (2, Some(201)), (4, Some(402))]);
+
+    let left = |missing| JoinOptions {
+        join_type: JoinType::Left(missing),
+        ..Default::default()
+    };
+    let kept = ids_and_attributes(join_with(
+        &probe,
+        &build,
+        left(MissingMatch::KeepUnenriched),
+    )?)?;
+    assert_eq!(
+        kept,
+        [
+            (0, Some(0)),
+            (1, None),
+            (2, Some(201)),
+            (3, None),
+            (4, Some(402)),
+            (5, None)
+        ]
+    );
+    let skipped = ids_and_attributes(join_with(&probe, &build, left(MissingMatch::Skip))?)?;
+    assert_eq!(skipped, inner);
+
+    let results = join_with(&probe, &build, left(MissingMatch::Error))?;
+    assert_eq!(results.len(), 2);
+    assert!(matches!(
+        &results[1],
+        Err(Error::RecordFailed { index: 1, .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn merged_outputs_test() -> Result<()> {
+    let probe = samples::tiny_dataset(1, 4)?;
+    let build = write_attributes(probe.dir(), "build.tfrecord", &[0, 1, 2, 3])?;
+
+    // write the joined examples and read them back
+    let output = probe.dir().join("joined.tfrecord");
+    let mut writer = ExampleWriter::create(&output)?;
+    for example in join_with(&probe, &build, JoinOptions::default())? {
+        writer.send(example?)?;
+    }
+    writer.flush()?;
+    let examples: Vec<Example> =
+        ExampleIter::open(&output, Default::default())?.collect::<Result<_, _>>()?;
+    assert_eq!(examples.len(), 4);
+    for (id, example) in examples.into_iter().enumerate() {
+        let mut expect = samples::example(id);
+        expect.push_i64s("attribute", &[id as i64 * 101]);
+        assert_eq!(example, expect);
+    }
+
+    // a colliding feature with different values
+    let path = probe.dir().join("labels.tfrecord");
+    let mut writer = ExampleWriter::create(&path)?;
+    for id in 0..4 {
+        let mut example = Example::empty();
+        example.push_i64s("id", &[id]);
+        example.push_i64s("label", &[42]);
+        writer.send(example)?;
+    }
+    writer.flush()?;
+    assert!(join_with(&probe, &path, JoinOptions::default())?
+        .remove(0)
+        .is_err());
+    let prefixed = JoinOptions {
+        collision: MergePolicy::PrefixOther("build/".into()),
+        ..Default::default()
+    };
+    let example = join_with(&probe, &path, prefixed)?.remove(0)?;
+    assert_eq!(example.get_i64s("label")?, [0]);
+    assert_eq!(example.get_i64s("build/label")?, [42]);
+    let keep_other = JoinOptions {
+        collision: MergePolicy::KeepOther,
+        ..Default::default()
+    };
+    let example = join_with(&probe, &path, keep_other)?.remove(0)?;
+    assert_eq!(example.get_i64s("label")?, [42]);
+    Ok(())
+}
+
+#[test]
+fn duplicate_build_keys_test() -> Result<()> {
+    let probe = samples::tiny_dataset(1, 3)?;
+    let build = write_attributes(probe.dir(), "build.tfrecord", &[1, 1, 2])?;
+    let with = |duplicates| JoinOptions {
+        duplicates,
+        ..Default::default()
+    };
+
+    let first = ids_and_attributes(join_with(&probe, &build, with(DuplicateKeys::First))?)?;
+    assert_eq!(first, [(1, Some(100)), (2, Some(202))]);
+    let last = ids_and_attributes(join_with(&probe, &build, with(DuplicateKeys::Last))?)?;
+    assert_eq!(last, [(1, Some(101)), (2, Some(202))]);
+    let err = join_with(&probe, &build, with(DuplicateKeys::Error))
+        .err()
+        .unwrap();
+    assert!(matches!(err, Error::RecordFailed { index: 1, .. }));
+    Ok(())
+}
+
+#[test]
+fn memory_cap_test() -> Result<()> {
+    let probe = samples::tiny_dataset(1, 3)?;
+    let ids: Vec<i64> = (0..100).collect();
+    let build = write_attributes(probe.dir(), "build.tfrecord", &ids)?;
+
+    let capped = JoinOptions {
+        max_build_bytes: 256,
+        ..Default::default()
+    };
+    let err = join_with(&probe, &build, capped).err().unwrap();
+    assert!(matches!(
+        err,
+        Error::JoinBuildTooLarge { limit: 256, num_keys } if num_keys < 100
+    ));
+    assert!(err.to_string().contains("max_build_bytes"));
+    let joined = join::hash_join(
+        probe.paths(),
+        [&build],
+        join::feature_key("id"),
+        join::feature_key("id"),
+        JoinOptions::default(),
+    )?;
+    assert_eq!(joined.num_build_keys(), 100);
+    Ok(())
+}
@@ -0,0 +1,198 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Wake, Waker},
+    time::{Duration, Instant},
+};
+use tfrecord::{samples, BytesIter, Error, Example, ExampleIter, JournalConfig, JournalWriter};
+
+#[test]
+fn durable_records_survive_abandon_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(0, 0)?;
+    let path = dataset.dir().join("journal.tfrecord");
+    let config = JournalConfig {
+        commit_window: Duration::from_millis(1),
+        ..Default::default()
+    };
+    let writer = JournalWriter::<Example>::create(&path, config)?;
+
+    for index in 0..10 {
+        assert_eq!(writer.send_durable(samples::example(index))?, index as u64);
+
+        // the acknowledged record is in the file
+        let examples: Vec<_> =
+            ExampleIter::open(&path, Default::default())?.collect::<Result<_, _>>()?;
+        assert_eq!(examples.len(), index + 1);
+        assert_eq!(examples[index], samples::example(index));
+    }
+
+    // simulate a crash right after acknowledgement, with records in flight
+    let tickets: Vec<_> = (10..20)
+        .map(|index| writer.submit(samples::example(index)))
+        .collect::<Result<_, _>>()?;
+    writer.abandon();
+
+    let examples: Vec<_> =
+        ExampleIter::open(&path, Default::default())?.collect::<Result<_, _>>()?;
+    assert!((10..=20).contains(&examples.len()));
+    for (index, example) in examples.iter().enumerate() {
+        assert_eq!(example, &samples::example(index));
+    }
+    // tickets resolve by whether their records made it
+    for ticket in tickets {
+        let seq = ticket.seq();
+        match ticket.wait() {
+            Ok(durable) => assert!(durable < examples.len() as u64),
+            Err(Error::WriterPoisoned { original }) => {
+                assert!(seq >= examples.len() as u64);
+                assert!(matches!(*original, Error::Cancelled { .. }));
+            }
+            Err(err) => panic!("unexpected error {}", err),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn burst_is_group_committed_test() -> Result<()> {
+    const NUM_THREADS: usize = 8;
+    const PER_THREAD: usize = 50;
+
+    let dataset = samples::tiny_dataset(0, 0)?;
+    let path = dataset.dir().join("burst.tfrecord");
+    let writer = JournalWriter::<Vec<u8>>::create(&path, Default::default())?;
+
+    let acknowledged: Vec<Vec<(u64, Vec<u8>)>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..NUM_THREADS)
+            .map(|thread| {
+                let writer = &writer;
+                scope.spawn(move || {
+                    (0..PER_THREAD)
+                        .map(|index| {
+                            let record = format!("{}-{}", thread, index).into_bytes();
+                            let seq = writer.send_durable(record.clone()).unwrap();
+                            (seq, record)
+                        })
+                        .collect()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    let stats = writer.stats();
+    assert_eq!(writer.close()?, (NUM_THREADS * PER_THREAD) as u64);
+
+    // records are stored at their sequence numbers
+    let records: Vec<_> = BytesIter::open(&path, Default::default())?.collect::<Result<_, _>>()?;
+    assert_eq!(records.len(), NUM_THREADS * PER_THREAD);
+    for (seq, record) in acknowledged.into_iter().flatten() {
+        assert_eq!(records[seq as usize], record);
+    }
+
+    // concurrent senders share commits
+    assert_eq!(stats.num_records, (NUM_THREADS * PER_THREAD) as u64);
+    assert!(stats.num_commits < stats.num_records, "{:?}", stats);
+    assert!(stats.max_batch_records > 1);
+    Ok(())
+}
+
+#[test]
+fn commit_triggers_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(0, 0)?;
+
+    // a lone record waits for the window
+    let window = Duration::from_millis(30);
+    let config = JournalConfig {
+        commit_window: window,
+        ..Default::default()
+    };
+    let writer = JournalWriter::<Vec<u8>>::create(dataset.dir().join("a.tfrecord"), config)?;
+    let since = Instant::now();
+    writer.send_durable(vec![1])?;
+    assert!(since.elapsed() >= window);
+
+    // a full batch commits before the window elapses
+    let config = JournalConfig {
+        commit_window: Duration::from_secs(60),
+        max_batch_records: 4,
+        ..Default::default()
+    };
+    let writer = JournalWriter::<Vec<u8>>::create(dataset.dir().join("b.tfrecord"), config)?;
+    let since = Instant::now();
+    let tickets: Vec<_> = (0..4u8)
+        .map(|index| writer.submit(vec![index]))
+        .collect::<Result<_, _>>()?;
+    for (index, ticket) in tickets.into_iter().enumerate() {
+        assert_eq!(ticket.wait()?, index as u64);
+    }
+    assert!(since.elapsed() < Duration::from_secs(30));
+    assert_eq!(writer.stats().num_commits, 1);
+
+    // tickets are futures, and closing commits pending records
+    let ticket = writer.submit(vec![4])?;
+    assert!(!ticket.is_durable());
+    let num_durable = std::thread::scope(|scope| {
+        let waiter = scope.spawn(|| async_std::task::block_on(ticket));
+        let num_durable = writer.close();
+        assert_eq!(waiter.join().unwrap().unwrap(), 4);
+        num_durable
+    })?;
+    assert_eq!(num_durable, 5);
+    Ok(())
+}
+
+#[test]
+fn repeated_polls_keep_one_waker_test() -> Result<()> {
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let dataset = samples::tiny_dataset(0, 0)?;
+    let config = JournalConfig {
+        commit_window: Duration::from_secs(60),
+        ..Default::default()
+    };
+    let writer = JournalWriter::<Vec<u8>>::create(dataset.dir().join("poll.tfrecord"), config)?;
+    let mut ticket = writer.submit(vec![0])?;
+
+    let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+    for _ in 0..100 {
+        assert!(Pin::new(&mut ticket).poll(&mut cx).is_pending());
+    }
+
+    // the commit on close wakes the ticket once
+    assert_eq!(writer.close()?, 1);
+    assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+    assert!(matches!(
+        Pin::new(&mut ticket).poll(&mut cx),
+        Poll::Ready(Ok(0))
+    ));
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn failed_sync_poisons_test() -> Result<()> {
+    let file = std::fs::OpenOptions::new().write(true).open("/dev/full")?;
+    let writer = JournalWriter::<Vec<u8>>::from_file(file, Default::default())?;
+    let err = writer.send_durable(vec![0; 16]).unwrap_err();
+    assert!(matches!(err, Error::WriterPoisoned { .. }), "{}", err);
+    assert!(writer.submit(vec![1]).is_err());
+    assert!(writer.close().is_err());
+    Ok(())
+}
@@ -0,0 +1,239 @@
+#![cfg(all(feature = "testing", feature = "incremental"))]
+//! Pathological examples with huge numbers of features or distinct values are processed
+//! or rejected within bounded memory.
+//!
+//! The file holds a single test, since the peak of the global allocator is shared by
+//! the threads of the test harness.
+
+mod common;
+
+use common::*;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use tfrecord::{
+    incremental::{self, NumericStats, OnOversized, UpdateOptions, VocabCounter},
+    samples,
+    subset::{self, Budget, Stratify, SubsetSpec},
+    Error, Example, ExampleWriter, Feature, Limits, RecordReaderConfig,
+};
+
+/// The number of features of an exploded example.
+const NUM_EXPLODED_FEATURES: usize = 100_000;
+
+/// The global allocator tracking the live and the peak heap bytes.
+struct PeakAlloc;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+impl PeakAlloc {
+    fn grow(size: usize) {
+        let live = LIVE.fetch_add(size, Ordering::SeqCst) + size;
+        PEAK.fetch_max(live, Ordering::SeqCst);
+    }
+
+    fn shrink(size: usize) {
+        LIVE.fetch_sub(size, Ordering::SeqCst);
+    }
+}
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        Self::shrink(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            Self::grow(new_size);
+            Self::shrink(layout.size());
+        }
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAlloc = PeakAlloc;
+
+/// Run the closure, returning its output and the peak of heap bytes allocated beyond
+/// the live bytes at the start.
+fn peak_during<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let base = LIVE.load(Ordering::SeqCst);
+    PEAK.store(base, Ordering::SeqCst);
+    let output = f();
+    (output, PEAK.load(Ordering::SeqCst).saturating_sub(base))
+}
+
+/// An example with [NUM_EXPLODED_FEATURES] tiny features.
+fn exploded_example() -> Example {
+    (0..NUM_EXPLODED_FEATURES)
+        .map(|index| (format!("f{}", index), Feature::from_i64_list(vec![1])))
+        .collect()
+}
+
+fn write_examples(path: &Path, examples: impl IntoIterator<Item = Example>) -> Result<()> {
+    let mut writer = ExampleWriter::create(path)?;
+    for example in examples {
+        writer.send(example)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Unwrap the limit violation from the failure of a record.
+fn limit_exceeded<T>(result: tfrecord::Result<T>) -> (&'static str, u64, u64) {
+    let err = match result {
+        Err(Error::RecordFailed { source, .. }) => *source,
+        Err(err) => err,
+        Ok(_) => panic!("the limit is not enforced"),
+    };
+    match err {
+        Error::LimitExceeded {
+            which,
+            limit,
+            observed,
+        } => (which, limit, observed),
+        err => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn key_explosion_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 4)?;
+    let dir = dataset.dir();
+    let exploded = exploded_example();
+    let exploded_bytes = exploded.encode_canonical_to_vec();
+    // reading a record takes a buffer of its length, but no per-feature state
+    let cap = 2 * exploded_bytes.len() + (1 << 20);
+
+    // unbounded decoding holds every feature, which the cap detects
+    let (example, peak) =
+        peak_during(|| Example::decode_with_limits(&exploded_bytes, &Limits::default()));
+    assert_eq!(example?.into_vec().len(), NUM_EXPLODED_FEATURES);
+    assert!(peak > cap, "the cap {} is not below {}", cap, peak);
+
+    // strict decoding rejects the example before holding any feature
+    let (result, peak) =
+        peak_during(|| Example::decode_with_limits(&exploded_bytes, &Limits::strict()));
+    assert_eq!(limit_exceeded(result), ("max_features", 10_000, 10_001));
+    assert!(peak < cap, "peak {} exceeds {}", peak, cap);
+
+    // statistics skip and count the exploded records, or fail on them
+    let mixed_path = dir.join("mixed.tfrecord");
+    write_examples(
+        &mixed_path,
+        [
+            samples::example(0),
+            exploded.clone(),
+            samples::example(1),
+            exploded,
+            samples::example(2),
+        ],
+    )?;
+    let skip = UpdateOptions {
+        on_oversized: OnOversized::Skip,
+        reader: RecordReaderConfig {
+            limits: Limits::strict(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (updated, peak) = peak_during(|| {
+        incremental::update(
+            dir.join("score.stats.json"),
+            [&mixed_path],
+            NumericStats::new("score"),
+            skip.clone(),
+        )
+    });
+    let updated = updated?;
+    assert_eq!(updated.skipped_records, 2);
+    assert_eq!(updated.accumulated.accumulator.count, 3);
+    assert!(peak < cap, "peak {} exceeds {}", peak, cap);
+
+    let fail = UpdateOptions {
+        on_oversized: OnOversized::Error,
+        ..skip
+    };
+    let result = incremental::update(
+        dir.join("name.vocab.json"),
+        [&mixed_path],
+        VocabCounter::new("name"),
+        fail,
+    );
+    assert!(matches!(&result, Err(Error::RecordFailed { index: 1, .. })));
+    assert_eq!(limit_exceeded(result), ("max_features", 10_000, 10_001));
+
+    // an exploding vocabulary fails once it holds too many distinct values
+    let tokens_path = dir.join("tokens.tfrecord");
+    write_examples(
+        &tokens_path,
+        (0..100).map(|record| {
+            let tokens: Vec<Vec<u8>> = (0..1000)
+                .map(|token| format!("token-{}-{}", record, token).into_bytes())
+                .collect();
+            vec![("tokens".to_string(), Feature::from_bytes_list(tokens))]
+                .into_iter()
+                .collect()
+        }),
+    )?;
+    let options = UpdateOptions {
+        reader: RecordReaderConfig {
+            limits: Limits {
+                max_distinct_keys: 10_000,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (result, peak) = peak_during(|| {
+        incremental::update(
+            dir.join("tokens.vocab.json"),
+            [&tokens_path],
+            VocabCounter::new("tokens"),
+            options,
+        )
+    });
+    assert!(matches!(
+        &result,
+        Err(Error::RecordFailed { index: 10, .. })
+    ));
+    assert_eq!(
+        limit_exceeded(result),
+        ("max_distinct_keys", 10_000, 11_000)
+    );
+    // the 100k distinct values are never held at once
+    assert!(peak < cap, "peak {} exceeds {}", peak, cap);
+    assert!(!dir.join("tokens.vocab.json").exists());
+
+    // so do the strata of a subset
+    let spec = SubsetSpec {
+        stratify: Some(Stratify::new(|bytes: &[u8]| {
+            Ok(format!(
+                "{:?}",
+                Example::decode_with_limits(bytes, &Limits::default())?.get_i64s("id")?
+            ))
+        })),
+        limits: Limits {
+            max_distinct_keys: 2,
+            ..Default::default()
+        },
+        ..SubsetSpec::new(Budget::Records(2))
+    };
+    let result = subset::create_subset(dataset.paths(), dir.join("subset").to_str().unwrap(), spec);
+    assert_eq!(limit_exceeded(result), ("max_distinct_keys", 2, 3));
+    Ok(())
+}
@@ -0,0 +1,137 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::{
+    io::{self, Read},
+    thread,
+    time::Duration,
+};
+use tfrecord::{
+    indexer,
+    latency::{LatencyRecorder, Phase, PhaseStats, LATENCY_BOUNDS},
+    record_reader::RecordReaderConfig,
+    samples, BytesIter, BytesWriter, Example,
+};
+
+/// A reader sleeping before every read.
+struct SlowReader<R> {
+    inner: R,
+    delay: Duration,
+}
+
+impl<R: Read> Read for SlowReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        thread::sleep(self.delay);
+        self.inner.read(buf)
+    }
+}
+
+fn config(recorder: &LatencyRecorder) -> RecordReaderConfig {
+    RecordReaderConfig {
+        latency: Some(recorder.clone()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn latency_percentiles() -> Result<()> {
+    let recorder = LatencyRecorder::new();
+    for _ in 0..90 {
+        recorder.record(Phase::Read, Duration::from_micros(15));
+    }
+    for _ in 0..9 {
+        recorder.record(Phase::Read, Duration::from_millis(1));
+    }
+    recorder.record(Phase::Read, Duration::from_secs(3));
+
+    assert_eq!(
+        recorder.snapshot().read,
+        PhaseStats {
+            count: 100,
+            p50: Duration::from_micros(20),
+            p95: Duration::from_micros(1280),
+            p99: Duration::from_micros(1280),
+            max: Duration::from_secs(3),
+        }
+    );
+    assert_eq!(recorder.snapshot().decode, PhaseStats::default());
+
+    // bounds are inclusive, and the overflow bucket reports the maximum
+    recorder.reset();
+    recorder.record(Phase::Seek, LATENCY_BOUNDS[0]);
+    assert_eq!(recorder.snapshot().seek.p50, LATENCY_BOUNDS[0]);
+    recorder.record(Phase::Open, Duration::from_secs(20));
+    assert_eq!(recorder.snapshot().open.p99, Duration::from_secs(20));
+    assert!(*LATENCY_BOUNDS.last().unwrap() >= Duration::from_secs(10));
+    Ok(())
+}
+
+#[test]
+fn latency_phase_attribution() -> Result<()> {
+    let (mut writer, buffer) = BytesWriter::in_memory()?;
+    for index in 0..5u8 {
+        writer.send(vec![index; 16])?;
+    }
+    writer.flush()?;
+
+    let delay = Duration::from_millis(10);
+    let recorder = LatencyRecorder::new();
+    let reader = SlowReader {
+        inner: io::Cursor::new(buffer.to_vec()),
+        delay,
+    };
+    let records: Vec<_> =
+        BytesIter::from_reader(reader, config(&recorder)).collect::<Result<_, _>>()?;
+    assert_eq!(records.len(), 5);
+
+    // the delay is attributed to reads only
+    let snapshot = recorder.snapshot();
+    assert!(snapshot.read.count >= 5);
+    assert!(snapshot.read.p50 >= delay);
+    assert_eq!(snapshot.decode.count, 5);
+    assert!(snapshot.decode.max < delay);
+    assert_eq!(snapshot.open.count, 0);
+    assert_eq!(snapshot.seek.count, 0);
+    Ok(())
+}
+
+#[test]
+fn latency_of_indexed_reads() -> Result<()> {
+    let dataset = samples::tiny_dataset(2, 3)?;
+    let indexes: Vec<_> =
+        indexer::load_paths(dataset.paths(), Default::default()).collect::<Result<_, _>>()?;
+
+    let recorder = LatencyRecorder::new();
+    let count = indexer::iter_from::<Example>(&indexes, 1, config(&recorder))
+        .collect::<Result<Vec<_>, _>>()?
+        .len();
+    assert_eq!(count, 5);
+    let snapshot = recorder.snapshot();
+    assert_eq!(snapshot.open.count, 2);
+    assert_eq!(snapshot.seek.count, 5);
+    assert_eq!(snapshot.read.count, 5);
+    assert_eq!(snapshot.decode.count, 5);
+
+    // batch reads share the recorder
+    let batch = indexer::get_many_detailed::<Example>(&indexes, &[0, 4], config(&recorder));
+    assert_eq!(batch.summary.num_ok, 2);
+    assert_eq!(recorder.snapshot().decode.count, 7);
+
+    recorder.reset();
+    assert_eq!(recorder.snapshot(), Default::default());
+    Ok(())
+}
+
+#[test]
+fn latency_disabled_by_default() -> Result<()> {
+    let config = RecordReaderConfig::default();
+    assert_eq!(config.latency, None);
+
+    // recorders are compared by identity
+    let recorder = LatencyRecorder::new();
+    assert_eq!(recorder, recorder.clone());
+    assert_ne!(recorder, LatencyRecorder::new());
+    Ok(())
+}
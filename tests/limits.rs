@@ -0,0 +1,231 @@
+mod common;
+
+use std::{io::Cursor, path::Path};
+use tfrecord::{
+    indexer::{self, PermutedIterOptions, RecordIndexerConfig},
+    mix::{self, MixOptions},
+    BytesIter, BytesWriter, Error, Example, ExampleIter, Feature, FeatureProjection, Limits,
+    RecordReaderConfig,
+};
+
+fn frame(payloads: &[Vec<u8>]) -> Vec<u8> {
+    let mut bytes = vec![];
+    {
+        let mut writer = BytesWriter::from_writer(&mut bytes).unwrap();
+        for payload in payloads {
+            writer.send(payload.clone()).unwrap();
+        }
+    }
+    bytes
+}
+
+fn assert_limit_exceeded<T>(result: Result<T, Error>, expect_which: &str, expect_observed: u64) {
+    match result {
+        Err(Error::LimitExceeded {
+            which, observed, ..
+        }) => {
+            assert_eq!(which, expect_which);
+            assert_eq!(observed, expect_observed);
+        }
+        Err(err) => panic!("unexpected error: {}", err),
+        Ok(_) => panic!("the limit {} is not enforced", expect_which),
+    }
+}
+
+/// An example with `count` empty feature map entries.
+fn make_many_entries_example(count: usize) -> Vec<u8> {
+    let body: Vec<u8> = [0x0a, 0x00].repeat(count);
+    let mut bytes = vec![0x0a];
+    prost::encoding::encode_varint(body.len() as u64, &mut bytes);
+    bytes.extend(body);
+    bytes
+}
+
+#[test]
+fn max_record_len_test() {
+    let limits = Limits {
+        max_record_len: 16,
+        ..Default::default()
+    };
+    let bytes = frame(&[vec![0; 16], vec![0; 17]]);
+
+    let mut iter = BytesIter::from_reader(
+        bytes.as_slice(),
+        RecordReaderConfig {
+            limits: limits.clone(),
+            ..Default::default()
+        },
+    );
+    assert_eq!(iter.next().unwrap().unwrap().len(), 16);
+    assert_limit_exceeded(iter.next().unwrap(), "max_record_len", 17);
+
+    let mut iter = indexer::load_reader(
+        Cursor::new(bytes),
+        RecordIndexerConfig {
+            limits,
+            ..Default::default()
+        },
+    );
+    assert!(iter.next().unwrap().is_ok());
+    assert_limit_exceeded(iter.next().unwrap(), "max_record_len", 17);
+}
+
+#[test]
+fn max_features_test() {
+    let limits = Limits {
+        max_features: 1000,
+        ..Default::default()
+    };
+    let bytes = make_many_entries_example(1_000_000);
+
+    // the absurd entries collapse into one feature without limits
+    let example = Example::decode_with_limits(&bytes, &Limits::default()).unwrap();
+    assert_eq!(example.into_vec().len(), 1);
+
+    assert_limit_exceeded(
+        Example::decode_with_limits(&bytes, &limits),
+        "max_features",
+        1001,
+    );
+    assert_limit_exceeded(
+        Example::decode_projected_with_limits(&bytes, &FeatureProjection::new(["a"]), &limits),
+        "max_features",
+        1001,
+    );
+
+    let framed = frame(&[bytes]);
+    let mut iter = ExampleIter::from_reader(
+        framed.as_slice(),
+        RecordReaderConfig {
+            limits,
+            ..Default::default()
+        },
+    );
+    assert_limit_exceeded(iter.next().unwrap(), "max_features", 1001);
+}
+
+#[test]
+fn max_key_len_test() {
+    let limits = Limits {
+        max_key_len: 8,
+        ..Default::default()
+    };
+    let example: Example = [("k".repeat(9), Feature::from_i64_list(vec![1]))]
+        .into_iter()
+        .collect();
+    let bytes = example.encode_canonical_to_vec();
+
+    assert_limit_exceeded(
+        Example::decode_with_limits(&bytes, &limits),
+        "max_key_len",
+        9,
+    );
+    assert!(Example::decode_with_limits(&bytes, &Limits::strict()).is_ok());
+}
+
+#[test]
+fn max_open_files_test() {
+    let reader = RecordReaderConfig {
+        limits: Limits {
+            max_open_files: 1,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    // the budgets are checked before any file is opened
+    let options = MixOptions {
+        max_open_files: 2,
+        reader: reader.clone(),
+        ..Default::default()
+    };
+    let sources = vec![(vec![Path::new("a.tfrecord")], 1.0)];
+    assert_limit_exceeded(
+        mix::weighted::<Example, _, _, _>(sources, 0, options),
+        "max_open_files",
+        2,
+    );
+
+    let options = PermutedIterOptions {
+        max_open_files: 2,
+        reader: reader.clone(),
+    };
+    assert_limit_exceeded(
+        indexer::iter_permuted::<Example>(&[], vec![], options),
+        "max_open_files",
+        2,
+    );
+    let options = PermutedIterOptions {
+        max_open_files: 1,
+        reader,
+    };
+    assert!(indexer::iter_permuted::<Example>(&[], vec![], options).is_ok());
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn max_in_flight_bytes_test() {
+    use futures::{io::Cursor, stream::StreamExt as _};
+    use tfrecord::{BytesStream, ParallelDecodeConfig};
+
+    let config = RecordReaderConfig {
+        limits: Limits {
+            max_in_flight_bytes: 16,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let bytes = frame(&[vec![0; 16], vec![0; 1]]);
+
+    // one worker holds one record at a time
+    let parallel = ParallelDecodeConfig {
+        num_workers: 1,
+        ..Default::default()
+    };
+    let stream =
+        BytesStream::from_reader_parallel(Cursor::new(bytes.clone()), config.clone(), parallel)
+            .unwrap();
+    let records: Vec<_> = stream.collect().await;
+    assert!(records.iter().all(|record| record.is_ok()));
+
+    // two workers take the second record while the first is in flight
+    let parallel = ParallelDecodeConfig {
+        num_workers: 2,
+        ..Default::default()
+    };
+    let stream = BytesStream::from_reader_parallel(Cursor::new(bytes), config, parallel).unwrap();
+    let records: Vec<_> = stream.collect().await;
+    assert_eq!(records.len(), 2);
+    assert!(records[0].is_ok());
+    assert_limit_exceeded(
+        records.into_iter().nth(1).unwrap(),
+        "max_in_flight_bytes",
+        17,
+    );
+}
+
+#[test]
+fn max_cache_bytes_test() {
+    let dir = common::make_temp_dir("limits_cache").unwrap();
+    let path = dir.join("cached.tfrecord");
+    std::fs::write(&path, frame(&[vec![0; 4]])).unwrap();
+    let cached = indexer::load_paths_cached([path.as_path()], Default::default()).unwrap();
+    assert_eq!(cached.num_scanned, 1);
+    let sidecar_len = std::fs::metadata(indexer::index_path(&path)).unwrap().len();
+
+    let config = |max_cache_bytes| RecordIndexerConfig {
+        limits: Limits {
+            max_cache_bytes,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let cached =
+        indexer::load_paths_cached([path.as_path()], config(sidecar_len as usize)).unwrap();
+    assert_eq!(cached.num_cached, 1);
+    assert_limit_exceeded(
+        indexer::load_paths_cached([path.as_path()], config(sidecar_len as usize - 1)),
+        "max_cache_bytes",
+        sidecar_len,
+    );
+}
@@ -0,0 +1,171 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::{
+    fs::{self, OpenOptions},
+    io::{Seek, SeekFrom, Write},
+    time::{Duration, UNIX_EPOCH},
+};
+use tfrecord::{
+    fingerprint::FingerprintLevel,
+    indexer::RecordIndexerConfig,
+    integrity::IntegrityMode,
+    manifest::{
+        self, FeatureType, Manifest, ManifestOptions, ManifestPolicy, Mismatch, MANIFEST_VERSION,
+    },
+    samples, Error,
+};
+
+#[test]
+fn manifest_generate_and_load() -> Result<()> {
+    let dataset = samples::tiny_dataset(3, 4)?;
+    let options = ManifestOptions {
+        content_fingerprints: true,
+        ..Default::default()
+    };
+    let manifest = manifest::generate(dataset.paths(), options)?;
+
+    assert_eq!(manifest.version, MANIFEST_VERSION);
+    assert_eq!(manifest.files.len(), 3);
+    for (file, path) in manifest.files.iter().zip(dataset.paths()) {
+        assert_eq!(&file.path, path);
+        assert_eq!(file.num_records, 4);
+        assert_eq!(file.num_bytes, fs::metadata(path)?.len());
+        assert_eq!(file.layout.level(), FingerprintLevel::Layout);
+        assert_eq!(file.content.unwrap().level(), FingerprintLevel::Content);
+    }
+    let schema = manifest.schema.as_ref().unwrap();
+    assert!(!schema.is_empty());
+    let example = samples::example(0);
+    for name in example.features.as_ref().unwrap().feature.keys() {
+        assert!(schema.contains_key(name));
+    }
+
+    // paths are relative to the manifest and resolved on read
+    let manifest_path = dataset.dir().join("MANIFEST");
+    manifest.write_to(&manifest_path)?;
+    let text = fs::read_to_string(&manifest_path)?;
+    assert!(!text.contains(dataset.dir().to_str().unwrap()));
+    let loaded = Manifest::read_from(&manifest_path)?;
+    assert_eq!(loaded, manifest);
+
+    let indexes = loaded.load_indexes(ManifestPolicy::VerifyContent, Default::default())?;
+    assert_eq!(indexes.len(), 12);
+    Ok(())
+}
+
+#[test]
+fn manifest_survives_relocation() -> Result<()> {
+    let dataset = samples::tiny_dataset(2, 3)?;
+    let manifest_path = dataset.dir().join("MANIFEST");
+    manifest::generate(dataset.paths(), Default::default())?.write_to(&manifest_path)?;
+
+    let moved = dataset.dir().join("moved");
+    fs::create_dir_all(&moved)?;
+    for path in dataset.paths() {
+        fs::copy(path, moved.join(path.file_name().unwrap()))?;
+    }
+    fs::copy(&manifest_path, moved.join("MANIFEST"))?;
+
+    let manifest = Manifest::read_from(moved.join("MANIFEST"))?;
+    assert!(manifest
+        .files
+        .iter()
+        .all(|file| file.path.starts_with(&moved)));
+    let diffs = manifest.verify(ManifestPolicy::Verify, Default::default())?;
+    assert!(diffs.is_empty());
+    Ok(())
+}
+
+#[test]
+fn manifest_preserves_unknown_keys() -> Result<()> {
+    let text = "tfrecord-dataset-manifest\n\
+                version=2\n\
+                created_at=1700000000.000000042\n\
+                producer=other 1.0\n\
+                feature=image/encoded:bytes\n\
+                feature=label:int64\n\
+                file=path=a.tfrecord\trecords=3\tbytes=100\tlayout=04000000000000000000000000000000\tshard=0\n\
+                owner=data-team\n";
+    let manifest = Manifest::parse(text)?;
+
+    assert_eq!(manifest.version, 2);
+    assert_eq!(
+        manifest.created_at,
+        UNIX_EPOCH + Duration::new(1_700_000_000, 42)
+    );
+    let schema = manifest.schema.as_ref().unwrap();
+    assert_eq!(schema["image/encoded"], FeatureType::Bytes);
+    assert_eq!(schema["label"], FeatureType::Int64);
+    assert_eq!(manifest.files[0].num_records, 3);
+    assert_eq!(manifest.files[0].content, None);
+    assert_eq!(manifest.files[0].extra["shard"], "0");
+    assert_eq!(manifest.extra["owner"], "data-team");
+
+    let rendered = manifest.to_text()?;
+    assert_eq!(Manifest::parse(&rendered)?, manifest);
+    assert!(rendered.contains("\tshard=0"));
+    assert!(rendered.contains("owner=data-team\n"));
+
+    assert!(Manifest::parse("version=1\n").is_err());
+    Ok(())
+}
+
+#[test]
+fn manifest_mismatch_names_file_and_quantity() -> Result<()> {
+    let dataset = samples::tiny_dataset(3, 4)?;
+    let options = ManifestOptions {
+        content_fingerprints: true,
+        ..Default::default()
+    };
+    let manifest = manifest::generate(dataset.paths(), options)?;
+
+    // content verification needs content fingerprints
+    let without_content = manifest::generate(dataset.paths(), Default::default())?;
+    assert!(without_content
+        .load_indexes(ManifestPolicy::VerifyContent, Default::default())
+        .is_err());
+
+    // flip a payload byte of the second file, keeping its size and layout
+    let tampered = &dataset.paths()[1];
+    let mut file = OpenOptions::new().write(true).open(tampered)?;
+    file.seek(SeekFrom::Start(12))?;
+    file.write_all(&[0xff])?;
+    drop(file);
+
+    // the default integrity mode fails on indexing, so only the content fingerprint
+    // detects the change without checksums
+    assert!(manifest
+        .load_indexes(ManifestPolicy::Verify, Default::default())
+        .is_err());
+    let unchecked = RecordIndexerConfig {
+        integrity: IntegrityMode::Off,
+        ..Default::default()
+    };
+    assert!(manifest
+        .load_indexes(ManifestPolicy::Verify, unchecked.clone())
+        .is_ok());
+    match manifest.load_indexes(ManifestPolicy::VerifyContent, unchecked.clone()) {
+        Err(Error::ManifestMismatch { diffs }) => {
+            assert_eq!(diffs.len(), 1);
+            assert_eq!(&diffs[0].path, tampered);
+            assert!(matches!(diffs[0].mismatch, Mismatch::Content { .. }));
+        }
+        other => return Err(format_err!("unexpected result {:?}", other)),
+    }
+
+    // remove the last file and append to the first
+    fs::remove_file(&dataset.paths()[2])?;
+    let mut file = OpenOptions::new().append(true).open(&dataset.paths()[0])?;
+    file.write_all(&fs::read(tampered)?)?;
+    drop(file);
+    let diffs = manifest.verify(ManifestPolicy::Verify, unchecked)?;
+    assert_eq!(diffs.len(), 2);
+    assert_eq!(diffs[0].path, dataset.paths()[0]);
+    assert!(matches!(diffs[0].mismatch, Mismatch::NumBytes { .. }));
+    assert_eq!(diffs[1].path, dataset.paths()[2]);
+    assert_eq!(diffs[1].mismatch, Mismatch::Missing);
+    Ok(())
+}
@@ -0,0 +1,154 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::{fs, path::Path, sync::Arc};
+use tfrecord::{
+    manifest,
+    metadata::{metadata_path, ShardMetadata},
+    migrate::{self, UpgradeMode, UpgradeOptions, UpgradeOutcome, STAMP_KEY},
+    samples, BytesIter, BytesWriter, ExampleIter, FloatIdentity,
+};
+
+fn read_payloads(path: &Path) -> Result<Vec<Vec<u8>>> {
+    Ok(BytesIter::open(path, Default::default())?.collect::<Result<_, _>>()?)
+}
+
+#[test]
+fn upgrade_passthrough() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 5)?;
+    let input = &dataset.paths()[0];
+    let output = dataset.dir().join("out").join("upgraded.tfrecord");
+    fs::create_dir_all(output.parent().unwrap())?;
+
+    let report = migrate::upgrade_shard(input, &output, Default::default())?;
+    assert_eq!(report.num_records, 5);
+    assert_eq!(report.num_reencoded, 0);
+    assert_eq!(report.metadata_before, None);
+    assert_eq!(read_payloads(&output)?, read_payloads(input)?);
+
+    let metadata = ShardMetadata::read_for(&output)?.unwrap();
+    assert!(!metadata.sorted_features);
+    assert_eq!(metadata.extra[STAMP_KEY], migrate::DEFAULT_STAMP);
+    assert!(migrate::is_upgraded(&output, migrate::DEFAULT_STAMP));
+    assert!(!migrate::is_upgraded(input, migrate::DEFAULT_STAMP));
+    Ok(())
+}
+
+#[test]
+fn upgrade_reencode_in_place() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 5)?;
+    let path = &dataset.paths()[0];
+    let original = fs::read(path)?;
+    let examples: Vec<_> =
+        ExampleIter::open(path, Default::default())?.collect::<Result<_, _>>()?;
+
+    // a dry run writes nothing
+    let options = UpgradeOptions {
+        mode: UpgradeMode::Reencode,
+        dry_run: true,
+        ..Default::default()
+    };
+    let report = migrate::upgrade_shard(path, path, options)?;
+    assert!(report.dry_run);
+    assert_eq!(report.num_records, 5);
+    assert!(report.metadata_after.sorted_features);
+    assert_eq!(fs::read(path)?, original);
+    assert!(!metadata_path(path).exists());
+
+    let options = UpgradeOptions {
+        mode: UpgradeMode::Reencode,
+        ..Default::default()
+    };
+    let report = migrate::upgrade_shard(path, path, options)?;
+    assert!(!report.dry_run);
+    assert!(ShardMetadata::read_for(path)?.unwrap().sorted_features);
+
+    let upgraded: Vec<_> =
+        ExampleIter::open(path, Default::default())?.collect::<Result<_, _>>()?;
+    assert_eq!(upgraded.len(), examples.len());
+    for (lhs, rhs) in examples.iter().zip(&upgraded) {
+        assert!(lhs.eq_with(rhs, FloatIdentity::Bitwise));
+    }
+    let leftovers: Vec<_> = fs::read_dir(dataset.dir())?
+        .map(|entry| Ok(entry?.file_name()))
+        .collect::<Result<_>>()?;
+    assert_eq!(leftovers.len(), 2, "{:?}", leftovers);
+    Ok(())
+}
+
+#[test]
+fn upgrade_verification_failure_keeps_input() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 5)?;
+    let path = &dataset.paths()[0];
+    let original = fs::read(path)?;
+
+    // replace the temporary output with different records before verification
+    let options = UpgradeOptions {
+        before_verify: Some(Arc::new(|temp: &Path| {
+            let mut writer = BytesWriter::create(temp)?;
+            for index in 0..5u8 {
+                writer.send(vec![index])?;
+            }
+            writer.flush()
+        })),
+        ..Default::default()
+    };
+    let error = migrate::upgrade_shard(path, path, options).unwrap_err();
+    assert!(
+        matches!(error, tfrecord::Error::VerificationFailed { index: 0, .. }),
+        "{:?}",
+        error
+    );
+    assert_eq!(fs::read(path)?, original);
+    assert!(!metadata_path(path).exists());
+    assert_eq!(fs::read_dir(dataset.dir())?.count(), 1);
+    Ok(())
+}
+
+#[test]
+fn upgrade_manifest_resumes() -> Result<()> {
+    let dataset = samples::tiny_dataset(3, 4)?;
+    let manifest_path = dataset.dir().join("dataset.manifest");
+    manifest::generate(
+        dataset.paths().iter().map(|path| path.as_path()),
+        Default::default(),
+    )?
+    .write_to(&manifest_path)?;
+    let output_dir = dataset.dir().join("upgraded");
+    let failing = dataset.paths()[1].file_name().unwrap().to_owned();
+
+    // the first run fails on the second file
+    let options = UpgradeOptions {
+        before_verify: Some(Arc::new(move |temp: &Path| {
+            let name = temp.file_name().unwrap().to_string_lossy();
+            if name.starts_with(&*failing.to_string_lossy()) {
+                return Err(tfrecord::Error::UnexpectedEof);
+            }
+            Ok(())
+        })),
+        ..Default::default()
+    };
+    let report = migrate::upgrade_manifest(&manifest_path, &output_dir, options)?;
+    assert!(!report.is_success());
+    let outcomes: Vec<_> = report.files.iter().map(|(_, outcome)| outcome).collect();
+    assert!(matches!(outcomes[0], UpgradeOutcome::Upgraded(_)));
+    assert!(matches!(outcomes[1], UpgradeOutcome::Failed(_)));
+    assert!(matches!(outcomes[2], UpgradeOutcome::Upgraded(_)));
+    assert_eq!(report.files[1].0, dataset.paths()[1]);
+
+    // the second run upgrades the failed file only
+    let report = migrate::upgrade_manifest(&manifest_path, &output_dir, Default::default())?;
+    assert!(report.is_success());
+    let outcomes: Vec<_> = report.files.iter().map(|(_, outcome)| outcome).collect();
+    assert!(matches!(outcomes[0], UpgradeOutcome::Skipped));
+    assert!(matches!(outcomes[1], UpgradeOutcome::Upgraded(_)));
+    assert!(matches!(outcomes[2], UpgradeOutcome::Skipped));
+
+    for path in dataset.paths() {
+        let output = output_dir.join(path.file_name().unwrap());
+        assert_eq!(read_payloads(&output)?, read_payloads(path)?);
+    }
+    Ok(())
+}
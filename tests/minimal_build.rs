@@ -0,0 +1,38 @@
+//! The reader, the writer and the indexer of examples in the minimal build.
+//!
+//! The test is run with `--no-default-features --features proto-example` by CI, so it
+//! must not use events, summaries or the other optional ProtocolBuffer groups.
+
+mod common;
+
+use common::*;
+use tfrecord::{indexer, samples, Example, ExampleIter, ExampleWriter, RecordWriterConfig};
+
+#[test]
+fn minimal_build_reads_and_writes_examples() -> Result<()> {
+    let (mut writer, buffer) = ExampleWriter::in_memory_with_config(RecordWriterConfig {
+        canonical_encoding: true,
+        ..Default::default()
+    })?;
+    for index in 0..5 {
+        writer.send(samples::example(index))?;
+    }
+    writer.flush()?;
+
+    let examples: Vec<Example> =
+        ExampleIter::from_bytes(buffer.to_vec(), Default::default()).collect::<Result<_, _>>()?;
+    assert_eq!(examples, (0..5).map(samples::example).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[test]
+fn minimal_build_indexes_examples() -> Result<()> {
+    let dataset = samples::tiny_dataset(2, 3)?;
+    let indexes: Vec<_> =
+        indexer::load_paths(dataset.paths(), Default::default()).collect::<Result<_, _>>()?;
+    assert_eq!(indexes.len(), 6);
+
+    let example: Example = indexes[4].load()?;
+    assert_eq!(example, samples::example(4));
+    Ok(())
+}
@@ -0,0 +1,164 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use tfrecord::{
+    mix::{self, Exhaustion, MixIter, MixOptions},
+    samples::{self, TempDataset},
+    Example,
+};
+
+fn make_datasets(sizes: &[(usize, usize)]) -> Result<Vec<TempDataset>> {
+    sizes
+        .iter()
+        .map(|&(num_files, records_per_file)| {
+            Ok(samples::tiny_dataset(num_files, records_per_file)?)
+        })
+        .collect()
+}
+
+fn mix_of(
+    datasets: &[TempDataset],
+    weights: &[f64],
+    seed: u64,
+    options: MixOptions,
+) -> Result<MixIter<Example>> {
+    let sources = datasets
+        .iter()
+        .zip(weights)
+        .map(|(dataset, &weight)| (dataset.paths(), weight));
+    Ok(mix::weighted(sources, seed, options)?)
+}
+
+/// The source indexes and ids of mixed examples.
+fn draws(
+    mix: impl Iterator<Item = tfrecord::Result<mix::Mixed<Example>>>,
+) -> Result<Vec<(usize, i64)>> {
+    mix.map(|mixed| {
+        let mixed = mixed?;
+        Ok((mixed.source, mixed.record.get_i64s("id")?[0]))
+    })
+    .collect()
+}
+
+fn counts(draws: &[(usize, i64)], num_sources: usize) -> Vec<usize> {
+    let mut counts = vec![0; num_sources];
+    for &(source, _) in draws {
+        counts[source] += 1;
+    }
+    counts
+}
+
+#[test]
+fn proportions_test() -> Result<()> {
+    let datasets = make_datasets(&[(3, 40), (2, 20), (1, 10)])?;
+    let options = MixOptions {
+        exhaustion: Exhaustion::Repeat { reshuffle: true },
+        ..Default::default()
+    };
+    let num_draws = 20_000;
+    let draws = draws(mix_of(&datasets, &[0.7, 0.2, 0.1], 42, options)?.take(num_draws))?;
+    assert_eq!(draws.len(), num_draws);
+    for (count, weight) in counts(&draws, 3).into_iter().zip([0.7, 0.2, 0.1]) {
+        let proportion = count as f64 / num_draws as f64;
+        ensure!(
+            (proportion - weight).abs() < 0.015,
+            "proportion {} is far from weight {}",
+            proportion,
+            weight
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn determinism_test() -> Result<()> {
+    let datasets = make_datasets(&[(2, 30), (2, 30)])?;
+    let run = |seed| draws(mix_of(&datasets, &[0.5, 0.5], seed, Default::default())?);
+    let first = run(1)?;
+    assert_eq!(first.len(), 120);
+    assert_eq!(first, run(1)?);
+    assert_ne!(first, run(2)?);
+    Ok(())
+}
+
+#[test]
+fn exhaustion_test() -> Result<()> {
+    let datasets = make_datasets(&[(2, 50), (1, 100), (1, 5)])?;
+    let weights = [0.4, 0.4, 0.2];
+
+    // the stream ends at the first pick of the exhausted small source
+    let options = MixOptions {
+        exhaustion: Exhaustion::StopWhenAnyExhausted,
+        ..Default::default()
+    };
+    let draws_any = draws(mix_of(&datasets, &weights, 3, options)?)?;
+    let counts_any = counts(&draws_any, 3);
+    assert_eq!(counts_any[2], 5);
+    assert!(counts_any[0] < 100 && counts_any[1] < 100);
+
+    // all records of all sources, each source in order
+    let draws_all = draws(mix_of(&datasets, &weights, 3, Default::default())?)?;
+    assert_eq!(counts(&draws_all, 3), [100, 100, 5]);
+    for source in 0..3 {
+        let ids: Vec<_> = draws_all
+            .iter()
+            .filter(|(index, _)| *index == source)
+            .map(|(_, id)| *id)
+            .collect();
+        assert_eq!(ids, (0..ids.len() as i64).collect::<Vec<_>>());
+    }
+
+    // restarted sources never end the stream
+    let options = MixOptions {
+        exhaustion: Exhaustion::Repeat { reshuffle: false },
+        ..Default::default()
+    };
+    let draws_repeat = draws(mix_of(&datasets, &weights, 3, options)?.take(1000))?;
+    assert_eq!(draws_repeat.len(), 1000);
+    let small: Vec<_> = draws_repeat
+        .iter()
+        .filter(|(index, _)| *index == 2)
+        .map(|(_, id)| *id)
+        .collect();
+    assert!(small.len() > 5);
+    assert!(small
+        .iter()
+        .enumerate()
+        .all(|(nth, id)| *id == nth as i64 % 5));
+
+    // an empty source cannot be repeated
+    let empty = make_datasets(&[(1, 10), (1, 0)])?;
+    let options = MixOptions {
+        exhaustion: Exhaustion::Repeat { reshuffle: false },
+        ..Default::default()
+    };
+    let results: Vec<_> = mix_of(&empty, &[0.5, 0.5], 0, options)?.take(100).collect();
+    assert!(results.last().unwrap().is_err());
+    Ok(())
+}
+
+#[test]
+fn open_file_budget_test() -> Result<()> {
+    // reopening files at their offsets yields the same records
+    let datasets = make_datasets(&[(2, 20), (3, 10), (1, 15)])?;
+    let weights = [0.3, 0.3, 0.4];
+    let unbounded = draws(mix_of(&datasets, &weights, 9, Default::default())?)?;
+    let options = MixOptions {
+        max_open_files: 1,
+        ..Default::default()
+    };
+    let bounded = draws(mix_of(&datasets, &weights, 9, options)?)?;
+    assert_eq!(bounded, unbounded);
+    assert_eq!(bounded.len(), 85);
+
+    let options = MixOptions {
+        max_open_files: 0,
+        ..Default::default()
+    };
+    assert!(mix_of(&datasets, &weights, 9, options).is_err());
+    assert!(mix_of(&datasets, &[0.0, 0.0, 0.0], 9, Default::default()).is_err());
+    assert!(mix_of(&datasets, &[f64::NAN, 1.0, 1.0], 9, Default::default()).is_err());
+    Ok(())
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "mmap")]
+
+mod common;
+
+use common::*;
+use std::fs;
+use tfrecord::{
+    mmap::{MmapConfig, MmapFile},
+    BytesIter, BytesWriter,
+};
+
+fn records() -> Vec<Vec<u8>> {
+    (0..50usize)
+        .map(|index| vec![index as u8; index * 7 % 31])
+        .collect()
+}
+
+fn regular_bytes(records: &[Vec<u8>]) -> Result<Vec<u8>> {
+    let (mut writer, buffer) = BytesWriter::in_memory()?;
+    for record in records {
+        writer.send(record.clone())?;
+    }
+    writer.flush()?;
+    Ok(buffer.to_vec())
+}
+
+#[test]
+fn mmap_writer_grows_and_truncates() -> Result<()> {
+    let dir = make_temp_dir("mmap/grow")?;
+    let path = dir.join("grow.tfrecord");
+    let records = records();
+    let expect = regular_bytes(&records)?;
+
+    let config = MmapConfig {
+        capacity: 100,
+        growth: 64,
+    };
+    let mut writer = BytesWriter::create_mmap(&path, Default::default(), config)?;
+    let mapped = writer.get_ref().is_mapped();
+    if mapped {
+        // the file is preallocated to the capacity
+        assert_eq!(fs::metadata(&path)?.len(), 100);
+    }
+
+    for record in &records {
+        writer.send(record.clone())?;
+    }
+    writer.flush()?;
+    if mapped {
+        // the file grows by whole increments beyond the capacity
+        let len = fs::metadata(&path)?.len();
+        assert!(len >= expect.len() as u64);
+        assert_eq!((len - 100) % 64, 0);
+        assert!(len - (expect.len() as u64) < 64);
+    }
+
+    // the file is truncated to the same bytes as the regular writer
+    writer.finish()?;
+    assert_eq!(fs::read(&path)?, expect);
+    let read: Vec<_> = BytesIter::open(&path, Default::default())?.collect::<Result<_, _>>()?;
+    assert_eq!(read, records);
+    Ok(())
+}
+
+#[test]
+fn mmap_writer_matches_regular_writer() -> Result<()> {
+    let dir = make_temp_dir("mmap/regular")?;
+    let records = records();
+
+    let regular_path = dir.join("regular.tfrecord");
+    let mut writer = BytesWriter::create(&regular_path)?;
+    for record in &records {
+        writer.send(record.clone())?;
+    }
+    drop(writer);
+
+    // the capacity exceeds the output, so the file shrinks on finish
+    let mmap_path = dir.join("mapped.tfrecord");
+    let mut writer = BytesWriter::create_mmap(&mmap_path, Default::default(), Default::default())?;
+    for record in &records {
+        writer.send(record.clone())?;
+    }
+    writer.finish()?;
+    assert_eq!(fs::read(&mmap_path)?, fs::read(&regular_path)?);
+
+    // dropping the writer truncates as well
+    let drop_path = dir.join("dropped.tfrecord");
+    let mut writer = BytesWriter::create_mmap(&drop_path, Default::default(), Default::default())?;
+    for record in &records {
+        writer.send(record.clone())?;
+    }
+    drop(writer);
+    assert_eq!(fs::read(&drop_path)?, fs::read(&regular_path)?);
+
+    // an empty output
+    let empty_path = dir.join("empty.tfrecord");
+    MmapFile::create(&empty_path, Default::default())?.finish()?;
+    assert_eq!(fs::metadata(&empty_path)?.len(), 0);
+    Ok(())
+}
+
+#[test]
+fn mmap_writer_rejects_zero_growth() -> Result<()> {
+    let dir = make_temp_dir("mmap/zero")?;
+    let config = MmapConfig {
+        capacity: 0,
+        growth: 0,
+    };
+    let result = MmapFile::create(dir.join("zero.tfrecord"), config);
+    assert!(result.is_err());
+    Ok(())
+}
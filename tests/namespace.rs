@@ -0,0 +1,139 @@
+mod common;
+
+use common::*;
+use prost::Message as _;
+use std::collections::BTreeMap;
+use tfrecord::{Example, ExampleBuilder, Feature};
+
+fn profile_example() -> Result<Example> {
+    let example = ExampleBuilder::new()
+        .push_i64s("id", &[7])
+        .namespace("user/profile")
+        .push_f32s("age", &[31.0])
+        .push_bytes("name", vec![b"alice".to_vec()])
+        .namespace("user")
+        .push_bools("active", &[true])?
+        .namespace("item/")
+        .push_u8s("digest", &[1, 2, 3])
+        .build();
+    Ok(example)
+}
+
+#[test]
+fn namespace_view() -> Result<()> {
+    let example = profile_example()?;
+
+    let profile = example.namespace("user/profile");
+    assert_eq!(profile.get_f32s("age")?, &[31.0]);
+    assert_eq!(profile.get_bytes("name")?, &[b"alice".to_vec()]);
+    assert!(profile.get_i64s("age").is_err());
+    assert!(profile.get("id").is_none());
+
+    // nested views resolve the same keys
+    let user = example.namespace("user");
+    assert_eq!(user.namespace("profile").get_f32s("age")?, &[31.0]);
+    assert_eq!(user.get_bools("active")?, vec![true]);
+    let mut keys: Vec<_> = user.iter().map(|(key, _)| key).collect();
+    keys.sort_unstable();
+    assert_eq!(keys, ["active", "profile/age", "profile/name"]);
+
+    // a trailing delimiter names the same namespace
+    assert_eq!(example.namespace("item/").get_u8s("digest")?, &[1, 2, 3]);
+    assert_eq!(example.namespace("item").get_u8s("digest")?, &[1, 2, 3]);
+    Ok(())
+}
+
+#[test]
+fn namespace_edge_cases() -> Result<()> {
+    let mut example = Example::empty();
+    example.push_i64s("a", &[1]);
+    example.push_i64s("a/b", &[2]);
+    example.push_i64s("a//c", &[3]);
+    example.push_i64s("/d", &[4]);
+
+    // the empty prefix views all keys
+    let root = example.namespace("");
+    assert_eq!(root.iter().count(), 4);
+    assert_eq!(root.get_i64s("a")?, &[1]);
+
+    // a key equal to the prefix is not in its namespace
+    let view = example.namespace("a");
+    assert!(view.get("").is_none());
+    assert_eq!(view.get_i64s("b")?, &[2]);
+    assert_eq!(view.get_i64s("/c")?, &[3]);
+    assert_eq!(view.iter().count(), 2);
+    assert!(example.namespace("missing").is_empty());
+
+    // odd keys are reachable by the raw API
+    assert_eq!(example.get_i64s("a//c")?, &[3]);
+    assert_eq!(example.get_i64s("/d")?, &[4]);
+
+    assert_eq!(example.namespaces(), BTreeMap::from([("", 1), ("a", 2)]));
+    Ok(())
+}
+
+#[test]
+fn namespace_custom_delimiter() -> Result<()> {
+    let example = ExampleBuilder::with_delimiter('.')
+        .namespace("user.profile")
+        .push_f32s("age", &[31.0])
+        .build();
+    assert_eq!(example.get_f32s("user.profile.age")?, &[31.0]);
+    assert_eq!(
+        example
+            .namespace_with_delimiter("user", '.')
+            .namespace("profile")
+            .get_f32s("age")?,
+        &[31.0]
+    );
+    assert_eq!(
+        example.namespaces_with_delimiter('.'),
+        BTreeMap::from([("user", 1)])
+    );
+    assert!(example.namespaces().is_empty());
+    Ok(())
+}
+
+#[test]
+fn namespace_builder_round_trip() -> Result<()> {
+    let example = profile_example()?;
+    let decoded = Example::decode(example.encode_to_vec().as_slice())?;
+    assert_eq!(decoded, example);
+    assert_eq!(
+        decoded.namespaces(),
+        BTreeMap::from([("item", 1), ("user", 3)])
+    );
+    assert_eq!(decoded.namespace("user/profile").get_f32s("age")?, &[31.0]);
+    assert_eq!(decoded.get_i64s("id")?, &[7]);
+    Ok(())
+}
+
+#[test]
+fn nested_json() -> Result<()> {
+    let example = ExampleBuilder::new()
+        .push_i64s("id", &[7])
+        .namespace("user/profile")
+        .push_f32s("age", &[31.5])
+        .push_bytes("name", vec![b"al\"ice".to_vec()])
+        .push_feature("empty", Feature::empty())
+        .build();
+    assert_eq!(
+        example.restructure_to_nested_json('/')?,
+        r#"{"id":{"int64_list":[7]},"user":{"profile":{"age":{"float_list":[31.5]},"empty":null,"name":{"bytes_list":["al\"ice"]}}}}"#
+    );
+
+    // a key cannot be both a leaf and a namespace
+    let mut conflict = Example::empty();
+    conflict.push_i64s("a", &[1]);
+    conflict.push_i64s("a/b", &[2]);
+    assert!(conflict.restructure_to_nested_json('/').is_err());
+
+    // values not representable in JSON
+    let mut nan = Example::empty();
+    nan.push_f32s("x", &[f32::NAN]);
+    assert!(nan.restructure_to_nested_json('/').is_err());
+    let mut binary = Example::empty();
+    binary.push_bytes("x", vec![vec![0xff]]);
+    assert!(binary.restructure_to_nested_json('/').is_err());
+    Ok(())
+}
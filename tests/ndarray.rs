@@ -0,0 +1,119 @@
+#![cfg(feature = "with-ndarray")]
+
+mod common;
+
+use common::*;
+use ndarray::{array, Array1, Array2, ArrayD, IxDyn};
+use std::collections::HashMap;
+use tfrecord::{
+    protobuf::{BytesList, FloatList, Int64List},
+    Error, Example, Feature,
+};
+
+#[test]
+fn list_to_array_test() -> Result<()> {
+    let array = Array1::try_from(&FloatList {
+        value: vec![1.0, 2.0],
+    })?;
+    assert_eq!(array, array![1f32, 2.0]);
+
+    let array = Array1::try_from(&Int64List { value: vec![] })?;
+    assert_eq!(array, Array1::<i64>::zeros(0));
+
+    let array = Array1::try_from(&BytesList {
+        value: vec![b"a".to_vec()],
+    })?;
+    assert_eq!(array, array![b"a".to_vec()]);
+    Ok(())
+}
+
+#[test]
+fn round_trip_test() -> Result<()> {
+    // 1-D
+    let array = array![1f32, 2.0, 3.0];
+    let feature = Feature::try_from(array.view())?;
+    assert_eq!(feature.to_f32_array(&[3])?, array.clone().into_dyn());
+
+    // 2-D, including a transposed layout which is flattened in row-major order
+    let array: Array2<i64> = array![[1, 2, 3], [4, 5, 6]];
+    let feature = Feature::try_from(array.view())?;
+    assert_eq!(feature.to_i64_array(&[2, 3])?, array.clone().into_dyn());
+    let feature = Feature::try_from(array.t())?;
+    assert_eq!(feature.as_i64_list(), Some(&[1, 4, 2, 5, 3, 6][..]));
+    assert_eq!(feature.to_i64_array(&[3, 2])?, array.t().into_dyn());
+
+    let array = array![[b"a".to_vec()], [b"bc".to_vec()]];
+    let feature = Feature::try_from(array.view())?;
+    assert_eq!(feature.to_bytes_array(&[2, 1])?, array.into_dyn());
+
+    // empty
+    let array = ArrayD::<f32>::zeros(IxDyn(&[2, 0]));
+    let feature = Feature::try_from(array.view())?;
+    assert_eq!(feature.as_f32_list(), Some(&[][..]));
+    assert_eq!(feature.to_f32_array(&[2, 0])?, array);
+    assert_eq!(feature.to_f32_array(&[0])?.len(), 0);
+
+    // a scalar has an empty shape
+    let array = ArrayD::from_elem(IxDyn(&[]), 7f32);
+    let feature = Feature::try_from(array.view())?;
+    assert_eq!(feature.to_f32_array(&[])?, array);
+    Ok(())
+}
+
+#[test]
+fn shape_mismatch_test() -> Result<()> {
+    let feature = Feature::from_f32_list(vec![0.0; 6]);
+    let err = feature.to_f32_array(&[4, 2]).unwrap_err();
+    assert!(matches!(err, Error::ConversionError { .. }));
+    assert_eq!(
+        err.to_string(),
+        "[TFR0006] conversion error: the shape [4, 2] does not match the FloatList of 6 values"
+    );
+    // overflowing shapes are mismatches rather than panics
+    assert!(feature.to_f32_array(&[usize::MAX, 2]).is_err());
+    assert!(feature.to_i64_array(&[6]).is_err());
+    Ok(())
+}
+
+#[test]
+fn example_to_arrays_test() -> Result<()> {
+    let mut example = Example::empty();
+    example.push_f32s("image", &[0.0, 1.0, 2.0, 3.0]);
+    example.push_f32s("score", &[0.5]);
+    example.push_i64s("label", &[1, 2, 3]);
+    example.push_bytes("name", vec![b"x".to_vec()]);
+
+    let shapes: HashMap<String, Vec<usize>> = [
+        ("image".to_string(), vec![2, 2]),
+        ("score".to_string(), vec![]),
+    ]
+    .into_iter()
+    .collect();
+    let arrays = example.to_f32_arrays(&shapes)?;
+    assert_eq!(arrays.len(), 2);
+    assert_eq!(arrays["image"], array![[0f32, 1.0], [2.0, 3.0]].into_dyn());
+    assert_eq!(arrays["score"], ArrayD::from_elem(IxDyn(&[]), 0.5));
+
+    let shapes = [("label".to_string(), vec![3, 1])].into_iter().collect();
+    let arrays = example.to_i64_arrays(&shapes)?;
+    assert_eq!(arrays["label"], array![[1i64], [2], [3]].into_dyn());
+
+    let shapes = [("name".to_string(), vec![1])].into_iter().collect();
+    let arrays = example.to_bytes_arrays(&shapes)?;
+    assert_eq!(arrays["name"], array![b"x".to_vec()].into_dyn());
+
+    // the error names the feature
+    let shapes = [("label".to_string(), vec![2])].into_iter().collect();
+    let err = example.to_i64_arrays(&shapes).unwrap_err();
+    assert!(
+        err.to_string().contains("feature 'label' of 3 values"),
+        "{}",
+        err
+    );
+    // missing features and features of other kinds are errors
+    let shapes = [("label".to_string(), vec![3])].into_iter().collect();
+    assert!(example.to_f32_arrays(&shapes).is_err());
+    let shapes = [("height".to_string(), vec![1])].into_iter().collect();
+    assert!(example.to_f32_arrays(&shapes).is_err());
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use prost::Message as _;
+use tfrecord::{Example, Feature, BOOLS_MARKER};
+
+#[test]
+fn bools_round_trip_test() -> tfrecord::Result<()> {
+    for len in [0, 1, 7, 8, 9, 63, 100] {
+        let values: Vec<bool> = (0..len).map(|index| index % 3 == 0).collect();
+        let mut example = Example::empty();
+        example.push_bools("flags", &values)?;
+
+        let example = Example::decode(example.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(example.get_bools("flags")?, values);
+
+        let bytes = &example.features.as_ref().unwrap().feature["flags"]
+            .as_bytes_list()
+            .unwrap()[0];
+        assert_eq!(bytes.len(), 5 + usize::div_ceil(len, 8));
+        assert_eq!(bytes[0], BOOLS_MARKER);
+    }
+    Ok(())
+}
+
+#[test]
+fn u8s_round_trip_test() -> tfrecord::Result<()> {
+    for values in [vec![], vec![0u8], (0..=255).collect()] {
+        let mut example = Example::empty();
+        example.push_u8s("pixels", &values);
+        let example = Example::decode(example.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(example.get_u8s("pixels")?, values.as_slice());
+    }
+    Ok(())
+}
+
+#[test]
+fn packed_features_validation_test() {
+    let example: Example = [
+        ("ints".to_string(), Feature::from_i64_list(vec![1, 0, 1])),
+        (
+            "two_values".to_string(),
+            Feature::from_bytes_list(vec![vec![], vec![]]),
+        ),
+        (
+            "no_marker".to_string(),
+            Feature::from_bytes_list(vec![vec![0, 3, 0, 0, 0, 0b101]]),
+        ),
+        (
+            "short_bitmap".to_string(),
+            Feature::from_bytes_list(vec![vec![BOOLS_MARKER, 9, 0, 0, 0, 0xff]]),
+        ),
+        (
+            "dirty_padding".to_string(),
+            Feature::from_bytes_list(vec![vec![BOOLS_MARKER, 3, 0, 0, 0, 0b1101]]),
+        ),
+    ]
+    .into_iter()
+    .collect();
+
+    for key in [
+        "ints",
+        "two_values",
+        "no_marker",
+        "short_bitmap",
+        "dirty_padding",
+        "missing",
+    ] {
+        assert!(example.get_bools(key).is_err(), "{}", key);
+    }
+    for key in ["ints", "two_values", "missing"] {
+        assert!(example.get_u8s(key).is_err(), "{}", key);
+    }
+}
+
+#[test]
+fn packed_features_size_test() -> tfrecord::Result<()> {
+    let values: Vec<bool> = (0..1000).map(|index| index % 2 == 0).collect();
+    let packed = Feature::from_bools(&values)?;
+    let naive =
+        Feature::from_i64_list(values.iter().map(|&value| value as i64).collect::<Vec<_>>());
+    assert!(packed.encoded_len() * 6 < naive.encoded_len());
+
+    let bytes: Vec<u8> = (0..1000).map(|index| (index * 7) as u8).collect();
+    let packed = Feature::from_u8s(&bytes);
+    let naive = Feature::from_i64_list(bytes.iter().map(|&value| value as i64).collect::<Vec<_>>());
+    assert!(packed.encoded_len() < naive.encoded_len());
+    Ok(())
+}
@@ -0,0 +1,108 @@
+#![cfg(all(feature = "async", feature = "testing"))]
+
+mod common;
+
+use common::*;
+use futures::{
+    io::Cursor,
+    stream::{StreamExt as _, TryStreamExt as _},
+};
+use tfrecord::{
+    samples, BytesWriter, DecodeOrder, Error, Example, ExampleStream, IntegrityMode,
+    ParallelDecodeConfig, RecordReaderConfig,
+};
+
+fn parallel(num_workers: usize, order: DecodeOrder) -> ParallelDecodeConfig {
+    ParallelDecodeConfig { num_workers, order }
+}
+
+#[async_std::test]
+async fn ordered_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 50)?;
+    let path = &dataset.paths()[0];
+    let expect: Vec<Example> = ExampleStream::open(path, Default::default())
+        .await?
+        .try_collect()
+        .await?;
+
+    for num_workers in [1, 3, 16] {
+        let examples: Vec<Example> = ExampleStream::open_parallel(
+            path,
+            Default::default(),
+            parallel(num_workers, DecodeOrder::Ordered),
+        )
+        .await?
+        .try_collect()
+        .await?;
+        assert_eq!(examples, expect);
+    }
+    Ok(())
+}
+
+#[async_std::test]
+async fn unordered_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 50)?;
+    let mut ids: Vec<i64> = ExampleStream::open_parallel(
+        &dataset.paths()[0],
+        Default::default(),
+        parallel(8, DecodeOrder::Unordered),
+    )
+    .await?
+    .map(|example| Ok::<_, Error>(example?.get_i64s("id")?[0]))
+    .try_collect()
+    .await?;
+    ids.sort();
+    assert_eq!(ids, (0..50).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[async_std::test]
+async fn errors_test() -> Result<()> {
+    let mut bytes = vec![];
+    {
+        let mut writer = BytesWriter::from_writer(&mut bytes)?;
+        writer.send(b"not an example".to_vec())?;
+        writer.send(vec![])?;
+    }
+    // the bytes of a wrong type are told from corrupted bytes on decode errors
+    let config = RecordReaderConfig {
+        integrity: IntegrityMode::OnDecodeError,
+        ..Default::default()
+    };
+    let results: Vec<_> = ExampleStream::from_reader_parallel(
+        Cursor::new(bytes.clone()),
+        config.clone(),
+        parallel(4, DecodeOrder::Ordered),
+    )?
+    .collect()
+    .await;
+    // the stream ends after the first error
+    assert_eq!(results.len(), 1);
+    assert!(
+        matches!(results[0], Err(Error::ExampleDecodeError(_))),
+        "{:?}",
+        results
+    );
+
+    bytes[14] ^= 0xff;
+    let results: Vec<_> = ExampleStream::from_reader_parallel(
+        Cursor::new(bytes),
+        config,
+        parallel(4, DecodeOrder::Ordered),
+    )?
+    .collect()
+    .await;
+    assert!(
+        matches!(results[..], [Err(Error::ChecksumMismatch { .. })]),
+        "{:?}",
+        results
+    );
+
+    let result = ExampleStream::from_reader_parallel(
+        Cursor::new(vec![]),
+        Default::default(),
+        parallel(0, DecodeOrder::Ordered),
+    );
+    assert!(matches!(result, Err(Error::ConversionError { .. })));
+    Ok(())
+}
@@ -0,0 +1,165 @@
+mod common;
+
+use common::*;
+use std::{path::PathBuf, sync::Arc};
+use tfrecord::{
+    indexer::{self, PathOrder, RecordIndex, RecordIndexerConfig},
+    BytesWriter,
+};
+
+fn make_dir(name: &str) -> Result<PathBuf> {
+    make_temp_dir(&format!("path_order/{}", name))
+}
+
+fn write_file(path: &PathBuf, payload: &[u8]) -> Result<()> {
+    let mut writer = BytesWriter::create(path)?;
+    writer.send(payload.to_vec())?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[test]
+fn sort_paths_test() -> Result<()> {
+    let paths: Vec<PathBuf> = [
+        "b/1.tfrecord",
+        "a/2.tfrecord",
+        "a/10.tfrecord",
+        "B.tfrecord",
+    ]
+    .iter()
+    .map(PathBuf::from)
+    .collect();
+    let expect: Vec<PathBuf> = [
+        "B.tfrecord",
+        "a/10.tfrecord",
+        "a/2.tfrecord",
+        "b/1.tfrecord",
+    ]
+    .iter()
+    .map(PathBuf::from)
+    .collect();
+
+    // identical for any input order
+    let mut reversed = paths.clone();
+    reversed.reverse();
+    for input in [paths.clone(), reversed] {
+        assert_eq!(
+            indexer::sort_paths(input, PathOrder::Lexicographic)?,
+            expect
+        );
+    }
+    assert_eq!(
+        indexer::sort_paths(paths.clone(), PathOrder::AsGiven)?,
+        paths
+    );
+    Ok(())
+}
+
+#[test]
+fn load_paths_order_test() -> Result<()> {
+    let dir = make_dir("load_paths")?;
+    let names = ["c.tfrecord", "a.tfrecord", "b.tfrecord"];
+    for name in names {
+        write_file(&dir.join(name), name.as_bytes())?;
+        // make modification times distinct
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    let paths: Vec<_> = names.iter().map(|name| dir.join(name)).collect();
+
+    let load = |path_order| -> Result<Vec<_>> {
+        let config = RecordIndexerConfig {
+            path_order,
+            ..Default::default()
+        };
+        let indexes: Vec<_> =
+            indexer::load_paths(paths.clone(), config).collect::<Result<_, _>>()?;
+        let paths: Vec<PathBuf> = indexer::file_paths(&indexes)
+            .iter()
+            .map(|path| path.strip_prefix(&dir).unwrap().to_path_buf())
+            .collect();
+        Ok(paths)
+    };
+
+    let to_paths = |names: &[&str]| -> Vec<PathBuf> { names.iter().map(PathBuf::from).collect() };
+    assert_eq!(load(PathOrder::AsGiven)?, to_paths(&names));
+    assert_eq!(
+        load(PathOrder::Lexicographic)?,
+        to_paths(&["a.tfrecord", "b.tfrecord", "c.tfrecord"])
+    );
+    assert_eq!(load(PathOrder::ByModifiedTime)?, to_paths(&names));
+    Ok(())
+}
+
+#[test]
+fn file_paths_interleaved_test() {
+    let index = |path: &Arc<PathBuf>, offset| RecordIndex {
+        path: path.clone(),
+        offset,
+        len: 1,
+    };
+    let a = Arc::new(PathBuf::from("a.tfrecord"));
+    let b = Arc::new(PathBuf::from("b.tfrecord"));
+    let indexes = vec![
+        index(&b, 0),
+        index(&a, 0),
+        index(&a, 1),
+        index(&b, 1),
+        index(&a, 2),
+    ];
+    assert_eq!(indexer::file_paths(&indexes), vec![b, a]);
+}
+
+#[cfg(feature = "glob")]
+#[test]
+fn glob_test() -> Result<()> {
+    let dir = make_dir("glob")?;
+    std::fs::create_dir_all(dir.join("sub.tfrecord"))?;
+    std::fs::create_dir_all(dir.join("nested"))?;
+    for name in [
+        "train-2.tfrecord",
+        "train-10.tfrecord",
+        "train-1.tfrecord",
+        "test-1.tfrecord",
+        "train-3.txt",
+        "nested/train-4.tfrecord",
+    ] {
+        write_file(&dir.join(name), name.as_bytes())?;
+    }
+
+    let pattern = format!("{}/*.tfrecord", dir.display());
+    let paths: Vec<_> = indexer::expand_glob(&pattern, PathOrder::AsGiven)?
+        .into_iter()
+        .map(|path| path.strip_prefix(&dir).unwrap().to_path_buf())
+        .collect();
+    let expect: Vec<PathBuf> = [
+        "test-1.tfrecord",
+        "train-1.tfrecord",
+        "train-10.tfrecord",
+        "train-2.tfrecord",
+    ]
+    .iter()
+    .map(PathBuf::from)
+    .collect();
+    assert_eq!(paths, expect);
+
+    let pattern = format!("{}/**/train-*.tfrecord", dir.display());
+    let indexes: Vec<_> =
+        indexer::load_glob(&pattern, Default::default())?.collect::<Result<_, _>>()?;
+    let paths: Vec<PathBuf> = indexer::file_paths(&indexes)
+        .iter()
+        .map(|path| path.strip_prefix(&dir).unwrap().to_path_buf())
+        .collect();
+    let expect: Vec<PathBuf> = [
+        "nested/train-4.tfrecord",
+        "train-1.tfrecord",
+        "train-10.tfrecord",
+        "train-2.tfrecord",
+    ]
+    .iter()
+    .map(PathBuf::from)
+    .collect();
+    assert_eq!(paths, expect);
+
+    assert!(indexer::expand_glob("[", PathOrder::AsGiven).is_err());
+    Ok(())
+}
@@ -0,0 +1,262 @@
+//! The golden files are written after the output conventions of `text_format.MessageToString` in Python.
+
+#![cfg(feature = "proto-summary")]
+
+use tfrecord::{
+    pbtxt,
+    prelude::*,
+    protobuf::{
+        event::What,
+        log_message::Level,
+        session_log::SessionStatus,
+        summary::{value::Value as SummaryValueKind, Value as SummaryValue},
+        summary_metadata::PluginData,
+        tensor_shape_proto::Dim,
+        DataClass, DataType, LogMessage, SessionLog, SummaryMetadata, TensorProto,
+        TensorShapeProto,
+    },
+};
+
+fn golden_example() -> Example {
+    vec![
+        (
+            "bytes".into(),
+            Feature::from_bytes_iter([
+                b"plain".to_vec(),
+                vec![0x00, 0x01, 0x7f, 0x80, 0xff],
+                b"tab\tnew\nline\rquote\"apos'back\\".to_vec(),
+                "h\u{e9}llo \u{2713}".as_bytes().to_vec(),
+            ]),
+        ),
+        ("empty".into(), Feature::from_i64_iter([])),
+        (
+            "float".into(),
+            Feature::from_f32_iter([
+                0.0,
+                -0.0,
+                0.1,
+                -1.5,
+                1e-5,
+                1e-4,
+                123456.79,
+                3e20,
+                1e16,
+                f32::INFINITY,
+                f32::NEG_INFINITY,
+                f32::NAN,
+            ]),
+        ),
+        (
+            "int64".into(),
+            Feature::from_i64_iter([0, -1, i64::MAX, i64::MIN]),
+        ),
+        ("key with \"quotes\"\n".into(), Feature::from_i64_iter([1])),
+        ("unset".into(), Feature { kind: None }),
+    ]
+    .into_iter()
+    .collect()
+}
+
+fn golden_event() -> Event {
+    let value = |tag: &str, value| SummaryValue {
+        node_name: "".into(),
+        tag: tag.into(),
+        metadata: None,
+        value: Some(value),
+    };
+
+    let summary = Summary {
+        value: vec![
+            value("loss", SummaryValueKind::SimpleValue(0.25)),
+            value("zero", SummaryValueKind::SimpleValue(0.0)),
+            value(
+                "weights",
+                SummaryValueKind::Histo(HistogramProto {
+                    min: -1.0,
+                    max: 2.5,
+                    num: 3.0,
+                    sum: 1.5,
+                    sum_squares: 7.25,
+                    bucket_limit: vec![0.0, 1.0, f64::MAX],
+                    bucket: vec![1.0, 1.0, 1.0],
+                }),
+            ),
+            SummaryValue {
+                metadata: Some(SummaryMetadata {
+                    plugin_data: Some(PluginData {
+                        plugin_name: "text".into(),
+                        content: vec![],
+                    }),
+                    display_name: "".into(),
+                    summary_description: "".into(),
+                    data_class: DataClass::Tensor as i32,
+                }),
+                ..value(
+                    "text/\u{e9}",
+                    SummaryValueKind::Tensor(TensorProto {
+                        dtype: DataType::DtString as i32,
+                        tensor_shape: Some(TensorShapeProto {
+                            dim: vec![Dim {
+                                size: 2,
+                                name: "".into(),
+                            }],
+                            unknown_rank: false,
+                        }),
+                        string_val: vec![b"a\0b".to_vec(), b"c".to_vec()],
+                        ..Default::default()
+                    }),
+                )
+            },
+            SummaryValue {
+                node_name: "dense/kernel".into(),
+                ..value(
+                    "matrix",
+                    SummaryValueKind::Tensor(TensorProto {
+                        dtype: DataType::DtFloat as i32,
+                        tensor_shape: Some(TensorShapeProto {
+                            dim: vec![
+                                Dim {
+                                    size: 2,
+                                    name: "rows".into(),
+                                },
+                                Dim {
+                                    size: 1,
+                                    name: "".into(),
+                                },
+                            ],
+                            unknown_rank: false,
+                        }),
+                        float_val: vec![1e-7, 2.5],
+                        ..Default::default()
+                    }),
+                )
+            },
+        ],
+    };
+
+    Event {
+        wall_time: 1234567890.5,
+        step: 42,
+        what: Some(What::Summary(summary)),
+    }
+}
+
+#[test]
+fn example_golden_test() {
+    let expect = include_str!("example_golden.pbtxt");
+    assert_eq!(golden_example().to_pbtxt(), expect);
+}
+
+#[test]
+fn event_golden_test() {
+    let expect = include_str!("event_golden.pbtxt");
+    assert_eq!(golden_event().to_pbtxt(), expect);
+}
+
+#[test]
+fn write_example_test() -> Result<()> {
+    let example = golden_example();
+    let mut buf = vec![];
+    pbtxt::write_example(&mut buf, &example)?;
+    assert_eq!(String::from_utf8(buf).unwrap(), example.to_pbtxt());
+    Ok(())
+}
+
+#[test]
+fn sequence_example_test() {
+    let example = SequenceExample {
+        context: Some(Features::default()),
+        feature_lists: Some(tfrecord::protobuf::FeatureLists {
+            feature_list: [(
+                "frames".to_string(),
+                tfrecord::protobuf::FeatureList {
+                    feature: vec![Feature::from_i64_iter([1]), Feature::from_i64_iter([2])],
+                },
+            )]
+            .into_iter()
+            .collect(),
+        }),
+    };
+    let expect = r#"context {
+}
+feature_lists {
+  feature_list {
+    key: "frames"
+    value {
+      feature {
+        int64_list {
+          value: 1
+        }
+      }
+      feature {
+        int64_list {
+          value: 2
+        }
+      }
+    }
+  }
+}
+"#;
+    assert_eq!(example.to_pbtxt(), expect);
+}
+
+#[test]
+fn event_kinds_test() {
+    let event = |what| Event {
+        wall_time: 0.0,
+        step: 0,
+        what: Some(what),
+    };
+
+    assert_eq!(
+        event(What::FileVersion("brain.Event:2".into())).to_pbtxt(),
+        "file_version: \"brain.Event:2\"\n"
+    );
+    assert_eq!(
+        event(What::LogMessage(LogMessage {
+            level: Level::Warn as i32,
+            message: "low disk".into(),
+        }))
+        .to_pbtxt(),
+        "log_message {\n  level: WARN\n  message: \"low disk\"\n}\n"
+    );
+    assert_eq!(
+        event(What::SessionLog(SessionLog {
+            status: SessionStatus::Checkpoint as i32,
+            checkpoint_path: "/tmp/ckpt".into(),
+            msg: "".into(),
+        }))
+        .to_pbtxt(),
+        "session_log {\n  status: CHECKPOINT\n  checkpoint_path: \"/tmp/ckpt\"\n}\n"
+    );
+}
+
+#[test]
+fn tensor_test() {
+    let tensor = TensorProto {
+        dtype: DataType::DtComplex64Ref as i32,
+        tensor_shape: Some(TensorShapeProto {
+            dim: vec![],
+            unknown_rank: true,
+        }),
+        int64_val: vec![3],
+        bool_val: vec![true, false],
+        ..Default::default()
+    };
+    let expect = r#"dtype: DT_COMPLEX64_REF
+tensor_shape {
+  unknown_rank: true
+}
+int64_val: 3
+bool_val: true
+bool_val: false
+"#;
+    assert_eq!(tensor.to_pbtxt(), expect);
+
+    // unknown enum values are printed as numbers
+    let tensor = TensorProto {
+        dtype: 99,
+        ..Default::default()
+    };
+    assert_eq!(tensor.to_pbtxt(), "dtype: 99\n");
+}
@@ -0,0 +1,158 @@
+#![cfg(feature = "proto-summary")]
+
+mod common;
+
+use common::*;
+use tfrecord::{
+    inspect,
+    metadata::{self, ShardMetadata},
+    protobuf::{event::What, Summary},
+    BytesIter, BytesWriter, EventClock, EventIter, EventWriter, EventWriterConfig,
+    RecordWriterConfig, WallClock,
+};
+
+struct ConstClock;
+
+impl WallClock for ConstClock {
+    fn wall_time(&self) -> f64 {
+        1.0
+    }
+}
+
+#[test]
+fn stamp_test() -> Result<()> {
+    let dir = make_temp_dir("producer_info_stamp")?;
+    let path = dir.join("stamped.tfevents");
+    let mut writer = EventWriter::create(
+        &path,
+        EventWriterConfig::default().with_clock(EventClock::new(ConstClock)),
+    )?;
+    writer.write_scalar("loss", 0, 0.5)?;
+    drop(writer);
+
+    let info = inspect::producer_info(&path)?.unwrap();
+    assert_eq!(info.name, "rust-tfrecord");
+    assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(info.options["canonical_encoding"], "false");
+    assert_eq!(info.options["compression"], "none");
+
+    // the stamp is an ignorable event after the file version and before the summaries
+    let events: Vec<_> = EventIter::open(&path, Default::default())?.collect::<Result<_, _>>()?;
+    assert_eq!(events.len(), 3);
+    assert_eq!(
+        events[0].what,
+        Some(What::FileVersion("brain.Event:2".into()))
+    );
+    assert!(matches!(events[1].what, Some(What::LogMessage(_))));
+    assert_eq!(events[1].wall_time, 1.0);
+    assert_eq!(
+        events[2].what,
+        Some(What::Summary(Summary::from_scalar("loss", 0.5)?))
+    );
+
+    // stamps are deterministic with fixed clocks
+    let other = dir.join("stamped_other.tfevents");
+    let writer = EventWriter::create(
+        &other,
+        EventWriterConfig::default().with_clock(EventClock::new(ConstClock)),
+    )?;
+    drop(writer);
+    let first_record = |path| -> Result<Vec<u8>> {
+        let record = tfrecord::BytesIter::open(path, Default::default())?
+            .next()
+            .unwrap()?;
+        Ok(record)
+    };
+    assert_eq!(first_record(&path)?, first_record(&other)?);
+    Ok(())
+}
+
+#[test]
+fn disabled_stamp_test() -> Result<()> {
+    let path = make_temp_dir("producer_info_disabled")?.join("unstamped.tfevents");
+    let mut writer = EventWriter::create(
+        &path,
+        EventWriterConfig::default().with_stamp_producer(false),
+    )?;
+    writer.write_scalar("loss", 0, 0.5)?;
+    drop(writer);
+
+    assert_eq!(inspect::producer_info(&path)?, None);
+    // the file version and the summary
+    assert_eq!(EventIter::open(&path, Default::default())?.count(), 2);
+    Ok(())
+}
+
+#[test]
+fn tfrecord_stamp_test() -> Result<()> {
+    let dir = make_temp_dir("producer_info_tfrecord")?;
+
+    // the stamp records the configuration of the writer in the sidecar
+    let path = dir.join("stamped.tfrecord");
+    let config = RecordWriterConfig {
+        canonical_encoding: true,
+        ..Default::default()
+    };
+    let mut writer = BytesWriter::create_with_config(&path, config)?;
+    writer.send(b"not an event".to_vec())?;
+    writer.flush()?;
+    drop(writer);
+
+    let info = inspect::producer_info(&path)?.unwrap();
+    assert_eq!(info.name, "rust-tfrecord");
+    assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(info.options["canonical_encoding"], "true");
+    assert_eq!(info.options["compression"], "none");
+    assert_eq!(info.options["sorted_features"], "false");
+    let metadata = ShardMetadata::read_for(&path)?.unwrap();
+    assert_eq!(metadata.producer, Some(info));
+    assert_eq!(ShardMetadata::parse(&metadata.to_text())?, metadata);
+
+    // the records are not changed by the stamp
+    let records: Vec<_> = BytesIter::open(&path, Default::default())?.collect::<Result<_, _>>()?;
+    assert_eq!(records, [b"not an event".to_vec()]);
+
+    // the stamp can be disabled
+    let path = dir.join("plain.tfrecord");
+    let config = RecordWriterConfig {
+        stamp_producer: false,
+        ..Default::default()
+    };
+    let mut writer = BytesWriter::create_with_config(&path, config)?;
+    writer.send(b"not an event".to_vec())?;
+    writer.flush()?;
+    drop(writer);
+
+    assert!(!metadata::metadata_path(&path).exists());
+    assert_eq!(inspect::producer_info(&path)?, None);
+    Ok(())
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn gzip_tfrecord_stamp_test() -> Result<()> {
+    let path = make_temp_dir("producer_info_gzip")?.join("stamped.tfrecord.gz");
+    let mut writer = BytesWriter::create_gzip(&path, Default::default(), 6)?;
+    writer.send(b"record".to_vec())?;
+    writer.finish()?;
+
+    let info = inspect::producer_info(&path)?.unwrap();
+    assert_eq!(info.options["compression"], "gzip");
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn async_stamp_test() -> Result<()> {
+    use tfrecord::EventAsyncWriter;
+
+    let path = make_temp_dir("producer_info_async")?.join("stamped.tfevents");
+    let mut writer = EventAsyncWriter::create(&path, Default::default()).await?;
+    writer.write_scalar("loss", 0, 0.5).await?;
+    drop(writer);
+
+    let info = inspect::producer_info(&path)?.unwrap();
+    assert_eq!(info.name, "rust-tfrecord");
+    assert_eq!(EventIter::open(&path, Default::default())?.count(), 3);
+    Ok(())
+}
@@ -0,0 +1,174 @@
+mod common;
+
+use common::*;
+use prost::Message as _;
+use tfrecord::{
+    BytesWriter, Example, ExampleIter, ExampleWriter, Feature, FeatureProjection,
+    RecordWriterConfig,
+};
+
+fn make_example(index: usize) -> Example {
+    (0..26u8)
+        .map(|key| {
+            (
+                ((b'a' + key) as char).to_string(),
+                Feature::from_i64_list(vec![index as i64; key as usize]),
+            )
+        })
+        .collect()
+}
+
+/// Serialize the features in descending key order.
+///
+/// Concatenated messages are merged on decoding, so each feature is encoded as a single-feature example.
+fn encode_reversed(example: &Example) -> Vec<u8> {
+    let mut entries = example.clone().into_vec();
+    entries.sort_by(|(lhs, _), (rhs, _)| rhs.cmp(lhs));
+    entries
+        .into_iter()
+        .flat_map(|entry| Example::from_iter([entry]).encode_to_vec())
+        .collect()
+}
+
+fn project(example: Example, projection: &FeatureProjection) -> Example {
+    example
+        .into_iter()
+        .filter(|(key, _)| projection.keys.contains(key))
+        .collect()
+}
+
+#[test]
+fn decode_projected_test() -> Result<()> {
+    let example = make_example(7);
+    let sorted = example.encode_canonical_to_vec();
+    let unsorted = encode_reversed(&example);
+    assert_eq!(Example::decode(unsorted.as_slice())?, example);
+
+    for keys in [
+        vec!["b", "c"],
+        vec!["a", "z"],
+        vec!["m"],
+        vec!["q", "missing"],
+        vec![],
+    ] {
+        let projection = FeatureProjection::new(keys);
+        let early_exit = FeatureProjection {
+            sorted_keys: true,
+            ..projection.clone()
+        };
+        let expect = project(example.clone(), &projection);
+
+        assert_eq!(Example::decode_projected(&sorted, &projection)?, expect);
+        assert_eq!(Example::decode_projected(&sorted, &early_exit)?, expect);
+        assert_eq!(Example::decode_projected(&unsorted, &projection)?, expect);
+    }
+
+    // the truncated tail is never reached with early exit
+    let mut truncated = sorted;
+    truncated.extend([0x0a, 0xff]);
+    let projection = FeatureProjection::new(["b"]);
+    assert!(Example::decode_projected(&truncated, &projection).is_err());
+    let projection = FeatureProjection {
+        sorted_keys: true,
+        ..projection
+    };
+    assert_eq!(
+        Example::decode_projected(&truncated, &projection)?,
+        project(example, &projection)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn read_projected_test() -> Result<()> {
+    let examples: Vec<_> = (0..16).map(make_example).collect();
+    let dir = make_temp_dir("projection_read")?;
+    let sorted_path = dir.join("sorted.tfrecord");
+    let unsorted_path = dir.join("unsorted.tfrecord");
+
+    {
+        let mut writer = ExampleWriter::create_with_config(
+            &sorted_path,
+            RecordWriterConfig {
+                canonical_encoding: true,
+                ..Default::default()
+            },
+        )?;
+        for example in &examples {
+            writer.send(example.clone())?;
+        }
+
+        let mut writer = BytesWriter::create(&unsorted_path)?;
+        for example in &examples {
+            writer.send(encode_reversed(example))?;
+        }
+    }
+
+    let projection = FeatureProjection::new(["c", "d"]);
+    let early_exit = FeatureProjection {
+        sorted_keys: true,
+        ..projection.clone()
+    };
+    let expect: Vec<_> = examples
+        .iter()
+        .map(|example| project(example.clone(), &projection))
+        .collect();
+
+    for (path, projection) in [
+        (&sorted_path, &projection),
+        (&sorted_path, &early_exit),
+        (&unsorted_path, &projection),
+    ] {
+        let output: Vec<_> =
+            ExampleIter::open_projected(path, Default::default(), projection.clone())?
+                .collect::<Result<_, _>>()?;
+        assert_eq!(output, expect);
+
+        let output: Vec<_> = tfrecord::indexer::load_file(path, Default::default())?
+            .map(|index| index?.load_projected(projection))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(output, expect);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn stream_projected_test() -> Result<()> {
+    use futures::stream::TryStreamExt as _;
+    use tfrecord::ExampleStream;
+
+    let examples: Vec<_> = (0..4).map(make_example).collect();
+    let mut bytes = vec![];
+    {
+        let mut writer = ExampleWriter::from_writer_with_config(
+            &mut bytes,
+            RecordWriterConfig {
+                canonical_encoding: true,
+                ..Default::default()
+            },
+        )?;
+        for example in &examples {
+            writer.send(example.clone())?;
+        }
+    }
+
+    let projection = FeatureProjection {
+        sorted_keys: true,
+        ..FeatureProjection::new(["x"])
+    };
+    let output: Vec<_> = ExampleStream::from_reader_projected(
+        futures::io::Cursor::new(bytes),
+        Default::default(),
+        projection.clone(),
+    )
+    .try_collect()
+    .await?;
+    let expect: Vec<_> = examples
+        .into_iter()
+        .map(|example| project(example, &projection))
+        .collect();
+    assert_eq!(output, expect);
+    Ok(())
+}
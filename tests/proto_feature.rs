@@ -0,0 +1,138 @@
+use std::error::Error as _;
+use tfrecord::{Error, Example, ExampleBuilder, Feature};
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct AdFeatures {
+    #[prost(string, tag = "1")]
+    campaign: String,
+    #[prost(float, repeated, tag = "2")]
+    bids: Vec<f32>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct Click {
+    #[prost(int64, tag = "1")]
+    timestamp: i64,
+    #[prost(bool, tag = "2")]
+    converted: bool,
+}
+
+const AD_TYPE: &str = "type.googleapis.com/test.AdFeatures";
+const CLICK_TYPE: &str = "type.googleapis.com/test.Click";
+
+fn ad(index: usize) -> AdFeatures {
+    AdFeatures {
+        campaign: format!("campaign-{}", index),
+        bids: vec![index as f32, 0.5],
+    }
+}
+
+#[test]
+fn proto_round_trip_test() -> anyhow::Result<()> {
+    let click = Click {
+        timestamp: 1_700_000_000,
+        converted: true,
+    };
+    let ads: Vec<_> = (0..3).map(ad).collect();
+
+    let mut example = Example::empty();
+    example.push_proto("click", &click);
+    example.push_protos("ads", &ads);
+    example.push_protos::<Click>("none", &[]);
+
+    assert_eq!(example.get_proto::<Click>("click")?, Some(click.clone()));
+    assert_eq!(example.get_protos::<AdFeatures>("ads")?, ads);
+    assert_eq!(example.get_proto::<Click>("none")?, None);
+    assert_eq!(example.get_proto::<Click>("missing")?, None);
+    assert!(example.get_protos::<Click>("missing")?.is_empty());
+
+    // many messages are not a single one
+    assert!(matches!(
+        example.get_proto::<AdFeatures>("ads"),
+        Err(Error::ConversionError { .. })
+    ));
+
+    // the builder and namespace views
+    let example = ExampleBuilder::new()
+        .namespace("user")
+        .push_proto("click", &click)
+        .push_protos("ads", &ads)
+        .build();
+    let user = example.namespace("user");
+    assert_eq!(user.get_proto::<Click>("click")?, Some(click));
+    assert_eq!(user.get_protos::<AdFeatures>("ads")?, ads);
+
+    // non-bytes features hold no messages
+    let mut example = Example::empty();
+    example.push_i64s("ids", &[1, 2]);
+    assert!(example.get_protos::<Click>("ids").is_err());
+    Ok(())
+}
+
+#[test]
+fn proto_wrong_schema_test() -> anyhow::Result<()> {
+    let ads: Vec<_> = (0..2).map(ad).collect();
+
+    // without the marker, the wrong schema fails to decode with the key and index
+    let mut example = Example::empty();
+    example.push_protos("ads", &ads);
+    let err = example.get_protos::<Click>("ads").unwrap_err();
+    match &err {
+        Error::FeatureDecodeFailed { key, index, .. } => {
+            assert_eq!(key, "ads");
+            assert_eq!(*index, 0);
+        }
+        err => panic!("unexpected error {}", err),
+    }
+    assert!(err.source().is_some());
+    assert!(err.to_string().starts_with("[TFR0024] "), "{}", err);
+
+    // the failing element is reported
+    let mut elements = vec![Click::default(); 2];
+    elements[1].timestamp = 3;
+    let mut bytes: Vec<_> = elements.iter().map(prost::Message::encode_to_vec).collect();
+    bytes.push(vec![0xff]);
+    example.push_bytes("clicks", bytes);
+    assert!(matches!(
+        example.get_protos::<Click>("clicks"),
+        Err(Error::FeatureDecodeFailed { index: 2, .. })
+    ));
+
+    // with the marker, the wrong schema is rejected even if it decodes
+    let clicks = vec![Click::default()];
+    let mut example = Example::empty();
+    example.push_typed_protos("ads", AD_TYPE, &ads);
+    example.push_typed_protos("clicks", CLICK_TYPE, &clicks);
+    assert_eq!(example.get_typed_protos::<AdFeatures>("ads", AD_TYPE)?, ads);
+    assert_eq!(
+        example.get_typed_proto::<Click>("clicks", CLICK_TYPE)?,
+        Some(clicks[0].clone())
+    );
+    // an empty click decodes as ad features, but the marker rejects it
+    assert!(example.get_protos::<AdFeatures>("clicks").is_ok());
+    let err = example
+        .get_typed_protos::<AdFeatures>("clicks", AD_TYPE)
+        .unwrap_err();
+    assert!(err.to_string().contains(CLICK_TYPE), "{}", err);
+
+    // a missing marker is rejected, and a missing feature is empty
+    let mut example = Example::empty();
+    example.push_protos("ads", &ads);
+    assert!(example
+        .get_typed_protos::<AdFeatures>("ads", AD_TYPE)
+        .is_err());
+    assert_eq!(
+        example.get_typed_proto::<AdFeatures>("missing", AD_TYPE)?,
+        None
+    );
+
+    // the marker is a plain companion feature
+    let example = ExampleBuilder::new()
+        .push_typed_protos("ads", AD_TYPE, &ads)
+        .build();
+    assert_eq!(
+        example.into_hash_map()["ads.type_url"],
+        Feature::from_bytes_list(vec![AD_TYPE.as_bytes().to_vec()])
+    );
+    Ok(())
+}
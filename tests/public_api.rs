@@ -0,0 +1,110 @@
+//! Guards the stable API listed in the prelude against accidental removals.
+//!
+//! Removing a name from the prelude breaks the compilation of this test. When a
+//! removal is intended, update the lists here together with the changelog.
+
+#![cfg(feature = "proto-summary")]
+
+#[allow(unused_imports)]
+use tfrecord::prelude::{
+    indexer, protobuf, BytesIter, BytesWriter, Error, Event, EventClock, EventIter, EventMeta,
+    EventWriter, EventWriterConfig, Example, ExampleIter, ExampleWriter, Feature, FeatureKind,
+    FeatureProjection, Features, FramedLen, HistogramProto, IntegrityMode, IntoHistogram,
+    IntoImageList, IntoShape, KeyCollisionPolicy, Limits, MemoryBuffer, Record, RecordIndex,
+    RecordIndexerConfig, RecordIter, RecordReaderConfig, RecordWriter, RecordWriterConfig, Result,
+    SequenceExample, Summary, SystemClock, TensorProtoElement, ToPbtxt, WallClock,
+};
+
+#[cfg(feature = "async")]
+#[allow(unused_imports)]
+use tfrecord::prelude::{
+    BytesAsyncWriter, BytesStream, EventAsyncWriter, EventStream, ExampleAsyncWriter,
+    ExampleStream, RecordAsyncWriter, RecordStream,
+};
+
+fn short_type_name<T: ?Sized>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    let name = name.split('<').next().unwrap();
+    name.rsplit("::").next().unwrap()
+}
+
+#[test]
+fn prelude_types_test() {
+    let names = [
+        short_type_name::<Error>(),
+        short_type_name::<Event>(),
+        short_type_name::<EventClock>(),
+        short_type_name::<EventMeta>(),
+        short_type_name::<EventWriter<Vec<u8>>>(),
+        short_type_name::<EventWriterConfig>(),
+        short_type_name::<Example>(),
+        short_type_name::<Feature>(),
+        short_type_name::<FeatureKind>(),
+        short_type_name::<FeatureProjection>(),
+        short_type_name::<Features>(),
+        short_type_name::<HistogramProto>(),
+        short_type_name::<IntegrityMode>(),
+        short_type_name::<KeyCollisionPolicy>(),
+        short_type_name::<Limits>(),
+        short_type_name::<MemoryBuffer>(),
+        short_type_name::<RecordIndex>(),
+        short_type_name::<RecordIndexerConfig>(),
+        short_type_name::<RecordIter<Example, &[u8]>>(),
+        short_type_name::<RecordReaderConfig>(),
+        short_type_name::<RecordWriter<Example, Vec<u8>>>(),
+        short_type_name::<RecordWriterConfig>(),
+        short_type_name::<SequenceExample>(),
+        short_type_name::<Summary>(),
+        short_type_name::<SystemClock>(),
+    ];
+    let expect = [
+        "Error",
+        "Event",
+        "EventClock",
+        "EventMeta",
+        "EventWriter",
+        "EventWriterConfig",
+        "Example",
+        "Feature",
+        "FeatureKind",
+        "FeatureProjection",
+        "Features",
+        "HistogramProto",
+        "IntegrityMode",
+        "KeyCollisionPolicy",
+        "Limits",
+        "MemoryBuffer",
+        "RecordIndex",
+        "RecordIndexerConfig",
+        "RecordIter",
+        "RecordReaderConfig",
+        "RecordWriter",
+        "RecordWriterConfig",
+        "SequenceExample",
+        "Summary",
+        "SystemClock",
+    ];
+    assert_eq!(names, expect);
+}
+
+#[test]
+fn prelude_traits_test() {
+    fn assert_record<T: Record>() {}
+    fn assert_framed_len<T: FramedLen + ?Sized>() {}
+    fn assert_wall_clock<T: WallClock>() {}
+    fn assert_to_pbtxt<T: ToPbtxt>() {}
+
+    assert_record::<Vec<u8>>();
+    assert_record::<Example>();
+    assert_record::<Event>();
+    assert_framed_len::<Example>();
+    assert_framed_len::<[u8]>();
+    assert_wall_clock::<SystemClock>();
+    assert_to_pbtxt::<Example>();
+    assert_to_pbtxt::<Event>();
+
+    // extension methods are visible with the prelude only
+    let histogram: HistogramProto = vec![1.0f64, 2.0, 3.0].try_into_histogram().unwrap();
+    assert_eq!(histogram.num, 3.0);
+    assert_eq!(vec![2usize, 3].to_shape().len(), 2);
+}
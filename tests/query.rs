@@ -0,0 +1,180 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use tfrecord::{
+    latency::{LatencyRecorder, Phase},
+    query::{Query, QueryOutput},
+    samples, BytesWriter, Error as TfError, Example, ExampleIter, Record, RecordReaderConfig,
+};
+
+#[test]
+fn query_limit_short_circuits() -> Result<()> {
+    let dataset = samples::tiny_dataset(3, 10)?;
+    let latency = LatencyRecorder::new();
+    let evaluated = Arc::new(AtomicU64::new(0));
+    let query = {
+        let evaluated = evaluated.clone();
+        Query::new()
+            .filter(move |example| {
+                evaluated.fetch_add(1, Ordering::SeqCst);
+                example.get_i64s("label").is_ok_and(|label| label[0] == 1)
+            })
+            .limit(2)
+            .with_reader_config(RecordReaderConfig {
+                latency: Some(latency.clone()),
+                ..Default::default()
+            })
+    };
+
+    let ids: Vec<_> = query
+        .run_stream(dataset.paths())
+        .map(|example| Ok(example?.get_i64s("id")?[0]))
+        .collect::<Result<_>>()?;
+    assert_eq!(ids, [1, 11]);
+
+    // no record is read past the second match in the second file, so the reads are
+    // the 12 records and the end of the first file
+    assert_eq!(evaluated.load(Ordering::SeqCst), 12);
+    assert_eq!(latency.snapshot().get(Phase::Read).count, 13);
+
+    assert_eq!(Query::new().limit(0).count(dataset.paths())?, 0);
+    assert_eq!(Query::new().count(dataset.paths())?, 30);
+    Ok(())
+}
+
+#[test]
+fn query_projection() -> Result<()> {
+    let dataset = samples::tiny_dataset(2, 10)?;
+
+    // the predicate sees the declared features, and the output the selected ones
+    let query = Query::new()
+        .select(["id", "name"])
+        .filter_on(["score"], |example| {
+            assert!(example.get_i64s("label").is_err());
+            example.get_f32s("score").is_ok_and(|score| score[0] >= 6.0)
+        });
+    let examples: Vec<_> = query
+        .run_stream(dataset.paths())
+        .collect::<Result<_, _>>()?;
+    assert_eq!(examples.len(), 8);
+    for (example, id) in examples.into_iter().zip(12..) {
+        let mut keys: Vec<_> = example
+            .clone()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+        keys.sort();
+        assert_eq!(keys, ["id", "name"]);
+        assert_eq!(example.get_i64s("id")?, [id]);
+    }
+
+    // whole-example predicates fall back to plain decoding
+    let query = Query::new()
+        .select(["id"])
+        .filter(|example| example.get_i64s("label").is_ok_and(|label| label[0] == 3));
+    let ids: Vec<_> = query
+        .run_stream(dataset.paths())
+        .map(|example| {
+            let example = example?;
+            ensure!(example.clone().into_vec().len() == 1, "unexpected features");
+            Ok(example.get_i64s("id")?[0])
+        })
+        .collect::<Result<_>>()?;
+    assert_eq!(ids, [3, 13]);
+    Ok(())
+}
+
+#[test]
+fn query_errors_carry_record_index() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 5)?;
+    let query = Query::new().try_filter(|example| {
+        let id = example.get_i64s("id")?[0];
+        if id == 3 {
+            example.get_f32s("id")?;
+        }
+        Ok(true)
+    });
+    let results: Vec<_> = query.run_stream(dataset.paths()).collect();
+    assert_eq!(results.len(), 4);
+    match &results[3] {
+        Err(TfError::RecordFailed { path, index: 3, .. }) => assert_eq!(path, &dataset.paths()[0]),
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    // undecodable records are reported likewise
+    let path = dataset.dir().join("garbage.tfrecord");
+    let mut writer = BytesWriter::create(&path)?;
+    writer.send(Example::to_bytes(samples::example(0))?)?;
+    writer.send(vec![0xff; 4])?;
+    writer.flush()?;
+    let error = Query::new().count([path.as_path()]).unwrap_err();
+    assert!(
+        matches!(error, TfError::RecordFailed { index: 1, .. }),
+        "{:?}",
+        error
+    );
+    Ok(())
+}
+
+#[test]
+fn query_run_to_files_matches_stream() -> Result<()> {
+    let dataset = samples::tiny_dataset(3, 10)?;
+    let query = Query::new()
+        .select(["id", "score"])
+        .filter_on(["label"], |example| {
+            example
+                .get_i64s("label")
+                .is_ok_and(|label| label[0] % 2 == 0)
+        })
+        .limit(13);
+    let expected: Vec<_> = query
+        .run_stream(dataset.paths())
+        .collect::<Result<_, _>>()?;
+    assert_eq!(expected.len(), 13);
+
+    let prefix = dataset.dir().join("selected");
+    let output = QueryOutput {
+        records_per_shard: Some(5),
+        ..Default::default()
+    };
+    let report = query.run_to_files(dataset.paths(), prefix.to_str().unwrap(), output)?;
+    assert_eq!(report.num_records, 13);
+    let names: Vec<_> = report
+        .paths
+        .iter()
+        .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "selected-00000-of-00003",
+            "selected-00001-of-00003",
+            "selected-00002-of-00003"
+        ]
+    );
+
+    let mut written = vec![];
+    for path in &report.paths {
+        written
+            .extend(ExampleIter::open(path, Default::default())?.collect::<Result<Vec<_>, _>>()?);
+    }
+    assert_eq!(written, expected);
+
+    // an empty result is a single empty file
+    let report = Query::new().limit(0).run_to_files(
+        dataset.paths(),
+        prefix.to_str().unwrap(),
+        Default::default(),
+    )?;
+    assert_eq!(report.num_records, 0);
+    assert_eq!(report.paths.len(), 1);
+    assert_eq!(std::fs::metadata(&report.paths[0])?.len(), 0);
+    Ok(())
+}
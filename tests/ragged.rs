@@ -0,0 +1,60 @@
+use tfrecord::{row_splits, Error, Example, ExampleBuilder, RaggedConfig};
+
+#[test]
+fn ragged_round_trip() -> Result<(), Error> {
+    let rows: [&[f32]; 4] = [&[1.0, 2.0], &[], &[3.0], &[]];
+    let mut example = Example::empty();
+    example.push_ragged_f32s("points", &rows);
+
+    // the wire format is a flat float list and an int64 companion
+    assert_eq!(example.get_f32s("points")?, [1.0, 2.0, 3.0]);
+    assert_eq!(example.get_i64s("points.row_lengths")?, [2, 0, 1, 0]);
+    assert_eq!(example.get_ragged_f32s("points")?, rows);
+
+    // zero rows
+    example.push_ragged_f32s("empty", &[]);
+    assert!(example.get_ragged_f32s("empty")?.is_empty());
+
+    // the builder agrees with the setter
+    let built = ExampleBuilder::new()
+        .push_ragged_f32s("points", &rows)
+        .build();
+    assert_eq!(built.get_ragged_f32s("points")?, rows);
+
+    assert_eq!(row_splits([2, 0, 1, 0]), [0, 2, 2, 3, 3]);
+    assert_eq!(row_splits([]), [0]);
+    Ok(())
+}
+
+#[test]
+fn ragged_custom_suffix() -> Result<(), Error> {
+    let config = RaggedConfig {
+        row_lengths_suffix: "/lengths".into(),
+    };
+    let rows: [&[f32]; 2] = [&[1.0], &[2.0, 3.0]];
+    let mut example = Example::empty();
+    example.push_ragged_f32s_with("x", &rows, &config);
+    assert_eq!(example.get_i64s("x/lengths")?, [1, 2]);
+    assert_eq!(example.get_ragged_f32s_with("x", &config)?, rows);
+    assert!(example.get_ragged_f32s("x").is_err());
+    Ok(())
+}
+
+#[test]
+fn ragged_invalid_lengths() {
+    let mut example = Example::empty();
+    example.push_f32s("x", &[1.0, 2.0, 3.0]);
+
+    // missing companion
+    assert!(example.get_ragged_f32s("x").is_err());
+
+    // lengths not summing to the number of values
+    example.push_i64s("x.row_lengths", &[1, 1]);
+    let message = example.get_ragged_f32s("x").unwrap_err().to_string();
+    assert!(message.contains("sum to 2"), "{}", message);
+    assert!(message.contains("has 3 values"), "{}", message);
+
+    // negative lengths
+    example.push_i64s("x.row_lengths", &[4, -1]);
+    assert!(example.get_ragged_f32s("x").is_err());
+}
@@ -0,0 +1,151 @@
+mod common;
+
+use common::*;
+use std::{fs, fs::File, path::PathBuf};
+use tfrecord::{indexer, io::sync::find_frame_start, BytesWriter, SequentialRangeReader};
+
+/// The frame of a record, as written by the record writer.
+fn frame(payload: &[u8]) -> Result<Vec<u8>> {
+    let (mut writer, buffer) = BytesWriter::in_memory()?;
+    writer.send(payload.to_vec())?;
+    writer.flush()?;
+    Ok(buffer.to_vec())
+}
+
+/// Write records including payloads mimicking frames, returning the path, the
+/// records and their frame starts.
+fn adversarial_file(name: &str) -> Result<(PathBuf, Vec<Vec<u8>>, Vec<u64>)> {
+    let fake_header = frame(b"abc")?[..12].to_vec();
+    let embedded_frame = [b"xx".as_slice(), &frame(b"inner")?, b"yyyyyyyyyyyyyyyy"].concat();
+
+    let mut records: Vec<Vec<u8>> = vec![];
+    for index in 0..12u8 {
+        records.push(match index % 4 {
+            0 => vec![index; index as usize * 3],
+            1 => [fake_header.as_slice(), &[index; 5]].concat(),
+            2 => embedded_frame.clone(),
+            _ => vec![],
+        });
+    }
+
+    let dir = make_temp_dir(&format!("range_reader/{}", name))?;
+    let path = dir.join(format!("{}.tfrecord", name));
+    let mut writer = BytesWriter::create(&path)?;
+    for record in &records {
+        writer.send(record.clone())?;
+    }
+    writer.flush()?;
+    drop(writer);
+
+    let starts = indexer::load_file(&path, Default::default())?
+        .map(|index| Ok(index?.offset - 12))
+        .collect::<Result<_>>()?;
+    Ok((path, records, starts))
+}
+
+/// Read the records of adjacent ranges split at the boundaries.
+fn read_split(path: &PathBuf, boundaries: &[u64]) -> Result<Vec<Vec<Vec<u8>>>> {
+    let len = fs::metadata(path)?.len();
+    let mut edges = vec![0];
+    edges.extend_from_slice(boundaries);
+    edges.push(len);
+    edges
+        .windows(2)
+        .map(|range| {
+            SequentialRangeReader::<Vec<u8>>::new(path, range[0]..range[1], Default::default())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(Error::from)
+        })
+        .collect()
+}
+
+#[test]
+fn range_reader_partitions_exactly_once() -> Result<()> {
+    let (path, records, starts) = adversarial_file("split")?;
+    let len = fs::metadata(&path)?.len();
+
+    // two workers split at every byte offset
+    for boundary in 0..=len {
+        let parts = read_split(&path, &[boundary])?;
+        let expect_first = starts.iter().filter(|&&start| start < boundary).count();
+        assert_eq!(parts[0].len(), expect_first, "boundary {}", boundary);
+        assert_eq!(parts.concat(), records, "boundary {}", boundary);
+    }
+
+    // many workers with ranges of several sizes
+    for size in [1, 5, 13, 40, 97] {
+        let boundaries: Vec<_> = (size..len).step_by(size as usize).collect();
+        assert_eq!(read_split(&path, &boundaries)?.concat(), records);
+    }
+    Ok(())
+}
+
+#[test]
+fn range_reader_boundaries() -> Result<()> {
+    let (path, records, starts) = adversarial_file("boundaries")?;
+    // a record with a fake header in its payload
+    let target = 5;
+
+    let cases = [
+        ("at a frame start", starts[target]),
+        ("inside a frame header", starts[target] + 4),
+        ("inside a payload", starts[target] + 14),
+        ("inside an embedded frame", starts[target + 1] + 12 + 2),
+    ];
+    for (name, boundary) in cases {
+        let parts = read_split(&path, &[boundary])?;
+        let expect_first = starts.iter().filter(|&&start| start < boundary).count();
+        assert_eq!(parts[0].len(), expect_first, "{}", name);
+        assert_eq!(parts[0], records[..expect_first], "{}", name);
+        assert_eq!(parts[1], records[expect_first..], "{}", name);
+    }
+
+    // empty ranges and ranges past the end yield nothing
+    let reader = SequentialRangeReader::<Vec<u8>>::new(&path, 10..10, Default::default())?;
+    assert_eq!(reader.count(), 0);
+    let len = fs::metadata(&path)?.len();
+    let reader = SequentialRangeReader::<Vec<u8>>::new(&path, len..len + 100, Default::default())?;
+    assert_eq!(reader.count(), 0);
+    Ok(())
+}
+
+#[test]
+fn find_frame_start_skips_mimicking_payloads() -> Result<()> {
+    let (path, _, starts) = adversarial_file("resync")?;
+    let len = fs::metadata(&path)?.len();
+    let mut file = File::open(&path)?;
+
+    for offset in 0..len {
+        let expect = starts.iter().copied().find(|&start| start >= offset);
+        assert_eq!(
+            find_frame_start(&mut file, offset)?,
+            expect,
+            "offset {}",
+            offset
+        );
+    }
+    assert_eq!(find_frame_start(&mut file, len)?, None);
+    Ok(())
+}
+
+#[test]
+fn find_frame_start_across_scan_windows() -> Result<()> {
+    let dir = make_temp_dir("range_reader/large")?;
+    let path = dir.join("large.tfrecord");
+    let records: Vec<_> = (0..4u8).map(|index| vec![index; 40_000]).collect();
+    let mut writer = BytesWriter::create(&path)?;
+    for record in &records {
+        writer.send(record.clone())?;
+    }
+    drop(writer);
+
+    // frames start every 40016 bytes, so the third one lies beyond the first window
+    let mut file = File::open(&path)?;
+    for offset in [1u64, 40_016, 40_017, 65_530, 65_536, 80_032] {
+        let expect = offset.div_ceil(40_016) * 40_016;
+        assert_eq!(find_frame_start(&mut file, offset)?, Some(expect));
+    }
+    let parts = read_split(&path, &[65_530, 100_000])?;
+    assert_eq!(parts.concat(), records);
+    Ok(())
+}
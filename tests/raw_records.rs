@@ -0,0 +1,78 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::{
+    fs::{self, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+};
+use tfrecord::{indexer, samples, BytesIter, BytesWriter, Error};
+
+#[test]
+fn raw_copy_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(3, 4)?;
+    let indexes: Vec<_> =
+        indexer::load_paths(dataset.paths(), Default::default()).collect::<Result<_, _>>()?;
+
+    // rewrite the records from the raw payloads
+    let copy = dataset.dir().join("copy.tfrecord");
+    let mut writer = BytesWriter::create(&copy)?;
+    let mut num_copied = 0;
+    for result in indexer::iter_raw_from(&indexes, 0, Default::default()) {
+        let (index, bytes) = result?;
+        assert_eq!(index, num_copied);
+        writer.send(bytes)?;
+        num_copied += 1;
+    }
+    writer.flush()?;
+    assert_eq!(num_copied, 12);
+
+    // the payloads are byte-identical, and so are the concatenated files
+    let copied: Vec<_> = BytesIter::open(&copy, Default::default())?.collect::<Result<_, _>>()?;
+    for (index, bytes) in copied.iter().enumerate() {
+        assert_eq!(indexer::get_raw(&indexes, index)?.as_ref(), Some(bytes));
+        assert_eq!(&indexes[index].load_raw()?, bytes);
+    }
+    let original: Vec<u8> = dataset
+        .paths()
+        .iter()
+        .map(fs::read)
+        .collect::<Result<Vec<_>, _>>()?
+        .concat();
+    assert_eq!(fs::read(&copy)?, original);
+    assert_eq!(indexer::get_raw(&indexes, 12)?, None);
+
+    // resuming from an index
+    let tail: Vec<_> =
+        indexer::iter_raw_from(&indexes, 10, Default::default()).collect::<Result<_, _>>()?;
+    assert_eq!(
+        tail,
+        vec![(10, copied[10].clone()), (11, copied[11].clone())]
+    );
+    Ok(())
+}
+
+#[test]
+fn raw_checksum_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 2)?;
+    let path = &dataset.paths()[0];
+    let indexes: Vec<_> =
+        indexer::load_file(path, Default::default())?.collect::<Result<_, _>>()?;
+
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let mut byte = [0];
+    file.seek(SeekFrom::Start(indexes[1].offset))?;
+    file.read_exact(&mut byte)?;
+    file.seek(SeekFrom::Start(indexes[1].offset))?;
+    file.write_all(&[byte[0] ^ 0xff])?;
+    drop(file);
+
+    // corrupt payloads are not copied
+    assert!(indexes[0].load_raw().is_ok());
+    assert!(matches!(
+        indexer::get_raw(&indexes, 1),
+        Err(Error::ChecksumMismatch { offset: Some(offset), .. }) if offset == indexes[1].offset - 12
+    ));
+    Ok(())
+}
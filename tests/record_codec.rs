@@ -0,0 +1,206 @@
+#![cfg(feature = "proto-summary")]
+
+use serde::{Deserialize, Serialize};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    io::{self, Cursor},
+};
+use tfrecord::{
+    protobuf::{Event, Example},
+    Error, Feature, ProstRecord, Record, RecordIter, RecordWriter,
+};
+
+/// Counts allocations made by the current thread.
+struct CountingAlloc;
+
+thread_local! {
+    static NUM_ALLOCS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = NUM_ALLOCS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+fn count_allocs<F, T>(f: F) -> (T, usize)
+where
+    F: FnOnce() -> T,
+{
+    let before = NUM_ALLOCS.with(Cell::get);
+    let output = f();
+    let after = NUM_ALLOCS.with(Cell::get);
+    (output, after - before)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Point {
+    x: f64,
+    y: f64,
+}
+
+/// The bincode record implementing the borrowed paths.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BincodePoint(Point);
+
+impl Record for BincodePoint {
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, Error> {
+        Self::from_slice(&bytes)
+    }
+
+    fn to_bytes(record: Self) -> Result<Vec<u8>, Error> {
+        let mut buf = vec![];
+        Self::encode_into(record, &mut buf)?;
+        Ok(buf)
+    }
+
+    fn from_slice(bytes: &[u8]) -> Result<Self, Error> {
+        let point = bincode::deserialize(bytes).map_err(|err| Error::ConversionError {
+            desc: err.to_string().into(),
+        })?;
+        Ok(Self(point))
+    }
+
+    fn encode_into(record: Self, buf: &mut Vec<u8>) -> Result<(), Error> {
+        bincode::serialize_into(buf, &record.0).map_err(|err| Error::ConversionError {
+            desc: err.to_string().into(),
+        })
+    }
+}
+
+/// The bincode record implementing only the owned paths.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OwnedBincodePoint(Point);
+
+impl Record for OwnedBincodePoint {
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, Error> {
+        BincodePoint::from_slice(&bytes).map(|BincodePoint(point)| Self(point))
+    }
+
+    fn to_bytes(record: Self) -> Result<Vec<u8>, Error> {
+        BincodePoint::to_bytes(BincodePoint(record.0))
+    }
+}
+
+fn make_points(count: usize) -> Vec<Point> {
+    (0..count)
+        .map(|index| Point {
+            x: index as f64,
+            y: -(index as f64),
+        })
+        .collect()
+}
+
+fn write_points<T, F>(points: &[Point], wrap: F) -> Vec<u8>
+where
+    T: Record,
+    F: Fn(Point) -> T,
+{
+    let mut bytes = vec![];
+    let mut writer: RecordWriter<T, _> = RecordWriter::from_writer(&mut bytes).unwrap();
+    for &point in points {
+        writer.send(wrap(point)).unwrap();
+    }
+    drop(writer);
+    bytes
+}
+
+/// The numbers of allocations to read and to write the records.
+fn measure<T, F>(points: &[Point], wrap: F) -> (usize, usize)
+where
+    T: Record,
+    F: Fn(Point) -> T,
+{
+    let bytes = write_points(points, &wrap);
+
+    let (records, read_allocs) = count_allocs(|| {
+        RecordIter::<T, _>::from_reader(bytes.as_slice(), Default::default())
+            .try_fold(0, |count, record| record.map(|_| count + 1))
+            .unwrap()
+    });
+    assert_eq!(records, points.len());
+
+    let (_, write_allocs) = count_allocs(|| {
+        let mut writer: RecordWriter<T, _> = RecordWriter::from_writer(io::sink()).unwrap();
+        for &point in points {
+            writer.send(wrap(point)).unwrap();
+        }
+    });
+
+    (read_allocs, write_allocs)
+}
+
+#[test]
+fn borrowed_path_allocation_test() {
+    let few = make_points(10);
+    let many = make_points(1000);
+
+    // the borrowed paths reuse buffers, so the allocations do not grow with records
+    let (few_read, few_write) = measure(&few, BincodePoint);
+    let (many_read, many_write) = measure(&many, BincodePoint);
+    assert_eq!(many_read, few_read);
+    assert!(many_write <= few_write + 1);
+
+    // the owned paths copy every record
+    let (few_read, few_write) = measure(&few, OwnedBincodePoint);
+    let (many_read, many_write) = measure(&many, OwnedBincodePoint);
+    assert!(many_read >= few_read + 990);
+    assert!(many_write >= few_write + 990);
+}
+
+#[test]
+fn bincode_round_trip_test() -> tfrecord::Result<()> {
+    let points = make_points(100);
+    let bytes = write_points(&points, BincodePoint);
+    let owned_bytes = write_points(&points, OwnedBincodePoint);
+    assert_eq!(bytes, owned_bytes);
+
+    let output: Vec<_> =
+        RecordIter::<BincodePoint, _>::from_reader(Cursor::new(bytes), Default::default())
+            .map(|record| record.map(|BincodePoint(point)| point))
+            .collect::<Result<_, _>>()?;
+    assert_eq!(output, points);
+    Ok(())
+}
+
+fn assert_round_trip<T>(record: T) -> tfrecord::Result<()>
+where
+    T: Record + Clone + PartialEq + std::fmt::Debug,
+{
+    let owned = T::to_bytes(record.clone())?;
+    let mut borrowed = vec![0xff];
+    T::encode_into(record.clone(), &mut borrowed)?;
+    assert_eq!(borrowed[1..], owned[..]);
+
+    assert_eq!(T::from_slice(&owned)?, record);
+    assert_eq!(T::from_bytes(owned)?, record);
+    Ok(())
+}
+
+#[test]
+fn record_round_trip_test() -> tfrecord::Result<()> {
+    let example: Example = vec![("value".into(), Feature::from_i64_list(vec![1, 2, 3]))]
+        .into_iter()
+        .collect();
+    let event = Event {
+        wall_time: 1.5,
+        step: 3,
+        ..Default::default()
+    };
+
+    assert_round_trip(vec![1u8, 2, 3])?;
+    assert_round_trip(example.clone())?;
+    assert_round_trip(event.clone())?;
+    assert_round_trip(ProstRecord(example))?;
+    assert_round_trip(ProstRecord(event))?;
+    Ok(())
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use common::*;
+use std::io::Cursor;
+use tfrecord::{
+    indexer::{self, RecordIndex},
+    BytesIter, BytesWriter, Error,
+};
+
+fn make_records(file_index: usize) -> Vec<Vec<u8>> {
+    (0..10)
+        .map(|index| vec![(file_index * 10 + index) as u8; 1 + index * 3])
+        .collect()
+}
+
+fn frame(records: &[Vec<u8>]) -> Result<Vec<u8>> {
+    let mut bytes = vec![];
+    {
+        let mut writer = BytesWriter::from_writer(&mut bytes)?;
+        for record in records {
+            writer.send(record.clone())?;
+        }
+    }
+    Ok(bytes)
+}
+
+#[test]
+fn iter_from_test() -> Result<()> {
+    let dir = make_temp_dir("resume_iter_from")?;
+    let paths: Vec<_> = (0..3)
+        .map(|file_index| -> Result<_> {
+            let path = dir.join(format!("resume_{}.tfrecord", file_index));
+            std::fs::write(&path, frame(&make_records(file_index))?)?;
+            Ok(path)
+        })
+        .collect::<Result<_>>()?;
+    let records: Vec<_> = (0..3).flat_map(make_records).collect();
+
+    let indexes: Vec<RecordIndex> =
+        indexer::load_paths(paths.iter().map(|path| path.as_path()), Default::default())
+            .collect::<Result<_, _>>()?;
+    assert_eq!(indexes.len(), 30);
+
+    for start in [0, 5, 9, 10, 11, 20, 29, 30, 31] {
+        let output: Vec<(usize, Vec<u8>)> =
+            indexer::iter_from(&indexes, start, Default::default()).collect::<Result<_, _>>()?;
+        let expect: Vec<_> = records.iter().cloned().enumerate().skip(start).collect();
+        assert_eq!(output, expect, "start {}", start);
+    }
+    Ok(())
+}
+
+#[test]
+fn skip_records_test() -> Result<()> {
+    let records = make_records(0);
+    let bytes = frame(&records)?;
+
+    for n in [0, 1, 5, 9, 10, 20] {
+        let mut iter = BytesIter::from_reader(Cursor::new(&bytes), Default::default());
+        assert_eq!(iter.skip_records(n, true)?, n.min(records.len()));
+        let output: Vec<_> = iter.collect::<Result<_, _>>()?;
+        assert_eq!(output, records[n.min(records.len())..], "skip {}", n);
+    }
+    Ok(())
+}
+
+#[test]
+fn skip_records_corrupted_test() -> Result<()> {
+    let records = make_records(0);
+    let bytes = frame(&records)?;
+    let second_record_start = 8 + 4 + records[0].len() + 4;
+
+    // corrupted payload of the skipped record is not inspected
+    let mut corrupted = bytes.clone();
+    corrupted[8 + 4] ^= 0xff;
+    let mut iter = BytesIter::from_reader(Cursor::new(&corrupted), Default::default());
+    assert_eq!(iter.skip_records(1, true)?, 1);
+    assert_eq!(iter.collect::<Result<Vec<_>, _>>()?, records[1..]);
+
+    // corrupted length checksum of a skipped record
+    let mut corrupted = bytes;
+    corrupted[second_record_start + 8] ^= 0xff;
+
+    let mut iter = BytesIter::from_reader(Cursor::new(&corrupted), Default::default());
+    assert!(matches!(
+        iter.skip_records(2, true),
+        Err(Error::ChecksumMismatch { .. })
+    ));
+
+    let mut iter = BytesIter::from_reader(Cursor::new(&corrupted), Default::default());
+    assert_eq!(iter.skip_records(2, false)?, 2);
+    assert_eq!(iter.collect::<Result<Vec<_>, _>>()?, records[2..]);
+    Ok(())
+}
@@ -0,0 +1,139 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::{
+    path::PathBuf,
+    time::{Duration, UNIX_EPOCH},
+};
+use tfrecord::{
+    retention::{self, MissingTimestamp, RetentionPolicy, TimeUnit},
+    BytesIter, BytesWriter, Error as TfError, Example, Record,
+};
+
+fn make_policy(unit: TimeUnit, missing: MissingTimestamp) -> RetentionPolicy {
+    RetentionPolicy {
+        timestamp_feature: "ts".into(),
+        unit,
+        max_age: Duration::from_secs(100),
+        now: UNIX_EPOCH + Duration::from_secs(1_000),
+        missing,
+    }
+}
+
+/// Write examples with the timestamps, where `None` leaves the feature out.
+fn write_input(name: &str, timestamps: &[Option<i64>]) -> Result<(PathBuf, Vec<Vec<u8>>)> {
+    let dir = make_temp_dir(&format!("retention/{}", name))?;
+    let path = dir.join(format!("{}.tfrecord", name));
+    let records: Vec<_> = timestamps
+        .iter()
+        .enumerate()
+        .map(|(index, timestamp)| {
+            let mut example = Example::empty();
+            example.push_i64s("id", &[index as i64]);
+            if let Some(timestamp) = timestamp {
+                example.push_i64s("ts", &[*timestamp, 0]);
+            }
+            Example::to_bytes(example)
+        })
+        .collect::<Result<_, _>>()?;
+    let mut writer = BytesWriter::create(&path)?;
+    for record in &records {
+        writer.send(record.clone())?;
+    }
+    writer.flush()?;
+    Ok((path, records))
+}
+
+fn read(path: &PathBuf) -> Result<Vec<Vec<u8>>> {
+    Ok(BytesIter::open(path, Default::default())?.collect::<Result<_, _>>()?)
+}
+
+#[test]
+fn retention_boundaries() -> Result<()> {
+    // the cutoff is 900 seconds or 900_000 milliseconds
+    let (path, records) = write_input("seconds", &[Some(899), Some(900), Some(901), Some(-5)])?;
+    let prefix = path.with_file_name("seconds-out");
+    let policy = make_policy(TimeUnit::Seconds, MissingTimestamp::Error);
+    assert_eq!(policy.cutoff(), 900);
+    let report = retention::apply([path.as_path()], prefix.to_str().unwrap(), &policy)?;
+    assert_eq!(report.files.len(), 1);
+    let file = &report.files[0];
+    assert_eq!((file.kept, file.dropped, file.missing), (2, 2, 0));
+    assert_eq!(
+        file.output,
+        path.with_file_name("seconds-out-00000-of-00001")
+    );
+
+    // surviving records are byte-identical
+    assert_eq!(read(&file.output)?, records[1..3]);
+
+    let (path, records) = write_input("millis", &[Some(899_999), Some(900_000), Some(900_001)])?;
+    let prefix = path.with_file_name("millis-out");
+    let policy = make_policy(TimeUnit::Millis, MissingTimestamp::Error);
+    let report = retention::apply([path.as_path()], prefix.to_str().unwrap(), &policy)?;
+    assert_eq!(report.num_kept(), 2);
+    assert_eq!(report.num_dropped(), 1);
+    assert_eq!(read(&report.files[0].output)?, records[1..]);
+    Ok(())
+}
+
+#[test]
+fn retention_missing_timestamps() -> Result<()> {
+    let (path, records) = write_input("missing", &[Some(950), None, Some(10), None])?;
+    let prefix = path.with_file_name("missing-out");
+    let prefix = prefix.to_str().unwrap();
+
+    let report = retention::apply(
+        [path.as_path()],
+        prefix,
+        &make_policy(TimeUnit::Seconds, MissingTimestamp::Keep),
+    )?;
+    let file = &report.files[0];
+    assert_eq!((file.kept, file.dropped, file.missing), (3, 1, 2));
+    assert_eq!(read(&file.output)?, [&records[..2], &records[3..]].concat());
+
+    let report = retention::apply(
+        [path.as_path()],
+        prefix,
+        &make_policy(TimeUnit::Seconds, MissingTimestamp::Drop),
+    )?;
+    let file = &report.files[0];
+    assert_eq!((file.kept, file.dropped, file.missing), (1, 3, 2));
+    assert_eq!(read(&file.output)?, records[..1]);
+
+    // failing passes remove their outputs
+    let output = file.output.clone();
+    let error = retention::apply(
+        [path.as_path()],
+        prefix,
+        &make_policy(TimeUnit::Seconds, MissingTimestamp::Error),
+    )
+    .unwrap_err();
+    assert!(
+        matches!(error, TfError::RecordFailed { index: 1, .. }),
+        "{:?}",
+        error
+    );
+    assert!(!output.exists());
+    Ok(())
+}
+
+#[cfg(feature = "with-serde")]
+#[test]
+fn retention_report_serde() -> Result<()> {
+    let (path, _) = write_input("serde", &[Some(950), Some(10)])?;
+    let prefix = path.with_file_name("serde-out");
+    let report = retention::apply(
+        [path.as_path()],
+        prefix.to_str().unwrap(),
+        &make_policy(TimeUnit::Seconds, MissingTimestamp::Error),
+    )?;
+    let json = serde_json::to_string(&report)?;
+    assert_eq!(
+        serde_json::from_str::<retention::RetentionReport>(&json)?,
+        report
+    );
+    Ok(())
+}
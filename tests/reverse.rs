@@ -0,0 +1,242 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use crc::{Crc, CRC_32_ISCSI};
+use std::io::Cursor;
+use tfrecord::{
+    indexer,
+    reverse::{self, ReverseConfig, ReverseIterator},
+    samples, BytesIter, BytesWriter, Example, ExampleIter,
+};
+
+const CASTAGNOLI: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+
+/// The masked checksum of TFRecord frames.
+fn checksum(bytes: &[u8]) -> u32 {
+    CASTAGNOLI
+        .checksum(bytes)
+        .rotate_right(15)
+        .wrapping_add(0xa282ead8)
+}
+
+fn header(len: usize) -> Vec<u8> {
+    let len = (len as u64).to_le_bytes();
+    [&len[..], &checksum(&len).to_le_bytes()].concat()
+}
+
+fn frame(payload: &[u8]) -> Vec<u8> {
+    [
+        header(payload.len()),
+        payload.to_vec(),
+        checksum(payload).to_le_bytes().to_vec(),
+    ]
+    .concat()
+}
+
+/// Overwrite the four bytes at the position so that the checksum of the bytes equals
+/// the target.
+///
+/// The unmasked checksum is affine in the overwritten bits, so they are solved by
+/// Gaussian elimination.
+fn forge(bytes: &mut [u8], at: usize, target: &[u8]) {
+    let crc_with = |bytes: &mut [u8], bits: u32| {
+        bytes[at..at + 4].copy_from_slice(&bits.to_le_bytes());
+        CASTAGNOLI.checksum(bytes)
+    };
+    let base = crc_with(bytes, 0);
+    // the reduced columns and the combinations of bits producing them, by pivot bit
+    let mut basis: [Option<(u32, u32)>; 32] = [None; 32];
+    for bit in 0..32 {
+        let (mut column, mut comb) = (crc_with(bytes, 1 << bit) ^ base, 1u32 << bit);
+        for pivot in (0..32).rev() {
+            if column >> pivot & 1 == 0 {
+                continue;
+            }
+            match basis[pivot] {
+                Some((other, other_comb)) => {
+                    column ^= other;
+                    comb ^= other_comb;
+                }
+                None => {
+                    basis[pivot] = Some((column, comb));
+                    break;
+                }
+            }
+        }
+    }
+
+    let mut diff = CASTAGNOLI.checksum(target) ^ base;
+    let mut bits = 0;
+    for pivot in (0..32).rev() {
+        if diff >> pivot & 1 == 1 {
+            let (column, comb) = basis[pivot].unwrap();
+            diff ^= column;
+            bits ^= comb;
+        }
+    }
+    assert_eq!(crc_with(bytes, bits), CASTAGNOLI.checksum(target));
+}
+
+/// A payload whose frame contains a fake frame ending where the real frame ends,
+/// preceded by a complete fake frame, so that the first fake frame survives a chain
+/// verification of depth one.
+fn adversarial_payload() -> Vec<u8> {
+    let fake_payload = b"fake record".repeat(3);
+    let mut payload = [
+        b"prefix".to_vec(),
+        vec![0; 4],
+        frame(b"nested fake record"),
+        header(fake_payload.len()),
+        fake_payload.clone(),
+    ]
+    .concat();
+    // the payload checksum of the real frame is also that of the fake frame
+    forge(&mut payload, 6, &fake_payload);
+    assert_eq!(checksum(&payload), checksum(&fake_payload));
+    payload
+}
+
+fn write_records(payloads: &[Vec<u8>]) -> Result<Vec<u8>> {
+    let (mut writer, buffer) = BytesWriter::in_memory()?;
+    for payload in payloads {
+        writer.send(payload.clone())?;
+    }
+    writer.flush()?;
+    Ok(buffer.to_vec())
+}
+
+/// The records with the offsets of their frames, read forwards.
+fn read_forward(bytes: &[u8]) -> Result<Vec<(u64, Vec<u8>)>> {
+    let mut offset = 0;
+    let mut records = vec![];
+    for record in BytesIter::from_bytes(bytes.to_vec(), Default::default()) {
+        let record = record?;
+        let len = record.len() as u64;
+        records.push((offset, record));
+        offset += len + 16;
+    }
+    Ok(records)
+}
+
+fn read_reverse(bytes: &[u8], config: ReverseConfig) -> Result<Vec<(u64, Vec<u8>)>> {
+    let iter = ReverseIterator::<Vec<u8>, _>::from_reader(Cursor::new(bytes.to_vec()), config)?;
+    Ok(iter.collect::<Result<_, _>>()?)
+}
+
+#[test]
+fn reverse_matches_forward_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 20)?;
+    let path = &dataset.paths()[0];
+
+    let forward: Vec<Example> =
+        ExampleIter::open(path, Default::default())?.collect::<Result<_, _>>()?;
+    let offsets: Vec<u64> = indexer::load_file(path, Default::default())?
+        .map(|index| Ok(index?.offset - 12))
+        .collect::<Result<_>>()?;
+    let mut reverse: Vec<(u64, Example)> =
+        ReverseIterator::open(path, Default::default())?.collect::<Result<_, _>>()?;
+    reverse.reverse();
+    assert_eq!(
+        reverse,
+        offsets.into_iter().zip(forward).collect::<Vec<_>>()
+    );
+
+    let last = reverse::last_records::<Example>(path, 3)?;
+    assert_eq!(last, reverse[17..]);
+    assert_eq!(reverse::last_records::<Example>(path, 100)?, reverse);
+
+    // records of arbitrary lengths, including empty ones
+    let payloads: Vec<Vec<u8>> = (0..50).map(|len| vec![len as u8; len * 7 % 31]).collect();
+    let bytes = write_records(&payloads)?;
+    let mut expect = read_forward(&bytes)?;
+    expect.reverse();
+    assert_eq!(read_reverse(&bytes, Default::default())?, expect);
+
+    // an empty file has no records
+    assert!(read_reverse(&[], Default::default())?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn adversarial_payload_test() -> Result<()> {
+    let payloads = vec![
+        b"first".to_vec(),
+        adversarial_payload(),
+        b"third".to_vec(),
+        adversarial_payload(),
+    ];
+    let bytes = write_records(&payloads)?;
+    let forward = read_forward(&bytes)?;
+    assert_eq!(
+        forward.iter().map(|(_, record)| record).collect::<Vec<_>>(),
+        payloads.iter().collect::<Vec<_>>()
+    );
+    let mut expect = forward;
+    expect.reverse();
+
+    // the default chain depth resolves the nested fake frames
+    assert_eq!(read_reverse(&bytes, Default::default())?, expect);
+
+    // shallower chains accept the fake frames
+    for chain_depth in [0, 1] {
+        let config = ReverseConfig {
+            chain_depth,
+            ..Default::default()
+        };
+        let iter = ReverseIterator::<Vec<u8>, _>::from_reader(Cursor::new(bytes.clone()), config)?;
+        let records: Vec<_> = iter.take(2).collect();
+        let (offset, record) = records[0].as_ref().unwrap();
+        assert!(*offset > expect[0].0);
+        assert_eq!(record, &b"fake record".repeat(3));
+        assert_ne!(records[1].as_ref().ok(), Some(&expect[1]));
+    }
+    Ok(())
+}
+
+#[test]
+fn truncated_tail_test() -> Result<()> {
+    let payloads: Vec<Vec<u8>> = (0..5).map(|index| vec![index; 100]).collect();
+    let bytes = write_records(&payloads)?;
+    let mut expect = read_forward(&bytes)?;
+    expect.reverse();
+
+    // a partially written frame embedding a complete frame
+    let partial = frame(&[frame(b"embedded").as_slice(), &[7; 50]].concat());
+    for cut in [1, 12, 30, partial.len() - 1] {
+        let truncated = [bytes.as_slice(), &partial[..cut]].concat();
+        let iter = ReverseIterator::<Vec<u8>, _>::from_reader(
+            Cursor::new(truncated.clone()),
+            Default::default(),
+        )?;
+        assert_eq!(iter.trailing_len(), cut as u64);
+        assert_eq!(iter.collect::<Result<Vec<_>, _>>()?, expect);
+    }
+
+    // a file of a partial frame has no records
+    let iter = ReverseIterator::<Vec<u8>, _>::from_reader(
+        Cursor::new(partial[..20].to_vec()),
+        Default::default(),
+    )?;
+    assert_eq!(iter.trailing_len(), 20);
+    assert_eq!(iter.count(), 0);
+
+    // frames longer than the scan window are not found
+    let config = ReverseConfig {
+        scan_window: 50,
+        ..Default::default()
+    };
+    let result = ReverseIterator::<Vec<u8>, _>::from_reader(Cursor::new(bytes), config.clone());
+    assert!(result.is_err());
+    let bytes = write_records(&[vec![0; 100], vec![1; 10]])?;
+    let config = ReverseConfig {
+        chain_depth: 0,
+        ..config
+    };
+    let mut iter = ReverseIterator::<Vec<u8>, _>::from_reader(Cursor::new(bytes), config)?;
+    assert_eq!(iter.next().transpose()?, Some((116, vec![1; 10])));
+    assert!(iter.next().unwrap().is_err());
+    assert!(iter.next().is_none());
+    Ok(())
+}
@@ -0,0 +1,311 @@
+use std::{
+    io::{self, BufWriter, Write},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+use tfrecord::{
+    BytesIter, BytesWriter, Error, FlushCoordinator, FlushCoordinatorConfig, FlushPolicy,
+    FlushStats, RoutingWriter, ShardOutcome,
+};
+
+/// A sink failing with ENOSPC-like errors after accepting a number of bytes.
+#[derive(Debug, Clone)]
+struct Sink {
+    bytes: Arc<Mutex<Vec<u8>>>,
+    capacity: usize,
+}
+
+impl Sink {
+    fn new(capacity: usize) -> Self {
+        Self {
+            bytes: Arc::new(Mutex::new(vec![])),
+            capacity,
+        }
+    }
+
+    fn records(&self) -> Vec<Vec<u8>> {
+        let bytes = self.bytes.lock().unwrap().clone();
+        BytesIter::from_reader(bytes.as_slice(), Default::default())
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut bytes = self.bytes.lock().unwrap();
+        if bytes.len() + buf.len() > self.capacity {
+            return Err(io::Error::other("no space left"));
+        }
+        bytes.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn make_record(index: usize) -> Vec<u8> {
+    vec![index as u8; 16]
+}
+
+#[test]
+fn routing_writer_test() -> tfrecord::Result<()> {
+    let sinks: Vec<_> = (0..3).map(|_| Sink::new(usize::MAX)).collect();
+    let writers = sinks
+        .iter()
+        .map(|sink| BytesWriter::from_writer(sink.clone()))
+        .collect::<Result<_, _>>()?;
+    let mut writer = RoutingWriter::new(writers, |record: &Vec<u8>| record[0] as usize % 3)?;
+
+    for index in 0..30 {
+        writer.send(make_record(index))?;
+    }
+    assert_eq!(writer.finish().into_result()?, vec![10, 10, 10]);
+
+    for (shard, sink) in sinks.iter().enumerate() {
+        let expect: Vec<_> = (0..30)
+            .filter(|index| index % 3 == shard)
+            .map(make_record)
+            .collect();
+        assert_eq!(sink.records(), expect);
+    }
+    Ok(())
+}
+
+#[test]
+fn routing_writer_poison_test() -> tfrecord::Result<()> {
+    // each framed record takes 32 bytes, so the middle sink fails on its 4th record
+    let sinks = [Sink::new(usize::MAX), Sink::new(100), Sink::new(usize::MAX)];
+    let writers = sinks
+        .iter()
+        .map(|sink| BytesWriter::from_writer(sink.clone()))
+        .collect::<Result<_, _>>()?;
+    let mut writer = RoutingWriter::new(writers, |record: &Vec<u8>| record[0] as usize % 3)?;
+
+    let mut acknowledged = vec![];
+    let mut first_error = None;
+    for index in 0..30 {
+        match writer.send(make_record(index)) {
+            Ok(()) => acknowledged.push(index),
+            Err(Error::WriterPoisoned { original }) => {
+                assert!(matches!(*original, Error::IoError(_)));
+                first_error.get_or_insert(index);
+            }
+            Err(err) => panic!("unexpected error: {}", err),
+        }
+    }
+
+    // the failure happens on record 10, and all later sends are rejected
+    assert_eq!(first_error, Some(10));
+    assert_eq!(acknowledged, (0..10).collect::<Vec<_>>());
+    assert!(writer.is_poisoned());
+
+    let report = writer.finish();
+    assert!(!report.is_success());
+    assert!(matches!(
+        report.outcomes[0],
+        ShardOutcome::Finished { num_records: 4 }
+    ));
+    assert!(matches!(
+        report.outcomes[1],
+        ShardOutcome::Failed { num_records: 3, .. }
+    ));
+    assert!(matches!(
+        report.outcomes[2],
+        ShardOutcome::Finished { num_records: 3 }
+    ));
+    assert!(report.into_result().is_err());
+
+    // every acknowledged record is either persisted or reported in a failed shard
+    let persisted: usize = sinks.iter().map(|sink| sink.records().len()).sum();
+    assert_eq!(persisted, acknowledged.len());
+    Ok(())
+}
+
+#[test]
+fn routing_writer_flush_failure_test() -> tfrecord::Result<()> {
+    // buffered writers fail on flush
+    let sinks = [Sink::new(usize::MAX), Sink::new(40)];
+    let writers = sinks
+        .iter()
+        .map(|sink| BytesWriter::from_writer(BufWriter::new(sink.clone())))
+        .collect::<Result<_, _>>()?;
+    let mut writer = RoutingWriter::new(writers, |record: &Vec<u8>| record[0] as usize % 2)?;
+
+    for index in 0..6 {
+        writer.send(make_record(index))?;
+    }
+    let report = writer.finish();
+    assert!(report.outcomes[0].is_finished());
+    assert!(matches!(
+        report.outcomes[1],
+        ShardOutcome::Failed { num_records: 3, .. }
+    ));
+
+    // salvage the healthy writer
+    let sinks = [Sink::new(usize::MAX), Sink::new(usize::MAX)];
+    let writers = sinks
+        .iter()
+        .map(|sink| BytesWriter::from_writer(sink.clone()))
+        .collect::<Result<_, _>>()?;
+    let writer = RoutingWriter::new(writers, |_: &Vec<u8>| 0)?;
+    let parts = writer.into_parts();
+    assert_eq!(parts.len(), 2);
+    assert!(parts.iter().all(Result::is_ok));
+    Ok(())
+}
+
+/// A sink whose bytes become durable only when flushed, counting the flushes.
+#[derive(Debug)]
+struct CountingSink {
+    pending: Vec<u8>,
+    durable: Arc<Mutex<Vec<u8>>>,
+    num_flushes: Arc<AtomicUsize>,
+}
+
+impl CountingSink {
+    fn new(num_flushes: &Arc<AtomicUsize>) -> (Self, Arc<Mutex<Vec<u8>>>) {
+        let durable = Arc::new(Mutex::new(vec![]));
+        let sink = Self {
+            pending: vec![],
+            durable: durable.clone(),
+            num_flushes: num_flushes.clone(),
+        };
+        (sink, durable)
+    }
+}
+
+impl Write for CountingSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.durable.lock().unwrap().append(&mut self.pending);
+        self.num_flushes.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+const NUM_PARTITIONS: usize = 20;
+const NUM_RECORDS: usize = 2000;
+/// The framed size of a record by [make_record].
+const FRAMED_LEN: u64 = 32;
+
+/// Write the records round-robin, checking that no record is acknowledged as flushed
+/// before it is durable, and return the number of flushes.
+fn write_partitions<F>(attach: F) -> tfrecord::Result<usize>
+where
+    F: FnOnce(RoutingWriter<Vec<u8>, CountingSink>) -> RoutingWriter<Vec<u8>, CountingSink>,
+{
+    let num_flushes = Arc::new(AtomicUsize::new(0));
+    let (sinks, durables): (Vec<_>, Vec<_>) = (0..NUM_PARTITIONS)
+        .map(|_| CountingSink::new(&num_flushes))
+        .unzip();
+    let writers = sinks
+        .into_iter()
+        .map(BytesWriter::from_writer)
+        .collect::<Result<_, _>>()?;
+    let mut next = 0;
+    let writer = RoutingWriter::new(writers, move |_: &Vec<u8>| {
+        let index = next;
+        next = (next + 1) % NUM_PARTITIONS;
+        index
+    })?;
+    let mut writer = attach(writer);
+
+    let durable_records = |index: usize| durables[index].lock().unwrap().len() as u64 / FRAMED_LEN;
+    for index in 0..NUM_RECORDS {
+        writer.send(make_record(index))?;
+        for (partition, num_flushed) in writer.flushed_records().into_iter().enumerate() {
+            assert!(num_flushed <= durable_records(partition));
+        }
+    }
+    assert_eq!(writer.finish().into_result()?, vec![100; NUM_PARTITIONS]);
+    for partition in 0..NUM_PARTITIONS {
+        assert_eq!(durable_records(partition), 100);
+    }
+    Ok(num_flushes.load(Ordering::SeqCst))
+}
+
+#[test]
+fn flush_coordinator_coalesces_flushes_test() -> tfrecord::Result<()> {
+    // each writer flushes every 2 records on its own
+    let per_writer = write_partitions(|writer| {
+        writer.with_flush_policy(FlushPolicy {
+            max_unflushed_bytes: Some(2 * FRAMED_LEN),
+            max_interval: None,
+        })
+    })?;
+    // finishing flushes every writer once more
+    assert_eq!(per_writer, NUM_RECORDS / 2 + NUM_PARTITIONS);
+
+    // the same number of bytes across all writers triggers a sweep
+    let coordinated = write_partitions(|writer| {
+        let coordinator = FlushCoordinator::new(FlushCoordinatorConfig {
+            policy: FlushPolicy {
+                max_unflushed_bytes: Some(10 * NUM_PARTITIONS as u64 * FRAMED_LEN),
+                max_interval: None,
+            },
+            max_concurrent_flushes: 4,
+        })
+        .unwrap();
+        writer.with_flush_coordinator(coordinator)
+    })?;
+    assert_eq!(coordinated, NUM_RECORDS / 10);
+    assert!(coordinated * 5 <= per_writer);
+    Ok(())
+}
+
+#[test]
+fn flush_coordinator_flush_all_test() -> tfrecord::Result<()> {
+    assert!(FlushCoordinator::new(FlushCoordinatorConfig {
+        max_concurrent_flushes: 0,
+        ..Default::default()
+    })
+    .is_err());
+
+    let num_flushes = Arc::new(AtomicUsize::new(0));
+    let (sinks, durables): (Vec<_>, Vec<_>) =
+        (0..3).map(|_| CountingSink::new(&num_flushes)).unzip();
+    let writers = sinks
+        .into_iter()
+        .map(BytesWriter::from_writer)
+        .collect::<Result<_, _>>()?;
+    let coordinator = FlushCoordinator::new(Default::default())?;
+    let mut writer = RoutingWriter::new(writers, |record: &Vec<u8>| record[0] as usize % 3)?
+        .with_flush_coordinator(coordinator);
+
+    // the default policy never sweeps on its own
+    for index in [0, 1, 3, 4] {
+        writer.send(make_record(index))?;
+    }
+    assert_eq!(writer.flushed_records(), [0, 0, 0]);
+    assert!(durables
+        .iter()
+        .all(|durable| durable.lock().unwrap().is_empty()));
+
+    // only the writers with unflushed records are flushed
+    writer.flush_all()?;
+    assert_eq!(writer.flushed_records(), [2, 2, 0]);
+    assert_eq!(
+        writer.flush_stats(),
+        Some(FlushStats {
+            num_sweeps: 1,
+            num_flushes: 2
+        })
+    );
+    assert_eq!(num_flushes.load(Ordering::SeqCst), 2);
+
+    // finishing waits for the final sweep
+    writer.send(make_record(5))?;
+    assert_eq!(writer.finish().into_result()?, vec![2, 2, 1]);
+    assert_eq!(num_flushes.load(Ordering::SeqCst), 3);
+    assert_eq!(durables[2].lock().unwrap().len() as u64, FRAMED_LEN);
+    Ok(())
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use tfrecord::{indexer, samples, Example, ExampleIter};
+
+#[test]
+fn samples_are_deterministic() -> Result<()> {
+    assert_eq!(samples::example(42), samples::example(42));
+    assert_ne!(samples::example(1), samples::example(2));
+
+    let label = samples::example(42)
+        .into_hash_map()
+        .remove("label")
+        .unwrap();
+    assert_eq!(label.as_i64_list(), Some(&[2][..]));
+
+    let lhs = samples::tiny_dataset(2, 5)?;
+    let rhs = samples::tiny_dataset(2, 5)?;
+    assert_ne!(lhs.dir(), rhs.dir());
+    for (lhs, rhs) in lhs.paths().iter().zip(rhs.paths()) {
+        assert_eq!(std::fs::read(lhs)?, std::fs::read(rhs)?);
+    }
+    Ok(())
+}
+
+#[test]
+fn tiny_dataset_contents() -> Result<()> {
+    let dataset = samples::tiny_dataset(3, 2)?;
+    assert_eq!(dataset.num_records(), 6);
+    assert!(dataset
+        .paths()
+        .iter()
+        .all(|path| path.starts_with(dataset.dir())));
+
+    let examples: Vec<Example> = dataset
+        .paths()
+        .iter()
+        .map(|path| ExampleIter::open(path, Default::default()))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+        .collect::<Result<_, _>>()?;
+    let expect: Vec<_> = (0..6).map(samples::example).collect();
+    assert_eq!(examples, expect);
+
+    let num_indexes = indexer::load_paths(dataset.paths(), Default::default()).count();
+    assert_eq!(num_indexes, 6);
+    Ok(())
+}
+
+#[test]
+fn tiny_dataset_cleans_up_on_drop() -> Result<()> {
+    let dataset = samples::tiny_dataset(2, 1)?;
+    let dir = dataset.dir().to_path_buf();
+    assert!(dir.is_dir());
+    drop(dataset);
+    assert!(!dir.exists());
+    Ok(())
+}
@@ -0,0 +1,158 @@
+#![cfg(feature = "proto-summary")]
+
+use tfrecord::{
+    protobuf::{event::What, summary::value::Value},
+    DedupPolicy, DedupStats, EventIter, EventWriter, EventWriterConfig, ScalarDedup,
+};
+
+fn dedup(max_gap: usize) -> ScalarDedup {
+    ScalarDedup {
+        policy: DedupPolicy::SkipIfUnchanged { tolerance: 1e-6 },
+        max_gap,
+    }
+}
+
+fn config() -> EventWriterConfig {
+    EventWriterConfig::default().with_stamp_producer(false)
+}
+
+/// The (tag, step, value) of the scalar events in the bytes.
+fn read_scalars(bytes: Vec<u8>) -> anyhow::Result<Vec<(String, i64, f32)>> {
+    let mut scalars = vec![];
+    for event in EventIter::from_bytes(bytes, Default::default()) {
+        let event = event?;
+        if let Some(What::Summary(summary)) = &event.what {
+            for value in &summary.value {
+                if let Some(Value::SimpleValue(scalar)) = value.value {
+                    scalars.push((value.tag.clone(), event.step, scalar));
+                }
+            }
+        }
+    }
+    Ok(scalars)
+}
+
+#[test]
+fn plateau_then_step_test() -> anyhow::Result<()> {
+    let (writer, buffer) = EventWriter::in_memory(config())?;
+    let mut writer = writer.with_scalar_dedup(dedup(3));
+
+    let values = [1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 2.0, 2.0, 2.0];
+    for (step, value) in values.into_iter().enumerate() {
+        writer.write_scalar("loss", step as i64, value)?;
+    }
+    let stats = writer.dedup_stats().unwrap();
+    assert_eq!(
+        stats,
+        DedupStats {
+            num_written: 4,
+            num_suppressed: 7,
+            num_anchors: 1,
+        }
+    );
+    writer.finish()?;
+
+    let loss = |step, value| ("loss".to_string(), step, value);
+    assert_eq!(
+        read_scalars(buffer.to_vec())?,
+        vec![
+            // the first point
+            loss(0, 1.0),
+            // the anchor after three suppressed points
+            loss(4, 1.0),
+            // the end of the plateau right before the step
+            loss(7, 1.0),
+            loss(8, 2.0),
+            // the final value on finish
+            loss(10, 2.0),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn tags_and_tolerance_test() -> anyhow::Result<()> {
+    let (writer, buffer) = EventWriter::in_memory(config())?;
+    let mut writer = writer.with_scalar_dedup(ScalarDedup {
+        policy: DedupPolicy::SkipIfUnchanged { tolerance: 0.1 },
+        max_gap: 100,
+    });
+    assert_eq!(writer.dedup_stats(), Some(DedupStats::default()));
+
+    // the noise within the tolerance of the last written value is suppressed
+    for (step, value) in [0.0, 0.05, 0.1, 0.15, 0.3].into_iter().enumerate() {
+        writer.write_scalar("noisy", step as i64, value)?;
+        writer.write_scalar("flat", step as i64, 5.0)?;
+    }
+    writer.flush()?;
+    // flushing again writes nothing more
+    writer.flush()?;
+
+    assert_eq!(
+        writer.dedup_stats_for("noisy"),
+        Some(DedupStats {
+            num_written: 4,
+            num_suppressed: 1,
+            num_anchors: 0,
+        })
+    );
+    assert_eq!(
+        writer.dedup_stats_for("flat"),
+        Some(DedupStats {
+            num_written: 2,
+            num_suppressed: 3,
+            num_anchors: 0,
+        })
+    );
+    assert_eq!(writer.dedup_stats_for("missing"), None);
+    drop(writer);
+
+    let scalars = read_scalars(buffer.to_vec())?;
+    let steps = |tag: &str| -> Vec<i64> {
+        scalars
+            .iter()
+            .filter(|(t, ..)| t == tag)
+            .map(|(_, step, _)| *step)
+            .collect()
+    };
+    // the point before the change ends the plateau
+    assert_eq!(steps("noisy"), vec![0, 2, 3, 4]);
+    assert_eq!(steps("flat"), vec![0, 4]);
+    Ok(())
+}
+
+#[test]
+fn without_dedup_test() -> anyhow::Result<()> {
+    let (mut writer, buffer) = EventWriter::in_memory(config())?;
+    for step in 0..4 {
+        writer.write_scalar("loss", step, 1.0)?;
+    }
+    assert_eq!(writer.dedup_stats(), None);
+    writer.finish()?;
+    assert_eq!(read_scalars(buffer.to_vec())?.len(), 4);
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_plateau_test() -> anyhow::Result<()> {
+    use tfrecord::EventAsyncWriter;
+
+    async_std::task::block_on(async {
+        let buffer = tfrecord::memory::MemoryBuffer::new();
+        let writer =
+            EventAsyncWriter::from_writer(futures::io::AllowStdIo::new(buffer.clone()), config())?;
+        let mut writer = writer.with_scalar_dedup(dedup(3));
+        for step in 0..6 {
+            writer.write_scalar("loss", step, 1.0).await?;
+        }
+        writer.finish().await?;
+
+        let steps: Vec<_> = read_scalars(buffer.to_vec())?
+            .into_iter()
+            .map(|(_, step, _)| step)
+            .collect();
+        assert_eq!(steps, vec![0, 4, 5]);
+        Ok(())
+    })
+}
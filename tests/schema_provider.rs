@@ -0,0 +1,316 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::{
+    fs,
+    sync::{Arc, Mutex},
+};
+use tfrecord::{
+    memory::MemoryBuffer,
+    samples,
+    schema::{
+        FeatureSpec, SchemaProvider, SchemaSource, SchemaUsage, StaticSchemaProvider, ValueCount,
+        ValueType,
+    },
+    Error, Example, Feature, RecordWriterConfig, ValidatingWriter, ValidatingWriterConfig,
+};
+
+fn specs() -> Vec<FeatureSpec> {
+    vec![
+        FeatureSpec::new("id", ValueType::I64, ValueCount::Fixed(1)),
+        FeatureSpec::new("score", ValueType::F32, ValueCount::Fixed(1)),
+        FeatureSpec {
+            required: false,
+            ..FeatureSpec::new(
+                "tags",
+                ValueType::Bytes,
+                ValueCount::Var {
+                    min: 1,
+                    max: Some(2),
+                },
+            )
+        },
+    ]
+}
+
+const SPECS_JSON: &str = r#"{
+    "features": [
+        {"key": "id", "value_type": "I64", "count": {"Fixed": 1}, "required": true},
+        {"key": "score", "value_type": "F32", "count": {"Fixed": 1}, "required": true},
+        {"key": "tags", "value_type": "Bytes", "count": {"Var": {"min": 1, "max": 2}}, "required": false}
+    ]
+}"#;
+
+const SPECS_TOML: &str = r#"
+[[features]]
+key = "id"
+value_type = "I64"
+count = { Fixed = 1 }
+required = true
+
+[[features]]
+key = "score"
+value_type = "F32"
+count = { Fixed = 1 }
+required = true
+
+[[features]]
+key = "tags"
+value_type = "Bytes"
+count = { Var = { min = 1, max = 2 } }
+required = false
+"#;
+
+/// Conforming and nonconforming examples.
+fn examples() -> Vec<Example> {
+    let with = |index: usize, key: &str, feature: Option<Feature>| {
+        let mut example = samples::example(index);
+        let features = &mut example.features.as_mut().unwrap().feature;
+        match feature {
+            Some(feature) => features.insert(key.to_string(), feature),
+            None => features.remove(key),
+        };
+        example
+    };
+    vec![
+        samples::example(0),
+        with(1, "id", None),
+        with(
+            2,
+            "tags",
+            Some(Feature::from_bytes_list(vec![b"a".to_vec()])),
+        ),
+        with(3, "tags", Some(Feature::from_bytes_list(vec![]))),
+        with(4, "score", Some(Feature::from_i64_list(vec![4]))),
+        with(5, "id", Some(Feature::from_i64_list(vec![5, 5]))),
+        samples::example(6),
+    ]
+}
+
+/// The outcome of each example and the written bytes in canonical encoding.
+fn write_all(schema: SchemaSource) -> Result<(Vec<Option<String>>, SchemaUsage, Vec<u8>)> {
+    let buffer = MemoryBuffer::new();
+    let config = ValidatingWriterConfig {
+        schema: Some(schema),
+        writer: RecordWriterConfig {
+            canonical_encoding: true,
+            ..Default::default()
+        },
+    };
+    let mut writer = ValidatingWriter::from_writer(buffer.clone(), config)?;
+    assert_eq!(writer.specs(), specs());
+    let outcomes = examples()
+        .into_iter()
+        .map(|example| writer.send(example).err().map(|err| err.to_string()))
+        .collect();
+    let usage = writer.finish()?;
+    Ok((outcomes, usage, buffer.to_vec()))
+}
+
+/// A provider failing to fetch, and recording the reported usage.
+#[derive(Default)]
+struct MockProvider {
+    fail_fetch: bool,
+    fail_report: bool,
+    reports: Mutex<Vec<(String, SchemaUsage)>>,
+}
+
+impl SchemaProvider for MockProvider {
+    fn fetch(&self, schema_id: &str) -> tfrecord::Result<Vec<FeatureSpec>> {
+        if self.fail_fetch {
+            let error = std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "registry down");
+            return Err(error.into());
+        }
+        assert_eq!(schema_id, "clicks");
+        Ok(specs())
+    }
+
+    fn report_usage(&self, schema_id: &str, usage: &SchemaUsage) -> tfrecord::Result<()> {
+        self.reports
+            .lock()
+            .unwrap()
+            .push((schema_id.to_string(), *usage));
+        if self.fail_report {
+            return Err(Error::UnexpectedEof);
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn sources_validate_alike_test() -> Result<()> {
+    let inline = write_all(SchemaSource::Inline(specs()))?;
+    let (outcomes, usage, bytes) = &inline;
+    let accepted: Vec<bool> = outcomes.iter().map(Option::is_none).collect();
+    assert_eq!(accepted, vec![true, false, true, false, false, false, true]);
+    assert_eq!(
+        *usage,
+        SchemaUsage {
+            num_accepted: 3,
+            num_rejected: 4,
+        }
+    );
+    let written: Vec<Example> =
+        tfrecord::ExampleIter::from_bytes(bytes.clone(), Default::default())
+            .collect::<Result<_, _>>()?;
+    assert_eq!(written.len(), 3);
+
+    let provider = Arc::new(StaticSchemaProvider::new().with_schema("clicks", specs()));
+    let source = SchemaSource::Provider(provider.clone(), "clicks".into());
+    assert_eq!(write_all(source.clone())?, inline);
+    assert_eq!(write_all(source)?, inline);
+    // the usage of both writers is reported
+    assert_eq!(
+        provider.usage("clicks"),
+        Some(SchemaUsage {
+            num_accepted: 6,
+            num_rejected: 8,
+        })
+    );
+    assert_eq!(provider.usage("views"), None);
+
+    let mock = Arc::new(MockProvider::default());
+    assert_eq!(
+        write_all(SchemaSource::Provider(mock.clone(), "clicks".into()))?,
+        inline
+    );
+    assert_eq!(
+        *mock.reports.lock().unwrap(),
+        vec![("clicks".into(), *usage)]
+    );
+
+    // without a schema, every example is written
+    let config = ValidatingWriterConfig::default();
+    let mut writer = ValidatingWriter::from_writer(MemoryBuffer::new(), config)?;
+    for example in examples() {
+        writer.send(example)?;
+    }
+    assert_eq!(writer.finish()?.num_accepted, 7);
+    Ok(())
+}
+
+#[cfg(feature = "schema-file")]
+#[test]
+fn file_sources_test() -> Result<()> {
+    let dir = make_temp_dir("schema_provider_files")?;
+    fs::write(dir.join("clicks.json"), SPECS_JSON)?;
+    fs::write(dir.join("views.toml"), SPECS_TOML)?;
+
+    let inline = write_all(SchemaSource::Inline(specs()))?;
+    assert_eq!(
+        write_all(SchemaSource::File(dir.join("clicks.json")))?,
+        inline
+    );
+    assert_eq!(
+        write_all(SchemaSource::File(dir.join("views.toml")))?,
+        inline
+    );
+
+    let provider = Arc::new(tfrecord::schema::FileSchemaProvider::new(&dir));
+    for schema_id in ["clicks", "views"] {
+        let source = SchemaSource::Provider(provider.clone(), schema_id.into());
+        assert_eq!(write_all(source)?, inline);
+    }
+
+    // missing and malformed files
+    let err = SchemaSource::Provider(provider.clone(), "absent".into())
+        .resolve()
+        .unwrap_err();
+    assert!(matches!(&err, Error::SchemaUnavailable { schema_id, .. } if schema_id == "absent"));
+    assert_eq!(err.io_error_kind(), std::io::ErrorKind::NotFound);
+    assert!(provider.fetch("../clicks").is_err());
+
+    fs::write(
+        dir.join("broken.json"),
+        "{\"features\": [{\"key\": \"id\"}]}",
+    )?;
+    let err = SchemaSource::File(dir.join("broken.json"))
+        .resolve()
+        .unwrap_err();
+    assert!(err.to_string().contains("broken.json"), "{}", err);
+    fs::write(dir.join("clicks.yaml"), "")?;
+    assert!(SchemaSource::File(dir.join("clicks.yaml"))
+        .resolve()
+        .is_err());
+    let err = SchemaSource::File(dir.join("absent.json"))
+        .resolve()
+        .unwrap_err();
+    assert_eq!(err.io_error_kind(), std::io::ErrorKind::NotFound);
+    Ok(())
+}
+
+#[cfg(not(feature = "schema-file"))]
+#[test]
+fn file_sources_unsupported_test() -> Result<()> {
+    let dir = make_temp_dir("schema_provider_unsupported")?;
+    fs::write(dir.join("clicks.json"), SPECS_JSON)?;
+    let _ = SPECS_TOML;
+    let err = SchemaSource::File(dir.join("clicks.json"))
+        .resolve()
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            Error::Unsupported {
+                feature: Some("schema-file"),
+                ..
+            }
+        ),
+        "{}",
+        err
+    );
+    Ok(())
+}
+
+#[test]
+fn provider_failures_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(0, 0)?;
+    let path = dataset.dir().join("rejected.tfrecord");
+
+    // a failed fetch creates no file
+    let mock = Arc::new(MockProvider {
+        fail_fetch: true,
+        ..Default::default()
+    });
+    let config = ValidatingWriterConfig {
+        schema: Some(SchemaSource::Provider(mock, "clicks".into())),
+        ..Default::default()
+    };
+    let err = match ValidatingWriter::create(&path, config) {
+        Err(err) => err,
+        Ok(_) => panic!("the fetch failure is not reported"),
+    };
+    let message = err.to_string();
+    assert!(
+        message.starts_with("[TFR0026] schema 'clicks' is unavailable"),
+        "{}",
+        message
+    );
+    assert!(message.contains("registry down"), "{}", message);
+    assert_eq!(err.io_error_kind(), std::io::ErrorKind::ConnectionRefused);
+    assert!(!path.exists());
+
+    let err = SchemaSource::Provider(Arc::new(StaticSchemaProvider::new()), "clicks".into())
+        .resolve()
+        .unwrap_err();
+    assert!(matches!(err, Error::SchemaUnavailable { .. }), "{}", err);
+
+    // a failed report fails the finish after the records are flushed
+    let mock = Arc::new(MockProvider {
+        fail_report: true,
+        ..Default::default()
+    });
+    let config = ValidatingWriterConfig {
+        schema: Some(SchemaSource::Provider(mock.clone(), "clicks".into())),
+        ..Default::default()
+    };
+    let buffer = MemoryBuffer::new();
+    let mut writer = ValidatingWriter::from_writer(buffer.clone(), config)?;
+    writer.send(samples::example(0))?;
+    assert!(writer.finish().is_err());
+    assert_eq!(mock.reports.lock().unwrap().len(), 1);
+    assert!(!buffer.to_vec().is_empty());
+    Ok(())
+}
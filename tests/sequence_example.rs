@@ -0,0 +1,288 @@
+use tfrecord::{
+    protobuf::{FeatureList, FeatureLists, Features, SequenceExample},
+    Error, Example, Feature, KeyCollisionPolicy, SequenceAccessOptions, SequenceExampleBuilder,
+    SequenceFeature,
+};
+
+fn make_sequence_example(lengths: &[(&str, usize)]) -> SequenceExample {
+    SequenceExample {
+        context: Some(Features {
+            feature: [
+                (
+                    "id".to_string(),
+                    Feature::from_bytes_list(vec![b"seq".to_vec()]),
+                ),
+                ("label".to_string(), Feature::from_i64_list(vec![3])),
+            ]
+            .into_iter()
+            .collect(),
+        }),
+        feature_lists: Some(FeatureLists {
+            feature_list: lengths
+                .iter()
+                .map(|&(key, len)| {
+                    let feature = (0..len)
+                        .map(|index| Feature::from_f32_list(vec![index as f32, key.len() as f32]))
+                        .collect();
+                    (key.to_string(), FeatureList { feature })
+                })
+                .collect(),
+        }),
+    }
+}
+
+#[test]
+fn explode_implode_round_trip_test() -> Result<(), Error> {
+    let sequence_example = make_sequence_example(&[("frame", 5), ("mask", 5)]);
+    let examples = sequence_example.explode(KeyCollisionPolicy::Error)?;
+    assert_eq!(examples.len(), 5);
+
+    for (index, example) in examples.iter().enumerate() {
+        let feature = &example.features.as_ref().unwrap().feature;
+        assert_eq!(feature.len(), 4);
+        assert_eq!(feature["label"], Feature::from_i64_list(vec![3]));
+        assert_eq!(
+            feature["frame"],
+            Feature::from_f32_list(vec![index as f32, 5.0])
+        );
+    }
+
+    let imploded = Example::implode(examples, &["id", "label"])?;
+    assert_eq!(imploded, sequence_example);
+    Ok(())
+}
+
+#[test]
+fn explode_collision_test() -> Result<(), Error> {
+    let mut sequence_example = make_sequence_example(&[("label", 2)]);
+
+    assert!(sequence_example.explode(KeyCollisionPolicy::Error).is_err());
+
+    let examples = sequence_example.explode(KeyCollisionPolicy::PreferContext)?;
+    assert!(examples.iter().all(
+        |example| example.features.as_ref().unwrap().feature["label"]
+            == Feature::from_i64_list(vec![3])
+    ));
+
+    let examples = sequence_example.explode(KeyCollisionPolicy::PreferFrame)?;
+    assert_eq!(
+        examples[1].features.as_ref().unwrap().feature["label"],
+        Feature::from_f32_list(vec![1.0, 5.0])
+    );
+
+    sequence_example.context = None;
+    assert_eq!(
+        sequence_example.explode(KeyCollisionPolicy::Error)?.len(),
+        2
+    );
+    Ok(())
+}
+
+#[test]
+fn explode_without_feature_lists_test() -> Result<(), Error> {
+    // the context cannot be dropped silently
+    let mut sequence_example = make_sequence_example(&[]);
+    let error = sequence_example
+        .explode(KeyCollisionPolicy::Error)
+        .unwrap_err();
+    assert!(matches!(error, Error::ConversionError { .. }), "{}", error);
+    sequence_example.feature_lists = None;
+    assert!(sequence_example.explode(KeyCollisionPolicy::Error).is_err());
+
+    // nothing to explode
+    sequence_example.context = None;
+    assert_eq!(sequence_example.explode(KeyCollisionPolicy::Error)?, vec![]);
+    assert_eq!(
+        make_sequence_example(&[("frame", 0)]).explode(KeyCollisionPolicy::Error)?,
+        vec![]
+    );
+    Ok(())
+}
+
+#[test]
+fn ragged_error_test() {
+    let sequence_example = make_sequence_example(&[("frame", 3), ("mask", 4)]);
+    let error = sequence_example
+        .explode(KeyCollisionPolicy::Error)
+        .unwrap_err();
+    assert!(error.to_string().contains("frame=3, mask=4"), "{}", error);
+
+    let examples = vec![
+        Example::from_iter([("a".to_string(), Feature::from_i64_list(vec![0]))]),
+        Example::from_iter([
+            ("a".to_string(), Feature::from_i64_list(vec![1])),
+            ("b".to_string(), Feature::from_i64_list(vec![1])),
+        ]),
+    ];
+    let error = Example::implode(examples, &[] as &[&str]).unwrap_err();
+    assert!(error.to_string().contains("a=2, b=1"), "{}", error);
+}
+
+#[test]
+fn implode_context_mismatch_test() {
+    let examples = vec![
+        Example::from_iter([("id".to_string(), Feature::from_i64_list(vec![0]))]),
+        Example::from_iter([("id".to_string(), Feature::from_i64_list(vec![1]))]),
+    ];
+    assert!(Example::implode(examples.clone(), &["id"]).is_err());
+    assert!(Example::implode(examples, &["missing"]).is_err());
+}
+
+fn access(on_collision: KeyCollisionPolicy) -> SequenceAccessOptions {
+    SequenceAccessOptions { on_collision }
+}
+
+#[test]
+fn unified_access_collision_test() -> Result<(), Error> {
+    let sequence_example = make_sequence_example(&[("label", 2), ("frame", 2)]);
+    let context_label = sequence_example.context_feature("label").unwrap();
+    let list_label = sequence_example.feature_list("label").unwrap();
+    assert_eq!(sequence_example.colliding_keys(), ["label"]);
+
+    // error names the key in both lookups and listings
+    let options = access(KeyCollisionPolicy::Error);
+    let error = sequence_example.get("label", options).unwrap_err();
+    assert!(error.to_string().contains("'label'"), "{}", error);
+    assert!(sequence_example.features(options).is_err());
+    assert!(matches!(
+        sequence_example.get("id", options)?,
+        Some(SequenceFeature::Context(_))
+    ));
+
+    let options = access(KeyCollisionPolicy::PreferContext);
+    assert_eq!(
+        sequence_example.get("label", options)?,
+        Some(SequenceFeature::Context(context_label))
+    );
+    let features = sequence_example.features(options)?;
+    let keys: Vec<_> = features.iter().map(|(key, _)| key.as_ref()).collect();
+    assert_eq!(keys, ["frame", "id", "label"]);
+    assert_eq!(features[2].1, SequenceFeature::Context(context_label));
+
+    let options = access(KeyCollisionPolicy::PreferFrame);
+    assert_eq!(
+        sequence_example.get("label", options)?,
+        Some(SequenceFeature::List(list_label))
+    );
+    let features = sequence_example.features(options)?;
+    assert_eq!(features[2].1, SequenceFeature::List(list_label));
+
+    // namespaced exposes both under distinct keys
+    let options = access(KeyCollisionPolicy::Namespaced);
+    assert_eq!(
+        sequence_example.get("context/label", options)?,
+        Some(SequenceFeature::Context(context_label))
+    );
+    assert_eq!(
+        sequence_example.get("label", options)?,
+        Some(SequenceFeature::List(list_label))
+    );
+    assert_eq!(sequence_example.get("id", options)?, None);
+    let features = sequence_example.features(options)?;
+    let keys: Vec<_> = features.iter().map(|(key, _)| key.as_ref()).collect();
+    assert_eq!(keys, ["context/id", "context/label", "frame", "label"]);
+    for (key, feature) in &features {
+        assert_eq!(sequence_example.get(key, options)?, Some(*feature));
+    }
+
+    let examples = sequence_example.explode(KeyCollisionPolicy::Namespaced)?;
+    let feature = &examples[0].features.as_ref().unwrap().feature;
+    assert_eq!(feature["context/label"], *context_label);
+    assert_eq!(feature["label"], list_label.feature[0]);
+    Ok(())
+}
+
+#[test]
+fn builder_collision_test() -> Result<(), Error> {
+    let builder = SequenceExampleBuilder::new()
+        .context("label", Feature::from_i64_list(vec![3]))
+        .push_frame("label", Feature::from_i64_list(vec![0]))
+        .push_frame("label", Feature::from_i64_list(vec![1]))
+        .feature_list("mask", vec![Feature::from_i64_list(vec![1]); 2]);
+
+    let sequence_example = builder.clone().build()?;
+    assert_eq!(sequence_example.colliding_keys(), ["label"]);
+    assert_eq!(
+        sequence_example
+            .feature_list("label")
+            .unwrap()
+            .feature
+            .len(),
+        2
+    );
+
+    let error = builder.reject_collisions().build().unwrap_err();
+    assert!(error.to_string().contains("'label'"), "{}", error);
+
+    let sequence_example = SequenceExampleBuilder::new()
+        .reject_collisions()
+        .context("id", Feature::from_i64_list(vec![3]))
+        .push_frame("label", Feature::from_i64_list(vec![0]))
+        .build()?;
+    assert!(sequence_example.colliding_keys().is_empty());
+    Ok(())
+}
+
+#[test]
+fn builder_typed_frames_test() -> Result<(), Error> {
+    let sequence_example = SequenceExampleBuilder::new()
+        .context_f32s("age", &[19.0])
+        .context_i64s("id", &[7])
+        .context_bytes("locale", vec![b"pt_BR".to_vec()])
+        .push_frame_f32s("movie_ratings", &[4.5])
+        .push_frame_bytes("movie_names", vec![b"Fight Club".to_vec()])
+        .push_frame_f32s("movie_ratings", &[5.0])
+        .push_frame_i64s("movie_ids", &[3])
+        .build()?;
+
+    assert_eq!(
+        sequence_example.context_feature("age"),
+        Some(&Feature::from_f32_list(vec![19.0]))
+    );
+    assert_eq!(
+        sequence_example.context_feature("id"),
+        Some(&Feature::from_i64_list(vec![7]))
+    );
+    let ratings = sequence_example.feature_list("movie_ratings").unwrap();
+    assert_eq!(
+        ratings.feature,
+        vec![
+            Feature::from_f32_list(vec![4.5]),
+            Feature::from_f32_list(vec![5.0])
+        ]
+    );
+    assert_eq!(
+        sequence_example
+            .feature_list("movie_names")
+            .unwrap()
+            .feature,
+        vec![Feature::from_bytes_list(vec![b"Fight Club".to_vec()])]
+    );
+
+    // frames of different kinds in one feature list
+    let error = SequenceExampleBuilder::new()
+        .push_frame_i64s("movie_ids", &[3])
+        .push_frame_f32s("movie_ratings", &[4.5])
+        .push_frame_f32s("movie_ratings", &[4.0])
+        .push_frame_i64s("movie_ratings", &[5])
+        .build()
+        .unwrap_err();
+    assert!(matches!(error, Error::ConversionError { .. }), "{}", error);
+    let message = error.to_string();
+    assert!(message.contains("'movie_ratings'"), "{}", message);
+    assert!(
+        message.contains("Int64List feature at frame 2"),
+        "{}",
+        message
+    );
+
+    let error = SequenceExampleBuilder::new()
+        .feature_list(
+            "mask",
+            vec![Feature::from_i64_list(vec![1]), Feature::empty()],
+        )
+        .build()
+        .unwrap_err();
+    assert!(error.to_string().contains("'mask'"), "{}", error);
+    Ok(())
+}
@@ -0,0 +1,209 @@
+#![cfg(feature = "with-serde")]
+
+mod common;
+
+use common::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tfrecord::{serde_example, Example, ExampleIter, ExampleWriter, Feature};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum Split {
+    Train,
+    Test,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Id(u32);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Sample {
+    flag: bool,
+    small: i8,
+    medium: i32,
+    large: i64,
+    byte: u8,
+    unsigned: u64,
+    weight: f32,
+    score: f64,
+    letter: char,
+    name: String,
+    split: Split,
+    id: Id,
+    payload: Vec<u8>,
+    chunks: Vec<Vec<u8>>,
+    tags: Vec<String>,
+    embedding: Vec<f32>,
+    positions: [i64; 3],
+    masks: Vec<bool>,
+    pair: (u16, u16),
+    empty: Vec<f32>,
+    note: Option<String>,
+    maybe_label: Option<i64>,
+    #[serde(skip)]
+    cached: Option<usize>,
+}
+
+fn sample(index: usize) -> Sample {
+    Sample {
+        flag: index.is_multiple_of(2),
+        small: -(index as i8),
+        medium: index as i32 * 1000,
+        large: i64::MIN + index as i64,
+        byte: 255 - index as u8,
+        unsigned: index as u64,
+        weight: index as f32 * 0.5,
+        score: 0.25,
+        letter: 'λ',
+        name: format!("sample-{}", index),
+        split: if index.is_multiple_of(3) {
+            Split::Test
+        } else {
+            Split::Train
+        },
+        id: Id(index as u32),
+        payload: vec![0, 1, index as u8],
+        chunks: vec![vec![index as u8; 2], vec![], vec![7]],
+        tags: vec!["a".into(), "b".into()],
+        embedding: vec![1.0, -1.0, index as f32],
+        positions: [1, 2, index as i64],
+        masks: vec![true, false],
+        pair: (3, 4),
+        empty: vec![],
+        note: (!index.is_multiple_of(2)).then(|| "odd".into()),
+        maybe_label: Some(index as i64),
+        cached: None,
+    }
+}
+
+#[test]
+fn round_trip_test() -> Result<()> {
+    let value = sample(5);
+    let example = serde_example::to_example(&value)?;
+
+    // the feature layout
+    assert_eq!(example.get_i64s("flag")?, &[0]);
+    assert_eq!(example.get_i64s("byte")?, &[250]);
+    assert_eq!(example.get_f32s("score")?, &[0.25]);
+    assert_eq!(example.get_u8s("payload")?, &[0, 1, 5]);
+    assert_eq!(example.get_u8s("letter")?, "λ".as_bytes());
+    assert_eq!(example.get_u8s("split")?, b"Train");
+    assert_eq!(example.get_i64s("id")?, &[5]);
+    assert_eq!(example.get_i64s("positions")?, &[1, 2, 5]);
+    assert_eq!(example.get_i64s("masks")?, &[1, 0]);
+    let features = example.clone().into_hash_map();
+    assert_eq!(
+        features["chunks"].as_bytes_list(),
+        Some(&[vec![5, 5], vec![], vec![7]][..])
+    );
+    assert_eq!(features["empty"], Feature::empty());
+    assert!(!features.contains_key("cached"));
+
+    let converted: Sample = serde_example::from_example(&example)?;
+    assert_eq!(converted, value);
+
+    // missing optional features are None
+    let mut features = example.into_hash_map();
+    features.remove("note");
+    features.remove("maybe_label");
+    features.insert("extra".into(), Feature::from_i64_list(vec![1]));
+    let converted: Sample = serde_example::from_example(&Example::from_iter(features))?;
+    assert_eq!(converted.note, None);
+    assert_eq!(converted.maybe_label, None);
+    Ok(())
+}
+
+#[test]
+fn writer_reader_test() -> Result<()> {
+    let values: Vec<_> = (0..6).map(sample).collect();
+    let (mut writer, buffer) = ExampleWriter::in_memory()?;
+    for value in &values {
+        writer.send_serde(value)?;
+    }
+    writer.flush()?;
+
+    let read: Vec<Sample> = ExampleIter::from_bytes(buffer.to_vec(), Default::default())
+        .into_serde()
+        .collect::<Result<_, _>>()?;
+    assert_eq!(read, values);
+    Ok(())
+}
+
+#[test]
+fn unsupported_test() -> Result<()> {
+    #[derive(Serialize, Deserialize)]
+    struct Inner {
+        value: i64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Nested {
+        inner: Inner,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct WithMap {
+        map: HashMap<String, i64>,
+    }
+
+    #[derive(Serialize)]
+    struct Overflow {
+        value: u64,
+    }
+
+    #[derive(Serialize)]
+    struct Mixed {
+        values: (i64, f32),
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Scalar {
+        #[allow(dead_code)]
+        value: i64,
+    }
+
+    let nested = Nested {
+        inner: Inner { value: 1 },
+    };
+    assert!(serde_example::to_example(&nested).is_err());
+    let with_map = WithMap {
+        map: HashMap::from([("a".into(), 1)]),
+    };
+    assert!(serde_example::to_example(&with_map).is_err());
+    assert!(serde_example::to_example(&Overflow { value: u64::MAX }).is_err());
+    assert!(serde_example::to_example(&Mixed { values: (1, 2.0) }).is_err());
+    assert!(serde_example::to_example(&5i64).is_err());
+
+    // a scalar field takes a feature with exactly one value of a compatible kind
+    let example = Example::from_iter([("value".to_string(), Feature::from_i64_list(vec![1, 2]))]);
+    assert!(serde_example::from_example::<Scalar>(&example).is_err());
+    let example = Example::from_iter([("value".to_string(), Feature::from_u8s(b"1"))]);
+    assert!(serde_example::from_example::<Scalar>(&example).is_err());
+    let example = Example::from_iter([("inner".to_string(), Feature::from_i64_list(vec![1]))]);
+    assert!(serde_example::from_example::<Nested>(&example).is_err());
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn async_serde_test() -> Result<()> {
+    use futures::stream::TryStreamExt as _;
+    use tfrecord::{ExampleAsyncWriter, ExampleStream};
+
+    let mut bytes = vec![];
+    {
+        let mut writer = ExampleAsyncWriter::from_writer(&mut bytes)?;
+        for index in 0..3 {
+            writer.send_serde(&sample(index)).await?;
+        }
+        writer.flush().await?;
+    }
+
+    let read: Vec<Sample> =
+        ExampleStream::from_reader(futures::io::Cursor::new(bytes), Default::default())
+            .into_serde()
+            .try_collect()
+            .await?;
+    assert_eq!(read, (0..3).map(sample).collect::<Vec<_>>());
+    Ok(())
+}
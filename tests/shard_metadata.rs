@@ -0,0 +1,116 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::fs;
+use tfrecord::{
+    indexer,
+    metadata::{self, DatasetMetadata, MetadataWarning, ShardMetadata, MAX_METADATA_LEN},
+    samples, ExampleIter,
+};
+
+#[test]
+fn foreign_sidecars_never_break_reading_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(6, 5)?;
+    let paths = dataset.paths();
+    let sidecars: Vec<_> = paths.iter().map(metadata::metadata_path).collect();
+
+    // a newer minor version with unknown keys
+    fs::write(
+        &sidecars[0],
+        "tfrecord-shard-metadata\nversion=1.4\nsorted_features=true\ncodec=none\n",
+    )?;
+    // a future major version
+    fs::write(
+        &sidecars[1],
+        "tfrecord-shard-metadata\nversion=2\nsorted_features=true\n",
+    )?;
+    // an oversized sidecar
+    let mut text = "tfrecord-shard-metadata\nversion=1\nsorted_features=true\n".to_string();
+    text.push_str(&format!(
+        "padding={}\n",
+        "x".repeat(MAX_METADATA_LEN as usize)
+    ));
+    fs::write(&sidecars[2], text)?;
+    // corrupted sidecars
+    fs::write(&sidecars[3], [0xff, 0xfe, 0x00, 0x80])?;
+    fs::write(
+        &sidecars[4],
+        "tfrecord-shard-metadata\nversion=1\nsorted_features=maybe\n",
+    )?;
+    // the last file has no sidecar
+
+    let dataset_metadata = DatasetMetadata::load(paths);
+    assert_eq!(dataset_metadata.len(), 6);
+    assert_eq!(dataset_metadata.num_warnings(), 4);
+
+    let first = dataset_metadata.shard_metadata(0).unwrap()?;
+    assert_eq!(first.version, 1);
+    assert_eq!(first.extra["codec"], "none");
+    assert!(dataset_metadata.declares_sorted_keys(0));
+
+    assert_eq!(
+        dataset_metadata.shard_metadata(1),
+        Some(Err(MetadataWarning::UnsupportedVersion { version: 2 }))
+    );
+    assert!(matches!(
+        dataset_metadata.shard_metadata(2),
+        Some(Err(MetadataWarning::Oversized {
+            limit: MAX_METADATA_LEN,
+            ..
+        }))
+    ));
+    for index in [3, 4] {
+        assert!(matches!(
+            dataset_metadata.shard_metadata(index),
+            Some(Err(MetadataWarning::Malformed { .. }))
+        ));
+    }
+    assert_eq!(dataset_metadata.shard_metadata(5), None);
+    assert_eq!(dataset_metadata.shard_metadata(6), None);
+    let warned: Vec<_> = dataset_metadata
+        .warnings()
+        .map(|(index, _)| index)
+        .collect();
+    assert_eq!(warned, [1, 2, 3, 4]);
+
+    // files with ignored sidecars fall back to declaring nothing
+    for (index, path) in paths.iter().enumerate().skip(1) {
+        assert!(!dataset_metadata.declares_sorted_keys(index));
+        assert!(!metadata::shard_declares_sorted_keys(path));
+    }
+
+    // the data records remain fully readable
+    for (index, path) in paths.iter().enumerate() {
+        let examples: Vec<_> =
+            ExampleIter::open(path, Default::default())?.collect::<Result<_, _>>()?;
+        assert_eq!(examples.len(), 5);
+        assert_eq!(examples[0], samples::example(index * 5));
+    }
+    let indexes: Vec<_> = indexer::load_prefix(
+        format!("{}{}", dataset.dir().display(), std::path::MAIN_SEPARATOR),
+        Default::default(),
+    )?
+    .collect::<Result<_, _>>()?;
+    assert_eq!(indexes.len(), 30);
+    Ok(())
+}
+
+#[test]
+fn metadata_warning_display_test() {
+    let warning = MetadataWarning::UnsupportedVersion { version: 3 };
+    assert_eq!(
+        warning.to_string(),
+        "unsupported shard metadata version 3, expect at most 1"
+    );
+
+    // the strict parser still rejects what the lenient loader ignores
+    assert!(ShardMetadata::parse("tfrecord-shard-metadata\nversion=x.1\n").is_err());
+    assert_eq!(
+        ShardMetadata::parse("tfrecord-shard-metadata\nversion=1.9\n")
+            .unwrap()
+            .version,
+        1
+    );
+}
@@ -0,0 +1,208 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::{fs, path::Path};
+use tfrecord::{
+    indexer::{self, RecordIndex},
+    metadata, samples,
+    shardspec::{self, ShardSpecOptions},
+    Error, Example, ExampleIter, ShardAssignment, ShardedWriter, ShardedWriterConfig,
+};
+
+fn write_examples(
+    prefix: &Path,
+    count: usize,
+    config: ShardedWriterConfig,
+) -> Result<Vec<tfrecord::WrittenShard>> {
+    let mut writer = ShardedWriter::<Example>::create(prefix.to_str().unwrap(), config)?;
+    for index in 0..count {
+        writer.send(samples::example(index))?;
+    }
+    Ok(writer.finalize()?)
+}
+
+/// The names of the record files in the directory, without their sidecars.
+fn file_names(dir: &Path) -> Result<Vec<String>> {
+    let mut names: Vec<_> = fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+        .filter(|name| !matches!(name, Ok(name) if metadata::is_metadata_path(name)))
+        .collect::<Result<_>>()?;
+    names.sort();
+    Ok(names)
+}
+
+fn read_ids(paths: &[impl AsRef<Path>]) -> Result<Vec<i64>> {
+    let mut ids = vec![];
+    for path in paths {
+        for example in ExampleIter::open(path, Default::default())? {
+            ids.push(example?.get_i64s("id")?[0]);
+        }
+    }
+    Ok(ids)
+}
+
+#[test]
+fn sequential_unknown_count_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(0, 0)?;
+    let dir = dataset.dir().join("sequential");
+    fs::create_dir(&dir)?;
+    let config = ShardedWriterConfig {
+        max_records_per_shard: Some(4),
+        ..Default::default()
+    };
+    let shards = write_examples(&dir.join("train"), 10, config)?;
+
+    // the temporary names are renamed by the final count
+    assert_eq!(
+        file_names(&dir)?,
+        [
+            "train-00000-of-00003",
+            "train-00001-of-00003",
+            "train-00002-of-00003"
+        ]
+    );
+    let counts: Vec<_> = shards.iter().map(|shard| shard.num_records).collect();
+    assert_eq!(counts, [4, 4, 2]);
+    for shard in &shards {
+        assert_eq!(fs::metadata(&shard.path)?.len(), shard.num_bytes);
+    }
+
+    // the shard set reads back in order through its spec
+    let spec = shardspec::parse_spec(&format!("{}@3", dir.join("train").display()))?;
+    let paths = spec.resolve("", ShardSpecOptions::default())?;
+    assert_eq!(
+        paths,
+        shards
+            .iter()
+            .map(|shard| shard.path.clone())
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(read_ids(&paths)?, (0..10).collect::<Vec<_>>());
+
+    let indexes: Vec<RecordIndex> =
+        indexer::load_paths(&paths, Default::default()).collect::<Result<_, _>>()?;
+    assert_eq!(indexes.len(), 10);
+    let example: Example = indexes[9].load()?;
+    assert_eq!(example.get_i64s("id")?, &[9]);
+    Ok(())
+}
+
+#[test]
+fn sequential_bytes_cap_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(0, 0)?;
+    let frame_len = samples::example(0).encoded_len_framed();
+    let config = ShardedWriterConfig {
+        max_bytes_per_shard: Some(frame_len * 3 - 1),
+        ..Default::default()
+    };
+    let shards = write_examples(&dataset.dir().join("bytes"), 7, config)?;
+    let counts: Vec<_> = shards.iter().map(|shard| shard.num_records).collect();
+    assert_eq!(counts, [3, 3, 1]);
+    assert!(shards[0].path.ends_with("bytes-00000-of-00003"));
+
+    // no records yield a single empty shard
+    let config = ShardedWriterConfig {
+        max_bytes_per_shard: Some(frame_len),
+        ..Default::default()
+    };
+    let shards = write_examples(&dataset.dir().join("empty"), 0, config)?;
+    assert_eq!(shards.len(), 1);
+    assert!(shards[0].path.ends_with("empty-00000-of-00001"));
+    assert_eq!(fs::metadata(&shards[0].path)?.len(), 0);
+    Ok(())
+}
+
+#[test]
+fn sequential_fixed_count_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(0, 0)?;
+    let dir = dataset.dir().join("fixed");
+    fs::create_dir(&dir)?;
+    let config = ShardedWriterConfig {
+        num_shards: Some(4),
+        max_records_per_shard: Some(3),
+        ..Default::default()
+    };
+    let shards = write_examples(&dir.join("eval"), 5, config.clone())?;
+    let counts: Vec<_> = shards.iter().map(|shard| shard.num_records).collect();
+    assert_eq!(counts, [3, 2, 0, 0]);
+    assert_eq!(file_names(&dir)?.len(), 4);
+    let paths = shardspec::parse_spec("eval@4")?.resolve(&dir, Default::default())?;
+    assert_eq!(read_ids(&paths)?, (0..5).collect::<Vec<_>>());
+
+    // exceeding the count poisons the writer
+    let mut writer = ShardedWriter::<Example>::create(dir.join("over").to_str().unwrap(), config)?;
+    for index in 0..12 {
+        writer.send(samples::example(index))?;
+    }
+    let err = writer.send(samples::example(12)).unwrap_err();
+    let Error::WriterPoisoned { original } = &err else {
+        panic!("unexpected error: {}", err);
+    };
+    assert!(
+        matches!(
+            **original,
+            Error::LimitExceeded {
+                which: "num_shards",
+                ..
+            }
+        ),
+        "{}",
+        err
+    );
+    assert!(writer.is_poisoned());
+    assert!(writer.finalize().is_err());
+    Ok(())
+}
+
+#[test]
+fn round_robin_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(0, 0)?;
+    let config = ShardedWriterConfig {
+        num_shards: Some(3),
+        assignment: ShardAssignment::RoundRobin,
+        ..Default::default()
+    };
+    let shards = write_examples(&dataset.dir().join("train"), 10, config)?;
+    let counts: Vec<_> = shards.iter().map(|shard| shard.num_records).collect();
+    assert_eq!(counts, [4, 3, 3]);
+    assert_eq!(read_ids(&[&shards[1].path])?, [1, 4, 7]);
+
+    let paths: Vec<_> = shards.iter().map(|shard| &shard.path).collect();
+    let mut ids = read_ids(&paths)?;
+    ids.sort();
+    assert_eq!(ids, (0..10).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[test]
+fn invalid_config_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(0, 0)?;
+    let prefix = dataset.dir().join("train");
+    let prefix = prefix.to_str().unwrap();
+    let configs = [
+        // sequential assignment of an unknown count has no cap
+        ShardedWriterConfig::default(),
+        // round-robin assignment needs the count
+        ShardedWriterConfig {
+            assignment: ShardAssignment::RoundRobin,
+            ..Default::default()
+        },
+        ShardedWriterConfig {
+            num_shards: Some(2),
+            max_records_per_shard: Some(1),
+            assignment: ShardAssignment::RoundRobin,
+            ..Default::default()
+        },
+        ShardedWriterConfig {
+            num_shards: Some(0),
+            ..Default::default()
+        },
+    ];
+    for config in configs {
+        let result = ShardedWriter::<Example>::create(prefix, config);
+        assert!(matches!(result, Err(Error::ConversionError { .. })));
+    }
+    Ok(())
+}
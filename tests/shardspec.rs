@@ -0,0 +1,156 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::path::PathBuf;
+use tfrecord::{
+    indexer,
+    shardspec::{self, ShardSpec, ShardSpecOptions},
+    BytesWriter, Error,
+};
+
+#[test]
+fn shard_name_width_test() -> Result<()> {
+    let cases = [
+        (9, 8, "train-00008-of-00009"),
+        (10, 0, "train-00000-of-00010"),
+        (10, 9, "train-00009-of-00010"),
+        (99999, 99998, "train-99998-of-99999"),
+        (100000, 3, "train-00003-of-100000"),
+        (100000, 99999, "train-99999-of-100000"),
+        (1234567, 1234566, "train-1234566-of-1234567"),
+    ];
+    for (num_shards, index, name) in cases {
+        let spec = shardspec::parse_spec(&format!("train@{}", num_shards))?;
+        assert_eq!(spec.file_name(index), name);
+        assert_eq!(
+            shardspec::parse_filename(name),
+            Some(("train".to_string(), index, num_shards))
+        );
+    }
+
+    let spec = shardspec::parse_spec("train@100000")?;
+    let paths = spec.paths("data");
+    assert_eq!(paths.len(), 100000);
+    assert_eq!(paths[0], PathBuf::from("data/train-00000-of-100000"));
+    assert_eq!(paths[99999], PathBuf::from("data/train-99999-of-100000"));
+    Ok(())
+}
+
+#[test]
+fn round_trip_test() -> Result<()> {
+    for spec in ["train@1", "train@10", "dir/eval.v2@12", "a@b@3"] {
+        let parsed: ShardSpec = spec.parse()?;
+        assert_eq!(parsed.to_string(), spec);
+        for (index, path) in parsed.paths("").into_iter().enumerate() {
+            let name = path.to_str().unwrap();
+            assert_eq!(
+                shardspec::parse_filename(name),
+                Some((parsed.base.clone(), index, parsed.num_shards))
+            );
+        }
+    }
+    assert_eq!(
+        shardspec::parse_spec("gs/train-x@7")?,
+        ShardSpec {
+            base: "gs/train-x".into(),
+            num_shards: 7,
+        }
+    );
+
+    // invalid specs
+    for spec in [
+        "train", "@10", "train@", "train@0", "train@-1", "train@1x", "train@ 2",
+    ] {
+        assert!(shardspec::parse_spec(spec).is_err(), "{}", spec);
+    }
+    // names not generated by the convention
+    for name in [
+        "train-3-of-10",
+        "train-00003-of-10",
+        "train-000003-of-00010",
+        "train-00010-of-00010",
+        "-00000-of-00001",
+        "train-00000-00001",
+        "train-0000a-of-00010",
+        "train-00003-of-00010.tfrecord",
+    ] {
+        assert_eq!(shardspec::parse_filename(name), None, "{}", name);
+    }
+    Ok(())
+}
+
+#[test]
+fn missing_shards_test() -> Result<()> {
+    let dir = make_temp_dir("shardspec_missing")?;
+
+    let spec = ShardSpec {
+        base: dir.join("train").display().to_string(),
+        num_shards: 4,
+    };
+    let paths = spec.paths("");
+    for index in [0, 2] {
+        let mut writer = BytesWriter::create(&paths[index])?;
+        writer.send(vec![index as u8])?;
+        writer.send(vec![index as u8])?;
+    }
+
+    // all or nothing by default
+    let err = match indexer::load_shard_spec(&spec, Default::default(), Default::default()) {
+        Err(err) => err,
+        Ok(_) => panic!("missing shards are not reported"),
+    };
+    match &err {
+        Error::MissingShards {
+            spec: name,
+            num_shards,
+            missing,
+        } => {
+            assert_eq!(name, &spec.to_string());
+            assert_eq!(*num_shards, 4);
+            assert_eq!(missing, &vec![paths[1].clone(), paths[3].clone()]);
+        }
+        err => panic!("unexpected error {}", err),
+    }
+    let message = err.to_string();
+    assert!(
+        message.starts_with("[TFR0025] 2 of 4 shards"),
+        "{}",
+        message
+    );
+    assert!(message.contains("train-00001-of-00004"), "{}", message);
+    assert!(message.contains("train-00003-of-00004"), "{}", message);
+    assert_eq!(err.io_error_kind(), std::io::ErrorKind::NotFound);
+
+    // the existing shards in order otherwise
+    let options = ShardSpecOptions {
+        allow_missing: true,
+    };
+    assert_eq!(
+        spec.resolve("", options.clone())?,
+        vec![paths[0].clone(), paths[2].clone()]
+    );
+    let indexes: Vec<_> =
+        indexer::load_shard_spec(&spec, options, Default::default())?.collect::<Result<_, _>>()?;
+    let files: Vec<_> = indexes.iter().map(|index| (*index.path).clone()).collect();
+    assert_eq!(
+        files,
+        vec![
+            paths[0].clone(),
+            paths[0].clone(),
+            paths[2].clone(),
+            paths[2].clone()
+        ]
+    );
+
+    // a long list is shortened in the message
+    let spec = ShardSpec {
+        base: dir.join("absent").display().to_string(),
+        num_shards: 20,
+    };
+    let err = spec.resolve("", Default::default()).unwrap_err();
+    assert!(err.to_string().ends_with("and 12 more"), "{}", err);
+    assert!(matches!(err, Error::MissingShards { missing, .. } if missing.len() == 20));
+    Ok(())
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use tfrecord::{
+    indexer::{self, PermutedIterOptions, RecordIndex},
+    samples, Example,
+};
+
+fn load_indexes(dataset: &samples::TempDataset) -> Result<Vec<RecordIndex>> {
+    Ok(indexer::load_paths(dataset.paths(), Default::default()).collect::<Result<_, _>>()?)
+}
+
+/// The global indexes and ids of the yielded examples.
+fn ids(
+    iter: impl Iterator<Item = tfrecord::Result<(usize, Example)>>,
+) -> Result<Vec<(usize, i64)>> {
+    iter.map(|record| {
+        let (index, example) = record?;
+        Ok((index, example.get_i64s("id")?[0]))
+    })
+    .collect()
+}
+
+#[test]
+fn shuffled_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(4, 30)?;
+    let indexes = load_indexes(&dataset)?;
+    let in_order = ids(indexer::iter_from(&indexes, 0, Default::default()))?;
+
+    let shuffled = ids(indexer::iter_shuffled(&indexes, 7, Default::default())?)?;
+    assert_eq!(shuffled.len(), indexes.len());
+    assert_ne!(shuffled, in_order);
+    // every record is yielded once along with its global index
+    for &(index, id) in &shuffled {
+        assert_eq!(in_order[index], (index, id));
+    }
+    let mut sorted = shuffled.clone();
+    sorted.sort_unstable();
+    assert_eq!(sorted, in_order);
+
+    // the same seed gives the same order, and another seed another order
+    let again = ids(indexer::iter_shuffled(&indexes, 7, Default::default())?)?;
+    assert_eq!(again, shuffled);
+    let other = ids(indexer::iter_shuffled(&indexes, 8, Default::default())?)?;
+    assert_ne!(other, shuffled);
+
+    // the permutation is stable across versions and platforms
+    assert_eq!(
+        indexer::shuffled_permutation(10, 7),
+        indexer::shuffled_permutation(10, 7)
+    );
+    let mut permutation = indexer::shuffled_permutation(1000, 1);
+    permutation.sort_unstable();
+    assert_eq!(permutation, (0..1000).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[test]
+fn permuted_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(5, 20)?;
+    let indexes = load_indexes(&dataset)?;
+    let in_order = ids(indexer::iter_from(&indexes, 0, Default::default()))?;
+
+    // jumping between files within a small budget of open files, with repeats and
+    // backward jumps within files
+    let permutation: Vec<usize> = (0..20)
+        .flat_map(|record| (0..5).rev().map(move |file| file * 20 + (19 - record)))
+        .chain([0, 0, 99, 3])
+        .collect();
+    let options = PermutedIterOptions {
+        max_open_files: 2,
+        ..Default::default()
+    };
+    let mut iter = indexer::iter_permuted::<Example>(&indexes, permutation.clone(), options)?;
+    let mut yielded = vec![];
+    for record in &mut iter {
+        let (index, example) = record?;
+        yielded.push((index, example.get_i64s("id")?[0]));
+    }
+    assert!(iter.num_open_files() <= 2);
+    let expect: Vec<_> = permutation.iter().map(|&index| in_order[index]).collect();
+    assert_eq!(yielded, expect);
+
+    // a subset of positions
+    let subset = ids(indexer::iter_permuted(
+        &indexes,
+        vec![42, 17],
+        Default::default(),
+    )?)?;
+    assert_eq!(subset, vec![in_order[42], in_order[17]]);
+
+    // invalid arguments
+    assert!(indexer::iter_permuted::<Example>(&indexes, vec![100], Default::default()).is_err());
+    let options = PermutedIterOptions {
+        max_open_files: 0,
+        ..Default::default()
+    };
+    assert!(indexer::iter_permuted::<Example>(&indexes, vec![0], options).is_err());
+    Ok(())
+}
@@ -0,0 +1,201 @@
+#![cfg(feature = "async")]
+
+use futures::future::BoxFuture;
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+use tfrecord::{
+    AsyncRecordSink, AsyncWriteSink, BytesIter, RecordSinkWriter, RecordSinkWriterConfig, Result,
+    ResumePoint, SinkPart,
+};
+
+#[derive(Debug, Default)]
+struct MockUpload {
+    parts: Vec<SinkPart>,
+    completed: bool,
+    aborted: bool,
+    fail_next_part: bool,
+}
+
+impl MockUpload {
+    fn bytes(&self) -> Vec<u8> {
+        self.parts
+            .iter()
+            .flat_map(|part| part.bytes.iter().cloned())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct MockSink {
+    upload: Arc<Mutex<MockUpload>>,
+}
+
+impl AsyncRecordSink for MockSink {
+    type PartId = usize;
+    type Session = Arc<Mutex<MockUpload>>;
+
+    fn write_part(&mut self, part: SinkPart) -> BoxFuture<'_, Result<usize>> {
+        Box::pin(async move {
+            let mut upload = self.upload.lock().unwrap();
+            if upload.fail_next_part {
+                upload.fail_next_part = false;
+                return Err(io::Error::new(io::ErrorKind::ConnectionReset, "part failed").into());
+            }
+            upload.parts.push(part);
+            Ok(upload.parts.len() - 1)
+        })
+    }
+
+    fn resume(&mut self, session: Self::Session) -> BoxFuture<'_, Result<ResumePoint>> {
+        Box::pin(async move {
+            self.upload = session;
+            let upload = self.upload.lock().unwrap();
+            let point = upload
+                .parts
+                .iter()
+                .fold(ResumePoint::default(), |point, part| ResumePoint {
+                    offset: point.offset + part.bytes.len() as u64,
+                    num_records: point.num_records + part.num_records,
+                });
+            Ok(point)
+        })
+    }
+
+    fn complete(&mut self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            self.upload.lock().unwrap().completed = true;
+            Ok(())
+        })
+    }
+
+    fn abort(&mut self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            self.upload.lock().unwrap().aborted = true;
+            Ok(())
+        })
+    }
+
+    fn abort_on_drop(&mut self) {
+        self.upload.lock().unwrap().aborted = true;
+    }
+}
+
+fn make_records(count: usize) -> Vec<Vec<u8>> {
+    (0..count).map(|index| vec![index as u8; 10]).collect()
+}
+
+fn read_records(bytes: Vec<u8>) -> Vec<Vec<u8>> {
+    BytesIter::from_reader(bytes.as_slice(), Default::default())
+        .collect::<Result<_>>()
+        .unwrap()
+}
+
+#[async_std::test]
+async fn sink_writer_resume_test() -> Result<()> {
+    let records = make_records(20);
+    let config = RecordSinkWriterConfig { part_size: 50 };
+    let sink = MockSink::default();
+    let session = sink.upload.clone();
+
+    // crash after part 2
+    {
+        let mut writer = RecordSinkWriter::new(sink, config.clone());
+        for record in records.iter().cloned() {
+            writer.send(record).await?;
+            if writer.part_ids().len() == 2 {
+                break;
+            }
+        }
+        std::mem::forget(writer);
+    }
+
+    assert_eq!(session.lock().unwrap().parts.len(), 2);
+    assert!(!session.lock().unwrap().completed);
+
+    // resume
+    let mut writer = RecordSinkWriter::resume(MockSink::default(), session.clone(), config).await?;
+    let skip = writer.num_committed_records() as usize;
+    assert_eq!(skip, 4);
+
+    for record in records.iter().skip(skip).cloned() {
+        writer.send(record).await?;
+    }
+    writer.finish().await?;
+
+    let upload = session.lock().unwrap();
+    assert!(upload.completed);
+    assert!(!upload.aborted);
+    assert_eq!(read_records(upload.bytes()), records);
+    Ok(())
+}
+
+#[async_std::test]
+async fn sink_writer_abort_on_drop_test() -> Result<()> {
+    // dropped without finishing
+    {
+        let sink = MockSink::default();
+        let upload = sink.upload.clone();
+        let mut writer = RecordSinkWriter::new(sink, Default::default());
+        writer.send(vec![1, 2, 3]).await?;
+        drop(writer);
+
+        let upload = upload.lock().unwrap();
+        assert!(upload.aborted);
+        assert!(!upload.completed);
+    }
+
+    // finished
+    {
+        let sink = MockSink::default();
+        let upload = sink.upload.clone();
+        let mut writer = RecordSinkWriter::new(sink, Default::default());
+        writer.send(vec![1, 2, 3]).await?;
+        let sink = writer.finish().await?;
+        drop(sink);
+
+        let upload = upload.lock().unwrap();
+        assert!(!upload.aborted);
+        assert!(upload.completed);
+        assert_eq!(upload.parts.len(), 1);
+    }
+
+    Ok(())
+}
+
+#[async_std::test]
+async fn sink_writer_retry_failed_part_test() -> Result<()> {
+    let records = make_records(10);
+    let sink = MockSink::default();
+    let upload = sink.upload.clone();
+    let mut writer = RecordSinkWriter::new(sink, RecordSinkWriterConfig { part_size: 50 });
+
+    upload.lock().unwrap().fail_next_part = true;
+    let mut num_failures = 0;
+    for record in records.iter().cloned() {
+        if writer.send(record).await.is_err() {
+            num_failures += 1;
+        }
+    }
+    assert_eq!(num_failures, 1);
+    writer.finish().await?;
+
+    let upload = upload.lock().unwrap();
+    assert!(upload.completed);
+    assert_eq!(read_records(upload.bytes()), records);
+    Ok(())
+}
+
+#[async_std::test]
+async fn async_write_sink_test() -> Result<()> {
+    let records = make_records(7);
+    let sink = AsyncWriteSink::new(futures::io::Cursor::new(vec![]));
+    let mut writer = RecordSinkWriter::new(sink, RecordSinkWriterConfig { part_size: 30 });
+    for record in records.iter().cloned() {
+        writer.send(record).await?;
+    }
+    let bytes = writer.finish().await?.into_inner().into_inner();
+    assert_eq!(read_records(bytes), records);
+    Ok(())
+}
@@ -0,0 +1,109 @@
+#![cfg(all(feature = "testing", feature = "proto-summary"))]
+
+mod common;
+
+use common::*;
+use prost::Message as _;
+use tfrecord::{
+    protobuf::{FeatureList, FeatureLists, Features, SequenceExample},
+    testing::{normalize_for_snapshot, SnapshotOptions, SnapshotRecordKind, StepClock},
+    EventClock, EventIter, EventWriter, EventWriterConfig, Example, Feature,
+};
+
+fn make_example(reverse: bool) -> Example {
+    let mut keys: Vec<_> = (0..32).collect();
+    if reverse {
+        keys.reverse();
+    }
+    keys.into_iter()
+        .map(|index| {
+            (
+                format!("key_{:02}", index),
+                Feature::from_f32_list(vec![index as f32]),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn canonical_encoding_test() -> Result<()> {
+    let lhs = make_example(false);
+    let rhs = make_example(true);
+    let bytes = lhs.encode_canonical_to_vec();
+    assert_eq!(bytes, rhs.encode_canonical_to_vec());
+    assert_eq!(bytes.len(), lhs.encoded_len());
+    assert_eq!(Example::decode(bytes.as_slice())?, lhs);
+
+    // keys appear in sorted order on the wire
+    let positions: Vec<_> = (0..32)
+        .map(|index| {
+            let key = format!("key_{:02}", index);
+            bytes
+                .windows(key.len())
+                .position(|window| window == key.as_bytes())
+                .unwrap()
+        })
+        .collect();
+    assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+
+    let sequence_example = SequenceExample {
+        context: Some(Features {
+            feature: lhs.clone().into_hash_map(),
+        }),
+        feature_lists: Some(FeatureLists {
+            feature_list: (0..8)
+                .map(|index| {
+                    (
+                        format!("list_{}", index),
+                        FeatureList {
+                            feature: vec![Feature::from_i64_list(vec![index])],
+                        },
+                    )
+                })
+                .collect(),
+        }),
+    };
+    let bytes = sequence_example.encode_canonical_to_vec();
+    assert_eq!(bytes.len(), sequence_example.encoded_len());
+    assert_eq!(SequenceExample::decode(bytes.as_slice())?, sequence_example);
+
+    Ok(())
+}
+
+#[test]
+fn normalize_for_snapshot_test() -> Result<()> {
+    let dir = make_temp_dir("snapshot_normalize")?;
+    let write_events = |name: &str, clock: EventClock| -> Result<_> {
+        let path = dir.join(name);
+        let mut writer =
+            EventWriter::create(&path, EventWriterConfig::default().with_clock(clock))?;
+        for step in 0..4 {
+            writer.write_scalar("value", step, step as f32)?;
+        }
+        Ok(path)
+    };
+
+    // step clock
+    let path = write_events(
+        "snapshot_step_clock.tfevents",
+        EventClock::new(StepClock::new(10.0, 0.5)),
+    )?;
+    let wall_times: Vec<_> = EventIter::open(&path, Default::default())?
+        .map(|event| Ok(event?.wall_time))
+        .collect::<Result<_>>()?;
+    // the file version and the producer stamp take the first reading of the clock
+    assert_eq!(wall_times, vec![10.0, 10.0, 10.5, 11.0, 11.5, 12.0]);
+
+    // system clock with zeroed wall times
+    let options = SnapshotOptions {
+        record_kind: SnapshotRecordKind::Event,
+        zero_wall_time: true,
+    };
+    let first = write_events("snapshot_first.tfevents", EventClock::default())?;
+    let second = write_events("snapshot_second.tfevents", EventClock::default())?;
+    assert_eq!(
+        normalize_for_snapshot(&first, options.clone())?,
+        normalize_for_snapshot(&second, options)?
+    );
+    Ok(())
+}
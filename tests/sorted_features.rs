@@ -0,0 +1,212 @@
+mod common;
+
+use common::*;
+use prost::Message as _;
+use std::fs;
+use tfrecord::{
+    indexer,
+    metadata::{self, ShardMetadata},
+    BytesIter, Example, ExampleIter, ExampleWriter, Feature, RecordWriterConfig,
+};
+
+const KEYS: [&str; 6] = ["zeta", "alpha", "mu", "beta", "omega", "delta"];
+
+fn make_example(index: i64) -> Example {
+    KEYS.iter()
+        .map(|key| (key.to_string(), Feature::from_i64_list(vec![index])))
+        .collect()
+}
+
+/// Read a varint from the wire.
+fn read_varint(bytes: &mut &[u8]) -> usize {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = bytes[0];
+        *bytes = &bytes[1..];
+        value |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return value;
+        }
+        shift += 7;
+    }
+}
+
+/// Read a length-delimited field with the expected key byte.
+fn read_field<'a>(bytes: &mut &'a [u8], key: u8) -> &'a [u8] {
+    assert_eq!(bytes[0], key);
+    *bytes = &bytes[1..];
+    let len = read_varint(bytes);
+    let (field, rest) = bytes.split_at(len);
+    *bytes = rest;
+    field
+}
+
+/// List the feature keys of a serialized example in wire order.
+fn wire_keys(mut bytes: &[u8]) -> Vec<String> {
+    let mut features = read_field(&mut bytes, 0x0a);
+    assert!(bytes.is_empty());
+
+    let mut keys = vec![];
+    while !features.is_empty() {
+        let mut entry = read_field(&mut features, 0x0a);
+        let key = read_field(&mut entry, 0x0a);
+        keys.push(String::from_utf8(key.to_vec()).unwrap());
+    }
+    keys
+}
+
+#[test]
+fn sorted_wire_order_test() -> Result<()> {
+    let path = make_temp_dir("sorted_features_wire_order")?.join("sorted.tfrecord");
+    let config = RecordWriterConfig {
+        sorted_features: true,
+        ..Default::default()
+    };
+    let mut writer = ExampleWriter::create_with_config(&path, config)?;
+    for index in 0..4 {
+        writer.send(make_example(index))?;
+    }
+    drop(writer);
+
+    let mut expect: Vec<_> = KEYS.iter().map(|key| key.to_string()).collect();
+    expect.sort();
+    for bytes in BytesIter::open(&path, Default::default())? {
+        let bytes = bytes?;
+        assert_eq!(wire_keys(&bytes), expect);
+        assert!(Example::has_sorted_keys(&bytes)?);
+    }
+
+    let examples: Vec<_> =
+        ExampleIter::open(&path, Default::default())?.collect::<Result<_, _>>()?;
+    assert_eq!(examples, (0..4).map(make_example).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[test]
+fn has_sorted_keys_test() -> Result<()> {
+    let example = make_example(0);
+    let mut entries = example.clone().into_vec();
+    entries.sort_by(|(lhs, _), (rhs, _)| rhs.cmp(lhs));
+    let reversed: Vec<u8> = entries
+        .into_iter()
+        .flat_map(|entry| Example::from_iter([entry]).encode_to_vec())
+        .collect();
+    assert!(!Example::has_sorted_keys(&reversed)?);
+    assert!(Example::has_sorted_keys(
+        &example.encode_canonical_to_vec()
+    )?);
+
+    // duplicated keys are not sorted
+    let single = Example::from_iter([("a".to_string(), Feature::from_i64_list(vec![1]))]);
+    let duplicated = [single.encode_to_vec(), single.encode_to_vec()].concat();
+    assert!(!Example::has_sorted_keys(&duplicated)?);
+    assert!(Example::has_sorted_keys(&[])?);
+    Ok(())
+}
+
+#[test]
+fn metadata_round_trip_test() -> Result<()> {
+    let dir = make_temp_dir("sorted_features_metadata")?;
+    let path = dir.join("shard-0.tfrecord");
+    let sidecar = metadata::metadata_path(&path);
+    assert_eq!(sidecar, dir.join("shard-0.tfrecord.meta"));
+
+    let config = RecordWriterConfig {
+        sorted_features: true,
+        ..Default::default()
+    };
+    let mut writer = ExampleWriter::create_with_config(&path, config)?;
+    writer.send(make_example(0))?;
+    drop(writer);
+
+    assert!(metadata::shard_declares_sorted_keys(&path));
+    let metadata = ShardMetadata::read_for(&path)?.unwrap();
+    assert_eq!(metadata.version, metadata::METADATA_VERSION);
+    assert!(metadata.sorted_features);
+    assert_eq!(ShardMetadata::parse(&metadata.to_text())?, metadata);
+
+    // the sidecar is not indexed as a record file
+    let indexes: Vec<_> = indexer::load_prefix(
+        format!("{}{}", dir.display(), std::path::MAIN_SEPARATOR),
+        Default::default(),
+    )?
+    .collect::<Result<_, _>>()?;
+    assert_eq!(indexes.len(), 1);
+
+    // rewriting without the option replaces the stale declaration by the producer stamp
+    let mut writer = ExampleWriter::create(&path)?;
+    writer.send(make_example(1))?;
+    drop(writer);
+    assert!(!metadata::shard_declares_sorted_keys(&path));
+    let metadata = ShardMetadata::read_for(&path)?.unwrap();
+    assert_eq!(
+        metadata.producer.unwrap().options["sorted_features"],
+        "false"
+    );
+
+    // and without the stamp removes the stale sidecar
+    let config = RecordWriterConfig {
+        stamp_producer: false,
+        ..Default::default()
+    };
+    let mut writer = ExampleWriter::create_with_config(&path, config)?;
+    writer.send(make_example(1))?;
+    drop(writer);
+    assert!(!sidecar.exists());
+    assert!(!metadata::shard_declares_sorted_keys(&path));
+    assert_eq!(ShardMetadata::read_for(&path)?, None);
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn async_metadata_test() -> Result<()> {
+    use tfrecord::ExampleAsyncWriter;
+
+    let path = make_temp_dir("sorted_features_async")?.join("shard-0.tfrecord");
+    let config = RecordWriterConfig {
+        sorted_features: true,
+        ..Default::default()
+    };
+    let mut writer = ExampleAsyncWriter::create_with_config(&path, config).await?;
+    writer.send(make_example(0)).await?;
+    writer.flush().await?;
+    drop(writer);
+    assert!(metadata::shard_declares_sorted_keys(&path));
+
+    // rewriting without the options removes the stale sidecar
+    let config = RecordWriterConfig {
+        stamp_producer: false,
+        ..Default::default()
+    };
+    let mut writer = ExampleAsyncWriter::create_with_config(&path, config).await?;
+    writer.send(make_example(1)).await?;
+    writer.flush().await?;
+    drop(writer);
+    assert!(!metadata::metadata_path(&path).exists());
+    Ok(())
+}
+
+#[test]
+fn metadata_parse_test() -> Result<()> {
+    let metadata = ShardMetadata::parse(
+        "tfrecord-shard-metadata\nversion=1\nsorted_features=true\nfoo=bar\n",
+    )?;
+    assert!(metadata.sorted_features);
+    assert_eq!(metadata.extra["foo"], "bar");
+    assert_eq!(ShardMetadata::parse(&metadata.to_text())?, metadata);
+
+    assert!(ShardMetadata::parse("version=1\n").is_err());
+    assert!(ShardMetadata::parse("tfrecord-shard-metadata\nsorted_features=true\n").is_err());
+    assert!(
+        ShardMetadata::parse("tfrecord-shard-metadata\nversion=1\nsorted_features=yes\n").is_err()
+    );
+
+    // malformed metadata declares nothing
+    let path = make_temp_dir("sorted_features_malformed")?.join("malformed.tfrecord");
+    fs::write(&path, [])?;
+    fs::write(metadata::metadata_path(&path), "garbage")?;
+    assert!(!metadata::shard_declares_sorted_keys(&path));
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use prost::Message as _;
+use rand::{prelude::*, rngs::StdRng};
+use std::collections::BTreeSet;
+use tfrecord::{
+    AccessorConfig, BytesWriter, Example, ExampleIter, ExampleWriter, Feature, RecordWriterConfig,
+    SortedIndicesEncoding,
+};
+
+#[test]
+fn sorted_indices_round_trip_test() -> tfrecord::Result<()> {
+    for indices in [
+        vec![],
+        vec![5],
+        vec![-3, -3, 0, 0, 0, 7, i64::MAX],
+        vec![i64::MIN, -1, 1, i64::MAX],
+    ] {
+        let feature = Feature::from_sorted_indices(&indices, false)?;
+        assert!(feature.is_sorted_indices());
+        assert_eq!(feature.to_sorted_indices()?, indices);
+    }
+
+    let mut example: Example = [
+        (
+            "indices".to_string(),
+            Feature::from_i64_list(vec![1, 4, 4, 9]),
+        ),
+        ("label".to_string(), Feature::from_i64_list(vec![9, 1])),
+    ]
+    .into_iter()
+    .collect();
+    let orig = example.clone();
+
+    example.encode_sorted_indices("indices", false)?;
+    assert_ne!(example, orig);
+    let decoded = Example::decode(example.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(decoded, example);
+
+    example.decode_sorted_indices("indices")?;
+    assert_eq!(example, orig);
+
+    example.encode_sorted_indices("indices", false)?;
+    example.decode_all_sorted_indices()?;
+    assert_eq!(example, orig);
+
+    assert!(example.decode_sorted_indices("label").is_err());
+    assert!(example.encode_sorted_indices("missing", false).is_err());
+    Ok(())
+}
+
+#[test]
+fn unsorted_indices_test() -> tfrecord::Result<()> {
+    let indices = [1, 3, 2, 5];
+    let error = Feature::from_sorted_indices(&indices, false).unwrap_err();
+    assert!(error.to_string().contains("position 2"), "{}", error);
+
+    let feature = Feature::from_sorted_indices(&indices, true)?;
+    assert_eq!(feature.to_sorted_indices()?, vec![1, 2, 3, 5]);
+    Ok(())
+}
+
+#[test]
+fn sorted_indices_size_test() -> tfrecord::Result<()> {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    // sparse ids drawn from a large vocabulary
+    let mut indices: Vec<i64> = (0..10_000).map(|_| rng.gen_range(0..(1 << 32))).collect();
+    indices.sort_unstable();
+
+    let plain = Feature::from_i64_list(indices.clone());
+    let compact = Feature::from_sorted_indices(&indices, false)?;
+    assert!(
+        compact.encoded_len() * 10 < plain.encoded_len() * 7,
+        "compact {} bytes, plain {} bytes",
+        compact.encoded_len(),
+        plain.encoded_len()
+    );
+    assert_eq!(compact.to_sorted_indices()?, indices);
+    Ok(())
+}
+
+#[test]
+fn sorted_indices_accessor_test() -> tfrecord::Result<()> {
+    let mut example = Example::empty();
+    example.push_i64s("indices", &[2, 2, 5, 11]);
+    example.push_i64s("label", &[1]);
+    example.encode_sorted_indices("indices", false)?;
+
+    // the encoded feature is a BytesList without the option
+    let plain = AccessorConfig::default();
+    assert!(example.get_i64s_with("indices", &plain).is_err());
+    assert_eq!(&*example.get_i64s_with("label", &plain)?, &[1]);
+
+    let config = AccessorConfig {
+        auto_decode_indices: true,
+    };
+    assert_eq!(&*example.get_i64s_with("indices", &config)?, &[2, 2, 5, 11]);
+    assert_eq!(&*example.get_i64s_with("label", &config)?, &[1]);
+    assert_eq!(example.try_get_i64s_with("missing", &config)?, None);
+    assert!(example.get_i64s_with("missing", &config).is_err());
+    Ok(())
+}
+
+#[test]
+fn sorted_indices_writer_test() -> tfrecord::Result<()> {
+    let config = RecordWriterConfig {
+        encode_sorted_indices: Some(SortedIndicesEncoding {
+            keys: BTreeSet::from(["indices".to_string()]),
+            sort: true,
+        }),
+        ..Default::default()
+    };
+    let (mut writer, buffer) = ExampleWriter::in_memory_with_config(config.clone())?;
+    let mut example = Example::empty();
+    example.push_i64s("indices", &[9, 1, 4]);
+    writer.send(example)?;
+    // examples without the key are written as they are
+    let mut other = Example::empty();
+    other.push_i64s("label", &[1]);
+    writer.send(other.clone())?;
+    writer.flush()?;
+
+    let examples: Vec<Example> =
+        ExampleIter::from_bytes(buffer.to_vec(), Default::default()).collect::<Result<_, _>>()?;
+    assert!(examples[0].clone().into_hash_map()["indices"].is_sorted_indices());
+    let accessor = AccessorConfig {
+        auto_decode_indices: true,
+    };
+    assert_eq!(
+        &*examples[0].get_i64s_with("indices", &accessor)?,
+        &[1, 4, 9]
+    );
+    assert_eq!(examples[1], other);
+
+    // records without features cannot be encoded
+    let (mut writer, _buffer) = BytesWriter::in_memory_with_config(config)?;
+    let error = writer.send(b"record".to_vec()).unwrap_err();
+    assert!(
+        matches!(error, tfrecord::Error::InvalidArgumentsError { .. }),
+        "{}",
+        error
+    );
+    Ok(())
+}
@@ -0,0 +1,179 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::collections::{HashMap, HashSet};
+use tfrecord::{
+    indexer::{self, RecordIndex},
+    samples,
+    split::{self, HashSplitOptions, MissingKeyPolicy, SplitKey},
+    BytesWriter, Error, Example, ExampleWriter, Feature,
+};
+
+fn load_indexes(paths: &[std::path::PathBuf]) -> Result<Vec<RecordIndex>> {
+    Ok(indexer::load_paths(paths, Default::default()).collect::<Result<_, _>>()?)
+}
+
+/// The split of every sample by its id.
+fn split_by_id(splits: &[Vec<RecordIndex>]) -> Result<HashMap<i64, usize>> {
+    let mut assignment = HashMap::new();
+    for (split, indexes) in splits.iter().enumerate() {
+        for index in indexes {
+            let example: Example = index.load()?;
+            let id = example.into_hash_map()["id"].as_i64_list().unwrap()[0];
+            assert!(assignment.insert(id, split).is_none());
+        }
+    }
+    Ok(assignment)
+}
+
+#[test]
+fn stable_assignment_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(4, 50)?;
+    let paths = dataset.paths();
+    let fractions = [0.6, 0.3];
+
+    for key in [SplitKey::FullPayload, SplitKey::Feature("name".into())] {
+        let all = split_by_id(&split::by_hash(
+            &load_indexes(paths)?,
+            key.clone(),
+            &fractions,
+        )?)?;
+        assert!(all.len() < 200);
+
+        // removing files, or reordering them, keeps the assignment of the others
+        let subset = [paths[3].clone(), paths[1].clone()];
+        let some = split_by_id(&split::by_hash(
+            &load_indexes(&subset)?,
+            key.clone(),
+            &fractions,
+        )?)?;
+        assert!(!some.is_empty());
+        for (id, split) in &some {
+            assert_eq!(all.get(id), Some(split), "{}", id);
+        }
+
+        // as does adding files
+        let more = samples::tiny_dataset(5, 50)?;
+        let grown = split_by_id(&split::by_hash(
+            &load_indexes(more.paths())?,
+            key,
+            &fractions,
+        )?)?;
+        for (id, split) in &all {
+            assert_eq!(grown.get(id), Some(split), "{}", id);
+        }
+    }
+
+    // another seed gives another assignment
+    let options = HashSplitOptions {
+        seed: 1,
+        ..Default::default()
+    };
+    let reseeded = split::by_hash_with_options(&load_indexes(paths)?, options, &fractions)?;
+    let default = split::by_hash(&load_indexes(paths)?, SplitKey::FullPayload, &fractions)?;
+    assert_ne!(split_by_id(&reseeded)?, split_by_id(&default)?);
+    Ok(())
+}
+
+#[test]
+fn missing_feature_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(0, 0)?;
+    let path = dataset.dir().join("groups.tfrecord");
+    let mut writer = ExampleWriter::create(&path)?;
+    for index in 0..100 {
+        let mut features = samples::example(index).into_hash_map();
+        // every third example has no group
+        if index % 3 != 0 {
+            features.insert(
+                "group".into(),
+                Feature::from_i64_list(vec![(index % 7) as i64]),
+            );
+        }
+        let example: Example = features.into_iter().collect();
+        writer.send(example)?;
+    }
+    drop(writer);
+    let indexes = load_indexes(&[path])?;
+    let key = SplitKey::Feature("group".into());
+
+    // failing by default
+    let err = split::by_hash(&indexes, key.clone(), &[0.5, 0.5]).unwrap_err();
+    assert!(matches!(err, Error::ConversionError { .. }), "{}", err);
+    assert!(err.to_string().contains("group"), "{}", err);
+
+    // skipping examples without the feature
+    let options = HashSplitOptions {
+        missing: MissingKeyPolicy::Skip,
+        ..HashSplitOptions::new(key.clone())
+    };
+    let splits = split_by_id(&split::by_hash_with_options(
+        &indexes,
+        options,
+        &[0.5, 0.5],
+    )?)?;
+    assert_eq!(splits.len(), 66);
+    assert!(splits.keys().all(|id| id % 3 != 0));
+    // examples of a group share the split
+    for (id, split) in &splits {
+        let first = splits
+            .iter()
+            .find(|(other, _)| *other % 7 == id % 7)
+            .unwrap();
+        assert_eq!(split, first.1);
+    }
+
+    // hashing the payloads of examples without the feature
+    let options = HashSplitOptions {
+        missing: MissingKeyPolicy::HashPayload,
+        ..HashSplitOptions::new(key)
+    };
+    let classification = split::classify(&indexes, options)?;
+    assert!(classification.buckets.iter().all(Option::is_some));
+    let splits = split_by_id(&classification.assign(&indexes, &[0.5, 0.5])?)?;
+    assert_eq!(splits.len(), 100);
+    Ok(())
+}
+
+#[test]
+fn fraction_accuracy_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(0, 0)?;
+    let path = dataset.dir().join("large.tfrecord");
+    let mut writer = BytesWriter::create(&path)?;
+    let num_records = 50_000;
+    for index in 0..num_records {
+        writer.send(format!("record-{}", index).into_bytes())?;
+    }
+    drop(writer);
+    let indexes = load_indexes(&[path])?;
+
+    // classified once, assigned to several splits
+    let classification = split::classify(&indexes, Default::default())?;
+    for fractions in [vec![0.8, 0.1, 0.1], vec![0.7, 0.2], vec![0.05]] {
+        let splits = classification.assign(&indexes, &fractions)?;
+        for (split, fraction) in splits.iter().zip(&fractions) {
+            let achieved = split.len() as f64 / num_records as f64;
+            assert!(
+                (achieved - fraction).abs() < 0.01,
+                "{} {}",
+                achieved,
+                fraction
+            );
+        }
+    }
+
+    // growing the first split only moves records from the others
+    let as_set = |indexes: &[RecordIndex]| indexes.iter().cloned().collect::<HashSet<_>>();
+    let small = classification.assign(&indexes, &[0.5, 0.5])?;
+    let large = classification.assign(&indexes, &[0.7, 0.3])?;
+    assert!(as_set(&small[0]).is_subset(&as_set(&large[0])));
+    assert!(as_set(&large[1]).is_subset(&as_set(&small[1])));
+
+    // invalid fractions
+    for fractions in [vec![0.6, 0.6], vec![-0.1], vec![f64::NAN]] {
+        assert!(classification.assign(&indexes, &fractions).is_err());
+    }
+    assert!(classification.assign(&indexes[1..], &[0.5]).is_err());
+    Ok(())
+}
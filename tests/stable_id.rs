@@ -0,0 +1,102 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::fs;
+use tfrecord::{
+    fingerprint::{self, FingerprintLevel},
+    indexer::{StableId, StableIndexes},
+    samples, Example,
+};
+
+#[test]
+fn stable_ids_across_datasets() -> Result<()> {
+    let dataset = samples::tiny_dataset(3, 10)?;
+    let [a, b, c] = [0, 1, 2].map(|index| &dataset.paths()[index]);
+
+    // the shared file `b` is second in one dataset and first in the other
+    let lhs = StableIndexes::load_paths([a, b], Default::default())?;
+    let rhs = StableIndexes::load_paths([b, c, a], Default::default())?;
+    assert_eq!(lhs.file_id(b), rhs.file_id(b));
+    assert_ne!(lhs.file_id(a), lhs.file_id(b));
+
+    for index in 10..20 {
+        let id = lhs.stable_id(index).unwrap();
+        let other = rhs.find_by_stable_id(&id).unwrap();
+        assert_eq!(other, index - 10);
+        assert_eq!(rhs.stable_id(other), Some(id));
+        assert_eq!(lhs.get::<Example>(index)?, rhs.get::<Example>(other)?);
+    }
+    for index in 0..10 {
+        let id = lhs.stable_id(index).unwrap();
+        assert_eq!(rhs.find_by_stable_id(&id), Some(index + 20));
+    }
+
+    // records of files not in the dataset are not found
+    let id = rhs.stable_id(10).unwrap();
+    assert_eq!(lhs.find_by_stable_id(&id), None);
+    assert_eq!(lhs.stable_id(20), None);
+    Ok(())
+}
+
+#[test]
+fn stable_id_format() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 3)?;
+    let indexes = StableIndexes::load_paths(dataset.paths(), Default::default())?;
+    let id = indexes.stable_id(2).unwrap();
+    let text = id.to_string();
+    assert_eq!(text.parse::<StableId>()?, id);
+    assert_eq!(indexes.find_by_stable_id(&text.parse()?), Some(2));
+
+    // the file id is the content fingerprint of the file alone
+    let file_id = fingerprint::fingerprint_paths(
+        dataset.paths(),
+        FingerprintLevel::Content,
+        Default::default(),
+    )?;
+    assert_eq!(id.file, file_id);
+
+    assert!("".parse::<StableId>().is_err());
+    assert!(format!("{}@x", id.file).parse::<StableId>().is_err());
+    let structure = fingerprint::fingerprint_paths(
+        dataset.paths(),
+        FingerprintLevel::Structure,
+        Default::default(),
+    )?;
+    assert!(format!("{}@0", structure).parse::<StableId>().is_err());
+    Ok(())
+}
+
+#[test]
+fn stable_ids_iter() -> Result<()> {
+    let dataset = samples::tiny_dataset(2, 5)?;
+    let indexes = StableIndexes::load_paths(dataset.paths(), Default::default())?;
+    let records: Vec<(StableId, Example)> = indexes
+        .iter(Default::default())
+        .collect::<tfrecord::Result<_>>()?;
+    assert_eq!(records.len(), 10);
+    for (index, (id, example)) in records.into_iter().enumerate() {
+        assert_eq!(indexes.stable_id(index), Some(id));
+        assert_eq!(example, samples::example(index));
+    }
+    Ok(())
+}
+
+#[test]
+fn stable_ids_break_on_content_change() -> Result<()> {
+    let dataset = samples::tiny_dataset(2, 5)?;
+    let before = StableIndexes::load_paths(dataset.paths(), Default::default())?;
+    let id = before.stable_id(0).unwrap();
+
+    // rewrite the first file with the records of the second one
+    fs::copy(&dataset.paths()[1], &dataset.paths()[0])?;
+    let after = StableIndexes::load_paths(dataset.paths(), Default::default())?;
+    assert_eq!(after.find_by_stable_id(&id), None);
+
+    // identical files share ids, which resolve to the first file
+    let id = after.stable_id(5).unwrap();
+    assert_eq!(after.stable_id(0), Some(id));
+    assert_eq!(after.find_by_stable_id(&id), Some(0));
+    Ok(())
+}
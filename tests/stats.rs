@@ -0,0 +1,141 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::{
+    fs::OpenOptions,
+    io::{Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+use tfrecord::{
+    indexer::{self, PathOrder, RecordIndexerConfig},
+    samples,
+    stats::{self, FileStats},
+    BytesWriter, IntegrityMode,
+};
+
+fn flip_byte(path: &Path, offset: u64) -> Result<()> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut byte = [0];
+    file.read_exact(&mut byte)?;
+    file.seek(SeekFrom::Start(offset))?;
+    file.write_all(&[byte[0] ^ 0xff])?;
+    Ok(())
+}
+
+#[test]
+fn file_stats_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 0)?;
+    let path = dataset.dir().join("lengths.tfrecord");
+    let mut writer = BytesWriter::create(&path)?;
+    for len in [3, 10, 5] {
+        writer.send(vec![0; len])?;
+    }
+    writer.flush()?;
+
+    let stats = stats::inspect(&path, Default::default())?;
+    assert_eq!(
+        stats,
+        FileStats {
+            num_records: 3,
+            min_len: Some(3),
+            max_len: Some(10),
+            total_len: 18,
+            num_corrupt: 0,
+        }
+    );
+    assert_eq!(stats.mean_len(), Some(6.0));
+    assert_eq!(stats::count_records(&path, Default::default())?, 3);
+
+    // an empty file has no lengths
+    let empty = dataset.dir().join("empty.tfrecord");
+    BytesWriter::create(&empty)?.flush()?;
+    let stats = stats::inspect(&empty, Default::default())?;
+    assert_eq!(stats, FileStats::default());
+    assert_eq!(stats.mean_len(), None);
+    Ok(())
+}
+
+#[test]
+fn corrupt_stats_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 4)?;
+    let path = &dataset.paths()[0];
+    let indexes: Vec<_> =
+        indexer::load_file(path, Default::default())?.collect::<Result<_, _>>()?;
+    flip_byte(path, indexes[1].offset + 1)?;
+
+    // the corrupt record is counted apart
+    let stats = stats::inspect(path, Default::default())?;
+    assert_eq!(stats.num_records, 3);
+    assert_eq!(stats.num_corrupt, 1);
+    assert!(!stats.checksums_valid());
+
+    // unverified payloads are not accounted for
+    let config = RecordIndexerConfig {
+        integrity: IntegrityMode::Off,
+        ..Default::default()
+    };
+    let stats = stats::inspect(path, config)?;
+    assert_eq!(stats.num_records, 4);
+    assert!(stats.checksums_valid());
+    Ok(())
+}
+
+#[test]
+fn dataset_stats_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(5, 3)?;
+    let mut paths = dataset.paths().to_vec();
+    paths.reverse();
+    let config = RecordIndexerConfig {
+        path_order: PathOrder::Lexicographic,
+        ..Default::default()
+    };
+
+    let dataset_stats = stats::inspect_paths(&paths, config.clone(), 2)?;
+    let files: Vec<_> = dataset_stats
+        .files
+        .iter()
+        .map(|(path, _)| path.clone())
+        .collect();
+    assert_eq!(files, dataset.paths());
+    assert!(dataset_stats
+        .files
+        .iter()
+        .all(|(_, stats)| stats.num_records == 3));
+
+    let mut total = FileStats::default();
+    for path in dataset.paths() {
+        total.merge(stats::inspect(path, Default::default())?);
+    }
+    assert_eq!(dataset_stats.total, total);
+    assert_eq!(total.num_records, 15);
+
+    assert!(stats::inspect_paths(&paths, config, 0).is_err());
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn async_stats_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(3, 4)?;
+    let dataset_stats = stats::inspect_paths(dataset.paths(), Default::default(), 2)?;
+    let async_stats = stats::inspect_paths_async(dataset.paths(), Default::default(), 2).await?;
+    assert_eq!(async_stats, dataset_stats);
+    assert_eq!(
+        stats::count_records_async(&dataset.paths()[1], Default::default()).await?,
+        4
+    );
+
+    // corrupt records fail async inspection
+    let indexes: Vec<_> =
+        indexer::load_file(&dataset.paths()[0], Default::default())?.collect::<Result<_, _>>()?;
+    flip_byte(&dataset.paths()[0], indexes[0].offset)?;
+    let result = stats::inspect_async(&dataset.paths()[0], Default::default()).await;
+    assert!(matches!(
+        result,
+        Err(tfrecord::Error::ChecksumMismatch { .. })
+    ));
+    Ok(())
+}
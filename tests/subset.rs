@@ -0,0 +1,241 @@
+mod common;
+
+use common::*;
+use prost::Message as _;
+use std::{collections::HashMap, fs, path::PathBuf};
+use tfrecord::{
+    subset::{self, Budget, KeyQuota, Shortfall, Stratify, SubsetReport, SubsetSpec},
+    BytesIter, Example, ExampleWriter, Feature, RecordWriterConfig,
+};
+
+/// The number of records per label, where label 2 is rare.
+const LABEL_COUNTS: [i64; 3] = [200, 50, 5];
+
+/// Write examples with imbalanced labels to two files.
+fn labeled_dataset(name: &str) -> Result<Vec<PathBuf>> {
+    let dir = make_temp_dir(&format!("subset/{}", name))?;
+
+    let mut labels: Vec<i64> = LABEL_COUNTS
+        .iter()
+        .enumerate()
+        .flat_map(|(label, &count)| (0..count).map(move |_| label as i64))
+        .collect();
+    // interleave labels deterministically
+    labels.sort_by_key(|label| (label * 7919) % 13);
+
+    let paths: Vec<_> = (0..2)
+        .map(|index| dir.join(format!("part-{}.tfrecord", index)))
+        .collect();
+    for (path, labels) in paths.iter().zip(labels.chunks(labels.len() / 2 + 1)) {
+        let mut writer = ExampleWriter::create_with_config(
+            path,
+            RecordWriterConfig {
+                canonical_encoding: true,
+                ..Default::default()
+            },
+        )?;
+        for (id, &label) in labels.iter().enumerate() {
+            let example: Example = vec![
+                ("id".to_string(), Feature::from_i64_list(vec![id as i64])),
+                ("label".to_string(), Feature::from_i64_list(vec![label])),
+            ]
+            .into_iter()
+            .collect();
+            writer.send(example)?;
+        }
+    }
+    Ok(paths)
+}
+
+fn label_of(bytes: &[u8]) -> tfrecord::Result<String> {
+    let example = Example::decode(bytes)?;
+    let label = example.into_hash_map().remove("label").unwrap();
+    Ok(label.as_i64_list().unwrap()[0].to_string())
+}
+
+fn read_payloads(paths: &[PathBuf]) -> Result<Vec<Vec<u8>>> {
+    let mut payloads = vec![];
+    for path in paths {
+        for bytes in BytesIter::open(path, Default::default())? {
+            payloads.push(bytes?);
+        }
+    }
+    Ok(payloads)
+}
+
+/// The output prefix in the directory of the inputs.
+fn output_prefix(inputs: &[PathBuf]) -> String {
+    inputs[0]
+        .parent()
+        .unwrap()
+        .join("dev")
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+fn key_counts(report: &SubsetReport) -> HashMap<&str, (u64, u64)> {
+    report
+        .keys
+        .iter()
+        .map(|key| (key.key.as_str(), (key.requested, key.achieved)))
+        .collect()
+}
+
+#[test]
+fn subset_quotas() -> Result<()> {
+    let inputs = labeled_dataset("quotas")?;
+    let spec = SubsetSpec {
+        stratify: Some(
+            Stratify::new(label_of)
+                .with_default_quota(KeyQuota { min: 10, max: None })
+                .with_quota(
+                    "1",
+                    KeyQuota {
+                        min: 10,
+                        max: Some(15),
+                    },
+                ),
+        ),
+        ..SubsetSpec::new(Budget::Records(60))
+    };
+    let report = subset::create_subset(&inputs, &output_prefix(&inputs), spec)?;
+
+    // label 2 is rarer than its minimum, label 1 is capped by its maximum,
+    // and label 0 takes the rest of the budget
+    assert_eq!(
+        key_counts(&report),
+        HashMap::from([("0", (40, 40)), ("1", (15, 15)), ("2", (10, 5))])
+    );
+    let rare = report.keys.iter().find(|key| key.key == "2").unwrap();
+    assert_eq!(rare.available, 5);
+    assert_eq!(rare.shortfall, Some(Shortfall::KeyTooRare));
+    assert!(report
+        .keys
+        .iter()
+        .filter(|key| key.key != "2")
+        .all(|key| key.shortfall.is_none()));
+    assert_eq!(report.num_records, 60);
+
+    // the written records agree with the report
+    let outputs = read_payloads(&report.paths)?;
+    assert_eq!(outputs.len(), 60);
+    let mut written: HashMap<String, u64> = HashMap::new();
+    for bytes in &outputs {
+        *written.entry(label_of(bytes)?).or_default() += 1;
+    }
+    assert_eq!(
+        written,
+        HashMap::from([("0".into(), 40), ("1".into(), 15), ("2".into(), 5)])
+    );
+    Ok(())
+}
+
+#[test]
+fn subset_proportional() -> Result<()> {
+    let inputs = labeled_dataset("proportional")?;
+    let spec = SubsetSpec {
+        stratify: Some(Stratify::new(label_of)),
+        ..SubsetSpec::new(Budget::Records(51))
+    };
+    let report = subset::create_subset(&inputs, &output_prefix(&inputs), spec)?;
+    assert_eq!(
+        key_counts(&report),
+        HashMap::from([("0", (40, 40)), ("1", (10, 10)), ("2", (1, 1))])
+    );
+    Ok(())
+}
+
+#[test]
+fn subset_is_deterministic() -> Result<()> {
+    let inputs = labeled_dataset("deterministic")?;
+    let spec = |seed| SubsetSpec {
+        stratify: Some(Stratify::new(label_of)),
+        seed,
+        ..SubsetSpec::new(Budget::Records(30))
+    };
+
+    let lhs = subset::create_subset(&inputs, &format!("{}-lhs", output_prefix(&inputs)), spec(7))?;
+    let rhs = subset::create_subset(&inputs, &format!("{}-rhs", output_prefix(&inputs)), spec(7))?;
+    let other = subset::create_subset(
+        &inputs,
+        &format!("{}-other", output_prefix(&inputs)),
+        spec(8),
+    )?;
+    assert_eq!(fs::read(&lhs.paths[0])?, fs::read(&rhs.paths[0])?);
+    assert_ne!(fs::read(&lhs.paths[0])?, fs::read(&other.paths[0])?);
+    assert_eq!(lhs.keys, other.keys);
+    Ok(())
+}
+
+#[test]
+fn subset_copies_records_verbatim() -> Result<()> {
+    let inputs = labeled_dataset("verbatim")?;
+    let spec = SubsetSpec {
+        records_per_shard: Some(8),
+        ..SubsetSpec::new(Budget::Records(20))
+    };
+    let prefix = output_prefix(&inputs);
+    let report = subset::create_subset(&inputs, &prefix, spec)?;
+
+    let expect_paths: Vec<PathBuf> = (0..3)
+        .map(|index| format!("{}-{:05}-of-00003", prefix, index).into())
+        .collect();
+    assert_eq!(report.paths, expect_paths);
+    assert_eq!(report.keys.len(), 1);
+    assert_eq!(report.keys[0].key, "");
+
+    // the outputs are a subsequence of the inputs
+    let input_payloads = read_payloads(&inputs)?;
+    let outputs = read_payloads(&report.paths)?;
+    assert_eq!(outputs.len(), 20);
+    let mut remaining = input_payloads.iter();
+    for output in &outputs {
+        ensure!(
+            remaining.any(|input| input == output),
+            "the output record is not a copy of an input record in order"
+        );
+    }
+
+    // the frames are byte-identical
+    let num_bytes: u64 = report
+        .paths
+        .iter()
+        .map(|path| fs::metadata(path).map(|metadata| metadata.len()))
+        .sum::<Result<_, _>>()?;
+    assert_eq!(num_bytes, report.num_bytes);
+    let frame_bytes: u64 = outputs.iter().map(|bytes| bytes.len() as u64 + 16).sum();
+    assert_eq!(num_bytes, frame_bytes);
+    Ok(())
+}
+
+#[test]
+fn subset_byte_budget() -> Result<()> {
+    let inputs = labeled_dataset("bytes")?;
+    let input_bytes: u64 = inputs
+        .iter()
+        .map(|path| fs::metadata(path).map(|metadata| metadata.len()))
+        .sum::<Result<_, _>>()?;
+
+    let report = subset::create_subset(
+        &inputs,
+        &output_prefix(&inputs),
+        SubsetSpec::new(Budget::Bytes(input_bytes / 5)),
+    )?;
+    let num_records: i64 = LABEL_COUNTS.iter().sum();
+    assert!((report.num_records as i64).abs_diff(num_records / 5) <= 1);
+    assert!(report.num_bytes.abs_diff(input_bytes / 5) < input_bytes / 50);
+    Ok(())
+}
+
+#[test]
+fn subset_rejects_excessive_minimums() -> Result<()> {
+    let inputs = labeled_dataset("excessive")?;
+    let spec = SubsetSpec {
+        stratify: Some(Stratify::new(label_of).with_default_quota(KeyQuota { min: 30, max: None })),
+        ..SubsetSpec::new(Budget::Records(60))
+    };
+    let result = subset::create_subset(&inputs, &output_prefix(&inputs), spec);
+    assert!(result.is_err());
+    Ok(())
+}
@@ -1,9 +1,4 @@
-#![cfg(all(
-    feature = "summary",
-    feature = "with-image",
-    feature = "with-tch",
-    feature = "with-ndarray"
-))]
+#![cfg(all(feature = "with-image", feature = "with-tch", feature = "with-ndarray"))]
 
 mod common;
 
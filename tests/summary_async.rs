@@ -1,6 +1,5 @@
 #![cfg(all(
     feature = "async",
-    feature = "summary",
     feature = "with-image",
     feature = "with-tch",
     feature = "with-ndarray"
@@ -0,0 +1,232 @@
+mod common;
+
+use common::*;
+use std::fs;
+use tfrecord::{
+    protobuf::feature::Kind,
+    schema::{self, FeatureSpec, ValueCount, ValueType},
+    synth::{self, CountDistribution, Distribution, GenOptions},
+    Error, Example, ExampleIter,
+};
+
+fn specs() -> Vec<FeatureSpec> {
+    vec![
+        FeatureSpec::new("id", ValueType::I64, ValueCount::Fixed(1)),
+        FeatureSpec::new("score", ValueType::F32, ValueCount::Fixed(4)),
+        FeatureSpec::new(
+            "tokens",
+            ValueType::I64,
+            ValueCount::Var {
+                min: 1,
+                max: Some(10),
+            },
+        ),
+        FeatureSpec::new(
+            "tags",
+            ValueType::Bytes,
+            ValueCount::Var { min: 0, max: None },
+        ),
+    ]
+}
+
+fn options(records: u64, seed: u64) -> GenOptions {
+    let mut options = GenOptions {
+        records,
+        seed,
+        ..Default::default()
+    };
+    options.distributions.insert(
+        "score".into(),
+        Distribution::NormalF32 {
+            mean: 5.0,
+            std_dev: 2.0,
+        },
+    );
+    options.distributions.insert(
+        "tokens".into(),
+        Distribution::Zipf {
+            ranks: 100,
+            exponent: 1.0,
+        },
+    );
+    options.distributions.insert(
+        "tags".into(),
+        Distribution::Dictionary(vec![b"red".to_vec(), b"green".to_vec()]),
+    );
+    options
+        .counts
+        .insert("tags".into(), CountDistribution::Geometric { mean: 2.0 });
+    options
+}
+
+fn values(example: &Example, key: &str) -> Kind {
+    example.features.as_ref().unwrap().feature[key]
+        .kind
+        .clone()
+        .unwrap()
+}
+
+#[test]
+fn synth_deterministic_test() -> Result<()> {
+    let specs = specs();
+    let first: Vec<_> = synth::generate(&specs, options(100, 1))?.collect();
+    let second: Vec<_> = synth::generate(&specs, options(100, 1))?.collect();
+    let other: Vec<_> = synth::generate(&specs, options(100, 2))?.collect();
+    assert_eq!(first.len(), 100);
+    assert_eq!(first, second);
+    assert_ne!(first, other);
+    Ok(())
+}
+
+#[test]
+fn synth_conforms_to_specs_test() -> Result<()> {
+    let specs = specs();
+    let mut max_tags = 0;
+    for example in synth::generate(&specs, options(1000, 3))? {
+        schema::validate(&specs, &example)?;
+        if let Kind::BytesList(list) = values(&example, "tags") {
+            max_tags = max_tags.max(list.value.len());
+            for tag in list.value {
+                assert!(tag == b"red" || tag == b"green");
+            }
+        }
+    }
+    assert!(max_tags > 2);
+
+    // features without distributions
+    let examples: Vec<_> = synth::generate(&specs, GenOptions::default())?.collect();
+    assert_eq!(examples.len(), 1000);
+    for example in &examples {
+        schema::validate(&specs, example)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn synth_distribution_sanity_test() -> Result<()> {
+    let specs = [
+        FeatureSpec::new("normal", ValueType::F32, ValueCount::Fixed(1)),
+        FeatureSpec::new("uniform", ValueType::I64, ValueCount::Fixed(1)),
+        FeatureSpec::new("zipf", ValueType::I64, ValueCount::Fixed(1)),
+    ];
+    let mut options = GenOptions {
+        records: 20_000,
+        seed: 11,
+        ..Default::default()
+    };
+    options.distributions.insert(
+        "normal".into(),
+        Distribution::NormalF32 {
+            mean: 3.0,
+            std_dev: 0.5,
+        },
+    );
+    options.distributions.insert(
+        "uniform".into(),
+        Distribution::UniformI64 { low: -10, high: 10 },
+    );
+    options.distributions.insert(
+        "zipf".into(),
+        Distribution::Zipf {
+            ranks: 10,
+            exponent: 1.0,
+        },
+    );
+
+    let mut normal = vec![];
+    let mut uniform = vec![];
+    let mut zipf = vec![];
+    for example in synth::generate(&specs, options)? {
+        match values(&example, "normal") {
+            Kind::FloatList(list) => normal.push(list.value[0] as f64),
+            kind => panic!("unexpected {:?}", kind),
+        }
+        match values(&example, "uniform") {
+            Kind::Int64List(list) => uniform.push(list.value[0]),
+            kind => panic!("unexpected {:?}", kind),
+        }
+        match values(&example, "zipf") {
+            Kind::Int64List(list) => zipf.push(list.value[0]),
+            kind => panic!("unexpected {:?}", kind),
+        }
+    }
+    let count = normal.len() as f64;
+
+    let mean = normal.iter().sum::<f64>() / count;
+    let variance = normal.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / count;
+    assert!((mean - 3.0).abs() < 0.02, "{}", mean);
+    assert!((variance.sqrt() - 0.5).abs() < 0.02, "{}", variance.sqrt());
+
+    assert!(uniform.iter().all(|x| (-10..=10).contains(x)));
+    assert!(uniform.contains(&-10) && uniform.contains(&10));
+    let mean = uniform.iter().sum::<i64>() as f64 / count;
+    assert!(mean.abs() < 0.2, "{}", mean);
+
+    // the probability of rank 1 is 1 / H(10) = 0.3414
+    assert!(zipf.iter().all(|x| (1..=10).contains(x)));
+    let rank1 = zipf.iter().filter(|&&x| x == 1).count() as f64 / count;
+    assert!((rank1 - 0.3414).abs() < 0.02, "{}", rank1);
+    let rank2 = zipf.iter().filter(|&&x| x == 2).count() as f64 / count;
+    assert!((rank2 - 0.1707).abs() < 0.02, "{}", rank2);
+    Ok(())
+}
+
+#[test]
+fn synth_invalid_options_test() {
+    let specs = specs();
+
+    // a distribution of another value type
+    let mut options = GenOptions::default();
+    options.distributions.insert(
+        "id".into(),
+        Distribution::UniformF32 {
+            low: 0.0,
+            high: 1.0,
+        },
+    );
+    let err = synth::generate(&specs, options).unwrap_err();
+    assert!(matches!(err, Error::ConversionError { .. }), "{}", err);
+
+    // a count distribution of a fixed feature
+    let mut options = GenOptions::default();
+    options
+        .counts
+        .insert("id".into(), CountDistribution::Uniform { min: 1, max: 2 });
+    assert!(synth::generate(&specs, options).is_err());
+
+    // an undeclared feature
+    let mut options = GenOptions::default();
+    options.distributions.insert(
+        "unknown".into(),
+        Distribution::UniformI64 { low: 0, high: 1 },
+    );
+    assert!(synth::generate(&specs, options).is_err());
+}
+
+#[test]
+fn synth_write_sharded_test() -> Result<()> {
+    let specs = specs();
+    let dir = make_temp_dir("synth_write_sharded")?;
+
+    let paths = synth::write_sharded(&specs, options(103, 5), dir.join("a"), 4)?;
+    assert_eq!(paths.len(), 4);
+    let mut examples = vec![];
+    for (index, path) in paths.iter().enumerate() {
+        let shard: Vec<_> =
+            ExampleIter::open(path, Default::default())?.collect::<Result<_, _>>()?;
+        assert_eq!(shard.len(), if index < 3 { 26 } else { 25 });
+        examples.push(shard);
+    }
+    // the examples are assigned in turn
+    let expect: Vec<_> = synth::generate(&specs, options(103, 5))?.collect();
+    for (index, example) in expect.iter().enumerate() {
+        assert_eq!(&examples[index % 4][index / 4], example);
+    }
+
+    // the same options write the same files
+    let again = synth::write_sharded(&specs, options(103, 5), dir.join("b"), 4)?;
+    for (lhs, rhs) in paths.iter().zip(&again) {
+        assert_eq!(fs::read(lhs)?, fs::read(rhs)?);
+    }
+    Ok(())
+}
@@ -0,0 +1,199 @@
+use tfrecord::{protobuf::TensorProto, Error};
+
+#[test]
+fn tensor_proto_shape_test() {
+    // mismatched element counts are errors rather than panics
+    assert!(TensorProto::from_slice([2usize, 3], &[0f32; 5]).is_err());
+    assert!(TensorProto::from_byte_slices([3usize], &[b"ab"]).is_err());
+
+    // claimed shapes overflowing the element count are rejected
+    let err = TensorProto::from_slice([usize::MAX, 2], &[0u8; 2]).unwrap_err();
+    assert!(matches!(err, Error::ConversionError { .. }));
+    assert!(TensorProto::from_slice([1u64 << 40, 1 << 40], &[0u8]).is_err());
+
+    // zero-sized dimensions hold no elements
+    let proto = TensorProto::from_slice([4usize, 0, 2], &[0f32; 0]).unwrap();
+    assert!(proto.tensor_content.is_empty());
+}
+
+#[cfg(feature = "with-tch")]
+mod with_tch {
+    use rand::{prelude::*, rngs::StdRng};
+    use std::panic::{self, AssertUnwindSafe};
+    use tch::{Device, Kind, Tensor};
+    use tfrecord::{
+        protobuf::{summary::Image, TensorProto},
+        protobuf_ext::{
+            ColorSpace, IntoHistogram, IntoImageList, TchChannelOrder, TchTensorAsImage,
+            TchTensorAsImageList, MAX_TENSOR_BYTES,
+        },
+        Error,
+    };
+
+    const KINDS: [Kind; 9] = [
+        Kind::Uint8,
+        Kind::Int8,
+        Kind::Int16,
+        Kind::Int,
+        Kind::Int64,
+        Kind::Half,
+        Kind::Float,
+        Kind::Double,
+        Kind::Bool,
+    ];
+    const COLOR_SPACES: [ColorSpace; 6] = [
+        ColorSpace::Luma,
+        ColorSpace::LumaA,
+        ColorSpace::Rgb,
+        ColorSpace::Rgba,
+        ColorSpace::DigitalYuv,
+        ColorSpace::Bgra,
+    ];
+    const ORDERS: [TchChannelOrder; 2] = [TchChannelOrder::CHW, TchChannelOrder::HWC];
+
+    /// A tensor of random shape, kind and values, which may be non-finite or
+    /// non-contiguous.
+    fn random_tensor(rng: &mut StdRng) -> Tensor {
+        let rank = rng.gen_range(0..=5);
+        let shape: Vec<i64> = (0..rank).map(|_| rng.gen_range(0..=5)).collect();
+        let kind = KINDS[rng.gen_range(0..KINDS.len())];
+        let mut tensor = Tensor::f_randn(&shape, (Kind::Double, Device::Cpu))
+            .unwrap()
+            .f_mul_scalar(rng.gen_range(-300.0..300.0))
+            .unwrap();
+        if rng.gen_bool(0.2) {
+            let fill = [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 0.0][rng.gen_range(0..4)];
+            tensor = tensor.f_fill_(fill).unwrap();
+        }
+        if rank >= 2 && rng.gen_bool(0.3) {
+            tensor = tensor.f_transpose(0, 1).unwrap();
+        }
+        tensor.f_to_kind(kind).unwrap()
+    }
+
+    /// Run every public conversion on the tensor, asserting none panics.
+    fn assert_no_panic(tensor: &Tensor, rng: &mut StdRng) {
+        let color_space = COLOR_SPACES[rng.gen_range(0..COLOR_SPACES.len())];
+        let order = ORDERS[rng.gen_range(0..ORDERS.len())];
+        let description = format!(
+            "kind {:?}, shape {:?}, {:?} {:?}",
+            tensor.kind(),
+            tensor.size(),
+            color_space,
+            order
+        );
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _ = TensorProto::try_from(tensor);
+            let _ = tensor.try_into_histogram();
+            let _ = TchTensorAsImage::new(color_space, order, tensor.shallow_clone())
+                .and_then(Image::try_from);
+            let _ = TchTensorAsImageList::new(color_space, order, tensor.shallow_clone())
+                .and_then(|list| list.into_image_list());
+        }));
+        assert!(result.is_ok(), "panicked on {}", description);
+    }
+
+    #[test]
+    fn random_tensors_test() {
+        let mut rng = StdRng::seed_from_u64(490);
+        for _ in 0..500 {
+            let tensor = random_tensor(&mut rng);
+            assert_no_panic(&tensor, &mut rng);
+        }
+    }
+
+    #[test]
+    fn image_shapes_test() {
+        let mut rng = StdRng::seed_from_u64(4900);
+        // image-like shapes with matching and mismatching channels
+        for _ in 0..200 {
+            let channels = rng.gen_range(0..=5);
+            let (height, width) = (rng.gen_range(0..=4), rng.gen_range(0..=4));
+            let mut shape = if rng.gen_bool(0.5) {
+                vec![channels, height, width]
+            } else {
+                vec![height, width, channels]
+            };
+            if rng.gen_bool(0.5) {
+                shape.insert(0, rng.gen_range(0..=3));
+            }
+            let kind = KINDS[rng.gen_range(0..KINDS.len())];
+            let tensor = Tensor::f_rand(&shape, (Kind::Float, Device::Cpu))
+                .unwrap()
+                .f_to_kind(kind)
+                .unwrap();
+            assert_no_panic(&tensor, &mut rng);
+        }
+    }
+
+    #[test]
+    fn empty_tensors_test() {
+        let empty = Tensor::f_zeros(&[0], (Kind::Float, Device::Cpu)).unwrap();
+        let proto = TensorProto::try_from(&empty).unwrap();
+        assert!(proto.tensor_content.is_empty());
+
+        let zero_height = Tensor::f_zeros(&[3, 0, 4], (Kind::Float, Device::Cpu)).unwrap();
+        let err =
+            TchTensorAsImage::new(ColorSpace::Rgb, TchChannelOrder::CHW, zero_height).unwrap_err();
+        assert!(err.to_string().contains("image size"), "{}", err);
+
+        let empty_batch = Tensor::f_zeros(&[0, 3, 2, 2], (Kind::Uint8, Device::Cpu)).unwrap();
+        let images = TchTensorAsImageList::new(ColorSpace::Rgb, TchChannelOrder::CHW, empty_batch)
+            .unwrap()
+            .into_image_list()
+            .unwrap();
+        assert!(images.is_empty());
+
+        // no finite values to normalize by
+        let nans = Tensor::f_full(&[2, 2, 1], f64::NAN, (Kind::Float, Device::Cpu)).unwrap();
+        let result = TchTensorAsImage::new(ColorSpace::Luma, TchChannelOrder::HWC, nans)
+            .and_then(Image::try_from);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn invalid_properties_test() {
+        let err = TensorProto::try_from(&Tensor::new()).unwrap_err();
+        assert!(err.to_string().contains("tensor"), "{}", err);
+
+        let vector = Tensor::f_zeros(&[4], (Kind::Float, Device::Cpu)).unwrap();
+        let err = TchTensorAsImage::new(ColorSpace::Rgb, TchChannelOrder::CHW, vector).unwrap_err();
+        assert!(err.to_string().contains("tensor rank"), "{}", err);
+
+        let bools = Tensor::f_zeros(&[3, 2, 2], (Kind::Bool, Device::Cpu)).unwrap();
+        let err = TchTensorAsImage::new(ColorSpace::Rgb, TchChannelOrder::CHW, bools).unwrap_err();
+        assert!(err.to_string().contains("tensor kind"), "{}", err);
+
+        let two_channels = Tensor::f_zeros(&[2, 2, 2], (Kind::Float, Device::Cpu)).unwrap();
+        let err =
+            TchTensorAsImage::new(ColorSpace::Rgb, TchChannelOrder::CHW, two_channels).unwrap_err();
+        assert!(err.to_string().contains("image channels"), "{}", err);
+
+        if tch::Cuda::is_available() {
+            let cuda = Tensor::f_zeros(&[3, 2, 2], (Kind::Float, Device::Cuda(0))).unwrap();
+            let err = TensorProto::try_from(&cuda).unwrap_err();
+            assert!(err.to_string().contains("tensor device"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn large_shapes_test() {
+        // a broadcast view claims a huge shape without allocating it
+        let huge = Tensor::f_zeros(&[1, 1], (Kind::Float, Device::Cpu))
+            .unwrap()
+            .f_expand(&[1 << 20, 1 << 20], false)
+            .unwrap();
+        let err = TensorProto::try_from(&huge).unwrap_err();
+        assert!(
+            matches!(err, Error::LimitExceeded { which: "tensor bytes", limit, .. } if limit == MAX_TENSOR_BYTES as u64)
+        );
+        assert!(huge.try_into_histogram().is_err());
+
+        let huge_image = Tensor::f_zeros(&[1, 1, 1], (Kind::Uint8, Device::Cpu))
+            .unwrap()
+            .f_expand(&[3, 1 << 16, 1 << 16], false)
+            .unwrap();
+        assert!(TchTensorAsImage::new(ColorSpace::Rgb, TchChannelOrder::CHW, huge_image).is_err());
+    }
+}
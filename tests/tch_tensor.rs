@@ -0,0 +1,117 @@
+#![cfg(feature = "with-tch")]
+
+mod common;
+
+use common::*;
+use tch::{Device, Kind, Tensor};
+use tfrecord::{
+    protobuf::{DataType, FloatList, Int64List, TensorProto},
+    Feature,
+};
+
+const SHAPES: [&[i64]; 5] = [&[], &[0], &[5], &[2, 3], &[3, 0, 2]];
+
+fn round_trip(tensor: &Tensor) -> Result<()> {
+    let proto = TensorProto::try_from(tensor)?;
+    assert_eq!(
+        proto.shape()?,
+        tensor
+            .size()
+            .iter()
+            .map(|&dim| dim as usize)
+            .collect::<Vec<_>>()
+    );
+    let restored = Tensor::try_from(&proto)?;
+    assert_eq!(restored.kind(), tensor.kind());
+    assert_eq!(restored.size(), tensor.size());
+    assert!(restored.equal(tensor));
+    Ok(())
+}
+
+#[test]
+fn tensor_round_trip_test() -> Result<()> {
+    for shape in SHAPES {
+        let numel = shape.iter().product::<i64>();
+        let values = Tensor::f_arange(numel, (Kind::Double, Device::Cpu))?.f_reshape(shape)?;
+        for kind in [
+            Kind::Uint8,
+            Kind::Int,
+            Kind::Int64,
+            Kind::Float,
+            Kind::Double,
+        ] {
+            round_trip(&values.f_to_kind(kind)?)?;
+        }
+
+        let masks = values.f_remainder(2)?.f_eq(0)?;
+        round_trip(&masks)?;
+        let proto = TensorProto::try_from(&masks)?;
+        assert_eq!(proto.try_dtype()?, DataType::DtBool);
+        assert_eq!(proto.tensor_content.len(), numel as usize);
+    }
+
+    // non-contiguous tensors
+    let transposed = Tensor::f_arange(6, (Kind::Float, Device::Cpu))?
+        .f_reshape(&[2, 3])?
+        .f_transpose(0, 1)?;
+    round_trip(&transposed)?;
+
+    // a scalar in the typed value field
+    let proto = TensorProto {
+        dtype: DataType::DtInt64 as i32,
+        int64_val: vec![7],
+        ..Default::default()
+    };
+    let scalar = Tensor::try_from(proto)?;
+    assert_eq!(scalar.size(), Vec::<i64>::new());
+    assert_eq!(scalar.int64_value(&[]), 7);
+    Ok(())
+}
+
+#[test]
+fn unsupported_dtype_test() -> Result<()> {
+    let strings = TensorProto::from_vec([1usize], vec![b"a".to_vec()])?;
+    let err = Tensor::try_from(&strings).unwrap_err();
+    assert!(err.to_string().contains("DtString"), "{}", err);
+
+    let unknown = TensorProto {
+        dtype: 12345,
+        ..Default::default()
+    };
+    assert!(Tensor::try_from(&unknown).is_err());
+
+    let half = Tensor::f_zeros(&[2], (Kind::Half, Device::Cpu))?;
+    assert!(TensorProto::try_from(&half).is_err());
+    Ok(())
+}
+
+#[test]
+fn tensor_to_feature_test() -> Result<()> {
+    let floats = Tensor::of_slice(&[1.5f64, -2.0, 3.25]);
+    assert_eq!(FloatList::try_from(&floats)?.value, vec![1.5, -2.0, 3.25]);
+    assert_eq!(
+        Feature::try_from(&floats)?,
+        Feature::from_f32_list(vec![1.5, -2.0, 3.25])
+    );
+
+    let bytes = Tensor::of_slice(&[0u8, 255, 7]);
+    assert_eq!(Int64List::try_from(&bytes)?.value, vec![0, 255, 7]);
+    let masks = Tensor::of_slice(&[true, false]);
+    assert_eq!(
+        Feature::try_from(masks)?,
+        Feature::from_i64_list(vec![1, 0])
+    );
+
+    let empty = Tensor::f_zeros(&[0], (Kind::Int64, Device::Cpu))?;
+    assert_eq!(
+        Feature::try_from(&empty)?,
+        Feature::from_i64_list(Vec::<i64>::new())
+    );
+
+    // only 1-D tensors of matching kinds are accepted
+    assert!(Feature::try_from(&Tensor::of_slice(&[1i64, 2]).f_reshape(&[1, 2])?).is_err());
+    assert!(Feature::try_from(&Tensor::from(1i64)).is_err());
+    assert!(FloatList::try_from(&bytes).is_err());
+    assert!(Int64List::try_from(&floats).is_err());
+    Ok(())
+}
@@ -0,0 +1,194 @@
+mod common;
+
+use common::*;
+use tfrecord::{
+    protobuf::{tensor_shape_proto::Dim, DataType, TensorProto, TensorShapeProto},
+    Error,
+};
+
+fn tensor(dtype: DataType, shape: &[i64]) -> TensorProto {
+    TensorProto {
+        dtype: dtype as i32,
+        tensor_shape: Some(TensorShapeProto {
+            dim: shape
+                .iter()
+                .map(|&size| Dim {
+                    size,
+                    name: "".into(),
+                })
+                .collect(),
+            unknown_rank: false,
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn round_trip_test() -> Result<()> {
+    let proto = TensorProto::from_vec([2usize, 3], vec![0.5f32, 1.0, 1.5, 2.0, 2.5, 3.0])?;
+    assert_eq!(proto.try_dtype()?, DataType::DtFloat);
+    assert_eq!(proto.shape()?, vec![2, 3]);
+    assert_eq!(proto.to_vec::<f32>()?, vec![0.5, 1.0, 1.5, 2.0, 2.5, 3.0]);
+    // the packed content is the same as from_slice()
+    assert_eq!(
+        proto,
+        TensorProto::from_slice([2usize, 3], &[0.5f32, 1.0, 1.5, 2.0, 2.5, 3.0])?
+    );
+
+    let proto = TensorProto::from_vec([2usize], vec![-1.0f64, 1e100])?;
+    assert_eq!(proto.to_vec::<f64>()?, vec![-1.0, 1e100]);
+    let proto = TensorProto::from_vec([3usize], vec![i32::MIN, 0, i32::MAX])?;
+    assert_eq!(proto.to_vec::<i32>()?, vec![i32::MIN, 0, i32::MAX]);
+    let proto = TensorProto::from_vec([1usize], vec![i64::MIN])?;
+    assert_eq!(proto.to_vec::<i64>()?, vec![i64::MIN]);
+    let proto = TensorProto::from_vec([2usize, 1], vec![0u8, 255])?;
+    assert_eq!(proto.to_vec::<u8>()?, vec![0, 255]);
+    let proto = TensorProto::from_vec([3usize], vec![true, false, true])?;
+    assert_eq!(proto.tensor_content, vec![1, 0, 1]);
+    assert_eq!(proto.to_vec::<bool>()?, vec![true, false, true]);
+
+    let strings = vec!["".to_string(), "tensor".to_string()];
+    let proto = TensorProto::from_vec([2usize], strings.clone())?;
+    assert_eq!(proto.string_val, vec![b"".to_vec(), b"tensor".to_vec()]);
+    assert_eq!(proto.to_vec::<String>()?, strings);
+    assert_eq!(
+        proto.to_vec::<Vec<u8>>()?,
+        vec![b"".to_vec(), b"tensor".to_vec()]
+    );
+
+    // scalars and empty tensors
+    let proto = TensorProto::from_vec(Vec::<usize>::new(), vec![7i64])?;
+    assert_eq!(proto.shape()?, Vec::<usize>::new());
+    assert_eq!(proto.to_vec::<i64>()?, vec![7]);
+    let proto = TensorProto::from_vec([0usize, 4], Vec::<f32>::new())?;
+    assert_eq!(proto.to_vec::<f32>()?, Vec::<f32>::new());
+
+    assert!(matches!(
+        TensorProto::from_vec([2usize], vec![1f32]),
+        Err(Error::ConversionError { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn typed_vals_test() -> Result<()> {
+    let mut proto = tensor(DataType::DtFloat, &[2]);
+    proto.float_val = vec![1.0, 2.0];
+    assert_eq!(proto.to_vec::<f32>()?, vec![1.0, 2.0]);
+
+    let mut proto = tensor(DataType::DtUint8, &[2]);
+    proto.int_val = vec![3, 255];
+    assert_eq!(proto.to_vec::<u8>()?, vec![3, 255]);
+    // int_val entries out of the range of the type are rejected
+    proto.int_val = vec![3, 256];
+    assert!(proto.to_vec::<u8>().is_err());
+
+    let mut proto = tensor(DataType::DtInt64, &[1]);
+    proto.int64_val = vec![-5];
+    assert_eq!(proto.to_vec::<i64>()?, vec![-5]);
+
+    let mut proto = tensor(DataType::DtBool, &[2]);
+    proto.bool_val = vec![false, true];
+    assert_eq!(proto.to_vec::<bool>()?, vec![false, true]);
+
+    // a scalar without a shape, as written by TensorBoard text summaries
+    let mut proto = TensorProto {
+        dtype: DataType::DtString as i32,
+        ..Default::default()
+    };
+    proto.string_val = vec![b"hello".to_vec()];
+    assert_eq!(proto.to_vec::<String>()?, vec!["hello".to_string()]);
+    proto.string_val = vec![vec![0xff]];
+    assert!(proto.to_vec::<String>().is_err());
+    assert_eq!(proto.to_vec::<Vec<u8>>()?, vec![vec![0xff]]);
+
+    // strings packed in the tensor content
+    let proto = TensorProto::from_byte_slices([3usize], &[&b"a"[..], b"", b"bcd"])?;
+    assert_eq!(proto.to_vec::<String>()?, vec!["a", "", "bcd"]);
+    Ok(())
+}
+
+#[test]
+fn repeated_value_test() -> Result<()> {
+    // in version 0, a single value fills the shape
+    let mut proto = tensor(DataType::DtDouble, &[2, 2]);
+    proto.double_val = vec![0.25];
+    assert_eq!(proto.to_vec::<f64>()?, vec![0.25; 4]);
+
+    let mut proto = tensor(DataType::DtInt32, &[3]);
+    proto.int_val = vec![1, 2];
+    assert!(proto.to_vec::<i32>().is_err());
+    proto.int_val = vec![9];
+    proto.version_number = 1;
+    assert!(proto.to_vec::<i32>().is_err());
+
+    // no values for a non-empty shape
+    let proto = tensor(DataType::DtFloat, &[2]);
+    assert!(proto.to_vec::<f32>().is_err());
+    Ok(())
+}
+
+#[test]
+fn mismatch_test() -> Result<()> {
+    let proto = TensorProto::from_vec([2usize], vec![1i32, 2])?;
+    // bytes are not reinterpreted as another type of the same size
+    let err = proto.to_vec::<f32>().unwrap_err();
+    assert!(matches!(err, Error::ConversionError { .. }));
+    assert!(err
+        .to_string()
+        .contains("expect a DtFloat tensor, but get DtInt32"));
+    assert!(proto.to_vec::<u32>().is_err());
+
+    // the content must hold the elements of the shape
+    let mut proto = tensor(DataType::DtInt32, &[3]);
+    proto.tensor_content = vec![0; 8];
+    assert!(proto.to_vec::<i32>().is_err());
+    proto.tensor_content = vec![0; 13];
+    assert!(proto.to_vec::<i32>().is_err());
+
+    let mut proto = tensor(DataType::DtBool, &[1]);
+    proto.tensor_content = vec![2];
+    assert!(proto.to_vec::<bool>().is_err());
+
+    let mut proto = tensor(DataType::DtFloat, &[1]);
+    proto.tensor_shape.as_mut().unwrap().unknown_rank = true;
+    proto.float_val = vec![1.0];
+    assert!(proto.shape().is_err());
+    assert!(proto.to_vec::<f32>().is_err());
+
+    let proto = TensorProto {
+        dtype: 12345,
+        ..Default::default()
+    };
+    assert!(matches!(
+        proto.to_vec::<f32>(),
+        Err(Error::UnknownEnumValue { .. })
+    ));
+    Ok(())
+}
+
+#[cfg(feature = "with-ndarray")]
+#[test]
+fn ndarray_test() -> Result<()> {
+    use ndarray::{array, ArrayD, IxDyn};
+
+    let array = array![[1f32, 2.0], [3.0, 4.0], [5.0, 6.0]].into_dyn();
+    let proto = TensorProto::from_ndarray(&array)?;
+    assert_eq!(proto.shape()?, vec![3, 2]);
+    assert_eq!(proto.to_ndarray::<f32>()?, array);
+    // the elements are stored in row-major order regardless of the layout
+    let proto = TensorProto::from_ndarray(&array.t())?;
+    assert_eq!(proto.to_vec::<f32>()?, vec![1.0, 3.0, 5.0, 2.0, 4.0, 6.0]);
+
+    let array = array![["a".to_string()], ["b".to_string()]];
+    let proto = TensorProto::from_ndarray(&array)?;
+    assert_eq!(proto.to_ndarray::<String>()?, array.into_dyn());
+
+    let mut proto = tensor(DataType::DtBool, &[2, 3]);
+    proto.bool_val = vec![true];
+    assert_eq!(
+        proto.to_ndarray::<bool>()?,
+        ArrayD::from_elem(IxDyn(&[2, 3]), true)
+    );
+    Ok(())
+}
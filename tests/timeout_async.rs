@@ -0,0 +1,81 @@
+#![cfg(feature = "async")]
+
+use futures::{
+    io::{AsyncRead, AsyncSeek},
+    stream::StreamExt as _,
+};
+use std::{
+    io::SeekFrom,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tfrecord::{
+    indexer::{self, RecordIndexerConfig},
+    io::OpTimeout,
+    BytesStream, Error, RecordReaderConfig,
+};
+
+/// A source whose operations never resolve.
+struct StalledSource;
+
+impl AsyncRead for StalledSource {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        _buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Poll::Pending
+    }
+}
+
+impl AsyncSeek for StalledSource {
+    fn poll_seek(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        _pos: SeekFrom,
+    ) -> Poll<std::io::Result<u64>> {
+        Poll::Pending
+    }
+}
+
+fn assert_timeout<T>(result: Option<Result<T, Error>>, expect_operation: &str) {
+    match result {
+        Some(Err(Error::Timeout { operation, elapsed })) => {
+            assert_eq!(operation, expect_operation);
+            assert_eq!(elapsed, Duration::from_millis(50));
+        }
+        Some(Err(err)) => panic!("unexpected error: {}", err),
+        _ => panic!("the operation did not time out"),
+    }
+}
+
+#[async_std::test]
+async fn stream_timeout_test() -> Result<(), Error> {
+    let op_timeout = Some(OpTimeout::new(Duration::from_millis(50))?);
+
+    let mut stream = BytesStream::from_reader(
+        StalledSource,
+        RecordReaderConfig {
+            op_timeout,
+            ..Default::default()
+        },
+    );
+    assert_timeout(stream.next().await, "read_record");
+
+    let mut stream = Box::pin(indexer::load_reader_async(
+        StalledSource,
+        RecordIndexerConfig {
+            op_timeout,
+            ..Default::default()
+        },
+    ));
+    assert_timeout(stream.next().await, "read_record");
+    Ok(())
+}
+
+#[test]
+fn zero_timeout_test() {
+    assert!(OpTimeout::new(Duration::ZERO).is_err());
+    assert!(OpTimeout::new(Duration::from_nanos(1)).is_ok());
+}
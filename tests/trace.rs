@@ -0,0 +1,315 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    sync::{Arc, Mutex},
+};
+use tfrecord::{
+    indexer::{self, PermutedIterOptions, RecordIndex, RecordIndexerConfig},
+    manifest::{self, ManifestPolicy},
+    record_reader::RecordReaderConfig,
+    samples,
+    trace::{AttributeValue, Operation, SpanHandle, TraceHooks},
+    Example,
+};
+
+/// A span reported to the recording hooks.
+#[derive(Debug, Clone)]
+struct RecordedSpan {
+    operation: Operation,
+    parent: Option<SpanHandle>,
+    start: HashMap<&'static str, AttributeValue>,
+    /// The outcome attributes, or `None` if the span is not ended.
+    end: Option<HashMap<&'static str, AttributeValue>>,
+}
+
+impl RecordedSpan {
+    fn outcome(&self, name: &str) -> Option<AttributeValue> {
+        self.end.as_ref().unwrap().get(name).cloned()
+    }
+}
+
+/// Hooks recording spans in the order of starts, handled by their positions.
+fn recording_hooks() -> (TraceHooks, Arc<Mutex<Vec<RecordedSpan>>>) {
+    let spans = Arc::new(Mutex::new(vec![]));
+    let hooks = TraceHooks {
+        on_operation_start: Some({
+            let spans = spans.clone();
+            Arc::new(move |operation, parent, attributes| {
+                let mut spans = spans.lock().unwrap();
+                spans.push(RecordedSpan {
+                    operation,
+                    parent,
+                    start: attributes.iter().cloned().collect(),
+                    end: None,
+                });
+                SpanHandle(spans.len() as u64 - 1)
+            })
+        }),
+        on_operation_end: Some({
+            let spans = spans.clone();
+            Arc::new(move |handle, attributes| {
+                let span = &mut spans.lock().unwrap()[handle.0 as usize];
+                assert!(span.end.is_none(), "the span is ended twice");
+                span.end = Some(attributes.iter().cloned().collect());
+            })
+        }),
+    };
+    (hooks, spans)
+}
+
+fn count(value: u64) -> Option<AttributeValue> {
+    Some(AttributeValue::U64(value))
+}
+
+fn load_indexes(paths: &[std::path::PathBuf]) -> Result<Vec<RecordIndex>> {
+    Ok(indexer::load_paths(paths, Default::default()).collect::<Result<_, _>>()?)
+}
+
+#[test]
+fn index_spans_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(3, 4)?;
+    let (hooks, spans) = recording_hooks();
+    let config = RecordIndexerConfig {
+        trace: Some(hooks),
+        ..Default::default()
+    };
+    let indexes: Vec<_> = indexer::load_paths(dataset.paths(), config).collect::<Result<_, _>>()?;
+    let payload_bytes: u64 = indexes.iter().map(|index| index.len as u64).sum();
+
+    let spans = spans.lock().unwrap().clone();
+    assert_eq!(spans.len(), 4);
+    let dataset_span = &spans[0];
+    assert_eq!(dataset_span.operation, Operation::IndexDataset);
+    assert_eq!(dataset_span.operation.name(), "tfrecord.index_dataset");
+    assert_eq!(dataset_span.parent, None);
+    assert_eq!(dataset_span.start["files"], AttributeValue::U64(3));
+    assert_eq!(dataset_span.outcome("files"), count(3));
+    assert_eq!(dataset_span.outcome("records"), count(12));
+    assert_eq!(dataset_span.outcome("bytes"), count(payload_bytes));
+    assert_eq!(dataset_span.outcome("error"), None);
+
+    // file spans nest under the dataset span
+    for (span, path) in spans[1..].iter().zip(dataset.paths()) {
+        assert_eq!(span.operation, Operation::IndexFile);
+        assert_eq!(span.parent, Some(SpanHandle(0)));
+        assert_eq!(
+            span.start["path"],
+            AttributeValue::Str(path.display().to_string())
+        );
+        assert_eq!(span.outcome("records"), count(4));
+    }
+    Ok(())
+}
+
+#[test]
+fn index_failure_spans_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(2, 4)?;
+    // truncate the last record of the second file
+    let path = &dataset.paths()[1];
+    let len = fs::metadata(path)?.len();
+    OpenOptions::new()
+        .write(true)
+        .open(path)?
+        .set_len(len - 3)?;
+
+    let (hooks, spans) = recording_hooks();
+    let config = RecordIndexerConfig {
+        trace: Some(hooks),
+        ..Default::default()
+    };
+    let results: Vec<_> = indexer::load_paths(dataset.paths(), config).collect();
+    assert!(results.last().unwrap().is_err());
+
+    let spans = spans.lock().unwrap().clone();
+    assert_eq!(spans.len(), 3);
+    assert_eq!(spans[1].outcome("error"), None);
+    assert_eq!(spans[1].outcome("records"), count(4));
+    let err = results.last().unwrap().as_ref().unwrap_err();
+    for span in [&spans[0], &spans[2]] {
+        assert_eq!(
+            span.outcome("error"),
+            Some(AttributeValue::Str(err.to_string()))
+        );
+        assert_eq!(
+            span.outcome("error.code"),
+            Some(AttributeValue::Str(err.code().to_string()))
+        );
+    }
+    assert_eq!(spans[2].outcome("records"), count(3));
+    assert_eq!(spans[0].outcome("records"), count(7));
+
+    // a missing file fails its span
+    let (hooks, spans) = recording_hooks();
+    let config = RecordIndexerConfig {
+        trace: Some(hooks),
+        ..Default::default()
+    };
+    let missing = dataset.dir().join("missing.tfrecord");
+    let results: Vec<_> = indexer::load_paths([&missing], config).collect();
+    assert!(results[0].is_err());
+    let spans = spans.lock().unwrap().clone();
+    assert_eq!(spans.len(), 2);
+    assert_eq!(spans[1].parent, Some(SpanHandle(0)));
+    assert!(spans.iter().all(|span| span.outcome("error").is_some()));
+    assert_eq!(spans[0].outcome("files"), count(0));
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn index_spans_async_test() -> Result<()> {
+    use futures::stream::{StreamExt as _, TryStreamExt as _};
+
+    let dataset = samples::tiny_dataset(3, 4)?;
+    let (hooks, spans) = recording_hooks();
+    let config = RecordIndexerConfig {
+        trace: Some(hooks),
+        ..Default::default()
+    };
+    let indexes: Vec<_> = indexer::load_paths_async(dataset.paths(), config)
+        .try_collect()
+        .await?;
+    let payload_bytes: u64 = indexes.iter().map(|index| index.len as u64).sum();
+
+    let spans = spans.lock().unwrap().clone();
+    assert_eq!(spans.len(), 4);
+    assert_eq!(spans[0].operation, Operation::IndexDataset);
+    assert_eq!(spans[0].start["files"], AttributeValue::U64(3));
+    assert_eq!(spans[0].outcome("files"), count(3));
+    assert_eq!(spans[0].outcome("records"), count(12));
+    assert_eq!(spans[0].outcome("bytes"), count(payload_bytes));
+    for (span, path) in spans[1..].iter().zip(dataset.paths()) {
+        assert_eq!(span.operation, Operation::IndexFile);
+        assert_eq!(span.parent, Some(SpanHandle(0)));
+        assert_eq!(
+            span.start["path"],
+            AttributeValue::Str(path.display().to_string())
+        );
+        assert_eq!(span.outcome("records"), count(4));
+    }
+
+    // files loaded concurrently share the dataset span, which ends with the last file
+    let (hooks, spans) = recording_hooks();
+    let config = RecordIndexerConfig {
+        trace: Some(hooks),
+        ..Default::default()
+    };
+    let missing = dataset.dir().join("missing.tfrecord");
+    let paths = [&dataset.paths()[0], &missing];
+    let results: Vec<_> = indexer::load_paths_futures(paths, config)
+        .buffered(2)
+        .collect()
+        .await;
+    assert!(results[1].is_err());
+    assert!(spans.lock().unwrap()[0].end.is_none());
+    let num_records = results
+        .into_iter()
+        .next()
+        .unwrap()?
+        .try_collect::<Vec<_>>()
+        .await?
+        .len();
+    assert_eq!(num_records, 4);
+
+    let spans = spans.lock().unwrap().clone();
+    assert_eq!(spans.len(), 3);
+    assert_eq!(spans[0].outcome("files"), count(1));
+    assert_eq!(spans[0].outcome("records"), count(4));
+    assert!(spans[0].outcome("error").is_some());
+    assert_eq!(spans[1].outcome("records"), count(4));
+    assert_eq!(spans[2].parent, Some(SpanHandle(0)));
+    assert!(spans[2].outcome("error").is_some());
+    Ok(())
+}
+
+#[test]
+fn stream_spans_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(2, 5)?;
+    let indexes = load_indexes(dataset.paths())?;
+
+    // a stream dropped early reports the records read so far
+    let (hooks, spans) = recording_hooks();
+    let config = RecordReaderConfig {
+        trace: Some(hooks.clone()),
+        ..Default::default()
+    };
+    let mut iter = indexer::iter_from::<Example>(&indexes, 2, config);
+    iter.next().unwrap()?;
+    iter.next().unwrap()?;
+    assert!(spans.lock().unwrap()[0].end.is_none());
+    drop(iter);
+    {
+        let spans = spans.lock().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].operation, Operation::Stream);
+        assert_eq!(spans[0].start["records"], AttributeValue::U64(8));
+        assert_eq!(spans[0].outcome("records"), count(2));
+        let bytes = (indexes[2].len + indexes[3].len) as u64;
+        assert_eq!(spans[0].outcome("bytes"), count(bytes));
+    }
+
+    // permuted streams fail their spans on errors
+    let path = &dataset.paths()[1];
+    let len = fs::metadata(path)?.len();
+    OpenOptions::new()
+        .write(true)
+        .open(path)?
+        .set_len(len - 3)?;
+    let options = PermutedIterOptions {
+        reader: RecordReaderConfig {
+            trace: Some(hooks),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let results: Vec<_> =
+        indexer::iter_permuted::<Example>(&indexes, vec![9, 0], options)?.collect::<Vec<_>>();
+    assert!(results[0].is_err());
+    assert!(results[1].is_ok());
+    let spans = spans.lock().unwrap().clone();
+    assert_eq!(spans.len(), 2);
+    assert_eq!(spans[1].start["records"], AttributeValue::U64(2));
+    assert_eq!(spans[1].outcome("records"), count(1));
+    assert!(spans[1].outcome("error").is_some());
+    Ok(())
+}
+
+#[test]
+fn verify_spans_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(3, 4)?;
+    let manifest = manifest::generate(dataset.paths(), Default::default())?;
+    fs::remove_file(&dataset.paths()[1])?;
+
+    let (hooks, spans) = recording_hooks();
+    let config = RecordIndexerConfig {
+        trace: Some(hooks),
+        ..Default::default()
+    };
+    let diffs = manifest.verify(ManifestPolicy::Verify, config)?;
+    assert_eq!(diffs.len(), 1);
+
+    let spans = spans.lock().unwrap().clone();
+    assert_eq!(spans.len(), 4);
+    assert_eq!(spans[0].operation, Operation::VerifyManifest);
+    assert_eq!(
+        spans[0].start["policy"],
+        AttributeValue::Str("Verify".into())
+    );
+    assert_eq!(spans[0].outcome("files"), count(3));
+    assert_eq!(spans[0].outcome("mismatches"), count(1));
+    for span in &spans[1..] {
+        assert_eq!(span.operation, Operation::VerifyFile);
+        assert_eq!(span.parent, Some(SpanHandle(0)));
+    }
+    assert_eq!(spans[1].outcome("mismatch"), None);
+    assert_eq!(
+        spans[2].outcome("mismatch"),
+        Some(AttributeValue::Str(diffs[0].to_string()))
+    );
+    Ok(())
+}
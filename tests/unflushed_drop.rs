@@ -0,0 +1,112 @@
+mod common;
+
+use common::*;
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+use tfrecord::{BytesWriter, RecordWriterConfig, UnflushedDrop, UnflushedDropHandler};
+
+/// A config collecting the reports.
+fn collecting_config() -> (RecordWriterConfig, Arc<Mutex<Vec<UnflushedDrop>>>) {
+    let reports = Arc::new(Mutex::new(vec![]));
+    let config = RecordWriterConfig {
+        on_unflushed_drop: Some(UnflushedDropHandler::new({
+            let reports = reports.clone();
+            move |report| reports.lock().unwrap().push(report.clone())
+        })),
+        ..Default::default()
+    };
+    (config, reports)
+}
+
+/// A writer accepting writes and failing flushes.
+struct FailingFlush;
+
+impl Write for FailingFlush {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Err(io::Error::other("disk full"))
+    }
+}
+
+#[test]
+fn sync_writer_reports_failed_flush_on_drop() -> Result<()> {
+    let (config, reports) = collecting_config();
+    let mut writer = BytesWriter::from_writer_with_config(FailingFlush, config.clone())?;
+    writer.send(b"abc".to_vec())?;
+    writer.send(b"de".to_vec())?;
+    drop(writer);
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].writer, "RecordWriter");
+    assert_eq!(reports[0].num_records, 2);
+    assert_eq!(reports[0].num_bytes, 3 + 2 + 2 * 16);
+    assert!(reports[0].flush_error.as_deref() == Some("disk full"));
+    Ok(())
+}
+
+#[test]
+fn sync_writer_flushes_on_drop() -> Result<()> {
+    let (config, reports) = collecting_config();
+    let mut bytes = vec![];
+    let mut writer = BytesWriter::from_writer_with_config(io::BufWriter::new(&mut bytes), config)?;
+    writer.send(b"abc".to_vec())?;
+    drop(writer);
+
+    assert_eq!(bytes.len(), 3 + 16);
+    assert!(reports.lock().unwrap().is_empty());
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn async_writer_reports_unflushed_drop() -> Result<()> {
+    use tfrecord::BytesAsyncWriter;
+
+    let (config, reports) = collecting_config();
+
+    // dropped without flush
+    let mut writer = BytesAsyncWriter::from_writer_with_config(vec![], config.clone())?;
+    writer.send(b"abc".to_vec()).await?;
+    drop(writer);
+    {
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].writer, "RecordAsyncWriter");
+        assert_eq!(reports[0].num_records, 1);
+        assert_eq!(reports[0].num_bytes, 3 + 16);
+        assert_eq!(reports[0].flush_error, None);
+    }
+
+    // flushed, then abandoned with unflushed records
+    let mut writer = BytesAsyncWriter::from_writer_with_config(vec![], config.clone())?;
+    writer.send(b"abc".to_vec()).await?;
+    writer.flush().await?;
+    drop(writer);
+    let mut writer = BytesAsyncWriter::from_writer_with_config(vec![], config)?;
+    writer.send(b"abc".to_vec()).await?;
+    writer.abandon();
+    assert_eq!(reports.lock().unwrap().len(), 1);
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+#[cfg_attr(debug_assertions, should_panic(expected = "unflushed records"))]
+async fn async_writer_panics_on_unflushed_drop() {
+    use tfrecord::BytesAsyncWriter;
+
+    let (config, _reports) = collecting_config();
+    let config = RecordWriterConfig {
+        panic_on_unflushed_drop: true,
+        ..config
+    };
+    let mut writer = BytesAsyncWriter::from_writer_with_config(vec![], config).unwrap();
+    writer.send(b"abc".to_vec()).await.unwrap();
+    drop(writer);
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use common::*;
+use std::{fs, path::PathBuf};
+use tfrecord::{
+    format::{self, FileFormat},
+    indexer::{self, RecordIndexerConfig, SkipReason},
+    BytesWriter, Error,
+};
+
+/// Write two plain files and a file declaring a fake encryption framing version.
+fn make_dataset(name: &str) -> Result<Vec<PathBuf>> {
+    let dir = make_temp_dir(&format!("unsupported_format/{}", name))?;
+
+    let plain = |file_name: &str| -> Result<PathBuf> {
+        let path = dir.join(file_name);
+        let mut writer = BytesWriter::create(&path)?;
+        writer.send(b"plain".to_vec())?;
+        writer.flush()?;
+        Ok(path)
+    };
+    let first = plain("0.tfrecord")?;
+    let unsupported = dir.join("1.tfrecord");
+    fs::write(&unsupported, b"TFRECENC\x63 rest of a future header")?;
+    let last = plain("2.tfrecord")?;
+    Ok(vec![first, unsupported, last])
+}
+
+#[test]
+fn detect_format_test() -> Result<()> {
+    let paths = make_dataset("detect")?;
+    assert_eq!(format::detect_file_format(&paths[0])?, FileFormat::Plain);
+    assert_eq!(
+        format::detect_file_format(&paths[1])?,
+        FileFormat::Encrypted { version: 0x63 }
+    );
+
+    // a plain record whose length starts with the GZIP magic bytes
+    let dir = paths[0].parent().unwrap();
+    let path = dir.join("gzip_like.tfrecord");
+    let mut writer = BytesWriter::create(&path)?;
+    writer.send(vec![0; 0x8b1f])?;
+    writer.flush()?;
+    assert_eq!(format::detect_file_format(&path)?, FileFormat::Plain);
+
+    // compressed files
+    let path = dir.join("zstd.tfrecord");
+    fs::write(&path, [0x28, 0xb5, 0x2f, 0xfd, 0, 0, 0, 0, 0, 0, 0, 0])?;
+    assert_eq!(format::detect_file_format(&path)?, FileFormat::Zstd);
+    Ok(())
+}
+
+#[test]
+fn reject_unsupported_test() -> Result<()> {
+    let paths = make_dataset("reject")?;
+    let error = indexer::load_paths(&paths, Default::default())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap_err();
+    assert!(matches!(
+        &error,
+        Error::Unsupported { capability, feature: None }
+            if capability == "encryption framing version 99"
+    ));
+
+    let error = indexer::load_paths_filtered(&paths, Default::default(), &[]).unwrap_err();
+    assert!(matches!(error, Error::Unsupported { .. }));
+    Ok(())
+}
+
+#[test]
+fn skip_unsupported_test() -> Result<()> {
+    let paths = make_dataset("skip")?;
+    let config = RecordIndexerConfig {
+        skip_unsupported: true,
+        ..Default::default()
+    };
+
+    let indexes: Vec<_> = indexer::load_paths(&paths, config.clone()).collect::<Result<_, _>>()?;
+    assert_eq!(indexes.len(), 2);
+    assert_eq!(*indexes[0].path, paths[0]);
+    assert_eq!(*indexes[1].path, paths[2]);
+
+    let filtered = indexer::load_paths_filtered(&paths, config, &[])?;
+    assert_eq!(filtered.indexes, indexes);
+    assert_eq!(filtered.skipped.len(), 1);
+    assert_eq!(*filtered.skipped[0].path, paths[1]);
+    assert!(matches!(
+        &filtered.skipped[0].reason,
+        SkipReason::Unsupported(unsupported) if unsupported.feature.is_none()
+    ));
+    Ok(())
+}
+
+#[cfg(not(feature = "encryption"))]
+#[test]
+fn missing_feature_test() -> Result<()> {
+    let path = make_temp_dir("unsupported_format/missing_feature")?.join("encrypted.tfrecord");
+    fs::write(&path, b"TFRECENC\x01 rest of the header")?;
+
+    let error = indexer::load_file(&path, Default::default()).err().unwrap();
+    assert!(matches!(
+        &error,
+        Error::Unsupported {
+            feature: Some("encryption"),
+            ..
+        }
+    ));
+    assert_eq!(
+        error.to_string(),
+        "[TFR0012] unsupported encryption: requires the `encryption` cargo feature"
+    );
+    Ok(())
+}
+
+#[cfg(not(feature = "gzip"))]
+#[test]
+fn missing_gzip_feature_test() -> Result<()> {
+    let config = tfrecord::RecordReaderConfig {
+        compression: tfrecord::compression::Compression::Gzip,
+        ..Default::default()
+    };
+    let mut records = tfrecord::BytesIter::from_bytes(vec![0x1f, 0x8b], config);
+    assert!(matches!(
+        records.next(),
+        Some(Err(Error::Unsupported {
+            feature: Some("gzip"),
+            ..
+        }))
+    ));
+    assert!(records.next().is_none());
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn skip_unsupported_async_test() -> Result<()> {
+    use futures::TryStreamExt as _;
+
+    let paths = make_dataset("skip_async")?;
+    let config = RecordIndexerConfig {
+        skip_unsupported: true,
+        ..Default::default()
+    };
+    let indexes: Vec<_> = indexer::load_paths_async(paths.clone(), config)
+        .try_collect()
+        .await?;
+    assert_eq!(indexes.len(), 2);
+
+    let error = indexer::load_file_async(&paths[1], Default::default())
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(error, Error::Unsupported { .. }));
+    Ok(())
+}
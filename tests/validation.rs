@@ -0,0 +1,280 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use prost::Message as _;
+use tfrecord::{
+    protobuf::{FeatureLists, Features, SequenceExample},
+    samples,
+    schema::ValueType,
+    validation::{
+        self, ExampleValidator, Location, SequenceExampleValidator, ValidationError,
+        ValidationOptions, Violation,
+    },
+    BytesWriter, Error, Example, Feature,
+};
+
+/// A sequence example with an `id` context feature, without the checks of the builder.
+fn sequence_example(frames: Vec<(&str, Feature)>) -> SequenceExample {
+    let mut feature_lists = FeatureLists::default();
+    for (key, feature) in frames {
+        feature_lists
+            .feature_list
+            .entry(key.to_string())
+            .or_default()
+            .feature
+            .push(feature);
+    }
+    SequenceExample {
+        context: Some(Features {
+            feature: [("id".to_string(), Feature::from_i64_list(vec![1]))]
+                .into_iter()
+                .collect(),
+        }),
+        feature_lists: Some(feature_lists),
+    }
+}
+
+#[test]
+fn sequence_example_kinds_test() -> Result<()> {
+    let valid = sequence_example(vec![
+        ("audio", Feature::from_f32_list(vec![0.0, 1.0])),
+        ("audio", Feature::from_f32_list(vec![2.0])),
+        ("words", Feature::from_bytes_list(vec![b"a".to_vec()])),
+    ]);
+    assert_eq!(SequenceExampleValidator::new().validate(&valid), Ok(()));
+
+    // frames take the kind of the first frame without an expected kind
+    let mixed = sequence_example(vec![
+        ("audio", Feature::from_f32_list(vec![0.0])),
+        ("audio", Feature::from_f32_list(vec![1.0])),
+        ("audio", Feature::from_i64_list(vec![2])),
+        ("audio", Feature { kind: None }),
+    ]);
+    let validator = SequenceExampleValidator::new();
+    assert_eq!(
+        validator.violations(&mixed),
+        [
+            ValidationError::KindMismatch {
+                key: "audio".into(),
+                location: Location::Frame(2),
+                expected: ValueType::F32,
+                found: Some(ValueType::I64),
+            },
+            ValidationError::KindMismatch {
+                key: "audio".into(),
+                location: Location::Frame(3),
+                expected: ValueType::F32,
+                found: None,
+            },
+        ]
+    );
+    let err = validator.validate(&mixed).unwrap_err();
+    assert_eq!(err.key(), "audio");
+    assert_eq!(err.frame(), Some(2));
+    assert_eq!(
+        err.to_string(),
+        "the frame 2 of the feature list 'audio' expects F32 values, but found I64"
+    );
+
+    // expected kinds apply to every frame and context features
+    let validator = SequenceExampleValidator::new()
+        .with_context_kind("id", ValueType::Bytes)
+        .with_feature_list_kind("words", ValueType::I64);
+    assert_eq!(
+        validator.violations(&valid),
+        [
+            ValidationError::KindMismatch {
+                key: "id".into(),
+                location: Location::Context,
+                expected: ValueType::Bytes,
+                found: Some(ValueType::I64),
+            },
+            ValidationError::KindMismatch {
+                key: "words".into(),
+                location: Location::Frame(0),
+                expected: ValueType::I64,
+                found: Some(ValueType::Bytes),
+            },
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn sequence_example_frame_sizes_test() -> Result<()> {
+    let ragged = sequence_example(vec![
+        ("audio", Feature::from_f32_list(vec![0.0, 1.0])),
+        ("audio", Feature::from_f32_list(vec![2.0, 3.0])),
+        ("audio", Feature::from_f32_list(vec![4.0])),
+        ("audio", Feature::from_i64_list(vec![5])),
+    ]);
+    assert_eq!(
+        SequenceExampleValidator::new()
+            .violations(&ragged)
+            .iter()
+            .map(|err| err.frame())
+            .collect::<Vec<_>>(),
+        [Some(3)]
+    );
+
+    // frames of another kind are not counted twice
+    let validator = SequenceExampleValidator::new().with_same_frame_sizes(true);
+    let violations = validator.violations(&ragged);
+    assert_eq!(
+        violations[0],
+        ValidationError::FrameSizeMismatch {
+            key: "audio".into(),
+            frame: 2,
+            expected: 2,
+            found: 1,
+        }
+    );
+    assert_eq!(violations.len(), 2);
+    assert_eq!(violations[1].frame(), Some(3));
+    assert_eq!(
+        violations[0].to_string(),
+        "the frame 2 of the feature list 'audio' has 1 values, but the first frame has 2"
+    );
+    Ok(())
+}
+
+#[test]
+fn example_kinds_test() -> Result<()> {
+    let example = samples::example(0);
+    assert_eq!(ExampleValidator::new().validate(&example), Ok(()));
+
+    let validator = ExampleValidator::new()
+        .with_kind("id", ValueType::I64)
+        .with_kind("name", ValueType::F32);
+    let err = validator.validate(&example).unwrap_err();
+    assert_eq!(
+        err,
+        ValidationError::KindMismatch {
+            key: "name".into(),
+            location: Location::Feature,
+            expected: ValueType::F32,
+            found: Some(ValueType::Bytes),
+        }
+    );
+    assert_eq!(err.frame(), None);
+    Ok(())
+}
+
+#[test]
+fn validate_records_test() -> Result<()> {
+    // the kinds of the first record are expected of later ones
+    let records = [
+        sequence_example(vec![("audio", Feature::from_f32_list(vec![0.0]))]),
+        sequence_example(vec![("audio", Feature::from_f32_list(vec![1.0]))]),
+        sequence_example(vec![("audio", Feature::from_i64_list(vec![2]))]),
+        sequence_example(vec![("audio", Feature::from_i64_list(vec![3]))]),
+    ];
+    let report = validation::validate_records(
+        &SequenceExampleValidator::new(),
+        records.iter().cloned().map(Ok),
+        1,
+    )?;
+    assert_eq!(report.num_records, 4);
+    assert_eq!(report.num_violations, 2);
+    assert!(!report.is_valid());
+    assert_eq!(
+        report.violations,
+        [Violation {
+            path: None,
+            record: 2,
+            error: ValidationError::KindMismatch {
+                key: "audio".into(),
+                location: Location::Frame(0),
+                expected: ValueType::F32,
+                found: Some(ValueType::I64),
+            },
+        }]
+    );
+
+    // errors of records fail the validation
+    let result = validation::validate_records(
+        &SequenceExampleValidator::new(),
+        [Ok(records[0].clone()), Err(Error::UnexpectedEof)],
+        1,
+    );
+    assert!(matches!(result, Err(Error::UnexpectedEof)));
+    Ok(())
+}
+
+#[test]
+fn validate_files_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 0)?;
+    let first: Example = vec![("label".into(), Feature::from_i64_list(vec![1]))]
+        .into_iter()
+        .collect();
+    let second: Example = vec![("label".into(), Feature::from_f32_list(vec![1.0]))]
+        .into_iter()
+        .collect();
+
+    let paths: Vec<_> = (0..2)
+        .map(|index| -> Result<_> {
+            let path = dataset.dir().join(format!("part-{}.tfrecord", index));
+            let mut writer = BytesWriter::create(&path)?;
+            writer.send(first.encode_to_vec())?;
+            writer.send(second.encode_to_vec())?;
+            writer.flush()?;
+            Ok(path)
+        })
+        .collect::<Result<_>>()?;
+
+    let report = validation::validate_files(
+        &ExampleValidator::new(),
+        &paths,
+        ValidationOptions::default(),
+    )?;
+    assert_eq!(report.num_records, 4);
+    let locations: Vec<_> = report
+        .violations
+        .iter()
+        .map(|violation| (violation.path.clone().unwrap(), violation.record))
+        .collect();
+    assert_eq!(locations, [(paths[0].clone(), 1), (paths[1].clone(), 1)]);
+    assert!(report.violations[0]
+        .to_string()
+        .ends_with("record 1: the feature 'label' expects I64 values, but found F32"));
+
+    // undecodable records fail with their location
+    let path = dataset.dir().join("corrupt.tfrecord");
+    let mut writer = BytesWriter::create(&path)?;
+    writer.send(vec![0xff])?;
+    writer.flush()?;
+    let err = validation::validate_files(
+        &SequenceExampleValidator::new(),
+        [&path],
+        ValidationOptions::default(),
+    )
+    .unwrap_err();
+    assert!(
+        matches!(err, Error::RecordFailed { index: 0, .. }),
+        "{}",
+        err
+    );
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn validate_stream_test() -> Result<()> {
+    let records = [
+        sequence_example(vec![("audio", Feature::from_f32_list(vec![0.0, 1.0]))]),
+        sequence_example(vec![("audio", Feature::from_f32_list(vec![2.0]))]),
+    ];
+    let validator = SequenceExampleValidator::new().with_same_frame_sizes(true);
+    let report = validation::validate_stream(
+        &validator,
+        futures::stream::iter(records.iter().cloned().map(Ok)),
+        10,
+    )
+    .await?;
+    // frame sizes are checked within records
+    assert!(report.is_valid());
+    assert_eq!(report.num_records, 2);
+    Ok(())
+}
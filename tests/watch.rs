@@ -0,0 +1,162 @@
+mod common;
+
+use common::*;
+use std::{
+    fs,
+    io::Write as _,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+use tfrecord::{
+    indexer::{LiveIndexes, WatchOptions},
+    BytesWriter,
+};
+
+fn make_dir(name: &str) -> Result<PathBuf> {
+    make_temp_dir(&format!("watch/{}", name))
+}
+
+/// The encoded file of records `[shard, index]`.
+fn shard_bytes(shard: u8, num_records: u8) -> Result<Vec<u8>> {
+    let mut writer = BytesWriter::from_writer(vec![])?;
+    for index in 0..num_records {
+        writer.send(vec![shard, index])?;
+    }
+    Ok(writer.into_inner())
+}
+
+fn append(path: &Path, bytes: &[u8]) -> Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+#[test]
+fn partial_file_waits_to_settle_test() -> Result<()> {
+    let dir = make_dir("partial")?;
+    let settle_time = Duration::from_millis(200);
+    let mut live = LiveIndexes::watch_directory(
+        &dir,
+        WatchOptions {
+            settle_time,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(live.files_discovered(), 0);
+
+    let bytes = shard_bytes(0, 4)?;
+    let path = dir.join("0.tfrecord");
+    append(&path, &bytes[..bytes.len() / 2 + 3])?;
+    assert_eq!(live.poll()?, 0);
+    assert_eq!(live.files_pending(), 1);
+
+    // the file grows before settling, which restarts the settle time
+    thread::sleep(settle_time / 2);
+    append(&path, &bytes[bytes.len() / 2 + 3..])?;
+    thread::sleep(settle_time / 2);
+    assert_eq!(live.poll()?, 0);
+    assert_eq!(live.files_discovered(), 0);
+
+    thread::sleep(settle_time);
+    assert_eq!(live.poll()?, 1);
+    assert_eq!(live.files_discovered(), 1);
+    assert_eq!(live.files_pending(), 0);
+    assert_eq!(live.len(), 4);
+
+    // indexed files are not indexed again
+    thread::sleep(settle_time);
+    assert_eq!(live.poll()?, 0);
+    assert_eq!(live.len(), 4);
+    Ok(())
+}
+
+#[test]
+fn shards_over_time_yield_once_test() -> Result<()> {
+    let dir = make_dir("over_time")?;
+    let num_shards = 4u8;
+    let per_shard = 5u8;
+
+    let writer = {
+        let dir = dir.clone();
+        thread::spawn(move || -> Result<()> {
+            for shard in 0..num_shards {
+                let bytes = shard_bytes(shard, per_shard)?;
+                let path = dir.join(format!("part-{}.tfrecord", shard));
+                // written in two steps to leave a partial file for a while
+                let (head, tail) = bytes.split_at(bytes.len() / 2 + 1);
+                append(&path, head)?;
+                thread::sleep(Duration::from_millis(30));
+                append(&path, tail)?;
+                thread::sleep(Duration::from_millis(60));
+            }
+            Ok(())
+        })
+    };
+
+    let mut live = LiveIndexes::watch_directory(
+        &dir,
+        WatchOptions {
+            file_name_prefix: "part-".into(),
+            poll_interval: Duration::from_millis(10),
+            settle_time: Duration::from_millis(150),
+            max_idle: Some(Duration::from_secs(2)),
+            ..Default::default()
+        },
+    )?;
+    let mut records: Vec<Vec<u8>> = live.records().collect::<tfrecord::Result<_>>()?;
+    writer.join().unwrap()?;
+
+    assert_eq!(live.files_discovered(), num_shards as usize);
+    assert_eq!(live.num_yielded(), records.len());
+    records.sort();
+    let expect: Vec<Vec<u8>> = (0..num_shards)
+        .flat_map(|shard| (0..per_shard).map(move |index| vec![shard, index]))
+        .collect();
+    assert_eq!(records, expect);
+
+    // records already yielded are not yielded again
+    assert_eq!(live.records::<Vec<u8>>().count(), 0);
+    Ok(())
+}
+
+#[test]
+fn ignored_and_removed_files_test() -> Result<()> {
+    let dir = make_dir("ignored")?;
+    let mut live = LiveIndexes::watch_directory(
+        &dir,
+        WatchOptions {
+            file_name_prefix: "train-".into(),
+            settle_time: Duration::ZERO,
+            ..Default::default()
+        },
+    )?;
+
+    append(&dir.join("train-0.tfrecord"), &shard_bytes(0, 2)?)?;
+    append(&dir.join("eval-0.tfrecord"), &shard_bytes(1, 2)?)?;
+    append(&dir.join("train-1.tfrecord"), &shard_bytes(2, 2)?)?;
+    assert_eq!(live.poll()?, 0);
+    assert_eq!(live.files_pending(), 2);
+
+    // a pending file removed before settling is forgotten
+    fs::remove_file(dir.join("train-1.tfrecord"))?;
+    assert_eq!(live.poll()?, 1);
+    assert_eq!(live.files_pending(), 0);
+    assert_eq!(live.files_discovered(), 1);
+    let records: Vec<Vec<u8>> = live.records().take(2).collect::<tfrecord::Result<_>>()?;
+    assert_eq!(records, [vec![0, 0], vec![0, 1]]);
+
+    // an indexed file removed before reading fails by the staleness guard
+    append(&dir.join("train-2.tfrecord"), &shard_bytes(3, 2)?)?;
+    live.poll()?;
+    assert_eq!(live.poll()?, 1);
+    fs::remove_file(dir.join("train-2.tfrecord"))?;
+    let mut records = live.records::<Vec<u8>>();
+    assert!(matches!(records.next(), Some(Err(_))));
+    assert!(records.next().is_none());
+    Ok(())
+}
@@ -0,0 +1,269 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use tfrecord::{
+    audit::{self, Anomaly, AnomalyExemplar, AuditOptions, DecodeReport, DEEP_NESTING_DEPTH},
+    limits::Limits,
+    samples, BytesWriter, Example, Feature,
+};
+
+fn varint(mut value: u64) -> Vec<u8> {
+    let mut bytes = vec![];
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return bytes;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn key(tag: u64, wire_type: u64) -> Vec<u8> {
+    varint(tag << 3 | wire_type)
+}
+
+/// A length-delimited field.
+fn nested(tag: u64, body: &[u8]) -> Vec<u8> {
+    [key(tag, 2), varint(body.len() as u64), body.to_vec()].concat()
+}
+
+/// An `Int64List` feature with a packed body.
+fn int64_feature(packed: &[u8]) -> Vec<u8> {
+    nested(3, &nested(1, packed))
+}
+
+/// A feature map entry.
+fn entry(name: &str, feature: Option<&[u8]>) -> Vec<u8> {
+    let mut body = nested(1, name.as_bytes());
+    if let Some(feature) = feature {
+        body.extend(nested(2, feature));
+    }
+    nested(1, &body)
+}
+
+/// An example of the map entries.
+fn example(entries: &[Vec<u8>]) -> Vec<u8> {
+    nested(1, &entries.concat())
+}
+
+/// Decode the bytes with a fresh report.
+fn report_of(bytes: &[u8]) -> Result<(Example, DecodeReport)> {
+    let mut report = DecodeReport::default();
+    let example = Example::decode_with_report(bytes, &Limits::default(), &mut report, 7)?;
+    assert_eq!(report.num_records, 1);
+    Ok((example, report))
+}
+
+fn exemplar(anomaly: Anomaly, key: Option<&str>) -> AnomalyExemplar {
+    AnomalyExemplar {
+        anomaly,
+        path: None,
+        record: 7,
+        key: key.map(str::to_string),
+    }
+}
+
+#[test]
+fn valid_example_test() -> Result<()> {
+    let bytes = example(&[entry("a", Some(&int64_feature(&varint(300))))]);
+    let (decoded, report) = report_of(&bytes)?;
+    assert_eq!(decoded.get_i64s("a")?, [300]);
+    assert_eq!(report.counts.total(), 0);
+    assert!(report.exemplars.is_empty());
+
+    let (decoded, report) = report_of(&samples::example(3).encode_canonical_to_vec())?;
+    assert_eq!(decoded, samples::example(3));
+    assert_eq!(report.counts.total(), 0);
+    Ok(())
+}
+
+#[test]
+fn unknown_fields_test() -> Result<()> {
+    // an unknown field of the example and of a feature
+    let feature = [int64_feature(&varint(1)), key(9, 0), varint(5)].concat();
+    let bytes = [
+        example(&[entry("a", Some(&feature))]),
+        key(5, 5),
+        vec![0; 4],
+    ]
+    .concat();
+    let (decoded, report) = report_of(&bytes)?;
+    assert_eq!(decoded.get_i64s("a")?, [1]);
+    assert_eq!(report.counts.get(Anomaly::UnknownField), 2);
+    assert_eq!(
+        report.exemplars,
+        [
+            exemplar(Anomaly::UnknownField, Some("a")),
+            exemplar(Anomaly::UnknownField, None),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn duplicate_keys_test() -> Result<()> {
+    let bytes = example(&[
+        entry("a", Some(&int64_feature(&varint(1)))),
+        entry("b", Some(&int64_feature(&varint(2)))),
+        entry("a", Some(&int64_feature(&varint(3)))),
+    ]);
+    let (decoded, report) = report_of(&bytes)?;
+    // the last entry wins
+    assert_eq!(decoded.get_i64s("a")?, [3]);
+    assert_eq!(report.counts.duplicate_keys, 1);
+    assert_eq!(
+        report.exemplars,
+        [exemplar(Anomaly::DuplicateKey, Some("a"))]
+    );
+    Ok(())
+}
+
+#[test]
+fn kind_not_set_test() -> Result<()> {
+    // an empty feature, and an entry without a feature
+    let bytes = example(&[entry("empty", Some(&[])), entry("missing", None)]);
+    let (decoded, report) = report_of(&bytes)?;
+    assert_eq!(
+        decoded.features.unwrap().feature["empty"],
+        Feature::default()
+    );
+    assert_eq!(report.counts.kind_not_set, 2);
+    assert_eq!(
+        report.exemplars,
+        [
+            exemplar(Anomaly::KindNotSet, Some("empty")),
+            exemplar(Anomaly::KindNotSet, Some("missing")),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn deep_nesting_test() -> Result<()> {
+    // groups nested beyond the threshold in an unknown field of the example
+    let levels = DEEP_NESTING_DEPTH + 2;
+    let mut group = vec![];
+    for _ in 0..levels {
+        group.extend(key(6, 3));
+    }
+    group.extend([key(1, 0), varint(1)].concat());
+    for _ in 0..levels {
+        group.extend(key(6, 4));
+    }
+    // a shallow group is not deep
+    let shallow = [key(7, 3), key(7, 4)].concat();
+    let bytes = [example(&[]), group, shallow].concat();
+
+    let (_, report) = report_of(&bytes)?;
+    assert_eq!(report.counts.unknown_fields, 2);
+    assert_eq!(report.counts.deep_nesting, 1);
+    assert_eq!(
+        report.exemplars,
+        [
+            exemplar(Anomaly::UnknownField, None),
+            exemplar(Anomaly::DeepNesting, None),
+            exemplar(Anomaly::UnknownField, None),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn non_minimal_varints_test() -> Result<()> {
+    // 1 encoded in two bytes, and a length in three bytes
+    let packed = [vec![0x81, 0x00], varint(2)].concat();
+    let list = [
+        key(1, 2),
+        vec![packed.len() as u8 | 0x80, 0x80, 0x00],
+        packed,
+    ]
+    .concat();
+    let bytes = example(&[entry("a", Some(&nested(3, &list)))]);
+    let (decoded, report) = report_of(&bytes)?;
+    assert_eq!(decoded.get_i64s("a")?, [1, 2]);
+    assert_eq!(report.counts.non_minimal_varints, 2);
+    assert_eq!(
+        report.exemplars,
+        [
+            exemplar(Anomaly::NonMinimalVarint, Some("a")),
+            exemplar(Anomaly::NonMinimalVarint, Some("a")),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn max_exemplars_test() -> Result<()> {
+    let bytes = example(&[entry("a", None), entry("b", None), entry("c", None)]);
+    let mut report = DecodeReport::new(2);
+    Example::decode_with_report(&bytes, &Limits::default(), &mut report, 0)?;
+    assert_eq!(report.counts.kind_not_set, 3);
+    assert_eq!(report.exemplars.len(), 2);
+
+    // merging keeps the cap of the receiver
+    let mut total = DecodeReport::new(3);
+    total.merge(report.clone());
+    total.merge(report);
+    assert_eq!(total.counts.kind_not_set, 6);
+    assert_eq!(total.num_records, 2);
+    assert_eq!(total.exemplars.len(), 3);
+
+    // failed records are not counted
+    let mut report = DecodeReport::default();
+    assert!(
+        Example::decode_with_report(&[0x0a, 0x05], &Limits::default(), &mut report, 0).is_err()
+    );
+    assert_eq!(report, DecodeReport::default());
+    Ok(())
+}
+
+#[test]
+fn dataset_audit_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 0)?;
+    let anomalous = example(&[entry("a", None)]);
+    let valid = samples::example(0).encode_canonical_to_vec();
+
+    // the anomalous record at index 1 of every file
+    let paths: Vec<_> = (0..5)
+        .map(|index| -> Result<_> {
+            let path = dataset.dir().join(format!("part-{}.tfrecord", index));
+            let mut writer = BytesWriter::create(&path)?;
+            writer.send(valid.clone())?;
+            writer.send(anomalous.clone())?;
+            writer.flush()?;
+            Ok(path)
+        })
+        .collect::<Result<_>>()?;
+
+    let options = AuditOptions {
+        max_exemplars: 3,
+        ..Default::default()
+    };
+    let report = audit::wire_anomalies(&paths, options)?;
+    assert_eq!(report.num_records, 10);
+    assert_eq!(report.counts.kind_not_set, 5);
+    let locations: Vec<_> = report
+        .exemplars
+        .iter()
+        .map(|exemplar| (exemplar.path.clone().unwrap(), exemplar.record))
+        .collect();
+    assert_eq!(
+        locations,
+        [
+            (paths[0].clone(), 1),
+            (paths[1].clone(), 1),
+            (paths[2].clone(), 1)
+        ]
+    );
+
+    #[cfg(feature = "with-serde")]
+    {
+        let json = serde_json::to_string(&report)?;
+        assert_eq!(serde_json::from_str::<DecodeReport>(&json)?, report);
+    }
+    Ok(())
+}
@@ -0,0 +1,130 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use std::io::{self, Write};
+use tfrecord::{samples, Error, Example, ExampleIter, ExampleWriter};
+
+/// The number of examples streamed by the round trips.
+const NUM_EXAMPLES: usize = 100_000;
+
+/// A writer failing once the capacity is exhausted.
+struct FailingWriter {
+    bytes: Vec<u8>,
+    capacity: usize,
+}
+
+impl Write for FailingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.bytes.len() + buf.len() > self.capacity {
+            return Err(io::Error::other("the disk is full"));
+        }
+        self.bytes.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn small_example(index: usize) -> Example {
+    let mut example = Example::empty();
+    example.push_i64s("id", &[index as i64]);
+    example
+}
+
+fn assert_ids(
+    examples: impl Iterator<Item = tfrecord::Result<Example>>,
+    count: usize,
+) -> Result<()> {
+    let mut num_examples = 0;
+    for (index, example) in examples.enumerate() {
+        assert_eq!(example?.get_i64s("id")?, &[index as i64]);
+        num_examples += 1;
+    }
+    assert_eq!(num_examples, count);
+    Ok(())
+}
+
+fn committed(err: Error) -> (u64, Error) {
+    match err {
+        Error::WriteInterrupted { committed, source } => (committed, *source),
+        err => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn write_all_round_trip_test() -> Result<()> {
+    let dataset = samples::tiny_dataset(1, 1)?;
+    let path = dataset.dir().join("streamed.tfrecord");
+
+    // the examples are generated lazily as they are written
+    let mut writer = ExampleWriter::create(&path)?;
+    let num_records = writer.write_all((0..NUM_EXAMPLES).map(small_example))?;
+    assert_eq!(num_records, NUM_EXAMPLES as u64);
+    drop(writer);
+
+    assert_ids(ExampleIter::open(&path, Default::default())?, NUM_EXAMPLES)?;
+    Ok(())
+}
+
+#[test]
+fn write_all_interrupted_test() -> Result<()> {
+    let frame_len = small_example(0).encoded_len_framed() as usize;
+    let writer = FailingWriter {
+        bytes: vec![],
+        capacity: frame_len * 10 + 5,
+    };
+    let mut writer = ExampleWriter::from_writer(writer)?;
+    let err = writer.write_all((0..100).map(small_example)).unwrap_err();
+    assert_eq!(err.code().to_string(), "TFR0027");
+    let (num_committed, source) = committed(err);
+    assert_eq!(num_committed, 10);
+    assert!(
+        source.to_string().contains("the disk is full"),
+        "{}",
+        source
+    );
+
+    // the committed records are readable, followed by the partial frame
+    let bytes = writer.into_inner().bytes;
+    let examples = ExampleIter::from_bytes(bytes, Default::default()).take(10);
+    assert_ids(examples, 10)?;
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[async_std::test]
+async fn write_stream_test() -> Result<()> {
+    use futures::stream;
+    use tfrecord::ExampleAsyncWriter;
+
+    let dataset = samples::tiny_dataset(1, 1)?;
+    let path = dataset.dir().join("streamed.tfrecord");
+    let mut writer = ExampleAsyncWriter::create(&path).await?;
+    let records = stream::iter((0..NUM_EXAMPLES).map(|index| Ok::<_, Error>(small_example(index))));
+    let num_records = writer.write_stream(records).await?;
+    assert_eq!(num_records, NUM_EXAMPLES as u64);
+    drop(writer);
+    assert_ids(ExampleIter::open(&path, Default::default())?, NUM_EXAMPLES)?;
+
+    // a failing stream commits the records before the failure
+    let path = dataset.dir().join("interrupted.tfrecord");
+    let mut writer = ExampleAsyncWriter::create(&path).await?;
+    let records = stream::iter((0..100).map(|index| match index {
+        42 => Err(io::Error::other("the producer failed")),
+        index => Ok(small_example(index)),
+    }));
+    let (num_committed, source) = committed(writer.write_stream(records).await.unwrap_err());
+    assert_eq!(num_committed, 42);
+    assert!(
+        source.to_string().contains("the producer failed"),
+        "{}",
+        source
+    );
+    drop(writer);
+    assert_ids(ExampleIter::open(&path, Default::default())?, 42)?;
+    Ok(())
+}